@@ -4,7 +4,7 @@
 
 use actix_web::{web, App, HttpResponse, HttpServer, middleware};
 use rust_template::{
-    auth::jwt::JwtManager,
+    auth::{jwt::JwtManager, LockoutPolicy, LoginAttemptTracker},
     middleware::rate_limit::{RateLimiter, RateLimitConfig, RateLimitAlgorithm},
     errors::ApiError,
 };
@@ -41,13 +41,35 @@ async fn protected_route(
     })))
 }
 
-async fn login(jwt_manager: web::Data<JwtManager>) -> Result<HttpResponse, ApiError> {
-    // In real app, verify credentials first
-    let user_id = "user123";
-    let email = "user@example.com";
-    let role = "user";
+#[derive(serde::Deserialize)]
+struct LoginRequest {
+    email: String,
+    password: String,
+}
+
+async fn login(
+    jwt_manager: web::Data<JwtManager>,
+    login_attempts: web::Data<LoginAttemptTracker>,
+    req: actix_web::HttpRequest,
+    body: web::Json<LoginRequest>,
+) -> Result<HttpResponse, ApiError> {
+    let ip = req
+        .connection_info()
+        .realip_remote_addr()
+        .unwrap_or("unknown")
+        .to_string();
+
+    // Bị khóa thì từ chối trước khi kiểm tra mật khẩu
+    login_attempts.check(&body.email, &ip)?;
 
-    let token = jwt_manager.create_token(user_id, email, role)?;
+    // In real app, verify the password hash from the database
+    if body.email != "user@example.com" || body.password != "password" {
+        login_attempts.record_failure(&body.email, &ip)?;
+        return Err(ApiError::unauthorized("Invalid email or password"));
+    }
+    login_attempts.record_success(&body.email);
+
+    let token = jwt_manager.create_token("user123", &body.email, "user")?;
 
     Ok(HttpResponse::Ok().json(serde_json::json!({
         "token": token,
@@ -70,16 +92,20 @@ async fn main() -> std::io::Result<()> {
         burst_size: Some(20),
     });
 
+    // Khóa 15 phút sau 5 lần đăng nhập sai
+    let login_attempts = LoginAttemptTracker::new(LockoutPolicy::default());
+
     println!("🚀 Starting API server on http://127.0.0.1:8080");
     println!("📝 Endpoints:");
     println!("   GET  /health - Health check");
-    println!("   POST /login - Get JWT token");
+    println!("   POST /login - Get JWT token ({{\"email\":\"user@example.com\",\"password\":\"password\"}})");
     println!("   GET  /protected - Protected route (requires JWT)");
 
     HttpServer::new(move || {
         App::new()
             .app_data(web::Data::new(jwt_manager.clone()))
             .app_data(web::Data::new(rate_limiter.clone()))
+            .app_data(web::Data::new(login_attempts.clone()))
             .wrap(middleware::Logger::default())
             .route("/health", web::get().to(health))
             .route("/login", web::post().to(login))
@@ -93,7 +93,7 @@ async fn main() -> std::io::Result<()> {
             Variant { name: "red".to_string(), weight: 50 },
             Variant { name: "blue".to_string(), weight: 50 },
         ],
-    });
+    }).expect("button_color weights are valid");
 
     ab_manager.add_test(ABTest {
         name: "pricing_page".to_string(),
@@ -103,7 +103,7 @@ async fn main() -> std::io::Result<()> {
             Variant { name: "annual".to_string(), weight: 33 },
             Variant { name: "lifetime".to_string(), weight: 34 },
         ],
-    });
+    }).expect("pricing_page weights are valid");
 
     println!("🚀 Starting Feature Flags Server on http://127.0.0.1:8080");
     println!("📝 Endpoints:");
@@ -9,9 +9,26 @@ use rust_template::errors::ApiError;
 /// 4. Handle version conflicts (optimistic locking)
 /// 5. Query events by type and time range
 use rust_template::patterns::{Aggregate, PostgresEventStore, StoredEvent};
+use serde::Deserialize;
 use serde_json::json;
 use sqlx::PgPool;
 
+#[derive(Deserialize)]
+struct UserCreated {
+    name: String,
+    email: String,
+}
+
+#[derive(Deserialize)]
+struct UserNameUpdated {
+    name: String,
+}
+
+#[derive(Deserialize)]
+struct UserEmailUpdated {
+    email: String,
+}
+
 /// Example User Aggregate
 #[derive(Debug, Clone)]
 struct UserAggregate {
@@ -44,38 +61,27 @@ impl Aggregate for UserAggregate {
     fn apply_event(&mut self, event: &StoredEvent) -> Result<(), ApiError> {
         match event.event_type.as_str() {
             "UserCreated" => {
-                self.name = event.payload["name"]
-                    .as_str()
-                    .unwrap_or_default()
-                    .to_string();
-                self.email = event.payload["email"]
-                    .as_str()
-                    .unwrap_or_default()
-                    .to_string();
-                self.version = event.version;
-                Ok(())
+                let payload: UserCreated = event.deserialize_payload()?;
+                self.name = payload.name;
+                self.email = payload.email;
             },
             "UserNameUpdated" => {
-                self.name = event.payload["name"]
-                    .as_str()
-                    .unwrap_or_default()
-                    .to_string();
-                self.version = event.version;
-                Ok(())
+                let payload: UserNameUpdated = event.deserialize_payload()?;
+                self.name = payload.name;
             },
             "UserEmailUpdated" => {
-                self.email = event.payload["email"]
-                    .as_str()
-                    .unwrap_or_default()
-                    .to_string();
-                self.version = event.version;
-                Ok(())
+                let payload: UserEmailUpdated = event.deserialize_payload()?;
+                self.email = payload.email;
+            },
+            _ => {
+                return Err(ApiError::bad_request(format!(
+                    "Unknown event type: {}",
+                    event.event_type
+                )))
             },
-            _ => Err(ApiError::bad_request(&format!(
-                "Unknown event type: {}",
-                event.event_type
-            ))),
         }
+        self.version = event.version;
+        Ok(())
     }
 }
 
@@ -95,7 +101,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
 
     // Run migrations
     println!("🔧 Running migrations...");
-    sqlx::migrate!("./migrations").run(&pool).await?;
+    sqlx::migrate!("./migrations/postgres").run(&pool).await?;
 
     // 2. Create event store
     let event_store = PostgresEventStore::new(pool);
@@ -160,8 +166,8 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     println!("🔄 Rebuilding user state from events...");
     let mut user = UserAggregate::new(user_id.clone());
 
+    user.replay(&events)?;
     for event in &events {
-        user.apply_event(event)?;
         println!("  Applied: {} (v{})", event.event_type, event.version);
     }
 
@@ -2,18 +2,10 @@
 /// 
 /// Run with: cargo run --example websocket_server --features websocket
 
-use actix_web::{web, App, HttpRequest, HttpResponse, HttpServer, Error};
-use actix_web_actors::ws;
-use rust_template::websocket::{WebSocketServer, WebSocketSession};
-
-async fn ws_handler(
-    req: HttpRequest,
-    stream: web::Payload,
-    _server: web::Data<WebSocketServer>,
-) -> Result<HttpResponse, Error> {
-    let session = WebSocketSession::new();
-    ws::start(session, &req, stream)
-}
+use actix_web::{web, App, HttpResponse, HttpServer};
+use rust_template::auth::JwtManager;
+use rust_template::handlers::event_stream;
+use rust_template::websocket::{ws_connect, WebSocketServer};
 
 async fn index() -> HttpResponse {
     HttpResponse::Ok().content_type("text/html").body(
@@ -33,7 +25,9 @@ async fn index() -> HttpResponse {
     <div id="messages"></div>
 
     <script>
-        const ws = new WebSocket('ws://localhost:8080/ws');
+        // Token lấy từ URL (server in ra link khi khởi động)
+        const token = new URLSearchParams(location.search).get('token');
+        const ws = new WebSocket('ws://localhost:8080/ws?token=' + encodeURIComponent(token));
         const status = document.getElementById('status');
         const messages = document.getElementById('messages');
 
@@ -81,15 +75,23 @@ async fn main() -> std::io::Result<()> {
     let _ = env_logger::try_init_from_env(env_logger::Env::new().default_filter_or("info"));
 
     let ws_server = WebSocketServer::new();
+    let jwt_manager = JwtManager::new("your-secret-key".to_string(), 24);
+    let demo_token = jwt_manager
+        .create_token("demo-user", "demo@example.com", "user")
+        .expect("failed to create demo token");
 
     println!("🚀 Starting WebSocket server on http://127.0.0.1:8080");
-    println!("📝 Open http://127.0.0.1:8080 in your browser to test");
+    println!("📝 Open http://127.0.0.1:8080/?token={} in your browser to test", demo_token);
+    println!("📡 Same events over SSE: curl -N http://127.0.0.1:8080/events/stream");
 
     HttpServer::new(move || {
         App::new()
             .app_data(web::Data::new(ws_server.clone()))
+            .app_data(web::Data::new(ws_server.events().clone()))
+            .app_data(web::Data::new(jwt_manager.clone()))
             .route("/", web::get().to(index))
-            .route("/ws", web::get().to(ws_handler))
+            .route("/ws", web::get().to(ws_connect))
+            .route("/events/stream", web::get().to(event_stream))
     })
     .bind(("127.0.0.1", 8080))?
     .run()
@@ -0,0 +1,20 @@
+-- Create users table (MySQL)
+CREATE TABLE IF NOT EXISTS users (
+    id CHAR(36) NOT NULL PRIMARY KEY,
+    name VARCHAR(255) NOT NULL,
+    email VARCHAR(255) NOT NULL UNIQUE,
+    -- Users created through the API or OAuth login have no local password
+    password_hash VARCHAR(255),
+    role VARCHAR(50) NOT NULL DEFAULT 'user',
+    age INTEGER CHECK (age > 0 AND age < 150),
+    -- Optional phone number, stored in E.164 format
+    phone VARCHAR(16),
+    is_active BOOLEAN NOT NULL DEFAULT TRUE,
+    created_at TIMESTAMP(6) NOT NULL DEFAULT CURRENT_TIMESTAMP(6),
+    updated_at TIMESTAMP(6) NOT NULL DEFAULT CURRENT_TIMESTAMP(6) ON UPDATE CURRENT_TIMESTAMP(6),
+    -- Soft delete support
+    deleted_at TIMESTAMP(6) NULL,
+
+    INDEX idx_users_role (role),
+    INDEX idx_users_deleted_at (deleted_at, created_at)
+);
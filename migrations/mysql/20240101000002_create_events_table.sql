@@ -0,0 +1,21 @@
+-- Create events table for Event Sourcing pattern (MySQL)
+CREATE TABLE IF NOT EXISTS events (
+    id CHAR(36) NOT NULL PRIMARY KEY,
+    aggregate_id VARCHAR(255) NOT NULL,
+    event_type VARCHAR(255) NOT NULL,
+    payload JSON NOT NULL,
+    timestamp TIMESTAMP(6) NOT NULL DEFAULT CURRENT_TIMESTAMP(6),
+    version BIGINT NOT NULL,
+
+    -- Global ordering across aggregates for projection catch-up
+    global_seq BIGINT NOT NULL AUTO_INCREMENT UNIQUE,
+
+    -- Ensure unique version per aggregate (optimistic locking)
+    CONSTRAINT unique_aggregate_version UNIQUE (aggregate_id, version),
+
+    -- Index for event type queries (useful for projections)
+    INDEX idx_events_event_type (event_type),
+
+    -- Index for timestamp-based queries (temporal queries)
+    INDEX idx_events_timestamp (timestamp)
+);
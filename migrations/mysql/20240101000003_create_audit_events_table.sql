@@ -0,0 +1,18 @@
+-- Create audit_events table for persisted security audit logs (MySQL)
+CREATE TABLE IF NOT EXISTS audit_events (
+    id CHAR(36) NOT NULL PRIMARY KEY,
+    timestamp TIMESTAMP(6) NOT NULL,
+    event_type VARCHAR(255) NOT NULL,
+    severity VARCHAR(50) NOT NULL,
+    user_id VARCHAR(255),
+    ip_address VARCHAR(64),
+    resource VARCHAR(255),
+    action TEXT NOT NULL,
+    result VARCHAR(50) NOT NULL,
+    metadata JSON NOT NULL,
+    request_id VARCHAR(255),
+
+    INDEX idx_audit_events_user_id (user_id),
+    INDEX idx_audit_events_timestamp (timestamp),
+    INDEX idx_audit_events_event_type (event_type)
+);
@@ -0,0 +1,4 @@
+-- Optional phone number, stored in E.164 format
+-- Already part of the users table on this backend (20240101000001); kept so
+-- every backend has the same migration versions
+SELECT 1;
@@ -0,0 +1,17 @@
+-- Create api_keys table for persisted API keys (only hashes are stored, MySQL)
+CREATE TABLE IF NOT EXISTS api_keys (
+    id VARCHAR(64) NOT NULL PRIMARY KEY,
+    key_hash VARCHAR(64) NOT NULL UNIQUE,
+    name VARCHAR(255) NOT NULL,
+    user_id VARCHAR(255) NOT NULL,
+    -- JSON array of scope strings
+    scopes JSON NOT NULL,
+    created_at TIMESTAMP(6) NOT NULL,
+    expires_at TIMESTAMP(6) NULL,
+    last_used_at TIMESTAMP(6) NULL,
+    is_active BOOLEAN NOT NULL DEFAULT TRUE,
+    rate_limit INTEGER,
+
+    INDEX idx_api_keys_user_id (user_id),
+    INDEX idx_api_keys_active (is_active, created_at)
+);
@@ -0,0 +1,12 @@
+-- Feature flags and A/B tests shared by all replicas (MySQL)
+CREATE TABLE IF NOT EXISTS feature_flags (
+    name VARCHAR(255) NOT NULL PRIMARY KEY,
+    flag JSON NOT NULL,
+    updated_at TIMESTAMP(6) NOT NULL DEFAULT CURRENT_TIMESTAMP(6)
+);
+
+CREATE TABLE IF NOT EXISTS ab_tests (
+    name VARCHAR(255) NOT NULL PRIMARY KEY,
+    test JSON NOT NULL,
+    updated_at TIMESTAMP(6) NOT NULL DEFAULT CURRENT_TIMESTAMP(6)
+);
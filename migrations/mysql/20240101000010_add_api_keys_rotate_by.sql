@@ -0,0 +1,2 @@
+-- Deadline set by the rotation job; the key stops validating after it
+ALTER TABLE api_keys ADD COLUMN rotate_by TIMESTAMP(6) NULL;
@@ -0,0 +1,10 @@
+-- OAuth identities linked to local users: (provider, provider user id) -> user
+CREATE TABLE IF NOT EXISTS oauth_identities (
+    provider VARCHAR(50) NOT NULL,
+    provider_user_id VARCHAR(255) NOT NULL,
+    user_id CHAR(36) NOT NULL,
+    created_at TIMESTAMP(6) NOT NULL DEFAULT CURRENT_TIMESTAMP(6),
+    PRIMARY KEY (provider, provider_user_id),
+    INDEX idx_oauth_identities_user_id (user_id),
+    FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE
+);
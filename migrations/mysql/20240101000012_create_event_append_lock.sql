@@ -0,0 +1,7 @@
+-- Single row every event append locks, so global_seq (AUTO_INCREMENT) is
+-- assigned in commit order and catch-up readers never skip an event
+CREATE TABLE IF NOT EXISTS event_append_lock (
+    id TINYINT NOT NULL PRIMARY KEY
+);
+
+INSERT IGNORE INTO event_append_lock (id) VALUES (1);
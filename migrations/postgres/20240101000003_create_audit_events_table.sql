@@ -0,0 +1,23 @@
+-- Create audit_events table for persisted security audit logs
+CREATE TABLE IF NOT EXISTS audit_events (
+    id UUID PRIMARY KEY,
+    timestamp TIMESTAMPTZ NOT NULL,
+    event_type VARCHAR(255) NOT NULL,
+    severity VARCHAR(50) NOT NULL,
+    user_id VARCHAR(255),
+    ip_address VARCHAR(64),
+    resource VARCHAR(255),
+    action TEXT NOT NULL,
+    result VARCHAR(50) NOT NULL,
+    metadata JSONB NOT NULL DEFAULT '{}',
+    request_id VARCHAR(255)
+);
+
+-- Index for per-user audit trails
+CREATE INDEX IF NOT EXISTS idx_audit_events_user_id ON audit_events(user_id);
+
+-- Index for time-range queries
+CREATE INDEX IF NOT EXISTS idx_audit_events_timestamp ON audit_events(timestamp);
+
+-- Index for filtering by event type
+CREATE INDEX IF NOT EXISTS idx_audit_events_event_type ON audit_events(event_type);
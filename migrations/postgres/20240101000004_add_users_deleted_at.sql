@@ -0,0 +1,5 @@
+-- Soft delete support for users
+ALTER TABLE users ADD COLUMN IF NOT EXISTS deleted_at TIMESTAMPTZ;
+
+-- Most queries only look at users that are not deleted
+CREATE INDEX IF NOT EXISTS idx_users_not_deleted ON users(created_at) WHERE deleted_at IS NULL;
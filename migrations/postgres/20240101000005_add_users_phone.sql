@@ -0,0 +1,2 @@
+-- Optional phone number, stored in E.164 format
+ALTER TABLE users ADD COLUMN IF NOT EXISTS phone VARCHAR(16);
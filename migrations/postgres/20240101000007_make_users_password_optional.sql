@@ -0,0 +1,2 @@
+-- Users created through the API or OAuth login have no local password
+ALTER TABLE users ALTER COLUMN password_hash DROP NOT NULL;
@@ -0,0 +1,4 @@
+-- Global ordering across aggregates for projection catch-up
+ALTER TABLE events ADD COLUMN IF NOT EXISTS global_seq BIGSERIAL;
+
+CREATE UNIQUE INDEX IF NOT EXISTS idx_events_global_seq ON events(global_seq);
@@ -0,0 +1,12 @@
+-- Feature flags and A/B tests shared by all replicas (see PostgresFlagStore)
+CREATE TABLE IF NOT EXISTS feature_flags (
+    name VARCHAR(255) PRIMARY KEY,
+    flag JSONB NOT NULL,
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE TABLE IF NOT EXISTS ab_tests (
+    name VARCHAR(255) PRIMARY KEY,
+    test JSONB NOT NULL,
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
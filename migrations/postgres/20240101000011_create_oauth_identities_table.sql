@@ -0,0 +1,13 @@
+-- OAuth identities linked to local users: (provider, provider user id) -> user
+CREATE TABLE IF NOT EXISTS oauth_identities (
+    provider VARCHAR(50) NOT NULL,
+    provider_user_id VARCHAR(255) NOT NULL,
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (provider, provider_user_id)
+);
+
+CREATE INDEX IF NOT EXISTS idx_oauth_identities_user_id ON oauth_identities(user_id);
+
+-- Case-insensitive email lookups (OAuth account linking)
+CREATE INDEX IF NOT EXISTS idx_users_email_lower ON users(LOWER(email));
@@ -0,0 +1,30 @@
+-- Create users table (SQLite)
+CREATE TABLE IF NOT EXISTS users (
+    id TEXT PRIMARY KEY NOT NULL,
+    name TEXT NOT NULL,
+    email TEXT NOT NULL UNIQUE,
+    -- Users created through the API or OAuth login have no local password
+    password_hash TEXT,
+    role TEXT NOT NULL DEFAULT 'user',
+    age INTEGER CHECK (age > 0 AND age < 150),
+    -- Optional phone number, stored in E.164 format
+    phone TEXT,
+    is_active BOOLEAN NOT NULL DEFAULT 1,
+    created_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    updated_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    -- Soft delete support
+    deleted_at TEXT
+);
+
+CREATE INDEX IF NOT EXISTS idx_users_role ON users(role);
+
+-- Most queries only look at users that are not deleted
+CREATE INDEX IF NOT EXISTS idx_users_not_deleted ON users(created_at) WHERE deleted_at IS NULL;
+
+CREATE TRIGGER IF NOT EXISTS update_users_updated_at
+    AFTER UPDATE ON users
+    FOR EACH ROW
+    WHEN NEW.updated_at = OLD.updated_at
+BEGIN
+    UPDATE users SET updated_at = CURRENT_TIMESTAMP WHERE id = NEW.id;
+END;
@@ -0,0 +1,20 @@
+-- Create events table for Event Sourcing pattern (SQLite)
+CREATE TABLE IF NOT EXISTS events (
+    -- Global ordering across aggregates for projection catch-up
+    global_seq INTEGER PRIMARY KEY AUTOINCREMENT,
+    id TEXT NOT NULL UNIQUE,
+    aggregate_id TEXT NOT NULL,
+    event_type TEXT NOT NULL,
+    payload TEXT NOT NULL,
+    timestamp TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    version INTEGER NOT NULL,
+
+    -- Ensure unique version per aggregate (optimistic locking)
+    CONSTRAINT unique_aggregate_version UNIQUE (aggregate_id, version)
+);
+
+-- Index for event type queries (useful for projections)
+CREATE INDEX IF NOT EXISTS idx_events_event_type ON events(event_type);
+
+-- Index for timestamp-based queries (temporal queries)
+CREATE INDEX IF NOT EXISTS idx_events_timestamp ON events(timestamp);
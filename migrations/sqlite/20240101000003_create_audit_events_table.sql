@@ -0,0 +1,18 @@
+-- Create audit_events table for persisted security audit logs (SQLite)
+CREATE TABLE IF NOT EXISTS audit_events (
+    id TEXT PRIMARY KEY NOT NULL,
+    timestamp TEXT NOT NULL,
+    event_type TEXT NOT NULL,
+    severity TEXT NOT NULL,
+    user_id TEXT,
+    ip_address TEXT,
+    resource TEXT,
+    action TEXT NOT NULL,
+    result TEXT NOT NULL,
+    metadata TEXT NOT NULL DEFAULT '{}',
+    request_id TEXT
+);
+
+CREATE INDEX IF NOT EXISTS idx_audit_events_user_id ON audit_events(user_id);
+CREATE INDEX IF NOT EXISTS idx_audit_events_timestamp ON audit_events(timestamp);
+CREATE INDEX IF NOT EXISTS idx_audit_events_event_type ON audit_events(event_type);
@@ -0,0 +1,19 @@
+-- Create api_keys table for persisted API keys (only hashes are stored, SQLite)
+CREATE TABLE IF NOT EXISTS api_keys (
+    id TEXT PRIMARY KEY NOT NULL,
+    key_hash TEXT NOT NULL UNIQUE,
+    name TEXT NOT NULL,
+    user_id TEXT NOT NULL,
+    -- JSON array of scope strings
+    scopes TEXT NOT NULL DEFAULT '[]',
+    created_at TEXT NOT NULL,
+    expires_at TEXT,
+    last_used_at TEXT,
+    is_active BOOLEAN NOT NULL DEFAULT 1,
+    rate_limit INTEGER
+);
+
+CREATE INDEX IF NOT EXISTS idx_api_keys_user_id ON api_keys(user_id);
+
+-- Partial index for jobs scanning active keys
+CREATE INDEX IF NOT EXISTS idx_api_keys_active ON api_keys(created_at) WHERE is_active;
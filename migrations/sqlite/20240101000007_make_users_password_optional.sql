@@ -0,0 +1,4 @@
+-- Users created through the API or OAuth login have no local password
+-- password_hash is already nullable on this backend (20240101000001); kept so
+-- every backend has the same migration versions
+SELECT 1;
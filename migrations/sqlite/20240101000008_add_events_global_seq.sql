@@ -0,0 +1,4 @@
+-- Global ordering across aggregates for projection catch-up
+-- Already part of the events table on this backend (20240101000002); kept so
+-- every backend has the same migration versions
+SELECT 1;
@@ -0,0 +1,12 @@
+-- Feature flags and A/B tests shared by all connections (SQLite)
+CREATE TABLE IF NOT EXISTS feature_flags (
+    name TEXT PRIMARY KEY NOT NULL,
+    flag TEXT NOT NULL,
+    updated_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE TABLE IF NOT EXISTS ab_tests (
+    name TEXT PRIMARY KEY NOT NULL,
+    test TEXT NOT NULL,
+    updated_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
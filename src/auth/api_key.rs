@@ -1,7 +1,10 @@
 // API Key Management System
 // Provides API key generation, validation, rotation, and revocation
 
+use super::api_key_store::ApiKeyStore;
 use crate::errors::ApiError;
+use crate::utils::clock::{Clock, SystemClock};
+use async_trait::async_trait;
 use chrono::{DateTime, Duration, Utc};
 use rand::Rng;
 use serde::{Deserialize, Serialize};
@@ -22,18 +25,29 @@ pub struct ApiKey {
     pub last_used_at: Option<DateTime<Utc>>,
     pub is_active: bool,
     pub rate_limit: Option<u32>,
+    /// Set by the rotation job once the key is due; the owner must rotate it
+    /// before this time or it stops validating
+    #[serde(default)]
+    pub rotate_by: Option<DateTime<Utc>>,
 }
 
 /// API Key Manager
 pub struct ApiKeyManager {
     keys: Arc<RwLock<HashMap<String, ApiKey>>>,
+    clock: Arc<dyn Clock>,
 }
 
 impl ApiKeyManager {
     /// Create new API key manager
     pub fn new() -> Self {
+        Self::with_clock(Arc::new(SystemClock))
+    }
+
+    /// Create an API key manager that reads the time from `clock`
+    pub fn with_clock(clock: Arc<dyn Clock>) -> Self {
         Self {
             keys: Arc::new(RwLock::new(HashMap::new())),
+            clock,
         }
     }
 
@@ -45,29 +59,20 @@ impl ApiKeyManager {
         scopes: Vec<String>,
         expires_in_days: Option<i64>,
     ) -> Result<(String, ApiKey), ApiError> {
-        // Generate random key
-        let key = Self::generate_random_key();
-        let key_hash = Self::hash_key(&key);
-
-        // Create API key record
-        let api_key = ApiKey {
-            id: uuid::Uuid::new_v4().to_string(),
-            key_hash: key_hash.clone(),
+        let now = self.clock.now();
+        let (key, api_key) = Self::issue(
             name,
             user_id,
             scopes,
-            created_at: Utc::now(),
-            expires_at: expires_in_days.map(|days| Utc::now() + Duration::days(days)),
-            last_used_at: None,
-            is_active: true,
-            rate_limit: Some(1000), // Default 1000 requests per hour
-        };
+            now,
+            expires_in_days.map(|days| now + Duration::days(days)),
+        );
 
         // Store key
         let mut keys = self.keys.write().map_err(|_| {
             ApiError::internal("Failed to acquire write lock on API keys")
         })?;
-        keys.insert(key_hash.clone(), api_key.clone());
+        keys.insert(api_key.key_hash.clone(), api_key.clone());
 
         Ok((key, api_key))
     }
@@ -89,15 +94,23 @@ impl ApiKeyManager {
             return Err(ApiError::unauthorized("API key is inactive"));
         }
 
+        let now = self.clock.now();
+
         // Check if key is expired
         if let Some(expires_at) = api_key.expires_at {
-            if Utc::now() > expires_at {
+            if now > expires_at {
                 return Err(ApiError::unauthorized("API key has expired"));
             }
         }
 
+        if let Some(rotate_by) = api_key.rotate_by {
+            if now > rotate_by {
+                return Err(ApiError::unauthorized("API key must be rotated"));
+            }
+        }
+
         // Update last used timestamp
-        api_key.last_used_at = Some(Utc::now());
+        api_key.last_used_at = Some(now);
 
         Ok(api_key.clone())
     }
@@ -147,7 +160,7 @@ impl ApiKeyManager {
         let scopes = old_key.scopes.clone();
         let expires_in_days = old_key
             .expires_at
-            .map(|exp| (exp - Utc::now()).num_days());
+            .map(|exp| (exp - self.clock.now()).num_days());
 
         drop(keys); // Release read lock
 
@@ -160,6 +173,32 @@ impl ApiKeyManager {
         Ok((new_key, new_api_key))
     }
 
+    /// Create a new key record without storing it; returns the raw key and
+    /// its record
+    pub fn issue(
+        name: String,
+        user_id: String,
+        scopes: Vec<String>,
+        created_at: DateTime<Utc>,
+        expires_at: Option<DateTime<Utc>>,
+    ) -> (String, ApiKey) {
+        let key = Self::generate_random_key();
+        let api_key = ApiKey {
+            id: uuid::Uuid::new_v4().to_string(),
+            key_hash: Self::hash_key(&key),
+            name,
+            user_id,
+            scopes,
+            created_at,
+            expires_at,
+            last_used_at: None,
+            is_active: true,
+            rate_limit: Some(1000), // Default 1000 requests per hour
+            rotate_by: None,
+        };
+        (key, api_key)
+    }
+
     // Helper functions
 
     fn generate_random_key() -> String {
@@ -181,3 +220,43 @@ impl Default for ApiKeyManager {
     }
 }
 
+#[async_trait]
+impl ApiKeyStore for ApiKeyManager {
+    async fn insert(&self, api_key: ApiKey) -> Result<(), ApiError> {
+        let mut keys = self.keys.write().map_err(|_| {
+            ApiError::internal("Failed to acquire write lock on API keys")
+        })?;
+        keys.insert(api_key.key_hash.clone(), api_key);
+        Ok(())
+    }
+
+    async fn list_active(&self) -> Result<Vec<ApiKey>, ApiError> {
+        let keys = self.keys.read().map_err(|_| {
+            ApiError::internal("Failed to acquire read lock on API keys")
+        })?;
+        Ok(keys.values().filter(|k| k.is_active).cloned().collect())
+    }
+
+    async fn deactivate(&self, key_hash: &str) -> Result<(), ApiError> {
+        self.revoke_key(key_hash)
+    }
+
+    async fn mark_rotation_due(
+        &self,
+        key_hash: &str,
+        rotate_by: DateTime<Utc>,
+    ) -> Result<(), ApiError> {
+        let mut keys = self.keys.write().map_err(|_| {
+            ApiError::internal("Failed to acquire write lock on API keys")
+        })?;
+
+        let api_key = keys
+            .get_mut(key_hash)
+            .ok_or_else(|| ApiError::not_found("API key not found"))?;
+
+        api_key.rotate_by = Some(rotate_by);
+
+        Ok(())
+    }
+}
+
@@ -0,0 +1,133 @@
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+
+use super::api_key::ApiKey;
+use crate::errors::ApiError;
+
+/// Persistence for API key records, used by background jobs that scan keys
+///
+/// `ApiKeyManager` is the in-memory implementation.
+#[async_trait]
+pub trait ApiKeyStore: Send + Sync {
+    async fn insert(&self, api_key: ApiKey) -> Result<(), ApiError>;
+
+    async fn list_active(&self) -> Result<Vec<ApiKey>, ApiError>;
+
+    /// Mark the key inactive so it no longer validates
+    async fn deactivate(&self, key_hash: &str) -> Result<(), ApiError>;
+
+    /// Ask the owner to rotate the key; it stops validating after `rotate_by`
+    async fn mark_rotation_due(
+        &self,
+        key_hash: &str,
+        rotate_by: DateTime<Utc>,
+    ) -> Result<(), ApiError>;
+}
+
+/// Stores API keys in the `api_keys` table
+#[cfg(feature = "database-postgres")]
+pub struct PostgresApiKeyStore {
+    pool: sqlx::PgPool,
+}
+
+#[cfg(feature = "database-postgres")]
+impl PostgresApiKeyStore {
+    pub fn new(pool: sqlx::PgPool) -> Self {
+        Self { pool }
+    }
+}
+
+#[cfg(feature = "database-postgres")]
+#[async_trait]
+impl ApiKeyStore for PostgresApiKeyStore {
+    async fn insert(&self, api_key: ApiKey) -> Result<(), ApiError> {
+        sqlx::query(
+            r#"
+            INSERT INTO api_keys
+                (id, key_hash, name, user_id, scopes, created_at, expires_at, last_used_at, is_active, rate_limit, rotate_by)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11)
+            "#
+        )
+        .bind(&api_key.id)
+        .bind(&api_key.key_hash)
+        .bind(&api_key.name)
+        .bind(&api_key.user_id)
+        .bind(&api_key.scopes)
+        .bind(api_key.created_at)
+        .bind(api_key.expires_at)
+        .bind(api_key.last_used_at)
+        .bind(api_key.is_active)
+        .bind(api_key.rate_limit.map(|limit| limit as i32))
+        .bind(api_key.rotate_by)
+        .execute(&self.pool)
+        .await
+        .map_err(|e| ApiError::database(format!("Failed to store API key: {}", e)))?;
+
+        Ok(())
+    }
+
+    async fn list_active(&self) -> Result<Vec<ApiKey>, ApiError> {
+        use sqlx::Row;
+
+        let rows = sqlx::query(
+            r#"
+            SELECT id, key_hash, name, user_id, scopes, created_at, expires_at, last_used_at, is_active, rate_limit, rotate_by
+            FROM api_keys
+            WHERE is_active = TRUE
+            "#
+        )
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| ApiError::database(format!("Failed to list API keys: {}", e)))?;
+
+        Ok(rows
+            .into_iter()
+            .map(|row| ApiKey {
+                id: row.get("id"),
+                key_hash: row.get("key_hash"),
+                name: row.get("name"),
+                user_id: row.get("user_id"),
+                scopes: row.get("scopes"),
+                created_at: row.get("created_at"),
+                expires_at: row.get("expires_at"),
+                last_used_at: row.get("last_used_at"),
+                is_active: row.get("is_active"),
+                rate_limit: row.get::<Option<i32>, _>("rate_limit").map(|limit| limit as u32),
+                rotate_by: row.get("rotate_by"),
+            })
+            .collect())
+    }
+
+    async fn deactivate(&self, key_hash: &str) -> Result<(), ApiError> {
+        let result = sqlx::query("UPDATE api_keys SET is_active = FALSE WHERE key_hash = $1")
+            .bind(key_hash)
+            .execute(&self.pool)
+            .await
+            .map_err(|e| ApiError::database(format!("Failed to deactivate API key: {}", e)))?;
+
+        if result.rows_affected() == 0 {
+            return Err(ApiError::not_found("API key not found"));
+        }
+        Ok(())
+    }
+
+    async fn mark_rotation_due(
+        &self,
+        key_hash: &str,
+        rotate_by: DateTime<Utc>,
+    ) -> Result<(), ApiError> {
+        let result = sqlx::query("UPDATE api_keys SET rotate_by = $2 WHERE key_hash = $1")
+            .bind(key_hash)
+            .bind(rotate_by)
+            .execute(&self.pool)
+            .await
+            .map_err(|e| {
+                ApiError::database(format!("Failed to mark API key for rotation: {}", e))
+            })?;
+
+        if result.rows_affected() == 0 {
+            return Err(ApiError::not_found("API key not found"));
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,51 @@
+use actix_web::{dev::Payload, FromRequest, HttpMessage, HttpRequest};
+use std::future::{ready, Ready};
+use std::ops::Deref;
+
+use crate::auth::Claims;
+use crate::errors::ApiError;
+
+/// Verified `Claims` of the caller, put in request extensions by
+/// `AuthMiddleware`
+///
+/// ```ignore
+/// async fn delete_user(user: AuthenticatedUser) -> Result<HttpResponse, ApiError> {
+///     if !user.has_permission("users:delete") { ... }
+/// }
+/// ```
+#[derive(Debug, Clone)]
+pub struct AuthenticatedUser(pub Claims);
+
+impl AuthenticatedUser {
+    pub fn claims(&self) -> &Claims {
+        &self.0
+    }
+
+    pub fn into_claims(self) -> Claims {
+        self.0
+    }
+}
+
+impl Deref for AuthenticatedUser {
+    type Target = Claims;
+
+    fn deref(&self) -> &Claims {
+        &self.0
+    }
+}
+
+impl FromRequest for AuthenticatedUser {
+    type Error = ApiError;
+    type Future = Ready<Result<Self, Self::Error>>;
+
+    fn from_request(req: &HttpRequest, _: &mut Payload) -> Self::Future {
+        // Không có claims nghĩa là route không đi qua AuthMiddleware
+        ready(
+            req.extensions()
+                .get::<Claims>()
+                .cloned()
+                .map(AuthenticatedUser)
+                .ok_or_else(|| ApiError::unauthorized("Authentication required")),
+        )
+    }
+}
@@ -1,7 +1,14 @@
-use jsonwebtoken::{decode, encode, DecodingKey, EncodingKey, Header, Validation};
+use jsonwebtoken::{decode, encode, Algorithm, DecodingKey, EncodingKey, Header, Validation};
 use serde::{Deserialize, Serialize};
-use chrono::{Duration, Utc};
+use chrono::Duration;
+use std::sync::Arc;
+use crate::config::{JwtAlgorithm, JwtSettings};
 use crate::errors::ApiError;
+use crate::models::User;
+use crate::utils::clock::{Clock, SystemClock};
+
+/// Clock skew tolerated when checking `exp`, in seconds
+const EXP_LEEWAY_SECS: i64 = 60;
 
 /// JWT Claims
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -11,67 +18,288 @@ pub struct Claims {
     pub role: String,       // User role
     pub exp: i64,           // Expiration time
     pub iat: i64,           // Issued at
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub iss: Option<String>, // Issuer
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub aud: Option<String>, // Audience
+    #[serde(default)]
+    pub roles: Vec<String>,
+    /// Permissions / scopes, e.g. `users:write`
+    #[serde(default)]
+    pub permissions: Vec<String>,
+    /// Tenant the token was issued for
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tenant_id: Option<String>,
+}
+
+impl Claims {
+    pub fn has_permission(&self, permission: &str) -> bool {
+        self.permissions.iter().any(|p| p == permission)
+    }
+
+    /// Whether `role` is the primary role or one of `roles`
+    pub fn has_role(&self, role: &str) -> bool {
+        self.role == role || self.roles.iter().any(|r| r == role)
+    }
+
+    pub fn has_any_role(&self, roles: &[&str]) -> bool {
+        roles.iter().any(|role| self.has_role(role))
+    }
+}
+
+/// Permissions mặc định của các role có sẵn; role khác không có permission nào
+pub fn role_permissions(role: &str) -> Vec<String> {
+    let permissions: &[&str] = match role {
+        "admin" => &["users:read", "users:write", "users:delete", "jobs:read", "flags:read", "flags:write", "debug:capture"],
+        "user" => &["users:read"],
+        _ => &[],
+    };
+    permissions.iter().map(|p| p.to_string()).collect()
 }
 
 /// JWT Manager để tạo và verify tokens
 #[derive(Clone)]
 pub struct JwtManager {
-    secret: String,
+    algorithm: JwtAlgorithm,
+    encoding_key: EncodingKey,
+    decoding_key: DecodingKey,
     expiration_hours: i64,
+    /// Set as `iss` on issued tokens and required on verified ones
+    issuer: Option<String>,
+    /// Set as `aud` on issued tokens and required on verified ones
+    audience: Option<String>,
+    clock: Arc<dyn Clock>,
 }
 
 impl JwtManager {
+    /// HS256 with a shared secret
     pub fn new(secret: String, expiration_hours: i64) -> Self {
         Self {
-            secret,
+            algorithm: JwtAlgorithm::Hs256,
+            encoding_key: EncodingKey::from_secret(secret.as_bytes()),
+            decoding_key: DecodingKey::from_secret(secret.as_bytes()),
             expiration_hours,
+            issuer: None,
+            audience: None,
+            clock: Arc::new(SystemClock),
         }
     }
 
-    /// Tạo JWT token mới
+    /// Build from `JwtSettings`, reading the PEM key pair for asymmetric
+    /// algorithms
+    pub fn from_settings(settings: &JwtSettings) -> Result<Self, ApiError> {
+        let algorithm = settings.algorithm;
+        let (encoding_key, decoding_key) = if algorithm.is_symmetric() {
+            (
+                EncodingKey::from_secret(settings.secret.as_bytes()),
+                DecodingKey::from_secret(settings.secret.as_bytes()),
+            )
+        } else {
+            let private_pem = read_key(settings.private_key_path.as_deref(), "JWT_PRIVATE_KEY_PATH")?;
+            let public_pem = read_key(settings.public_key_path.as_deref(), "JWT_PUBLIC_KEY_PATH")?;
+            let invalid = |e: jsonwebtoken::errors::Error| {
+                ApiError::configuration(format!("Invalid {} key: {}", algorithm, e))
+            };
+
+            match algorithm {
+                JwtAlgorithm::Es256 | JwtAlgorithm::Es384 => (
+                    EncodingKey::from_ec_pem(&private_pem).map_err(invalid)?,
+                    DecodingKey::from_ec_pem(&public_pem).map_err(invalid)?,
+                ),
+                JwtAlgorithm::EdDsa => (
+                    EncodingKey::from_ed_pem(&private_pem).map_err(invalid)?,
+                    DecodingKey::from_ed_pem(&public_pem).map_err(invalid)?,
+                ),
+                // RS* và PS* đều dùng RSA key
+                _ => (
+                    EncodingKey::from_rsa_pem(&private_pem).map_err(invalid)?,
+                    DecodingKey::from_rsa_pem(&public_pem).map_err(invalid)?,
+                ),
+            }
+        };
+
+        Ok(Self {
+            algorithm,
+            encoding_key,
+            decoding_key,
+            expiration_hours: settings.expiration_hours,
+            issuer: settings.issuer.clone(),
+            audience: settings.audience.clone(),
+            clock: Arc::new(SystemClock),
+        })
+    }
+
+    pub fn with_issuer(mut self, issuer: impl Into<String>) -> Self {
+        self.issuer = Some(issuer.into());
+        self
+    }
+
+    pub fn with_audience(mut self, audience: impl Into<String>) -> Self {
+        self.audience = Some(audience.into());
+        self
+    }
+
+    pub fn algorithm(&self) -> JwtAlgorithm {
+        self.algorithm
+    }
+
+    /// Đọc thời gian từ `clock` thay vì system time (dùng trong tests)
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// Tạo JWT token mới; permissions lấy theo [`role_permissions`]
     pub fn create_token(
         &self,
         user_id: &str,
         email: &str,
         role: &str,
     ) -> Result<String, ApiError> {
-        let now = Utc::now();
+        self.create_token_with_permissions(
+            user_id,
+            email,
+            &[role.to_string()],
+            &role_permissions(role),
+        )
+    }
+
+    /// Token for `user` with the default permissions of their role
+    pub fn create_token_for(&self, user: &User) -> Result<String, ApiError> {
+        self.create_token(&user.id, &user.email, &user.role)
+    }
+
+    /// Token with custom roles and permissions; the first role is also the
+    /// primary `role` claim
+    pub fn create_token_with_permissions(
+        &self,
+        user_id: &str,
+        email: &str,
+        roles: &[String],
+        permissions: &[String],
+    ) -> Result<String, ApiError> {
+        self.issue(user_id, email, roles, permissions, None)
+    }
+
+    /// Like [`create_token`](Self::create_token), bound to `tenant_id`
+    pub fn create_tenant_token(
+        &self,
+        user_id: &str,
+        email: &str,
+        role: &str,
+        tenant_id: &str,
+    ) -> Result<String, ApiError> {
+        self.issue(
+            user_id,
+            email,
+            &[role.to_string()],
+            &role_permissions(role),
+            Some(tenant_id.to_string()),
+        )
+    }
+
+    fn issue(
+        &self,
+        user_id: &str,
+        email: &str,
+        roles: &[String],
+        permissions: &[String],
+        tenant_id: Option<String>,
+    ) -> Result<String, ApiError> {
+        let now = self.clock.now();
         let exp = now + Duration::hours(self.expiration_hours);
 
         let claims = Claims {
             sub: user_id.to_string(),
             email: email.to_string(),
-            role: role.to_string(),
+            role: roles.first().cloned().unwrap_or_default(),
             exp: exp.timestamp(),
             iat: now.timestamp(),
+            iss: self.issuer.clone(),
+            aud: self.audience.clone(),
+            roles: roles.to_vec(),
+            permissions: permissions.to_vec(),
+            tenant_id,
         };
 
-        encode(
-            &Header::default(),
-            &claims,
-            &EncodingKey::from_secret(self.secret.as_bytes()),
-        )
-        .map_err(|e| ApiError::internal(format!("Token creation failed: {}", e)))
+        encode(&Header::new(jwt_algorithm(self.algorithm)), &claims, &self.encoding_key)
+            .map_err(|e| ApiError::internal(format!("Token creation failed: {}", e)))
     }
 
     /// Verify và decode JWT token
     pub fn verify_token(&self, token: &str) -> Result<Claims, ApiError> {
-        decode::<Claims>(
-            token,
-            &DecodingKey::from_secret(self.secret.as_bytes()),
-            &Validation::default(),
-        )
-        .map(|data| data.claims)
-        .map_err(|e| ApiError::unauthorized(format!("Invalid token: {}", e)))
+        // Chỉ chấp nhận đúng algorithm đã cấu hình (chống algorithm confusion);
+        // `exp` được kiểm tra theo clock của manager thay vì system time
+        let mut validation = Validation::new(jwt_algorithm(self.algorithm));
+        validation.validate_exp = false;
+        if let Some(issuer) = &self.issuer {
+            validation.set_issuer(&[issuer]);
+        }
+        match &self.audience {
+            Some(audience) => validation.set_audience(&[audience]),
+            None => validation.validate_aud = false,
+        }
+
+        let claims = decode::<Claims>(token, &self.decoding_key, &validation)
+            .map(|data| data.claims)
+            .map_err(verification_error)?;
+
+        if claims.exp < self.clock.now().timestamp() - EXP_LEEWAY_SECS {
+            return Err(ApiError::unauthorized("Invalid token: ExpiredSignature"));
+        }
+
+        Ok(claims)
     }
 
     /// Refresh token (tạo token mới với claims cũ)
     pub fn refresh_token(&self, old_token: &str) -> Result<String, ApiError> {
         let claims = self.verify_token(old_token)?;
-        self.create_token(&claims.sub, &claims.email, &claims.role)
+        let roles = if claims.roles.is_empty() {
+            vec![claims.role.clone()]
+        } else {
+            claims.roles.clone()
+        };
+        self.issue(&claims.sub, &claims.email, &roles, &claims.permissions, claims.tenant_id)
+    }
+}
+
+fn jwt_algorithm(algorithm: JwtAlgorithm) -> Algorithm {
+    match algorithm {
+        JwtAlgorithm::Hs256 => Algorithm::HS256,
+        JwtAlgorithm::Hs384 => Algorithm::HS384,
+        JwtAlgorithm::Hs512 => Algorithm::HS512,
+        JwtAlgorithm::Rs256 => Algorithm::RS256,
+        JwtAlgorithm::Rs384 => Algorithm::RS384,
+        JwtAlgorithm::Rs512 => Algorithm::RS512,
+        JwtAlgorithm::Ps256 => Algorithm::PS256,
+        JwtAlgorithm::Ps384 => Algorithm::PS384,
+        JwtAlgorithm::Ps512 => Algorithm::PS512,
+        JwtAlgorithm::Es256 => Algorithm::ES256,
+        JwtAlgorithm::Es384 => Algorithm::ES384,
+        JwtAlgorithm::EdDsa => Algorithm::EdDSA,
+    }
+}
+
+/// Token của service khác (sai `iss`/`aud`) là `InvalidToken`
+fn verification_error(e: jsonwebtoken::errors::Error) -> ApiError {
+    use jsonwebtoken::errors::ErrorKind;
+
+    match e.kind() {
+        ErrorKind::InvalidIssuer | ErrorKind::InvalidAudience | ErrorKind::MissingRequiredClaim(_) => {
+            ApiError::InvalidToken {
+                message: format!("Token not accepted by this service: {}", e),
+                source: Some(Box::new(e)),
+            }
+        }
+        _ => ApiError::unauthorized(format!("Invalid token: {}", e)),
     }
 }
 
+fn read_key(path: Option<&str>, variable: &str) -> Result<Vec<u8>, ApiError> {
+    let path = path.ok_or_else(|| ApiError::configuration(format!("{} is not set", variable)))?;
+    std::fs::read(path).map_err(|e| ApiError::configuration(format!("Failed to read {} ({}): {}", variable, path, e)))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -88,4 +316,82 @@ mod tests {
         assert_eq!(claims.email, "test@test.com");
         assert_eq!(claims.role, "admin");
     }
+
+    #[test]
+    fn test_token_expires_by_clock() {
+        use crate::utils::clock::MockClock;
+
+        let clock = MockClock::default();
+        let jwt_manager = JwtManager::new("secret123".to_string(), 1).with_clock(Arc::new(clock.clone()));
+        let token = jwt_manager
+            .create_token("user123", "test@test.com", "admin")
+            .unwrap();
+
+        clock.advance(Duration::minutes(59));
+        assert!(jwt_manager.verify_token(&token).is_ok());
+
+        clock.advance(Duration::minutes(2) + Duration::seconds(EXP_LEEWAY_SECS));
+        assert!(jwt_manager.verify_token(&token).is_err());
+    }
+
+    #[test]
+    fn test_audience_is_enforced() {
+        let issuer = JwtManager::new("shared-secret".to_string(), 1).with_audience("billing");
+        let token = issuer.create_token("user123", "test@test.com", "admin").unwrap();
+
+        let billing = JwtManager::new("shared-secret".to_string(), 1).with_audience("billing");
+        let claims = billing.verify_token(&token).unwrap();
+        assert_eq!(claims.aud.as_deref(), Some("billing"));
+
+        let orders = JwtManager::new("shared-secret".to_string(), 1).with_audience("orders");
+        assert!(matches!(orders.verify_token(&token), Err(ApiError::InvalidToken { .. })));
+    }
+
+    #[test]
+    fn test_issuer_is_enforced() {
+        let expected = JwtManager::new("shared-secret".to_string(), 1).with_issuer("https://auth.example.com");
+
+        let token = JwtManager::new("shared-secret".to_string(), 1)
+            .with_issuer("https://other.example.com")
+            .create_token("user123", "test@test.com", "admin")
+            .unwrap();
+        assert!(matches!(expected.verify_token(&token), Err(ApiError::InvalidToken { .. })));
+
+        // Token không có `iss` cũng bị từ chối
+        let token = JwtManager::new("shared-secret".to_string(), 1)
+            .create_token("user123", "test@test.com", "admin")
+            .unwrap();
+        assert!(matches!(expected.verify_token(&token), Err(ApiError::InvalidToken { .. })));
+    }
+
+    #[test]
+    fn test_token_carries_roles_and_permissions() {
+        let jwt_manager = JwtManager::new("secret123".to_string(), 24);
+
+        let claims = jwt_manager
+            .verify_token(&jwt_manager.create_token("user123", "test@test.com", "admin").unwrap())
+            .unwrap();
+        assert!(claims.has_role("admin"));
+        assert!(claims.has_permission("users:delete"));
+
+        let token = jwt_manager
+            .create_token_with_permissions(
+                "user123",
+                "test@test.com",
+                &["editor".to_string(), "auditor".to_string()],
+                &["reports:read".to_string()],
+            )
+            .unwrap();
+        let claims = jwt_manager.verify_token(&token).unwrap();
+        assert_eq!(claims.role, "editor");
+        assert!(claims.has_any_role(&["viewer", "auditor"]));
+        assert!(!claims.has_any_role(&["admin"]));
+        assert!(claims.has_permission("reports:read"));
+        assert!(!claims.has_permission("users:read"));
+
+        // Refresh giữ nguyên roles/permissions
+        let refreshed = jwt_manager.verify_token(&jwt_manager.refresh_token(&token).unwrap()).unwrap();
+        assert_eq!(refreshed.roles, claims.roles);
+        assert_eq!(refreshed.permissions, claims.permissions);
+    }
 }
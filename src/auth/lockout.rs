@@ -0,0 +1,366 @@
+use chrono::{DateTime, Duration, Utc};
+use std::collections::HashMap;
+use std::sync::{Arc, RwLock};
+
+use crate::config::settings::LockoutSettings;
+use crate::errors::ApiError;
+use crate::security::{AuditEvent, AuditEventType, AuditLogger, AuditResult, AuditSeverity};
+use crate::utils::clock::{Clock, SystemClock};
+
+/// When repeated login failures lock an account or IP
+#[derive(Debug, Clone)]
+pub struct LockoutPolicy {
+    /// Failures within `window` that trigger a lockout
+    pub max_failures: u32,
+    pub window: Duration,
+    pub lockout: Duration,
+    /// Users and IPs tracked at once; when full, stale entries are swept and
+    /// then unlocked ones dropped, so spraying IPs cannot exhaust memory
+    pub max_tracked: usize,
+}
+
+/// Default for [`LockoutPolicy::max_tracked`]
+pub const DEFAULT_MAX_TRACKED_LOGINS: usize = 100_000;
+
+impl Default for LockoutPolicy {
+    fn default() -> Self {
+        Self {
+            max_failures: 5,
+            window: Duration::minutes(15),
+            lockout: Duration::minutes(15),
+            max_tracked: DEFAULT_MAX_TRACKED_LOGINS,
+        }
+    }
+}
+
+impl From<&LockoutSettings> for LockoutPolicy {
+    fn from(settings: &LockoutSettings) -> Self {
+        Self {
+            max_failures: settings.max_failures,
+            window: Duration::seconds(settings.window_secs as i64),
+            lockout: Duration::seconds(settings.lockout_secs as i64),
+            max_tracked: DEFAULT_MAX_TRACKED_LOGINS,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+struct Attempts {
+    failures: u32,
+    first_failure: DateTime<Utc>,
+    locked_until: Option<DateTime<Utc>>,
+}
+
+impl Attempts {
+    fn is_locked(&self, now: DateTime<Utc>) -> bool {
+        self.locked_until.is_some_and(|until| until > now)
+    }
+
+    /// Neither locked nor inside the counting window: safe to forget
+    fn is_stale(&self, now: DateTime<Utc>, policy: &LockoutPolicy) -> bool {
+        match self.locked_until {
+            Some(until) => until <= now,
+            None => now - self.first_failure > policy.window,
+        }
+    }
+}
+
+/// Theo dõi đăng nhập thất bại theo user và theo IP để chống brute force
+///
+/// Call [`check`](Self::check) before verifying credentials, then
+/// [`record_failure`](Self::record_failure) or
+/// [`record_success`](Self::record_success). A success clears the user's
+/// counter but not the IP's, so one valid account can't be used to reset an
+/// IP that is guessing other accounts' passwords.
+///
+/// The template has no password login route, so nothing builds one by
+/// default; a login handler should own it, created from
+/// `settings.auth.lockout` and given the app's [`AuditLogger`].
+#[derive(Clone)]
+pub struct LoginAttemptTracker {
+    policy: LockoutPolicy,
+    attempts: Arc<RwLock<HashMap<String, Attempts>>>,
+    clock: Arc<dyn Clock>,
+    audit_logger: Option<Arc<AuditLogger>>,
+}
+
+impl LoginAttemptTracker {
+    pub fn new(policy: LockoutPolicy) -> Self {
+        Self {
+            policy,
+            attempts: Arc::new(RwLock::new(HashMap::new())),
+            clock: Arc::new(SystemClock),
+            audit_logger: None,
+        }
+    }
+
+    /// Đọc thời gian từ `clock` thay vì system time (dùng trong tests)
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// Log a `SecurityViolation` whenever a lockout starts
+    pub fn with_audit_logger(mut self, audit_logger: Arc<AuditLogger>) -> Self {
+        self.audit_logger = Some(audit_logger);
+        self
+    }
+
+    /// `RateLimitExceeded` if the user or the IP is locked out
+    pub fn check(&self, user: &str, ip: &str) -> Result<(), ApiError> {
+        let now = self.clock.now();
+        let attempts = self.read()?;
+
+        let locked_until = [user_key(user), ip_key(ip)]
+            .iter()
+            .filter_map(|key| attempts.get(key)?.locked_until)
+            .filter(|until| *until > now)
+            .max();
+
+        match locked_until {
+            Some(until) => Err(locked_error(until - now)),
+            None => Ok(()),
+        }
+    }
+
+    /// Count a failed login; returns the lockout error once the threshold is
+    /// reached
+    pub fn record_failure(&self, user: &str, ip: &str) -> Result<(), ApiError> {
+        let now = self.clock.now();
+        let mut newly_locked = Vec::new();
+
+        {
+            let mut attempts = self.write()?;
+            for key in [user_key(user), ip_key(ip)] {
+                if !attempts.contains_key(&key) && !self.make_room(&mut attempts, now) {
+                    tracing::warn!(key = %key, "Login attempt table full of locked entries");
+                    continue;
+                }
+                let entry = attempts.entry(key.clone()).or_insert(Attempts {
+                    failures: 0,
+                    first_failure: now,
+                    locked_until: None,
+                });
+
+                // Cửa sổ đếm hoặc lockout đã hết hạn thì đếm lại từ đầu
+                if entry.is_stale(now, &self.policy) {
+                    *entry = Attempts {
+                        failures: 0,
+                        first_failure: now,
+                        locked_until: None,
+                    };
+                }
+
+                if entry.locked_until.is_some() {
+                    continue;
+                }
+                entry.failures += 1;
+                if entry.failures >= self.policy.max_failures {
+                    entry.locked_until = Some(now + self.policy.lockout);
+                    newly_locked.push((key, entry.failures));
+                }
+            }
+        }
+
+        for (key, failures) in &newly_locked {
+            tracing::warn!(key = %key, failures, "Login locked after repeated failures");
+            if let Some(audit_logger) = &self.audit_logger {
+                audit_logger.log(
+                    AuditEvent::new_with_clock(
+                        AuditEventType::SecurityViolation,
+                        "login_lockout".to_string(),
+                        self.clock.as_ref(),
+                    )
+                    .with_user(user.to_string())
+                    .with_ip(ip.to_string())
+                    .with_severity(AuditSeverity::Warning)
+                    .with_result(AuditResult::Failure)
+                    .with_metadata("locked".to_string(), key.clone())
+                    .with_metadata("failures".to_string(), failures.to_string())
+                    .with_metadata("lockout_secs".to_string(), self.policy.lockout.num_seconds().to_string()),
+                );
+            }
+        }
+
+        self.check(user, ip)
+    }
+
+    /// Clear the user's failure count after a successful login
+    pub fn record_success(&self, user: &str) {
+        if let Ok(mut attempts) = self.attempts.write() {
+            attempts.remove(&user_key(user));
+        }
+    }
+
+    /// Drop entries whose window and lockout are over; returns how many
+    ///
+    /// [`record_failure`](Self::record_failure) also sweeps when the table
+    /// is full, so calling this periodically only keeps memory lower.
+    pub fn purge_expired(&self) -> usize {
+        let now = self.clock.now();
+        let Ok(mut attempts) = self.attempts.write() else {
+            return 0;
+        };
+        let before = attempts.len();
+        attempts.retain(|_, entry| !entry.is_stale(now, &self.policy));
+        before - attempts.len()
+    }
+
+    /// Number of users and IPs currently tracked
+    pub fn tracked(&self) -> usize {
+        self.attempts.read().map(|attempts| attempts.len()).unwrap_or_default()
+    }
+
+    /// Free a slot for a new key; `false` if every entry is an active lockout
+    fn make_room(&self, attempts: &mut HashMap<String, Attempts>, now: DateTime<Utc>) -> bool {
+        if attempts.len() < self.policy.max_tracked {
+            return true;
+        }
+        attempts.retain(|_, entry| !entry.is_stale(now, &self.policy));
+        if attempts.len() < self.policy.max_tracked {
+            return true;
+        }
+        // Vẫn đầy: bỏ các bộ đếm chưa bị khóa, giữ nguyên các lockout
+        attempts.retain(|_, entry| entry.is_locked(now));
+        attempts.len() < self.policy.max_tracked
+    }
+
+    fn read(&self) -> Result<std::sync::RwLockReadGuard<'_, HashMap<String, Attempts>>, ApiError> {
+        self.attempts
+            .read()
+            .map_err(|_| ApiError::internal("Failed to acquire read lock on login attempts"))
+    }
+
+    fn write(&self) -> Result<std::sync::RwLockWriteGuard<'_, HashMap<String, Attempts>>, ApiError> {
+        self.attempts
+            .write()
+            .map_err(|_| ApiError::internal("Failed to acquire write lock on login attempts"))
+    }
+}
+
+fn user_key(user: &str) -> String {
+    format!("user:{}", user.to_lowercase())
+}
+
+fn ip_key(ip: &str) -> String {
+    format!("ip:{}", ip)
+}
+
+fn locked_error(remaining: Duration) -> ApiError {
+    // Làm tròn lên để client không retry sớm hơn
+    let secs = (remaining.num_milliseconds() as u64).div_ceil(1000);
+    ApiError::rate_limit(
+        format!("Too many failed login attempts. Try again in {} seconds", secs),
+        Some(secs),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::clock::MockClock;
+
+    fn tracker(clock: &MockClock) -> LoginAttemptTracker {
+        LoginAttemptTracker::new(LockoutPolicy {
+            max_failures: 3,
+            window: Duration::minutes(10),
+            lockout: Duration::minutes(5),
+            max_tracked: 8,
+        })
+        .with_clock(Arc::new(clock.clone()))
+    }
+
+    #[test]
+    fn test_locks_after_max_failures() {
+        let clock = MockClock::default();
+        let audit_logger = Arc::new(AuditLogger::new(100));
+        let tracker = tracker(&clock).with_audit_logger(audit_logger.clone());
+
+        assert!(tracker.record_failure("alice", "10.0.0.1").is_ok());
+        assert!(tracker.record_failure("alice", "10.0.0.1").is_ok());
+        let locked = tracker.record_failure("alice", "10.0.0.1");
+        assert!(matches!(
+            locked,
+            Err(ApiError::RateLimitExceeded { retry_after: Some(300), .. })
+        ));
+
+        // Khóa theo user (IP khác) và theo IP (user khác)
+        assert!(tracker.check("alice", "10.0.0.2").is_err());
+        assert!(tracker.check("bob", "10.0.0.1").is_err());
+        assert!(tracker.check("bob", "10.0.0.2").is_ok());
+
+        let events = audit_logger.get_recent_events(10);
+        assert!(events
+            .iter()
+            .all(|e| e.event_type == AuditEventType::SecurityViolation));
+        assert_eq!(events.len(), 2);
+    }
+
+    #[test]
+    fn test_unlocks_after_lockout_expires() {
+        let clock = MockClock::default();
+        let tracker = tracker(&clock);
+        for _ in 0..3 {
+            let _ = tracker.record_failure("alice", "10.0.0.1");
+        }
+        assert!(tracker.check("alice", "10.0.0.1").is_err());
+
+        clock.advance(Duration::minutes(5) + Duration::seconds(1));
+        assert!(tracker.check("alice", "10.0.0.1").is_ok());
+
+        // Bộ đếm bắt đầu lại sau khi mở khóa
+        assert!(tracker.record_failure("alice", "10.0.0.1").is_ok());
+    }
+
+    #[test]
+    fn test_failures_outside_window_are_forgotten() {
+        let clock = MockClock::default();
+        let tracker = tracker(&clock);
+        let _ = tracker.record_failure("alice", "10.0.0.1");
+        let _ = tracker.record_failure("alice", "10.0.0.1");
+
+        clock.advance(Duration::minutes(11));
+        assert!(tracker.record_failure("alice", "10.0.0.1").is_ok());
+    }
+
+    #[test]
+    fn test_success_resets_user_counter() {
+        let clock = MockClock::default();
+        let tracker = tracker(&clock);
+        let _ = tracker.record_failure("alice", "10.0.0.1");
+        let _ = tracker.record_failure("alice", "10.0.0.2");
+
+        tracker.record_success("alice");
+        assert!(tracker.record_failure("alice", "10.0.0.3").is_ok());
+        assert!(tracker.record_failure("alice", "10.0.0.4").is_ok());
+    }
+
+    #[test]
+    fn test_stale_entries_are_purged() {
+        let clock = MockClock::default();
+        let tracker = tracker(&clock);
+        let _ = tracker.record_failure("alice", "10.0.0.1");
+        assert_eq!(tracker.tracked(), 2);
+
+        clock.advance(Duration::minutes(11));
+        assert_eq!(tracker.purge_expired(), 2);
+        assert_eq!(tracker.tracked(), 0);
+    }
+
+    #[test]
+    fn test_table_is_capped_but_keeps_lockouts() {
+        let clock = MockClock::default();
+        let tracker = tracker(&clock);
+        for _ in 0..3 {
+            let _ = tracker.record_failure("alice", "10.0.0.1");
+        }
+
+        // Mỗi lần thử với user/IP mới thêm 2 key; bảng không vượt quá cap
+        for i in 0..20 {
+            let _ = tracker.record_failure(&format!("user{}", i), &format!("10.1.0.{}", i));
+        }
+        assert!(tracker.tracked() <= 8);
+        assert!(tracker.check("alice", "10.0.0.9").is_err());
+        assert!(tracker.check("nobody", "10.0.0.1").is_err());
+    }
+}
@@ -1,19 +1,41 @@
 pub mod jwt;
 pub mod password;
 pub mod middleware;
+pub mod extractor;
+pub mod lockout;
 
 #[cfg(feature = "auth-oauth2")]
 pub mod oauth2;
 
+#[cfg(feature = "auth-oauth2")]
+pub mod oauth2_state;
+
 #[cfg(feature = "auth-api-key")]
 pub mod api_key;
 
-pub use jwt::{Claims, JwtManager};
+#[cfg(feature = "auth-api-key")]
+pub mod api_key_store;
+
+pub use jwt::{role_permissions, Claims, JwtManager};
+pub use extractor::AuthenticatedUser;
+pub use lockout::{LockoutPolicy, LoginAttemptTracker, DEFAULT_MAX_TRACKED_LOGINS};
 pub use password::PasswordManager;
 pub use middleware::AuthMiddleware;
 
 #[cfg(feature = "auth-oauth2")]
 pub use oauth2::{OAuth2Config, OAuth2Provider, OAuth2UserInfo, AuthorizationUrlResponse};
 
+#[cfg(feature = "auth-oauth2")]
+pub use oauth2_state::{InMemoryStateStore, PendingAuthorization, StateStore};
+
+#[cfg(all(feature = "auth-oauth2", feature = "cache-redis"))]
+pub use oauth2_state::RedisStateStore;
+
 #[cfg(feature = "auth-api-key")]
 pub use api_key::{ApiKey, ApiKeyManager};
+
+#[cfg(feature = "auth-api-key")]
+pub use api_key_store::ApiKeyStore;
+
+#[cfg(all(feature = "auth-api-key", feature = "database-postgres"))]
+pub use api_key_store::PostgresApiKeyStore;
@@ -1,12 +1,19 @@
 use oauth2::{
     AuthUrl, AuthorizationCode, ClientId, ClientSecret, CsrfToken, PkceCodeChallenge,
-    RedirectUrl, Scope, TokenResponse, TokenUrl,
+    PkceCodeVerifier, RedirectUrl, Scope, TokenResponse, TokenUrl,
     basic::BasicClient,
     reqwest::async_http_client,
 };
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::time::Duration;
 use crate::errors::ApiError;
+use crate::monitoring::TracePropagation;
+use crate::utils::retry::{retry_with_backoff, RetryPolicy};
+use crate::utils::with_timeout;
+
+/// Default bound on a whole user-info fetch, retries included
+pub const DEFAULT_USER_INFO_TIMEOUT: Duration = Duration::from_secs(10);
 
 /// OAuth2 provider configuration
 #[derive(Debug, Clone)]
@@ -14,12 +21,15 @@ pub struct OAuth2Provider {
     pub name: String,
     pub client: BasicClient,
     pub scopes: Vec<String>,
+    /// Userinfo endpoint from OpenID Connect discovery
+    pub userinfo_url: Option<String>,
 }
 
 /// OAuth2 configuration for multiple providers
 #[derive(Debug, Clone)]
 pub struct OAuth2Config {
     providers: HashMap<String, OAuth2Provider>,
+    user_info_timeout: Duration,
 }
 
 /// OAuth2 user info from provider
@@ -30,6 +40,9 @@ pub struct OAuth2UserInfo {
     pub name: Option<String>,
     pub picture: Option<String>,
     pub provider: String,
+    /// Whether the provider asserts the email is verified
+    #[serde(default)]
+    pub email_verified: bool,
 }
 
 /// OAuth2 authorization URL response
@@ -45,9 +58,16 @@ impl OAuth2Config {
     pub fn new() -> Self {
         Self {
             providers: HashMap::new(),
+            user_info_timeout: DEFAULT_USER_INFO_TIMEOUT,
         }
     }
 
+    /// Fail `get_user_info` with `GatewayTimeout` after `timeout`
+    pub fn with_user_info_timeout(mut self, timeout: Duration) -> Self {
+        self.user_info_timeout = timeout;
+        self
+    }
+
     /// Add Google OAuth2 provider
     pub fn add_google(
         mut self,
@@ -80,6 +100,7 @@ impl OAuth2Config {
                     "email".to_string(),
                     "profile".to_string(),
                 ],
+                userinfo_url: None,
             },
         );
 
@@ -114,6 +135,7 @@ impl OAuth2Config {
                 name: "github".to_string(),
                 client,
                 scopes: vec!["user:email".to_string()],
+                userinfo_url: None,
             },
         );
 
@@ -161,6 +183,91 @@ impl OAuth2Config {
                     "email".to_string(),
                     "profile".to_string(),
                 ],
+                userinfo_url: None,
+            },
+        );
+
+        Ok(self)
+    }
+
+    /// Add a generic OpenID Connect provider (Okta, Auth0, Keycloak, ...)
+    ///
+    /// Endpoints are read from `{issuer_url}/.well-known/openid-configuration`.
+    pub async fn add_oidc(
+        mut self,
+        name: String,
+        issuer_url: String,
+        client_id: String,
+        client_secret: String,
+        redirect_url: String,
+    ) -> Result<Self, ApiError> {
+        #[derive(Deserialize)]
+        struct DiscoveryDocument {
+            issuer: String,
+            authorization_endpoint: String,
+            token_endpoint: String,
+            userinfo_endpoint: Option<String>,
+        }
+
+        let issuer_url = issuer_url.trim_end_matches('/').to_string();
+        let discovery_url = format!("{}/.well-known/openid-configuration", issuer_url);
+
+        let response = send_with_retry(
+            || reqwest::Client::new().get(&discovery_url),
+            "fetch OIDC discovery document",
+            &name,
+        )
+        .await?;
+
+        if !response.status().is_success() {
+            return Err(ApiError::external_service(
+                format!("OIDC discovery returned status {}", response.status()),
+                name
+            ));
+        }
+
+        let document: DiscoveryDocument = response
+            .json()
+            .await
+            .map_err(|e| ApiError::external_service(
+                format!("Failed to parse OIDC discovery document: {}", e),
+                name.clone()
+            ))?;
+
+        // The discovered issuer must match the configured one (OIDC Discovery 4.3)
+        if document.issuer.trim_end_matches('/') != issuer_url {
+            return Err(ApiError::configuration(format!(
+                "OIDC issuer mismatch for '{}': expected {}, got {}",
+                name, issuer_url, document.issuer
+            )));
+        }
+
+        let client = BasicClient::new(
+            ClientId::new(client_id),
+            Some(ClientSecret::new(client_secret)),
+            AuthUrl::new(document.authorization_endpoint)
+                .map_err(|e| ApiError::configuration(format!("Invalid OIDC auth URL: {}", e)))?,
+            Some(
+                TokenUrl::new(document.token_endpoint)
+                    .map_err(|e| ApiError::configuration(format!("Invalid OIDC token URL: {}", e)))?,
+            ),
+        )
+        .set_redirect_uri(
+            RedirectUrl::new(redirect_url)
+                .map_err(|e| ApiError::configuration(format!("Invalid redirect URL: {}", e)))?,
+        );
+
+        self.providers.insert(
+            name.clone(),
+            OAuth2Provider {
+                name,
+                client,
+                scopes: vec![
+                    "openid".to_string(),
+                    "email".to_string(),
+                    "profile".to_string(),
+                ],
+                userinfo_url: document.userinfo_endpoint,
             },
         );
 
@@ -213,7 +320,7 @@ impl OAuth2Config {
         &self,
         provider: &str,
         code: String,
-        _pkce_verifier: Option<String>,
+        pkce_verifier: Option<String>,
     ) -> Result<String, ApiError> {
         let oauth_provider = self
             .providers
@@ -223,9 +330,14 @@ impl OAuth2Config {
                 "oauth2_provider"
             ))?;
 
-        let token_result = oauth_provider
+        let mut token_request = oauth_provider
             .client
-            .exchange_code(AuthorizationCode::new(code))
+            .exchange_code(AuthorizationCode::new(code));
+        if let Some(verifier) = pkce_verifier {
+            token_request = token_request.set_pkce_verifier(PkceCodeVerifier::new(verifier));
+        }
+
+        let token_result = token_request
             .request_async(async_http_client)
             .await
             .map_err(|e| ApiError::external_service(
@@ -242,34 +354,100 @@ impl OAuth2Config {
         provider: &str,
         access_token: &str,
     ) -> Result<OAuth2UserInfo, ApiError> {
-        match provider {
-            "google" => self.get_google_user_info(access_token).await,
-            "github" => self.get_github_user_info(access_token).await,
-            "microsoft" => self.get_microsoft_user_info(access_token).await,
-            _ => Err(ApiError::not_found_resource(
-                format!("OAuth2 provider '{}' not supported", provider),
+        let fetch = async {
+            match provider {
+                "google" => self.get_google_user_info(access_token).await,
+                "github" => self.get_github_user_info(access_token).await,
+                "microsoft" => self.get_microsoft_user_info(access_token).await,
+                _ if self.get_provider(provider).and_then(|p| p.userinfo_url.as_ref()).is_some() => {
+                    self.get_userinfo(provider, access_token).await
+                }
+                _ => Err(ApiError::not_found_resource(
+                    format!("OAuth2 provider '{}' not supported", provider),
+                    "oauth2_provider"
+                )),
+            }
+        };
+        with_timeout(self.user_info_timeout, fetch).await?
+    }
+
+    /// Get user info from a discovered OIDC userinfo endpoint, mapping the
+    /// standard `sub`, `email`, `email_verified`, `name` and `picture` claims
+    pub async fn get_userinfo(
+        &self,
+        provider: &str,
+        access_token: &str,
+    ) -> Result<OAuth2UserInfo, ApiError> {
+        let userinfo_url = self
+            .get_provider(provider)
+            .and_then(|p| p.userinfo_url.clone())
+            .ok_or_else(|| ApiError::not_found_resource(
+                format!("OAuth2 provider '{}' has no userinfo endpoint", provider),
                 "oauth2_provider"
-            )),
+            ))?;
+
+        let response = send_with_retry(
+            || reqwest::Client::new().get(&userinfo_url).bearer_auth(access_token),
+            &format!("get {} user info", provider),
+            provider,
+        )
+        .await?;
+
+        if !response.status().is_success() {
+            return Err(ApiError::external_service(
+                format!("Userinfo endpoint returned status {}", response.status()),
+                provider
+            ));
+        }
+
+        #[derive(Deserialize)]
+        struct OidcClaims {
+            sub: String,
+            email: Option<String>,
+            #[serde(default)]
+            email_verified: bool,
+            name: Option<String>,
+            picture: Option<String>,
         }
+
+        let claims: OidcClaims = response
+            .json()
+            .await
+            .map_err(|e| ApiError::external_service(
+                format!("Failed to parse {} user info: {}", provider, e),
+                provider
+            ))?;
+
+        Ok(OAuth2UserInfo {
+            id: claims.sub,
+            email: claims.email,
+            name: claims.name,
+            picture: claims.picture,
+            provider: provider.to_string(),
+            email_verified: claims.email_verified,
+        })
     }
 
     /// Get Google user info
     async fn get_google_user_info(&self, access_token: &str) -> Result<OAuth2UserInfo, ApiError> {
         let client = reqwest::Client::new();
-        let response = client
-            .get("https://www.googleapis.com/oauth2/v2/userinfo")
-            .bearer_auth(access_token)
-            .send()
-            .await
-            .map_err(|e| ApiError::external_service(
-                format!("Failed to get Google user info: {}", e),
-                "google"
-            ))?;
+        let response = send_with_retry(
+            || {
+                client
+                    .get("https://www.googleapis.com/oauth2/v2/userinfo")
+                    .bearer_auth(access_token)
+            },
+            "get Google user info",
+            "google",
+        )
+        .await?;
 
         #[derive(Deserialize)]
         struct GoogleUserInfo {
             id: String,
             email: Option<String>,
+            #[serde(default)]
+            verified_email: bool,
             name: Option<String>,
             picture: Option<String>,
         }
@@ -288,6 +466,7 @@ impl OAuth2Config {
             name: user_info.name,
             picture: user_info.picture,
             provider: "google".to_string(),
+            email_verified: user_info.verified_email,
         })
     }
 
@@ -296,16 +475,17 @@ impl OAuth2Config {
         let client = reqwest::Client::new();
 
         // Get user profile
-        let response = client
-            .get("https://api.github.com/user")
-            .bearer_auth(access_token)
-            .header("User-Agent", "api-management-template")
-            .send()
-            .await
-            .map_err(|e| ApiError::external_service(
-                format!("Failed to get GitHub user info: {}", e),
-                "github"
-            ))?;
+        let response = send_with_retry(
+            || {
+                client
+                    .get("https://api.github.com/user")
+                    .bearer_auth(access_token)
+                    .header("User-Agent", "api-management-template")
+            },
+            "get GitHub user info",
+            "github",
+        )
+        .await?;
 
         #[derive(Deserialize)]
         struct GitHubUserInfo {
@@ -329,20 +509,23 @@ impl OAuth2Config {
             name: user_info.name,
             picture: user_info.avatar_url,
             provider: "github".to_string(),
+            // The public profile email is not guaranteed to be verified
+            email_verified: false,
         })
     }
     /// Get Microsoft user info
     async fn get_microsoft_user_info(&self, access_token: &str) -> Result<OAuth2UserInfo, ApiError> {
         let client = reqwest::Client::new();
-        let response = client
-            .get("https://graph.microsoft.com/v1.0/me")
-            .bearer_auth(access_token)
-            .send()
-            .await
-            .map_err(|e| ApiError::external_service(
-                format!("Failed to get Microsoft user info: {}", e),
-                "microsoft"
-            ))?;
+        let response = send_with_retry(
+            || {
+                client
+                    .get("https://graph.microsoft.com/v1.0/me")
+                    .bearer_auth(access_token)
+            },
+            "get Microsoft user info",
+            "microsoft",
+        )
+        .await?;
 
         #[derive(Deserialize)]
         struct MicrosoftUserInfo {
@@ -367,6 +550,7 @@ impl OAuth2Config {
             name: user_info.name,
             picture: None,
             provider: "microsoft".to_string(),
+            email_verified: false,
         })
     }
 
@@ -381,6 +565,23 @@ impl OAuth2Config {
     }
 }
 
+/// Send a GET built by `build`, retrying connection errors with backoff
+///
+/// Only the transport is retried; a non-success status is left to the caller.
+async fn send_with_retry<F>(build: F, action: &str, provider: &str) -> Result<reqwest::Response, ApiError>
+where
+    F: Fn() -> reqwest::RequestBuilder,
+{
+    retry_with_backoff(RetryPolicy::default(), || async {
+        build()
+            .with_trace_context()
+            .send()
+            .await
+            .map_err(|e| ApiError::external_service(format!("Failed to {}: {}", action, e), provider))
+    })
+    .await
+}
+
 impl Default for OAuth2Config {
     fn default() -> Self {
         Self::new()
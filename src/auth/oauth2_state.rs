@@ -0,0 +1,144 @@
+use async_trait::async_trait;
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::{Arc, RwLock};
+
+use crate::errors::ApiError;
+
+#[cfg(feature = "cache-redis")]
+use crate::cache::CacheManager;
+
+/// Default lifetime of a pending OAuth2 authorization
+pub const DEFAULT_STATE_TTL_SECS: i64 = 600;
+
+/// Authorization started by `get_auth_url`, keyed by its CSRF token
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PendingAuthorization {
+    pub provider: String,
+    pub pkce_verifier: Option<String>,
+}
+
+/// Storage for OAuth2 `state` (CSRF token) values between the authorization
+/// redirect and the callback
+#[async_trait]
+pub trait StateStore: Send + Sync {
+    /// Remember a pending authorization
+    async fn put(&self, csrf_token: &str, pending: PendingAuthorization) -> Result<(), ApiError>;
+
+    /// Consume a pending authorization; `None` if it is unknown, expired or
+    /// was already used
+    async fn take(&self, csrf_token: &str) -> Result<Option<PendingAuthorization>, ApiError>;
+}
+
+/// In-memory state store with TTL
+pub struct InMemoryStateStore {
+    entries: Arc<RwLock<HashMap<String, (PendingAuthorization, DateTime<Utc>)>>>,
+    ttl: Duration,
+}
+
+impl InMemoryStateStore {
+    pub fn new() -> Self {
+        Self::with_ttl(Duration::seconds(DEFAULT_STATE_TTL_SECS))
+    }
+
+    pub fn with_ttl(ttl: Duration) -> Self {
+        Self {
+            entries: Arc::new(RwLock::new(HashMap::new())),
+            ttl,
+        }
+    }
+}
+
+impl Default for InMemoryStateStore {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[async_trait]
+impl StateStore for InMemoryStateStore {
+    async fn put(&self, csrf_token: &str, pending: PendingAuthorization) -> Result<(), ApiError> {
+        let now = Utc::now();
+        let mut entries = self
+            .entries
+            .write()
+            .map_err(|_| ApiError::internal("Failed to acquire write lock on OAuth2 state store"))?;
+
+        // Drop expired entries so abandoned logins don't accumulate
+        entries.retain(|_, (_, expires_at)| *expires_at > now);
+        entries.insert(csrf_token.to_string(), (pending, now + self.ttl));
+        Ok(())
+    }
+
+    async fn take(&self, csrf_token: &str) -> Result<Option<PendingAuthorization>, ApiError> {
+        let mut entries = self
+            .entries
+            .write()
+            .map_err(|_| ApiError::internal("Failed to acquire write lock on OAuth2 state store"))?;
+
+        Ok(entries
+            .remove(csrf_token)
+            .filter(|(_, expires_at)| *expires_at > Utc::now())
+            .map(|(pending, _)| pending))
+    }
+}
+
+/// Redis-backed state store, shared across instances
+#[cfg(feature = "cache-redis")]
+pub struct RedisStateStore {
+    conn: redis::aio::ConnectionManager,
+    ttl_secs: u64,
+}
+
+#[cfg(feature = "cache-redis")]
+impl RedisStateStore {
+    pub fn new(cache: &CacheManager) -> Self {
+        Self::with_ttl(cache, DEFAULT_STATE_TTL_SECS as u64)
+    }
+
+    pub fn with_ttl(cache: &CacheManager, ttl_secs: u64) -> Self {
+        Self {
+            conn: cache.get_connection(),
+            ttl_secs,
+        }
+    }
+
+    fn key(csrf_token: &str) -> String {
+        format!("oauth2_state:{}", csrf_token)
+    }
+}
+
+#[cfg(feature = "cache-redis")]
+#[async_trait]
+impl StateStore for RedisStateStore {
+    async fn put(&self, csrf_token: &str, pending: PendingAuthorization) -> Result<(), ApiError> {
+        use redis::AsyncCommands;
+
+        let serialized = serde_json::to_string(&pending)
+            .map_err(|e| ApiError::cache(format!("OAuth2 state serialize error: {}", e)))?;
+
+        let mut conn = self.conn.clone();
+        conn.set_ex::<_, _, ()>(Self::key(csrf_token), serialized, self.ttl_secs)
+            .await
+            .map_err(|e| ApiError::cache(format!("OAuth2 state set error: {}", e)))
+    }
+
+    async fn take(&self, csrf_token: &str) -> Result<Option<PendingAuthorization>, ApiError> {
+        use redis::AsyncCommands;
+
+        // GETDEL makes consumption atomic, so a token can only be used once
+        let mut conn = self.conn.clone();
+        let value: Option<String> = conn
+            .get_del(Self::key(csrf_token))
+            .await
+            .map_err(|e| ApiError::cache(format!("OAuth2 state get error: {}", e)))?;
+
+        value
+            .map(|v| {
+                serde_json::from_str(&v)
+                    .map_err(|e| ApiError::cache(format!("OAuth2 state deserialize error: {}", e)))
+            })
+            .transpose()
+    }
+}
@@ -0,0 +1,85 @@
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use std::io::{Read, Write};
+
+use crate::errors::ApiError;
+
+/// Byte đứng đầu value đã nén bằng gzip
+///
+/// Neither plain JSON nor the tags of [`crate::utils::codec`] start with it,
+/// so values written without compression (including those from before
+/// compression existed) are read back as-is.
+pub const GZIP_MARKER: u8 = 0x01;
+
+/// Largest value `decode` inflates, so a small corrupt or hostile entry
+/// can't expand into gigabytes
+pub const DEFAULT_MAX_DECOMPRESSED_BYTES: usize = 16 * 1024 * 1024;
+
+/// Compression codec for cached values
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CacheCodec {
+    Gzip,
+}
+
+/// When and how `CacheManager` compresses values
+#[derive(Debug, Clone, Copy)]
+pub struct CompressionConfig {
+    pub codec: CacheCodec,
+    /// Serialized values shorter than this are stored uncompressed
+    pub threshold_bytes: usize,
+    /// Compressed values inflating past this are rejected on read
+    pub max_decompressed_bytes: usize,
+}
+
+impl Default for CompressionConfig {
+    fn default() -> Self {
+        Self {
+            codec: CacheCodec::Gzip,
+            threshold_bytes: 1024,
+            max_decompressed_bytes: DEFAULT_MAX_DECOMPRESSED_BYTES,
+        }
+    }
+}
+
+impl CompressionConfig {
+    /// Bytes to store for `serialized`: unchanged below the threshold,
+    /// otherwise the codec marker followed by the compressed data
+    pub fn encode(&self, serialized: &[u8]) -> Result<Vec<u8>, ApiError> {
+        if serialized.len() < self.threshold_bytes {
+            return Ok(serialized.to_vec());
+        }
+
+        match self.codec {
+            CacheCodec::Gzip => {
+                let mut encoder = GzEncoder::new(vec![GZIP_MARKER], flate2::Compression::default());
+                encoder
+                    .write_all(serialized)
+                    .and_then(|_| encoder.finish())
+                    .map_err(|e| ApiError::cache(format!("Cache compression error: {}", e)))
+            }
+        }
+    }
+}
+
+/// Serialized value from stored bytes, whichever way they were written;
+/// compressed values inflating past `max_bytes` are an error
+pub fn decode(stored: &[u8], max_bytes: usize) -> Result<Vec<u8>, ApiError> {
+    match stored.first() {
+        Some(&GZIP_MARKER) => {
+            let mut decoded = Vec::new();
+            // Đọc dư 1 byte để phân biệt "vừa đủ" với "vượt giới hạn"
+            GzDecoder::new(&stored[1..])
+                .take(max_bytes as u64 + 1)
+                .read_to_end(&mut decoded)
+                .map_err(|e| ApiError::cache(format!("Cache decompression error: {}", e)))?;
+            if decoded.len() > max_bytes {
+                return Err(ApiError::cache(format!(
+                    "Cache value decompresses to more than {} bytes",
+                    max_bytes
+                )));
+            }
+            Ok(decoded)
+        }
+        _ => Ok(stored.to_vec()),
+    }
+}
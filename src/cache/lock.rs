@@ -0,0 +1,95 @@
+use redis::aio::ConnectionManager;
+use std::time::Duration;
+
+use crate::errors::ApiError;
+
+/// Delete the lock only if it still holds our token, so a lock that expired
+/// and was re-acquired by another node is never released by us
+const RELEASE_SCRIPT: &str = r#"
+if redis.call("GET", KEYS[1]) == ARGV[1] then
+    return redis.call("DEL", KEYS[1])
+else
+    return 0
+end
+"#;
+
+/// Held distributed lock, created by `CacheManager::acquire_lock`
+///
+/// The lock is released when the guard is dropped (in a background task);
+/// call `release` to release it synchronously and learn whether it was
+/// still held.
+pub struct LockGuard {
+    conn: ConnectionManager,
+    key: String,
+    token: String,
+    released: bool,
+}
+
+impl LockGuard {
+    pub(crate) async fn acquire(
+        mut conn: ConnectionManager,
+        key: String,
+        ttl: Duration,
+    ) -> Result<Option<Self>, ApiError> {
+        let token = uuid::Uuid::new_v4().to_string();
+
+        // SET NX PX: chỉ set khi key chưa tồn tại, tự hết hạn sau ttl
+        let acquired: Option<String> = redis::cmd("SET")
+            .arg(&key)
+            .arg(&token)
+            .arg("NX")
+            .arg("PX")
+            .arg(ttl.as_millis().max(1) as u64)
+            .query_async(&mut conn)
+            .await
+            .map_err(|e| ApiError::cache(format!("Lock acquire error: {}", e)))?;
+
+        Ok(acquired.map(|_| Self {
+            conn,
+            key,
+            token,
+            released: false,
+        }))
+    }
+
+    pub fn key(&self) -> &str {
+        &self.key
+    }
+
+    /// Release the lock; `false` if it had already expired
+    pub async fn release(mut self) -> Result<bool, ApiError> {
+        self.released = true;
+        release(&mut self.conn, &self.key, &self.token).await
+    }
+}
+
+impl Drop for LockGuard {
+    fn drop(&mut self) {
+        if self.released {
+            return;
+        }
+
+        // Drop không thể async; nếu không có runtime lock sẽ tự hết hạn theo ttl
+        if let Ok(handle) = tokio::runtime::Handle::try_current() {
+            let mut conn = self.conn.clone();
+            let key = std::mem::take(&mut self.key);
+            let token = std::mem::take(&mut self.token);
+            handle.spawn(async move {
+                if let Err(e) = release(&mut conn, &key, &token).await {
+                    tracing::warn!(key = %key, "Failed to release lock: {}", e);
+                }
+            });
+        }
+    }
+}
+
+async fn release(conn: &mut ConnectionManager, key: &str, token: &str) -> Result<bool, ApiError> {
+    let deleted: i64 = redis::Script::new(RELEASE_SCRIPT)
+        .key(key)
+        .arg(token)
+        .invoke_async(conn)
+        .await
+        .map_err(|e| ApiError::cache(format!("Lock release error: {}", e)))?;
+
+    Ok(deleted == 1)
+}
@@ -1,10 +1,26 @@
+pub mod compression;
+pub mod lock;
+
+pub use compression::{CacheCodec, CompressionConfig};
+pub use lock::LockGuard;
+
+use async_trait::async_trait;
 use redis::{aio::ConnectionManager, AsyncCommands, Client};
 use serde::{de::DeserializeOwned, Serialize};
+use std::time::Duration;
+use crate::config::RedisSettings;
 use crate::errors::ApiError;
+use crate::health::{CheckResult, HealthCheckable};
+use crate::multitenancy::Tenant;
+use crate::utils::codec::{self, Codec, PayloadFormat};
 
 /// Redis cache manager
+#[derive(Clone)]
 pub struct CacheManager {
     conn: ConnectionManager,
+    compression: Option<CompressionConfig>,
+    format: PayloadFormat,
+    fail_open: bool,
 }
 
 impl CacheManager {
@@ -18,7 +34,56 @@ impl CacheManager {
             .await
             .map_err(|e| ApiError::cache(format!("Redis connection error: {}", e)))?;
 
-        Ok(Self { conn })
+        Ok(Self {
+            conn,
+            compression: None,
+            format: PayloadFormat::default(),
+            fail_open: false,
+        })
+    }
+
+    /// [`new`](Self::new) for `settings.url`, fail-open if `settings.fail_open`
+    pub async fn from_settings(settings: &RedisSettings) -> Result<Self, ApiError> {
+        Ok(Self::new(&settings.url).await?.with_fail_open(settings.fail_open))
+    }
+
+    /// Compress values at least `config.threshold_bytes` long on `set`;
+    /// `get` reads compressed and uncompressed values either way
+    pub fn with_compression(mut self, config: CompressionConfig) -> Self {
+        self.compression = Some(config);
+        self
+    }
+
+    /// Codec used by `set` (default JSON); `get` reads every format, so the
+    /// codec can be changed without flushing the cache
+    pub fn with_format(mut self, format: PayloadFormat) -> Self {
+        self.format = format;
+        self
+    }
+
+    /// Cache-optional mode: Redis errors in `get`, `set`, `delete` and
+    /// `exists` are logged and treated as a miss instead of failing the
+    /// request, so handlers fall back to the source during an outage.
+    /// Deserialize errors and the remaining operations still fail. Off by
+    /// default; `REDIS_FAIL_OPEN=true` turns it on via
+    /// [`from_settings`](Self::from_settings).
+    pub fn with_fail_open(mut self, fail_open: bool) -> Self {
+        self.fail_open = fail_open;
+        self
+    }
+
+    pub fn is_fail_open(&self) -> bool {
+        self.fail_open
+    }
+
+    /// `fallback` instead of `error` in fail-open mode
+    fn degrade<T>(&self, error: ApiError, fallback: T) -> Result<T, ApiError> {
+        if self.fail_open {
+            tracing::warn!(error = %error, "Cache unavailable, treating as miss");
+            Ok(fallback)
+        } else {
+            Err(error)
+        }
     }
 
     /// Get connection manager (for health checks)
@@ -26,18 +91,74 @@ impl CacheManager {
         self.conn.clone()
     }
 
+    /// View whose keys are transparently namespaced as `{prefix}:{key}`
+    pub fn with_prefix(&self, prefix: impl Into<String>) -> PrefixedCache {
+        PrefixedCache {
+            inner: self.clone(),
+            prefix: prefix.into(),
+        }
+    }
+
+    /// View namespaced by tenant id, so tenants never see each other's keys
+    pub fn scoped_for(&self, tenant: &Tenant) -> PrefixedCache {
+        self.with_prefix(tenant.id.clone())
+    }
+
+    /// Delete every key under `{prefix}:` using `SCAN` (non-blocking, unlike
+    /// `KEYS`); returns the number of deleted keys
+    pub async fn delete_prefix(&mut self, prefix: &str) -> Result<u64, ApiError> {
+        let pattern = format!("{}:*", escape_glob(prefix));
+        let mut cursor: u64 = 0;
+        let mut deleted: u64 = 0;
+
+        loop {
+            let (next, keys): (u64, Vec<String>) = redis::cmd("SCAN")
+                .arg(cursor)
+                .arg("MATCH")
+                .arg(&pattern)
+                .arg("COUNT")
+                .arg(100)
+                .query_async(&mut self.conn)
+                .await
+                .map_err(|e| ApiError::cache(format!("Cache scan error: {}", e)))?;
+
+            if !keys.is_empty() {
+                let count: u64 = self
+                    .conn
+                    .del(&keys)
+                    .await
+                    .map_err(|e| ApiError::cache(format!("Cache delete error: {}", e)))?;
+                deleted += count;
+            }
+
+            if next == 0 {
+                return Ok(deleted);
+            }
+            cursor = next;
+        }
+    }
+
+    /// Try to take a distributed lock on `lock:{key}` for at most `ttl`;
+    /// `None` if another holder has it
+    pub async fn acquire_lock(&self, key: &str, ttl: Duration) -> Result<Option<LockGuard>, ApiError> {
+        LockGuard::acquire(self.conn.clone(), format!("lock:{}", key), ttl).await
+    }
+
     /// Get value from cache
     pub async fn get<T: DeserializeOwned>(&mut self, key: &str) -> Result<Option<T>, ApiError> {
-        let value: Option<String> = self
-            .conn
-            .get(key)
-            .await
-            .map_err(|e| ApiError::cache(format!("Cache get error: {}", e)))?;
+        let value: Option<Vec<u8>> = match self.conn.get(key).await {
+            Ok(value) => value,
+            Err(e) => return self.degrade(ApiError::cache(format!("Cache get error: {}", e)), None),
+        };
 
         match value {
             Some(v) => {
-                let data = serde_json::from_str(&v)
-                    .map_err(|e| ApiError::cache(format!("Cache deserialize error: {}", e)))?;
+                let max_bytes = self
+                    .compression
+                    .map(|config| config.max_decompressed_bytes)
+                    .unwrap_or(compression::DEFAULT_MAX_DECOMPRESSED_BYTES);
+                let data = codec::decode_any(&compression::decode(&v, max_bytes)?)
+                    .map_err(|e| ApiError::cache(e.message()))?;
                 Ok(Some(data))
             }
             None => Ok(None),
@@ -51,33 +172,66 @@ impl CacheManager {
         value: &T,
         expiration: u64,
     ) -> Result<(), ApiError> {
-        let serialized = serde_json::to_string(value)
-            .map_err(|e| ApiError::cache(format!("Cache serialize error: {}", e)))?;
+        let serialized = self
+            .format
+            .encode(value)
+            .map_err(|e| ApiError::cache(e.message()))?;
+        let serialized = match &self.compression {
+            Some(config) => config.encode(&serialized)?,
+            None => serialized,
+        };
 
-        self.conn
-            .set_ex::<_, _, ()>(key, serialized, expiration)
-            .await
-            .map_err(|e| ApiError::cache(format!("Cache set error: {}", e)))?;
-
-        Ok(())
+        match self.conn.set_ex::<_, _, ()>(key, serialized, expiration).await {
+            Ok(()) => Ok(()),
+            Err(e) => self.degrade(ApiError::cache(format!("Cache set error: {}", e)), ()),
+        }
     }
 
     /// Delete key from cache
     pub async fn delete(&mut self, key: &str) -> Result<(), ApiError> {
-        self.conn
-            .del::<_, ()>(key)
-            .await
-            .map_err(|e| ApiError::cache(format!("Cache delete error: {}", e)))?;
-
-        Ok(())
+        match self.conn.del::<_, ()>(key).await {
+            Ok(()) => Ok(()),
+            Err(e) => self.degrade(ApiError::cache(format!("Cache delete error: {}", e)), ()),
+        }
     }
 
     /// Check if key exists
     pub async fn exists(&mut self, key: &str) -> Result<bool, ApiError> {
+        match self.conn.exists(key).await {
+            Ok(exists) => Ok(exists),
+            Err(e) => self.degrade(ApiError::cache(format!("Cache exists error: {}", e)), false),
+        }
+    }
+
+    /// Remaining time to live in seconds; `None` if the key has no expiry or
+    /// doesn't exist
+    pub async fn ttl(&mut self, key: &str) -> Result<Option<i64>, ApiError> {
+        let ttl: i64 = self
+            .conn
+            .ttl(key)
+            .await
+            .map_err(|e| ApiError::cache(format!("Cache ttl error: {}", e)))?;
+
+        // Redis trả về -1 khi không có expiry và -2 khi key không tồn tại
+        Ok((ttl >= 0).then_some(ttl))
+    }
+
+    /// Set expiry (seconds) without touching the value; `false` if the key
+    /// doesn't exist
+    pub async fn expire(&mut self, key: &str, seconds: u64) -> Result<bool, ApiError> {
+        self.conn
+            .expire(key, seconds as i64)
+            .await
+            .map_err(|e| ApiError::cache(format!("Cache expire error: {}", e)))
+    }
+
+    /// Remove expiry so the key is kept; `false` if the key doesn't exist or
+    /// had no expiry
+    pub async fn persist(&mut self, key: &str) -> Result<bool, ApiError> {
         self.conn
-            .exists(key)
+            .persist(key)
             .await
-            .map_err(|e| ApiError::cache(format!("Cache exists error: {}", e)))
+            .map_err(|e| ApiError::cache(format!("Cache persist error: {}", e)))
     }
 
     /// Increment counter (for rate limiting)
@@ -98,3 +252,87 @@ impl CacheManager {
         Ok(count)
     }
 }
+
+/// Escape Redis glob metacharacters so a prefix only matches literally
+fn escape_glob(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        if matches!(c, '*' | '?' | '[' | ']' | '\\') {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+/// Cache view with namespaced keys, created by `CacheManager::with_prefix`
+#[derive(Clone)]
+pub struct PrefixedCache {
+    inner: CacheManager,
+    prefix: String,
+}
+
+impl PrefixedCache {
+    pub fn prefix(&self) -> &str {
+        &self.prefix
+    }
+
+    fn key(&self, key: &str) -> String {
+        format!("{}:{}", self.prefix, key)
+    }
+
+    pub async fn get<T: DeserializeOwned>(&mut self, key: &str) -> Result<Option<T>, ApiError> {
+        let key = self.key(key);
+        self.inner.get(&key).await
+    }
+
+    pub async fn set<T: Serialize>(
+        &mut self,
+        key: &str,
+        value: &T,
+        expiration: u64,
+    ) -> Result<(), ApiError> {
+        let key = self.key(key);
+        self.inner.set(&key, value, expiration).await
+    }
+
+    pub async fn delete(&mut self, key: &str) -> Result<(), ApiError> {
+        let key = self.key(key);
+        self.inner.delete(&key).await
+    }
+
+    pub async fn exists(&mut self, key: &str) -> Result<bool, ApiError> {
+        let key = self.key(key);
+        self.inner.exists(&key).await
+    }
+
+    /// Delete every key in this namespace
+    pub async fn clear(&mut self) -> Result<u64, ApiError> {
+        let prefix = self.prefix.clone();
+        self.inner.delete_prefix(&prefix).await
+    }
+}
+
+#[async_trait]
+impl HealthCheckable for CacheManager {
+    fn name(&self) -> &str {
+        "cache"
+    }
+
+    /// Cache outages degrade the service but don't take it out of rotation
+    fn is_critical(&self) -> bool {
+        false
+    }
+
+    async fn check(&self) -> CheckResult {
+        CheckResult::timed(Duration::from_millis(500), async {
+            let mut conn = self.conn.clone();
+            let _: String = redis::cmd("PING")
+                .query_async(&mut conn)
+                .await
+                .map_err(|e| format!("Cache error: {}", e))?;
+            Ok(())
+        })
+        .await
+    }
+}
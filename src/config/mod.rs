@@ -1,5 +1,14 @@
 pub mod seed_data;
 pub mod settings;
 
+#[cfg(feature = "tls")]
+pub mod tls;
+
 pub use seed_data::create_seed_data;
-pub use settings::Settings;
+pub use settings::{
+    CorsSettings, JwtAlgorithm, JwtSettings, MetricsMode, MetricsSettings, RedisSettings,
+    ServerSettings, Settings,
+};
+
+#[cfg(feature = "tls")]
+pub use tls::{https_redirect, load_rustls_config};
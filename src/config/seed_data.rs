@@ -9,30 +9,36 @@ pub fn create_seed_data() -> Vec<User> {
             name: "Nguyễn Văn A".to_string(),
             email: "nguyenvana@example.com".to_string(),
             age: 25,
+            phone: None,
             role: "admin".to_string(),
             is_active: true,
             created_at: Utc::now(),
             updated_at: Utc::now(),
+            deleted_at: None,
         },
         User {
             id: Uuid::new_v4().to_string(),
             name: "Trần Thị B".to_string(),
             email: "tranthib@example.com".to_string(),
             age: 30,
+            phone: None,
             role: "user".to_string(),
             is_active: true,
             created_at: Utc::now(),
             updated_at: Utc::now(),
+            deleted_at: None,
         },
         User {
             id: Uuid::new_v4().to_string(),
             name: "Lê Văn C".to_string(),
             email: "levanc@example.com".to_string(),
             age: 28,
+            phone: None,
             role: "user".to_string(),
             is_active: true,
             created_at: Utc::now(),
             updated_at: Utc::now(),
+            deleted_at: None,
         },
     ]
 }
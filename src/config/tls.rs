@@ -0,0 +1,87 @@
+use actix_web::{http::header, HttpRequest, HttpResponse};
+use rustls::pki_types::{CertificateDer, PrivateKeyDer};
+use rustls::ServerConfig;
+use std::fs::File;
+use std::io::BufReader;
+use std::sync::Arc;
+
+use super::settings::ServerSettings;
+use crate::errors::ApiError;
+
+/// Đọc cert chain và private key (PEM) cho HTTPS
+///
+/// Every failure (missing paths, unreadable files, no certificate or key in
+/// the PEM, key not matching the certificate) is a `ConfigurationError`
+/// naming the offending setting.
+pub fn load_rustls_config(settings: &ServerSettings) -> Result<ServerConfig, ApiError> {
+    settings.validate().map_err(ApiError::configuration)?;
+    let (Some(cert_path), Some(key_path)) = (&settings.tls_cert_path, &settings.tls_key_path) else {
+        return Err(ApiError::configuration("HTTPS is not enabled"));
+    };
+
+    let certs = load_certs(cert_path)?;
+    let key = load_private_key(key_path)?;
+
+    ServerConfig::builder_with_provider(Arc::new(rustls::crypto::ring::default_provider()))
+        .with_safe_default_protocol_versions()
+        .map_err(|e| ApiError::configuration(format!("Invalid TLS protocol configuration: {}", e)))?
+        .with_no_client_auth()
+        .with_single_cert(certs, key)
+        .map_err(|e| ApiError::configuration(format!("TLS_KEY_PATH does not match TLS_CERT_PATH: {}", e)))
+}
+
+fn open(setting: &str, path: &str) -> Result<BufReader<File>, ApiError> {
+    File::open(path)
+        .map(BufReader::new)
+        .map_err(|e| ApiError::configuration(format!("Failed to open {} {}: {}", setting, path, e)))
+}
+
+fn load_certs(path: &str) -> Result<Vec<CertificateDer<'static>>, ApiError> {
+    let certs = rustls_pemfile::certs(&mut open("TLS_CERT_PATH", path)?)
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| ApiError::configuration(format!("Failed to parse TLS_CERT_PATH {}: {}", path, e)))?;
+
+    if certs.is_empty() {
+        return Err(ApiError::configuration(format!(
+            "TLS_CERT_PATH {} contains no PEM certificate",
+            path
+        )));
+    }
+    Ok(certs)
+}
+
+fn load_private_key(path: &str) -> Result<PrivateKeyDer<'static>, ApiError> {
+    rustls_pemfile::private_key(&mut open("TLS_KEY_PATH", path)?)
+        .map_err(|e| ApiError::configuration(format!("Failed to parse TLS_KEY_PATH {}: {}", path, e)))?
+        .ok_or_else(|| ApiError::configuration(format!("TLS_KEY_PATH {} contains no PEM private key", path)))
+}
+
+/// `308` tới cùng path trên HTTPS, dùng cho listener `HTTP_REDIRECT_PORT`
+pub fn https_redirect(req: &HttpRequest, https_port: u16) -> HttpResponse {
+    let connection = req.connection_info();
+    let host = strip_port(connection.host());
+    let authority = if https_port == 443 {
+        host.to_string()
+    } else {
+        format!("{}:{}", host, https_port)
+    };
+    let path = req
+        .uri()
+        .path_and_query()
+        .map(|pq| pq.as_str())
+        .unwrap_or("/");
+
+    HttpResponse::PermanentRedirect()
+        .insert_header((header::LOCATION, format!("https://{}{}", authority, path)))
+        .finish()
+}
+
+/// `example.com:8080` → `example.com`, `[::1]:8080` → `[::1]`
+fn strip_port(host: &str) -> &str {
+    match host.rsplit_once(':') {
+        Some((name, port)) if port.parse::<u16>().is_ok() && (!name.contains(':') || name.ends_with(']')) => {
+            name
+        }
+        _ => host,
+    }
+}
@@ -0,0 +1,72 @@
+use serde::Serialize;
+use sqlx::migrate::{Migrate, Migrator};
+use std::collections::HashSet;
+
+use crate::errors::ApiError;
+
+/// Migrations cho Postgres (`Database`)
+#[cfg(feature = "database-postgres")]
+pub static POSTGRES_MIGRATOR: Migrator = sqlx::migrate!("./migrations/postgres");
+
+#[cfg(feature = "database-mysql")]
+pub static MYSQL_MIGRATOR: Migrator = sqlx::migrate!("./migrations/mysql");
+
+#[cfg(feature = "database-sqlite")]
+pub static SQLITE_MIGRATOR: Migrator = sqlx::migrate!("./migrations/sqlite");
+
+/// One migration file
+#[derive(Debug, Clone, Serialize)]
+pub struct MigrationInfo {
+    pub version: i64,
+    pub description: String,
+}
+
+/// Applied vs pending migrations of one database
+#[derive(Debug, Clone, Serialize)]
+pub struct MigrationStatus {
+    pub applied: Vec<MigrationInfo>,
+    pub pending: Vec<MigrationInfo>,
+}
+
+impl MigrationStatus {
+    pub fn is_up_to_date(&self) -> bool {
+        self.pending.is_empty()
+    }
+}
+
+/// Log and wrap a failed migration run
+pub(crate) fn migration_error(err: sqlx::migrate::MigrateError) -> ApiError {
+    tracing::error!("Migration failed: {}", err);
+    ApiError::internal(format!("Migration failed: {}", err))
+}
+
+/// Compare `migrator`'s files with the versions recorded in `conn`
+///
+/// Creates the (empty) `_sqlx_migrations` table on a fresh database, as
+/// `sqlx migrate info` does.
+pub async fn status<C>(migrator: &Migrator, conn: &mut C) -> Result<MigrationStatus, ApiError>
+where
+    C: Migrate + Send,
+{
+    conn.ensure_migrations_table()
+        .await
+        .map_err(|e| ApiError::database(format!("Failed to read migrations table: {}", e)))?;
+    let applied_versions: HashSet<i64> = conn
+        .list_applied_migrations()
+        .await
+        .map_err(|e| ApiError::database(format!("Failed to list applied migrations: {}", e)))?
+        .into_iter()
+        .map(|migration| migration.version)
+        .collect();
+
+    let (applied, pending) = migrator
+        .iter()
+        .filter(|migration| !migration.migration_type.is_down_migration())
+        .map(|migration| MigrationInfo {
+            version: migration.version,
+            description: migration.description.to_string(),
+        })
+        .partition(|info| applied_versions.contains(&info.version));
+
+    Ok(MigrationStatus { applied, pending })
+}
@@ -1,5 +1,17 @@
+use async_trait::async_trait;
 use sqlx::{postgres::PgPoolOptions, PgPool};
+use std::time::Duration;
+use crate::config::settings::PostgresSettings;
 use crate::errors::ApiError;
+use crate::health::{CheckResult, HealthCheckable};
+use crate::utils::with_timeout;
+
+/// Thời gian tối đa cho `SELECT 1` của health check
+const HEALTH_CHECK_TIMEOUT: Duration = Duration::from_secs(3);
+
+pub mod migrations;
+pub mod router;
+pub mod timing;
 
 #[cfg(feature = "database-mysql")]
 pub mod mysql;
@@ -7,15 +19,21 @@ pub mod mysql;
 #[cfg(feature = "database-sqlite")]
 pub mod sqlite;
 
+pub use migrations::{MigrationInfo, MigrationStatus};
+pub use router::PoolRouter;
+pub use timing::{QueryTimer, DEFAULT_SLOW_QUERY_THRESHOLD};
+
 #[cfg(feature = "database-mysql")]
-pub use mysql::{MySqlConfig, init_mysql_pool};
+pub use mysql::{MySqlConfig, init_mysql_pool, mysql_migration_status, run_mysql_migrations};
 
 #[cfg(feature = "database-sqlite")]
-pub use sqlite::{SqliteConfig, init_sqlite_pool};
+pub use sqlite::{SqliteConfig, init_sqlite_pool, run_sqlite_migrations, sqlite_migration_status};
 
 /// Database connection manager với connection pooling
+///
+/// Holds the primary pool and any read replicas; see [`PoolRouter`].
 pub struct Database {
-    pool: PgPool,
+    pools: PoolRouter,
 }
 
 impl Database {
@@ -33,35 +51,131 @@ impl Database {
             })?;
 
         tracing::info!("Database connected successfully");
-        Ok(Self { pool })
+        Ok(Self::from_pool(pool))
+    }
+
+    /// Connect with every pool option from `settings`
+    pub async fn from_settings(settings: &PostgresSettings) -> Result<Self, ApiError> {
+        let options = Self::pool_options(settings);
+        tracing::info!(
+            max_connections = options.get_max_connections(),
+            min_connections = options.get_min_connections(),
+            acquire_timeout_secs = options.get_acquire_timeout().as_secs(),
+            idle_timeout_secs = options.get_idle_timeout().map(|d| d.as_secs()),
+            max_lifetime_secs = options.get_max_lifetime().map(|d| d.as_secs()),
+            "Connecting to database..."
+        );
+
+        let primary = options.clone().connect(&settings.url).await.map_err(|e| {
+            tracing::error!("Database connection failed: {}", e);
+            ApiError::database(format!("Database connection failed: {}", e))
+        })?;
+
+        // Replica dùng cùng pool options với primary
+        let mut replicas = Vec::with_capacity(settings.replica_urls.len());
+        for (index, url) in settings.replica_urls.iter().enumerate() {
+            let replica = options.clone().connect(url).await.map_err(|e| {
+                tracing::error!(replica = index, "Replica connection failed: {}", e);
+                ApiError::database(format!("Replica {} connection failed: {}", index, e))
+            })?;
+            replicas.push(replica);
+        }
+
+        tracing::info!(replicas = replicas.len(), "Database connected successfully");
+        Ok(Self {
+            pools: PoolRouter::new(primary, replicas),
+        })
     }
 
-    /// Get connection pool reference
+    /// Pool options from `settings`; an `idle_timeout` or `max_lifetime` of 0
+    /// disables that limit
+    pub fn pool_options(settings: &PostgresSettings) -> PgPoolOptions {
+        let secs = |secs: u64| (secs > 0).then(|| Duration::from_secs(secs));
+
+        PgPoolOptions::new()
+            .max_connections(settings.max_connections)
+            .min_connections(settings.min_connections)
+            .acquire_timeout(Duration::from_secs(settings.connect_timeout))
+            .idle_timeout(secs(settings.idle_timeout))
+            .max_lifetime(secs(settings.max_lifetime))
+    }
+
+    /// Wrap an existing connection pool
+    pub fn from_pool(pool: PgPool) -> Self {
+        Self::from_router(PoolRouter::primary_only(pool))
+    }
+
+    /// Wrap existing primary and replica pools
+    pub fn from_router(pools: PoolRouter) -> Self {
+        Self { pools }
+    }
+
+    /// Get connection pool reference (the primary)
     pub fn pool(&self) -> &PgPool {
-        &self.pool
+        self.pools.write_pool()
+    }
+
+    /// Next replica pool, or the primary without replicas
+    pub fn read_pool(&self) -> &PgPool {
+        self.pools.read_pool()
+    }
+
+    pub fn write_pool(&self) -> &PgPool {
+        self.pools.write_pool()
+    }
+
+    /// Cloneable handle on the primary and replica pools, for repositories
+    pub fn router(&self) -> PoolRouter {
+        self.pools.clone()
     }
 
-    /// Run database migrations
+    /// Run database migrations from `migrations/postgres`
     pub async fn run_migrations(&self) -> Result<(), ApiError> {
         tracing::info!("Running database migrations...");
-        
-        sqlx::migrate!("./migrations")
-            .run(&self.pool)
+
+        migrations::POSTGRES_MIGRATOR
+            .run(self.write_pool())
             .await
-            .map_err(|e| {
-                tracing::error!("Migration failed: {}", e);
-                ApiError::internal(format!("Migration failed: {}", e))
-            })?;
+            .map_err(migrations::migration_error)?;
 
         tracing::info!("Migrations completed successfully");
         Ok(())
     }
 
+    /// Which of the `migrations/postgres` files have been applied
+    pub async fn migration_status(&self) -> Result<MigrationStatus, ApiError> {
+        let mut conn = self
+            .write_pool()
+            .acquire()
+            .await
+            .map_err(|e| ApiError::database(format!("Failed to acquire connection: {}", e)))?;
+        migrations::status(&migrations::POSTGRES_MIGRATOR, &mut *conn).await
+    }
+
     /// Health check - verify database connection
     pub async fn health_check(&self) -> bool {
         sqlx::query("SELECT 1")
-            .execute(&self.pool)
+            .execute(self.write_pool())
             .await
             .is_ok()
     }
 }
+
+#[async_trait]
+impl HealthCheckable for Database {
+    fn name(&self) -> &str {
+        "database"
+    }
+
+    async fn check(&self) -> CheckResult {
+        CheckResult::timed(Duration::from_millis(1000), async {
+            // Pool cạn connection thì query treo tới acquire_timeout
+            with_timeout(HEALTH_CHECK_TIMEOUT, sqlx::query("SELECT 1").execute(self.write_pool()))
+                .await
+                .map_err(|e| format!("Database error: {}", e.message()))?
+                .map(|_| ())
+                .map_err(|e| format!("Database error: {}", e))
+        })
+        .await
+    }
+}
@@ -1,6 +1,7 @@
 use sqlx::mysql::{MySqlPool, MySqlPoolOptions};
 use std::time::Duration;
 use crate::errors::ApiError;
+use super::migrations::{self, MigrationStatus};
 
 /// MySQL database configuration
 #[derive(Debug, Clone)]
@@ -36,3 +37,19 @@ pub async fn init_mysql_pool(config: MySqlConfig) -> Result<MySqlPool, ApiError>
         .map_err(|e| ApiError::database(&format!("Failed to connect to MySQL: {}", e)))
 }
 
+/// Run the `migrations/mysql` migrations
+pub async fn run_mysql_migrations(pool: &MySqlPool) -> Result<(), ApiError> {
+    migrations::MYSQL_MIGRATOR
+        .run(pool)
+        .await
+        .map_err(migrations::migration_error)
+}
+
+/// Which of the `migrations/mysql` files have been applied
+pub async fn mysql_migration_status(pool: &MySqlPool) -> Result<MigrationStatus, ApiError> {
+    let mut conn = pool
+        .acquire()
+        .await
+        .map_err(|e| ApiError::database(format!("Failed to acquire connection: {}", e)))?;
+    migrations::status(&migrations::MYSQL_MIGRATOR, &mut *conn).await
+}
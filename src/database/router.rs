@@ -0,0 +1,46 @@
+use sqlx::PgPool;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+
+/// Primary pool cộng các read replica
+///
+/// Writes always go to the primary; reads rotate round-robin across the
+/// replicas, or use the primary when there are none. Replicas lag behind the
+/// primary, so read-your-own-write paths should use [`write_pool`](Self::write_pool).
+#[derive(Clone)]
+pub struct PoolRouter {
+    primary: PgPool,
+    replicas: Arc<[PgPool]>,
+    next_replica: Arc<AtomicUsize>,
+}
+
+impl PoolRouter {
+    pub fn new(primary: PgPool, replicas: Vec<PgPool>) -> Self {
+        Self {
+            primary,
+            replicas: replicas.into(),
+            next_replica: Arc::new(AtomicUsize::new(0)),
+        }
+    }
+
+    /// Router without replicas: reads and writes both use `primary`
+    pub fn primary_only(primary: PgPool) -> Self {
+        Self::new(primary, Vec::new())
+    }
+
+    pub fn write_pool(&self) -> &PgPool {
+        &self.primary
+    }
+
+    pub fn read_pool(&self) -> &PgPool {
+        if self.replicas.is_empty() {
+            return &self.primary;
+        }
+        let index = self.next_replica.fetch_add(1, Ordering::Relaxed) % self.replicas.len();
+        &self.replicas[index]
+    }
+
+    pub fn replica_count(&self) -> usize {
+        self.replicas.len()
+    }
+}
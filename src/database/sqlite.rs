@@ -1,6 +1,7 @@
 use sqlx::sqlite::{SqlitePool, SqlitePoolOptions};
 use std::time::Duration;
 use crate::errors::ApiError;
+use super::migrations::{self, MigrationStatus};
 
 /// SQLite database configuration
 #[derive(Debug, Clone)]
@@ -30,3 +31,19 @@ pub async fn init_sqlite_pool(config: SqliteConfig) -> Result<SqlitePool, ApiErr
         .map_err(|e| ApiError::database(&format!("Failed to connect to SQLite: {}", e)))
 }
 
+/// Run the `migrations/sqlite` migrations
+pub async fn run_sqlite_migrations(pool: &SqlitePool) -> Result<(), ApiError> {
+    migrations::SQLITE_MIGRATOR
+        .run(pool)
+        .await
+        .map_err(migrations::migration_error)
+}
+
+/// Which of the `migrations/sqlite` files have been applied
+pub async fn sqlite_migration_status(pool: &SqlitePool) -> Result<MigrationStatus, ApiError> {
+    let mut conn = pool
+        .acquire()
+        .await
+        .map_err(|e| ApiError::database(format!("Failed to acquire connection: {}", e)))?;
+    migrations::status(&migrations::SQLITE_MIGRATOR, &mut *conn).await
+}
@@ -0,0 +1,87 @@
+use std::future::Future;
+use std::time::{Duration, Instant};
+
+#[cfg(feature = "observability-metrics")]
+use std::sync::Arc;
+
+use crate::config::settings::PostgresSettings;
+#[cfg(feature = "observability-metrics")]
+use crate::metrics::MetricsCollector;
+
+/// Ngưỡng mặc định để một query bị coi là chậm
+pub const DEFAULT_SLOW_QUERY_THRESHOLD: Duration = Duration::from_millis(500);
+
+/// Đo thời gian query cho repository và event store
+///
+/// Every query is recorded under its operation name in the
+/// `db_query_duration_seconds` histogram (when metrics are attached), and
+/// queries taking at least `slow_threshold` are logged at WARN. Only the
+/// operation name is logged, never the bound values.
+#[derive(Clone)]
+pub struct QueryTimer {
+    slow_threshold: Duration,
+    #[cfg(feature = "observability-metrics")]
+    metrics: Option<Arc<MetricsCollector>>,
+}
+
+impl Default for QueryTimer {
+    fn default() -> Self {
+        Self {
+            slow_threshold: DEFAULT_SLOW_QUERY_THRESHOLD,
+            #[cfg(feature = "observability-metrics")]
+            metrics: None,
+        }
+    }
+}
+
+impl QueryTimer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Threshold from `DATABASE_SLOW_QUERY_MS`
+    pub fn from_settings(settings: &PostgresSettings) -> Self {
+        Self::new().with_slow_threshold(Duration::from_millis(settings.slow_query_ms))
+    }
+
+    pub fn with_slow_threshold(mut self, threshold: Duration) -> Self {
+        self.slow_threshold = threshold;
+        self
+    }
+
+    #[cfg(feature = "observability-metrics")]
+    pub fn with_metrics(mut self, metrics: Arc<MetricsCollector>) -> Self {
+        self.metrics = Some(metrics);
+        self
+    }
+
+    pub fn slow_threshold(&self) -> Duration {
+        self.slow_threshold
+    }
+
+    /// Await `query` and record how long it took under `operation`
+    /// (e.g. `"users.find_by_id"`), whether it succeeded or not
+    pub async fn time<F: Future>(&self, operation: &'static str, query: F) -> F::Output {
+        let started = Instant::now();
+        let output = query.await;
+        self.record(operation, started.elapsed());
+        output
+    }
+
+    /// Record an already measured query
+    pub fn record(&self, operation: &str, elapsed: Duration) {
+        #[cfg(feature = "observability-metrics")]
+        if let Some(metrics) = &self.metrics {
+            metrics.record_db_query(operation, elapsed);
+        }
+
+        if elapsed >= self.slow_threshold {
+            tracing::warn!(
+                operation,
+                duration_ms = elapsed.as_millis() as u64,
+                threshold_ms = self.slow_threshold.as_millis() as u64,
+                "Slow query"
+            );
+        }
+    }
+}
@@ -1,10 +1,43 @@
 use actix_web::{error::ResponseError, http::StatusCode, HttpResponse};
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 use std::fmt;
+use std::sync::atomic::{AtomicBool, AtomicU8, Ordering};
 use thiserror::Error;
 
+/// Base of the RFC 7807 `type` URI; the numeric `ErrorCode` is appended
+pub const PROBLEM_TYPE_BASE: &str = "/errors";
+
+/// Body format used for error responses
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ErrorFormat {
+    /// The `ErrorResponse` envelope (default, backward compatible)
+    #[default]
+    Envelope,
+    /// RFC 7807 `application/problem+json`
+    ProblemJson,
+}
+
+impl std::str::FromStr for ErrorFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "envelope" | "default" => Ok(Self::Envelope),
+            "problem_json" | "problem+json" | "problem" => Ok(Self::ProblemJson),
+            other => Err(format!("Unknown error format: {}", other)),
+        }
+    }
+}
+
+static ERROR_FORMAT: AtomicU8 = AtomicU8::new(0);
+
+/// Có đưa nội dung lỗi nội bộ (source chain) vào response hay không
+static EXPOSE_ERROR_DETAILS: AtomicBool = AtomicBool::new(false);
+
 /// Error codes for API responses
-#[derive(Debug, Clone, Copy, Serialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize)]
+#[cfg_attr(feature = "docs", derive(utoipa::ToSchema))]
 pub enum ErrorCode {
     // Client Errors (4xx)
     BadRequest = 40000,
@@ -13,8 +46,10 @@ pub enum ErrorCode {
     Forbidden = 40300,
     NotFound = 40400,
     MethodNotAllowed = 40500,
+    NotAcceptable = 40600,
     Conflict = 40900,
     Gone = 41000,
+    PayloadTooLarge = 41300,
     UnprocessableEntity = 42200,
     TooManyRequests = 42900,
 
@@ -64,6 +99,9 @@ pub enum ApiError {
         source: Option<Box<dyn std::error::Error + Send + Sync>>,
     },
 
+    #[error("Payment required: {message}")]
+    PaymentRequired { message: String },
+
     #[error("Forbidden: {message}")]
     Forbidden {
         message: String,
@@ -77,12 +115,30 @@ pub enum ApiError {
         resource: Option<String>,
     },
 
+    #[error("Method not allowed: {message}")]
+    MethodNotAllowed { message: String },
+
+    #[error("Not acceptable: {message}")]
+    NotAcceptable { message: String },
+
     #[error("Conflict: {message}")]
     Conflict {
         message: String,
         field: Option<String>,
     },
 
+    #[error("Gone: {message}")]
+    Gone {
+        message: String,
+        resource: Option<String>,
+    },
+
+    #[error("Payload too large: {message}")]
+    PayloadTooLarge {
+        message: String,
+        limit: Option<usize>,
+    },
+
     #[error("Validation error: {message}")]
     ValidationError {
         message: String,
@@ -107,6 +163,16 @@ pub enum ApiError {
         source: Option<Box<dyn std::error::Error + Send + Sync>>,
     },
 
+    #[error("Not implemented: {message}")]
+    NotImplemented { message: String },
+
+    #[error("Bad gateway: {message}")]
+    BadGateway {
+        message: String,
+        #[source]
+        source: Option<Box<dyn std::error::Error + Send + Sync>>,
+    },
+
     #[error("Service unavailable: {message}")]
     ServiceUnavailable {
         message: String,
@@ -207,10 +273,31 @@ pub enum ApiError {
         message: String,
         field: Option<String>,
     },
+
+    // ============================================================================
+    // Resource Errors
+    // ============================================================================
+    #[error("Resource exhausted: {message}")]
+    ResourceExhausted {
+        message: String,
+        resource: Option<String>,
+    },
 }
 
 /// Enhanced error response with detailed information
+///
+/// This is the standard error envelope returned by every endpoint.
 #[derive(Serialize, Debug)]
+#[cfg_attr(feature = "docs", derive(utoipa::ToSchema))]
+#[cfg_attr(feature = "docs", schema(example = json!({
+    "success": false,
+    "status_code": 404,
+    "error_code": "NotFound",
+    "message": "User with id 42 not found",
+    "resource": "user",
+    "request_id": "550e8400-e29b-41d4-a716-446655440000",
+    "timestamp": "2024-01-01T00:00:00Z"
+})))]
 pub struct ErrorResponse {
     /// Always false for errors
     pub success: bool,
@@ -232,6 +319,11 @@ pub struct ErrorResponse {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub field: Option<String>,
 
+    /// Full `source()` chain, outermost first (only when internal details are
+    /// exposed)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub causes: Option<Vec<String>>,
+
     /// Optional resource identifier (for not found errors)
     #[serde(skip_serializing_if = "Option::is_none")]
     pub resource: Option<String>,
@@ -248,18 +340,100 @@ pub struct ErrorResponse {
     pub timestamp: String,
 }
 
+/// RFC 7807 problem details
+#[derive(Serialize, Debug)]
+pub struct ProblemDetails {
+    /// `{PROBLEM_TYPE_BASE}/{error_code}`
+    #[serde(rename = "type")]
+    pub type_uri: String,
+
+    /// Short summary of the problem type (HTTP reason phrase)
+    pub title: String,
+
+    pub status: u16,
+
+    /// Human-readable explanation of this occurrence
+    pub detail: String,
+
+    /// `urn:request-id:{id}` when the request has an ID
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub instance: Option<String>,
+
+    /// Extension member: numeric `ErrorCode`
+    pub code: u32,
+
+    /// Extension member: per-field errors
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub errors: Option<Vec<ProblemFieldError>>,
+
+    /// Extension member: seconds until retry is allowed
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub retry_after: Option<u64>,
+
+    /// Extension member: `source()` chain (only when internal details are
+    /// exposed)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub causes: Option<Vec<String>>,
+}
+
+#[derive(Serialize, Debug)]
+pub struct ProblemFieldError {
+    pub field: String,
+    pub message: String,
+}
+
 impl ApiError {
+    /// Choose the body format for all error responses (set once at startup)
+    pub fn set_response_format(format: ErrorFormat) {
+        ERROR_FORMAT.store(format as u8, Ordering::Relaxed);
+    }
+
+    pub fn response_format() -> ErrorFormat {
+        match ERROR_FORMAT.load(Ordering::Relaxed) {
+            1 => ErrorFormat::ProblemJson,
+            _ => ErrorFormat::Envelope,
+        }
+    }
+
+    /// Include wrapped errors in responses (set once at startup; only for
+    /// development, since they can leak internals such as SQL or hostnames)
+    pub fn set_expose_details(expose: bool) {
+        EXPOSE_ERROR_DETAILS.store(expose, Ordering::Relaxed);
+    }
+
+    pub fn exposes_details() -> bool {
+        EXPOSE_ERROR_DETAILS.load(Ordering::Relaxed)
+    }
+
+    /// Messages of every wrapped error, outermost first
+    pub fn source_chain(&self) -> Vec<String> {
+        let mut chain = Vec::new();
+        let mut current = std::error::Error::source(self);
+        while let Some(err) = current {
+            chain.push(err.to_string());
+            current = err.source();
+        }
+        chain
+    }
+
     /// Get the error message
     pub fn message(&self) -> String {
         match self {
             ApiError::BadRequest { message, .. } => message.clone(),
             ApiError::Unauthorized { message, .. } => message.clone(),
+            ApiError::PaymentRequired { message } => message.clone(),
             ApiError::Forbidden { message, .. } => message.clone(),
             ApiError::NotFound { message, .. } => message.clone(),
+            ApiError::MethodNotAllowed { message } => message.clone(),
+            ApiError::NotAcceptable { message } => message.clone(),
             ApiError::Conflict { message, .. } => message.clone(),
+            ApiError::Gone { message, .. } => message.clone(),
+            ApiError::PayloadTooLarge { message, .. } => message.clone(),
             ApiError::ValidationError { message, .. } => message.clone(),
             ApiError::RateLimitExceeded { message, .. } => message.clone(),
             ApiError::InternalError { message, .. } => message.clone(),
+            ApiError::NotImplemented { message } => message.clone(),
+            ApiError::BadGateway { message, .. } => message.clone(),
             ApiError::ServiceUnavailable { message, .. } => message.clone(),
             ApiError::GatewayTimeout { message, .. } => message.clone(),
             ApiError::DatabaseError { message, .. } => message.clone(),
@@ -273,6 +447,7 @@ impl ApiError {
             ApiError::ExternalServiceError { message, .. } => message.clone(),
             ApiError::ConfigurationError { message, .. } => message.clone(),
             ApiError::DataIntegrityError { message, .. } => message.clone(),
+            ApiError::ResourceExhausted { message, .. } => message.clone(),
         }
     }
 
@@ -282,14 +457,21 @@ impl ApiError {
             // Client errors
             ApiError::BadRequest { .. } => ErrorCode::BadRequest,
             ApiError::Unauthorized { .. } => ErrorCode::Unauthorized,
+            ApiError::PaymentRequired { .. } => ErrorCode::PaymentRequired,
             ApiError::Forbidden { .. } => ErrorCode::Forbidden,
             ApiError::NotFound { .. } => ErrorCode::NotFound,
+            ApiError::MethodNotAllowed { .. } => ErrorCode::MethodNotAllowed,
+            ApiError::NotAcceptable { .. } => ErrorCode::NotAcceptable,
             ApiError::Conflict { .. } => ErrorCode::Conflict,
+            ApiError::Gone { .. } => ErrorCode::Gone,
+            ApiError::PayloadTooLarge { .. } => ErrorCode::PayloadTooLarge,
             ApiError::ValidationError { .. } => ErrorCode::ValidationError,
             ApiError::RateLimitExceeded { .. } => ErrorCode::RateLimitError,
 
             // Server errors
             ApiError::InternalError { .. } => ErrorCode::InternalServerError,
+            ApiError::NotImplemented { .. } => ErrorCode::NotImplemented,
+            ApiError::BadGateway { .. } => ErrorCode::BadGateway,
             ApiError::ServiceUnavailable { .. } => ErrorCode::ServiceUnavailable,
             ApiError::GatewayTimeout { .. } => ErrorCode::GatewayTimeout,
 
@@ -315,11 +497,22 @@ impl ApiError {
 
             // Data integrity errors
             ApiError::DataIntegrityError { .. } => ErrorCode::DataIntegrityError,
+
+            // Resource errors
+            ApiError::ResourceExhausted { .. } => ErrorCode::ResourceExhausted,
         }
     }
 
-    /// Create an error response with all details
-    fn to_error_response(&self) -> ErrorResponse {
+    /// Create an error response, exposing wrapped errors according to
+    /// [`set_expose_details`](Self::set_expose_details)
+    pub fn to_error_response(&self) -> ErrorResponse {
+        self.to_error_response_with(Self::exposes_details())
+    }
+
+    /// Create an error response; with `expose_details` false anything derived
+    /// from the wrapped `source` is left out, as are internals such as the
+    /// failed query, operation or configuration key
+    pub fn to_error_response_with(&self, expose_details: bool) -> ErrorResponse {
         let status_code = self.status_code();
         let error_code = self.error_code();
         let timestamp = chrono::Utc::now().to_rfc3339();
@@ -331,15 +524,30 @@ impl ApiError {
             ApiError::Unauthorized { message, source } => {
                 (message.clone(), source.as_ref().map(|e| e.to_string()), None, None, None)
             }
+            ApiError::PaymentRequired { message } => {
+                (message.clone(), None, None, None, None)
+            }
             ApiError::Forbidden { message, source } => {
                 (message.clone(), source.as_ref().map(|e| e.to_string()), None, None, None)
             }
             ApiError::NotFound { message, resource } => {
                 (message.clone(), None, None, resource.clone(), None)
             }
+            ApiError::MethodNotAllowed { message } => {
+                (message.clone(), None, None, None, None)
+            }
+            ApiError::NotAcceptable { message } => {
+                (message.clone(), None, None, None, None)
+            }
             ApiError::Conflict { message, field } => {
                 (message.clone(), None, field.clone(), None, None)
             }
+            ApiError::Gone { message, resource } => {
+                (message.clone(), None, None, resource.clone(), None)
+            }
+            ApiError::PayloadTooLarge { message, limit } => {
+                (message.clone(), limit.map(|l| format!("Limit is {} bytes", l)), None, None, None)
+            }
             ApiError::ValidationError { message, field, source } => {
                 (message.clone(), source.as_ref().map(|e| e.to_string()), field.clone(), None, None)
             }
@@ -349,6 +557,12 @@ impl ApiError {
             ApiError::InternalError { message, source } => {
                 (message.clone(), source.as_ref().map(|e| e.to_string()), None, None, None)
             }
+            ApiError::NotImplemented { message } => {
+                (message.clone(), None, None, None, None)
+            }
+            ApiError::BadGateway { message, source } => {
+                (message.clone(), source.as_ref().map(|e| e.to_string()), None, None, None)
+            }
             ApiError::ServiceUnavailable { message, retry_after } => {
                 (message.clone(), None, None, None, *retry_after)
             }
@@ -356,16 +570,20 @@ impl ApiError {
                 (message.clone(), None, None, None, None)
             }
             ApiError::DatabaseError { message, operation, source } => {
-                (message.clone(), source.as_ref().map(|e| e.to_string()), operation.clone(), None, None)
+                let operation = operation.clone().filter(|_| expose_details);
+                (message.clone(), source.as_ref().map(|e| e.to_string()), operation, None, None)
             }
             ApiError::DatabaseConnectionError { message, source } => {
                 (message.clone(), source.as_ref().map(|e| e.to_string()), None, None, None)
             }
             ApiError::DatabaseQueryError { message, query, source } => {
-                (message.clone(), source.as_ref().map(|e| e.to_string()), query.clone(), None, None)
+                // Câu SQL lộ schema nên chỉ hiện khi bật details
+                let query = query.clone().filter(|_| expose_details);
+                (message.clone(), source.as_ref().map(|e| e.to_string()), query, None, None)
             }
             ApiError::CacheError { message, operation, source } => {
-                (message.clone(), source.as_ref().map(|e| e.to_string()), operation.clone(), None, None)
+                let operation = operation.clone().filter(|_| expose_details);
+                (message.clone(), source.as_ref().map(|e| e.to_string()), operation, None, None)
             }
             ApiError::AuthenticationError { message, source } => {
                 (message.clone(), source.as_ref().map(|e| e.to_string()), None, None, None)
@@ -383,13 +601,32 @@ impl ApiError {
                 (message.clone(), source.as_ref().map(|e| format!("{}: {}", service, e)), None, None, None)
             }
             ApiError::ConfigurationError { message, key } => {
-                (message.clone(), key.clone(), None, None, None)
+                (message.clone(), key.clone().filter(|_| expose_details), None, None, None)
             }
             ApiError::DataIntegrityError { message, field } => {
                 (message.clone(), None, field.clone(), None, None)
             }
+            ApiError::ResourceExhausted { message, resource } => {
+                (message.clone(), None, None, resource.clone(), None)
+            }
         };
 
+        // Với variant có source, `details` lấy từ source nên cũng phải ẩn
+        let chain = self.source_chain();
+        let (details, causes) = if chain.is_empty() {
+            (details, None)
+        } else if expose_details {
+            (details, Some(chain))
+        } else {
+            (None, None)
+        };
+
+        let message = Self::message_catalog().localize(
+            crate::middleware::current_locale().as_deref(),
+            error_code,
+            message,
+        );
+
         ErrorResponse {
             success: false,
             status_code: status_code.as_u16(),
@@ -397,28 +634,61 @@ impl ApiError {
             message,
             details,
             field,
+            causes,
             resource,
             retry_after,
-            request_id: None, // Can be set by middleware
+            request_id: crate::middleware::current_request_id(),
             timestamp,
         }
     }
 }
 
+impl ApiError {
+    /// Build the RFC 7807 representation from the same details as the envelope
+    pub fn to_problem_details(&self) -> ProblemDetails {
+        let response = self.to_error_response();
+        let status = self.status_code();
+
+        ProblemDetails {
+            type_uri: format!("{}/{}", PROBLEM_TYPE_BASE, response.error_code),
+            title: status.canonical_reason().unwrap_or("Error").to_string(),
+            status: response.status_code,
+            detail: response.message.clone(),
+            instance: response.request_id.map(|id| format!("urn:request-id:{}", id)),
+            code: response.error_code as u32,
+            errors: response.field.map(|field| {
+                vec![ProblemFieldError {
+                    field,
+                    message: response.message,
+                }]
+            }),
+            retry_after: response.retry_after,
+            causes: response.causes,
+        }
+    }
+}
+
 impl ResponseError for ApiError {
     fn status_code(&self) -> StatusCode {
         match self {
             // Client errors
             ApiError::BadRequest { .. } => StatusCode::BAD_REQUEST,
             ApiError::Unauthorized { .. } => StatusCode::UNAUTHORIZED,
+            ApiError::PaymentRequired { .. } => StatusCode::PAYMENT_REQUIRED,
             ApiError::Forbidden { .. } => StatusCode::FORBIDDEN,
             ApiError::NotFound { .. } => StatusCode::NOT_FOUND,
+            ApiError::MethodNotAllowed { .. } => StatusCode::METHOD_NOT_ALLOWED,
+            ApiError::NotAcceptable { .. } => StatusCode::NOT_ACCEPTABLE,
             ApiError::Conflict { .. } => StatusCode::CONFLICT,
+            ApiError::Gone { .. } => StatusCode::GONE,
+            ApiError::PayloadTooLarge { .. } => StatusCode::PAYLOAD_TOO_LARGE,
             ApiError::ValidationError { .. } => StatusCode::UNPROCESSABLE_ENTITY,
             ApiError::RateLimitExceeded { .. } => StatusCode::TOO_MANY_REQUESTS,
 
             // Server errors
             ApiError::InternalError { .. } => StatusCode::INTERNAL_SERVER_ERROR,
+            ApiError::NotImplemented { .. } => StatusCode::NOT_IMPLEMENTED,
+            ApiError::BadGateway { .. } => StatusCode::BAD_GATEWAY,
             ApiError::ServiceUnavailable { .. } => StatusCode::SERVICE_UNAVAILABLE,
             ApiError::GatewayTimeout { .. } => StatusCode::GATEWAY_TIMEOUT,
 
@@ -444,6 +714,9 @@ impl ResponseError for ApiError {
 
             // Data integrity errors
             ApiError::DataIntegrityError { .. } => StatusCode::UNPROCESSABLE_ENTITY,
+
+            // Resource errors
+            ApiError::ResourceExhausted { .. } => StatusCode::TOO_MANY_REQUESTS,
         }
     }
 
@@ -453,12 +726,27 @@ impl ResponseError for ApiError {
 
         let mut response = HttpResponse::build(status_code);
 
+        // Middleware (e.g. metrics) đọc error code từ response extensions
+        response.extensions_mut().insert(error_response.error_code);
+
         // Add retry-after header if present
         if let Some(retry_after) = error_response.retry_after {
             response.insert_header(("Retry-After", retry_after.to_string()));
         }
 
-        response.json(error_response)
+        // Ngôn ngữ của message đã chọn, để replay (idempotency) vẫn đúng nhãn
+        let language = Self::message_catalog().language(
+            crate::middleware::current_locale().as_deref(),
+            error_response.error_code,
+        );
+        response.insert_header(("Content-Language", language));
+
+        match Self::response_format() {
+            ErrorFormat::Envelope => response.json(error_response),
+            ErrorFormat::ProblemJson => response
+                .content_type("application/problem+json")
+                .json(self.to_problem_details()),
+        }
     }
 }
 
@@ -486,6 +774,13 @@ impl ApiError {
         }
     }
 
+    /// Create a payment required error (plan limit, unpaid subscription)
+    pub fn payment_required(message: impl Into<String>) -> Self {
+        Self::PaymentRequired {
+            message: message.into(),
+        }
+    }
+
     /// Create a simple forbidden error
     pub fn forbidden(message: impl Into<String>) -> Self {
         Self::Forbidden {
@@ -510,6 +805,28 @@ impl ApiError {
         }
     }
 
+    /// Create a method not allowed error
+    pub fn method_not_allowed(message: impl Into<String>) -> Self {
+        Self::MethodNotAllowed {
+            message: message.into(),
+        }
+    }
+
+    /// Create a not acceptable error (no representation matches `Accept`)
+    pub fn not_acceptable(message: impl Into<String>) -> Self {
+        Self::NotAcceptable {
+            message: message.into(),
+        }
+    }
+
+    /// Create a gone error for a resource that was permanently removed
+    pub fn gone(message: impl Into<String>, resource: impl Into<String>) -> Self {
+        Self::Gone {
+            message: message.into(),
+            resource: Some(resource.into()),
+        }
+    }
+
     /// Create a simple validation error
     pub fn validation(message: impl Into<String>) -> Self {
         Self::ValidationError {
@@ -536,6 +853,36 @@ impl ApiError {
         }
     }
 
+    /// Create a not implemented error
+    pub fn not_implemented(message: impl Into<String>) -> Self {
+        Self::NotImplemented {
+            message: message.into(),
+        }
+    }
+
+    /// Create a bad gateway error (invalid response from an upstream)
+    pub fn bad_gateway(message: impl Into<String>) -> Self {
+        Self::BadGateway {
+            message: message.into(),
+            source: None,
+        }
+    }
+
+    /// Create a payload too large error for a body limit in bytes
+    pub fn payload_too_large(limit: usize) -> Self {
+        Self::PayloadTooLarge {
+            message: "Request body is too large".to_string(),
+            limit: Some(limit),
+        }
+    }
+
+    /// Create a gateway timeout error
+    pub fn gateway_timeout(message: impl Into<String>) -> Self {
+        Self::GatewayTimeout {
+            message: message.into(),
+        }
+    }
+
     /// Create a database error
     pub fn database(message: impl Into<String>) -> Self {
         Self::DatabaseError {
@@ -578,6 +925,14 @@ impl ApiError {
         }
     }
 
+    /// Create a resource exhausted error (pool, quota or capacity used up)
+    pub fn resource_exhausted(message: impl Into<String>, resource: impl Into<String>) -> Self {
+        Self::ResourceExhausted {
+            message: message.into(),
+            resource: Some(resource.into()),
+        }
+    }
+
     /// Create a configuration error
     pub fn configuration(message: impl Into<String>) -> Self {
         Self::ConfigurationError {
@@ -634,6 +989,22 @@ impl From<std::env::VarError> for ApiError {
 #[cfg(feature = "database-postgres")]
 impl From<sqlx::Error> for ApiError {
     fn from(err: sqlx::Error) -> Self {
+        // 23505 = unique_violation
+        if let Some(db_err) = err.as_database_error() {
+            if db_err.code().as_deref() == Some("23505") {
+                let field = db_err
+                    .constraint()
+                    .and_then(|constraint| unique_violation_field(constraint, db_err.table()));
+                return ApiError::Conflict {
+                    message: match &field {
+                        Some(field) => format!("{} already exists", capitalize(field)),
+                        None => "Record already exists".to_string(),
+                    },
+                    field,
+                };
+            }
+        }
+
         match err {
             sqlx::Error::RowNotFound => ApiError::NotFound {
                 message: "Record not found in database".to_string(),
@@ -652,6 +1023,27 @@ impl From<sqlx::Error> for ApiError {
     }
 }
 
+/// Column guarded by a unique constraint, from Postgres' default
+/// `{table}_{column}_key` naming (e.g. `users_email_key` -> `email`)
+#[cfg(feature = "database-postgres")]
+fn unique_violation_field(constraint: &str, table: Option<&str>) -> Option<String> {
+    let column = constraint.strip_suffix("_key")?;
+    let column = match table {
+        Some(table) => column.strip_prefix(table)?.strip_prefix('_')?,
+        None => column.split_once('_')?.1,
+    };
+    (!column.is_empty()).then(|| column.to_string())
+}
+
+#[cfg(feature = "database-postgres")]
+fn capitalize(s: &str) -> String {
+    let mut chars = s.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
+
 #[cfg(feature = "cache-redis")]
 impl From<redis::RedisError> for ApiError {
     fn from(err: redis::RedisError) -> Self {
@@ -733,6 +1125,156 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_new_variant_status_codes() {
+        assert_eq!(
+            ApiError::payment_required("test").status_code(),
+            StatusCode::PAYMENT_REQUIRED
+        );
+        assert_eq!(
+            ApiError::method_not_allowed("test").status_code(),
+            StatusCode::METHOD_NOT_ALLOWED
+        );
+        assert_eq!(
+            ApiError::gone("test", "user").status_code(),
+            StatusCode::GONE
+        );
+        assert_eq!(
+            ApiError::not_implemented("test").status_code(),
+            StatusCode::NOT_IMPLEMENTED
+        );
+        assert_eq!(
+            ApiError::bad_gateway("test").status_code(),
+            StatusCode::BAD_GATEWAY
+        );
+        assert_eq!(
+            ApiError::resource_exhausted("test", "connections").status_code(),
+            StatusCode::TOO_MANY_REQUESTS
+        );
+    }
+
+    #[test]
+    fn test_new_variant_error_codes() {
+        assert_eq!(ApiError::payment_required("test").error_code() as u32, 40200);
+        assert_eq!(ApiError::method_not_allowed("test").error_code() as u32, 40500);
+        assert_eq!(ApiError::not_acceptable("test").error_code() as u32, 40600);
+        assert_eq!(ApiError::gone("test", "user").error_code() as u32, 41000);
+        assert_eq!(ApiError::not_implemented("test").error_code() as u32, 50100);
+        assert_eq!(ApiError::bad_gateway("test").error_code() as u32, 50200);
+        assert_eq!(ApiError::resource_exhausted("test", "connections").error_code() as u32, 60900);
+
+        let response = ApiError::gone("User was deleted", "user").to_error_response();
+        assert_eq!(response.status_code, 410);
+        assert_eq!(response.resource, Some("user".to_string()));
+    }
+
+    #[derive(Debug)]
+    struct Wrapped {
+        message: &'static str,
+        source: Option<Box<Wrapped>>,
+    }
+
+    impl fmt::Display for Wrapped {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            f.write_str(self.message)
+        }
+    }
+
+    impl std::error::Error for Wrapped {
+        fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+            self.source.as_deref().map(|e| e as _)
+        }
+    }
+
+    fn database_error() -> ApiError {
+        ApiError::DatabaseError {
+            message: "Failed to load user".to_string(),
+            operation: None,
+            source: Some(Box::new(Wrapped {
+                message: "query failed",
+                source: Some(Box::new(Wrapped {
+                    message: "connection refused (db.internal:5432)",
+                    source: None,
+                })),
+            })),
+        }
+    }
+
+    #[test]
+    fn test_source_chain_exposed_in_development() {
+        let response = database_error().to_error_response_with(true);
+
+        assert_eq!(response.details, Some("query failed".to_string()));
+        assert_eq!(
+            response.causes,
+            Some(vec![
+                "query failed".to_string(),
+                "connection refused (db.internal:5432)".to_string(),
+            ])
+        );
+    }
+
+    #[test]
+    fn test_source_chain_hidden_in_production() {
+        let response = database_error().to_error_response_with(false);
+        assert_eq!(response.message, "Failed to load user");
+        assert!(response.details.is_none());
+        assert!(response.causes.is_none());
+
+        let body = serde_json::to_value(&response).unwrap();
+        assert!(!body.to_string().contains("db.internal"));
+
+        // Details không đến từ source vẫn giữ nguyên
+        let response = ApiError::payload_too_large(64).to_error_response_with(false);
+        assert_eq!(response.details, Some("Limit is 64 bytes".to_string()));
+    }
+
+    #[test]
+    fn test_query_hidden_in_production() {
+        let err = ApiError::DatabaseQueryError {
+            message: "Query failed".to_string(),
+            query: Some("SELECT password_hash FROM users".to_string()),
+            source: None,
+        };
+        assert!(err.to_error_response_with(false).field.is_none());
+        assert_eq!(
+            err.to_error_response_with(true).field.as_deref(),
+            Some("SELECT password_hash FROM users")
+        );
+    }
+
+    #[test]
+    fn test_database_operation_hidden_in_production() {
+        let err = ApiError::DatabaseError {
+            message: "Database error".to_string(),
+            operation: Some("users.update".to_string()),
+            source: None,
+        };
+        assert!(err.to_error_response_with(false).field.is_none());
+        assert_eq!(err.to_error_response_with(true).field.as_deref(), Some("users.update"));
+    }
+
+    #[test]
+    fn test_cache_operation_hidden_in_production() {
+        let err = ApiError::CacheError {
+            message: "Cache error".to_string(),
+            operation: Some("GET session:abc".to_string()),
+            source: None,
+        };
+        assert!(err.to_error_response_with(false).field.is_none());
+        assert_eq!(err.to_error_response_with(true).field.as_deref(), Some("GET session:abc"));
+    }
+
+    #[test]
+    fn test_configuration_key_hidden_in_production() {
+        let err = ApiError::ConfigurationError {
+            message: "Invalid configuration".to_string(),
+            key: Some("DATABASE_URL".to_string()),
+        };
+        assert!(err.to_error_response_with(false).details.is_none());
+        assert_eq!(err.to_error_response_with(true).details.as_deref(), Some("DATABASE_URL"));
+    }
+
     #[test]
     fn test_error_response_structure() {
         let err = ApiError::validation_field("Invalid email format", "email");
@@ -744,4 +1286,49 @@ mod tests {
         assert_eq!(response.message, "Invalid email format");
         assert_eq!(response.field, Some("email".to_string()));
     }
+
+    #[test]
+    fn test_envelope_serialization() {
+        let err = ApiError::validation_field("Invalid email format", "email");
+        let body = serde_json::to_value(err.to_error_response()).unwrap();
+
+        assert_eq!(body["success"], false);
+        assert_eq!(body["status_code"], 422);
+        assert_eq!(body["error_code"], "ValidationError");
+        assert_eq!(body["field"], "email");
+        assert!(body.get("type").is_none());
+    }
+
+    #[test]
+    fn test_problem_details_serialization() {
+        let err = ApiError::validation_field("Invalid email format", "email");
+        let body = serde_json::to_value(err.to_problem_details()).unwrap();
+
+        assert_eq!(body["type"], "/errors/60000");
+        assert_eq!(body["title"], "Unprocessable Entity");
+        assert_eq!(body["status"], 422);
+        assert_eq!(body["detail"], "Invalid email format");
+        assert_eq!(body["code"], 60000);
+        assert_eq!(body["errors"][0]["field"], "email");
+        assert_eq!(body["errors"][0]["message"], "Invalid email format");
+        assert!(body.get("success").is_none());
+    }
+
+    #[test]
+    fn test_error_format_parsing() {
+        assert_eq!("problem+json".parse::<ErrorFormat>().unwrap(), ErrorFormat::ProblemJson);
+        assert_eq!("envelope".parse::<ErrorFormat>().unwrap(), ErrorFormat::Envelope);
+        assert!("xml".parse::<ErrorFormat>().is_err());
+        assert_eq!(ApiError::response_format(), ErrorFormat::Envelope);
+    }
+
+    #[cfg(feature = "database-postgres")]
+    #[test]
+    fn test_unique_violation_field() {
+        assert_eq!(unique_violation_field("users_email_key", Some("users")), Some("email".to_string()));
+        assert_eq!(unique_violation_field("api_keys_key_hash_key", Some("api_keys")), Some("key_hash".to_string()));
+        assert_eq!(unique_violation_field("users_email_key", None), Some("email".to_string()));
+        assert_eq!(unique_violation_field("users_pkey", Some("users")), None);
+        assert_eq!(unique_violation_field("custom_unique_idx", Some("users")), None);
+    }
 }
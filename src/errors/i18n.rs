@@ -0,0 +1,131 @@
+use std::collections::HashMap;
+use std::sync::OnceLock;
+
+use super::api_error::{ApiError, ErrorCode};
+
+/// Ngôn ngữ của message viết trong code
+pub const SOURCE_LOCALE: &str = "en";
+
+static MESSAGE_CATALOG: OnceLock<MessageCatalog> = OnceLock::new();
+
+/// Localized `ErrorResponse.message` per `ErrorCode`
+///
+/// Messages written in code are English and specific ("User with id 42 not
+/// found"), so English clients and locales without an entry keep them; other
+/// locales get the catalog's generic text for the error code. `error_code`,
+/// `field` and the rest of the response never change.
+#[derive(Debug, Clone, Default)]
+pub struct MessageCatalog {
+    messages: HashMap<String, HashMap<ErrorCode, String>>,
+}
+
+impl MessageCatalog {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// English (from code) and Vietnamese
+    pub fn builtin() -> Self {
+        VIETNAMESE.iter().fold(Self::new(), |catalog, (code, message)| {
+            catalog.with_message("vi", *code, *message)
+        })
+    }
+
+    /// Add or replace the message for `code` in `locale` (e.g. `vi`, `pt-BR`)
+    pub fn with_message(
+        mut self,
+        locale: &str,
+        code: ErrorCode,
+        message: impl Into<String>,
+    ) -> Self {
+        self.messages
+            .entry(locale.to_ascii_lowercase())
+            .or_default()
+            .insert(code, message.into());
+        self
+    }
+
+    /// Exact tag first, then its primary language (`vi-VN` → `vi`)
+    pub fn message(&self, locale: &str, code: ErrorCode) -> Option<&str> {
+        self.entry(locale, code).map(|(_, message)| message)
+    }
+
+    /// Language of the message [`localize`](Self::localize) picks, for
+    /// `Content-Language`
+    pub fn language(&self, locale: Option<&str>, code: ErrorCode) -> &str {
+        match locale {
+            Some(locale) if !is_source_locale(locale) => {
+                self.entry(locale, code).map(|(tag, _)| tag).unwrap_or(SOURCE_LOCALE)
+            }
+            _ => SOURCE_LOCALE,
+        }
+    }
+
+    /// `message` in `locale`, or unchanged for English, no locale or no entry
+    pub fn localize(&self, locale: Option<&str>, code: ErrorCode, message: String) -> String {
+        match locale {
+            Some(locale) if !is_source_locale(locale) => {
+                self.message(locale, code).map(String::from).unwrap_or(message)
+            }
+            _ => message,
+        }
+    }
+
+    /// Matching catalog tag and its message for `code`
+    fn entry(&self, locale: &str, code: ErrorCode) -> Option<(&str, &str)> {
+        let locale = locale.to_ascii_lowercase();
+        let primary = locale.split(['-', '_']).next().unwrap_or_default();
+
+        [locale.as_str(), primary].into_iter().find_map(|tag| {
+            let (tag, messages) = self.messages.get_key_value(tag)?;
+            Some((tag.as_str(), messages.get(&code)?.as_str()))
+        })
+    }
+}
+
+fn is_source_locale(locale: &str) -> bool {
+    let primary = locale.split(['-', '_']).next().unwrap_or_default();
+    primary.eq_ignore_ascii_case(SOURCE_LOCALE)
+}
+
+impl ApiError {
+    /// Catalog used for error responses (set once at startup; later calls are
+    /// ignored). Defaults to [`MessageCatalog::builtin`].
+    pub fn set_message_catalog(catalog: MessageCatalog) {
+        let _ = MESSAGE_CATALOG.set(catalog);
+    }
+
+    pub fn message_catalog() -> &'static MessageCatalog {
+        MESSAGE_CATALOG.get_or_init(MessageCatalog::builtin)
+    }
+}
+
+const VIETNAMESE: &[(ErrorCode, &str)] = &[
+    (ErrorCode::BadRequest, "Yêu cầu không hợp lệ"),
+    (ErrorCode::Unauthorized, "Cần xác thực để truy cập"),
+    (ErrorCode::PaymentRequired, "Cần thanh toán để tiếp tục"),
+    (ErrorCode::Forbidden, "Bạn không có quyền thực hiện thao tác này"),
+    (ErrorCode::NotFound, "Không tìm thấy tài nguyên"),
+    (ErrorCode::MethodNotAllowed, "Phương thức không được hỗ trợ"),
+    (ErrorCode::NotAcceptable, "Không hỗ trợ định dạng được yêu cầu"),
+    (ErrorCode::Conflict, "Dữ liệu bị xung đột"),
+    (ErrorCode::Gone, "Tài nguyên không còn tồn tại"),
+    (ErrorCode::PayloadTooLarge, "Dữ liệu gửi lên quá lớn"),
+    (ErrorCode::UnprocessableEntity, "Không thể xử lý dữ liệu"),
+    (ErrorCode::TooManyRequests, "Quá nhiều yêu cầu, vui lòng thử lại sau"),
+    (ErrorCode::InternalServerError, "Lỗi hệ thống, vui lòng thử lại sau"),
+    (ErrorCode::NotImplemented, "Chức năng chưa được hỗ trợ"),
+    (ErrorCode::BadGateway, "Dịch vụ phía sau trả về lỗi"),
+    (ErrorCode::ServiceUnavailable, "Dịch vụ tạm thời không khả dụng"),
+    (ErrorCode::GatewayTimeout, "Hết thời gian chờ phản hồi"),
+    (ErrorCode::ValidationError, "Dữ liệu không hợp lệ"),
+    (ErrorCode::DatabaseError, "Lỗi cơ sở dữ liệu"),
+    (ErrorCode::CacheError, "Lỗi bộ nhớ đệm"),
+    (ErrorCode::AuthenticationError, "Xác thực thất bại"),
+    (ErrorCode::AuthorizationError, "Bạn không có quyền thực hiện thao tác này"),
+    (ErrorCode::RateLimitError, "Quá nhiều yêu cầu, vui lòng thử lại sau"),
+    (ErrorCode::ExternalServiceError, "Dịch vụ bên ngoài gặp lỗi"),
+    (ErrorCode::ConfigurationError, "Lỗi cấu hình hệ thống"),
+    (ErrorCode::DataIntegrityError, "Dữ liệu không nhất quán"),
+    (ErrorCode::ResourceExhausted, "Đã vượt quá giới hạn sử dụng"),
+];
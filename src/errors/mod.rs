@@ -1,3 +1,5 @@
 pub mod api_error;
+pub mod i18n;
 
-pub use api_error::{ApiError, ApiResult};
+pub use api_error::{ApiError, ApiResult, ErrorCode, ErrorFormat, ErrorResponse, ProblemDetails};
+pub use i18n::{MessageCatalog, SOURCE_LOCALE};
@@ -1,11 +1,18 @@
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::sync::{Arc, RwLock};
+use std::time::Duration;
+
+use super::bucketing::bucket;
+use super::store::FlagStore;
+use crate::errors::ApiError;
 
 /// A/B test variant
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Variant {
     pub name: String,
+    /// Relative share; weights are normalized by their total, so `1`/`3`
+    /// means 25%/75%. A weight of 0 is never selected.
     pub weight: u8,
 }
 
@@ -14,26 +21,136 @@ pub struct Variant {
 pub struct ABTest {
     pub name: String,
     pub enabled: bool,
+    /// No variants means the test is not set up yet: `get_variant` is `None`
     pub variants: Vec<Variant>,
 }
 
+impl ABTest {
+    /// Variants need distinct names and, if there are any, a positive total weight
+    pub fn validate(&self) -> Result<(), ApiError> {
+        if self.variants.is_empty() {
+            return Ok(());
+        }
+
+        let mut names = std::collections::HashSet::new();
+        if let Some(duplicate) = self.variants.iter().find(|v| !names.insert(v.name.as_str())) {
+            return Err(ApiError::validation_field(
+                format!("Duplicate variant {} in A/B test {}", duplicate.name, self.name),
+                "variants",
+            ));
+        }
+        if self.total_weight() == 0 {
+            return Err(ApiError::validation_field(
+                format!("A/B test {} needs at least one variant with a positive weight", self.name),
+                "variants",
+            ));
+        }
+        Ok(())
+    }
+
+    fn total_weight(&self) -> u32 {
+        self.variants.iter().map(|v| v.weight as u32).sum()
+    }
+}
+
 /// A/B test manager
+///
+/// Clones share the same tests. As with `FeatureFlagManager`, writes and
+/// reloads are serialized so a periodic `load` cannot overwrite a
+/// `set_test` that happened while it was reading the store.
 #[derive(Clone)]
 pub struct ABTestManager {
     tests: Arc<RwLock<HashMap<String, ABTest>>>,
+    store: Option<Arc<dyn FlagStore>>,
+    write_lock: Arc<tokio::sync::Mutex<()>>,
 }
 
 impl ABTestManager {
     pub fn new() -> Self {
         Self {
             tests: Arc::new(RwLock::new(HashMap::new())),
+            store: None,
+            write_lock: Arc::new(tokio::sync::Mutex::new(())),
         }
     }
 
-    pub fn add_test(&self, test: ABTest) {
-        if let Ok(mut tests) = self.tests.write() {
-            tests.insert(test.name.clone(), test);
+    /// Persist tests in `store`; call [`load`](Self::load) at startup
+    pub fn with_store(mut self, store: Arc<dyn FlagStore>) -> Self {
+        self.store = Some(store);
+        self
+    }
+
+    /// Thay toàn bộ test bằng dữ liệu trong store; trả về số test
+    pub async fn load(&self) -> Result<usize, ApiError> {
+        let Some(store) = &self.store else {
+            return Ok(self.list_tests().len());
+        };
+
+        let _write = self.write_lock.lock().await;
+        let loaded: HashMap<String, ABTest> = store
+            .load_tests()
+            .await?
+            .into_iter()
+            .filter(|test| match test.validate() {
+                Ok(()) => true,
+                Err(e) => {
+                    tracing::warn!("Skipping stored A/B test {}: {}", test.name, e);
+                    false
+                }
+            })
+            .map(|test| (test.name.clone(), test))
+            .collect();
+        let count = loaded.len();
+        *self
+            .tests
+            .write()
+            .map_err(|_| ApiError::internal("Failed to acquire write lock on A/B tests"))? = loaded;
+        Ok(count)
+    }
+
+    /// Write `test` to the store (if any), then apply it locally
+    pub async fn set_test(&self, test: ABTest) -> Result<(), ApiError> {
+        test.validate()?;
+        let _write = self.write_lock.lock().await;
+        if let Some(store) = &self.store {
+            store.save_test(&test).await?;
+        }
+        self.add_test(test)
+    }
+
+    pub async fn delete_test(&self, name: &str) -> Result<(), ApiError> {
+        let _write = self.write_lock.lock().await;
+        if let Some(store) = &self.store {
+            store.delete_test(name).await?;
         }
+        self.remove_test(name);
+        Ok(())
+    }
+
+    /// Reload from the store every `interval`; `None` without a store
+    pub fn start_refresh(&self, interval: Duration) -> Option<tokio::task::JoinHandle<()>> {
+        self.store.as_ref()?;
+        let manager = self.clone();
+        Some(tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+            ticker.tick().await;
+            loop {
+                ticker.tick().await;
+                if let Err(e) = manager.load().await {
+                    tracing::warn!("Failed to refresh A/B tests: {}", e);
+                }
+            }
+        }))
+    }
+
+    /// Lỗi validation nếu weight không hợp lệ (xem [`ABTest::validate`])
+    pub fn add_test(&self, test: ABTest) -> Result<(), ApiError> {
+        test.validate()?;
+        self.tests
+            .write()
+            .map_err(|_| ApiError::internal("Failed to acquire write lock on A/B tests"))?
+            .insert(test.name.clone(), test);
+        Ok(())
     }
 
     pub fn get_variant(&self, test_name: &str, user_id: &str) -> Option<String> {
@@ -43,13 +160,18 @@ impl ABTestManager {
                     return None;
                 }
 
-                let hash = self.hash_user_id(user_id);
-                let total_weight: u8 = test.variants.iter().map(|v| v.weight).sum();
-                let mut cumulative = 0u8;
-                let target = (hash % total_weight as u64) as u8;
+                let total_weight = test.total_weight();
+                if total_weight == 0 {
+                    return None;
+                }
+
+                // Cùng bucket với feature flag (xem `bucketing::bucket`),
+                // co giãn theo tổng weight
+                let mut cumulative = 0u32;
+                let target = bucket(test_name, user_id) as u32 * total_weight / 100;
 
                 for variant in &test.variants {
-                    cumulative += variant.weight;
+                    cumulative += variant.weight as u32;
                     if target < cumulative {
                         return Some(variant.name.clone());
                     }
@@ -85,15 +207,6 @@ impl ABTestManager {
             tests.remove(name);
         }
     }
-
-    fn hash_user_id(&self, user_id: &str) -> u64 {
-        use std::collections::hash_map::DefaultHasher;
-        use std::hash::{Hash, Hasher};
-        
-        let mut hasher = DefaultHasher::new();
-        user_id.hash(&mut hasher);
-        hasher.finish()
-    }
 }
 
 impl Default for ABTestManager {
@@ -0,0 +1,17 @@
+use xxhash_rust::xxh3::xxh3_64;
+
+/// Bucket ổn định 0..100 của `id` trong flag/test `name`
+///
+/// XXH3-64 of `"{name}:{id}"` modulo 100. The hash is specified (unlike
+/// `DefaultHasher`), so buckets are the same across processes, Rust versions
+/// and restarts, and salting with the name keeps different flags from
+/// enrolling the same users. A user is in a rollout of `p`% when
+/// `bucket < p`, so raising `p` only ever adds users.
+pub fn bucket(name: &str, id: &str) -> u8 {
+    (xxh3_64(format!("{}:{}", name, id).as_bytes()) % 100) as u8
+}
+
+/// Whether `id` falls inside a `percentage` rollout of `name`
+pub fn in_rollout(name: &str, id: &str, percentage: u8) -> bool {
+    bucket(name, id) < percentage
+}
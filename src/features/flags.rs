@@ -1,6 +1,14 @@
+use actix_web::{HttpMessage, HttpRequest};
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::sync::{Arc, RwLock};
+use std::time::Duration;
+
+use super::bucketing::in_rollout;
+use super::store::FlagStore;
+use crate::auth::Claims;
+use crate::errors::ApiError;
+use crate::multitenancy::TenantId;
 
 /// Feature flag
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -11,17 +19,137 @@ pub struct FeatureFlag {
     pub rollout_percentage: u8,
 }
 
+/// Bật/tắt flag cho một nhóm tenant (vd. nhóm beta)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TargetingRule {
+    pub tenant_ids: HashSet<TenantId>,
+    pub enabled: bool,
+}
+
 /// Feature flag manager
+///
+/// For a tenant, a flag is decided by the first of: the tenant's override, the
+/// first targeting rule containing the tenant, the rollout percentage, the
+/// global `enabled`.
+///
+/// Clones share the same flags, so one manager can back both request
+/// handling and `/admin/flags`. Writes and reloads from the store are
+/// serialized within the process, so a reload never overwrites a change made
+/// while it was reading the store.
 #[derive(Clone)]
 pub struct FeatureFlagManager {
     flags: Arc<RwLock<HashMap<String, FeatureFlag>>>,
+    /// flag -> tenant -> enabled
+    tenant_overrides: Arc<RwLock<HashMap<String, HashMap<TenantId, bool>>>>,
+    targeting_rules: Arc<RwLock<HashMap<String, Vec<TargetingRule>>>>,
+    store: Option<Arc<dyn FlagStore>>,
+    /// Giữ qua cả lần ghi store để các thay đổi không ghi đè lẫn nhau
+    write_lock: Arc<tokio::sync::Mutex<()>>,
 }
 
 impl FeatureFlagManager {
     pub fn new() -> Self {
         Self {
             flags: Arc::new(RwLock::new(HashMap::new())),
+            tenant_overrides: Arc::new(RwLock::new(HashMap::new())),
+            targeting_rules: Arc::new(RwLock::new(HashMap::new())),
+            store: None,
+            write_lock: Arc::new(tokio::sync::Mutex::new(())),
+        }
+    }
+
+    /// Persist flags in `store`; call [`load`](Self::load) at startup
+    pub fn with_store(mut self, store: Arc<dyn FlagStore>) -> Self {
+        self.store = Some(store);
+        self
+    }
+
+    /// Thay toàn bộ flag bằng dữ liệu trong store; trả về số flag
+    ///
+    /// Flags deleted from the store disappear here too. Without a store this
+    /// keeps the current flags.
+    pub async fn load(&self) -> Result<usize, ApiError> {
+        let Some(store) = &self.store else {
+            return Ok(self.list_flags().len());
+        };
+
+        let _write = self.write_lock.lock().await;
+        let loaded: HashMap<String, FeatureFlag> = store
+            .load_flags()
+            .await?
+            .into_iter()
+            .map(|flag| (flag.name.clone(), flag))
+            .collect();
+        let count = loaded.len();
+        *self
+            .flags
+            .write()
+            .map_err(|_| ApiError::internal("Failed to acquire write lock on feature flags"))? = loaded;
+        Ok(count)
+    }
+
+    /// Write `flag` to the store (if any), then apply it locally
+    pub async fn set_flag(&self, flag: FeatureFlag) -> Result<(), ApiError> {
+        let _write = self.write_lock.lock().await;
+        self.save_flag(flag).await
+    }
+
+    /// Replace `name` with `change(current)` (`None` for a new flag) and save
+    /// it; returns the previous and new flag
+    ///
+    /// The read and the write happen under the write lock, so concurrent
+    /// partial updates of the same flag do not drop each other's fields.
+    pub async fn update_flag<F>(
+        &self,
+        name: &str,
+        change: F,
+    ) -> Result<(Option<FeatureFlag>, FeatureFlag), ApiError>
+    where
+        F: FnOnce(Option<&FeatureFlag>) -> FeatureFlag,
+    {
+        let _write = self.write_lock.lock().await;
+        let previous = self.get_flag(name);
+        let flag = change(previous.as_ref());
+        self.save_flag(flag.clone()).await?;
+        Ok((previous, flag))
+    }
+
+    /// Delete from the store (if any), then [`remove_flag`](Self::remove_flag);
+    /// returns the deleted flag, `None` if there was none
+    pub async fn delete_flag(&self, name: &str) -> Result<Option<FeatureFlag>, ApiError> {
+        let _write = self.write_lock.lock().await;
+        let previous = self.get_flag(name);
+        if let Some(store) = &self.store {
+            store.delete_flag(name).await?;
+        }
+        self.remove_flag(name);
+        Ok(previous)
+    }
+
+    async fn save_flag(&self, flag: FeatureFlag) -> Result<(), ApiError> {
+        if let Some(store) = &self.store {
+            store.save_flag(&flag).await?;
         }
+        self.add_flag(flag);
+        Ok(())
+    }
+
+    /// Reload from the store every `interval` so replicas converge; `None`
+    /// without a store. Must be called from within a Tokio runtime.
+    pub fn start_refresh(&self, interval: Duration) -> Option<tokio::task::JoinHandle<()>> {
+        self.store.as_ref()?;
+        let manager = self.clone();
+        Some(tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+            // Tick đầu tiên chạy ngay; flags đã được load lúc startup
+            ticker.tick().await;
+            loop {
+                ticker.tick().await;
+                if let Err(e) = manager.load().await {
+                    tracing::warn!("Failed to refresh feature flags: {}", e);
+                }
+            }
+        }))
     }
 
     pub fn add_flag(&self, flag: FeatureFlag) {
@@ -45,10 +173,8 @@ impl FeatureFlagManager {
                     return false;
                 }
 
-                // Simple hash-based rollout
-                let hash = self.hash_user_id(user_id);
-                let percentage = hash % 100;
-                percentage < flag.rollout_percentage as u64
+                // Bucket ổn định theo flag, tăng rollout chỉ thêm user
+                in_rollout(name, user_id, flag.rollout_percentage)
             } else {
                 false
             }
@@ -57,6 +183,69 @@ impl FeatureFlagManager {
         }
     }
 
+    /// Force `name` on or off for one tenant, regardless of the global flag
+    pub fn set_tenant_override(&self, name: &str, tenant_id: &str, enabled: bool) {
+        if let Ok(mut overrides) = self.tenant_overrides.write() {
+            overrides
+                .entry(name.to_string())
+                .or_default()
+                .insert(tenant_id.to_string(), enabled);
+        }
+    }
+
+    pub fn remove_tenant_override(&self, name: &str, tenant_id: &str) {
+        if let Ok(mut overrides) = self.tenant_overrides.write() {
+            if let Some(tenants) = overrides.get_mut(name) {
+                tenants.remove(tenant_id);
+            }
+        }
+    }
+
+    /// Rules are checked in the order they were added
+    pub fn add_targeting_rule(&self, name: &str, rule: TargetingRule) {
+        if let Ok(mut rules) = self.targeting_rules.write() {
+            rules.entry(name.to_string()).or_default().push(rule);
+        }
+    }
+
+    pub fn is_enabled_for_tenant(&self, name: &str, tenant_id: &str) -> bool {
+        let tenant_override = self
+            .tenant_overrides
+            .read()
+            .ok()
+            .and_then(|overrides| overrides.get(name)?.get(tenant_id).copied());
+        if let Some(enabled) = tenant_override {
+            return enabled;
+        }
+
+        let rule = self.targeting_rules.read().ok().and_then(|rules| {
+            rules
+                .get(name)?
+                .iter()
+                .find(|rule| rule.tenant_ids.contains(tenant_id))
+                .map(|rule| rule.enabled)
+        });
+        if let Some(enabled) = rule {
+            return enabled;
+        }
+
+        // Rollout theo tenant: cả tenant cùng nhận một kết quả
+        self.is_enabled_for_user(name, tenant_id)
+    }
+
+    /// [`is_enabled_for_tenant`](Self::is_enabled_for_tenant) for the tenant
+    /// of the verified token (`Claims.tenant_id`); the global flag without one
+    ///
+    /// `X-Tenant-ID` is set by the client and never consulted, so a caller
+    /// can't turn on another tenant's overrides.
+    pub fn is_enabled_for_request(&self, name: &str, req: &HttpRequest) -> bool {
+        let tenant_id = req.extensions().get::<Claims>().and_then(|c| c.tenant_id.clone());
+        match tenant_id {
+            Some(tenant_id) => self.is_enabled_for_tenant(name, &tenant_id),
+            None => self.is_enabled(name),
+        }
+    }
+
     pub fn get_flag(&self, name: &str) -> Option<FeatureFlag> {
         if let Ok(flags) = self.flags.read() {
             flags.get(name).cloned()
@@ -77,15 +266,12 @@ impl FeatureFlagManager {
         if let Ok(mut flags) = self.flags.write() {
             flags.remove(name);
         }
-    }
-
-    fn hash_user_id(&self, user_id: &str) -> u64 {
-        use std::collections::hash_map::DefaultHasher;
-        use std::hash::{Hash, Hasher};
-        
-        let mut hasher = DefaultHasher::new();
-        user_id.hash(&mut hasher);
-        hasher.finish()
+        if let Ok(mut overrides) = self.tenant_overrides.write() {
+            overrides.remove(name);
+        }
+        if let Ok(mut rules) = self.targeting_rules.write() {
+            rules.remove(name);
+        }
     }
 }
 
@@ -1,6 +1,16 @@
 pub mod flags;
 pub mod ab_testing;
+pub mod bucketing;
+pub mod store;
 
-pub use flags::{FeatureFlag, FeatureFlagManager};
+pub use flags::{FeatureFlag, FeatureFlagManager, TargetingRule};
 pub use ab_testing::{ABTest, ABTestManager, Variant};
+pub use bucketing::{bucket, in_rollout};
+pub use store::{FlagStore, InMemoryFlagStore, DEFAULT_FLAG_REFRESH_INTERVAL};
+
+#[cfg(feature = "cache-redis")]
+pub use store::RedisFlagStore;
+
+#[cfg(feature = "database-postgres")]
+pub use store::PostgresFlagStore;
 
@@ -0,0 +1,278 @@
+use async_trait::async_trait;
+use std::collections::HashMap;
+use std::sync::{Arc, RwLock};
+
+#[cfg(feature = "cache-redis")]
+use crate::cache::CacheManager;
+use crate::errors::ApiError;
+
+use super::ab_testing::ABTest;
+use super::flags::FeatureFlag;
+
+/// Khoảng thời gian mặc định giữa hai lần reload từ store
+pub const DEFAULT_FLAG_REFRESH_INTERVAL: std::time::Duration = std::time::Duration::from_secs(30);
+
+/// Shared storage for feature flags and A/B tests
+///
+/// Managers attached with `with_store` write through on every change and
+/// replace their in-memory copy on `load`, so replicas sharing a store
+/// converge on the next refresh. Tenant overrides and targeting rules stay
+/// local to the process.
+#[async_trait]
+pub trait FlagStore: Send + Sync {
+    async fn load_flags(&self) -> Result<Vec<FeatureFlag>, ApiError>;
+
+    /// Insert or replace the flag with the same name
+    async fn save_flag(&self, flag: &FeatureFlag) -> Result<(), ApiError>;
+
+    async fn delete_flag(&self, name: &str) -> Result<(), ApiError>;
+
+    async fn load_tests(&self) -> Result<Vec<ABTest>, ApiError>;
+
+    /// Insert or replace the test with the same name
+    async fn save_test(&self, test: &ABTest) -> Result<(), ApiError>;
+
+    async fn delete_test(&self, name: &str) -> Result<(), ApiError>;
+}
+
+/// In-memory store; clones share the same data (single process, tests)
+#[derive(Clone, Default)]
+pub struct InMemoryFlagStore {
+    flags: Arc<RwLock<HashMap<String, FeatureFlag>>>,
+    tests: Arc<RwLock<HashMap<String, ABTest>>>,
+}
+
+impl InMemoryFlagStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+#[async_trait]
+impl FlagStore for InMemoryFlagStore {
+    async fn load_flags(&self) -> Result<Vec<FeatureFlag>, ApiError> {
+        let flags = self
+            .flags
+            .read()
+            .map_err(|_| ApiError::internal("Failed to acquire read lock on flag store"))?;
+        Ok(flags.values().cloned().collect())
+    }
+
+    async fn save_flag(&self, flag: &FeatureFlag) -> Result<(), ApiError> {
+        self.flags
+            .write()
+            .map_err(|_| ApiError::internal("Failed to acquire write lock on flag store"))?
+            .insert(flag.name.clone(), flag.clone());
+        Ok(())
+    }
+
+    async fn delete_flag(&self, name: &str) -> Result<(), ApiError> {
+        self.flags
+            .write()
+            .map_err(|_| ApiError::internal("Failed to acquire write lock on flag store"))?
+            .remove(name);
+        Ok(())
+    }
+
+    async fn load_tests(&self) -> Result<Vec<ABTest>, ApiError> {
+        let tests = self
+            .tests
+            .read()
+            .map_err(|_| ApiError::internal("Failed to acquire read lock on flag store"))?;
+        Ok(tests.values().cloned().collect())
+    }
+
+    async fn save_test(&self, test: &ABTest) -> Result<(), ApiError> {
+        self.tests
+            .write()
+            .map_err(|_| ApiError::internal("Failed to acquire write lock on flag store"))?
+            .insert(test.name.clone(), test.clone());
+        Ok(())
+    }
+
+    async fn delete_test(&self, name: &str) -> Result<(), ApiError> {
+        self.tests
+            .write()
+            .map_err(|_| ApiError::internal("Failed to acquire write lock on flag store"))?
+            .remove(name);
+        Ok(())
+    }
+}
+
+/// Redis store: one hash per kind, field = name, value = JSON
+#[cfg(feature = "cache-redis")]
+#[derive(Clone)]
+pub struct RedisFlagStore {
+    conn: redis::aio::ConnectionManager,
+    prefix: String,
+}
+
+#[cfg(feature = "cache-redis")]
+impl RedisFlagStore {
+    pub fn new(cache: &CacheManager) -> Self {
+        Self {
+            conn: cache.get_connection(),
+            prefix: "features".to_string(),
+        }
+    }
+
+    /// Key prefix, e.g. per environment (default `features`)
+    pub fn with_prefix(mut self, prefix: impl Into<String>) -> Self {
+        self.prefix = prefix.into();
+        self
+    }
+
+    fn key(&self, kind: &str) -> String {
+        format!("{}:{}", self.prefix, kind)
+    }
+
+    async fn load<T: serde::de::DeserializeOwned>(&self, kind: &str) -> Result<Vec<T>, ApiError> {
+        use redis::AsyncCommands;
+
+        let mut conn = self.conn.clone();
+        let values: HashMap<String, String> = conn
+            .hgetall(self.key(kind))
+            .await
+            .map_err(|e| ApiError::cache(format!("Flag store read error: {}", e)))?;
+
+        values
+            .into_values()
+            .map(|value| {
+                serde_json::from_str(&value)
+                    .map_err(|e| ApiError::cache(format!("Flag store deserialize error: {}", e)))
+            })
+            .collect()
+    }
+
+    async fn save<T: serde::Serialize + Sync>(&self, kind: &str, name: &str, value: &T) -> Result<(), ApiError> {
+        use redis::AsyncCommands;
+
+        let value = serde_json::to_string(value)
+            .map_err(|e| ApiError::cache(format!("Flag store serialize error: {}", e)))?;
+        let mut conn = self.conn.clone();
+        conn.hset::<_, _, _, ()>(self.key(kind), name, value)
+            .await
+            .map_err(|e| ApiError::cache(format!("Flag store write error: {}", e)))
+    }
+
+    async fn delete(&self, kind: &str, name: &str) -> Result<(), ApiError> {
+        use redis::AsyncCommands;
+
+        let mut conn = self.conn.clone();
+        conn.hdel::<_, _, ()>(self.key(kind), name)
+            .await
+            .map_err(|e| ApiError::cache(format!("Flag store delete error: {}", e)))
+    }
+}
+
+#[cfg(feature = "cache-redis")]
+#[async_trait]
+impl FlagStore for RedisFlagStore {
+    async fn load_flags(&self) -> Result<Vec<FeatureFlag>, ApiError> {
+        self.load("flags").await
+    }
+
+    async fn save_flag(&self, flag: &FeatureFlag) -> Result<(), ApiError> {
+        self.save("flags", &flag.name, flag).await
+    }
+
+    async fn delete_flag(&self, name: &str) -> Result<(), ApiError> {
+        self.delete("flags", name).await
+    }
+
+    async fn load_tests(&self) -> Result<Vec<ABTest>, ApiError> {
+        self.load("ab_tests").await
+    }
+
+    async fn save_test(&self, test: &ABTest) -> Result<(), ApiError> {
+        self.save("ab_tests", &test.name, test).await
+    }
+
+    async fn delete_test(&self, name: &str) -> Result<(), ApiError> {
+        self.delete("ab_tests", name).await
+    }
+}
+
+/// Postgres store (tables `feature_flags` and `ab_tests`, JSONB values)
+#[cfg(feature = "database-postgres")]
+#[derive(Clone)]
+pub struct PostgresFlagStore {
+    pool: sqlx::PgPool,
+}
+
+#[cfg(feature = "database-postgres")]
+impl PostgresFlagStore {
+    pub fn new(pool: sqlx::PgPool) -> Self {
+        Self { pool }
+    }
+}
+
+#[cfg(feature = "database-postgres")]
+#[async_trait]
+impl FlagStore for PostgresFlagStore {
+    async fn load_flags(&self) -> Result<Vec<FeatureFlag>, ApiError> {
+        let rows: Vec<(sqlx::types::Json<FeatureFlag>,)> = sqlx::query_as("SELECT flag FROM feature_flags")
+            .fetch_all(&self.pool)
+            .await
+            .map_err(|e| ApiError::database(format!("Failed to load feature flags: {}", e)))?;
+        Ok(rows.into_iter().map(|(flag,)| flag.0).collect())
+    }
+
+    async fn save_flag(&self, flag: &FeatureFlag) -> Result<(), ApiError> {
+        sqlx::query(
+            r#"
+            INSERT INTO feature_flags (name, flag, updated_at)
+            VALUES ($1, $2, NOW())
+            ON CONFLICT (name) DO UPDATE SET flag = EXCLUDED.flag, updated_at = NOW()
+            "#,
+        )
+        .bind(&flag.name)
+        .bind(sqlx::types::Json(flag))
+        .execute(&self.pool)
+        .await
+        .map_err(|e| ApiError::database(format!("Failed to save feature flag {}: {}", flag.name, e)))?;
+        Ok(())
+    }
+
+    async fn delete_flag(&self, name: &str) -> Result<(), ApiError> {
+        sqlx::query("DELETE FROM feature_flags WHERE name = $1")
+            .bind(name)
+            .execute(&self.pool)
+            .await
+            .map_err(|e| ApiError::database(format!("Failed to delete feature flag {}: {}", name, e)))?;
+        Ok(())
+    }
+
+    async fn load_tests(&self) -> Result<Vec<ABTest>, ApiError> {
+        let rows: Vec<(sqlx::types::Json<ABTest>,)> = sqlx::query_as("SELECT test FROM ab_tests")
+            .fetch_all(&self.pool)
+            .await
+            .map_err(|e| ApiError::database(format!("Failed to load A/B tests: {}", e)))?;
+        Ok(rows.into_iter().map(|(test,)| test.0).collect())
+    }
+
+    async fn save_test(&self, test: &ABTest) -> Result<(), ApiError> {
+        sqlx::query(
+            r#"
+            INSERT INTO ab_tests (name, test, updated_at)
+            VALUES ($1, $2, NOW())
+            ON CONFLICT (name) DO UPDATE SET test = EXCLUDED.test, updated_at = NOW()
+            "#,
+        )
+        .bind(&test.name)
+        .bind(sqlx::types::Json(test))
+        .execute(&self.pool)
+        .await
+        .map_err(|e| ApiError::database(format!("Failed to save A/B test {}: {}", test.name, e)))?;
+        Ok(())
+    }
+
+    async fn delete_test(&self, name: &str) -> Result<(), ApiError> {
+        sqlx::query("DELETE FROM ab_tests WHERE name = $1")
+            .bind(name)
+            .execute(&self.pool)
+            .await
+            .map_err(|e| ApiError::database(format!("Failed to delete A/B test {}: {}", name, e)))?;
+        Ok(())
+    }
+}
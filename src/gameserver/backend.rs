@@ -0,0 +1,53 @@
+use async_trait::async_trait;
+use crate::errors::ApiError;
+use super::leaderboard::{Leaderboard, LeaderboardEntry};
+
+/// Leaderboard operations shared by the in-memory and Redis implementations
+#[async_trait]
+pub trait LeaderboardBackend: Send + Sync {
+    /// Set a player's score, replacing any previous score
+    async fn update_score(&self, player_id: &str, score: i64) -> Result<(), ApiError>;
+
+    /// Add `delta` to a player's score (starting from 0) and return the new score
+    async fn increment_score(&self, player_id: &str, delta: i64) -> Result<i64, ApiError>;
+
+    /// Get the highest ranked players
+    async fn get_top(&self, limit: usize) -> Result<Vec<LeaderboardEntry>, ApiError>;
+
+    /// Get a page of the leaderboard, starting at the 0-based `offset`
+    async fn get_range(&self, offset: usize, limit: usize) -> Result<Vec<LeaderboardEntry>, ApiError>;
+
+    /// Get a player's rank and score
+    async fn get_player_rank(&self, player_id: &str) -> Result<Option<LeaderboardEntry>, ApiError>;
+
+    /// Archive the current standings as `archive_as` and clear the board
+    async fn reset(&self, archive_as: &str) -> Result<(), ApiError>;
+}
+
+#[async_trait]
+impl LeaderboardBackend for Leaderboard {
+    async fn update_score(&self, player_id: &str, score: i64) -> Result<(), ApiError> {
+        Leaderboard::update_score(self, player_id.to_string(), score);
+        Ok(())
+    }
+
+    async fn increment_score(&self, player_id: &str, delta: i64) -> Result<i64, ApiError> {
+        Leaderboard::increment_score(self, player_id.to_string(), delta)
+    }
+
+    async fn get_top(&self, limit: usize) -> Result<Vec<LeaderboardEntry>, ApiError> {
+        Ok(Leaderboard::get_top(self, limit))
+    }
+
+    async fn get_range(&self, offset: usize, limit: usize) -> Result<Vec<LeaderboardEntry>, ApiError> {
+        Ok(Leaderboard::get_range(self, offset, limit))
+    }
+
+    async fn get_player_rank(&self, player_id: &str) -> Result<Option<LeaderboardEntry>, ApiError> {
+        Ok(Leaderboard::get_player_rank(self, player_id))
+    }
+
+    async fn reset(&self, archive_as: &str) -> Result<(), ApiError> {
+        Leaderboard::reset(self, archive_as.to_string())
+    }
+}
@@ -1,7 +1,9 @@
 use serde::{Deserialize, Serialize};
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, HashMap};
 use std::sync::{Arc, RwLock};
 
+use crate::errors::ApiError;
+
 /// Leaderboard entry
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct LeaderboardEntry {
@@ -11,10 +13,16 @@ pub struct LeaderboardEntry {
 }
 
 /// Leaderboard
+///
+/// Players are ranked by score (highest first). Ties are broken by earliest
+/// achievement: the player who reached a score first ranks above players who
+/// reach the same score later. Every player gets a distinct rank.
 #[derive(Clone)]
 pub struct Leaderboard {
     name: String,
     scores: Arc<RwLock<BTreeMap<i64, Vec<String>>>>,
+    /// Past seasons by archive name
+    archives: Arc<RwLock<HashMap<String, BTreeMap<i64, Vec<String>>>>>,
 }
 
 impl Leaderboard {
@@ -22,44 +30,114 @@ impl Leaderboard {
         Self {
             name,
             scores: Arc::new(RwLock::new(BTreeMap::new())),
+            archives: Arc::new(RwLock::new(HashMap::new())),
         }
     }
 
+    /// End the season: keep the current standings as `archive_as` and clear
+    /// the live board. Fails with `Conflict` if the archive name is taken; an
+    /// empty board leaves no archive, as with the Redis leaderboard.
+    pub fn reset(&self, archive_as: String) -> Result<(), ApiError> {
+        let mut archives = self
+            .archives
+            .write()
+            .map_err(|_| ApiError::internal("Failed to acquire write lock on leaderboard archives"))?;
+        if archives.contains_key(&archive_as) {
+            return Err(ApiError::Conflict {
+                message: format!("Leaderboard archive {} already exists", archive_as),
+                field: None,
+            });
+        }
+
+        let mut scores = self
+            .scores
+            .write()
+            .map_err(|_| ApiError::internal("Failed to acquire write lock on leaderboard"))?;
+        if !scores.is_empty() {
+            archives.insert(archive_as, std::mem::take(&mut *scores));
+        }
+        Ok(())
+    }
+
+    /// Archived season `name`, with the ranks it ended on; `None` if no such
+    /// archive exists (or the season ended empty)
+    pub fn get_archive(&self, name: &str) -> Option<LeaderboardArchive> {
+        let archives = self.archives.read().ok()?;
+        let scores = archives.get(name)?.clone();
+        Some(LeaderboardArchive(Self {
+            name: name.to_string(),
+            scores: Arc::new(RwLock::new(scores)),
+            archives: Arc::new(RwLock::new(HashMap::new())),
+        }))
+    }
+
     pub fn update_score(&self, player_id: String, score: i64) {
         if let Ok(mut scores) = self.scores.write() {
-            // Remove player from old score
-            for players in scores.values_mut() {
-                players.retain(|p| p != &player_id);
-            }
+            Self::place(&mut scores, player_id, score);
+        }
+    }
+
+    /// Add `delta` to a player's score (0 if not on the board yet), saturating
+    /// at the `i64` bounds; returns the new score
+    pub fn increment_score(&self, player_id: String, delta: i64) -> Result<i64, ApiError> {
+        let mut scores = self
+            .scores
+            .write()
+            .map_err(|_| ApiError::internal("Failed to acquire write lock on leaderboard"))?;
 
-            // Add player to new score
-            scores.entry(score).or_insert_with(Vec::new).push(player_id);
+        let current = scores
+            .iter()
+            .find(|(_, players)| players.contains(&player_id))
+            .map(|(score, _)| *score)
+            .unwrap_or(0);
+        let score = current.saturating_add(delta);
+        Self::place(&mut scores, player_id, score);
+        Ok(score)
+    }
+
+    fn place(scores: &mut BTreeMap<i64, Vec<String>>, player_id: String, score: i64) {
+        // Keep the original achievement order if the score is unchanged
+        if scores.get(&score).is_some_and(|players| players.contains(&player_id)) {
+            return;
+        }
+
+        // Remove player from old score
+        for players in scores.values_mut() {
+            players.retain(|p| p != &player_id);
         }
+        scores.retain(|_, players| !players.is_empty());
+
+        // Add player to new score
+        scores.entry(score).or_insert_with(Vec::new).push(player_id);
     }
 
     pub fn get_top(&self, limit: usize) -> Vec<LeaderboardEntry> {
-        if let Ok(scores) = self.scores.read() {
-            let mut entries = Vec::new();
-            let mut rank = 1;
+        self.get_range(0, limit)
+    }
 
-            for (score, players) in scores.iter().rev() {
-                for player_id in players {
-                    if entries.len() >= limit {
-                        return entries;
-                    }
+    /// Get a page of the leaderboard, starting at the 0-based `offset`
+    pub fn get_range(&self, offset: usize, limit: usize) -> Vec<LeaderboardEntry> {
+        self.ranked_entries()
+            .into_iter()
+            .skip(offset)
+            .take(limit)
+            .collect()
+    }
 
-                    entries.push(LeaderboardEntry {
-                        player_id: player_id.clone(),
-                        score: *score,
-                        rank,
-                    });
-                    rank += 1;
-                }
-            }
+    /// Get the `radius` players ranked above and below a player, including the player.
+    ///
+    /// The window is clipped at the top and bottom of the board. Returns an empty
+    /// list if the player is not on the board.
+    pub fn get_around(&self, player_id: &str, radius: usize) -> Vec<LeaderboardEntry> {
+        let entries = self.ranked_entries();
 
-            entries
-        } else {
-            Vec::new()
+        match entries.iter().position(|e| e.player_id == player_id) {
+            Some(index) => {
+                let start = index.saturating_sub(radius);
+                let end = (index + radius + 1).min(entries.len());
+                entries[start..end].to_vec()
+            }
+            None => Vec::new(),
         }
     }
 
@@ -89,5 +167,52 @@ impl Leaderboard {
     pub fn name(&self) -> &str {
         &self.name
     }
+
+    /// All entries in rank order
+    fn ranked_entries(&self) -> Vec<LeaderboardEntry> {
+        if let Ok(scores) = self.scores.read() {
+            scores
+                .iter()
+                .rev()
+                .flat_map(|(score, players)| {
+                    players.iter().map(move |player_id| (player_id, *score))
+                })
+                .enumerate()
+                .map(|(i, (player_id, score))| LeaderboardEntry {
+                    player_id: player_id.clone(),
+                    score,
+                    rank: i + 1,
+                })
+                .collect()
+        } else {
+            Vec::new()
+        }
+    }
 }
 
+/// Read-only snapshot of an archived season, from [`Leaderboard::get_archive`]
+#[derive(Clone)]
+pub struct LeaderboardArchive(Leaderboard);
+
+impl LeaderboardArchive {
+    pub fn name(&self) -> &str {
+        self.0.name()
+    }
+
+    pub fn get_top(&self, limit: usize) -> Vec<LeaderboardEntry> {
+        self.0.get_top(limit)
+    }
+
+    /// Get a page of the archive, starting at the 0-based `offset`
+    pub fn get_range(&self, offset: usize, limit: usize) -> Vec<LeaderboardEntry> {
+        self.0.get_range(offset, limit)
+    }
+
+    pub fn get_around(&self, player_id: &str, radius: usize) -> Vec<LeaderboardEntry> {
+        self.0.get_around(player_id, radius)
+    }
+
+    pub fn get_player_rank(&self, player_id: &str) -> Option<LeaderboardEntry> {
+        self.0.get_player_rank(player_id)
+    }
+}
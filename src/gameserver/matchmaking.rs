@@ -1,13 +1,20 @@
 use serde::{Deserialize, Serialize};
 use std::collections::VecDeque;
 use std::sync::{Arc, RwLock};
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, Duration, Utc};
+
+use crate::utils::clock::{Clock, SystemClock};
+
+/// Số request hết hạn tối đa giữ lại cho `timed_out`; cũ nhất bị bỏ trước
+pub const MAX_TIMED_OUT_REQUESTS: usize = 1000;
 
 /// Matchmaking request
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct MatchmakingRequest {
     pub player_id: String,
     pub skill_rating: u32,
+    /// Set by `add_player` from the queue's clock; any value sent by the
+    /// caller is replaced
     pub requested_at: DateTime<Utc>,
 }
 
@@ -24,6 +31,10 @@ pub struct Match {
 pub struct MatchmakingQueue {
     queue: Arc<RwLock<VecDeque<MatchmakingRequest>>>,
     skill_range: u32,
+    /// Requests waiting longer than this are dropped from the queue
+    max_wait: Option<Duration>,
+    timed_out: Arc<RwLock<VecDeque<MatchmakingRequest>>>,
+    clock: Arc<dyn Clock>,
 }
 
 impl MatchmakingQueue {
@@ -31,17 +42,88 @@ impl MatchmakingQueue {
         Self {
             queue: Arc::new(RwLock::new(VecDeque::new())),
             skill_range,
+            max_wait: None,
+            timed_out: Arc::new(RwLock::new(VecDeque::new())),
+            clock: Arc::new(SystemClock),
         }
     }
 
-    pub fn add_player(&self, request: MatchmakingRequest) {
+    /// Thời gian chờ tối đa trước khi request bị loại khỏi queue
+    pub fn with_max_wait(mut self, max_wait: Duration) -> Self {
+        self.max_wait = Some(max_wait);
+        self
+    }
+
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// Queue `request`, stamping `requested_at` with the current time so a
+    /// client cannot jump the queue or dodge `max_wait`
+    pub fn add_player(&self, mut request: MatchmakingRequest) {
         if let Ok(mut queue) = self.queue.write() {
+            self.purge_expired(&mut queue);
+            request.requested_at = self.clock.now();
             queue.push_back(request);
         }
     }
 
+    /// Cancel a queued request (player left); `false` if not queued
+    pub fn remove_player(&self, player_id: &str) -> bool {
+        let Ok(mut queue) = self.queue.write() else {
+            return false;
+        };
+        let before = queue.len();
+        queue.retain(|req| req.player_id != player_id);
+        queue.len() != before
+    }
+
+    /// Requests dropped for waiting longer than `max_wait` since the last
+    /// call, oldest first; at most [`MAX_TIMED_OUT_REQUESTS`] are kept
+    pub fn timed_out(&self) -> Vec<MatchmakingRequest> {
+        self.timed_out
+            .write()
+            .map(|mut timed_out| std::mem::take(&mut *timed_out).into())
+            .unwrap_or_default()
+    }
+
+    /// Drop requests older than `max_wait` now, without matching; returns
+    /// how many were dropped
+    pub fn purge(&self) -> usize {
+        let Ok(mut queue) = self.queue.write() else {
+            return 0;
+        };
+        let before = queue.len();
+        self.purge_expired(&mut queue);
+        before - queue.len()
+    }
+
+    /// Move expired requests from `queue` to `timed_out`
+    fn purge_expired(&self, queue: &mut VecDeque<MatchmakingRequest>) {
+        let Some(max_wait) = self.max_wait else {
+            return;
+        };
+        let deadline = self.clock.now() - max_wait;
+        let (expired, waiting): (VecDeque<_>, VecDeque<_>) =
+            queue.drain(..).partition(|req| req.requested_at < deadline);
+        *queue = waiting;
+
+        if !expired.is_empty() {
+            if let Ok(mut timed_out) = self.timed_out.write() {
+                timed_out.extend(expired);
+                let overflow = timed_out.len().saturating_sub(MAX_TIMED_OUT_REQUESTS);
+                timed_out.drain(..overflow);
+            }
+        }
+    }
+
+    /// Match the oldest waiting player with others in skill range, after
+    /// dropping timed-out requests
     pub fn find_match(&self, players_per_match: usize) -> Option<Match> {
         if let Ok(mut queue) = self.queue.write() {
+            self.purge_expired(&mut queue);
+
             if queue.len() < players_per_match {
                 return None;
             }
@@ -77,7 +159,7 @@ impl MatchmakingQueue {
                 Some(Match {
                     id: uuid::Uuid::new_v4().to_string(),
                     players: matched_players,
-                    created_at: Utc::now(),
+                    created_at: self.clock.now(),
                 })
             } else {
                 None
@@ -87,8 +169,10 @@ impl MatchmakingQueue {
         }
     }
 
+    /// Requests still waiting, after dropping timed-out ones
     pub fn queue_size(&self) -> usize {
-        if let Ok(queue) = self.queue.read() {
+        if let Ok(mut queue) = self.queue.write() {
+            self.purge_expired(&mut queue);
             queue.len()
         } else {
             0
@@ -1,8 +1,15 @@
 pub mod matchmaking;
 pub mod leaderboard;
+pub mod backend;
 pub mod session;
 
-pub use matchmaking::{MatchmakingQueue, MatchmakingRequest, Match};
-pub use leaderboard::{Leaderboard, LeaderboardEntry};
+#[cfg(feature = "cache-redis")]
+pub mod redis_leaderboard;
+
+pub use matchmaking::{MatchmakingQueue, MatchmakingRequest, Match, MAX_TIMED_OUT_REQUESTS};
+pub use leaderboard::{Leaderboard, LeaderboardArchive, LeaderboardEntry};
+pub use backend::LeaderboardBackend;
 pub use session::{GameSession, GameSessionManager};
 
+#[cfg(feature = "cache-redis")]
+pub use redis_leaderboard::{RedisLeaderboard, RedisLeaderboardArchive};
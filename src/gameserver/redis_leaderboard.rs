@@ -0,0 +1,196 @@
+use async_trait::async_trait;
+use redis::{aio::ConnectionManager, AsyncCommands};
+use crate::cache::CacheManager;
+use crate::errors::ApiError;
+use super::backend::LeaderboardBackend;
+use super::leaderboard::LeaderboardEntry;
+
+/// Archive the live set unless the archive name is taken; Redis drops empty
+/// sorted sets, so an empty board leaves no archive
+/// (`-1` name taken, `0` nothing to archive, `1` archived)
+const RESET_SCRIPT: &str = r#"
+if redis.call("EXISTS", KEYS[2]) == 1 then
+    return -1
+end
+if redis.call("EXISTS", KEYS[1]) == 0 then
+    return 0
+end
+redis.call("RENAME", KEYS[1], KEYS[2])
+return 1
+"#;
+
+/// Redis-backed leaderboard stored in a sorted set
+///
+/// Scores survive restarts and are shared by every replica using the same Redis.
+/// Ties are ordered by Redis (reverse lexicographic player id).
+#[derive(Clone)]
+pub struct RedisLeaderboard {
+    name: String,
+    key: String,
+    conn: ConnectionManager,
+}
+
+impl RedisLeaderboard {
+    pub fn new(name: String, cache_manager: &CacheManager) -> Self {
+        Self {
+            key: format!("leaderboard:{}", name),
+            name,
+            conn: cache_manager.get_connection(),
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Redis key of the sorted set backing this leaderboard
+    pub fn key(&self) -> &str {
+        &self.key
+    }
+
+    fn archive_key(&self, archive: &str) -> String {
+        format!("{}:archive:{}", self.key, archive)
+    }
+
+    /// Archived season `name` on its own sorted set; `None` if no such
+    /// archive exists (or the season ended empty)
+    pub async fn get_archive(&self, name: &str) -> Result<Option<RedisLeaderboardArchive>, ApiError> {
+        let mut conn = self.conn.clone();
+        let key = self.archive_key(name);
+
+        let exists: bool = conn
+            .exists(&key)
+            .await
+            .map_err(|e| ApiError::cache(format!("Failed to read leaderboard archive: {}", e)))?;
+
+        Ok(exists.then(|| {
+            RedisLeaderboardArchive(RedisLeaderboard {
+                name: name.to_string(),
+                key,
+                conn: self.conn.clone(),
+            })
+        }))
+    }
+
+    fn to_entries(start: usize, members: Vec<(String, i64)>) -> Vec<LeaderboardEntry> {
+        members
+            .into_iter()
+            .enumerate()
+            .map(|(i, (player_id, score))| LeaderboardEntry {
+                player_id,
+                score,
+                rank: start + i + 1,
+            })
+            .collect()
+    }
+}
+
+#[async_trait]
+impl LeaderboardBackend for RedisLeaderboard {
+    async fn update_score(&self, player_id: &str, score: i64) -> Result<(), ApiError> {
+        let mut conn = self.conn.clone();
+
+        conn.zadd::<_, _, _, ()>(&self.key, player_id, score)
+            .await
+            .map_err(|e| ApiError::cache(format!("Failed to update score: {}", e)))
+    }
+
+    async fn increment_score(&self, player_id: &str, delta: i64) -> Result<i64, ApiError> {
+        let mut conn = self.conn.clone();
+
+        // Sorted set scores are doubles; `as` saturates at the i64 bounds
+        let score: f64 = conn
+            .zincr(&self.key, player_id, delta)
+            .await
+            .map_err(|e| ApiError::cache(format!("Failed to increment score: {}", e)))?;
+        Ok(score as i64)
+    }
+
+    async fn get_top(&self, limit: usize) -> Result<Vec<LeaderboardEntry>, ApiError> {
+        self.get_range(0, limit).await
+    }
+
+    async fn get_range(&self, offset: usize, limit: usize) -> Result<Vec<LeaderboardEntry>, ApiError> {
+        if limit == 0 {
+            return Ok(Vec::new());
+        }
+
+        let mut conn = self.conn.clone();
+        let stop = (offset + limit - 1) as isize;
+
+        let members: Vec<(String, i64)> = conn
+            .zrevrange_withscores(&self.key, offset as isize, stop)
+            .await
+            .map_err(|e| ApiError::cache(format!("Failed to read leaderboard: {}", e)))?;
+
+        Ok(Self::to_entries(offset, members))
+    }
+
+    async fn get_player_rank(&self, player_id: &str) -> Result<Option<LeaderboardEntry>, ApiError> {
+        let mut conn = self.conn.clone();
+
+        let (rank, score): (Option<usize>, Option<i64>) = redis::pipe()
+            .zrevrank(&self.key, player_id)
+            .zscore(&self.key, player_id)
+            .query_async(&mut conn)
+            .await
+            .map_err(|e| ApiError::cache(format!("Failed to read player rank: {}", e)))?;
+
+        Ok(match (rank, score) {
+            (Some(rank), Some(score)) => Some(LeaderboardEntry {
+                player_id: player_id.to_string(),
+                score,
+                rank: rank + 1,
+            }),
+            _ => None,
+        })
+    }
+
+    /// Rename of the sorted set in one script, so archiving is atomic and
+    /// never overwrites a past season; an empty board leaves no archive
+    async fn reset(&self, archive_as: &str) -> Result<(), ApiError> {
+        let mut conn = self.conn.clone();
+
+        let result: i64 = redis::Script::new(RESET_SCRIPT)
+            .key(&self.key)
+            .key(self.archive_key(archive_as))
+            .invoke_async(&mut conn)
+            .await
+            .map_err(|e| ApiError::cache(format!("Failed to reset leaderboard: {}", e)))?;
+        if result < 0 {
+            return Err(ApiError::Conflict {
+                message: format!("Leaderboard archive {} already exists", archive_as),
+                field: None,
+            });
+        }
+        Ok(())
+    }
+}
+
+/// Read-only view of an archived season, from [`RedisLeaderboard::get_archive`]
+#[derive(Clone)]
+pub struct RedisLeaderboardArchive(RedisLeaderboard);
+
+impl RedisLeaderboardArchive {
+    pub fn name(&self) -> &str {
+        self.0.name()
+    }
+
+    /// Redis key of the archived sorted set
+    pub fn key(&self) -> &str {
+        self.0.key()
+    }
+
+    pub async fn get_top(&self, limit: usize) -> Result<Vec<LeaderboardEntry>, ApiError> {
+        self.0.get_top(limit).await
+    }
+
+    /// Get a page of the archive, starting at the 0-based `offset`
+    pub async fn get_range(&self, offset: usize, limit: usize) -> Result<Vec<LeaderboardEntry>, ApiError> {
+        self.0.get_range(offset, limit).await
+    }
+
+    pub async fn get_player_rank(&self, player_id: &str) -> Result<Option<LeaderboardEntry>, ApiError> {
+        self.0.get_player_rank(player_id).await
+    }
+}
@@ -1,7 +1,11 @@
 use serde::{Deserialize, Serialize};
+use serde_json::Value;
 use std::collections::HashMap;
-use std::sync::{Arc, RwLock};
-use chrono::{DateTime, Utc};
+use std::sync::{Arc, Mutex, RwLock};
+use chrono::{DateTime, Duration, Utc};
+
+use crate::errors::ApiError;
+use super::backend::LeaderboardBackend;
 
 /// Game session status
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
@@ -24,16 +28,45 @@ pub struct GameSession {
     pub ended_at: Option<DateTime<Utc>>,
 }
 
+/// Single-use token letting a dropped player rejoin their session
+#[derive(Debug, Clone)]
+struct ReconnectToken {
+    session_id: String,
+    player_id: String,
+    expires_at: DateTime<Utc>,
+}
+
+/// In-game state of one session, behind its own lock
+#[derive(Debug, Default)]
+struct SessionState {
+    player_states: HashMap<String, Value>,
+    scores: HashMap<String, i64>,
+}
+
 /// Game session manager
+///
+/// Player state and scores live behind a per-session lock, so message
+/// handlers of different sessions don't contend with each other.
 #[derive(Clone)]
 pub struct GameSessionManager {
     sessions: Arc<RwLock<HashMap<String, GameSession>>>,
+    states: Arc<RwLock<HashMap<String, Arc<Mutex<SessionState>>>>>,
+    reconnect_tokens: Arc<RwLock<HashMap<String, ReconnectToken>>>,
+    reconnect_ttl: Duration,
 }
 
 impl GameSessionManager {
     pub fn new() -> Self {
+        Self::with_reconnect_ttl(Duration::minutes(2))
+    }
+
+    /// Create a manager whose reconnect tokens expire after `reconnect_ttl`
+    pub fn with_reconnect_ttl(reconnect_ttl: Duration) -> Self {
         Self {
             sessions: Arc::new(RwLock::new(HashMap::new())),
+            states: Arc::new(RwLock::new(HashMap::new())),
+            reconnect_tokens: Arc::new(RwLock::new(HashMap::new())),
+            reconnect_ttl,
         }
     }
 
@@ -48,6 +81,9 @@ impl GameSessionManager {
             ended_at: None,
         };
 
+        if let Ok(mut states) = self.states.write() {
+            states.insert(session_id.clone(), Arc::new(Mutex::new(SessionState::default())));
+        }
         if let Ok(mut sessions) = self.sessions.write() {
             sessions.insert(session_id.clone(), session);
         }
@@ -96,6 +132,170 @@ impl GameSessionManager {
             Vec::new()
         }
     }
+
+    /// Replace a player's in-game state; `false` unless the session is in
+    /// progress and the player is part of it
+    pub fn update_player_state(&self, session_id: &str, player_id: &str, state: Value) -> bool {
+        self.with_state(session_id, player_id, |session_state| {
+            session_state.player_states.insert(player_id.to_string(), state);
+        })
+        .is_some()
+    }
+
+    pub fn get_player_state(&self, session_id: &str, player_id: &str) -> Option<Value> {
+        let state = self.states.read().ok()?.get(session_id)?.clone();
+        let state = state.lock().ok()?;
+        state.player_states.get(player_id).cloned()
+    }
+
+    /// Add `delta` (may be negative) to a player's score; returns the new
+    /// total, or `None` unless the session is in progress and the player is
+    /// part of it
+    pub fn record_score(&self, session_id: &str, player_id: &str, delta: i64) -> Option<i64> {
+        self.with_state(session_id, player_id, |session_state| {
+            let score = session_state.scores.entry(player_id.to_string()).or_insert(0);
+            *score += delta;
+            *score
+        })
+    }
+
+    /// Final scores, highest first (ties keep the session's player order);
+    /// players who never scored get 0. Empty until the session has ended.
+    pub fn get_results(&self, session_id: &str) -> Vec<(String, i64)> {
+        let Some(session) = self.get_session(session_id) else {
+            return Vec::new();
+        };
+        if session.status != SessionStatus::Completed {
+            return Vec::new();
+        }
+
+        let scores = self
+            .states
+            .read()
+            .ok()
+            .and_then(|states| states.get(session_id).cloned())
+            .and_then(|state| state.lock().ok().map(|guard| guard.scores.clone()))
+            .unwrap_or_default();
+
+        let mut results: Vec<(String, i64)> = session
+            .players
+            .into_iter()
+            .map(|player_id| {
+                let score = scores.get(&player_id).copied().unwrap_or(0);
+                (player_id, score)
+            })
+            .collect();
+        // sort_by là stable nên giữ thứ tự người chơi khi hòa điểm
+        results.sort_by(|a, b| b.1.cmp(&a.1));
+        results
+    }
+
+    /// Add the final scores of an ended session to each player's score on
+    /// `leaderboard`; returns the session results that were added
+    pub async fn publish_results(
+        &self,
+        session_id: &str,
+        leaderboard: &dyn LeaderboardBackend,
+    ) -> Result<Vec<(String, i64)>, ApiError> {
+        let results = self.get_results(session_id);
+        for (player_id, score) in &results {
+            leaderboard.increment_score(player_id, *score).await?;
+        }
+        Ok(results)
+    }
+
+    /// Run `f` on the session's state while holding its lock
+    ///
+    /// The sessions read lock is held too, so `end_session` waits for
+    /// in-flight updates and no score lands after the session has ended.
+    fn with_state<T>(
+        &self,
+        session_id: &str,
+        player_id: &str,
+        f: impl FnOnce(&mut SessionState) -> T,
+    ) -> Option<T> {
+        let sessions = self.sessions.read().ok()?;
+        let session = sessions.get(session_id)?;
+        let is_playing = session.players.iter().any(|p| p == player_id);
+        if session.status != SessionStatus::InProgress || !is_playing {
+            return None;
+        }
+
+        let state = self.states.read().ok()?.get(session_id)?.clone();
+        let mut state = state.lock().ok()?;
+        Some(f(&mut state))
+    }
+
+    /// Issue a reconnect token for a player of an active session
+    pub fn issue_reconnect_token(&self, session_id: &str, player_id: &str) -> Option<String> {
+        let is_member = self
+            .get_session(session_id)
+            .map(|s| Self::is_active(&s) && s.players.iter().any(|p| p == player_id))
+            .unwrap_or(false);
+
+        if !is_member {
+            return None;
+        }
+
+        let token = uuid::Uuid::new_v4().to_string();
+        let reconnect_token = ReconnectToken {
+            session_id: session_id.to_string(),
+            player_id: player_id.to_string(),
+            expires_at: Utc::now() + self.reconnect_ttl,
+        };
+
+        if let Ok(mut tokens) = self.reconnect_tokens.write() {
+            tokens.insert(token.clone(), reconnect_token);
+            Some(token)
+        } else {
+            None
+        }
+    }
+
+    /// Consume a reconnect token and return the session to rejoin.
+    ///
+    /// Tokens are single-use: the token is invalidated even if it has expired
+    /// or the session has since ended.
+    pub fn reconnect(&self, token: &str) -> Option<GameSession> {
+        let reconnect_token = self.reconnect_tokens.write().ok()?.remove(token)?;
+
+        if Utc::now() >= reconnect_token.expires_at {
+            return None;
+        }
+
+        self.get_session(&reconnect_token.session_id).filter(|s| {
+            Self::is_active(s) && s.players.contains(&reconnect_token.player_id)
+        })
+    }
+
+    /// Remove sessions that ended more than `max_age` ago, returning how many were removed
+    pub fn cleanup_stale(&self, max_age: Duration) -> usize {
+        let cutoff = Utc::now() - max_age;
+
+        let removed = if let Ok(mut sessions) = self.sessions.write() {
+            let before = sessions.len();
+            sessions.retain(|_, s| !s.ended_at.is_some_and(|ended_at| ended_at <= cutoff));
+            before - sessions.len()
+        } else {
+            0
+        };
+
+        if let (Ok(sessions), Ok(mut states)) = (self.sessions.read(), self.states.write()) {
+            states.retain(|session_id, _| sessions.contains_key(session_id));
+        }
+
+        // Drop expired tokens and tokens pointing at removed sessions
+        if let (Ok(sessions), Ok(mut tokens)) = (self.sessions.read(), self.reconnect_tokens.write()) {
+            let now = Utc::now();
+            tokens.retain(|_, t| t.expires_at > now && sessions.contains_key(&t.session_id));
+        }
+
+        removed
+    }
+
+    fn is_active(session: &GameSession) -> bool {
+        session.status == SessionStatus::InProgress || session.status == SessionStatus::Waiting
+    }
 }
 
 impl Default for GameSessionManager {
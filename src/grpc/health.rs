@@ -0,0 +1,194 @@
+// gRPC health checking protocol (grpc.health.v1.Health)
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use tonic_health::server::{health_reporter, Health, HealthReporter, HealthServer};
+use tonic_health::ServingStatus;
+
+use crate::health::{DependencyStatus, HealthCheckable};
+
+/// Khoảng thời gian giữa hai lần chạy lại dependency checks
+pub const GRPC_HEALTH_POLL_INTERVAL: Duration = Duration::from_secs(5);
+
+/// `grpc.health.v1.Health` backed by the readiness probe's `HealthCheckable`s
+///
+/// The overall service (`""`) reports `SERVING` exactly when `/health/ready`
+/// would return 200; each dependency is also exposed under its own name.
+/// `Watch` streams receive a new status whenever [`refresh`](Self::refresh)
+/// sees a change.
+#[derive(Clone)]
+pub struct GrpcHealth {
+    reporter: HealthReporter,
+    checks: Vec<Arc<dyn HealthCheckable>>,
+    /// Trạng thái đã publish, để chỉ gửi khi có thay đổi
+    published: Arc<Mutex<HashMap<String, ServingStatus>>>,
+}
+
+impl GrpcHealth {
+    /// The reporter and the tonic service to add to a `Server`
+    pub fn new(checks: Vec<Arc<dyn HealthCheckable>>) -> (Self, HealthServer<impl Health>) {
+        let (reporter, service) = health_reporter();
+        let health = Self {
+            reporter,
+            checks,
+            published: Arc::new(Mutex::new(HashMap::new())),
+        };
+        (health, service)
+    }
+
+    /// Run every check once and publish the resulting statuses
+    pub async fn refresh(&self) -> DependencyStatus {
+        let status = DependencyStatus::collect(&self.checks).await;
+
+        let mut current: Vec<(String, ServingStatus)> = status
+            .dependencies
+            .iter()
+            .map(|(name, result)| (name.clone(), serving_status(!result.is_unhealthy())))
+            .collect();
+        current.push((String::new(), serving_status(status.is_ready())));
+
+        let changed: Vec<(String, ServingStatus)> = {
+            let mut published = self.published.lock().unwrap_or_else(|e| e.into_inner());
+            current
+                .into_iter()
+                .filter(|(name, serving)| published.insert(name.clone(), *serving) != Some(*serving))
+                .collect()
+        };
+
+        let mut reporter = self.reporter.clone();
+        for (name, serving) in changed {
+            reporter.set_service_status(name, serving).await;
+        }
+
+        status
+    }
+
+    /// Refresh now and then every `interval` on the current tokio runtime
+    pub fn start(&self, interval: Duration) -> tokio::task::JoinHandle<()> {
+        let health = self.clone();
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(interval);
+            loop {
+                interval.tick().await;
+                health.refresh().await;
+            }
+        })
+    }
+}
+
+fn serving_status(serving: bool) -> ServingStatus {
+    if serving {
+        ServingStatus::Serving
+    } else {
+        ServingStatus::NotServing
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::health::CheckResult;
+    use async_trait::async_trait;
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use tonic_health::pb::health_check_response::ServingStatus as ProtoStatus;
+    use tonic_health::pb::health_client::HealthClient;
+    use tonic_health::pb::HealthCheckRequest;
+
+    struct Toggle {
+        healthy: AtomicBool,
+    }
+
+    #[async_trait]
+    impl HealthCheckable for Toggle {
+        fn name(&self) -> &str {
+            "database"
+        }
+
+        async fn check(&self) -> CheckResult {
+            if self.healthy.load(Ordering::SeqCst) {
+                CheckResult::ok(1)
+            } else {
+                CheckResult::unhealthy("connection refused".to_string())
+            }
+        }
+    }
+
+    /// Serve `service` on a random local port and connect a client to it
+    async fn connect(
+        service: HealthServer<impl Health>,
+    ) -> HealthClient<tonic::transport::Channel> {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let incoming = futures_util::stream::unfold(listener, |listener| async move {
+            let stream = listener.accept().await.map(|(stream, _)| stream);
+            Some((stream, listener))
+        });
+
+        tokio::spawn(
+            tonic::transport::Server::builder()
+                .add_service(service)
+                .serve_with_incoming(incoming),
+        );
+
+        HealthClient::connect(format!("http://{}", addr)).await.unwrap()
+    }
+
+    async fn check(client: &mut HealthClient<tonic::transport::Channel>, service: &str) -> ProtoStatus {
+        let response = client
+            .check(HealthCheckRequest {
+                service: service.to_string(),
+            })
+            .await
+            .unwrap();
+        response.into_inner().status()
+    }
+
+    #[tokio::test]
+    async fn test_check_follows_dependency_health() {
+        let toggle = Arc::new(Toggle {
+            healthy: AtomicBool::new(true),
+        });
+        let (health, service) = GrpcHealth::new(vec![toggle.clone()]);
+        let mut client = connect(service).await;
+
+        health.refresh().await;
+        assert_eq!(check(&mut client, "").await, ProtoStatus::Serving);
+        assert_eq!(check(&mut client, "database").await, ProtoStatus::Serving);
+
+        toggle.healthy.store(false, Ordering::SeqCst);
+        health.refresh().await;
+        assert_eq!(check(&mut client, "").await, ProtoStatus::NotServing);
+        assert_eq!(check(&mut client, "database").await, ProtoStatus::NotServing);
+    }
+
+    #[tokio::test]
+    async fn test_watch_pushes_status_changes() {
+        let toggle = Arc::new(Toggle {
+            healthy: AtomicBool::new(true),
+        });
+        let (health, service) = GrpcHealth::new(vec![toggle.clone()]);
+        let mut client = connect(service).await;
+        health.refresh().await;
+
+        let mut updates = client
+            .watch(HealthCheckRequest {
+                service: String::new(),
+            })
+            .await
+            .unwrap()
+            .into_inner();
+        let first = updates.message().await.unwrap().unwrap();
+        assert_eq!(first.status(), ProtoStatus::Serving);
+
+        toggle.healthy.store(false, Ordering::SeqCst);
+        health.refresh().await;
+        let next = tokio::time::timeout(Duration::from_secs(5), updates.message())
+            .await
+            .expect("no status pushed")
+            .unwrap()
+            .unwrap();
+        assert_eq!(next.status(), ProtoStatus::NotServing);
+    }
+}
@@ -7,7 +7,9 @@
 // Example implementation would go here
 // This is a placeholder for gRPC integration
 
+pub mod health;
 pub mod user_service;
 
+pub use health::{GrpcHealth, GRPC_HEALTH_POLL_INTERVAL};
 pub use user_service::UserServiceImpl;
 
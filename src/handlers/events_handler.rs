@@ -0,0 +1,73 @@
+use actix_web::http::header::{self, CacheControl, CacheDirective};
+use actix_web::{web, HttpRequest, HttpResponse};
+use bytes::Bytes;
+use futures_util::stream::{self, StreamExt};
+use std::convert::Infallible;
+use std::time::Duration;
+
+use crate::errors::ApiError;
+use crate::websocket::{EventBus, StreamEvent};
+
+/// Khoảng cách mặc định giữa các keep-alive comment
+pub const DEFAULT_SSE_KEEP_ALIVE: Duration = Duration::from_secs(15);
+
+/// Options for `GET /events/stream`, registered as `web::Data`
+#[derive(Debug, Clone, Copy)]
+pub struct EventStreamConfig {
+    /// Send a `: keep-alive` comment this often so proxies don't drop idle
+    /// connections
+    pub keep_alive: Duration,
+}
+
+impl Default for EventStreamConfig {
+    fn default() -> Self {
+        Self {
+            keep_alive: DEFAULT_SSE_KEEP_ALIVE,
+        }
+    }
+}
+
+/// GET /events/stream - Server-Sent Events từ `EventBus`
+///
+/// Each event carries its bus id, so a reconnecting client (`Last-Event-ID`
+/// header) first receives the retained events it missed.
+pub async fn event_stream(
+    req: HttpRequest,
+    events: web::Data<EventBus>,
+    config: Option<web::Data<EventStreamConfig>>,
+) -> Result<HttpResponse, ApiError> {
+    let last_event_id = req
+        .headers()
+        .get("Last-Event-ID")
+        .map(|value| {
+            value
+                .to_str()
+                .ok()
+                .and_then(|id| id.trim().parse::<u64>().ok())
+                .ok_or_else(|| ApiError::bad_request("Invalid Last-Event-ID header"))
+        })
+        .transpose()?;
+    let keep_alive = config.map(|c| c.keep_alive).unwrap_or(DEFAULT_SSE_KEEP_ALIVE);
+
+    let events = events.subscribe(last_event_id).map(|event| encode_event(&event));
+    let comments = stream::unfold(
+        tokio::time::interval_at(tokio::time::Instant::now() + keep_alive, keep_alive),
+        |mut interval| async move {
+            interval.tick().await;
+            Some((Bytes::from_static(b": keep-alive\n\n"), interval))
+        },
+    );
+
+    Ok(HttpResponse::Ok()
+        .content_type("text/event-stream")
+        .insert_header(CacheControl(vec![CacheDirective::NoCache]))
+        // Tắt buffering của nginx để event tới client ngay
+        .insert_header((header::HeaderName::from_static("x-accel-buffering"), "no"))
+        .streaming(stream::select(events, comments).map(Ok::<_, Infallible>)))
+}
+
+fn encode_event(event: &StreamEvent) -> Bytes {
+    // serde_json không xuống dòng nên data nằm gọn trên một dòng
+    let data = serde_json::to_string(&event.message).unwrap_or_else(|_| "{}".to_string());
+    Bytes::from(format!("id: {}\ndata: {}\n\n", event.id, data))
+}
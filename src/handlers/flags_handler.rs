@@ -0,0 +1,148 @@
+use actix_web::{web, HttpResponse};
+use serde::Deserialize;
+use serde_json::json;
+use std::sync::Arc;
+
+use crate::auth::AuthenticatedUser;
+use crate::errors::ApiError;
+use crate::features::{FeatureFlag, FeatureFlagManager};
+use crate::models::ApiResponse;
+use crate::security::{AuditEvent, AuditEventType, AuditLogger, AuditSeverity};
+use crate::utils::ApiJson;
+
+/// Permission để xem `GET /admin/flags`
+pub const FLAGS_READ_PERMISSION: &str = "flags:read";
+
+/// Permission để sửa/xóa flag
+pub const FLAGS_WRITE_PERMISSION: &str = "flags:write";
+
+/// Flags managed by `/admin/flags`, registered as `web::Data`
+///
+/// `flags` should be a clone of the manager in `AppState::feature_flags`, so
+/// changes apply to the flags the rest of the app reads.
+#[derive(Clone)]
+pub struct FeatureFlagsState {
+    pub flags: FeatureFlagManager,
+    /// Every change is logged as `ConfigurationChange`
+    pub audit: Arc<AuditLogger>,
+}
+
+/// Body of `PUT /admin/flags/{name}`; omitted fields keep their current value
+#[derive(Debug, Deserialize)]
+pub struct UpdateFlagRequest {
+    pub enabled: Option<bool>,
+    pub rollout_percentage: Option<u8>,
+    pub description: Option<String>,
+}
+
+/// GET /admin/flags - Danh sách feature flag, sắp xếp theo tên
+pub async fn list_flags(
+    user: AuthenticatedUser,
+    state: web::Data<FeatureFlagsState>,
+) -> Result<HttpResponse, ApiError> {
+    if !user.has_permission(FLAGS_READ_PERMISSION) {
+        return Err(ApiError::forbidden("Missing permission flags:read"));
+    }
+
+    let mut flags = state.flags.list_flags();
+    flags.sort_by(|a, b| a.name.cmp(&b.name));
+
+    Ok(HttpResponse::Ok().json(ApiResponse::success(
+        "Feature flags retrieved successfully",
+        json!({
+            "flags": flags,
+            "count": flags.len(),
+        }),
+    )))
+}
+
+/// PUT /admin/flags/{name} - Tạo hoặc cập nhật flag
+///
+/// A new flag defaults to disabled with a 100% rollout.
+pub async fn update_flag(
+    user: AuthenticatedUser,
+    state: web::Data<FeatureFlagsState>,
+    name: web::Path<String>,
+    body: ApiJson<UpdateFlagRequest>,
+) -> Result<HttpResponse, ApiError> {
+    if !user.has_permission(FLAGS_WRITE_PERMISSION) {
+        return Err(ApiError::forbidden("Missing permission flags:write"));
+    }
+
+    let name = name.into_inner();
+    let body = body.into_inner();
+    if body.rollout_percentage.is_some_and(|p| p > 100) {
+        return Err(ApiError::validation_field(
+            "rollout_percentage must be between 0 and 100",
+            "rollout_percentage",
+        ));
+    }
+
+    let (previous, flag) = state
+        .flags
+        .update_flag(&name, |current| {
+            let mut flag = current.cloned().unwrap_or_else(|| FeatureFlag {
+                name: name.clone(),
+                enabled: false,
+                description: String::new(),
+                rollout_percentage: 100,
+            });
+            if let Some(enabled) = body.enabled {
+                flag.enabled = enabled;
+            }
+            if let Some(rollout_percentage) = body.rollout_percentage {
+                flag.rollout_percentage = rollout_percentage;
+            }
+            if let Some(description) = body.description {
+                flag.description = description;
+            }
+            flag
+        })
+        .await?;
+
+    let action = if previous.is_some() { "feature_flag.update" } else { "feature_flag.create" };
+    state.audit.log(
+        audit_event(&user, &name, action)
+            .with_metadata("before".to_string(), flag_json(previous.as_ref()))
+            .with_metadata("after".to_string(), flag_json(Some(&flag))),
+    );
+
+    Ok(HttpResponse::Ok().json(ApiResponse::success("Feature flag updated", flag)))
+}
+
+/// DELETE /admin/flags/{name} - Xóa flag cùng override và targeting rule
+pub async fn delete_flag(
+    user: AuthenticatedUser,
+    state: web::Data<FeatureFlagsState>,
+    name: web::Path<String>,
+) -> Result<HttpResponse, ApiError> {
+    if !user.has_permission(FLAGS_WRITE_PERMISSION) {
+        return Err(ApiError::forbidden("Missing permission flags:write"));
+    }
+
+    let name = name.into_inner();
+    let previous = state
+        .flags
+        .delete_flag(&name)
+        .await?
+        .ok_or_else(|| ApiError::not_found_resource(format!("Feature flag {} not found", name), "feature_flag"))?;
+
+    state.audit.log(
+        audit_event(&user, &name, "feature_flag.delete")
+            .with_metadata("before".to_string(), flag_json(Some(&previous))),
+    );
+
+    Ok(HttpResponse::NoContent().finish())
+}
+
+fn audit_event(user: &AuthenticatedUser, name: &str, action: &str) -> AuditEvent {
+    AuditEvent::new(AuditEventType::ConfigurationChange, action.to_string())
+        .with_user(user.sub.clone())
+        .with_resource(format!("feature_flag:{}", name))
+        .with_severity(AuditSeverity::Warning)
+}
+
+/// `null` khi flag chưa tồn tại
+fn flag_json(flag: Option<&FeatureFlag>) -> String {
+    serde_json::to_string(&flag).unwrap_or_default()
+}
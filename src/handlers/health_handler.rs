@@ -2,11 +2,12 @@ use actix_web::{web, HttpResponse, Responder};
 use chrono::Utc;
 use serde::{Deserialize, Serialize};
 use serde_json::json;
-use crate::config::Settings;
+use crate::config::settings::ApplicationSettings;
 use crate::models::ApiResponse;
 use crate::state::AppState;
-use std::env;
-use std::time::Instant;
+use std::time::Duration;
+
+pub use crate::health::{CheckResult, DependencyStatus};
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct HealthStatus {
@@ -24,57 +25,20 @@ pub struct ServiceInfo {
     pub environment: String,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
-pub struct DependencyStatus {
-    pub database: CheckResult,
-    pub cache: CheckResult,
-    pub overall: String,
-}
-
-#[derive(Debug, Serialize, Deserialize)]
-pub struct CheckResult {
-    pub status: String,
-    pub response_time_ms: Option<u64>,
-    pub message: Option<String>,
-}
-
-impl CheckResult {
-    pub fn ok(response_time_ms: u64) -> Self {
-        Self {
-            status: "healthy".to_string(),
-            response_time_ms: Some(response_time_ms),
-            message: None,
-        }
-    }
-
-    pub fn degraded(response_time_ms: u64, message: String) -> Self {
-        Self {
-            status: "degraded".to_string(),
-            response_time_ms: Some(response_time_ms),
-            message: Some(message),
-        }
-    }
-
-    pub fn unhealthy(message: String) -> Self {
-        Self {
-            status: "unhealthy".to_string(),
-            response_time_ms: None,
-            message: Some(message),
-        }
-    }
-
-    pub fn not_configured() -> Self {
-        Self {
-            status: "not_configured".to_string(),
-            response_time_ms: None,
-            message: Some("Dependency not configured".to_string()),
-        }
-    }
-}
-
 /// Health check endpoint với thông tin chi tiết
-pub async fn health_check() -> impl Responder {
-    let settings = Settings::from_env();
+#[cfg_attr(feature = "docs", utoipa::path(
+    get,
+    path = "/health",
+    tag = "health",
+    responses((status = 200, description = "Service info, build metadata and uptime"))
+))]
+pub async fn health_check(state: web::Data<AppState>) -> impl Responder {
+    // Chỉ đọc phần application: config khác sai không được làm hỏng /health
+    let application = ApplicationSettings::from_env();
+    let uptime = state.uptime();
+    let started_at = chrono::Duration::from_std(uptime)
+        .map(|uptime| Utc::now() - uptime)
+        .unwrap_or_else(|_| Utc::now());
 
     HttpResponse::Ok().json(ApiResponse::success(
         "API is running",
@@ -82,134 +46,105 @@ pub async fn health_check() -> impl Responder {
             "status": "healthy",
             "timestamp": Utc::now(),
             "service": {
-                "name": settings.application.name,
+                "name": application.name,
+                "version": env!("CARGO_PKG_VERSION"),
+                "environment": application.environment,
+            },
+            "build": {
                 "version": env!("CARGO_PKG_VERSION"),
-                "environment": settings.application.environment,
+                // Set GIT_SHA at build time (e.g. `docker build --build-arg GIT_SHA=...`)
+                "git_sha": option_env!("GIT_SHA").unwrap_or("unknown"),
             },
-            "uptime": "operational",
+            "started_at": started_at,
+            "uptime": format_uptime(uptime),
+            "uptime_seconds": uptime.as_secs_f64(),
         }),
     ))
 }
 
-/// Readiness check - Kiểm tra dependencies (database, cache, etc.)
-pub async fn readiness_check(state: web::Data<AppState>) -> impl Responder {
-    let mut checks = DependencyStatus {
-        database: CheckResult::not_configured(),
-        cache: CheckResult::not_configured(),
-        overall: "healthy".to_string(),
-    };
-
-    // Check database if configured
-    #[cfg(feature = "database-postgres")]
-    {
-        let start = Instant::now();
-        match check_database(&state).await {
-            Ok(_) => {
-                let elapsed = start.elapsed().as_millis() as u64;
-                checks.database = if elapsed > 1000 {
-                    CheckResult::degraded(elapsed, "Slow response".to_string())
-                } else {
-                    CheckResult::ok(elapsed)
-                };
-            }
-            Err(e) => {
-                checks.database = CheckResult::unhealthy(e);
-                checks.overall = "unhealthy".to_string();
-            }
-        }
+/// Uptime dạng `3d 4h 5m 6s` (bỏ các đơn vị lớn bằng 0)
+fn format_uptime(uptime: Duration) -> String {
+    let secs = uptime.as_secs();
+    let (days, hours, minutes, seconds) = (secs / 86_400, secs / 3_600 % 24, secs / 60 % 60, secs % 60);
+
+    if days > 0 {
+        format!("{}d {}h {}m {}s", days, hours, minutes, seconds)
+    } else if hours > 0 {
+        format!("{}h {}m {}s", hours, minutes, seconds)
+    } else if minutes > 0 {
+        format!("{}m {}s", minutes, seconds)
+    } else {
+        format!("{}s", seconds)
     }
+}
 
-    // Check cache if configured
-    #[cfg(feature = "cache-redis")]
-    {
-        let start = Instant::now();
-        match check_cache(&state).await {
-            Ok(_) => {
-                let elapsed = start.elapsed().as_millis() as u64;
-                checks.cache = if elapsed > 500 {
-                    CheckResult::degraded(elapsed, "Slow response".to_string())
-                } else {
-                    CheckResult::ok(elapsed)
-                };
-            }
-            Err(e) => {
-                checks.cache = CheckResult::unhealthy(e);
-                if checks.overall != "unhealthy" {
-                    checks.overall = "degraded".to_string();
-                }
-            }
-        }
-    }
+/// Readiness check - Kiểm tra tất cả dependencies đã đăng ký trong AppState
+#[cfg_attr(feature = "docs", utoipa::path(
+    get,
+    path = "/health/ready",
+    tag = "health",
+    responses(
+        (status = 200, description = "All critical dependencies are healthy"),
+        (status = 503, description = "A critical dependency is unhealthy"),
+    )
+))]
+pub async fn readiness_check(state: web::Data<AppState>) -> impl Responder {
+    let checks = DependencyStatus::collect(&state.health_checks).await;
 
-    let status_code = match checks.overall.as_str() {
-        "healthy" => 200,
-        "degraded" => 200,
-        _ => 503,
-    };
+    let status_code = if checks.is_ready() { 200 } else { 503 };
 
     HttpResponse::build(actix_web::http::StatusCode::from_u16(status_code).unwrap())
         .json(ApiResponse::success(
             "Readiness check completed",
             json!({
-                "ready": checks.overall == "healthy" || checks.overall == "degraded",
+                "ready": checks.is_ready(),
                 "checks": checks,
             }),
         ))
 }
 
 /// Liveness check - Kiểm tra process còn sống
-pub async fn liveness_check() -> impl Responder {
-    HttpResponse::Ok().json(json!({
-        "alive": true,
-        "timestamp": Utc::now(),
-    }))
-}
-
-// Helper functions for dependency checks
-
-#[cfg(feature = "database-postgres")]
-async fn check_database(state: &AppState) -> Result<(), String> {
-    use sqlx::Row;
-
-    let pool = state.db_pool.as_ref()
-        .ok_or_else(|| "Database pool not initialized".to_string())?;
-
-    // Simple query to check database connectivity
-    let result = sqlx::query("SELECT 1 as health_check")
-        .fetch_one(pool)
-        .await
-        .map_err(|e| format!("Database error: {}", e))?;
+///
+/// Với watchdog trong `AppState`, trả về `503` khi tick đã quá hạn.
+#[cfg_attr(feature = "docs", utoipa::path(
+    get,
+    path = "/health/live",
+    tag = "health",
+    responses(
+        (status = 200, description = "Process is alive"),
+        (status = 503, description = "Runtime watchdog is stale (runtime wedged)"),
+    )
+))]
+pub async fn liveness_check(state: web::Data<AppState>) -> impl Responder {
+    let Some(watchdog) = &state.watchdog else {
+        return HttpResponse::Ok().json(ApiResponse::success(
+            "Service is alive",
+            json!({
+                "alive": true,
+                "timestamp": Utc::now(),
+            }),
+        ));
+    };
 
-    let value: i32 = result.get("health_check");
-    if value == 1 {
-        Ok(())
+    let alive = watchdog.is_alive();
+    let (status, message) = if alive {
+        (actix_web::http::StatusCode::OK, "Service is alive")
     } else {
-        Err("Database health check failed".to_string())
-    }
-}
-
-#[cfg(not(feature = "database-postgres"))]
-async fn check_database(_state: &AppState) -> Result<(), String> {
-    Ok(())
-}
-
-#[cfg(feature = "cache-redis")]
-async fn check_cache(state: &AppState) -> Result<(), String> {
-    let cache_manager = state.cache_manager.as_ref()
-        .ok_or_else(|| "Cache manager not initialized".to_string())?;
-
-    let mut conn = cache_manager.get_connection();
-
-    // Simple PING command to check Redis connectivity
-    let _: String = redis::cmd("PING")
-        .query_async(&mut conn)
-        .await
-        .map_err(|e| format!("Cache error: {}", e))?;
-
-    Ok(())
-}
+        tracing::error!(
+            tick_age_ms = watchdog.tick_age().as_millis() as u64,
+            "Liveness watchdog is stale"
+        );
+        (actix_web::http::StatusCode::SERVICE_UNAVAILABLE, "Runtime watchdog is stale")
+    };
 
-#[cfg(not(feature = "cache-redis"))]
-async fn check_cache(_state: &AppState) -> Result<(), String> {
-    Ok(())
+    HttpResponse::build(status).json(ApiResponse::success(
+        message,
+        json!({
+            "alive": alive,
+            "timestamp": Utc::now(),
+            "last_tick": watchdog.last_tick(),
+            "tick_age_ms": watchdog.tick_age().as_millis() as u64,
+            "stale_after_ms": watchdog.stale_after().as_millis() as u64,
+        }),
+    ))
 }
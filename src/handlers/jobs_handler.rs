@@ -0,0 +1,94 @@
+use actix_web::{web, HttpResponse};
+use serde::Serialize;
+use serde_json::json;
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+use crate::auth::AuthenticatedUser;
+use crate::errors::ApiError;
+use crate::jobs::{JobExecutor, JobMetadata, JobScheduler, JobStatus};
+use crate::models::ApiResponse;
+
+/// Permission cần có để xem `GET /admin/jobs`
+pub const JOBS_READ_PERMISSION: &str = "jobs:read";
+
+/// Executor and scheduler shown by `GET /admin/jobs`, registered as `web::Data`
+#[derive(Clone)]
+pub struct JobsState {
+    pub executor: Arc<JobExecutor>,
+    pub scheduler: JobScheduler,
+}
+
+/// One job type: its latest run and, if scheduled, when it runs next
+#[derive(Debug, Serialize)]
+pub struct JobInfo {
+    pub name: String,
+    /// Status of the latest run; `None` if it has not run yet
+    pub status: Option<JobStatus>,
+    pub last_run_at: Option<String>,
+    pub last_duration_ms: Option<u64>,
+    pub next_run_at: Option<String>,
+    pub runs: usize,
+}
+
+/// GET /admin/jobs - Trạng thái các background job
+///
+/// Needs the `jobs:read` permission; the route must sit behind
+/// `AuthMiddleware`, otherwise every request gets 401.
+pub async fn list_jobs(
+    user: AuthenticatedUser,
+    state: web::Data<JobsState>,
+) -> Result<HttpResponse, ApiError> {
+    if !user.has_permission(JOBS_READ_PERMISSION) {
+        return Err(ApiError::forbidden("Missing permission jobs:read"));
+    }
+
+    let jobs = job_infos(&state.executor.list_jobs(), &state.scheduler);
+
+    Ok(HttpResponse::Ok().json(ApiResponse::success(
+        "Jobs retrieved successfully",
+        json!({
+            "jobs": jobs,
+            "count": jobs.len(),
+        }),
+    )))
+}
+
+/// Gộp lịch chạy và lịch sử chạy theo tên job, sắp xếp theo tên
+fn job_infos(runs: &[JobMetadata], scheduler: &JobScheduler) -> Vec<JobInfo> {
+    let mut jobs: BTreeMap<String, JobInfo> = BTreeMap::new();
+
+    // `runs` đã sắp xếp mới nhất trước
+    for run in runs {
+        let info = job_entry(&mut jobs, &run.job_type);
+        if info.runs == 0 {
+            info.status = Some(run.status.clone());
+            info.last_run_at = run.started_at.map(|dt| dt.to_rfc3339());
+            info.last_duration_ms = run.duration_ms;
+        }
+        info.runs += 1;
+    }
+
+    // Nhiều lịch cùng tên thì lấy lần chạy sớm nhất
+    let mut scheduled = scheduler.get_scheduled_jobs();
+    scheduled.sort_by_key(|job| job.next_run);
+    for job in scheduled {
+        let info = job_entry(&mut jobs, &job.name);
+        if info.next_run_at.is_none() {
+            info.next_run_at = Some(job.next_run.to_rfc3339());
+        }
+    }
+
+    jobs.into_values().collect()
+}
+
+fn job_entry<'a>(jobs: &'a mut BTreeMap<String, JobInfo>, name: &str) -> &'a mut JobInfo {
+    jobs.entry(name.to_string()).or_insert_with(|| JobInfo {
+        name: name.to_string(),
+        status: None,
+        last_run_at: None,
+        last_duration_ms: None,
+        next_run_at: None,
+        runs: 0,
+    })
+}
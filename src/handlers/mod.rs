@@ -1,5 +1,8 @@
 pub mod user_handler;
 pub mod health_handler;
+pub mod upload_handler;
+pub mod jobs_handler;
+pub mod flags_handler;
 
 #[cfg(feature = "auth-oauth2")]
 pub mod oauth2_handler;
@@ -7,11 +10,20 @@ pub mod oauth2_handler;
 #[cfg(feature = "auth-api-key")]
 pub mod api_key_handler;
 
+#[cfg(feature = "websocket")]
+pub mod events_handler;
+
 pub use user_handler::*;
 pub use health_handler::{health_check, readiness_check, liveness_check};
+pub use upload_handler::upload_files;
+pub use jobs_handler::{list_jobs, JobsState};
+pub use flags_handler::{delete_flag, list_flags, update_flag, FeatureFlagsState};
 
 #[cfg(feature = "auth-oauth2")]
 pub use oauth2_handler::{OAuth2State, configure_oauth2_routes, init_oauth2_config};
 
 #[cfg(feature = "auth-api-key")]
 pub use api_key_handler::{ApiKeyState, configure_api_key_routes};
+
+#[cfg(feature = "websocket")]
+pub use events_handler::{event_stream, EventStreamConfig};
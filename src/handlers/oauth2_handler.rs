@@ -1,13 +1,44 @@
 use actix_web::{web, HttpResponse, Responder};
 use serde::{Deserialize, Serialize};
 use serde_json::json;
+use std::sync::Arc;
 use crate::auth::oauth2::OAuth2Config;
-use crate::models::ApiResponse;
+use crate::auth::oauth2_state::{InMemoryStateStore, PendingAuthorization, StateStore};
+use crate::auth::JwtManager;
+use crate::models::{ApiResponse, LoginResponse, UserInfo};
 use crate::errors::ApiError;
+use crate::services::UserService;
+use crate::state::AppState;
 
 /// OAuth2 state with configuration
 pub struct OAuth2State {
     pub config: OAuth2Config,
+    pub state_store: Arc<dyn StateStore>,
+    pub jwt_manager: JwtManager,
+    /// See `OAuth2Settings::link_by_verified_email`
+    pub link_by_verified_email: bool,
+}
+
+impl OAuth2State {
+    /// Create state backed by an in-memory CSRF state store
+    pub fn new(config: OAuth2Config, jwt_manager: JwtManager) -> Self {
+        Self {
+            config,
+            state_store: Arc::new(InMemoryStateStore::new()),
+            jwt_manager,
+            link_by_verified_email: false,
+        }
+    }
+
+    pub fn with_state_store(mut self, state_store: Arc<dyn StateStore>) -> Self {
+        self.state_store = state_store;
+        self
+    }
+
+    pub fn with_email_linking(mut self, enabled: bool) -> Self {
+        self.link_by_verified_email = enabled;
+        self
+    }
 }
 
 /// Request to get authorization URL
@@ -26,12 +57,14 @@ pub struct OAuth2Callback {
 }
 
 /// OAuth2 callback request body
+///
+/// The PKCE verifier is looked up from the state store by `csrf_token`.
 #[derive(Debug, Deserialize)]
 pub struct OAuth2CallbackRequest {
     pub provider: String,
     pub code: String,
+    #[serde(default)]
     pub csrf_token: String,
-    pub pkce_verifier: Option<String>,
 }
 
 /// OAuth2 token response
@@ -62,27 +95,50 @@ pub async fn get_auth_url(
         .config
         .get_authorization_url(&req.provider, req.use_pkce)?;
 
+    // Lưu csrf_token -> pkce_verifier để verify ở callback
+    oauth2_state
+        .state_store
+        .put(
+            &auth_response.csrf_token,
+            PendingAuthorization {
+                provider: req.provider.clone(),
+                pkce_verifier: auth_response.pkce_verifier,
+            },
+        )
+        .await?;
+
     Ok(HttpResponse::Ok().json(ApiResponse::success(
         "Authorization URL generated",
         json!({
             "auth_url": auth_response.auth_url,
             "csrf_token": auth_response.csrf_token,
-            "pkce_verifier": auth_response.pkce_verifier,
         }),
     )))
 }
 
-/// Handle OAuth2 callback and exchange code for token
+/// Handle OAuth2 callback: exchange the code, upsert the user and issue a JWT
 pub async fn oauth2_callback(
     oauth2_state: web::Data<OAuth2State>,
+    app_state: web::Data<AppState>,
     req: web::Json<OAuth2CallbackRequest>,
 ) -> Result<impl Responder, ApiError> {
-    // TODO: Verify CSRF token (should be stored in session/cache)
-    
+    // Verify CSRF token: mỗi state chỉ dùng được một lần
+    if req.csrf_token.is_empty() {
+        return Err(ApiError::unauthorized("Missing OAuth2 state"));
+    }
+    let pending = oauth2_state
+        .state_store
+        .take(&req.csrf_token)
+        .await?
+        .ok_or_else(|| ApiError::unauthorized("Invalid, expired or already used OAuth2 state"))?;
+    if pending.provider != req.provider {
+        return Err(ApiError::unauthorized("OAuth2 state was issued for a different provider"));
+    }
+
     // Exchange code for access token
     let access_token = oauth2_state
         .config
-        .exchange_code(&req.provider, req.code.clone(), req.pkce_verifier.clone())
+        .exchange_code(&req.provider, req.code.clone(), pending.pkce_verifier)
         .await?;
 
     // Get user info
@@ -91,15 +147,29 @@ pub async fn oauth2_callback(
         .get_user_info(&req.provider, &access_token)
         .await?;
 
-    // TODO: Create or update user in database
-    // TODO: Generate JWT token for the user
-    
+    // Tạo hoặc cập nhật user
+    let user = UserService::upsert_oauth_user(
+        app_state.users.as_ref(),
+        &user_info,
+        oauth2_state.link_by_verified_email,
+    )
+    .await?;
+
+    // Phát hành JWT của ứng dụng (không trả về access token của provider)
+    let token = oauth2_state
+        .jwt_manager
+        .create_token_for(&user)?;
+
     Ok(HttpResponse::Ok().json(ApiResponse::success(
         "OAuth2 authentication successful",
-        json!({
-            "access_token": access_token,
-            "user_info": user_info,
-        }),
+        LoginResponse {
+            token,
+            user: UserInfo {
+                id: user.id,
+                email: user.email,
+                role: user.role,
+            },
+        },
     )))
 }
 
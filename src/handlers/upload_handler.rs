@@ -0,0 +1,209 @@
+use actix_multipart::{Field, Multipart, MultipartError};
+use actix_web::{web, HttpResponse};
+use futures_util::TryStreamExt;
+use std::sync::Arc;
+
+use crate::errors::ApiError;
+use crate::models::{ApiResponse, UploadedFile};
+use crate::services::StorageService;
+use crate::state::AppState;
+
+/// Kích thước tối đa của một file upload
+pub const MAX_UPLOAD_BYTES: usize = 10 * 1024 * 1024;
+
+/// Content types accepted by `POST /uploads`
+pub const ALLOWED_UPLOAD_CONTENT_TYPES: &[&str] = &[
+    "image/png",
+    "image/jpeg",
+    "image/gif",
+    "image/webp",
+    "application/pdf",
+    "text/plain",
+    "text/csv",
+];
+
+/// Prefix của object key cho file upload
+const UPLOAD_KEY_PREFIX: &str = "uploads";
+
+/// POST /uploads - Upload một hoặc nhiều file (multipart/form-data)
+///
+/// Mỗi field có filename được lưu vào `StorageService`; field thường bị bỏ
+/// qua. Nếu một file bị từ chối hoặc client ngắt kết nối giữa chừng, các file
+/// đã lưu trong request này sẽ bị xóa.
+#[cfg_attr(feature = "docs", utoipa::path(
+    post,
+    path = "/uploads",
+    tag = "uploads",
+    request_body(content_type = "multipart/form-data", description = "One or more file fields"),
+    responses(
+        (status = 201, description = "Files stored", body = ApiResponse<Vec<UploadedFile>>),
+        (status = 400, description = "Malformed multipart body", body = crate::errors::ErrorResponse),
+        (status = 413, description = "A file exceeds the size limit", body = crate::errors::ErrorResponse),
+        (status = 422, description = "No files, or a content type that is not allowed", body = crate::errors::ErrorResponse),
+        (status = 503, description = "Storage is not configured", body = crate::errors::ErrorResponse),
+    )
+))]
+pub async fn upload_files(
+    data: web::Data<AppState>,
+    payload: Multipart,
+) -> Result<HttpResponse, ApiError> {
+    let storage = data.storage.clone().ok_or_else(|| ApiError::ServiceUnavailable {
+        message: "File storage is not configured".to_string(),
+        retry_after: None,
+    })?;
+
+    let mut guard = UploadGuard::new(storage.clone());
+    match store_files(storage.as_ref(), payload, &mut guard).await {
+        Ok(files) if files.is_empty() => {
+            Err(ApiError::validation_field("No files were uploaded", "file"))
+        }
+        Ok(files) => {
+            guard.commit();
+            Ok(HttpResponse::Created().json(ApiResponse::success("Files uploaded successfully", files)))
+        }
+        Err(e) => {
+            guard.rollback().await;
+            Err(e)
+        }
+    }
+}
+
+async fn store_files(
+    storage: &dyn StorageService,
+    mut payload: Multipart,
+    guard: &mut UploadGuard,
+) -> Result<Vec<UploadedFile>, ApiError> {
+    let mut files = Vec::new();
+
+    while let Some(mut field) = payload.try_next().await.map_err(multipart_error)? {
+        let field_name = field.name().unwrap_or_default().to_string();
+        let Some(filename) = field
+            .content_disposition()
+            .and_then(|cd| cd.get_filename())
+            .map(String::from)
+        else {
+            // Field thường (không phải file): đọc bỏ
+            read_field(&mut field).await?;
+            continue;
+        };
+
+        let content_type = field
+            .content_type()
+            .map(|mime| mime.essence_str().to_string())
+            .unwrap_or_default();
+        if !ALLOWED_UPLOAD_CONTENT_TYPES.contains(&content_type.as_str()) {
+            return Err(ApiError::validation_field(
+                format!("Content type '{}' is not allowed for {}", content_type, filename),
+                "content_type",
+            ));
+        }
+
+        let body = read_field(&mut field).await?;
+        let size = body.len();
+        let key = format!(
+            "{}/{}-{}",
+            UPLOAD_KEY_PREFIX,
+            uuid::Uuid::new_v4(),
+            sanitize_filename(&filename)
+        );
+
+        storage.put_object(&key, body, Some(&content_type)).await?;
+        guard.track(key.clone());
+
+        files.push(UploadedFile {
+            field: field_name,
+            filename,
+            key,
+            size,
+            content_type,
+        });
+    }
+
+    Ok(files)
+}
+
+/// Đọc toàn bộ field, dừng ngay khi vượt `MAX_UPLOAD_BYTES`
+async fn read_field(field: &mut Field) -> Result<Vec<u8>, ApiError> {
+    let mut body = Vec::new();
+    while let Some(chunk) = field.try_next().await.map_err(multipart_error)? {
+        if body.len() + chunk.len() > MAX_UPLOAD_BYTES {
+            return Err(ApiError::payload_too_large(MAX_UPLOAD_BYTES));
+        }
+        body.extend_from_slice(&chunk);
+    }
+    Ok(body)
+}
+
+/// Chỉ giữ ký tự an toàn cho object key
+fn sanitize_filename(filename: &str) -> String {
+    let name = filename.rsplit(['/', '\\']).next().unwrap_or_default();
+    let sanitized: String = name
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() || matches!(c, '.' | '-' | '_') { c } else { '_' })
+        .collect();
+    let sanitized = sanitized.trim_start_matches('.');
+
+    if sanitized.is_empty() {
+        "file".to_string()
+    } else {
+        sanitized.to_string()
+    }
+}
+
+fn multipart_error(e: MultipartError) -> ApiError {
+    ApiError::bad_request(format!("Invalid multipart body: {}", e))
+}
+
+/// Xóa các object đã lưu nếu request không hoàn tất
+///
+/// `rollback` handles errors; `Drop` covers the handler future being dropped
+/// when the client disconnects mid-upload.
+struct UploadGuard {
+    storage: Arc<dyn StorageService>,
+    keys: Vec<String>,
+}
+
+impl UploadGuard {
+    fn new(storage: Arc<dyn StorageService>) -> Self {
+        Self {
+            storage,
+            keys: Vec::new(),
+        }
+    }
+
+    fn track(&mut self, key: String) {
+        self.keys.push(key);
+    }
+
+    fn commit(&mut self) {
+        self.keys.clear();
+    }
+
+    async fn rollback(&mut self) {
+        for key in std::mem::take(&mut self.keys) {
+            delete_partial(self.storage.as_ref(), &key).await;
+        }
+    }
+}
+
+impl Drop for UploadGuard {
+    fn drop(&mut self) {
+        if self.keys.is_empty() {
+            return;
+        }
+
+        let storage = self.storage.clone();
+        let keys = std::mem::take(&mut self.keys);
+        actix_web::rt::spawn(async move {
+            for key in keys {
+                delete_partial(storage.as_ref(), &key).await;
+            }
+        });
+    }
+}
+
+async fn delete_partial(storage: &dyn StorageService, key: &str) {
+    if let Err(e) = storage.delete_object(key).await {
+        tracing::warn!(key, error = %e, "Failed to delete partial upload");
+    }
+}
@@ -1,31 +1,257 @@
-use actix_web::{web, HttpResponse};
+use actix_web::{http::{header, StatusCode}, web, HttpRequest, HttpResponse};
+use futures_util::stream::{self, StreamExt};
+use std::collections::{HashMap, HashSet};
+use std::convert::Infallible;
+use std::future::ready;
 use crate::errors::ApiError;
-use crate::models::{CreateUserRequest, UpdateUserRequest, ApiResponse};
+use crate::models::{
+    CreateUserRequest, UpdateUserRequest, ApiResponse, BatchItemError, BatchResult, ListQuery,
+    PaginationQuery, User, USER_LIST_FIELDS,
+};
 use crate::services::UserService;
 use crate::state::AppState;
+use crate::utils::{
+    check_if_match, csv_field, if_none_match, weak_etag, ApiJson, CsvRecord, ParallelProcessor, ResponseFormat,
+};
 
-/// GET /users - Lấy tất cả người dùng
-pub async fn get_users(data: web::Data<AppState>) -> Result<HttpResponse, ApiError> {
-    let users = data.users.lock().unwrap();
-    Ok(HttpResponse::Ok().json(ApiResponse::success(
-        "Users retrieved successfully",
-        &*users,
-    )))
+/// Số record tối đa trong một request `POST /users/batch`
+pub const MAX_BATCH_SIZE: usize = 1000;
+
+/// Số insert chạy đồng thời khi import batch
+const BATCH_INSERT_CONCURRENCY: usize = 8;
+
+/// Parse `?include_deleted=true|false` (mặc định false)
+fn include_deleted(params: &HashMap<String, String>) -> Result<bool, ApiError> {
+    params
+        .get("include_deleted")
+        .map(|v| v.parse::<bool>())
+        .transpose()
+        .map(Option::unwrap_or_default)
+        .map_err(|_| ApiError::validation_field("include_deleted must be true or false", "include_deleted"))
+}
+
+/// GET /users?page=&per_page=&sort=&filter[field]=&include_deleted= - Lấy danh sách người dùng
+///
+/// JSON by default; `Accept: application/msgpack` or `text/csv` selects
+/// MessagePack or CSV (the page's users, total in `X-Total-Count`).
+#[cfg_attr(feature = "docs", utoipa::path(
+    get,
+    path = "/users",
+    tag = "users",
+    params(
+        ("page" = Option<u32>, Query, description = "Page number, starting at 1"),
+        ("per_page" = Option<u32>, Query, description = "Items per page (max 100)"),
+        ("sort" = Option<String>, Query, description = "Sort field, prefix with `-` for descending"),
+        ("include_deleted" = Option<bool>, Query, description = "Include soft-deleted users"),
+    ),
+    responses(
+        (status = 200, description = "Page of users", content(
+            (ApiResponse<crate::models::Paginated<User>> = "application/json"),
+            (ApiResponse<crate::models::Paginated<User>> = "application/msgpack"),
+            (String = "text/csv"),
+        )),
+        (status = 406, description = "Unsupported Accept", body = crate::errors::ErrorResponse),
+        (status = 422, description = "Invalid query parameters", body = crate::errors::ErrorResponse),
+    )
+))]
+pub async fn get_users(
+    req: HttpRequest,
+    data: web::Data<AppState>,
+    params: web::Query<HashMap<String, String>>,
+) -> Result<HttpResponse, ApiError> {
+    let pagination = PaginationQuery {
+        page: params.get("page").cloned(),
+        per_page: params.get("per_page").cloned(),
+    }
+    .resolve()?;
+    let list_query = ListQuery::from_params(&params, USER_LIST_FIELDS)?;
+    let format = ResponseFormat::negotiate(&req, true)?;
+
+    let include_deleted = include_deleted(&params)?;
+
+    let page = data.users.find_page(&list_query, pagination, include_deleted).await?;
+
+    format.respond_list(
+        &mut HttpResponse::Ok(),
+        &ApiResponse::success("Users retrieved successfully", page),
+    )
+}
+
+/// Định dạng của `GET /users/export`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ExportFormat {
+    Ndjson,
+    Csv,
+}
+
+impl ExportFormat {
+    fn from_params(params: &HashMap<String, String>) -> Result<Self, ApiError> {
+        match params.get("format").map(String::as_str) {
+            None | Some("ndjson") => Ok(Self::Ndjson),
+            Some("csv") => Ok(Self::Csv),
+            Some(_) => Err(ApiError::validation_field("format must be ndjson or csv", "format")),
+        }
+    }
+
+    fn content_type(self) -> &'static str {
+        match self {
+            Self::Ndjson => "application/x-ndjson",
+            Self::Csv => "text/csv; charset=utf-8",
+        }
+    }
+
+    fn content_disposition(self) -> &'static str {
+        match self {
+            Self::Ndjson => "attachment; filename=\"users.ndjson\"",
+            Self::Csv => "attachment; filename=\"users.csv\"",
+        }
+    }
+
+    /// Một dòng (kết thúc bằng `\n`) cho user
+    fn encode(self, user: &User) -> Result<web::Bytes, ApiError> {
+        match self {
+            Self::Ndjson => {
+                let mut line = serde_json::to_vec(user)
+                    .map_err(|e| ApiError::internal(format!("Failed to serialize user: {}", e)))?;
+                line.push(b'\n');
+                Ok(line.into())
+            }
+            Self::Csv => Ok(user.csv_row().into()),
+        }
+    }
+}
+
+impl CsvRecord for User {
+    const CSV_HEADER: &'static str =
+        "id,name,email,age,phone,role,is_active,created_at,updated_at,deleted_at\n";
+
+    fn csv_row(&self) -> String {
+        format!(
+            "{},{},{},{},{},{},{},{},{},{}\n",
+            csv_field(&self.id),
+            csv_field(&self.name),
+            csv_field(&self.email),
+            self.age,
+            csv_field(self.phone.as_deref().unwrap_or_default()),
+            csv_field(&self.role),
+            self.is_active,
+            self.created_at.to_rfc3339(),
+            self.updated_at.to_rfc3339(),
+            self.deleted_at.map(|at| at.to_rfc3339()).unwrap_or_default(),
+        )
+    }
+}
+
+/// GET /users/export?format=ndjson|csv&include_deleted= - Xuất toàn bộ người dùng
+///
+/// Response được stream từng dòng nên bộ nhớ không phụ thuộc số user. Vì
+/// status đã được gửi, lỗi giữa chừng chỉ được log và stream kết thúc sớm.
+#[cfg_attr(feature = "docs", utoipa::path(
+    get,
+    path = "/users/export",
+    tag = "users",
+    params(
+        ("format" = Option<String>, Query, description = "`ndjson` (default) or `csv`"),
+        ("include_deleted" = Option<bool>, Query, description = "Include soft-deleted users"),
+    ),
+    responses(
+        (status = 200, description = "One user per line", content(
+            (String = "application/x-ndjson"),
+            (String = "text/csv"),
+        )),
+        (status = 422, description = "Invalid query parameters", body = crate::errors::ErrorResponse),
+    )
+))]
+pub async fn export_users(
+    data: web::Data<AppState>,
+    params: web::Query<HashMap<String, String>>,
+) -> Result<HttpResponse, ApiError> {
+    let format = ExportFormat::from_params(&params)?;
+    let include_deleted = include_deleted(&params)?;
+
+    let lines = data
+        .users
+        .stream_all()
+        .filter(move |item| {
+            ready(match item {
+                Ok(user) => include_deleted || !user.is_deleted(),
+                Err(_) => true,
+            })
+        })
+        .map(move |item| item.and_then(|user| format.encode(&user)))
+        .take_while(|line| {
+            if let Err(e) = line {
+                tracing::error!(error = %e, "User export aborted");
+            }
+            ready(line.is_ok())
+        })
+        .filter_map(|line| ready(line.ok()));
+
+    let body = match format {
+        ExportFormat::Ndjson => lines.boxed_local(),
+        ExportFormat::Csv => stream::once(ready(web::Bytes::from_static(User::CSV_HEADER.as_bytes())))
+            .chain(lines)
+            .boxed_local(),
+    };
+
+    Ok(HttpResponse::Ok()
+        .content_type(format.content_type())
+        .insert_header((header::CONTENT_DISPOSITION, format.content_disposition()))
+        .streaming(body.map(Ok::<_, Infallible>)))
 }
 
 /// GET /users/{id} - Lấy một người dùng theo ID
+///
+/// Trả về `304 Not Modified` nếu `If-None-Match` khớp với ETag hiện tại.
+/// User đã soft delete chỉ được trả về với `?include_deleted=true`.
+/// JSON by default, MessagePack with `Accept: application/msgpack`.
+#[cfg_attr(feature = "docs", utoipa::path(
+    get,
+    path = "/users/{id}",
+    tag = "users",
+    params(
+        ("id" = String, Path, description = "User ID"),
+        ("include_deleted" = Option<bool>, Query, description = "Return the user even if soft-deleted"),
+    ),
+    responses(
+        (status = 200, description = "User found", content(
+            (ApiResponse<User> = "application/json"),
+            (ApiResponse<User> = "application/msgpack"),
+        )),
+        (status = 304, description = "`If-None-Match` matches the current ETag"),
+        (status = 404, description = "User not found", body = crate::errors::ErrorResponse),
+        (status = 406, description = "Unsupported Accept", body = crate::errors::ErrorResponse),
+    )
+))]
 pub async fn get_user_by_id(
+    req: HttpRequest,
     data: web::Data<AppState>,
     path: web::Path<String>,
+    params: web::Query<HashMap<String, String>>,
 ) -> Result<HttpResponse, ApiError> {
     let user_id = path.into_inner();
-    let users = data.users.lock().unwrap();
-    
-    match users.iter().find(|u| u.id == user_id) {
-        Some(user) => Ok(HttpResponse::Ok().json(ApiResponse::success(
-            "User found",
-            user,
-        ))),
+    let include_deleted = include_deleted(&params)?;
+    let format = ResponseFormat::negotiate(&req, false)?;
+
+    match data
+        .users
+        .find_by_id(&user_id)
+        .await?
+        .filter(|u| include_deleted || !u.is_deleted())
+    {
+        Some(user) => {
+            let etag = weak_etag(&user)?;
+            if if_none_match(&req, &etag) {
+                return Ok(HttpResponse::NotModified()
+                    .insert_header((header::ETAG, etag))
+                    .finish());
+            }
+
+            format.respond(
+                HttpResponse::Ok().insert_header((header::ETAG, etag)),
+                &ApiResponse::success("User found", user),
+            )
+        }
         None => Err(ApiError::not_found_resource(
             format!("User with id {} not found", user_id),
             "user"
@@ -34,58 +260,228 @@ pub async fn get_user_by_id(
 }
 
 /// POST /users - Tạo người dùng mới
+#[cfg_attr(feature = "docs", utoipa::path(
+    post,
+    path = "/users",
+    tag = "users",
+    request_body = CreateUserRequest,
+    responses(
+        (status = 201, description = "User created", body = ApiResponse<User>),
+        (status = 409, description = "Email already exists", body = crate::errors::ErrorResponse),
+        (status = 422, description = "Validation failed", body = crate::errors::ErrorResponse),
+    )
+))]
 pub async fn create_user(
     data: web::Data<AppState>,
-    user_req: web::Json<CreateUserRequest>,
+    user_req: ApiJson<CreateUserRequest>,
 ) -> Result<HttpResponse, ApiError> {
-    let mut users = data.users.lock().unwrap();
-    
-    // Kiểm tra email đã tồn tại chưa
-    if UserService::check_email_exists(&users, &user_req.email, None) {
-        return Err(ApiError::Conflict {
-            message: "Email already exists".to_string(),
-            field: Some("email".to_string()),
-        });
-    }
-    
-    // Validate và tạo user mới thông qua service
+    // Validate và tạo user mới thông qua service; repository báo Conflict nếu email đã tồn tại
     let new_user = UserService::create_user(&user_req)?;
-    
-    users.push(new_user.clone());
-    
+    let new_user = data.users.create(new_user).await?;
+
     Ok(HttpResponse::Created().json(ApiResponse::success(
         "User created successfully",
         new_user,
     )))
 }
 
+/// POST /users/batch?all_or_nothing= - Tạo nhiều người dùng cùng lúc
+///
+/// Mỗi record được validate và tạo riêng; record lỗi được báo trong `errors`
+/// theo index mà không làm hỏng cả batch. Với `?all_or_nothing=true`, chỉ tạo
+/// khi mọi record hợp lệ và được ghi trong cùng một transaction.
+/// Trả về `201` nếu tất cả thành công, ngược lại `207 Multi-Status`.
+#[cfg_attr(feature = "docs", utoipa::path(
+    post,
+    path = "/users/batch",
+    tag = "users",
+    params(
+        ("all_or_nothing" = Option<bool>, Query, description = "Create the users only if every record succeeds"),
+    ),
+    request_body = Vec<CreateUserRequest>,
+    responses(
+        (status = 201, description = "All users created", body = ApiResponse<BatchResult<User>>),
+        (status = 207, description = "Some or all records failed", body = ApiResponse<BatchResult<User>>),
+        (status = 409, description = "`all_or_nothing` and an email is taken or repeated", body = ApiResponse<BatchResult<User>>),
+        (status = 422, description = "Batch too large, or `all_or_nothing` and a record is invalid", body = crate::errors::ErrorResponse),
+    )
+))]
+pub async fn create_users_batch(
+    data: web::Data<AppState>,
+    params: web::Query<HashMap<String, String>>,
+    user_reqs: ApiJson<Vec<CreateUserRequest>>,
+) -> Result<HttpResponse, ApiError> {
+    let all_or_nothing = params
+        .get("all_or_nothing")
+        .map(|v| v.parse::<bool>())
+        .transpose()
+        .map_err(|_| ApiError::validation_field("all_or_nothing must be true or false", "all_or_nothing"))?
+        .unwrap_or(false);
+
+    if user_reqs.len() > MAX_BATCH_SIZE {
+        return Err(ApiError::validation_field(
+            format!("At most {} users can be created per batch", MAX_BATCH_SIZE),
+            "users",
+        ));
+    }
+
+    let mut errors = Vec::new();
+    let mut valid: Vec<(usize, User)> = Vec::new();
+    let mut emails = HashSet::new();
+    for (index, req) in user_reqs.iter().enumerate() {
+        let result = UserService::create_user(req).and_then(|user| {
+            // Email không phân biệt hoa thường, kể cả giữa các record trong batch
+            if emails.insert(user.email.to_ascii_lowercase()) {
+                Ok(user)
+            } else {
+                Err(ApiError::Conflict {
+                    message: "Email appears more than once in the batch".to_string(),
+                    field: Some("email".to_string()),
+                })
+            }
+        });
+        match result {
+            Ok(user) => valid.push((index, user)),
+            Err(error) => errors.push(BatchItemError { index, error: error.to_error_response() }),
+        }
+    }
+
+    let created = if all_or_nothing {
+        if !errors.is_empty() {
+            Vec::new()
+        } else {
+            let (indexes, users): (Vec<usize>, Vec<User>) = valid.into_iter().unzip();
+            match data.users.create_all(users).await {
+                Ok(created) => created,
+                Err(e) => {
+                    errors.push(BatchItemError {
+                        index: indexes.get(e.index).copied().unwrap_or(e.index),
+                        error: e.error.to_error_response(),
+                    });
+                    Vec::new()
+                }
+            }
+        }
+    } else {
+        let results = ParallelProcessor::process_bounded(valid, BATCH_INSERT_CONCURRENCY, |(index, user)| {
+            let users = data.users.clone();
+            async move { (index, users.create(user).await) }
+        })
+        .await;
+
+        let mut created = Vec::new();
+        for (index, result) in results {
+            match result {
+                Ok(user) => created.push(user),
+                Err(error) => errors.push(BatchItemError { index, error: error.to_error_response() }),
+            }
+        }
+        errors.sort_by_key(|e| e.index);
+        created
+    };
+
+    if all_or_nothing && !errors.is_empty() {
+        // Không có gì được lưu: trả về lỗi thay vì 207
+        let conflict = StatusCode::CONFLICT.as_u16();
+        let status = if errors.iter().any(|e| e.error.status_code == conflict) {
+            StatusCode::CONFLICT
+        } else {
+            StatusCode::UNPROCESSABLE_ENTITY
+        };
+        return Ok(HttpResponse::build(status).json(ApiResponse {
+            success: false,
+            ..ApiResponse::success("No users were created", BatchResult { created, errors })
+        }));
+    }
+
+    let (status, message) = if errors.is_empty() {
+        (StatusCode::CREATED, "Users created successfully")
+    } else if created.is_empty() {
+        (StatusCode::MULTI_STATUS, "No users were created")
+    } else {
+        (StatusCode::MULTI_STATUS, "Some users could not be created")
+    };
+
+    Ok(HttpResponse::build(status).json(ApiResponse::success(
+        message,
+        BatchResult { created, errors },
+    )))
+}
+
 /// PUT /users/{id} - Cập nhật người dùng
+///
+/// Nếu có `If-Match`, chỉ cập nhật khi ETag còn khớp (optimistic concurrency).
+#[cfg_attr(feature = "docs", utoipa::path(
+    put,
+    path = "/users/{id}",
+    tag = "users",
+    params(("id" = String, Path, description = "User ID")),
+    request_body = UpdateUserRequest,
+    responses(
+        (status = 200, description = "User updated", body = ApiResponse<User>),
+        (status = 404, description = "User not found", body = crate::errors::ErrorResponse),
+        (status = 409, description = "Email already exists or `If-Match` is stale", body = crate::errors::ErrorResponse),
+        (status = 422, description = "Validation failed", body = crate::errors::ErrorResponse),
+    )
+))]
 pub async fn update_user(
+    req: HttpRequest,
     data: web::Data<AppState>,
     path: web::Path<String>,
-    user_req: web::Json<UpdateUserRequest>,
+    user_req: ApiJson<UpdateUserRequest>,
 ) -> Result<HttpResponse, ApiError> {
     let user_id = path.into_inner();
-    let mut users = data.users.lock().unwrap();
-    
-    // Kiểm tra email mới có trùng với user khác không
-    if let Some(email) = &user_req.email {
-        if UserService::check_email_exists(&users, email, Some(&user_id)) {
-            return Err(ApiError::Conflict {
-                message: "Email already exists".to_string(),
-                field: Some("email".to_string()),
-            });
+
+    // Tìm và cập nhật user (không cập nhật user đã xóa)
+    match data.users.find_by_id_for_update(&user_id).await?.filter(|u| !u.is_deleted()) {
+        Some(mut user) => {
+            check_if_match(&req, &weak_etag(&user)?)?;
+            let read_at = user.updated_at;
+            UserService::update_user(&mut user, &user_req)?;
+
+            // Repository báo Conflict nếu email mới trùng với user khác, hoặc
+            // nếu user bị sửa sau khi đọc (ETag đã kiểm tra không còn đúng)
+            let user = data.users.update_if_unmodified(user, read_at).await?;
+
+            Ok(HttpResponse::Ok()
+                .insert_header((header::ETAG, weak_etag(&user)?))
+                .json(ApiResponse::success(
+                    "User updated successfully",
+                    user,
+                )))
         }
+        None => Err(ApiError::not_found_resource(
+            format!("User with id {} not found", user_id),
+            "user"
+        )),
     }
+}
 
-    // Tìm và cập nhật user
-    match users.iter_mut().find(|u| u.id == user_id) {
-        Some(user) => {
-            UserService::update_user(user, &user_req)?;
+/// DELETE /users/{id} - Xóa mềm người dùng (set `deleted_at`)
+#[cfg_attr(feature = "docs", utoipa::path(
+    delete,
+    path = "/users/{id}",
+    tag = "users",
+    params(("id" = String, Path, description = "User ID")),
+    responses(
+        (status = 200, description = "User soft-deleted"),
+        (status = 404, description = "User not found", body = crate::errors::ErrorResponse),
+    )
+))]
+pub async fn delete_user(
+    data: web::Data<AppState>,
+    path: web::Path<String>,
+) -> Result<HttpResponse, ApiError> {
+    let user_id = path.into_inner();
 
-            Ok(HttpResponse::Ok().json(ApiResponse::success(
-                "User updated successfully",
-                user.clone(),
+    match data.users.find_by_id_for_update(&user_id).await?.filter(|u| !u.is_deleted()) {
+        Some(mut user) => {
+            UserService::soft_delete(&mut user);
+            data.users.update(user).await?;
+
+            Ok(HttpResponse::Ok().json(ApiResponse::<()>::success(
+                "User deleted successfully",
+                (),
             )))
         }
         None => Err(ApiError::not_found_resource(
@@ -95,26 +491,36 @@ pub async fn update_user(
     }
 }
 
-/// DELETE /users/{id} - Xóa người dùng
-pub async fn delete_user(
+/// POST /users/{id}/restore - Khôi phục người dùng đã xóa mềm
+#[cfg_attr(feature = "docs", utoipa::path(
+    post,
+    path = "/users/{id}/restore",
+    tag = "users",
+    params(("id" = String, Path, description = "User ID")),
+    responses(
+        (status = 200, description = "User restored", body = ApiResponse<User>),
+        (status = 404, description = "User not found", body = crate::errors::ErrorResponse),
+    )
+))]
+pub async fn restore_user(
     data: web::Data<AppState>,
     path: web::Path<String>,
 ) -> Result<HttpResponse, ApiError> {
     let user_id = path.into_inner();
-    let mut users = data.users.lock().unwrap();
-    
-    let initial_len = users.len();
-    users.retain(|u| u.id != user_id);
-    
-    if users.len() < initial_len {
-        Ok(HttpResponse::Ok().json(ApiResponse::<()>::success(
-            "User deleted successfully",
-            (),
-        )))
-    } else {
-        Err(ApiError::not_found_resource(
+
+    match data.users.find_by_id_for_update(&user_id).await? {
+        Some(mut user) => {
+            UserService::restore(&mut user);
+            let user = data.users.update(user).await?;
+
+            Ok(HttpResponse::Ok().json(ApiResponse::success(
+                "User restored successfully",
+                user,
+            )))
+        }
+        None => Err(ApiError::not_found_resource(
             format!("User with id {} not found", user_id),
             "user"
-        ))
+        )),
     }
 }
@@ -0,0 +1,152 @@
+// Startup self-check: validate configuration and probe every enabled dependency
+
+use async_trait::async_trait;
+use std::fmt;
+use std::sync::Arc;
+
+use crate::config::Settings;
+use crate::health::{CheckResult, DependencyStatus, HealthCheckable};
+
+/// Placeholder for a dependency that could not even be connected to
+pub struct Unavailable {
+    name: String,
+    message: String,
+}
+
+impl Unavailable {
+    pub fn new(name: impl Into<String>, message: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            message: message.into(),
+        }
+    }
+}
+
+#[async_trait]
+impl HealthCheckable for Unavailable {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    async fn check(&self) -> CheckResult {
+        CheckResult::unhealthy(self.message.clone())
+    }
+}
+
+/// Build health checks for every dependency enabled by `settings`
+pub async fn dependency_checks(settings: &Settings) -> Vec<Arc<dyn HealthCheckable>> {
+    let mut checks: Vec<Arc<dyn HealthCheckable>> = Vec::new();
+
+    #[cfg(feature = "database-postgres")]
+    {
+        use crate::database::Database;
+
+        let postgres = &settings.database.postgres;
+        let timeout = std::time::Duration::from_secs(postgres.connect_timeout);
+        let check: Arc<dyn HealthCheckable> =
+            match tokio::time::timeout(timeout, Database::new(&postgres.url, 1)).await {
+                Ok(Ok(db)) => Arc::new(db),
+                Ok(Err(e)) => Arc::new(Unavailable::new("database", e.to_string())),
+                Err(_) => Arc::new(Unavailable::new("database", "Database connection timed out")),
+            };
+        checks.push(check);
+    }
+
+    #[cfg(feature = "cache-redis")]
+    if settings.cache.redis.enabled {
+        use crate::cache::CacheManager;
+
+        let timeout = std::time::Duration::from_secs(settings.cache.redis.timeout);
+        let check: Arc<dyn HealthCheckable> =
+            match tokio::time::timeout(timeout, CacheManager::new(&settings.cache.redis.url)).await {
+                Ok(Ok(cache)) => Arc::new(cache),
+                Ok(Err(e)) => Arc::new(Unavailable::new("cache", e.to_string())),
+                Err(_) => Arc::new(Unavailable::new("cache", "Cache connection timed out")),
+            };
+        checks.push(check);
+    }
+
+    #[cfg(feature = "mq-kafka")]
+    if settings.messaging.kafka.enabled {
+        use crate::messaging::kafka::{KafkaConfig, KafkaProducer};
+
+        checks.push(Arc::new(KafkaProducer::new(KafkaConfig {
+            brokers: settings.messaging.kafka.brokers.clone(),
+            client_id: settings.application.name.clone(),
+        })));
+    }
+
+    #[cfg(feature = "mq-rabbitmq")]
+    if settings.messaging.rabbitmq.enabled {
+        use crate::messaging::rabbitmq::{RabbitMQClient, RabbitMQConfig};
+
+        checks.push(Arc::new(RabbitMQClient::new(RabbitMQConfig {
+            url: settings.messaging.rabbitmq.url.clone(),
+            exchange: settings.messaging.rabbitmq.exchange.clone(),
+        })));
+    }
+
+    #[cfg(feature = "mq-nats")]
+    if settings.messaging.nats.enabled {
+        use crate::messaging::nats_client::{NatsClient, NatsConfig};
+
+        checks.push(Arc::new(NatsClient::new(NatsConfig {
+            url: settings.messaging.nats.url.clone(),
+        })));
+    }
+
+    // Keeps `settings` used when no dependency features are enabled
+    let _ = settings;
+    checks
+}
+
+/// Outcome of `cargo run -- check`
+#[derive(Debug)]
+pub struct SelfCheckReport {
+    pub config: Result<(), String>,
+    pub dependencies: DependencyStatus,
+}
+
+impl SelfCheckReport {
+    /// Probe `checks` and combine the results with the config validation outcome
+    pub async fn run(config: Result<(), String>, checks: &[Arc<dyn HealthCheckable>]) -> Self {
+        Self {
+            config,
+            dependencies: DependencyStatus::collect(checks).await,
+        }
+    }
+
+    /// Validate `settings` and probe every enabled dependency
+    pub async fn from_settings(settings: &Settings) -> Self {
+        let checks = dependency_checks(settings).await;
+        Self::run(settings.validate(), &checks).await
+    }
+
+    /// Unlike readiness, any failing dependency fails the self-check
+    pub fn is_ok(&self) -> bool {
+        self.config.is_ok() && self.dependencies.dependencies.values().all(|r| !r.is_unhealthy())
+    }
+}
+
+impl fmt::Display for SelfCheckReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.config {
+            Ok(()) => writeln!(f, "[ OK ] configuration")?,
+            Err(e) => writeln!(f, "[FAIL] configuration: {}", e)?,
+        }
+
+        for (name, result) in &self.dependencies.dependencies {
+            let tag = if result.is_unhealthy() { "FAIL" } else { " OK " };
+            write!(f, "[{}] {} ({})", tag, name, result.status)?;
+            if let Some(ms) = result.response_time_ms {
+                write!(f, " {}ms", ms)?;
+            }
+            if let Some(message) = &result.message {
+                write!(f, ": {}", message)?;
+            }
+            writeln!(f)?;
+        }
+
+        write!(f, "Self-check {}", if self.is_ok() { "passed" } else { "failed" })
+    }
+}
@@ -0,0 +1,123 @@
+use chrono::{DateTime, Utc};
+use std::sync::atomic::{AtomicI64, Ordering};
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
+
+use crate::utils::{Clock, SystemClock};
+
+/// How often the background task refreshes the tick
+pub const WATCHDOG_TICK_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Runtime watchdog cho liveness probe
+///
+/// Background tasks record a tick every second: [`start`](Self::start) on
+/// the current tokio runtime, [`start_on_worker`](Self::start_on_worker) on
+/// each actix worker, which runs its own single-threaded runtime. If any of
+/// them is wedged (e.g. a handler blocking its worker), its tick goes stale
+/// and [`is_alive`](Self::is_alive) turns false.
+pub struct Watchdog {
+    /// Last tick, as milliseconds since the Unix epoch
+    last_tick_ms: AtomicI64,
+    /// One slot per registered worker, same unit as `last_tick_ms`
+    workers: RwLock<Vec<Arc<AtomicI64>>>,
+    stale_after: Duration,
+    clock: Arc<dyn Clock>,
+}
+
+/// Tick slot of one worker runtime, from [`Watchdog::register_worker`]
+pub struct WorkerHeartbeat {
+    watchdog: Arc<Watchdog>,
+    last_tick_ms: Arc<AtomicI64>,
+}
+
+impl WorkerHeartbeat {
+    pub fn tick(&self) {
+        self.last_tick_ms.store(self.watchdog.now_ms(), Ordering::Relaxed);
+    }
+}
+
+impl Watchdog {
+    pub fn new(stale_after: Duration) -> Arc<Self> {
+        Self::with_clock(stale_after, Arc::new(SystemClock))
+    }
+
+    pub fn with_clock(stale_after: Duration, clock: Arc<dyn Clock>) -> Arc<Self> {
+        Arc::new(Self {
+            last_tick_ms: AtomicI64::new(clock.now().timestamp_millis()),
+            workers: RwLock::new(Vec::new()),
+            stale_after,
+            clock,
+        })
+    }
+
+    /// Spawn the ticking task on the current tokio runtime
+    pub fn start(self: &Arc<Self>) -> tokio::task::JoinHandle<()> {
+        let watchdog = self.clone();
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(WATCHDOG_TICK_INTERVAL);
+            loop {
+                interval.tick().await;
+                watchdog.tick();
+            }
+        })
+    }
+
+    /// Spawn a ticking task on the current actix worker; call it from the
+    /// `HttpServer::new` factory, which runs once per worker
+    pub fn start_on_worker(self: &Arc<Self>) -> tokio::task::JoinHandle<()> {
+        let heartbeat = self.register_worker();
+        actix_rt::spawn(async move {
+            let mut interval = tokio::time::interval(WATCHDOG_TICK_INTERVAL);
+            loop {
+                interval.tick().await;
+                heartbeat.tick();
+            }
+        })
+    }
+
+    /// Add a tick slot that must stay fresh for the process to be alive
+    pub fn register_worker(self: &Arc<Self>) -> WorkerHeartbeat {
+        let last_tick_ms = Arc::new(AtomicI64::new(self.now_ms()));
+        if let Ok(mut workers) = self.workers.write() {
+            workers.push(last_tick_ms.clone());
+        }
+        WorkerHeartbeat {
+            watchdog: self.clone(),
+            last_tick_ms,
+        }
+    }
+
+    pub fn tick(&self) {
+        self.last_tick_ms.store(self.now_ms(), Ordering::Relaxed);
+    }
+
+    /// Oldest tick across the main runtime and all workers
+    pub fn last_tick(&self) -> DateTime<Utc> {
+        let mut oldest = self.last_tick_ms.load(Ordering::Relaxed);
+        if let Ok(workers) = self.workers.read() {
+            for worker in workers.iter() {
+                oldest = oldest.min(worker.load(Ordering::Relaxed));
+            }
+        }
+        DateTime::from_timestamp_millis(oldest).unwrap_or(DateTime::UNIX_EPOCH)
+    }
+
+    /// Time since the last tick
+    pub fn tick_age(&self) -> Duration {
+        (self.clock.now() - self.last_tick())
+            .to_std()
+            .unwrap_or(Duration::ZERO)
+    }
+
+    pub fn stale_after(&self) -> Duration {
+        self.stale_after
+    }
+
+    pub fn is_alive(&self) -> bool {
+        self.tick_age() <= self.stale_after
+    }
+
+    fn now_ms(&self) -> i64 {
+        self.clock.now().timestamp_millis()
+    }
+}
@@ -0,0 +1,147 @@
+use async_trait::async_trait;
+use chrono::{DateTime, Duration, Utc};
+use std::sync::Arc;
+
+use super::background_job::{Job, JobResult};
+use super::scheduler::{JobScheduler, Schedule};
+use crate::auth::api_key::ApiKey;
+use crate::auth::api_key_store::ApiKeyStore;
+use crate::errors::ApiError;
+use crate::security::audit::{AuditEvent, AuditEventType, AuditLogger, AuditSeverity};
+
+pub const API_KEY_ROTATION_JOB: &str = "api_key_rotation";
+
+/// Default time between a key becoming due and its deactivation
+pub const DEFAULT_ROTATION_GRACE_DAYS: u32 = 14;
+
+/// Outcome of one rotation run
+#[derive(Debug, Clone, Default)]
+pub struct RotationReport {
+    /// Keys that reached the rotation age and now carry a `rotate_by` deadline
+    pub due: Vec<ApiKey>,
+    /// Keys deactivated because the owner did not rotate them by `rotate_by`
+    pub overdue: Vec<ApiKey>,
+    /// Keys deactivated because they passed `expires_at`
+    pub expired: Vec<ApiKey>,
+}
+
+/// Flags API keys older than `rotation_days` for rotation and deactivates
+/// expired ones
+///
+/// The job never issues replacement keys itself: the raw secret would have
+/// nowhere to go. An aged key gets a `rotate_by` deadline (`grace_days`
+/// later) and an `api_key_rotation_due` audit event; the owner rotates it
+/// with [`ApiKeyManager::rotate_key`](crate::auth::ApiKeyManager::rotate_key) in the meantime, and keys still active
+/// after the deadline are deactivated.
+pub struct ApiKeyRotationJob {
+    store: Arc<dyn ApiKeyStore>,
+    audit: Arc<AuditLogger>,
+    /// `None` disables rotation (`API_KEY_ROTATION_DAYS=0`)
+    max_age: Option<Duration>,
+    grace: Duration,
+}
+
+impl ApiKeyRotationJob {
+    pub fn new(store: Arc<dyn ApiKeyStore>, audit: Arc<AuditLogger>, rotation_days: u32) -> Self {
+        Self {
+            store,
+            audit,
+            max_age: (rotation_days > 0).then(|| Duration::days(rotation_days as i64)),
+            grace: Duration::days(DEFAULT_ROTATION_GRACE_DAYS as i64),
+        }
+    }
+
+    /// Time the owner has to rotate a due key (`API_KEY_ROTATION_GRACE_DAYS`)
+    pub fn with_grace_days(mut self, grace_days: u32) -> Self {
+        self.grace = Duration::days(grace_days as i64);
+        self
+    }
+
+    /// Register the job to run daily
+    pub fn register(scheduler: &JobScheduler) -> String {
+        scheduler.schedule(API_KEY_ROTATION_JOB.to_string(), Schedule::Interval(Duration::days(1)))
+    }
+
+    /// Run the rotation as if the current time were `now`
+    pub async fn run_at(&self, now: DateTime<Utc>) -> Result<RotationReport, ApiError> {
+        let mut report = RotationReport::default();
+
+        for mut key in self.store.list_active().await? {
+            if let Some(expires_at) = key.expires_at.filter(|expires_at| *expires_at <= now) {
+                self.store.deactivate(&key.key_hash).await?;
+                self.audit.log(
+                    audit_event(&key, "api_key_expired", "Deactivate expired API key")
+                        .with_metadata("expires_at".to_string(), expires_at.to_rfc3339()),
+                );
+                report.expired.push(key);
+                continue;
+            }
+
+            if let Some(rotate_by) = key.rotate_by {
+                if rotate_by <= now {
+                    self.store.deactivate(&key.key_hash).await?;
+                    let event = audit_event(
+                        &key,
+                        "api_key_rotation_overdue",
+                        "Deactivate unrotated API key",
+                    );
+                    self.audit.log(
+                        event.with_metadata("rotate_by".to_string(), rotate_by.to_rfc3339()),
+                    );
+                    report.overdue.push(key);
+                }
+                continue;
+            }
+
+            let is_aged = self.max_age.is_some_and(|max_age| key.created_at + max_age <= now);
+            if !is_aged {
+                continue;
+            }
+
+            // Chỉ đặt hạn chót: key vẫn dùng được cho tới khi chủ key tự xoay
+            let rotate_by = now + self.grace;
+            self.store.mark_rotation_due(&key.key_hash, rotate_by).await?;
+            self.audit.log(
+                audit_event(&key, "api_key_rotation_due", "Request rotation of aged API key")
+                    .with_metadata("rotate_by".to_string(), rotate_by.to_rfc3339()),
+            );
+            key.rotate_by = Some(rotate_by);
+            report.due.push(key);
+        }
+
+        Ok(report)
+    }
+}
+
+#[async_trait]
+impl Job for ApiKeyRotationJob {
+    async fn execute(&self) -> Result<JobResult, ApiError> {
+        let report = self.run_at(Utc::now()).await?;
+
+        Ok(JobResult {
+            success: true,
+            message: Some(format!(
+                "{} API keys due for rotation, deactivated {} overdue and {} expired",
+                report.due.len(),
+                report.overdue.len(),
+                report.expired.len()
+            )),
+            data: Some(serde_json::json!({
+                "due": report.due.iter().map(|k| &k.id).collect::<Vec<_>>(),
+                "overdue": report.overdue.iter().map(|k| &k.id).collect::<Vec<_>>(),
+                "expired": report.expired.iter().map(|k| &k.id).collect::<Vec<_>>(),
+            })),
+        })
+    }
+
+    fn job_type(&self) -> &str {
+        API_KEY_ROTATION_JOB
+    }
+}
+
+fn audit_event(key: &ApiKey, event_type: &str, action: &str) -> AuditEvent {
+    AuditEvent::new(AuditEventType::Custom(event_type.to_string()), action.to_string())
+        .with_user(key.user_id.clone())
+        .with_resource(format!("api_key:{}", key.id))
+        .with_severity(AuditSeverity::Warning)
+}
@@ -3,8 +3,12 @@ use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::sync::{Arc, RwLock};
+use std::time::Instant;
 use crate::errors::ApiError;
 
+#[cfg(feature = "observability-metrics")]
+use crate::metrics::MetricsCollector;
+
 /// Job status
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 #[serde(rename_all = "SCREAMING_SNAKE_CASE")]
@@ -34,6 +38,23 @@ pub trait Job: Send + Sync + 'static {
     }
 }
 
+/// Lets jobs be kept type-erased (e.g. `Arc<dyn Job>` in a scheduler's
+/// registry) and still be submitted
+#[async_trait]
+impl<J: Job + ?Sized> Job for Arc<J> {
+    async fn execute(&self) -> Result<JobResult, ApiError> {
+        (**self).execute().await
+    }
+
+    fn job_type(&self) -> &str {
+        (**self).job_type()
+    }
+
+    fn max_retries(&self) -> u32 {
+        (**self).max_retries()
+    }
+}
+
 /// Job metadata
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct JobMetadata {
@@ -46,20 +67,33 @@ pub struct JobMetadata {
     pub retry_count: u32,
     pub max_retries: u32,
     pub result: Option<JobResult>,
+    /// Thời gian chạy của lần execute gần nhất
+    pub duration_ms: Option<u64>,
 }
 
 /// Job executor
 pub struct JobExecutor {
     jobs: Arc<RwLock<HashMap<String, JobMetadata>>>,
+    #[cfg(feature = "observability-metrics")]
+    metrics: Option<Arc<MetricsCollector>>,
 }
 
 impl JobExecutor {
     pub fn new() -> Self {
         Self {
             jobs: Arc::new(RwLock::new(HashMap::new())),
+            #[cfg(feature = "observability-metrics")]
+            metrics: None,
         }
     }
 
+    /// Record `jobs_total` and `job_last_duration_seconds` for every run
+    #[cfg(feature = "observability-metrics")]
+    pub fn with_metrics(mut self, metrics: Arc<MetricsCollector>) -> Self {
+        self.metrics = Some(metrics);
+        self
+    }
+
     pub async fn submit<J: Job>(&self, job: J) -> Result<String, ApiError> {
         let job_id = uuid::Uuid::new_v4().to_string();
         let metadata = JobMetadata {
@@ -72,6 +106,7 @@ impl JobExecutor {
             retry_count: 0,
             max_retries: job.max_retries(),
             result: None,
+            duration_ms: None,
         };
 
         if let Ok(mut jobs) = self.jobs.write() {
@@ -81,7 +116,12 @@ impl JobExecutor {
         // Spawn background task
         let jobs_clone = self.jobs.clone();
         let job_id_clone = job_id.clone();
+        #[cfg(feature = "observability-metrics")]
+        let metrics = self.metrics.clone();
         tokio::spawn(async move {
+            #[cfg(feature = "observability-metrics")]
+            Self::execute_job(jobs_clone, job_id_clone, job, metrics).await;
+            #[cfg(not(feature = "observability-metrics"))]
             Self::execute_job(jobs_clone, job_id_clone, job).await;
         });
 
@@ -92,6 +132,7 @@ impl JobExecutor {
         jobs: Arc<RwLock<HashMap<String, JobMetadata>>>,
         job_id: String,
         job: J,
+        #[cfg(feature = "observability-metrics")] metrics: Option<Arc<MetricsCollector>>,
     ) {
         // Update status to running
         if let Ok(mut jobs_map) = jobs.write() {
@@ -102,11 +143,19 @@ impl JobExecutor {
         }
 
         // Execute job
+        let started = Instant::now();
         let result = job.execute().await;
+        let duration = started.elapsed();
+
+        #[cfg(feature = "observability-metrics")]
+        if let Some(metrics) = &metrics {
+            metrics.record_job_run(job.job_type(), result.is_ok(), duration);
+        }
 
         // Update status based on result
         if let Ok(mut jobs_map) = jobs.write() {
             if let Some(metadata) = jobs_map.get_mut(&job_id) {
+                metadata.duration_ms = Some(duration.as_millis() as u64);
                 match result {
                     Ok(job_result) => {
                         metadata.status = JobStatus::Completed;
@@ -134,6 +183,17 @@ impl JobExecutor {
             None
         }
     }
+
+    /// Every submitted job, newest first
+    pub fn list_jobs(&self) -> Vec<JobMetadata> {
+        let mut jobs: Vec<JobMetadata> = if let Ok(jobs) = self.jobs.read() {
+            jobs.values().cloned().collect()
+        } else {
+            Vec::new()
+        };
+        jobs.sort_by(|a, b| b.created_at.cmp(&a.created_at));
+        jobs
+    }
 }
 
 impl Default for JobExecutor {
@@ -1,6 +1,11 @@
 pub mod background_job;
 pub mod scheduler;
 
-pub use background_job::{Job, JobStatus, JobResult, JobExecutor};
-pub use scheduler::{JobScheduler, Schedule};
+#[cfg(feature = "auth-api-key")]
+pub mod api_key_rotation;
 
+pub use background_job::{Job, JobStatus, JobResult, JobExecutor, JobMetadata};
+pub use scheduler::{JobScheduler, Schedule, ScheduledJob};
+
+#[cfg(feature = "auth-api-key")]
+pub use api_key_rotation::{ApiKeyRotationJob, RotationReport, DEFAULT_ROTATION_GRACE_DAYS};
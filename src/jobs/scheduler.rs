@@ -2,14 +2,36 @@ use chrono::{DateTime, Utc, Duration};
 use std::collections::HashMap;
 use std::sync::{Arc, RwLock};
 
+use super::background_job::{Job, JobExecutor};
+use crate::utils::clock::{Clock, SystemClock};
+
 /// Schedule type
 #[derive(Debug, Clone)]
 pub enum Schedule {
+    /// Chạy một lần tại thời điểm cho trước
     Once(DateTime<Utc>),
+    /// Chạy một lần, sau khoảng delay tính từ lúc schedule
+    After(Duration),
     Interval(Duration),
     Cron(String),
 }
 
+impl Schedule {
+    /// Run once, `delay` after the job is scheduled
+    pub fn once_after(delay: Duration) -> Self {
+        Schedule::After(delay)
+    }
+
+    /// Run once at `at`; a time in the past fires on the next tick
+    pub fn once_at(at: DateTime<Utc>) -> Self {
+        Schedule::Once(at)
+    }
+
+    pub fn is_one_shot(&self) -> bool {
+        matches!(self, Schedule::Once(_) | Schedule::After(_))
+    }
+}
+
 /// Scheduled job
 #[derive(Debug, Clone)]
 pub struct ScheduledJob {
@@ -21,23 +43,37 @@ pub struct ScheduledJob {
 }
 
 /// Job scheduler
+///
+/// Jobs become due when the clock reaches `next_run`; [`take_due`](Self::take_due)
+/// hands them out, removing one-shot jobs and rescheduling interval ones.
+#[derive(Clone)]
 pub struct JobScheduler {
     jobs: Arc<RwLock<HashMap<String, ScheduledJob>>>,
+    clock: Arc<dyn Clock>,
 }
 
 impl JobScheduler {
     pub fn new() -> Self {
         Self {
             jobs: Arc::new(RwLock::new(HashMap::new())),
+            clock: Arc::new(SystemClock),
         }
     }
 
+    /// Đọc thời gian từ `clock` thay vì system time (dùng trong tests)
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = clock;
+        self
+    }
+
     pub fn schedule(&self, name: String, schedule: Schedule) -> String {
         let job_id = uuid::Uuid::new_v4().to_string();
+        let now = self.clock.now();
         let next_run = match &schedule {
             Schedule::Once(dt) => *dt,
-            Schedule::Interval(duration) => Utc::now() + *duration,
-            Schedule::Cron(_) => Utc::now(), // Placeholder
+            Schedule::After(delay) => now + *delay,
+            Schedule::Interval(duration) => now + *duration,
+            Schedule::Cron(_) => now, // Placeholder
         };
 
         let job = ScheduledJob {
@@ -70,6 +106,87 @@ impl JobScheduler {
             Vec::new()
         }
     }
+
+    /// Enabled jobs whose `next_run` has passed, oldest first
+    ///
+    /// A one-shot job is returned exactly once and removed; an interval job
+    /// gets its `next_run` moved to one interval from now. Cron schedules are
+    /// not evaluated yet and never become due.
+    pub fn take_due(&self) -> Vec<ScheduledJob> {
+        let now = self.clock.now();
+        let Ok(mut jobs) = self.jobs.write() else {
+            return Vec::new();
+        };
+
+        let due_ids: Vec<String> = jobs
+            .values()
+            .filter(|job| job.enabled && job.next_run <= now)
+            .filter(|job| !matches!(job.schedule, Schedule::Cron(_)))
+            .map(|job| job.id.clone())
+            .collect();
+
+        let mut due = Vec::with_capacity(due_ids.len());
+        for id in due_ids {
+            let fired = if jobs.get(&id).is_some_and(|job| job.schedule.is_one_shot()) {
+                jobs.remove(&id)
+            } else {
+                jobs.get_mut(&id).map(|job| {
+                    let fired = job.clone();
+                    if let Schedule::Interval(interval) = job.schedule {
+                        job.next_run = now + interval;
+                    }
+                    fired
+                })
+            };
+            due.extend(fired);
+        }
+
+        due.sort_by_key(|job| job.next_run);
+        due
+    }
+
+    /// Check for due jobs every `tick` on the current tokio runtime and pass
+    /// each one to `on_due`
+    pub fn start<F>(&self, tick: std::time::Duration, on_due: F) -> tokio::task::JoinHandle<()>
+    where
+        F: Fn(ScheduledJob) + Send + Sync + 'static,
+    {
+        let scheduler = self.clone();
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(tick);
+            loop {
+                interval.tick().await;
+                for job in scheduler.take_due() {
+                    tracing::debug!(job_id = %job.id, job = %job.name, "Scheduled job due");
+                    on_due(job);
+                }
+            }
+        })
+    }
+
+    /// [`start`](Self::start), submitting each due job to `executor`
+    ///
+    /// `jobs` maps the names used with [`schedule`](Self::schedule) to the
+    /// job that runs; a due name without an entry is logged and skipped.
+    pub fn start_dispatch(
+        &self,
+        tick: std::time::Duration,
+        executor: Arc<JobExecutor>,
+        jobs: HashMap<String, Arc<dyn Job>>,
+    ) -> tokio::task::JoinHandle<()> {
+        self.start(tick, move |scheduled| {
+            let Some(job) = jobs.get(&scheduled.name).cloned() else {
+                tracing::warn!(job = %scheduled.name, "No job registered for schedule");
+                return;
+            };
+            let executor = executor.clone();
+            tokio::spawn(async move {
+                if let Err(e) = executor.submit(job).await {
+                    tracing::error!(job = %scheduled.name, error = %e, "Failed to submit job");
+                }
+            });
+        })
+    }
 }
 
 impl Default for JobScheduler {
@@ -77,4 +194,3 @@ impl Default for JobScheduler {
         Self::new()
     }
 }
-
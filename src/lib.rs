@@ -14,6 +14,9 @@
 //! - `errors/` - Error handling
 //! - `utils/` - Utility functions (validation, helpers)
 //! - `database/` - Database abstraction layer
+//! - `health/` - Dependency health checks
+//! - `openapi/` - OpenAPI spec & Swagger UI (feature `docs`)
+//! - `webhooks/` - Signed webhook delivery with retries (feature `webhooks`)
 //! 
 //! ## Sử dụng Template
 //! 
@@ -54,6 +57,7 @@ pub mod middleware;
 pub mod errors;
 pub mod utils;
 pub mod database;
+pub mod health;
 pub mod auth;
 pub mod cache;
 pub mod metrics;
@@ -72,6 +76,12 @@ pub mod grpc;
 #[cfg(feature = "websocket")]
 pub mod websocket;
 
+#[cfg(feature = "docs")]
+pub mod openapi;
+
+#[cfg(feature = "webhooks")]
+pub mod webhooks;
+
 pub mod multitenancy;
 pub mod features;
 pub mod gameserver;
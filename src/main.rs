@@ -3,13 +3,27 @@
 //! Đây là entry point của ứng dụng. 
 //! Tất cả configuration, middleware, và routes được setup ở đây.
 
-use actix_web::{web, App, HttpServer, middleware::Logger as ActixLogger};
-use actix_cors::Cors;
+use actix_web::{
+    http::KeepAlive,
+    middleware::Condition,
+    web, App, HttpServer,
+};
+use std::{collections::HashMap, sync::Arc};
+
 use rust_template::{
     config::{create_seed_data, Settings},
-    middleware::{Logger, RequestId},
-    routes::{configure_health_routes, configure_user_routes},
+    errors::ApiError,
+    health::{SelfCheckReport, Watchdog},
+    middleware::{
+        access_logger, build_cors, Compression, Idempotency, InMemoryIdempotencyStore,
+        Localization, Logger, RequestCoalescer, RequestId, Timeout,
+    },
+    auth::AuthMiddleware,
+    routes::{
+        configure_admin_routes, configure_health_routes, configure_upload_routes, configure_user_routes,
+    },
     state::AppState,
+    utils::{json_config, payload_config, set_ids_as_strings},
 };
 
 #[actix_web::main]
@@ -17,18 +31,43 @@ async fn main() -> std::io::Result<()> {
     // 1. Load environment variables từ file .env
     dotenv::dotenv().ok();
     
-    // 2. Initialize tracing subscriber
-    tracing_subscriber::fmt()
-        .with_env_filter(
-            tracing_subscriber::EnvFilter::try_from_default_env()
-                .unwrap_or_else(|_| tracing_subscriber::EnvFilter::new("info"))
-        )
-        .json()
-        .init();
-    
-    // 3. Load settings
-    let settings = Settings::from_env();
+    // 2. Load settings (tracing chưa init nên lỗi in ra stderr)
+    let settings = match Settings::from_env() {
+        Ok(settings) => settings,
+        Err(e) => {
+            eprintln!("❌ Invalid configuration: {}", e);
+            return Err(std::io::Error::new(std::io::ErrorKind::InvalidInput, e.to_string()));
+        }
+    };
+
+    // 3. Initialize tracing subscriber (+ OTLP export khi OTEL_ENABLED=true)
+    #[cfg(feature = "observability-tracing")]
+    if let Err(e) = rust_template::monitoring::init_tracing(&settings.tracing) {
+        eprintln!("Failed to initialize OpenTelemetry tracing, falling back to JSON logs: {}", e);
+        init_json_logging();
+    }
+    #[cfg(not(feature = "observability-tracing"))]
+    init_json_logging();
+
+    // `cargo run -- check`: kiểm tra config và kết nối dependencies rồi thoát,
+    // không bind HTTP port (dùng cho CI smoke tests / Kubernetes init containers)
+    match std::env::args().nth(1).as_deref() {
+        None | Some("serve") => {}
+        Some("check") => {
+            let report = SelfCheckReport::from_settings(&settings).await;
+            println!("{}", report);
+            std::process::exit(if report.is_ok() { 0 } else { 1 });
+        }
+        Some(other) => {
+            eprintln!("Unknown command: {}\nUsage: rust-template [serve|check]", other);
+            std::process::exit(2);
+        }
+    }
+
     let bind_address = settings.bind_address();
+    ApiError::set_response_format(settings.application.error_format);
+    // Chỉ development mới trả source chain của lỗi cho client
+    ApiError::set_expose_details(settings.is_development());
     
     tracing::info!("🚀 Starting {} v{}", 
         settings.application.name, 
@@ -36,11 +75,279 @@ async fn main() -> std::io::Result<()> {
     );
     tracing::info!("📝 Environment: {}", settings.application.environment);
     tracing::info!("🌐 Server will bind to: {}", bind_address);
+
+    // Kiểm tra CORS settings trước khi start server
+    let cors_settings = settings.cors.clone();
+    if let Err(e) = build_cors(&cors_settings) {
+        tracing::error!("❌ Invalid CORS configuration: {}", e);
+        return Err(std::io::Error::new(std::io::ErrorKind::InvalidInput, e.to_string()));
+    }
+
+    // Đọc cert/key trước khi start để lỗi TLS báo ngay
+    #[cfg(feature = "tls")]
+    let tls_config = if settings.server.enable_https {
+        match rust_template::config::load_rustls_config(&settings.server) {
+            Ok(config) => Some(config),
+            Err(e) => {
+                tracing::error!("❌ Invalid TLS configuration: {}", e);
+                return Err(std::io::Error::new(std::io::ErrorKind::InvalidInput, e.to_string()));
+            }
+        }
+    } else {
+        None
+    };
+    #[cfg(not(feature = "tls"))]
+    if settings.server.enable_https {
+        tracing::error!("❌ ENABLE_HTTPS requires building with the `tls` feature");
+        return Err(std::io::Error::new(
+            std::io::ErrorKind::InvalidInput,
+            "ENABLE_HTTPS requires the tls feature",
+        ));
+    }
     
     // 4. Initialize application state
-    // TODO: Khi có database, initialize DB connection pool ở đây
+    // Gắn thêm cache qua builder khi cần: .with_cache(cache)
     let seed_data = create_seed_data();
-    let app_state = web::Data::new(AppState::with_users(seed_data));
+    // Audit log dùng chung cho /admin/flags, xoay API key và AppState
+    let audit_logger = Arc::new(rust_template::security::AuditLogger::new(10_000));
+    #[allow(unused_mut)]
+    let mut state_builder = AppState::builder()
+        .with_users(seed_data)
+        .with_audit_logger(audit_logger.clone());
+
+    // Không kết nối được thì vẫn chạy; readiness check sẽ báo database not_configured
+    #[cfg(feature = "database-postgres")]
+    let mut db_pool = None;
+    #[cfg(feature = "database-postgres")]
+    if settings.database.postgres.enabled {
+        match rust_template::database::Database::from_settings(&settings.database.postgres).await {
+            Ok(database) => {
+                db_pool = Some(database.pool().clone());
+                state_builder = state_builder.with_database(database);
+            }
+            Err(e) => tracing::warn!("⚠️  Continuing without database: {}", e),
+        }
+    }
+
+    // Feature flags bật/tắt qua /admin/flags, mọi thay đổi vào audit log.
+    // Flag và A/B test lưu trong Postgres để các replica dùng chung; handler
+    // và AppState dùng chung một manager
+    let (feature_flags, ab_tests) = {
+        use rust_template::features::{
+            ABTestManager, FeatureFlagManager, FlagStore, DEFAULT_FLAG_REFRESH_INTERVAL,
+        };
+
+        let flag_store: Option<Arc<dyn FlagStore>> = None;
+        #[cfg(feature = "database-postgres")]
+        let flag_store = flag_store.or_else(|| {
+            db_pool.clone().map(|pool| {
+                Arc::new(rust_template::features::PostgresFlagStore::new(pool)) as Arc<dyn FlagStore>
+            })
+        });
+
+        let (flags, tests) = match flag_store {
+            Some(store) => (
+                FeatureFlagManager::new().with_store(store.clone()),
+                ABTestManager::new().with_store(store),
+            ),
+            None => (FeatureFlagManager::new(), ABTestManager::new()),
+        };
+        match flags.load().await {
+            Ok(count) => tracing::info!("🚩 Loaded {} feature flags", count),
+            Err(e) => tracing::warn!("⚠️  Failed to load feature flags: {}", e),
+        }
+        match tests.load().await {
+            Ok(count) => tracing::info!("🧪 Loaded {} A/B tests", count),
+            Err(e) => tracing::warn!("⚠️  Failed to load A/B tests: {}", e),
+        }
+        flags.start_refresh(DEFAULT_FLAG_REFRESH_INTERVAL);
+        tests.start_refresh(DEFAULT_FLAG_REFRESH_INTERVAL);
+        (flags, tests)
+    };
+    let mut state_builder = state_builder
+        .with_feature_flags(feature_flags.clone())
+        .with_ab_tests(ab_tests);
+
+    // Watchdog cho liveness probe: tick mỗi giây trên runtime chính và trên
+    // từng worker (bắt đầu trong factory của HttpServer bên dưới)
+    let watchdog = (settings.server.liveness_stale_after_secs > 0).then(|| {
+        Watchdog::new(std::time::Duration::from_secs(
+            settings.server.liveness_stale_after_secs,
+        ))
+    });
+    if let Some(watchdog) = &watchdog {
+        watchdog.start();
+        state_builder = state_builder.with_watchdog(watchdog.clone());
+    }
+
+    // Object storage cho POST /uploads
+    #[cfg(feature = "storage-s3")]
+    if settings.storage.s3_enabled {
+        let storage = rust_template::services::S3StorageService::new(&settings.storage).await;
+        state_builder = state_builder.with_storage(std::sync::Arc::new(storage));
+    }
+
+    #[cfg(feature = "observability-metrics")]
+    let metrics = rust_template::metrics::MetricsCollector::from_settings(&settings.observability.metrics);
+    #[cfg(feature = "observability-metrics")]
+    let state_builder = state_builder.with_metrics(metrics.clone());
+
+    // METRICS_MODE=push: gửi cùng registry tới OTLP collector thay vì chờ scrape
+    #[cfg(feature = "metrics-otlp")]
+    if settings.observability.metrics.mode == rust_template::config::MetricsMode::Push {
+        match rust_template::metrics::OtlpMetricsPusher::from_settings(
+            metrics.clone(),
+            &settings.observability.metrics,
+            &settings.observability.tracing,
+        ) {
+            Ok(pusher) => {
+                pusher.start();
+                tracing::info!(
+                    "📈 Pushing metrics to {} every {}s",
+                    settings.observability.metrics.otlp_endpoint,
+                    settings.observability.metrics.push_interval_secs
+                );
+            }
+            Err(e) => tracing::error!("Failed to start OTLP metrics push: {}", e),
+        }
+    }
+    #[cfg(not(feature = "metrics-otlp"))]
+    if settings.observability.metrics.mode == rust_template::config::MetricsMode::Push {
+        tracing::warn!("METRICS_MODE=push requires the metrics-otlp feature; metrics stay pull-only");
+    }
+
+    let app_state = web::Data::new(state_builder.build());
+
+    // gRPC health checking (grpc.health.v1.Health) trên cùng các dependency checks
+    #[cfg(feature = "grpc")]
+    {
+        let (grpc_health, health_service) =
+            rust_template::grpc::GrpcHealth::new(app_state.health_checks.clone());
+        grpc_health.start(rust_template::grpc::GRPC_HEALTH_POLL_INTERVAL);
+
+        let grpc_address = format!("{}:{}", settings.server.host, settings.server.grpc_port);
+        let grpc_address: std::net::SocketAddr = grpc_address.parse().map_err(|e| {
+            std::io::Error::new(std::io::ErrorKind::InvalidInput, format!("Invalid gRPC address: {}", e))
+        })?;
+        tracing::info!("🔌 gRPC server will bind to: {}", grpc_address);
+        tokio::spawn(async move {
+            let result = tonic::transport::Server::builder()
+                .add_service(health_service)
+                .serve(grpc_address)
+                .await;
+            if let Err(e) = result {
+                tracing::error!("❌ gRPC server failed: {}", e);
+            }
+        });
+    }
+
+    let jwt_manager = web::Data::new(
+        rust_template::auth::JwtManager::from_settings(&settings.auth.jwt)
+            .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidInput, e.to_string()))?,
+    );
+
+    // Background jobs: scheduler nộp job đến hạn vào executor; GET /admin/jobs
+    // đọc đúng hai instance này
+    let job_executor = rust_template::jobs::JobExecutor::new();
+    #[cfg(feature = "observability-metrics")]
+    let job_executor = job_executor.with_metrics(metrics.clone());
+    let job_executor = Arc::new(job_executor);
+    let job_scheduler = rust_template::jobs::JobScheduler::new();
+    #[allow(unused_mut)]
+    let mut scheduled_jobs: HashMap<String, Arc<dyn rust_template::jobs::Job>> = HashMap::new();
+
+    // Xoay API key cần bảng api_keys nên chỉ chạy khi có database
+    #[cfg(all(feature = "auth-api-key", feature = "database-postgres"))]
+    if let Some(pool) = app_state.db_pool.clone() {
+        use rust_template::auth::api_key_store::PostgresApiKeyStore;
+        use rust_template::jobs::{api_key_rotation::API_KEY_ROTATION_JOB, ApiKeyRotationJob};
+
+        let rotation = ApiKeyRotationJob::new(
+            Arc::new(PostgresApiKeyStore::new(pool)),
+            audit_logger.clone(),
+            settings.auth.api_key.rotation_days,
+        )
+        .with_grace_days(settings.auth.api_key.rotation_grace_days);
+        ApiKeyRotationJob::register(&job_scheduler);
+        scheduled_jobs.insert(API_KEY_ROTATION_JOB.to_string(), Arc::new(rotation));
+    }
+
+    job_scheduler.start_dispatch(
+        std::time::Duration::from_secs(1),
+        job_executor.clone(),
+        scheduled_jobs,
+    );
+    let jobs_state = web::Data::new(rust_template::handlers::JobsState {
+        executor: job_executor,
+        scheduler: job_scheduler,
+    });
+
+    let flags_state = web::Data::new(rust_template::handlers::FeatureFlagsState {
+        flags: feature_flags,
+        audit: audit_logger.clone(),
+    });
+
+    // Giới hạn body và thời gian xử lý request
+    let max_body_bytes = settings.server.max_body_bytes;
+    let request_timeout = std::time::Duration::from_secs(settings.server.request_timeout_secs);
+    let keep_alive = match settings.server.keep_alive_secs {
+        0 => KeepAlive::Disabled,
+        secs => KeepAlive::Timeout(std::time::Duration::from_secs(secs)),
+    };
+    tracing::info!(
+        "⚙️  Workers: {}, keep-alive: {:?}, request timeout: {}s, shutdown timeout: {}s",
+        settings.server.workers,
+        keep_alive,
+        settings.server.request_timeout_secs,
+        settings.server.shutdown_timeout_secs
+    );
+
+    // Id số nguyên dạng string cho client JavaScript
+    set_ids_as_strings(settings.server.ids_as_strings);
+
+    // Nén response theo Accept-Encoding
+    let compression = Compression::new(settings.server.compression)
+        .with_min_bytes(settings.server.compression_min_bytes);
+
+    // Idempotency store dùng chung giữa các worker
+    let idempotency_store = std::sync::Arc::new(InMemoryIdempotencyStore::new());
+
+    // GET trùng lặp đang chạy dùng chung một response (tắt mặc định)
+    let coalescer = RequestCoalescer::new();
+    let coalesce_get_requests = settings.server.coalesce_get_requests;
+
+    // Event source chung cho WebSocket và SSE
+    #[cfg(feature = "websocket")]
+    let events = web::Data::new(rust_template::websocket::EventBus::new());
+    #[cfg(feature = "websocket")]
+    let ws_server = {
+        use rust_template::middleware::{RateLimitAlgorithm, RateLimitConfig};
+        use rust_template::websocket::{MessageRateLimit, RateLimitAction, WebSocketServer};
+
+        let server = WebSocketServer::with_events(events.get_ref().clone());
+        let server = if settings.server.ws_rate_limit_messages > 0 {
+            server.with_rate_limit(MessageRateLimit {
+                limit: RateLimitConfig {
+                    algorithm: RateLimitAlgorithm::SlidingWindow,
+                    max_requests: settings.server.ws_rate_limit_messages,
+                    window_secs: settings.server.ws_rate_limit_window_secs,
+                    burst_size: None,
+                },
+                on_exceeded: if settings.server.ws_rate_limit_close {
+                    RateLimitAction::Close
+                } else {
+                    RateLimitAction::Drop
+                },
+            })
+        } else {
+            server
+        };
+        web::Data::new(server)
+    };
+    #[cfg(feature = "websocket")]
+    let event_stream_config = web::Data::new(rust_template::handlers::EventStreamConfig {
+        keep_alive: std::time::Duration::from_secs(settings.server.sse_keep_alive_secs),
+    });
     
     // 5. Print available endpoints
     println!("\n📚 Available Endpoints:");
@@ -50,8 +357,26 @@ async fn main() -> std::io::Result<()> {
     println!("  GET    /users            - Get all users");
     println!("  GET    /users/{{id}}      - Get user by ID");
     println!("  POST   /users            - Create new user");
+    println!("  POST   /users/batch      - Create many users (partial success)");
+    println!("  GET    /users/export     - Stream all users as NDJSON or CSV");
     println!("  PUT    /users/{{id}}      - Update user");
-    println!("  DELETE /users/{{id}}      - Delete user");
+    println!("  DELETE /users/{{id}}      - Soft delete user");
+    println!("  POST   /users/{{id}}/restore - Restore deleted user");
+    println!("  POST   /uploads          - Upload files (multipart/form-data)");
+    println!("  GET    /admin/jobs       - Background job status (JWT, jobs:read)");
+    println!("  GET    /admin/flags      - Feature flags (JWT, flags:read)");
+    println!("  PUT    /admin/flags/{{name}} - Set flag enabled/rollout (JWT, flags:write)");
+    println!("  DELETE /admin/flags/{{name}} - Remove flag (JWT, flags:write)");
+    #[cfg(feature = "websocket")]
+    {
+        println!("  GET    /events/stream    - Live events (Server-Sent Events)");
+        println!("  GET    /ws?token=<jwt>   - Live events (WebSocket, JWT required)");
+    }
+    #[cfg(feature = "docs")]
+    {
+        println!("  GET    /api-docs/openapi.json - OpenAPI spec");
+        println!("  GET    /swagger-ui/      - Swagger UI");
+    }
     println!("\n💡 Example Usage:");
     println!("  curl http://localhost:{}/health", settings.server.port);
     println!("  curl http://localhost:{}/users", settings.server.port);
@@ -61,32 +386,113 @@ async fn main() -> std::io::Result<()> {
     println!("\n✅ Server is ready!\n");
     
     // 6. Start HTTP server
-    HttpServer::new(move || {
-        // CORS configuration
-        let cors = Cors::default()
-            .allow_any_origin()
-            .allow_any_method()
-            .allow_any_header()
-            .max_age(3600);
+    let server = HttpServer::new(move || {
+        // Factory chạy một lần trên mỗi worker
+        if let Some(watchdog) = &watchdog {
+            watchdog.start_on_worker();
+        }
+
+        // CORS configuration (đã validate ở trên)
+        let cors = build_cors(&cors_settings).expect("CORS settings validated at startup");
         
-        App::new()
+        let app = App::new()
             // Application state
             .app_data(app_state.clone())
+            .app_data(json_config(max_body_bytes))
+            .app_data(payload_config(max_body_bytes))
             
             // Middleware stack (executed in order)
+            .wrap(Condition::new(coalesce_get_requests, coalescer.clone())) // Share identical in-flight GETs
+            .wrap(Timeout::new(request_timeout))                // Request timeout (504)
+            .wrap(Idempotency::new(idempotency_store.clone())) // Idempotency-Key replay
+            .wrap(compression.clone())     // gzip/br (streams are compressed, not buffered)
             .wrap(cors)                    // CORS
-            .wrap(ActixLogger::default())  // Access logging
-            .wrap(Logger)                  // Custom request/response logger
+            .wrap(access_logger())         // Access logging (query tokens masked)
+            .wrap(Logger::default())       // Custom request/response logger
+            .wrap(Localization)            // Error messages per Accept-Language
             .wrap(RequestId)               // Request ID injection
             
             // Routes configuration
             .configure(configure_health_routes)
             .configure(configure_user_routes)
+            .configure(configure_upload_routes)
+            .service(
+                web::scope("/admin")
+                    .wrap(AuthMiddleware::new(jwt_manager.get_ref().clone()))
+                    .app_data(jobs_state.clone())
+                    .app_data(flags_state.clone())
+                    .configure(configure_admin_routes),
+            );
             // TODO: Thêm routes mới ở đây
             // .configure(configure_product_routes)
             // .configure(configure_order_routes)
+
+        // Server-Sent Events + WebSocket (cùng EventBus)
+        #[cfg(feature = "websocket")]
+        let app = app
+            .app_data(events.clone())
+            .app_data(event_stream_config.clone())
+            .app_data(ws_server.clone())
+            .app_data(jwt_manager.clone())
+            .configure(rust_template::routes::configure_event_routes)
+            .route("/ws", web::get().to(rust_template::websocket::ws_connect));
+
+        // OpenAPI spec + Swagger UI
+        #[cfg(feature = "docs")]
+        let app = app.configure(rust_template::openapi::configure_docs_routes);
+
+        // Prometheus HTTP metrics (outermost, đo cả thời gian của middleware khác)
+        #[cfg(feature = "observability-metrics")]
+        let app = app.wrap(rust_template::middleware::Metrics::new(metrics.clone()));
+
+        app
     })
-    .bind(&bind_address)?
-    .run()
-    .await
+    .workers(settings.server.workers)
+    .keep_alive(keep_alive)
+    // Chống slow-loris: giới hạn thời gian nhận request headers
+    .client_request_timeout(request_timeout)
+    .shutdown_timeout(settings.server.shutdown_timeout_secs);
+
+    #[cfg(feature = "tls")]
+    let server = match tls_config {
+        Some(tls_config) => {
+            tracing::info!("🔒 Serving HTTPS on {}", bind_address);
+            if let Some(redirect_port) = settings.server.http_redirect_port {
+                let https_port = settings.server.port;
+                let redirect_address = format!("{}:{}", settings.server.host, redirect_port);
+                tracing::info!("↪️  Redirecting HTTP on {} to HTTPS", redirect_address);
+                let redirect = HttpServer::new(move || {
+                    App::new().default_service(web::to(move |req: actix_web::HttpRequest| async move {
+                        rust_template::config::https_redirect(&req, https_port)
+                    }))
+                })
+                .workers(1)
+                .bind(&redirect_address)?
+                .run();
+                actix_web::rt::spawn(redirect);
+            }
+            server.bind_rustls_0_23(&bind_address, tls_config)?
+        }
+        None => server.bind(&bind_address)?,
+    };
+    #[cfg(not(feature = "tls"))]
+    let server = server.bind(&bind_address)?;
+
+    let result = server.run().await;
+
+    // Flush spans còn trong exporter sau khi server dừng
+    #[cfg(feature = "observability-tracing")]
+    rust_template::monitoring::shutdown_tracing();
+
+    result
+}
+
+fn init_json_logging() {
+    tracing_subscriber::fmt()
+        .with_env_filter(
+            tracing_subscriber::EnvFilter::try_from_default_env()
+                .unwrap_or_else(|_| tracing_subscriber::EnvFilter::new("info"))
+        )
+        .json()
+        .init();
 }
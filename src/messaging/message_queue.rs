@@ -1,7 +1,12 @@
 use async_trait::async_trait;
-use serde::{Deserialize, Serialize};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
 use std::collections::HashMap;
 use crate::errors::ApiError;
+use crate::utils::codec::{self, Codec, PayloadFormat};
+use crate::utils::retry::{retry_with_backoff, RetryPolicy};
+
+/// Header naming the codec of `payload` (`json`, `msgpack`, `bincode`)
+pub const PAYLOAD_FORMAT_HEADER: &str = "payload-format";
 
 /// Generic message structure
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -24,6 +29,28 @@ impl Message {
         }
     }
 
+    /// Message whose payload is `value` encoded with `format`
+    pub fn encode<T: Serialize + ?Sized>(
+        topic: impl Into<String>,
+        value: &T,
+        format: PayloadFormat,
+    ) -> Result<Self, ApiError> {
+        Ok(Self::new(topic, format.encode(value)?)
+            .with_header(PAYLOAD_FORMAT_HEADER.to_string(), format.to_string()))
+    }
+
+    /// Decode the payload with the codec named in [`PAYLOAD_FORMAT_HEADER`];
+    /// messages without the header are decoded by their tag
+    pub fn decode_payload<T: DeserializeOwned>(&self) -> Result<T, ApiError> {
+        match self.headers.get(PAYLOAD_FORMAT_HEADER) {
+            Some(format) => format
+                .parse::<PayloadFormat>()
+                .map_err(|e| ApiError::internal(format!("Message {}: {}", self.id, e)))?
+                .decode(&self.payload),
+            None => codec::decode_any(&self.payload),
+        }
+    }
+
     pub fn with_header(mut self, key: String, value: String) -> Self {
         self.headers.insert(key, value);
         self
@@ -36,6 +63,14 @@ pub trait MessageQueue: Send + Sync {
     /// Publish a message to a topic
     async fn publish(&self, message: Message) -> Result<(), ApiError>;
 
+    /// `publish`, retried with backoff on transient errors
+    ///
+    /// Every attempt sends the same message `id`, so consumers can drop
+    /// duplicates when an attempt that timed out was actually delivered.
+    async fn publish_with_retry(&self, message: Message, policy: RetryPolicy) -> Result<(), ApiError> {
+        retry_with_backoff(policy, || self.publish(message.clone())).await
+    }
+
     /// Subscribe to a topic
     async fn subscribe(&self, topic: &str, handler: Box<dyn MessageHandler>) -> Result<(), ApiError>;
 
@@ -9,7 +9,7 @@ pub mod rabbitmq;
 #[cfg(feature = "mq-nats")]
 pub mod nats_client;
 
-pub use message_queue::{Message, MessageQueue, MessageHandler};
+pub use message_queue::{Message, MessageQueue, MessageHandler, PAYLOAD_FORMAT_HEADER};
 
 #[cfg(feature = "mq-kafka")]
 pub use kafka::KafkaProducer;
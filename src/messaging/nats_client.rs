@@ -1,6 +1,8 @@
 use crate::errors::ApiError;
 use crate::messaging::message_queue::{Message, MessageQueue, MessageHandler};
+use crate::health::{tcp_probe, CheckResult, HealthCheckable};
 use async_trait::async_trait;
+use std::time::Duration;
 
 /// NATS configuration
 #[derive(Debug, Clone)]
@@ -44,3 +46,21 @@ impl MessageQueue for NatsClient {
     }
 }
 
+#[async_trait]
+impl HealthCheckable for NatsClient {
+    fn name(&self) -> &str {
+        "nats"
+    }
+
+    fn is_critical(&self) -> bool {
+        false
+    }
+
+    async fn check(&self) -> CheckResult {
+        CheckResult::timed(
+            Duration::from_millis(500),
+            tcp_probe(&self.config.url, Duration::from_secs(2)),
+        )
+        .await
+    }
+}
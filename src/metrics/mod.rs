@@ -1,7 +1,18 @@
 use prometheus::{
-    Encoder, HistogramVec, IntCounterVec, IntGaugeVec, Registry, TextEncoder,
+    Encoder, GaugeVec, HistogramOpts, HistogramVec, IntCounterVec, IntGaugeVec, Opts, Registry,
+    TextEncoder,
 };
-use std::sync::Arc;
+use std::collections::HashMap;
+use std::sync::{Arc, RwLock};
+
+use crate::config::settings::{MetricsSettings, DEFAULT_HTTP_DURATION_BUCKETS};
+use crate::errors::ApiError;
+
+#[cfg(feature = "metrics-otlp")]
+pub mod otlp;
+
+#[cfg(feature = "metrics-otlp")]
+pub use otlp::OtlpMetricsPusher;
 
 /// Metrics collector cho Prometheus
 pub struct MetricsCollector {
@@ -10,11 +21,42 @@ pub struct MetricsCollector {
     pub http_request_duration_seconds: HistogramVec,
     pub http_requests_in_flight: IntGaugeVec,
     pub active_connections: IntGaugeVec,
+    /// Error responses by numeric `ErrorCode` and route pattern
+    pub errors_total: IntCounterVec,
+    /// Background job runs by job type and result (`success`/`failure`)
+    pub jobs_total: IntCounterVec,
+    /// Duration of the most recent run per job type
+    pub job_last_duration_seconds: GaugeVec,
+    /// Database query duration by operation name (see `QueryTimer`)
+    pub db_query_duration_seconds: HistogramVec,
+    /// Calls to routes wrapped in the `Deprecation` middleware, by route pattern
+    pub deprecated_endpoint_hits_total: IntCounterVec,
+    /// Business metrics registered at startup, by name
+    counters: Arc<RwLock<HashMap<String, IntCounterVec>>>,
+    histograms: Arc<RwLock<HashMap<String, HistogramVec>>>,
 }
 
 impl MetricsCollector {
+    /// Collector without namespace, with the default latency buckets
     pub fn new() -> Arc<Self> {
-        let registry = Registry::new();
+        Self::build(Registry::new(), DEFAULT_HTTP_DURATION_BUCKETS.to_vec())
+    }
+
+    /// Every metric name is prefixed with `{namespace}_` (none if empty),
+    /// including business metrics registered later
+    pub fn from_settings(settings: &MetricsSettings) -> Arc<Self> {
+        let registry = match settings.namespace.trim() {
+            "" => Registry::new(),
+            namespace => Registry::new_custom(Some(namespace.to_string()), None)
+                .unwrap_or_else(|e| {
+                    tracing::warn!("Invalid metrics namespace {}: {}", namespace, e);
+                    Registry::new()
+                }),
+        };
+        Self::build(registry, settings.http_duration_buckets.clone())
+    }
+
+    fn build(registry: Registry, http_duration_buckets: Vec<f64>) -> Arc<Self> {
 
         // HTTP request counter
         let http_requests_total = IntCounterVec::new(
@@ -27,7 +69,8 @@ impl MetricsCollector {
         let http_request_duration_seconds = HistogramVec::new(
             prometheus::histogram_opts!(
                 "http_request_duration_seconds",
-                "HTTP request duration in seconds"
+                "HTTP request duration in seconds",
+                http_duration_buckets
             ),
             &["method", "endpoint"],
         )
@@ -47,11 +90,53 @@ impl MetricsCollector {
         )
         .unwrap();
 
+        // Error counter
+        let errors_total = IntCounterVec::new(
+            prometheus::opts!("errors_total", "Total error responses"),
+            &["error_code", "endpoint"],
+        )
+        .unwrap();
+
+        // Background job counters
+        let jobs_total = IntCounterVec::new(
+            prometheus::opts!("jobs_total", "Total background job runs"),
+            &["job_type", "result"],
+        )
+        .unwrap();
+
+        let job_last_duration_seconds = GaugeVec::new(
+            prometheus::opts!("job_last_duration_seconds", "Duration of the last background job run"),
+            &["job_type"],
+        )
+        .unwrap();
+
+        // Database query duration histogram
+        let db_query_duration_seconds = HistogramVec::new(
+            prometheus::histogram_opts!(
+                "db_query_duration_seconds",
+                "Database query duration in seconds"
+            ),
+            &["operation"],
+        )
+        .unwrap();
+
+        // Deprecated endpoint counter
+        let deprecated_endpoint_hits_total = IntCounterVec::new(
+            prometheus::opts!("deprecated_endpoint_hits_total", "Total calls to deprecated endpoints"),
+            &["route"],
+        )
+        .unwrap();
+
         // Register all metrics
         registry.register(Box::new(http_requests_total.clone())).unwrap();
         registry.register(Box::new(http_request_duration_seconds.clone())).unwrap();
         registry.register(Box::new(http_requests_in_flight.clone())).unwrap();
         registry.register(Box::new(active_connections.clone())).unwrap();
+        registry.register(Box::new(errors_total.clone())).unwrap();
+        registry.register(Box::new(jobs_total.clone())).unwrap();
+        registry.register(Box::new(job_last_duration_seconds.clone())).unwrap();
+        registry.register(Box::new(db_query_duration_seconds.clone())).unwrap();
+        registry.register(Box::new(deprecated_endpoint_hits_total.clone())).unwrap();
 
         Arc::new(Self {
             registry,
@@ -59,13 +144,113 @@ impl MetricsCollector {
             http_request_duration_seconds,
             http_requests_in_flight,
             active_connections,
+            errors_total,
+            jobs_total,
+            job_last_duration_seconds,
+            db_query_duration_seconds,
+            deprecated_endpoint_hits_total,
+            counters: Arc::new(RwLock::new(HashMap::new())),
+            histograms: Arc::new(RwLock::new(HashMap::new())),
         })
     }
 
+    /// Register a business counter, e.g. `orders_placed_total`
+    pub fn register_counter(&self, name: &str, help: &str, label_names: &[&str]) -> Result<(), ApiError> {
+        let counter = IntCounterVec::new(Opts::new(name, help), label_names)
+            .map_err(|e| ApiError::configuration(format!("Invalid counter {}: {}", name, e)))?;
+        self.registry
+            .register(Box::new(counter.clone()))
+            .map_err(|e| ApiError::configuration(format!("Failed to register counter {}: {}", name, e)))?;
+
+        self.counters
+            .write()
+            .map_err(|_| ApiError::internal("Failed to acquire write lock on metrics"))?
+            .insert(name.to_string(), counter);
+        Ok(())
+    }
+
+    /// Register a business histogram with the default buckets
+    pub fn register_histogram(&self, name: &str, help: &str, label_names: &[&str]) -> Result<(), ApiError> {
+        let histogram = HistogramVec::new(HistogramOpts::new(name, help), label_names)
+            .map_err(|e| ApiError::configuration(format!("Invalid histogram {}: {}", name, e)))?;
+        self.registry
+            .register(Box::new(histogram.clone()))
+            .map_err(|e| ApiError::configuration(format!("Failed to register histogram {}: {}", name, e)))?;
+
+        self.histograms
+            .write()
+            .map_err(|_| ApiError::internal("Failed to acquire write lock on metrics"))?
+            .insert(name.to_string(), histogram);
+        Ok(())
+    }
+
+    /// Increment a counter registered with [`register_counter`](Self::register_counter)
+    pub fn inc_counter(&self, name: &str, labels: &[(&str, &str)]) -> Result<(), ApiError> {
+        let counters = self
+            .counters
+            .read()
+            .map_err(|_| ApiError::internal("Failed to acquire read lock on metrics"))?;
+        let counter = counters
+            .get(name)
+            .ok_or_else(|| ApiError::internal(format!("Counter {} is not registered", name)))?;
+
+        let labels: HashMap<&str, &str> = labels.iter().copied().collect();
+        counter
+            .get_metric_with(&labels)
+            .map_err(|e| ApiError::internal(format!("Invalid labels for counter {}: {}", name, e)))?
+            .inc();
+        Ok(())
+    }
+
+    /// Record a value in a histogram registered with
+    /// [`register_histogram`](Self::register_histogram)
+    pub fn observe(&self, name: &str, value: f64, labels: &[(&str, &str)]) -> Result<(), ApiError> {
+        let histograms = self
+            .histograms
+            .read()
+            .map_err(|_| ApiError::internal("Failed to acquire read lock on metrics"))?;
+        let histogram = histograms
+            .get(name)
+            .ok_or_else(|| ApiError::internal(format!("Histogram {} is not registered", name)))?;
+
+        let labels: HashMap<&str, &str> = labels.iter().copied().collect();
+        histogram
+            .get_metric_with(&labels)
+            .map_err(|e| ApiError::internal(format!("Invalid labels for histogram {}: {}", name, e)))?
+            .observe(value);
+        Ok(())
+    }
+
+    /// Count one background job run and remember its duration
+    pub fn record_job_run(&self, job_type: &str, success: bool, duration: std::time::Duration) {
+        let result = if success { "success" } else { "failure" };
+        self.jobs_total.with_label_values(&[job_type, result]).inc();
+        self.job_last_duration_seconds
+            .with_label_values(&[job_type])
+            .set(duration.as_secs_f64());
+    }
+
+    /// Observe one database query
+    pub fn record_db_query(&self, operation: &str, duration: std::time::Duration) {
+        self.db_query_duration_seconds
+            .with_label_values(&[operation])
+            .observe(duration.as_secs_f64());
+    }
+
+    /// Count one call to a deprecated route
+    pub fn record_deprecated_hit(&self, route: &str) {
+        self.deprecated_endpoint_hits_total.with_label_values(&[route]).inc();
+    }
+
+    /// Snapshot of every registered family, shared by scrape and OTLP push
+    pub fn gather(&self) -> Vec<prometheus::proto::MetricFamily> {
+        self.registry.gather()
+    }
+
     /// Export metrics in Prometheus format
     pub fn export(&self) -> String {
         let encoder = TextEncoder::new();
-        let metric_families = self.registry.gather();
+        let metric_families = self.gather();
         let mut buffer = Vec::new();
         encoder.encode(&metric_families, &mut buffer).unwrap();
         String::from_utf8(buffer).unwrap()
@@ -86,6 +271,13 @@ impl Clone for MetricsCollector {
             http_request_duration_seconds: self.http_request_duration_seconds.clone(),
             http_requests_in_flight: self.http_requests_in_flight.clone(),
             active_connections: self.active_connections.clone(),
+            errors_total: self.errors_total.clone(),
+            jobs_total: self.jobs_total.clone(),
+            job_last_duration_seconds: self.job_last_duration_seconds.clone(),
+            db_query_duration_seconds: self.db_query_duration_seconds.clone(),
+            deprecated_endpoint_hits_total: self.deprecated_endpoint_hits_total.clone(),
+            counters: self.counters.clone(),
+            histograms: self.histograms.clone(),
         }
     }
 }
@@ -0,0 +1,233 @@
+use opentelemetry::{InstrumentationScope, KeyValue};
+use opentelemetry_otlp::WithExportConfig;
+use opentelemetry_sdk::metrics::data::{
+    Aggregation, DataPoint, Gauge, Histogram, HistogramDataPoint, Metric, ResourceMetrics,
+    ScopeMetrics, Sum,
+};
+use opentelemetry_sdk::metrics::exporter::PushMetricExporter;
+use opentelemetry_sdk::metrics::Temporality;
+use opentelemetry_sdk::Resource;
+use prometheus::proto::{MetricFamily, MetricType};
+use std::sync::Arc;
+use std::time::{Duration, SystemTime};
+use tokio::task::JoinHandle;
+
+use super::MetricsCollector;
+use crate::config::settings::{MetricsSettings, TracingSettings};
+use crate::errors::ApiError;
+
+/// Đẩy metrics của `MetricsCollector` tới OTLP collector theo chu kỳ
+///
+/// Every push converts a fresh `gather()` of the same registry that
+/// Prometheus scrapes, so both modes report identical values. Counters and
+/// histograms are sent as cumulative sums since process start.
+pub struct OtlpMetricsPusher<E> {
+    collector: Arc<MetricsCollector>,
+    exporter: E,
+    interval: Duration,
+    resource: Resource,
+    start_time: SystemTime,
+}
+
+impl OtlpMetricsPusher<opentelemetry_otlp::MetricExporter> {
+    /// OTLP/gRPC exporter to `metrics.otlp_endpoint`, tagged with the
+    /// tracing service name and version
+    pub fn from_settings(
+        collector: Arc<MetricsCollector>,
+        metrics: &MetricsSettings,
+        tracing: &TracingSettings,
+    ) -> Result<Self, ApiError> {
+        let exporter = opentelemetry_otlp::MetricExporter::builder()
+            .with_tonic()
+            .with_endpoint(&metrics.otlp_endpoint)
+            .with_temporality(Temporality::Cumulative)
+            .build()
+            .map_err(|e| {
+                ApiError::configuration(format!("Failed to build OTLP metrics exporter: {}", e))
+            })?;
+
+        Ok(Self::new(collector, exporter)
+            .with_interval(Duration::from_secs(metrics.push_interval_secs))
+            .with_resource(Resource::new(vec![
+                KeyValue::new("service.name", tracing.service_name.clone()),
+                KeyValue::new("service.version", tracing.service_version.clone()),
+            ])))
+    }
+}
+
+impl<E: PushMetricExporter> OtlpMetricsPusher<E> {
+    pub fn new(collector: Arc<MetricsCollector>, exporter: E) -> Self {
+        Self {
+            collector,
+            exporter,
+            interval: Duration::from_secs(60),
+            resource: Resource::empty(),
+            start_time: SystemTime::now(),
+        }
+    }
+
+    pub fn with_interval(mut self, interval: Duration) -> Self {
+        self.interval = interval;
+        self
+    }
+
+    pub fn with_resource(mut self, resource: Resource) -> Self {
+        self.resource = resource;
+        self
+    }
+
+    /// Current registry contents in OTLP form
+    pub fn resource_metrics(&self) -> ResourceMetrics {
+        let now = SystemTime::now();
+        let metrics = self
+            .collector
+            .gather()
+            .iter()
+            .filter_map(|family| convert_family(family, self.start_time, now))
+            .collect();
+
+        ResourceMetrics {
+            resource: self.resource.clone(),
+            scope_metrics: vec![ScopeMetrics {
+                scope: InstrumentationScope::builder(env!("CARGO_PKG_NAME"))
+                    .with_version(env!("CARGO_PKG_VERSION"))
+                    .build(),
+                metrics,
+            }],
+        }
+    }
+
+    /// Push one snapshot now
+    pub async fn push(&self) -> Result<(), ApiError> {
+        let mut metrics = self.resource_metrics();
+        self.exporter
+            .export(&mut metrics)
+            .await
+            .map_err(|e| ApiError::external_service(format!("Metrics push failed: {}", e), "otlp"))
+    }
+
+    /// Push every `interval` (first push after one interval); failures are
+    /// logged and retried on the next tick
+    pub fn start(self) -> JoinHandle<()> {
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(self.interval);
+            ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+            ticker.tick().await;
+
+            loop {
+                ticker.tick().await;
+                if let Err(e) = self.push().await {
+                    tracing::warn!("OTLP metrics push failed: {}", e);
+                }
+            }
+        })
+    }
+}
+
+fn convert_family(
+    family: &MetricFamily,
+    start_time: SystemTime,
+    now: SystemTime,
+) -> Option<Metric> {
+    let data: Box<dyn Aggregation> = match family.get_field_type() {
+        MetricType::COUNTER => Box::new(Sum {
+            data_points: family
+                .get_metric()
+                .iter()
+                .map(|m| data_point(m, m.get_counter().get_value(), start_time, now))
+                .collect(),
+            temporality: Temporality::Cumulative,
+            is_monotonic: true,
+        }),
+        MetricType::GAUGE | MetricType::UNTYPED => Box::new(Gauge {
+            data_points: family
+                .get_metric()
+                .iter()
+                .map(|m| {
+                    let value = if family.get_field_type() == MetricType::GAUGE {
+                        m.get_gauge().get_value()
+                    } else {
+                        m.get_untyped().get_value()
+                    };
+                    data_point(m, value, start_time, now)
+                })
+                .collect(),
+        }),
+        MetricType::HISTOGRAM => Box::new(Histogram {
+            data_points: family
+                .get_metric()
+                .iter()
+                .map(|m| histogram_point(m, start_time, now))
+                .collect(),
+            temporality: Temporality::Cumulative,
+        }),
+        // Không có summary nào được đăng ký
+        MetricType::SUMMARY => return None,
+    };
+
+    Some(Metric {
+        name: family.get_name().to_string().into(),
+        description: family.get_help().to_string().into(),
+        unit: "".into(),
+        data,
+    })
+}
+
+fn attributes(metric: &prometheus::proto::Metric) -> Vec<KeyValue> {
+    metric
+        .get_label()
+        .iter()
+        .map(|label| KeyValue::new(label.get_name().to_string(), label.get_value().to_string()))
+        .collect()
+}
+
+fn data_point(
+    metric: &prometheus::proto::Metric,
+    value: f64,
+    start_time: SystemTime,
+    now: SystemTime,
+) -> DataPoint<f64> {
+    DataPoint {
+        attributes: attributes(metric),
+        start_time: Some(start_time),
+        time: Some(now),
+        value,
+        exemplars: Vec::new(),
+    }
+}
+
+/// Prometheus buckets are cumulative and omit `+Inf`; OTLP wants per-bucket
+/// counts with one extra overflow bucket
+fn histogram_point(
+    metric: &prometheus::proto::Metric,
+    start_time: SystemTime,
+    now: SystemTime,
+) -> HistogramDataPoint<f64> {
+    let histogram = metric.get_histogram();
+    let mut bounds = Vec::new();
+    let mut bucket_counts = Vec::new();
+    let mut previous = 0;
+
+    for bucket in histogram.get_bucket() {
+        if bucket.get_upper_bound().is_infinite() {
+            continue;
+        }
+        bounds.push(bucket.get_upper_bound());
+        bucket_counts.push(bucket.get_cumulative_count().saturating_sub(previous));
+        previous = bucket.get_cumulative_count();
+    }
+    bucket_counts.push(histogram.get_sample_count().saturating_sub(previous));
+
+    HistogramDataPoint {
+        attributes: attributes(metric),
+        start_time,
+        time: now,
+        count: histogram.get_sample_count(),
+        bounds,
+        bucket_counts,
+        min: None,
+        max: None,
+        sum: histogram.get_sample_sum(),
+        exemplars: Vec::new(),
+    }
+}
@@ -0,0 +1,33 @@
+use actix_web::{dev::ServiceRequest, http::header, HttpMessage};
+use sha2::{Digest, Sha256};
+
+use crate::auth::Claims;
+
+/// Caller identity for per-caller middleware state (idempotency keys,
+/// coalesced requests)
+///
+/// The JWT subject when `AuthMiddleware` already ran, else a hash of the
+/// `Authorization`/`Cookie` headers, so raw credentials are never stored.
+pub(crate) fn caller_subject(req: &ServiceRequest) -> String {
+    if let Some(claims) = req.extensions().get::<Claims>() {
+        return format!("sub:{}", claims.sub);
+    }
+
+    let mut hasher = Sha256::new();
+    let mut has_credentials = false;
+    for name in [header::AUTHORIZATION, header::COOKIE] {
+        for value in req.headers().get_all(&name) {
+            hasher.update(name.as_str().as_bytes());
+            hasher.update(b":");
+            hasher.update(value.as_bytes());
+            hasher.update(b"\n");
+            has_credentials = true;
+        }
+    }
+
+    if has_credentials {
+        format!("credentials:{}", hex::encode(hasher.finalize()))
+    } else {
+        "anonymous".to_string()
+    }
+}
@@ -0,0 +1,250 @@
+use actix_web::{
+    body::{to_bytes, BodySize, BoxBody, MessageBody},
+    dev::{forward_ready, Service, ServiceRequest, ServiceResponse, Transform},
+    http::{
+        header::{self, HeaderMap},
+        Method, StatusCode,
+    },
+    web::Bytes,
+    Error, HttpResponse,
+};
+use futures_util::future::LocalBoxFuture;
+use std::collections::HashMap;
+use std::future::{ready, Ready};
+use std::rc::Rc;
+use std::sync::{Arc, Mutex};
+use tokio::sync::watch;
+
+use super::caller::caller_subject;
+use crate::errors::ApiError;
+
+/// Set on responses shared from another in-flight request
+pub const COALESCED_HEADER: &str = "coalesced";
+
+/// Their response depends on the caller's cached copy, so they are never shared
+const CONDITIONAL_HEADERS: [header::HeaderName; 6] = [
+    header::IF_NONE_MATCH,
+    header::IF_MODIFIED_SINCE,
+    header::IF_MATCH,
+    header::IF_UNMODIFIED_SINCE,
+    header::IF_RANGE,
+    header::RANGE,
+];
+
+type SharedSlot = Option<Arc<SharedResponse>>;
+
+/// Response of the leading request, handed to every duplicate
+#[derive(Debug)]
+struct SharedResponse {
+    status: StatusCode,
+    headers: HeaderMap,
+    body: Bytes,
+}
+
+impl SharedResponse {
+    /// Copy for a follower; cookies set for the leader stay with the leader
+    fn to_http_response(&self) -> HttpResponse {
+        let mut response = HttpResponse::build(self.status);
+        for (name, value) in self.headers.iter() {
+            if *name != header::SET_COOKIE {
+                response.append_header((name.clone(), value.clone()));
+            }
+        }
+        response
+            .insert_header((COALESCED_HEADER, "true"))
+            .body(self.body.clone())
+    }
+}
+
+/// Middleware gộp các GET giống hệt nhau đang chạy đồng thời
+///
+/// Requests are identical when method, path, query, `Accept`,
+/// `Accept-Language` and the caller (JWT subject, else a hash of
+/// `Authorization`/`Cookie`) match. The first one runs the handler;
+/// duplicates arriving before it finishes wait and get a copy of its
+/// response, without its `Set-Cookie`. Nothing is kept once the leader
+/// completes, so this is not a cache. Streaming bodies are not shared: their
+/// duplicates, like those of a failed leader, run the handler themselves.
+/// Conditional and `Range` requests are never coalesced.
+///
+/// Clones share the in-flight table, so build it once outside the
+/// `HttpServer` factory to coalesce across workers.
+#[derive(Clone, Default)]
+pub struct RequestCoalescer {
+    in_flight: Arc<Mutex<HashMap<String, watch::Receiver<SharedSlot>>>>,
+}
+
+impl RequestCoalescer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Number of distinct requests currently being computed
+    pub fn in_flight(&self) -> usize {
+        self.in_flight.lock().map(|map| map.len()).unwrap_or_default()
+    }
+
+    /// Join the computation for `key`, or become its leader
+    fn join(&self, key: &str) -> Result<Role, ApiError> {
+        let mut in_flight = self
+            .in_flight
+            .lock()
+            .map_err(|_| ApiError::internal("Failed to acquire lock on request coalescer"))?;
+
+        if let Some(receiver) = in_flight.get(key) {
+            return Ok(Role::Follower(receiver.clone()));
+        }
+
+        let (sender, receiver) = watch::channel(None);
+        in_flight.insert(key.to_string(), receiver);
+        Ok(Role::Leader(InFlight {
+            in_flight: self.in_flight.clone(),
+            key: key.to_string(),
+            sender,
+        }))
+    }
+}
+
+enum Role {
+    Leader(InFlight),
+    Follower(watch::Receiver<SharedSlot>),
+}
+
+/// Leader's slot; removed from the table when dropped (also on cancellation),
+/// which wakes followers still waiting for a result
+struct InFlight {
+    in_flight: Arc<Mutex<HashMap<String, watch::Receiver<SharedSlot>>>>,
+    key: String,
+    sender: watch::Sender<SharedSlot>,
+}
+
+impl InFlight {
+    fn publish(self, response: SharedResponse) {
+        // Gỡ key trước: request đến sau lúc này phải tự chạy handler
+        self.release();
+        let _ = self.sender.send(Some(Arc::new(response)));
+    }
+
+    fn release(&self) {
+        if let Ok(mut in_flight) = self.in_flight.lock() {
+            in_flight.remove(&self.key);
+        }
+    }
+}
+
+impl Drop for InFlight {
+    fn drop(&mut self) {
+        self.release();
+    }
+}
+
+impl<S, B> Transform<S, ServiceRequest> for RequestCoalescer
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    S::Future: 'static,
+    B: MessageBody + 'static,
+{
+    type Response = ServiceResponse<BoxBody>;
+    type Error = Error;
+    type InitError = ();
+    type Transform = RequestCoalescerMiddleware<S>;
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ready(Ok(RequestCoalescerMiddleware {
+            service: Rc::new(service),
+            coalescer: self.clone(),
+        }))
+    }
+}
+
+pub struct RequestCoalescerMiddleware<S> {
+    service: Rc<S>,
+    coalescer: RequestCoalescer,
+}
+
+impl<S, B> Service<ServiceRequest> for RequestCoalescerMiddleware<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    S::Future: 'static,
+    B: MessageBody + 'static,
+{
+    type Response = ServiceResponse<BoxBody>;
+    type Error = Error;
+    type Future = LocalBoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    forward_ready!(service);
+
+    fn call(&self, req: ServiceRequest) -> Self::Future {
+        let is_conditional = CONDITIONAL_HEADERS
+            .iter()
+            .any(|name| req.headers().contains_key(name));
+        if req.method() != Method::GET || is_conditional {
+            let fut = self.service.call(req);
+            return Box::pin(async move { Ok(fut.await?.map_into_boxed_body()) });
+        }
+
+        let service = self.service.clone();
+        let coalescer = self.coalescer.clone();
+
+        Box::pin(async move {
+            let leader = match coalescer.join(&coalesce_key(&req))? {
+                Role::Leader(leader) => leader,
+                Role::Follower(mut receiver) => {
+                    let shared = receiver
+                        .wait_for(Option::is_some)
+                        .await
+                        .ok()
+                        .and_then(|slot| slot.clone());
+                    return match shared {
+                        Some(shared) => Ok(req.into_response(shared.to_http_response())),
+                        // Leader lỗi, bị hủy hoặc trả stream
+                        None => Ok(service.call(req).await?.map_into_boxed_body()),
+                    };
+                }
+            };
+
+            let res = service.call(req).await?;
+            if matches!(res.response().body().size(), BodySize::Stream) {
+                return Ok(res.map_into_boxed_body());
+            }
+
+            let (req, res) = res.into_parts();
+            let (head, body) = res.into_parts();
+            let body = match to_bytes(body).await {
+                Ok(body) => body,
+                Err(e) => {
+                    let e: Box<dyn std::error::Error> = e.into();
+                    return Err(ApiError::internal(format!("Failed to read response body: {}", e)).into());
+                }
+            };
+
+            leader.publish(SharedResponse {
+                status: head.status(),
+                headers: head.headers().clone(),
+                body: body.clone(),
+            });
+
+            Ok(ServiceResponse::new(req, head.set_body(body).map_into_boxed_body()))
+        })
+    }
+}
+
+/// `{method} {path}?{query} {accept} {accept-language} {subject}`
+fn coalesce_key(req: &ServiceRequest) -> String {
+    let header_value = |name: header::HeaderName| {
+        req.headers()
+            .get(name)
+            .and_then(|v| v.to_str().ok())
+            .unwrap_or_default()
+    };
+    format!(
+        "{} {}?{} {} {} {}",
+        req.method(),
+        req.path(),
+        req.query_string(),
+        header_value(header::ACCEPT),
+        header_value(header::ACCEPT_LANGUAGE),
+        caller_subject(req)
+    )
+}
@@ -0,0 +1,210 @@
+use actix_http::encoding::Encoder;
+use actix_web::{
+    body::{BodySize, BoxBody, MessageBody},
+    dev::{forward_ready, Service, ServiceRequest, ServiceResponse, Transform},
+    http::header::{self, ContentEncoding, HeaderValue},
+    Error,
+};
+use futures_util::future::LocalBoxFuture;
+use serde::Deserialize;
+use std::future::{ready, Ready};
+
+/// Response nhỏ hơn ngưỡng này không được nén
+pub const DEFAULT_COMPRESSION_MIN_BYTES: usize = 1024;
+
+/// Encodings the server may use for responses
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CompressionMode {
+    Off,
+    Gzip,
+    Brotli,
+    /// Brotli or gzip, whichever the client prefers (brotli on a tie)
+    #[default]
+    Auto,
+}
+
+impl std::str::FromStr for CompressionMode {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "off" | "none" | "false" => Ok(Self::Off),
+            "gzip" => Ok(Self::Gzip),
+            "brotli" | "br" => Ok(Self::Brotli),
+            "auto" | "true" => Ok(Self::Auto),
+            other => Err(format!("Unknown compression mode: {}", other)),
+        }
+    }
+}
+
+impl CompressionMode {
+    /// Allowed encodings, preferred first
+    fn encodings(self) -> &'static [ContentEncoding] {
+        match self {
+            Self::Off => &[],
+            Self::Gzip => &[ContentEncoding::Gzip],
+            Self::Brotli => &[ContentEncoding::Brotli],
+            Self::Auto => &[ContentEncoding::Brotli, ContentEncoding::Gzip],
+        }
+    }
+
+    /// Allowed encoding with the highest quality in `Accept-Encoding`
+    fn negotiate(self, accept_encoding: &str) -> Option<ContentEncoding> {
+        let mut best: Option<(ContentEncoding, f32)> = None;
+        for &encoding in self.encodings() {
+            let quality = quality_of(accept_encoding, encoding.as_str());
+            if quality > best.map_or(0.0, |(_, q)| q) {
+                best = Some((encoding, quality));
+            }
+        }
+        best.map(|(encoding, _)| encoding)
+    }
+}
+
+/// `q` of `coding` in `Accept-Encoding`, falling back to `*`; 0 if absent
+fn quality_of(accept_encoding: &str, coding: &str) -> f32 {
+    let mut wildcard = None;
+    for entry in accept_encoding.split(',') {
+        let mut parts = entry.split(';');
+        let name = parts.next().unwrap_or_default().trim();
+        let quality = parts
+            .filter_map(|param| param.trim().strip_prefix("q="))
+            .find_map(|q| q.trim().parse::<f32>().ok())
+            .unwrap_or(1.0);
+
+        if name.eq_ignore_ascii_case(coding) {
+            return quality;
+        }
+        if name == "*" {
+            wildcard = Some(quality);
+        }
+    }
+    wildcard.unwrap_or(0.0)
+}
+
+/// SSE phải tới client ngay từng event
+fn is_event_stream(headers: &header::HeaderMap) -> bool {
+    headers
+        .get(header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .is_some_and(|v| v.trim_start().to_ascii_lowercase().starts_with("text/event-stream"))
+}
+
+/// Middleware nén response (gzip/brotli) theo `Accept-Encoding`
+///
+/// Uses Actix's encoder, so streaming bodies (e.g. the NDJSON export) are
+/// compressed chunk by chunk instead of being buffered. Responses whose size
+/// is known and below `min_bytes`, that already carry `Content-Encoding`, or
+/// that are `text/event-stream` (the encoder would hold events back until its
+/// buffer fills) are sent as is. A handler opts a response out with
+/// `Content-Encoding: identity`, which is removed before sending.
+#[derive(Debug, Clone)]
+pub struct Compression {
+    mode: CompressionMode,
+    min_bytes: usize,
+}
+
+impl Compression {
+    pub fn new(mode: CompressionMode) -> Self {
+        Self {
+            mode,
+            min_bytes: DEFAULT_COMPRESSION_MIN_BYTES,
+        }
+    }
+
+    pub fn with_min_bytes(mut self, min_bytes: usize) -> Self {
+        self.min_bytes = min_bytes;
+        self
+    }
+}
+
+impl Default for Compression {
+    fn default() -> Self {
+        Self::new(CompressionMode::default())
+    }
+}
+
+impl<S, B> Transform<S, ServiceRequest> for Compression
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error>,
+    S::Future: 'static,
+    B: MessageBody + 'static,
+{
+    type Response = ServiceResponse<BoxBody>;
+    type Error = Error;
+    type InitError = ();
+    type Transform = CompressionMiddleware<S>;
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ready(Ok(CompressionMiddleware {
+            service,
+            config: self.clone(),
+        }))
+    }
+}
+
+pub struct CompressionMiddleware<S> {
+    service: S,
+    config: Compression,
+}
+
+impl<S, B> Service<ServiceRequest> for CompressionMiddleware<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error>,
+    S::Future: 'static,
+    B: MessageBody + 'static,
+{
+    type Response = ServiceResponse<BoxBody>;
+    type Error = Error;
+    type Future = LocalBoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    forward_ready!(service);
+
+    fn call(&self, req: ServiceRequest) -> Self::Future {
+        let mode = self.config.mode;
+        let min_bytes = self.config.min_bytes as u64;
+        let encoding = req
+            .headers()
+            .get(header::ACCEPT_ENCODING)
+            .and_then(|v| v.to_str().ok())
+            .and_then(|accept_encoding| mode.negotiate(accept_encoding));
+        let fut = self.service.call(req);
+
+        Box::pin(async move {
+            let mut res = fut.await?;
+
+            let opted_out = res
+                .headers()
+                .get(header::CONTENT_ENCODING)
+                .is_some_and(|v| v.as_bytes().eq_ignore_ascii_case(b"identity"));
+            if opted_out {
+                res.headers_mut().remove(header::CONTENT_ENCODING);
+            }
+
+            let compressible = !opted_out
+                && !res.headers().contains_key(header::CONTENT_ENCODING)
+                && !is_event_stream(res.headers())
+                && match res.response().body().size() {
+                    BodySize::Sized(size) => size >= min_bytes,
+                    BodySize::Stream => true,
+                    BodySize::None => false,
+                };
+
+            match encoding {
+                // Encoder tự thêm Content-Encoding và Vary
+                Some(encoding) if compressible => {
+                    Ok(res.map_body(|head, body| Encoder::response(encoding, head, body).boxed()))
+                }
+                _ => {
+                    if mode != CompressionMode::Off {
+                        res.headers_mut()
+                            .append(header::VARY, HeaderValue::from_static("accept-encoding"));
+                    }
+                    Ok(res.map_into_boxed_body())
+                }
+            }
+        })
+    }
+}
@@ -0,0 +1,102 @@
+use actix_web::{dev::Payload, http::header, FromRequest, HttpMessage, HttpRequest};
+use std::future::{ready, Ready};
+use std::sync::Arc;
+
+use super::locale::preferred_locale;
+use super::request_id::{trace_id_from_traceparent, RequestIdValue};
+use crate::auth::{AuthenticatedUser, Claims};
+use crate::errors::ApiError;
+use crate::multitenancy::{TenantId, TenantMiddleware};
+
+/// Các giá trị cross-cutting của request trong một extractor
+///
+/// ```ignore
+/// async fn get_report(ctx: RequestContext) -> Result<HttpResponse, ApiError> {
+///     let user = ctx.require_user()?;
+///     tracing::info!(request_id = ?ctx.request_id(), tenant = ?ctx.tenant_id(), "report");
+/// }
+/// ```
+///
+/// Every piece is optional: routes outside `AuthMiddleware` simply have no
+/// user, and tokens without a tenant no tenant. The context is built on each
+/// extraction rather than cached, so one taken before `AuthMiddleware` ran
+/// never hides the user from handlers.
+#[derive(Debug, Clone)]
+pub struct RequestContext(Arc<ContextInner>);
+
+#[derive(Debug)]
+struct ContextInner {
+    request_id: Option<String>,
+    user: Option<AuthenticatedUser>,
+    tenant_id: Option<TenantId>,
+    requested_tenant_id: Option<TenantId>,
+    locale: Option<String>,
+    trace_id: Option<String>,
+}
+
+impl RequestContext {
+    fn from_http_request(req: &HttpRequest) -> Self {
+        let extensions = req.extensions();
+        let header_value =
+            |name: header::HeaderName| req.headers().get(name).and_then(|v| v.to_str().ok());
+
+        let claims = extensions.get::<Claims>().cloned();
+
+        Self(Arc::new(ContextInner {
+            request_id: extensions.get::<RequestIdValue>().map(|id| id.0.clone()),
+            tenant_id: claims.as_ref().and_then(|claims| claims.tenant_id.clone()),
+            user: claims.map(AuthenticatedUser),
+            requested_tenant_id: TenantMiddleware::extract_tenant_id(req)
+                .filter(|id| !id.trim().is_empty()),
+            locale: header_value(header::ACCEPT_LANGUAGE).and_then(preferred_locale),
+            trace_id: header_value(header::HeaderName::from_static("traceparent"))
+                .and_then(trace_id_from_traceparent),
+        }))
+    }
+
+    /// Set by the `RequestId` middleware
+    pub fn request_id(&self) -> Option<&str> {
+        self.0.request_id.as_deref()
+    }
+
+    /// Caller verified by `AuthMiddleware`
+    pub fn user(&self) -> Option<&AuthenticatedUser> {
+        self.0.user.as_ref()
+    }
+
+    /// Like [`user`](Self::user), but `401` when the route is not authenticated
+    pub fn require_user(&self) -> Result<&AuthenticatedUser, ApiError> {
+        self.user().ok_or_else(|| ApiError::unauthorized("Authentication required"))
+    }
+
+    /// Tenant of the verified token (`Claims.tenant_id`)
+    pub fn tenant_id(&self) -> Option<&str> {
+        self.0.tenant_id.as_deref()
+    }
+
+    /// From `X-Tenant-ID`; set by the client and **not verified**, so check it
+    /// against [`tenant_id`](Self::tenant_id) or the user's memberships
+    /// before trusting it
+    pub fn requested_tenant_id(&self) -> Option<&str> {
+        self.0.requested_tenant_id.as_deref()
+    }
+
+    /// Highest-weighted tag of `Accept-Language`, e.g. `vi-VN`
+    pub fn locale(&self) -> Option<&str> {
+        self.0.locale.as_deref()
+    }
+
+    /// W3C trace id of the inbound `traceparent`
+    pub fn trace_id(&self) -> Option<&str> {
+        self.0.trace_id.as_deref()
+    }
+}
+
+impl FromRequest for RequestContext {
+    type Error = ApiError;
+    type Future = Ready<Result<Self, Self::Error>>;
+
+    fn from_request(req: &HttpRequest, _: &mut Payload) -> Self::Future {
+        ready(Ok(Self::from_http_request(req)))
+    }
+}
@@ -0,0 +1,40 @@
+use actix_cors::Cors;
+
+use crate::config::CorsSettings;
+use crate::errors::ApiError;
+
+/// Build the CORS middleware from settings
+///
+/// Fails with a configuration error when the settings are invalid, e.g. a
+/// wildcard origin combined with `allow_credentials`.
+pub fn build_cors(settings: &CorsSettings) -> Result<Cors, ApiError> {
+    settings.validate().map_err(ApiError::configuration)?;
+
+    let mut cors = Cors::default().max_age(settings.max_age);
+
+    if settings.allows_any_origin() {
+        cors = cors.allow_any_origin();
+    } else {
+        for origin in &settings.allowed_origins {
+            cors = cors.allowed_origin(origin);
+        }
+    }
+
+    if settings.allowed_methods.iter().any(|method| method == "*") {
+        cors = cors.allow_any_method();
+    } else {
+        cors = cors.allowed_methods(settings.allowed_methods.iter().map(String::as_str));
+    }
+
+    if settings.allowed_headers.iter().any(|header| header == "*") {
+        cors = cors.allow_any_header();
+    } else {
+        cors = cors.allowed_headers(settings.allowed_headers.iter().map(String::as_str));
+    }
+
+    if settings.allow_credentials {
+        cors = cors.supports_credentials();
+    }
+
+    Ok(cors)
+}
@@ -0,0 +1,256 @@
+use actix_web::{
+    body::{to_bytes, BodySize, BoxBody, MessageBody},
+    dev::{forward_ready, Payload, Service, ServiceRequest, ServiceResponse, Transform},
+    error::PayloadError,
+    http::header::{self, HeaderMap},
+    web::{Bytes, BytesMut},
+    Error, HttpMessage,
+};
+use futures_util::future::LocalBoxFuture;
+use futures_util::StreamExt;
+use std::collections::HashSet;
+use std::future::{ready, Ready};
+use std::rc::Rc;
+use std::sync::Arc;
+
+use super::request_id::RequestIdValue;
+use crate::auth::Claims;
+use crate::errors::ApiError;
+use crate::security::{AuditEvent, AuditEventType, AuditLogger, Redactor};
+
+/// Header bật capture cho một request (cần permission [`DEBUG_CAPTURE_PERMISSION`])
+pub const DEBUG_CAPTURE_HEADER: &str = "x-debug-capture";
+
+pub const DEBUG_CAPTURE_PERMISSION: &str = "debug:capture";
+
+/// `AuditEventType::Custom` name of captured exchanges
+pub const DEBUG_CAPTURE_EVENT: &str = "DEBUG_CAPTURE";
+
+/// Bodies larger than this are recorded by size only
+pub const DEFAULT_CAPTURE_MAX_BYTES: usize = 64 * 1024;
+
+/// Ghi lại request/response body vào audit log để debug
+///
+/// Off unless the request matches a configured route pattern or request id,
+/// or sends `X-Debug-Capture: true` with the `debug:capture` permission in
+/// its JWT claims (so it must sit inside `AuthMiddleware`). Requests that are
+/// not captured pass straight through without buffering. Only JSON bodies
+/// are recorded, with sensitive fields masked by the [`Redactor`].
+///
+/// At most `max_body_bytes` of a request body are buffered; the rest streams
+/// to the handler untouched. Streaming responses (SSE, downloads) and sized
+/// ones over the limit are passed through and recorded by size only.
+#[derive(Clone)]
+pub struct DebugCapture {
+    audit: Arc<AuditLogger>,
+    routes: HashSet<String>,
+    request_ids: HashSet<String>,
+    max_body_bytes: usize,
+    redactor: Redactor,
+}
+
+impl DebugCapture {
+    pub fn new(audit: Arc<AuditLogger>) -> Self {
+        Self {
+            audit,
+            routes: HashSet::new(),
+            request_ids: HashSet::new(),
+            max_body_bytes: DEFAULT_CAPTURE_MAX_BYTES,
+            redactor: Redactor::default(),
+        }
+    }
+
+    /// Capture every request to `pattern` (as registered, e.g. `/users/{id}`)
+    pub fn with_route(mut self, pattern: impl Into<String>) -> Self {
+        self.routes.insert(pattern.into());
+        self
+    }
+
+    /// Capture the request with this `X-Request-ID`
+    pub fn with_request_id(mut self, request_id: impl Into<String>) -> Self {
+        self.request_ids.insert(request_id.into());
+        self
+    }
+
+    pub fn with_max_body_bytes(mut self, max_bytes: usize) -> Self {
+        self.max_body_bytes = max_bytes;
+        self
+    }
+
+    /// Fields masked in captured bodies (default: [`Redactor::default`])
+    pub fn with_redactor(mut self, redactor: Redactor) -> Self {
+        self.redactor = redactor;
+        self
+    }
+
+    /// Redacted JSON; other bodies are recorded by size only, since they
+    /// cannot be redacted
+    fn describe_body(&self, headers: &HeaderMap, body: &[u8]) -> String {
+        if body.is_empty() {
+            return String::new();
+        }
+        if body.len() > self.max_body_bytes {
+            return format!("<{} bytes, over capture limit>", body.len());
+        }
+
+        let is_json = headers
+            .get(header::CONTENT_TYPE)
+            .and_then(|v| v.to_str().ok())
+            .is_some_and(|v| v.to_ascii_lowercase().contains("json"));
+        is_json
+            .then(|| self.redactor.redact_body(body))
+            .flatten()
+            .unwrap_or_else(|| format!("<{} bytes, not JSON>", body.len()))
+    }
+}
+
+impl<S, B> Transform<S, ServiceRequest> for DebugCapture
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    S::Future: 'static,
+    B: MessageBody + 'static,
+{
+    type Response = ServiceResponse<BoxBody>;
+    type Error = Error;
+    type InitError = ();
+    type Transform = DebugCaptureMiddleware<S>;
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ready(Ok(DebugCaptureMiddleware {
+            service: Rc::new(service),
+            config: Rc::new(self.clone()),
+        }))
+    }
+}
+
+pub struct DebugCaptureMiddleware<S> {
+    service: Rc<S>,
+    config: Rc<DebugCapture>,
+}
+
+impl<S> DebugCaptureMiddleware<S> {
+    fn is_enabled(&self, req: &ServiceRequest) -> bool {
+        let route_matches = req
+            .match_pattern()
+            .is_some_and(|pattern| self.config.routes.contains(&pattern));
+        let id_matches = !self.config.request_ids.is_empty()
+            && req
+                .extensions()
+                .get::<RequestIdValue>()
+                .is_some_and(|id| self.config.request_ids.contains(&id.0));
+
+        route_matches || id_matches || header_requested(req)
+    }
+}
+
+/// `X-Debug-Capture: true` from a caller allowed to capture
+fn header_requested(req: &ServiceRequest) -> bool {
+    let requested = req
+        .headers()
+        .get(DEBUG_CAPTURE_HEADER)
+        .and_then(|v| v.to_str().ok())
+        .is_some_and(|v| v.eq_ignore_ascii_case("true") || v == "1");
+
+    requested
+        && req
+            .extensions()
+            .get::<Claims>()
+            .is_some_and(|claims| claims.has_permission(DEBUG_CAPTURE_PERMISSION))
+}
+
+impl<S, B> Service<ServiceRequest> for DebugCaptureMiddleware<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    S::Future: 'static,
+    B: MessageBody + 'static,
+{
+    type Response = ServiceResponse<BoxBody>;
+    type Error = Error;
+    type Future = LocalBoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    forward_ready!(service);
+
+    fn call(&self, mut req: ServiceRequest) -> Self::Future {
+        if !self.is_enabled(&req) {
+            let fut = self.service.call(req);
+            return Box::pin(async move { Ok(fut.await?.map_into_boxed_body()) });
+        }
+
+        let service = self.service.clone();
+        let config = self.config.clone();
+
+        Box::pin(async move {
+            let mut event = AuditEvent::new(
+                AuditEventType::Custom(DEBUG_CAPTURE_EVENT.to_string()),
+                format!("{} {}", req.method(), req.path()),
+            );
+            if let Some(id) = req.extensions().get::<RequestIdValue>() {
+                event = event.with_request_id(id.0.clone());
+            }
+            if let Some(claims) = req.extensions().get::<Claims>() {
+                event = event.with_user(claims.sub.clone());
+            }
+
+            // Chỉ buffer tối đa max_body_bytes, phần còn lại stream tiếp tới handler
+            let mut payload = req.take_payload();
+            let mut bytes = BytesMut::new();
+            let mut over_limit = false;
+            while let Some(chunk) = payload.next().await {
+                bytes.extend_from_slice(&chunk?);
+                if bytes.len() > config.max_body_bytes {
+                    over_limit = true;
+                    break;
+                }
+            }
+            let bytes = bytes.freeze();
+            let request_body = if over_limit {
+                format!("<over {} bytes, over capture limit>", config.max_body_bytes)
+            } else {
+                config.describe_body(req.headers(), &bytes)
+            };
+            if over_limit {
+                let buffered = futures_util::stream::once(ready(Ok::<Bytes, PayloadError>(bytes)));
+                req.set_payload(Payload::Stream {
+                    payload: Box::pin(buffered.chain(payload)),
+                });
+            } else {
+                req.set_payload(Payload::from(bytes));
+            }
+
+            let res = service.call(req).await?;
+            let status = res.status();
+            let event = event
+                .with_metadata("status".to_string(), status.as_u16().to_string())
+                .with_metadata("request_body".to_string(), request_body);
+
+            let skipped = match res.response().body().size() {
+                BodySize::Stream => Some("<streamed, not captured>".to_string()),
+                BodySize::Sized(len) if len > config.max_body_bytes as u64 => {
+                    Some(format!("<{} bytes, over capture limit>", len))
+                }
+                _ => None,
+            };
+            if let Some(response_body) = skipped {
+                config
+                    .audit
+                    .log(event.with_metadata("response_body".to_string(), response_body));
+                return Ok(res.map_into_boxed_body());
+            }
+
+            let (req, res) = res.into_parts();
+            let (head, body) = res.into_parts();
+            let body = to_bytes(body).await.map_err(|e| {
+                let e: Box<dyn std::error::Error> = e.into();
+                ApiError::internal(format!("Failed to read response body: {}", e))
+            })?;
+            let response_body = config.describe_body(head.headers(), &body);
+
+            config
+                .audit
+                .log(event.with_metadata("response_body".to_string(), response_body));
+
+            Ok(ServiceResponse::new(req, head.set_body(body).map_into_boxed_body()))
+        })
+    }
+}
@@ -0,0 +1,149 @@
+use actix_web::{
+    dev::{forward_ready, Service, ServiceRequest, ServiceResponse, Transform},
+    http::header::{self, HeaderName, HeaderValue},
+    Error,
+};
+use chrono::{DateTime, Utc};
+use futures_util::future::LocalBoxFuture;
+use std::future::{ready, Ready};
+#[cfg(feature = "observability-metrics")]
+use std::sync::Arc;
+
+#[cfg(feature = "observability-metrics")]
+use crate::metrics::MetricsCollector;
+
+pub const DEPRECATION_HEADER: &str = "deprecation";
+pub const SUNSET_HEADER: &str = "sunset";
+
+/// Middleware đánh dấu route sắp bị gỡ bỏ
+///
+/// Wrap the deprecated resource or scope; every response from it gets
+/// `Deprecation: true`, plus `Sunset` (RFC 8594) and a `Link` to the
+/// replacement when configured. Calls are logged and counted in
+/// `deprecated_endpoint_hits_total{route}` when metrics are attached.
+#[derive(Clone, Default)]
+pub struct Deprecation {
+    sunset: Option<DateTime<Utc>>,
+    replacement: Option<String>,
+    #[cfg(feature = "observability-metrics")]
+    metrics: Option<Arc<MetricsCollector>>,
+}
+
+impl Deprecation {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Date after which the route may be removed
+    pub fn with_sunset(mut self, sunset: DateTime<Utc>) -> Self {
+        self.sunset = Some(sunset);
+        self
+    }
+
+    /// URL of the endpoint to migrate to, sent as `rel="successor-version"`
+    pub fn with_replacement(mut self, url: impl Into<String>) -> Self {
+        self.replacement = Some(url.into());
+        self
+    }
+
+    #[cfg(feature = "observability-metrics")]
+    pub fn with_metrics(mut self, metrics: Arc<MetricsCollector>) -> Self {
+        self.metrics = Some(metrics);
+        self
+    }
+
+    /// Header values, built once per route
+    fn headers(&self) -> Vec<(HeaderName, HeaderValue)> {
+        let mut headers = vec![(
+            HeaderName::from_static(DEPRECATION_HEADER),
+            HeaderValue::from_static("true"),
+        )];
+        if let Some(sunset) = self.sunset {
+            // IMF-fixdate, như header Date
+            let date = sunset.format("%a, %d %b %Y %H:%M:%S GMT").to_string();
+            if let Ok(value) = HeaderValue::from_str(&date) {
+                headers.push((HeaderName::from_static(SUNSET_HEADER), value));
+            }
+        }
+        if let Some(url) = &self.replacement {
+            match HeaderValue::from_str(&format!("<{}>; rel=\"successor-version\"", url)) {
+                Ok(value) => headers.push((header::LINK, value)),
+                Err(_) => tracing::warn!("Invalid deprecation replacement URL: {}", url),
+            }
+        }
+        headers
+    }
+}
+
+impl<S, B> Transform<S, ServiceRequest> for Deprecation
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error>,
+    S::Future: 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Error = Error;
+    type InitError = ();
+    type Transform = DeprecationMiddleware<S>;
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ready(Ok(DeprecationMiddleware {
+            service,
+            headers: self.headers(),
+            #[cfg(feature = "observability-metrics")]
+            metrics: self.metrics.clone(),
+        }))
+    }
+}
+
+pub struct DeprecationMiddleware<S> {
+    service: S,
+    headers: Vec<(HeaderName, HeaderValue)>,
+    #[cfg(feature = "observability-metrics")]
+    metrics: Option<Arc<MetricsCollector>>,
+}
+
+impl<S, B> Service<ServiceRequest> for DeprecationMiddleware<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error>,
+    S::Future: 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Error = Error;
+    type Future = LocalBoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    forward_ready!(service);
+
+    fn call(&self, req: ServiceRequest) -> Self::Future {
+        let route = req.match_pattern().unwrap_or_else(|| req.path().to_string());
+        let user_agent = req
+            .headers()
+            .get(header::USER_AGENT)
+            .and_then(|v| v.to_str().ok())
+            .unwrap_or("unknown");
+        tracing::info!(route = %route, user_agent = %user_agent, "Deprecated endpoint called");
+
+        #[cfg(feature = "observability-metrics")]
+        if let Some(metrics) = &self.metrics {
+            metrics.record_deprecated_hit(&route);
+        }
+
+        let headers = self.headers.clone();
+        let fut = self.service.call(req);
+
+        Box::pin(async move {
+            let mut res = fut.await?;
+            for (name, value) in headers {
+                // Link có thể đã có giá trị từ handler (vd. phân trang)
+                if name == header::LINK {
+                    res.headers_mut().append(name, value);
+                } else {
+                    res.headers_mut().insert(name, value);
+                }
+            }
+            Ok(res)
+        })
+    }
+}
@@ -0,0 +1,544 @@
+use actix_web::{
+    body::{to_bytes, BoxBody, MessageBody},
+    dev::{forward_ready, Payload, Service, ServiceRequest, ServiceResponse, Transform},
+    http::{header, Method, StatusCode},
+    web::BytesMut,
+    Error, HttpResponse, ResponseError,
+};
+use async_trait::async_trait;
+use chrono::{DateTime, Duration, Utc};
+use futures_util::future::LocalBoxFuture;
+use futures_util::StreamExt;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::future::{ready, Ready};
+use std::rc::Rc;
+use std::sync::{Arc, RwLock};
+
+use super::caller::caller_subject;
+use crate::errors::ApiError;
+
+#[cfg(feature = "cache-redis")]
+use crate::cache::CacheManager;
+
+pub const IDEMPOTENCY_KEY_HEADER: &str = "idempotency-key";
+
+/// Set on responses replayed from the idempotency store
+pub const IDEMPOTENT_REPLAYED_HEADER: &str = "idempotent-replayed";
+
+/// Default lifetime of a stored response
+pub const DEFAULT_IDEMPOTENCY_TTL_SECS: i64 = 24 * 60 * 60;
+
+/// Default lifetime of an in-flight marker, so a request that never
+/// completes (crash, lost instance) only blocks retries briefly
+pub const DEFAULT_IDEMPOTENCY_LEASE_SECS: i64 = 60;
+
+/// Request bodies larger than this are rejected with 413 before hashing
+pub const DEFAULT_IDEMPOTENCY_MAX_BODY_BYTES: usize = 1024 * 1024;
+
+const MAX_IDEMPOTENCY_KEY_LEN: usize = 255;
+
+/// Response headers kept with the stored response and replayed
+const REPLAYED_HEADERS: [header::HeaderName; 4] = [
+    header::LOCATION,
+    header::ETAG,
+    header::LAST_MODIFIED,
+    header::CONTENT_LANGUAGE,
+];
+
+/// Response captured for replay
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StoredResponse {
+    pub status: u16,
+    pub content_type: Option<String>,
+    /// `Location`, `ETag`, `Last-Modified` and `Content-Language` of the
+    /// original response
+    #[serde(default)]
+    pub headers: Vec<(String, String)>,
+    pub body: Vec<u8>,
+}
+
+impl StoredResponse {
+    fn to_http_response(&self) -> HttpResponse {
+        let status = StatusCode::from_u16(self.status).unwrap_or(StatusCode::OK);
+        let mut response = HttpResponse::build(status);
+        if let Some(content_type) = &self.content_type {
+            response.insert_header((header::CONTENT_TYPE, content_type.as_str()));
+        }
+        for (name, value) in &self.headers {
+            response.insert_header((name.as_str(), value.as_str()));
+        }
+        response
+            .insert_header((IDEMPOTENT_REPLAYED_HEADER, "true"))
+            .body(self.body.clone())
+    }
+}
+
+/// State of an idempotency key
+///
+/// `fingerprint` is the SHA-256 of the request body that used the key first;
+/// reusing the key with another body is rejected.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum IdempotencyEntry {
+    /// The first request with this key is still being handled
+    InFlight { fingerprint: String },
+    Completed {
+        fingerprint: String,
+        response: StoredResponse,
+    },
+}
+
+impl IdempotencyEntry {
+    pub fn fingerprint(&self) -> &str {
+        match self {
+            Self::InFlight { fingerprint } | Self::Completed { fingerprint, .. } => fingerprint,
+        }
+    }
+}
+
+/// Storage for idempotency keys and their responses
+#[async_trait]
+pub trait IdempotencyStore: Send + Sync {
+    /// Atomically mark `key` as in flight for a short lease; returns the
+    /// existing entry instead if the key was already used
+    async fn begin(
+        &self,
+        key: &str,
+        fingerprint: &str,
+    ) -> Result<Option<IdempotencyEntry>, ApiError>;
+
+    /// Store the response of the request that began `key`
+    async fn complete(
+        &self,
+        key: &str,
+        fingerprint: &str,
+        response: StoredResponse,
+    ) -> Result<(), ApiError>;
+
+    /// Forget `key` so the request can be retried (e.g. after a server error)
+    async fn release(&self, key: &str) -> Result<(), ApiError>;
+}
+
+type Entries = HashMap<String, (IdempotencyEntry, DateTime<Utc>)>;
+
+/// In-memory idempotency store with TTL
+pub struct InMemoryIdempotencyStore {
+    entries: Arc<RwLock<Entries>>,
+    ttl: Duration,
+    lease: Duration,
+}
+
+impl InMemoryIdempotencyStore {
+    pub fn new() -> Self {
+        Self::with_ttl(Duration::seconds(DEFAULT_IDEMPOTENCY_TTL_SECS))
+    }
+
+    pub fn with_ttl(ttl: Duration) -> Self {
+        Self {
+            entries: Arc::new(RwLock::new(HashMap::new())),
+            ttl,
+            lease: Duration::seconds(DEFAULT_IDEMPOTENCY_LEASE_SECS),
+        }
+    }
+
+    /// How long an in-flight marker blocks retries if it is never completed
+    pub fn with_lease(mut self, lease: Duration) -> Self {
+        self.lease = lease;
+        self
+    }
+
+    fn write(&self) -> Result<std::sync::RwLockWriteGuard<'_, Entries>, ApiError> {
+        self.entries
+            .write()
+            .map_err(|_| ApiError::internal("Failed to acquire write lock on idempotency store"))
+    }
+}
+
+impl Default for InMemoryIdempotencyStore {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[async_trait]
+impl IdempotencyStore for InMemoryIdempotencyStore {
+    async fn begin(
+        &self,
+        key: &str,
+        fingerprint: &str,
+    ) -> Result<Option<IdempotencyEntry>, ApiError> {
+        let now = Utc::now();
+        let mut entries = self.write()?;
+
+        entries.retain(|_, (_, expires_at)| *expires_at > now);
+        if let Some((entry, _)) = entries.get(key) {
+            return Ok(Some(entry.clone()));
+        }
+
+        let entry = IdempotencyEntry::InFlight {
+            fingerprint: fingerprint.to_string(),
+        };
+        entries.insert(key.to_string(), (entry, now + self.lease));
+        Ok(None)
+    }
+
+    async fn complete(
+        &self,
+        key: &str,
+        fingerprint: &str,
+        response: StoredResponse,
+    ) -> Result<(), ApiError> {
+        let entry = IdempotencyEntry::Completed {
+            fingerprint: fingerprint.to_string(),
+            response,
+        };
+        self.write()?
+            .insert(key.to_string(), (entry, Utc::now() + self.ttl));
+        Ok(())
+    }
+
+    async fn release(&self, key: &str) -> Result<(), ApiError> {
+        self.write()?.remove(key);
+        Ok(())
+    }
+}
+
+/// Redis-backed idempotency store, shared across instances
+#[cfg(feature = "cache-redis")]
+pub struct RedisIdempotencyStore {
+    conn: redis::aio::ConnectionManager,
+    ttl_secs: u64,
+    lease_secs: u64,
+}
+
+#[cfg(feature = "cache-redis")]
+impl RedisIdempotencyStore {
+    pub fn new(cache: &CacheManager) -> Self {
+        Self::with_ttl(cache, DEFAULT_IDEMPOTENCY_TTL_SECS as u64)
+    }
+
+    pub fn with_ttl(cache: &CacheManager, ttl_secs: u64) -> Self {
+        Self {
+            conn: cache.get_connection(),
+            ttl_secs,
+            lease_secs: DEFAULT_IDEMPOTENCY_LEASE_SECS as u64,
+        }
+    }
+
+    /// How long an in-flight marker blocks retries if it is never completed
+    pub fn with_lease(mut self, lease_secs: u64) -> Self {
+        self.lease_secs = lease_secs.max(1);
+        self
+    }
+
+    fn key(key: &str) -> String {
+        format!("idempotency:{}", key)
+    }
+
+    fn serialize(entry: &IdempotencyEntry) -> Result<String, ApiError> {
+        serde_json::to_string(entry)
+            .map_err(|e| ApiError::cache(format!("Idempotency serialize error: {}", e)))
+    }
+}
+
+#[cfg(feature = "cache-redis")]
+#[async_trait]
+impl IdempotencyStore for RedisIdempotencyStore {
+    async fn begin(
+        &self,
+        key: &str,
+        fingerprint: &str,
+    ) -> Result<Option<IdempotencyEntry>, ApiError> {
+        use redis::AsyncCommands;
+
+        let mut conn = self.conn.clone();
+        let redis_key = Self::key(key);
+        let in_flight = IdempotencyEntry::InFlight {
+            fingerprint: fingerprint.to_string(),
+        };
+
+        // SET NX: chỉ request đầu tiên đánh dấu được in-flight
+        let created: Option<String> = redis::cmd("SET")
+            .arg(&redis_key)
+            .arg(Self::serialize(&in_flight)?)
+            .arg("NX")
+            .arg("EX")
+            .arg(self.lease_secs)
+            .query_async(&mut conn)
+            .await
+            .map_err(|e| ApiError::cache(format!("Idempotency set error: {}", e)))?;
+        if created.is_some() {
+            return Ok(None);
+        }
+
+        let existing: Option<String> = conn
+            .get(&redis_key)
+            .await
+            .map_err(|e| ApiError::cache(format!("Idempotency get error: {}", e)))?;
+
+        match existing {
+            Some(value) => serde_json::from_str(&value)
+                .map(Some)
+                .map_err(|e| ApiError::cache(format!("Idempotency deserialize error: {}", e))),
+            // Expired between SET and GET; treat as still in flight
+            None => Ok(Some(in_flight)),
+        }
+    }
+
+    async fn complete(
+        &self,
+        key: &str,
+        fingerprint: &str,
+        response: StoredResponse,
+    ) -> Result<(), ApiError> {
+        use redis::AsyncCommands;
+
+        let value = Self::serialize(&IdempotencyEntry::Completed {
+            fingerprint: fingerprint.to_string(),
+            response,
+        })?;
+        let mut conn = self.conn.clone();
+        conn.set_ex::<_, _, ()>(Self::key(key), value, self.ttl_secs)
+            .await
+            .map_err(|e| ApiError::cache(format!("Idempotency set error: {}", e)))
+    }
+
+    async fn release(&self, key: &str) -> Result<(), ApiError> {
+        use redis::AsyncCommands;
+
+        let mut conn = self.conn.clone();
+        conn.del::<_, ()>(Self::key(key))
+            .await
+            .map_err(|e| ApiError::cache(format!("Idempotency delete error: {}", e)))
+    }
+}
+
+/// Middleware replaying responses for repeated `Idempotency-Key` headers
+///
+/// Applies to POST and PATCH requests that carry the header. Keys are scoped
+/// to the caller, so two clients picking the same key never collide. The
+/// first response for a `(caller, key, method, path)` is stored and replayed
+/// for later duplicates; a duplicate arriving while the first is still in
+/// flight gets `409 Conflict`, and reusing a key with a different body gets
+/// `422`. Server errors are not stored, and a request that is dropped or
+/// panics releases its key, so the client can retry. A replay keeps the
+/// language of the first response, which its `Content-Language` states.
+#[derive(Clone)]
+pub struct Idempotency {
+    store: Arc<dyn IdempotencyStore>,
+    max_body_bytes: usize,
+}
+
+impl Idempotency {
+    pub fn new(store: Arc<dyn IdempotencyStore>) -> Self {
+        Self {
+            store,
+            max_body_bytes: DEFAULT_IDEMPOTENCY_MAX_BODY_BYTES,
+        }
+    }
+
+    /// Largest request body that is buffered for fingerprinting
+    pub fn with_max_body_bytes(mut self, max_body_bytes: usize) -> Self {
+        self.max_body_bytes = max_body_bytes;
+        self
+    }
+}
+
+impl<S, B> Transform<S, ServiceRequest> for Idempotency
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    S::Future: 'static,
+    B: MessageBody + 'static,
+{
+    type Response = ServiceResponse<BoxBody>;
+    type Error = Error;
+    type InitError = ();
+    type Transform = IdempotencyMiddleware<S>;
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ready(Ok(IdempotencyMiddleware {
+            service: Rc::new(service),
+            store: self.store.clone(),
+            max_body_bytes: self.max_body_bytes,
+        }))
+    }
+}
+
+pub struct IdempotencyMiddleware<S> {
+    service: Rc<S>,
+    store: Arc<dyn IdempotencyStore>,
+    max_body_bytes: usize,
+}
+
+/// In-flight key of the current request; released in the background if the
+/// request future is dropped (client gone, panic) before it is disarmed
+struct Lease {
+    store: Arc<dyn IdempotencyStore>,
+    key: Option<String>,
+}
+
+impl Lease {
+    fn disarm(&mut self) {
+        self.key = None;
+    }
+}
+
+impl Drop for Lease {
+    fn drop(&mut self) {
+        let Some(key) = self.key.take() else {
+            return;
+        };
+        let store = self.store.clone();
+        if let Ok(handle) = tokio::runtime::Handle::try_current() {
+            handle.spawn(async move {
+                if let Err(e) = store.release(&key).await {
+                    tracing::warn!("Failed to release idempotency key: {}", e);
+                }
+            });
+        }
+    }
+}
+
+impl<S, B> Service<ServiceRequest> for IdempotencyMiddleware<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    S::Future: 'static,
+    B: MessageBody + 'static,
+{
+    type Response = ServiceResponse<BoxBody>;
+    type Error = Error;
+    type Future = LocalBoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    forward_ready!(service);
+
+    fn call(&self, mut req: ServiceRequest) -> Self::Future {
+        let store_key = match idempotency_key(&req) {
+            Some(key) => key,
+            None => {
+                let fut = self.service.call(req);
+                return Box::pin(async move { Ok(fut.await?.map_into_boxed_body()) });
+            }
+        };
+
+        let service = self.service.clone();
+        let store = self.store.clone();
+        let max_body_bytes = self.max_body_bytes;
+
+        Box::pin(async move {
+            // Đọc body để tính fingerprint rồi trả lại cho handler
+            let mut payload = req.take_payload();
+            let mut bytes = BytesMut::new();
+            while let Some(chunk) = payload.next().await {
+                let chunk = chunk?;
+                if bytes.len() + chunk.len() > max_body_bytes {
+                    let error = ApiError::payload_too_large(max_body_bytes);
+                    return Ok(req.into_response(error.error_response()));
+                }
+                bytes.extend_from_slice(&chunk);
+            }
+            let bytes = bytes.freeze();
+            let fingerprint = hex::encode(Sha256::digest(&bytes));
+            req.set_payload(Payload::from(bytes));
+
+            match store.begin(&store_key, &fingerprint).await? {
+                Some(entry) if entry.fingerprint() != fingerprint => {
+                    let error = ApiError::validation_field(
+                        "Idempotency-Key was already used with a different request body",
+                        "Idempotency-Key",
+                    );
+                    return Ok(req.into_response(error.error_response()));
+                }
+                Some(IdempotencyEntry::Completed { response, .. }) => {
+                    return Ok(req.into_response(response.to_http_response()));
+                }
+                Some(IdempotencyEntry::InFlight { .. }) => {
+                    let error = ApiError::Conflict {
+                        message: "A request with this Idempotency-Key is already in progress".to_string(),
+                        field: Some("Idempotency-Key".to_string()),
+                    };
+                    return Ok(req.into_response(error.error_response()));
+                }
+                None => {}
+            }
+
+            let mut lease = Lease {
+                store: store.clone(),
+                key: Some(store_key.clone()),
+            };
+
+            let res = match service.call(req).await {
+                Ok(res) => res,
+                Err(e) => {
+                    lease.disarm();
+                    store.release(&store_key).await?;
+                    return Err(e);
+                }
+            };
+
+            let (req, res) = res.into_parts();
+            let status = res.status();
+            let content_type = res
+                .headers()
+                .get(header::CONTENT_TYPE)
+                .and_then(|v| v.to_str().ok())
+                .map(String::from);
+            let headers = REPLAYED_HEADERS
+                .iter()
+                .filter_map(|name| {
+                    let value = res.headers().get(name)?.to_str().ok()?;
+                    Some((name.as_str().to_string(), value.to_string()))
+                })
+                .collect();
+            let (head, body) = res.into_parts();
+
+            let body = match to_bytes(body).await {
+                Ok(body) => body,
+                Err(e) => {
+                    lease.disarm();
+                    store.release(&store_key).await?;
+                    let e: Box<dyn std::error::Error> = e.into();
+                    return Err(ApiError::internal(format!("Failed to read response body: {}", e)).into());
+                }
+            };
+
+            lease.disarm();
+            if status.is_server_error() {
+                store.release(&store_key).await?;
+            } else {
+                store
+                    .complete(
+                        &store_key,
+                        &fingerprint,
+                        StoredResponse {
+                            status: status.as_u16(),
+                            content_type,
+                            headers,
+                            body: body.to_vec(),
+                        },
+                    )
+                    .await?;
+            }
+
+            Ok(ServiceResponse::new(req, head.set_body(body).map_into_boxed_body()))
+        })
+    }
+}
+
+/// `{caller} {method} {path} {key}` for POST/PATCH requests with a usable
+/// header
+fn idempotency_key(req: &ServiceRequest) -> Option<String> {
+    if req.method() != Method::POST && req.method() != Method::PATCH {
+        return None;
+    }
+
+    req.headers()
+        .get(IDEMPOTENCY_KEY_HEADER)
+        .and_then(|v| v.to_str().ok())
+        .map(str::trim)
+        .filter(|key| !key.is_empty() && key.len() <= MAX_IDEMPOTENCY_KEY_LEN)
+        .map(|key| {
+            format!("{} {} {} {}", caller_subject(req), req.method(), req.path(), key)
+        })
+}
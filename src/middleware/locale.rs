@@ -0,0 +1,119 @@
+use actix_web::{
+    body::{EitherBody, MessageBody},
+    dev::{forward_ready, Service, ServiceRequest, ServiceResponse, Transform},
+    http::header::{self, HeaderValue},
+    Error,
+};
+use futures_util::future::LocalBoxFuture;
+use std::future::{ready, Ready};
+
+tokio::task_local! {
+    static CURRENT_LOCALE: String;
+}
+
+/// Locale of the request currently being handled, from `Accept-Language`
+///
+/// Only available inside the `Localization` middleware scope.
+pub fn current_locale() -> Option<String> {
+    CURRENT_LOCALE.try_with(|locale| locale.clone()).ok()
+}
+
+/// Middleware chọn ngôn ngữ cho error message theo `Accept-Language`
+///
+/// `ApiError` responses rendered inside its scope use the `MessageCatalog`
+/// entry for the preferred locale, including errors returned by inner
+/// middleware, which are rendered before the scope ends. Error responses get
+/// `Vary: Accept-Language`. Requests without the header keep the English
+/// messages.
+pub struct Localization;
+
+impl<S, B> Transform<S, ServiceRequest> for Localization
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error>,
+    S::Future: 'static,
+    B: MessageBody + 'static,
+{
+    type Response = ServiceResponse<EitherBody<B>>;
+    type Error = Error;
+    type InitError = ();
+    type Transform = LocalizationMiddleware<S>;
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ready(Ok(LocalizationMiddleware { service }))
+    }
+}
+
+pub struct LocalizationMiddleware<S> {
+    service: S,
+}
+
+impl<S, B> Service<ServiceRequest> for LocalizationMiddleware<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error>,
+    S::Future: 'static,
+    B: MessageBody + 'static,
+{
+    type Response = ServiceResponse<EitherBody<B>>;
+    type Error = Error;
+    type Future = LocalBoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    forward_ready!(service);
+
+    fn call(&self, req: ServiceRequest) -> Self::Future {
+        let locale = req
+            .headers()
+            .get(header::ACCEPT_LANGUAGE)
+            .and_then(|v| v.to_str().ok())
+            .and_then(preferred_locale);
+
+        let http_req = req.request().clone();
+        let fut = self.service.call(req);
+        let respond = async move {
+            let mut res = match fut.await {
+                Ok(res) => res.map_into_left_body(),
+                // Render ngay trong scope, nếu để actix render thì đã mất locale
+                Err(e) => ServiceResponse::new(http_req, e.error_response()).map_into_right_body(),
+            };
+
+            let status = res.status();
+            if status.is_client_error() || status.is_server_error() {
+                res.headers_mut()
+                    .append(header::VARY, HeaderValue::from_static("accept-language"));
+            }
+            Ok(res)
+        };
+
+        match locale {
+            Some(locale) => Box::pin(CURRENT_LOCALE.scope(locale, respond)),
+            None => Box::pin(respond),
+        }
+    }
+}
+
+/// `da, en-GB;q=0.8` → `da`; `*` and malformed weights are ignored
+pub(crate) fn preferred_locale(accept_language: &str) -> Option<String> {
+    let mut best: Option<(&str, f32)> = None;
+
+    for entry in accept_language.split(',') {
+        let mut parts = entry.split(';').map(str::trim);
+        let tag = parts.next().unwrap_or_default();
+        if tag.is_empty() || tag == "*" {
+            continue;
+        }
+
+        let weight = match parts.find_map(|p| p.strip_prefix("q=")) {
+            Some(q) => match q.parse::<f32>() {
+                Ok(q) if (0.0..=1.0).contains(&q) => q,
+                _ => continue,
+            },
+            None => 1.0,
+        };
+
+        if weight > 0.0 && !matches!(best, Some((_, w)) if w >= weight) {
+            best = Some((tag, weight));
+        }
+    }
+
+    best.map(|(tag, _)| tag.to_string())
+}
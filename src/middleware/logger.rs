@@ -1,17 +1,93 @@
 use actix_web::{
-    dev::{forward_ready, Service, ServiceRequest, ServiceResponse, Transform},
-    Error,
+    dev::{forward_ready, Payload, Service, ServiceRequest, ServiceResponse, Transform},
+    http::header,
+    web::BytesMut,
+    Error, HttpMessage,
 };
 use futures_util::future::LocalBoxFuture;
+use futures_util::StreamExt;
 use std::future::{ready, Ready};
-use std::time::Instant;
+use std::rc::Rc;
+use std::time::{Duration, Instant};
+use tracing::Instrument;
+
+use super::request_id::RequestIdValue;
+use crate::security::Redactor;
+
+/// Same fields as `actix_web::middleware::Logger::default()`, with the
+/// request line from [`access_logger`]
+const ACCESS_LOG_FORMAT: &str =
+    r#"%a "%{request_line}xi" %s %b "%{Referer}i" "%{User-Agent}i" %T"#;
+
+/// Access log của actix, nhưng che giá trị query param nhạy cảm
+///
+/// `%r` would log `GET /ws?token=<jwt>` verbatim; here sensitive parameters
+/// (per [`Redactor::default`]) are logged as `token=***`.
+pub fn access_logger() -> actix_web::middleware::Logger {
+    let redactor = Redactor::default();
+    actix_web::middleware::Logger::new(ACCESS_LOG_FORMAT).custom_request_replace(
+        "request_line",
+        move |req| {
+            let query = req.query_string();
+            let target = if query.is_empty() {
+                req.path().to_string()
+            } else {
+                format!("{}?{}", req.path(), redactor.redact_query(query))
+            };
+            format!("{} {} {:?}", req.method(), target, req.version())
+        },
+    )
+}
 
 /// Middleware để log mỗi request
-pub struct Logger;
+///
+/// Opens an `http_request` span per request and emits one structured event
+/// on completion; requests slower than `slow_threshold` are logged at WARN.
+/// Install it inside `RequestId` so the span carries the final request id.
+#[derive(Debug, Clone)]
+pub struct Logger {
+    slow_threshold: Duration,
+    body_limit: Option<usize>,
+    redactor: Redactor,
+}
+
+impl Logger {
+    pub fn new() -> Self {
+        Self {
+            slow_threshold: Duration::from_secs(1),
+            body_limit: None,
+            redactor: Redactor::default(),
+        }
+    }
+
+    pub fn with_slow_threshold(mut self, threshold: Duration) -> Self {
+        self.slow_threshold = threshold;
+        self
+    }
+
+    /// Log JSON request bodies up to `max_bytes` (by `Content-Length`) at
+    /// DEBUG, with sensitive fields masked
+    pub fn with_request_body(mut self, max_bytes: usize) -> Self {
+        self.body_limit = Some(max_bytes);
+        self
+    }
+
+    /// Fields masked in logged bodies (default: [`Redactor::default`])
+    pub fn with_redactor(mut self, redactor: Redactor) -> Self {
+        self.redactor = redactor;
+        self
+    }
+}
+
+impl Default for Logger {
+    fn default() -> Self {
+        Self::new()
+    }
+}
 
 impl<S, B> Transform<S, ServiceRequest> for Logger
 where
-    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error>,
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
     S::Future: 'static,
     B: 'static,
 {
@@ -22,17 +98,25 @@ where
     type Future = Ready<Result<Self::Transform, Self::InitError>>;
 
     fn new_transform(&self, service: S) -> Self::Future {
-        ready(Ok(LoggerMiddleware { service }))
+        ready(Ok(LoggerMiddleware {
+            service: Rc::new(service),
+            slow_threshold: self.slow_threshold,
+            body_limit: self.body_limit,
+            redactor: Rc::new(self.redactor.clone()),
+        }))
     }
 }
 
 pub struct LoggerMiddleware<S> {
-    service: S,
+    service: Rc<S>,
+    slow_threshold: Duration,
+    body_limit: Option<usize>,
+    redactor: Rc<Redactor>,
 }
 
 impl<S, B> Service<ServiceRequest> for LoggerMiddleware<S>
 where
-    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error>,
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
     S::Future: 'static,
     B: 'static,
 {
@@ -42,34 +126,108 @@ where
 
     forward_ready!(service);
 
-    fn call(&self, req: ServiceRequest) -> Self::Future {
+    fn call(&self, mut req: ServiceRequest) -> Self::Future {
         let start = Instant::now();
-        let method = req.method().to_string();
-        let path = req.path().to_string();
+        let slow_threshold = self.slow_threshold;
+
+        // Ưu tiên request ID do RequestId middleware gán
         let request_id = req
-            .headers()
-            .get("X-Request-ID")
-            .and_then(|v| v.to_str().ok())
-            .unwrap_or("none")
+            .extensions()
+            .get::<RequestIdValue>()
+            .map(|id| id.0.clone())
+            .or_else(|| {
+                req.headers()
+                    .get("X-Request-ID")
+                    .and_then(|v| v.to_str().ok())
+                    .map(String::from)
+            })
+            .unwrap_or_else(|| "none".to_string());
+        let route = req
+            .match_pattern()
+            .unwrap_or_else(|| req.path().to_string());
+        let client_ip = req
+            .connection_info()
+            .realip_remote_addr()
+            .unwrap_or("unknown")
             .to_string();
 
-        let fut = self.service.call(req);
+        // `request_id` là field của span nên có trong mọi log line của request
+        // và thành attribute của OTel span; `trace_id` chỉ có khi OTLP bật
+        let span = tracing::info_span!(
+            "http_request",
+            method = %req.method(),
+            route = %route,
+            request_id = %request_id,
+            client_ip = %client_ip,
+            trace_id = tracing::field::Empty,
+        );
+        // Nối vào trace của caller nếu request có `traceparent`
+        #[cfg(feature = "observability-tracing")]
+        {
+            crate::monitoring::tracing::set_parent_from_headers(&span, req.headers());
+            if let Some(trace_id) = crate::monitoring::tracing::trace_id(&span) {
+                span.record("trace_id", trace_id.as_str());
+            }
+        }
+
+        let log_body = self.body_limit.is_some_and(|limit| is_small_json(&req, limit));
+        let service = Rc::clone(&self.service);
+        let redactor = Rc::clone(&self.redactor);
+
+        Box::pin(
+            async move {
+                if log_body {
+                    // Đọc hết body rồi trả lại cho handler
+                    let mut payload = req.take_payload();
+                    let mut bytes = BytesMut::new();
+                    while let Some(chunk) = payload.next().await {
+                        bytes.extend_from_slice(&chunk?);
+                    }
+                    let bytes = bytes.freeze();
+                    if let Some(body) = redactor.redact_body(&bytes) {
+                        tracing::debug!(body = %body, "request body");
+                    }
+                    req.set_payload(Payload::from(bytes));
+                }
 
-        Box::pin(async move {
-            let res = fut.await?;
-            let elapsed = start.elapsed();
-            let status = res.status();
+                let result = service.call(req).await;
+                let elapsed = start.elapsed();
+                let duration_ms = elapsed.as_secs_f64() * 1000.0;
 
-            tracing::info!(
-                "[{}] {} {} - {} ({:.2}ms)",
-                request_id,
-                method,
-                path,
-                status.as_u16(),
-                elapsed.as_secs_f64() * 1000.0
-            );
+                match &result {
+                    Ok(res) => {
+                        let status = res.status().as_u16();
+                        if elapsed >= slow_threshold {
+                            tracing::warn!(status, duration_ms, "slow request");
+                        } else {
+                            tracing::info!(status, duration_ms, "request completed");
+                        }
+                    }
+                    Err(e) => {
+                        let status = e.as_response_error().status_code().as_u16();
+                        tracing::error!(status, duration_ms, error = %e, "request failed");
+                    }
+                }
 
-            Ok(res)
-        })
+                result
+            }
+            .instrument(span),
+        )
     }
 }
+
+/// JSON body whose declared `Content-Length` is at most `limit`; chunked
+/// bodies are never buffered for logging
+fn is_small_json(req: &ServiceRequest, limit: usize) -> bool {
+    let is_json = req
+        .headers()
+        .get(header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .is_some_and(|v| v.to_ascii_lowercase().contains("json"));
+    let length = req
+        .headers()
+        .get(header::CONTENT_LENGTH)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.parse::<usize>().ok());
+    is_json && length.is_some_and(|length| length <= limit)
+}
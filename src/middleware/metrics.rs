@@ -0,0 +1,112 @@
+use actix_web::{
+    dev::{forward_ready, Service, ServiceRequest, ServiceResponse, Transform},
+    Error,
+};
+use futures_util::future::LocalBoxFuture;
+use std::future::{ready, Ready};
+use std::sync::Arc;
+use std::time::Instant;
+
+use crate::errors::api_error::ErrorCode;
+use crate::metrics::MetricsCollector;
+
+/// Label for requests that did not match any route
+const UNMATCHED_ENDPOINT: &str = "unmatched";
+
+/// Middleware ghi HTTP metrics vào `MetricsCollector`
+///
+/// Records `http_requests_total` and `http_request_duration_seconds`, plus
+/// `errors_total` for every response built by `ApiError::error_response`.
+/// Endpoints are labeled by route pattern (`/users/{id}`) to keep the label
+/// cardinality bounded.
+#[derive(Clone)]
+pub struct Metrics {
+    collector: Arc<MetricsCollector>,
+}
+
+impl Metrics {
+    pub fn new(collector: Arc<MetricsCollector>) -> Self {
+        Self { collector }
+    }
+}
+
+impl<S, B> Transform<S, ServiceRequest> for Metrics
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error>,
+    S::Future: 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Error = Error;
+    type InitError = ();
+    type Transform = MetricsMiddleware<S>;
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ready(Ok(MetricsMiddleware {
+            service,
+            collector: self.collector.clone(),
+        }))
+    }
+}
+
+pub struct MetricsMiddleware<S> {
+    service: S,
+    collector: Arc<MetricsCollector>,
+}
+
+impl<S, B> Service<ServiceRequest> for MetricsMiddleware<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error>,
+    S::Future: 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Error = Error;
+    type Future = LocalBoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    forward_ready!(service);
+
+    fn call(&self, req: ServiceRequest) -> Self::Future {
+        let start = Instant::now();
+        let method = req.method().to_string();
+        let collector = self.collector.clone();
+        let fut = self.service.call(req);
+
+        Box::pin(async move {
+            let result = fut.await;
+
+            // Route pattern chỉ có sau khi request đã được routing
+            let (endpoint, status, error_code) = match &result {
+                Ok(res) => (
+                    res.request().match_pattern(),
+                    res.status(),
+                    res.response().extensions().get::<ErrorCode>().copied(),
+                ),
+                Err(e) => {
+                    let response = e.error_response();
+                    let error_code = response.extensions().get::<ErrorCode>().copied();
+                    (None, response.status(), error_code)
+                }
+            };
+            let endpoint = endpoint.unwrap_or_else(|| UNMATCHED_ENDPOINT.to_string());
+
+            collector
+                .http_requests_total
+                .with_label_values(&[&method, &endpoint, status.as_str()])
+                .inc();
+            collector
+                .http_request_duration_seconds
+                .with_label_values(&[&method, &endpoint])
+                .observe(start.elapsed().as_secs_f64());
+            if let Some(error_code) = error_code {
+                collector
+                    .errors_total
+                    .with_label_values(&[&(error_code as u32).to_string(), &endpoint])
+                    .inc();
+            }
+
+            result
+        })
+    }
+}
@@ -1,13 +1,41 @@
+mod caller;
+pub mod coalesce;
+pub mod compression;
+pub mod context;
+pub mod cors;
+pub mod debug_capture;
+pub mod deprecation;
+pub mod idempotency;
+pub mod locale;
 pub mod logger;
 pub mod request_id;
 pub mod rate_limit;
+pub mod timeout;
 
 #[cfg(feature = "cache-redis")]
 pub mod redis_rate_limit;
 
-pub use logger::Logger;
-pub use request_id::RequestId;
+#[cfg(feature = "observability-metrics")]
+pub mod metrics;
+
+pub use coalesce::{RequestCoalescer, COALESCED_HEADER};
+pub use compression::{Compression, CompressionMode, DEFAULT_COMPRESSION_MIN_BYTES};
+pub use context::RequestContext;
+pub use cors::build_cors;
+pub use debug_capture::{DebugCapture, DEBUG_CAPTURE_HEADER, DEBUG_CAPTURE_PERMISSION};
+pub use deprecation::{Deprecation, DEPRECATION_HEADER, SUNSET_HEADER};
+pub use idempotency::{Idempotency, IdempotencyStore, InMemoryIdempotencyStore};
+pub use locale::{current_locale, Localization};
+pub use logger::{access_logger, Logger};
+pub use request_id::{current_request_id, RequestId, RequestIdValue};
 pub use rate_limit::{RateLimitConfig, RateLimitAlgorithm, RateLimiter};
+pub use timeout::Timeout;
+
+#[cfg(feature = "cache-redis")]
+pub use idempotency::RedisIdempotencyStore;
+
+#[cfg(feature = "observability-metrics")]
+pub use metrics::Metrics;
 
 #[cfg(feature = "cache-redis")]
-pub use redis_rate_limit::{RedisRateLimiter, RedisRateLimitConfig};
+pub use redis_rate_limit::{RateLimitDecision, RateLimitFailurePolicy, RedisRateLimiter, RedisRateLimitConfig};
@@ -1,14 +1,56 @@
-use redis::AsyncCommands;
-use std::time::{SystemTime, UNIX_EPOCH};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 use crate::cache::CacheManager;
 use crate::errors::ApiError;
 
+/// Sliding-window-log check-and-increment, atomic on the Redis server
+///
+/// Timestamps come from the Redis server clock so replicas with skewed
+/// clocks still share one window. Returns `{allowed, remaining, retry_after_ms}`.
+const SLIDING_WINDOW_SCRIPT: &str = r#"
+local key = KEYS[1]
+local window = tonumber(ARGV[1])
+local limit = tonumber(ARGV[2])
+local member = ARGV[3]
+
+local time = redis.call("TIME")
+local now = tonumber(time[1]) * 1000 + math.floor(tonumber(time[2]) / 1000)
+
+redis.call("ZREMRANGEBYSCORE", key, "-inf", now - window)
+local count = redis.call("ZCARD", key)
+
+if count < limit then
+    redis.call("ZADD", key, now, member)
+    redis.call("PEXPIRE", key, window)
+    return {1, limit - count - 1, 0}
+end
+
+local retry_after = window
+local oldest = redis.call("ZRANGE", key, 0, 0, "WITHSCORES")
+if oldest[2] then
+    retry_after = math.max(tonumber(oldest[2]) + window - now, 1)
+end
+return {0, 0, retry_after}
+"#;
+
+/// What `check` decides when Redis can't be reached
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum RateLimitFailurePolicy {
+    /// Return the `CacheError`, as before failure policies existed (default)
+    #[default]
+    ReturnError,
+    /// Allow the request, unlimited until Redis is back
+    FailOpen,
+    /// Reject the request as if the window were full
+    FailClosed,
+}
+
 /// Redis-based distributed rate limiter configuration
 #[derive(Debug, Clone)]
 pub struct RedisRateLimitConfig {
     pub max_requests: u32,
     pub window_secs: u64,
     pub key_prefix: String,
+    pub on_backend_error: RateLimitFailurePolicy,
 }
 
 impl Default for RedisRateLimitConfig {
@@ -17,10 +59,21 @@ impl Default for RedisRateLimitConfig {
             max_requests: 100,
             window_secs: 60,
             key_prefix: "rate_limit".to_string(),
+            on_backend_error: RateLimitFailurePolicy::default(),
         }
     }
 }
 
+/// Outcome of a rate limit check
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RateLimitDecision {
+    pub allowed: bool,
+    /// Requests left in the current window
+    pub remaining: u32,
+    /// Time until the next request would be allowed (zero when allowed)
+    pub retry_after: Duration,
+}
+
 /// Redis-based distributed rate limiter
 pub struct RedisRateLimiter {
     config: RedisRateLimitConfig,
@@ -35,62 +88,79 @@ impl RedisRateLimiter {
         }
     }
 
-    /// Check rate limit using sliding window algorithm in Redis
-    pub async fn check_rate_limit(&self, key: &str) -> Result<(bool, u32, u64), ApiError> {
+    /// Check and record one request using a sliding window log
+    ///
+    /// The whole check-and-increment runs as a single Lua script, so
+    /// concurrent requests from different replicas can't overshoot the limit.
+    /// Redis errors are returned, or resolved by `on_backend_error` when it
+    /// is `FailOpen`/`FailClosed`.
+    pub async fn check(&self, key: &str) -> Result<RateLimitDecision, ApiError> {
         let mut conn = self.cache_manager.get_connection();
         let redis_key = format!("{}:{}", self.config.key_prefix, key);
-        
+        let window_ms = self.config.window_secs * 1000;
+
+        // Member phải unique, nếu không các request cùng millisecond sẽ bị gộp
+        let member = uuid::Uuid::new_v4().to_string();
+
+        let result: Result<(i64, i64, i64), _> = redis::Script::new(SLIDING_WINDOW_SCRIPT)
+            .key(&redis_key)
+            .arg(window_ms)
+            .arg(self.config.max_requests)
+            .arg(member)
+            .invoke_async(&mut conn)
+            .await;
+
+        match result {
+            Ok((allowed, remaining, retry_after_ms)) => Ok(RateLimitDecision {
+                allowed: allowed == 1,
+                remaining: remaining.max(0) as u32,
+                retry_after: Duration::from_millis(retry_after_ms.max(0) as u64),
+            }),
+            Err(e) => {
+                self.on_backend_error(ApiError::cache(format!("Rate limit script error: {}", e)))
+            }
+        }
+    }
+
+    /// Decision from `on_backend_error` while Redis is failing
+    fn on_backend_error(&self, error: ApiError) -> Result<RateLimitDecision, ApiError> {
+        match self.config.on_backend_error {
+            RateLimitFailurePolicy::ReturnError => Err(error),
+            RateLimitFailurePolicy::FailOpen => {
+                tracing::warn!(error = %error, "Rate limiter unavailable, allowing request");
+                Ok(RateLimitDecision {
+                    allowed: true,
+                    remaining: self.config.max_requests,
+                    retry_after: Duration::ZERO,
+                })
+            }
+            RateLimitFailurePolicy::FailClosed => {
+                tracing::warn!(error = %error, "Rate limiter unavailable, rejecting request");
+                Ok(RateLimitDecision {
+                    allowed: false,
+                    remaining: 0,
+                    retry_after: Duration::from_secs(self.config.window_secs),
+                })
+            }
+        }
+    }
+
+    /// Check rate limit; returns `(allowed, remaining, reset_at)` with
+    /// `reset_at` as a Unix timestamp in seconds
+    pub async fn check_rate_limit(&self, key: &str) -> Result<(bool, u32, u64), ApiError> {
+        let decision = self.check(key).await?;
+
         let now = SystemTime::now()
             .duration_since(UNIX_EPOCH)
             .unwrap()
             .as_secs();
-        
-        let window_start = now - self.config.window_secs;
-
-        // Remove old entries
-        let _: () = conn
-            .zrembyscore(&redis_key, 0, window_start as f64)
-            .await
-            .map_err(|e| ApiError::cache(format!("Failed to remove old entries: {}", e)))?;
-
-        // Count requests in current window
-        let count: u32 = conn
-            .zcard(&redis_key)
-            .await
-            .map_err(|e| ApiError::cache(format!("Failed to count requests: {}", e)))?;
-
-        if count < self.config.max_requests {
-            // Add current request
-            let _: () = conn
-                .zadd(&redis_key, now, now)
-                .await
-                .map_err(|e| ApiError::cache(format!("Failed to add request: {}", e)))?;
-
-            // Set expiration
-            let _: () = conn
-                .expire(&redis_key, self.config.window_secs as i64)
-                .await
-                .map_err(|e| ApiError::cache(format!("Failed to set expiration: {}", e)))?;
-
-            let remaining = self.config.max_requests - count - 1;
-            Ok((true, remaining, now + self.config.window_secs))
+
+        let reset_at = if decision.allowed {
+            now + self.config.window_secs
         } else {
-            // Get oldest request timestamp
-            let oldest: Vec<f64> = conn
-                .zrange(&redis_key, 0, 0)
-                .await
-                .map_err(|e| ApiError::cache(format!("Failed to get oldest request: {}", e)))?;
-
-            let retry_after = if let Some(&oldest_time) = oldest.first() {
-                (oldest_time as u64 + self.config.window_secs).saturating_sub(now)
-            } else {
-                self.config.window_secs
-            };
-
-            Ok((false, 0, now + retry_after))
-        }
+            now + decision.retry_after.as_secs_f64().ceil() as u64
+        };
+
+        Ok((decision.allowed, decision.remaining, reset_at))
     }
 }
-
-
-
@@ -1,12 +1,56 @@
 use actix_web::{
-    dev::{forward_ready, Service, ServiceRequest, ServiceResponse, Transform},
-    Error, HttpMessage,
+    dev::{forward_ready, Payload, Service, ServiceRequest, ServiceResponse, Transform},
+    Error, FromRequest, HttpMessage, HttpRequest,
 };
 use futures_util::future::LocalBoxFuture;
 use std::future::{ready, Ready};
 use uuid::Uuid;
 
+use crate::errors::ApiError;
+
+const MAX_REQUEST_ID_LEN: usize = 128;
+
+tokio::task_local! {
+    static CURRENT_REQUEST_ID: String;
+}
+
+/// Request ID của request hiện tại, được lưu trong request extensions
+///
+/// Có thể dùng trực tiếp làm extractor trong handler.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RequestIdValue(pub String);
+
+impl RequestIdValue {
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl FromRequest for RequestIdValue {
+    type Error = ApiError;
+    type Future = Ready<Result<Self, Self::Error>>;
+
+    fn from_request(req: &HttpRequest, _payload: &mut Payload) -> Self::Future {
+        ready(
+            req.extensions()
+                .get::<RequestIdValue>()
+                .cloned()
+                .ok_or_else(|| ApiError::internal("RequestId middleware is not installed")),
+        )
+    }
+}
+
+/// Request ID of the request currently being handled, if any
+///
+/// Only available inside the `RequestId` middleware scope.
+pub fn current_request_id() -> Option<String> {
+    CURRENT_REQUEST_ID.try_with(|id| id.clone()).ok()
+}
+
 /// Middleware để thêm unique request ID vào mỗi request
+///
+/// Reuses an inbound `X-Request-Id` (or the trace id of a W3C `traceparent`)
+/// when it is valid, and echoes the final id in the response.
 pub struct RequestId;
 
 impl<S, B> Transform<S, ServiceRequest> for RequestId
@@ -43,29 +87,63 @@ where
     forward_ready!(service);
 
     fn call(&self, req: ServiceRequest) -> Self::Future {
-        // Tạo hoặc lấy request ID từ header
-        let request_id = req
-            .headers()
-            .get("X-Request-ID")
-            .and_then(|v| v.to_str().ok())
-            .map(String::from)
-            .unwrap_or_else(|| Uuid::new_v4().to_string());
+        // Lấy request ID từ header hoặc tạo mới
+        let request_id = inbound_request_id(&req).unwrap_or_else(|| Uuid::new_v4().to_string());
 
         // Thêm request ID vào extensions để các handler có thể truy cập
-        req.extensions_mut().insert(request_id.clone());
+        req.extensions_mut().insert(RequestIdValue(request_id.clone()));
 
-        let fut = self.service.call(req);
+        let fut = CURRENT_REQUEST_ID.scope(request_id.clone(), self.service.call(req));
 
         Box::pin(async move {
             let mut res = fut.await?;
-            
+
             // Thêm request ID vào response header
-            res.headers_mut().insert(
-                actix_web::http::header::HeaderName::from_static("x-request-id"),
-                actix_web::http::header::HeaderValue::from_str(&request_id).unwrap(),
-            );
+            if let Ok(value) = actix_web::http::header::HeaderValue::from_str(&request_id) {
+                res.headers_mut().insert(
+                    actix_web::http::header::HeaderName::from_static("x-request-id"),
+                    value,
+                );
+            }
 
             Ok(res)
         })
     }
 }
+
+fn inbound_request_id(req: &ServiceRequest) -> Option<String> {
+    let header = |name: &str| req.headers().get(name).and_then(|v| v.to_str().ok());
+
+    header("x-request-id")
+        .map(str::trim)
+        .filter(|id| is_valid_request_id(id))
+        .map(String::from)
+        .or_else(|| header("traceparent").and_then(trace_id_from_traceparent))
+}
+
+fn is_valid_request_id(id: &str) -> bool {
+    !id.is_empty()
+        && id.len() <= MAX_REQUEST_ID_LEN
+        && id
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.' | ':'))
+}
+
+/// Extract the trace id from `version-traceid-parentid-flags`
+pub(crate) fn trace_id_from_traceparent(traceparent: &str) -> Option<String> {
+    let parts: Vec<&str> = traceparent.trim().split('-').collect();
+    let is_hex = |s: &str, len: usize| s.len() == len && s.chars().all(|c| c.is_ascii_hexdigit());
+
+    match parts.as_slice() {
+        [version, trace_id, parent_id, flags]
+            if is_hex(version, 2)
+                && is_hex(trace_id, 32)
+                && is_hex(parent_id, 16)
+                && is_hex(flags, 2)
+                && trace_id.chars().any(|c| c != '0') =>
+        {
+            Some(trace_id.to_ascii_lowercase())
+        }
+        _ => None,
+    }
+}
@@ -0,0 +1,82 @@
+use actix_web::{
+    body::EitherBody,
+    dev::{forward_ready, Service, ServiceRequest, ServiceResponse, Transform},
+    Error, ResponseError,
+};
+use futures_util::future::LocalBoxFuture;
+use std::future::{ready, Ready};
+use std::time::Duration;
+
+use crate::errors::ApiError;
+
+/// Middleware giới hạn thời gian xử lý mỗi request
+///
+/// Requests that don't complete within `duration` are answered with
+/// `504 Gateway Timeout`; the handler future is dropped.
+#[derive(Debug, Clone)]
+pub struct Timeout {
+    duration: Duration,
+}
+
+impl Timeout {
+    pub fn new(duration: Duration) -> Self {
+        Self { duration }
+    }
+}
+
+impl<S, B> Transform<S, ServiceRequest> for Timeout
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error>,
+    S::Future: 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<EitherBody<B>>;
+    type Error = Error;
+    type InitError = ();
+    type Transform = TimeoutMiddleware<S>;
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ready(Ok(TimeoutMiddleware {
+            service,
+            duration: self.duration,
+        }))
+    }
+}
+
+pub struct TimeoutMiddleware<S> {
+    service: S,
+    duration: Duration,
+}
+
+impl<S, B> Service<ServiceRequest> for TimeoutMiddleware<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error>,
+    S::Future: 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<EitherBody<B>>;
+    type Error = Error;
+    type Future = LocalBoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    forward_ready!(service);
+
+    fn call(&self, req: ServiceRequest) -> Self::Future {
+        let duration = self.duration;
+        let http_req = req.request().clone();
+        let fut = self.service.call(req);
+
+        Box::pin(async move {
+            match tokio::time::timeout(duration, fut).await {
+                Ok(res) => res.map(ServiceResponse::map_into_left_body),
+                Err(_) => {
+                    let error = ApiError::gateway_timeout(format!(
+                        "Request did not complete within {}s",
+                        duration.as_secs_f64()
+                    ));
+                    Ok(ServiceResponse::new(http_req, error.error_response()).map_into_right_body())
+                }
+            }
+        })
+    }
+}
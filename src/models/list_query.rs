@@ -0,0 +1,180 @@
+use chrono::{DateTime, Utc};
+use std::collections::HashMap;
+
+use crate::errors::ApiError;
+
+/// Whitelist of queryable fields: query parameter name -> SQL column, type
+pub type FieldWhitelist = &'static [(&'static str, &'static str, FieldKind)];
+
+/// Sort and filter fields allowed on the users list
+pub const USER_LIST_FIELDS: FieldWhitelist = &[
+    ("name", "name", FieldKind::Text),
+    ("email", "email", FieldKind::Text),
+    ("age", "age", FieldKind::Integer),
+    ("role", "role", FieldKind::Text),
+    ("created_at", "created_at", FieldKind::Timestamp),
+];
+
+/// How a filter value is parsed
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FieldKind {
+    Text,
+    Integer,
+    /// RFC 3339, e.g. `2024-05-01T10:00:00Z`
+    Timestamp,
+}
+
+/// Parsed filter value; every backend compares it as a value of the field's
+/// type, never as the column's text form
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FilterValue {
+    Text(String),
+    Integer(i64),
+    Timestamp(DateTime<Utc>),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SortDirection {
+    Asc,
+    Desc,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SortField {
+    pub field: &'static str,
+    pub column: &'static str,
+    pub direction: SortDirection,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FilterField {
+    pub field: &'static str,
+    pub column: &'static str,
+    pub value: FilterValue,
+}
+
+/// Parsed `?sort=name,-age&filter[email]=...` list query
+///
+/// Field names are resolved against a whitelist, so the stored column names
+/// are always static strings from code and safe to put into SQL; filter
+/// values are only ever used as bind parameters.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ListQuery {
+    pub sort: Vec<SortField>,
+    pub filters: Vec<FilterField>,
+}
+
+impl ListQuery {
+    /// Parse sort and filter parameters, ignoring unrelated keys such as
+    /// `page` and `per_page`
+    pub fn from_params(
+        params: &HashMap<String, String>,
+        whitelist: FieldWhitelist,
+    ) -> Result<Self, ApiError> {
+        let mut query = Self::default();
+
+        if let Some(sort) = params.get("sort") {
+            for item in sort.split(',').map(str::trim).filter(|s| !s.is_empty()) {
+                let (name, direction) = match item.strip_prefix('-') {
+                    Some(name) => (name, SortDirection::Desc),
+                    None => (item, SortDirection::Asc),
+                };
+                let (field, column, _) = lookup(whitelist, name, "sort")?;
+                query.sort.push(SortField { field, column, direction });
+            }
+        }
+
+        // Sort filter keys so the generated SQL is deterministic
+        let mut filter_keys: Vec<&String> = params
+            .keys()
+            .filter(|key| key.starts_with("filter["))
+            .collect();
+        filter_keys.sort();
+
+        for key in filter_keys {
+            let name = key
+                .strip_prefix("filter[")
+                .and_then(|rest| rest.strip_suffix(']'))
+                .ok_or_else(|| ApiError::validation(format!("Malformed filter parameter: {}", key)))?;
+            let (field, column, kind) = lookup(whitelist, name, "filter")?;
+            query.filters.push(FilterField {
+                field,
+                column,
+                value: parse_filter_value(key, kind, &params[key])?,
+            });
+        }
+
+        Ok(query)
+    }
+
+    /// Append the filters as `AND` conditions and the `ORDER BY` clause to a
+    /// query that already has a `WHERE`; values are bind parameters
+    ///
+    /// `tie_breaker` (e.g. `created_at, id`) ends the ordering so that pages
+    /// are stable.
+    #[cfg(feature = "database-postgres")]
+    pub fn push_sql(
+        &self,
+        builder: &mut sqlx::QueryBuilder<'_, sqlx::Postgres>,
+        tie_breaker: &'static str,
+    ) {
+        self.push_filters(builder);
+
+        builder.push(" ORDER BY ");
+        for sort in &self.sort {
+            builder.push(sort.column);
+            builder.push(match sort.direction {
+                SortDirection::Asc => " ASC, ",
+                SortDirection::Desc => " DESC, ",
+            });
+        }
+        builder.push(tie_breaker);
+    }
+
+    /// Only the `AND` conditions of [`push_sql`](Self::push_sql), e.g. for a
+    /// `COUNT(*)` of the same list
+    #[cfg(feature = "database-postgres")]
+    pub fn push_filters(&self, builder: &mut sqlx::QueryBuilder<'_, sqlx::Postgres>) {
+        for filter in &self.filters {
+            builder.push(" AND ");
+            builder.push(filter.column);
+            builder.push(" = ");
+            match &filter.value {
+                FilterValue::Text(value) => builder.push_bind(value.clone()),
+                FilterValue::Integer(value) => builder.push_bind(*value),
+                FilterValue::Timestamp(value) => builder.push_bind(*value),
+            };
+        }
+    }
+}
+
+fn lookup(
+    whitelist: FieldWhitelist,
+    name: &str,
+    kind: &str,
+) -> Result<(&'static str, &'static str, FieldKind), ApiError> {
+    whitelist
+        .iter()
+        .find(|(field, _, _)| *field == name)
+        .copied()
+        .ok_or_else(|| ApiError::validation(format!("Unknown {} field: {}", kind, name)))
+}
+
+fn parse_filter_value(key: &str, kind: FieldKind, raw: &str) -> Result<FilterValue, ApiError> {
+    let invalid = |expected: &str| {
+        ApiError::validation_field(format!("{} must be {}", key, expected), key)
+    };
+
+    match kind {
+        FieldKind::Text => Ok(FilterValue::Text(raw.to_string())),
+        FieldKind::Integer => raw
+            .trim()
+            .parse()
+            .map(FilterValue::Integer)
+            .map_err(|_| invalid("an integer")),
+        FieldKind::Timestamp => DateTime::parse_from_rfc3339(raw.trim())
+            .map(|value| FilterValue::Timestamp(value.with_timezone(&Utc)))
+            .map_err(|_| invalid("an RFC 3339 timestamp")),
+    }
+}
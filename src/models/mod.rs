@@ -1,7 +1,11 @@
 pub mod user;
 pub mod request;
 pub mod response;
+pub mod list_query;
+pub mod money;
 
 pub use user::User;
-pub use request::{CreateUserRequest, UpdateUserRequest, LoginRequest};
-pub use response::{ApiResponse, LoginResponse, UserInfo};
+pub use request::{CreateUserRequest, UpdateUserRequest, LoginRequest, Pagination, PaginationQuery};
+pub use response::{ApiResponse, BatchItemError, BatchResult, LoginResponse, Paginated, UploadedFile, UserInfo};
+pub use list_query::{FieldKind, FilterValue, ListQuery, SortDirection, USER_LIST_FIELDS};
+pub use money::{Currency, Money};
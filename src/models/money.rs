@@ -0,0 +1,167 @@
+use rust_decimal::{Decimal, RoundingStrategy};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+use crate::errors::ApiError;
+
+/// Số tiền kèm mã tiền tệ, không dùng float
+///
+/// Serialized as `{"amount": "12.50", "currency": "USD"}`: the amount is a
+/// string so JSON clients never round it through a double. Arithmetic only
+/// combines amounts of the same currency. Use this for every monetary field
+/// (quotas, billing, payments) instead of `f64`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct Money {
+    #[serde(with = "rust_decimal::serde::str")]
+    amount: Decimal,
+    currency: Currency,
+}
+
+/// ISO 4217 code, e.g. `USD` (three uppercase ASCII letters)
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Currency([u8; 3]);
+
+impl Currency {
+    pub fn new(code: &str) -> Result<Self, ApiError> {
+        match code.as_bytes() {
+            &[a, b, c] if code.bytes().all(|byte| byte.is_ascii_alphabetic()) => Ok(Self([
+                a.to_ascii_uppercase(),
+                b.to_ascii_uppercase(),
+                c.to_ascii_uppercase(),
+            ])),
+            _ => Err(ApiError::validation_field(
+                format!("Invalid currency code: {}", code),
+                "currency",
+            )),
+        }
+    }
+
+    pub fn as_str(&self) -> &str {
+        // Chỉ chứa chữ ASCII, đã kiểm tra trong `new`
+        std::str::from_utf8(&self.0).unwrap_or_default()
+    }
+}
+
+impl fmt::Display for Currency {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl fmt::Debug for Currency {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Currency({})", self.as_str())
+    }
+}
+
+impl Serialize for Currency {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.as_str())
+    }
+}
+
+impl<'de> Deserialize<'de> for Currency {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let code = String::deserialize(deserializer)?;
+        Self::new(&code).map_err(|e| serde::de::Error::custom(e.message()))
+    }
+}
+
+impl Money {
+    pub fn new(amount: Decimal, currency: &str) -> Result<Self, ApiError> {
+        Ok(Self {
+            amount,
+            currency: Currency::new(currency)?,
+        })
+    }
+
+    pub fn zero(currency: &str) -> Result<Self, ApiError> {
+        Self::new(Decimal::ZERO, currency)
+    }
+
+    /// `"12.50"` → 12.50; rejects anything that isn't a plain decimal
+    pub fn parse(amount: &str, currency: &str) -> Result<Self, ApiError> {
+        let amount = amount.trim().parse::<Decimal>().map_err(|_| {
+            ApiError::validation_field(format!("Invalid amount: {}", amount), "amount")
+        })?;
+        Self::new(amount, currency)
+    }
+
+    pub fn amount(&self) -> Decimal {
+        self.amount
+    }
+
+    pub fn currency(&self) -> Currency {
+        self.currency
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.amount.is_zero()
+    }
+
+    pub fn is_negative(&self) -> bool {
+        self.amount.is_sign_negative() && !self.amount.is_zero()
+    }
+
+    pub fn checked_add(&self, other: &Money) -> Result<Money, ApiError> {
+        self.same_currency(other, "add")?;
+        let amount = self.amount.checked_add(other.amount).ok_or_else(overflow)?;
+        Ok(Self { amount, ..*self })
+    }
+
+    pub fn checked_sub(&self, other: &Money) -> Result<Money, ApiError> {
+        self.same_currency(other, "subtract")?;
+        let amount = self.amount.checked_sub(other.amount).ok_or_else(overflow)?;
+        Ok(Self { amount, ..*self })
+    }
+
+    /// Scale by a quantity or rate, e.g. unit price × seats
+    pub fn checked_mul(&self, factor: Decimal) -> Result<Money, ApiError> {
+        let amount = self.amount.checked_mul(factor).ok_or_else(overflow)?;
+        Ok(Self { amount, ..*self })
+    }
+
+    /// Round half away from zero to `decimal_places` (2 for most currencies)
+    pub fn round(&self, decimal_places: u32) -> Money {
+        Self {
+            amount: self
+                .amount
+                .round_dp_with_strategy(decimal_places, RoundingStrategy::MidpointAwayFromZero),
+            ..*self
+        }
+    }
+
+    /// Sum of `items`, all in `currency`
+    pub fn sum<'a>(
+        currency: &str,
+        items: impl IntoIterator<Item = &'a Money>,
+    ) -> Result<Money, ApiError> {
+        items
+            .into_iter()
+            .try_fold(Self::zero(currency)?, |total, item| total.checked_add(item))
+    }
+
+    fn same_currency(&self, other: &Money, operation: &str) -> Result<(), ApiError> {
+        if self.currency == other.currency {
+            Ok(())
+        } else {
+            Err(ApiError::validation_field(
+                format!(
+                    "Cannot {} amounts in different currencies ({} and {})",
+                    operation, self.currency, other.currency
+                ),
+                "currency",
+            ))
+        }
+    }
+}
+
+fn overflow() -> ApiError {
+    ApiError::validation_field("Amount out of range", "amount")
+}
+
+impl fmt::Display for Money {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {}", self.amount, self.currency)
+    }
+}
@@ -1,15 +1,24 @@
 use serde::Deserialize;
-use utoipa::ToSchema;
 use validator::Validate;
 
+use crate::errors::ApiError;
+
+/// Default number of items per page
+pub const DEFAULT_PER_PAGE: u32 = 20;
+
+/// Maximum number of items per page
+pub const MAX_PER_PAGE: u32 = 100;
+
 /// Create user request
-#[derive(Debug, Deserialize, ToSchema, Validate)]
-#[schema(example = json!({
+#[derive(Debug, Deserialize, Validate)]
+#[cfg_attr(feature = "docs", derive(utoipa::ToSchema))]
+#[cfg_attr(feature = "docs", schema(example = json!({
     "name": "John Doe",
     "email": "john@example.com",
     "password": "SecurePass123!",
-    "age": 30
-}))]
+    "age": 30,
+    "phone": "+84912345678"
+})))]
 pub struct CreateUserRequest {
     #[validate(length(min = 2, max = 100))]
     pub name: String,
@@ -22,10 +31,14 @@ pub struct CreateUserRequest {
     
     #[validate(range(min = 1, max = 150))]
     pub age: u32,
+
+    /// Số điện thoại; không có `+` thì hiểu theo region mặc định
+    pub phone: Option<String>,
 }
 
 /// Update user request
-#[derive(Debug, Deserialize, ToSchema, Validate)]
+#[derive(Debug, Deserialize, Validate)]
+#[cfg_attr(feature = "docs", derive(utoipa::ToSchema))]
 pub struct UpdateUserRequest {
     #[validate(length(min = 2, max = 100))]
     pub name: Option<String>,
@@ -35,16 +48,73 @@ pub struct UpdateUserRequest {
     
     #[validate(range(min = 1, max = 150))]
     pub age: Option<u32>,
+
+    pub phone: Option<String>,
 }
 
 /// Login request
-#[derive(Debug, Deserialize, ToSchema, Validate)]
-#[schema(example = json!({
+#[derive(Debug, Deserialize, Validate)]
+#[cfg_attr(feature = "docs", derive(utoipa::ToSchema))]
+#[cfg_attr(feature = "docs", schema(example = json!({
     "email": "john@example.com",
     "password": "SecurePass123!"
-}))]
+})))]
 pub struct LoginRequest {
     #[validate(email)]
     pub email: String,
     pub password: String,
 }
+
+/// Pagination query (`?page=&per_page=`)
+///
+/// Values are kept as strings so malformed input surfaces as a field-level
+/// validation error instead of a generic query parse failure.
+#[derive(Debug, Default, Deserialize)]
+pub struct PaginationQuery {
+    pub page: Option<String>,
+    pub per_page: Option<String>,
+}
+
+/// Validated pagination parameters
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Pagination {
+    pub page: u32,
+    pub per_page: u32,
+}
+
+impl PaginationQuery {
+    pub fn resolve(&self) -> Result<Pagination, ApiError> {
+        let page = parse_positive(self.page.as_deref(), "page")?.unwrap_or(1);
+        let per_page = parse_positive(self.per_page.as_deref(), "per_page")?
+            .unwrap_or(DEFAULT_PER_PAGE);
+
+        if per_page > MAX_PER_PAGE {
+            return Err(ApiError::validation_field(
+                format!("per_page must not exceed {}", MAX_PER_PAGE),
+                "per_page",
+            ));
+        }
+
+        Ok(Pagination { page, per_page })
+    }
+}
+
+impl Pagination {
+    /// Number of items to skip
+    pub fn offset(&self) -> usize {
+        (self.page as usize - 1) * self.per_page as usize
+    }
+}
+
+fn parse_positive(value: Option<&str>, field: &str) -> Result<Option<u32>, ApiError> {
+    match value {
+        None => Ok(None),
+        Some(raw) => match raw.trim().parse::<u32>() {
+            Ok(n) if n >= 1 => Ok(Some(n)),
+            _ => Err(ApiError::validation_field(
+                format!("{} must be a positive integer", field),
+                field,
+            )),
+        },
+    }
+}
@@ -1,18 +1,30 @@
 use serde::Serialize;
-use utoipa::ToSchema;
+
+use super::request::Pagination;
+use crate::errors::ErrorResponse;
+use crate::middleware::current_request_id;
 
 /// Standard API response wrapper
-#[derive(Serialize, ToSchema)]
-#[schema(example = json!({
+///
+/// `request_id` is filled from the `RequestId` middleware when the response
+/// is built inside a request.
+#[derive(Serialize)]
+#[cfg_attr(feature = "docs", derive(utoipa::ToSchema))]
+#[cfg_attr(feature = "docs", schema(example = json!({
     "success": true,
     "message": "Operation successful",
-    "data": {}
-}))]
+    "data": {},
+    "request_id": "550e8400-e29b-41d4-a716-446655440000"
+})))]
 pub struct ApiResponse<T> {
     pub success: bool,
     pub message: String,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub data: Option<T>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub meta: Option<serde_json::Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub request_id: Option<String>,
 }
 
 impl<T> ApiResponse<T> {
@@ -21,6 +33,15 @@ impl<T> ApiResponse<T> {
             success: true,
             message: message.to_string(),
             data: Some(data),
+            meta: None,
+            request_id: current_request_id(),
+        }
+    }
+
+    pub fn success_with_meta(message: &str, data: T, meta: serde_json::Value) -> Self {
+        Self {
+            meta: Some(meta),
+            ..Self::success(message, data)
         }
     }
 
@@ -29,34 +50,109 @@ impl<T> ApiResponse<T> {
             success: false,
             message: message.to_string(),
             data: None,
+            meta: None,
+            request_id: current_request_id(),
         }
     }
 
     pub fn error_with_code(message: &str, _code: u16) -> ApiResponse<()> {
-        ApiResponse {
-            success: false,
-            message: message.to_string(),
-            data: None,
+        ApiResponse::<()>::error(message)
+    }
+
+    /// Override the request id, e.g. outside the `RequestId` middleware
+    pub fn with_request_id(mut self, request_id: impl Into<String>) -> Self {
+        self.request_id = Some(request_id.into());
+        self
+    }
+}
+
+/// Paginated list of items
+#[derive(Serialize)]
+#[cfg_attr(feature = "docs", derive(utoipa::ToSchema))]
+pub struct Paginated<T> {
+    pub items: Vec<T>,
+    pub total: usize,
+    pub page: u32,
+    pub per_page: u32,
+    pub total_pages: u32,
+}
+
+impl<T> Paginated<T> {
+    /// Page `pagination` of a list of `total` items, already sliced
+    pub fn new(items: Vec<T>, total: usize, pagination: Pagination) -> Self {
+        Self {
+            items,
+            total,
+            page: pagination.page,
+            per_page: pagination.per_page,
+            total_pages: total.div_ceil(pagination.per_page as usize) as u32,
         }
     }
 }
 
+impl<T: Clone> Paginated<T> {
+    /// Take one page out of the full, already ordered, list of items
+    pub fn from_slice(all: &[T], pagination: Pagination) -> Self {
+        let items = all
+            .iter()
+            .skip(pagination.offset())
+            .take(pagination.per_page as usize)
+            .cloned()
+            .collect();
+
+        Self::new(items, all.len(), pagination)
+    }
+}
+
+/// Kết quả của một thao tác batch: record thành công và lỗi theo từng index
+#[derive(Serialize)]
+#[cfg_attr(feature = "docs", derive(utoipa::ToSchema))]
+pub struct BatchResult<T> {
+    pub created: Vec<T>,
+    pub errors: Vec<BatchItemError>,
+}
+
+/// Lỗi của một record trong batch, `index` là vị trí trong request
+#[derive(Serialize)]
+#[cfg_attr(feature = "docs", derive(utoipa::ToSchema))]
+pub struct BatchItemError {
+    pub index: usize,
+    pub error: ErrorResponse,
+}
+
+/// Metadata của một file đã upload qua `POST /uploads`
+#[derive(Debug, Serialize)]
+#[cfg_attr(feature = "docs", derive(utoipa::ToSchema))]
+pub struct UploadedFile {
+    /// Form field the file was sent in
+    pub field: String,
+    /// Original filename from the client
+    pub filename: String,
+    /// Object key in storage
+    pub key: String,
+    /// Size in bytes
+    pub size: usize,
+    pub content_type: String,
+}
+
 /// Login response with JWT token
-#[derive(Serialize, ToSchema)]
-#[schema(example = json!({
+#[derive(Serialize)]
+#[cfg_attr(feature = "docs", derive(utoipa::ToSchema))]
+#[cfg_attr(feature = "docs", schema(example = json!({
     "token": "eyJhbGciOiJIUzI1NiIsInR5cCI6IkpXVCJ9...",
     "user": {
         "id": "550e8400-e29b-41d4-a716-446655440000",
         "email": "john@example.com",
         "role": "user"
     }
-}))]
+})))]
 pub struct LoginResponse {
     pub token: String,
     pub user: UserInfo,
 }
 
-#[derive(Serialize, ToSchema)]
+#[derive(Serialize)]
+#[cfg_attr(feature = "docs", derive(utoipa::ToSchema))]
 pub struct UserInfo {
     pub id: String,
     pub email: String,
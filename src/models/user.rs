@@ -1,30 +1,46 @@
 use serde::{Deserialize, Serialize};
 use chrono::{DateTime, Utc};
-use utoipa::ToSchema;
 
 /// User model
-#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
-#[schema(example = json!({
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "docs", derive(utoipa::ToSchema))]
+#[cfg_attr(feature = "docs", schema(example = json!({
     "id": "550e8400-e29b-41d4-a716-446655440000",
     "name": "John Doe",
     "email": "john@example.com",
     "age": 30,
+    "phone": "+84912345678",
     "role": "user",
     "is_active": true,
     "created_at": "2024-01-01T00:00:00Z",
-    "updated_at": "2024-01-01T00:00:00Z"
-}))]
+    "updated_at": "2024-01-01T00:00:00Z",
+    "deleted_at": null
+})))]
 pub struct User {
+    /// Numeric ids follow `JSON_IDS_AS_STRINGS` (see `utils::id_as_string`)
+    #[serde(with = "crate::utils::id_as_string")]
     pub id: String,
     pub name: String,
     pub email: String,
     pub age: u32,
+    /// Phone number in E.164 format
+    #[serde(default)]
+    pub phone: Option<String>,
     #[serde(default = "default_role")]
     pub role: String,
     #[serde(default = "default_active")]
     pub is_active: bool,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
+    /// Set when the user is soft-deleted
+    #[serde(default)]
+    pub deleted_at: Option<DateTime<Utc>>,
+}
+
+impl User {
+    pub fn is_deleted(&self) -> bool {
+        self.deleted_at.is_some()
+    }
 }
 
 fn default_role() -> String {
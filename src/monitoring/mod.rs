@@ -8,8 +8,32 @@ pub mod metrics;
 
 // Re-export commonly used items
 #[cfg(feature = "observability-tracing")]
-pub use self::tracing::{init_tracing, shutdown_tracing};
+pub use self::tracing::{init_tracing, shutdown_tracing, trace_context_headers};
 
 #[cfg(feature = "observability-metrics")]
 pub use self::metrics::{init_metrics, record_request, record_error};
 
+
+/// Gắn trace context (`traceparent`) của span hiện tại vào outbound request
+///
+/// No-op unless `observability-tracing` is enabled and OTLP export is on.
+#[cfg(feature = "reqwest")]
+pub trait TracePropagation {
+    fn with_trace_context(self) -> Self;
+}
+
+#[cfg(feature = "reqwest")]
+impl TracePropagation for reqwest::RequestBuilder {
+    fn with_trace_context(self) -> Self {
+        #[cfg(feature = "observability-tracing")]
+        {
+            trace_context_headers()
+                .into_iter()
+                .fold(self, |request, (name, value)| request.header(name, value))
+        }
+        #[cfg(not(feature = "observability-tracing"))]
+        {
+            self
+        }
+    }
+}
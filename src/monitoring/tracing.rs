@@ -1,15 +1,30 @@
 // OpenTelemetry Tracing Integration
 // Provides distributed tracing capabilities with Jaeger/Tempo support
 
+use std::collections::HashMap;
+use std::sync::OnceLock;
+
+use opentelemetry::propagation::{Extractor, TextMapPropagator};
+use opentelemetry::trace::TracerProvider as _;
+use opentelemetry::{global, KeyValue};
+use opentelemetry_otlp::WithExportConfig;
+use opentelemetry_sdk::propagation::TraceContextPropagator;
+use opentelemetry_sdk::trace::TracerProvider;
+use opentelemetry_sdk::Resource;
+use tracing_opentelemetry::OpenTelemetrySpanExt;
 use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt, EnvFilter, Registry};
 
+use crate::config::settings::TracingSettings;
+
+/// Provider giữ lại để flush khi shutdown
+static TRACER_PROVIDER: OnceLock<TracerProvider> = OnceLock::new();
+
 /// Initialize tracing with JSON formatting
 ///
-/// For full OpenTelemetry integration with OTLP exporter:
-/// 1. Uncomment the opentelemetry dependencies in Cargo.toml
-/// 2. Use the init_tracing_with_otlp function below
-/// 3. Ensure you have a running OTLP collector (Jaeger/Tempo)
-pub fn init_tracing(_service_name: &str, _otlp_endpoint: &str) -> Result<(), Box<dyn std::error::Error>> {
+/// When `settings.otel_enabled`, spans are also exported over OTLP/gRPC to
+/// `settings.otel_endpoint`, tagged with `service.name` and
+/// `service.version`, and W3C `traceparent` propagation is enabled.
+pub fn init_tracing(settings: &TracingSettings) -> Result<(), Box<dyn std::error::Error>> {
     // Create env filter for log levels
     let env_filter = EnvFilter::try_from_default_env()
         .unwrap_or_else(|_| EnvFilter::new("info"));
@@ -21,62 +36,100 @@ pub fn init_tracing(_service_name: &str, _otlp_endpoint: &str) -> Result<(), Box
         .with_line_number(true)
         .json();
 
+    let otel_layer = if settings.otel_enabled {
+        let provider = build_tracer_provider(settings)?;
+        let tracer = provider.tracer(settings.service_name.clone());
+
+        global::set_text_map_propagator(TraceContextPropagator::new());
+        global::set_tracer_provider(provider.clone());
+        let _ = TRACER_PROVIDER.set(provider);
+
+        Some(tracing_opentelemetry::layer().with_tracer(tracer))
+    } else {
+        None
+    };
+
     // Combine layers
     Registry::default()
         .with(env_filter)
         .with(fmt_layer)
-        .init();
+        .with(otel_layer)
+        .try_init()?;
 
-    tracing::info!("Tracing initialized (JSON format)");
-    tracing::info!("For OTLP export, configure OpenTelemetry collector");
+    if settings.otel_enabled {
+        tracing::info!(endpoint = %settings.otel_endpoint, "Tracing initialized (JSON + OTLP export)");
+    } else {
+        tracing::info!("Tracing initialized (JSON format)");
+    }
 
     Ok(())
 }
 
-/// Initialize OpenTelemetry tracing with OTLP exporter (Advanced)
-///
-/// This is a reference implementation for full OpenTelemetry integration.
-/// Requires proper OpenTelemetry setup and running collector.
-///
-/// Example usage:
-/// ```ignore
-/// use opentelemetry::{global, trace::TracerProvider as _, KeyValue};
-/// use opentelemetry_sdk::{
-///     trace::{RandomIdGenerator, Sampler, TracerProvider},
-///     Resource,
-/// };
-/// use opentelemetry_otlp::WithExportConfig;
-///
-/// let resource = Resource::new(vec![
-///     KeyValue::new("service.name", service_name.to_string()),
-///     KeyValue::new("service.version", env!("CARGO_PKG_VERSION")),
-/// ]);
-///
-/// let exporter = opentelemetry_otlp::SpanExporter::builder()
-///     .with_tonic()
-///     .with_endpoint(otlp_endpoint)
-///     .build()?;
-///
-/// let provider = TracerProvider::builder()
-///     .with_batch_exporter(exporter, opentelemetry_sdk::runtime::Tokio)
-///     .with_resource(resource)
-///     .with_sampler(Sampler::AlwaysOn)
-///     .with_id_generator(RandomIdGenerator::default())
-///     .build();
-///
-/// global::set_tracer_provider(provider);
-/// ```
-#[allow(dead_code)]
-pub fn init_tracing_with_otlp_reference() {
-    // This is a reference implementation
-    // Actual implementation depends on your OpenTelemetry setup
-    tracing::warn!("OTLP tracing not configured. Using JSON logging instead.");
+fn build_tracer_provider(settings: &TracingSettings) -> Result<TracerProvider, Box<dyn std::error::Error>> {
+    let exporter = opentelemetry_otlp::SpanExporter::builder()
+        .with_tonic()
+        .with_endpoint(&settings.otel_endpoint)
+        .build()?;
+
+    let resource = Resource::new(vec![
+        KeyValue::new("service.name", settings.service_name.clone()),
+        KeyValue::new("service.version", settings.service_version.clone()),
+    ]);
+
+    Ok(TracerProvider::builder()
+        .with_batch_exporter(exporter, opentelemetry_sdk::runtime::Tokio)
+        .with_resource(resource)
+        .build())
+}
+
+/// Continue the trace of an incoming request carrying `traceparent`
+pub fn set_parent_from_headers(span: &tracing::Span, headers: &actix_web::http::header::HeaderMap) {
+    let context = global::get_text_map_propagator(|propagator| propagator.extract(&HeaderExtractor(headers)));
+    span.set_parent(context);
+}
+
+/// `traceparent`/`tracestate` headers for the current span, to attach to
+/// outbound requests; empty if OTLP export is not enabled
+pub fn trace_context_headers() -> HashMap<String, String> {
+    let context = tracing::Span::current().context();
+    let mut headers = HashMap::new();
+    global::get_text_map_propagator(|propagator| propagator.inject_context(&context, &mut headers));
+    headers
+}
+
+/// OTel trace id of `span` (32 hex chars), if the span is being exported
+pub fn trace_id(span: &tracing::Span) -> Option<String> {
+    use opentelemetry::trace::TraceContextExt;
+
+    let context = span.context();
+    let span_context = context.span().span_context().clone();
+    span_context.is_valid().then(|| span_context.trace_id().to_string())
+}
+
+struct HeaderExtractor<'a>(&'a actix_web::http::header::HeaderMap);
+
+impl Extractor for HeaderExtractor<'_> {
+    fn get(&self, key: &str) -> Option<&str> {
+        self.0.get(key).and_then(|value| value.to_str().ok())
+    }
+
+    fn keys(&self) -> Vec<&str> {
+        self.0.keys().map(|name| name.as_str()).collect()
+    }
 }
 
-/// Shutdown tracing gracefully
+/// Shutdown tracing gracefully, flushing spans still buffered in the exporter
 pub fn shutdown_tracing() {
-    // For basic tracing, no special shutdown needed
-    // For full OpenTelemetry, use: opentelemetry::global::shutdown_tracer_provider();
+    if let Some(provider) = TRACER_PROVIDER.get() {
+        for result in provider.force_flush() {
+            if let Err(e) = result {
+                tracing::warn!(error = %e, "Failed to flush spans");
+            }
+        }
+        if let Err(e) = provider.shutdown() {
+            tracing::warn!(error = %e, "Failed to shut down tracer provider");
+        }
+    }
     tracing::info!("Tracing shutdown");
 }
 
@@ -86,13 +139,47 @@ mod tests {
 
     #[test]
     fn test_tracing_init() {
-        // Test that tracing can be initialized
-        // Note: This will fail if OTLP endpoint is not available
-        // In production, you should have a running Jaeger/Tempo instance
-        let result = init_tracing("test-service", "http://localhost:4317");
-        // We don't assert success here as it requires external service
-        // Just ensure it doesn't panic
-        drop(result);
+        // OTLP export tắt nên không cần collector
+        let settings = TracingSettings {
+            otel_enabled: false,
+            otel_endpoint: "http://localhost:4317".to_string(),
+            service_name: "test-service".to_string(),
+            service_version: "0.0.0".to_string(),
+        };
+        // Subscriber có thể đã được cài bởi test khác; chỉ cần không panic
+        drop(init_tracing(&settings));
     }
-}
 
+    #[test]
+    fn test_trace_context_headers_round_trip() {
+        use opentelemetry::trace::{SpanContext, SpanId, TraceContextExt, TraceFlags, TraceId, TraceState};
+
+        let propagator = TraceContextPropagator::new();
+        let span_context = SpanContext::new(
+            TraceId::from_hex("4bf92f3577b34da6a3ce929d0e0e4736").unwrap(),
+            SpanId::from_hex("00f067aa0ba902b7").unwrap(),
+            TraceFlags::SAMPLED,
+            true,
+            TraceState::default(),
+        );
+        let context = opentelemetry::Context::new().with_remote_span_context(span_context);
+
+        let mut headers = HashMap::new();
+        propagator.inject_context(&context, &mut headers);
+        assert_eq!(
+            headers.get("traceparent").map(String::as_str),
+            Some("00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01")
+        );
+
+        let mut actix_headers = actix_web::http::header::HeaderMap::new();
+        actix_headers.insert(
+            actix_web::http::header::HeaderName::from_static("traceparent"),
+            actix_web::http::header::HeaderValue::from_str(&headers["traceparent"]).unwrap(),
+        );
+        let extracted = propagator.extract(&HeaderExtractor(&actix_headers));
+        assert_eq!(
+            extracted.span().span_context().trace_id(),
+            TraceId::from_hex("4bf92f3577b34da6a3ce929d0e0e4736").unwrap()
+        );
+    }
+}
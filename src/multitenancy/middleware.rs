@@ -1,5 +1,14 @@
-use actix_web::HttpRequest;
-use super::tenant::TenantId;
+use actix_web::{
+    dev::{forward_ready, Service, ServiceRequest, ServiceResponse, Transform},
+    http::header::{HeaderName, HeaderValue},
+    Error, HttpMessage, HttpRequest,
+};
+use futures_util::future::LocalBoxFuture;
+use std::future::{ready, Ready};
+use std::rc::Rc;
+use std::sync::Arc;
+use super::tenant::{TenantId, TenantManager};
+use crate::auth::Claims;
 
 /// Tenant middleware for extracting tenant information from requests
 pub struct TenantMiddleware;
@@ -23,3 +32,89 @@ impl TenantMiddleware {
     }
 }
 
+
+/// Header báo phần quota còn lại sau request
+pub const QUOTA_REMAINING_HEADER: &str = "x-quota-remaining";
+
+/// Middleware tính mỗi request vào quota `resource` của tenant trong JWT
+///
+/// The tenant is the `tenant_id` claim verified by `AuthMiddleware`, so wrap
+/// this inside it; `X-Tenant-ID` is client-controlled and never counted.
+/// Requests without a tenant claim, or from a tenant with no quota on
+/// `resource`, pass through; once the quota is used up they get 429 with
+/// `Retry-After` set to the next window.
+pub struct TenantQuota {
+    tenants: Arc<TenantManager>,
+    resource: Rc<str>,
+}
+
+impl TenantQuota {
+    pub fn new(tenants: Arc<TenantManager>, resource: &str) -> Self {
+        Self {
+            tenants,
+            resource: Rc::from(resource),
+        }
+    }
+}
+
+impl<S, B> Transform<S, ServiceRequest> for TenantQuota
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    S::Future: 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Error = Error;
+    type InitError = ();
+    type Transform = TenantQuotaService<S>;
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ready(Ok(TenantQuotaService {
+            service: Rc::new(service),
+            tenants: self.tenants.clone(),
+            resource: self.resource.clone(),
+        }))
+    }
+}
+
+pub struct TenantQuotaService<S> {
+    service: Rc<S>,
+    tenants: Arc<TenantManager>,
+    resource: Rc<str>,
+}
+
+impl<S, B> Service<ServiceRequest> for TenantQuotaService<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    S::Future: 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Error = Error;
+    type Future = LocalBoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    forward_ready!(service);
+
+    fn call(&self, req: ServiceRequest) -> Self::Future {
+        let tenant_id = req.extensions().get::<Claims>().and_then(|c| c.tenant_id.clone());
+        let service = self.service.clone();
+        let tenants = self.tenants.clone();
+        let resource = self.resource.clone();
+
+        Box::pin(async move {
+            let remaining = match tenant_id {
+                Some(tenant_id) => tenants.consume_quota(&tenant_id, &resource, 1).await?,
+                None => None,
+            };
+            let mut res = service.call(req).await?;
+            if let Some(remaining) = remaining {
+                res.headers_mut().insert(
+                    HeaderName::from_static(QUOTA_REMAINING_HEADER),
+                    HeaderValue::from(remaining),
+                );
+            }
+            Ok(res)
+        })
+    }
+}
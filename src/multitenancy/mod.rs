@@ -1,6 +1,11 @@
 pub mod tenant;
 pub mod middleware;
+pub mod quota;
 
 pub use tenant::{Tenant, TenantId, TenantManager};
-pub use middleware::TenantMiddleware;
+pub use middleware::{TenantMiddleware, TenantQuota};
+pub use quota::{InMemoryQuotaStore, Quota, QuotaPeriod, QuotaStore, QuotaTracker};
+
+#[cfg(feature = "cache-redis")]
+pub use quota::RedisQuotaStore;
 
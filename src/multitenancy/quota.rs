@@ -0,0 +1,323 @@
+use async_trait::async_trait;
+use chrono::{DateTime, Datelike, Duration, TimeZone, Utc};
+use std::collections::HashMap;
+use std::sync::{Arc, RwLock};
+
+use super::tenant::TenantId;
+use crate::errors::ApiError;
+use crate::utils::clock::{Clock, SystemClock};
+
+#[cfg(feature = "cache-redis")]
+use crate::cache::CacheManager;
+
+/// Chu kỳ reset của một quota
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum QuotaPeriod {
+    /// Never resets, e.g. max users
+    Total,
+    /// Resets at 00:00 UTC
+    Daily,
+    /// Resets on the 1st of each month, 00:00 UTC
+    Monthly,
+}
+
+impl QuotaPeriod {
+    /// Start of the window containing `now` and the start of the next one
+    fn window(&self, now: DateTime<Utc>) -> (Option<DateTime<Utc>>, Option<DateTime<Utc>>) {
+        match self {
+            QuotaPeriod::Total => (None, None),
+            QuotaPeriod::Daily => {
+                let midnight = now.date_naive().and_hms_opt(0, 0, 0).expect("midnight is a valid time");
+                let start = Utc.from_utc_datetime(&midnight);
+                (Some(start), Some(start + Duration::days(1)))
+            }
+            QuotaPeriod::Monthly => {
+                let start = Utc.with_ymd_and_hms(now.year(), now.month(), 1, 0, 0, 0).single();
+                let (year, month) = if now.month() == 12 {
+                    (now.year() + 1, 1)
+                } else {
+                    (now.year(), now.month() + 1)
+                };
+                (start, Utc.with_ymd_and_hms(year, month, 1, 0, 0, 0).single())
+            }
+        }
+    }
+}
+
+/// Giới hạn theo plan cho một resource của tenant
+#[derive(Debug, Clone, Copy)]
+pub struct Quota {
+    pub limit: u64,
+    pub period: QuotaPeriod,
+}
+
+/// Bộ đếm mức dùng quota
+///
+/// `counter` identifies a (tenant, resource) pair and `window_start` the
+/// period being counted (`None` for [`QuotaPeriod::Total`]), so a new window
+/// starts from zero without an explicit reset.
+#[async_trait]
+pub trait QuotaStore: Send + Sync {
+    /// Units of `counter` used in the window
+    async fn used(&self, counter: &str, window_start: Option<DateTime<Utc>>) -> Result<u64, ApiError>;
+
+    /// Atomically add `amount` unless that would exceed `limit`; the new
+    /// usage, or `None` when rejected. `expires_at` is the end of the window.
+    async fn try_add(
+        &self,
+        counter: &str,
+        window_start: Option<DateTime<Utc>>,
+        expires_at: Option<DateTime<Utc>>,
+        amount: u64,
+        limit: u64,
+    ) -> Result<Option<u64>, ApiError>;
+}
+
+#[derive(Debug, Clone)]
+struct Usage {
+    window_start: Option<DateTime<Utc>>,
+    used: u64,
+}
+
+/// Single-process counters; use [`RedisQuotaStore`] when running replicas
+#[derive(Default)]
+pub struct InMemoryQuotaStore {
+    usage: RwLock<HashMap<String, Usage>>,
+}
+
+impl InMemoryQuotaStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+#[async_trait]
+impl QuotaStore for InMemoryQuotaStore {
+    async fn used(&self, counter: &str, window_start: Option<DateTime<Utc>>) -> Result<u64, ApiError> {
+        let usage = self
+            .usage
+            .read()
+            .map_err(|_| ApiError::internal("Failed to acquire read lock on quota usage"))?;
+        Ok(usage
+            .get(counter)
+            .filter(|usage| usage.window_start == window_start)
+            .map(|usage| usage.used)
+            .unwrap_or(0))
+    }
+
+    async fn try_add(
+        &self,
+        counter: &str,
+        window_start: Option<DateTime<Utc>>,
+        _expires_at: Option<DateTime<Utc>>,
+        amount: u64,
+        limit: u64,
+    ) -> Result<Option<u64>, ApiError> {
+        let mut usage = self
+            .usage
+            .write()
+            .map_err(|_| ApiError::internal("Failed to acquire write lock on quota usage"))?;
+        let entry = usage.entry(counter.to_string()).or_insert(Usage {
+            window_start,
+            used: 0,
+        });
+        // Sang cửa sổ mới thì đếm lại từ đầu
+        if entry.window_start != window_start {
+            *entry = Usage {
+                window_start,
+                used: 0,
+            };
+        }
+
+        match entry.used.checked_add(amount).filter(|used| *used <= limit) {
+            Some(used) => {
+                entry.used = used;
+                Ok(Some(used))
+            }
+            None => Ok(None),
+        }
+    }
+}
+
+/// Check-and-increment, atomic on the Redis server. Returns the new usage,
+/// or -1 when `amount` would exceed `limit`.
+#[cfg(feature = "cache-redis")]
+const TRY_ADD_SCRIPT: &str = r#"
+local used = tonumber(redis.call("GET", KEYS[1]) or "0")
+local amount = tonumber(ARGV[1])
+local limit = tonumber(ARGV[2])
+local expires_at = tonumber(ARGV[3])
+
+if used + amount > limit then
+    return -1
+end
+
+used = redis.call("INCRBY", KEYS[1], amount)
+if expires_at > 0 then
+    redis.call("EXPIREAT", KEYS[1], expires_at)
+end
+return used
+"#;
+
+/// Counters shared by every replica through Redis
+///
+/// Each window is its own key (`quota:{counter}:{window start}`) that
+/// expires when the window ends.
+#[cfg(feature = "cache-redis")]
+pub struct RedisQuotaStore {
+    conn: redis::aio::ConnectionManager,
+}
+
+#[cfg(feature = "cache-redis")]
+impl RedisQuotaStore {
+    pub fn new(cache: &CacheManager) -> Self {
+        Self {
+            conn: cache.get_connection(),
+        }
+    }
+
+    fn key(counter: &str, window_start: Option<DateTime<Utc>>) -> String {
+        match window_start {
+            Some(start) => format!("quota:{}:{}", counter, start.timestamp()),
+            None => format!("quota:{}:total", counter),
+        }
+    }
+}
+
+#[cfg(feature = "cache-redis")]
+#[async_trait]
+impl QuotaStore for RedisQuotaStore {
+    async fn used(&self, counter: &str, window_start: Option<DateTime<Utc>>) -> Result<u64, ApiError> {
+        use redis::AsyncCommands;
+
+        let mut conn = self.conn.clone();
+        let used: Option<u64> = conn
+            .get(Self::key(counter, window_start))
+            .await
+            .map_err(|e| ApiError::cache(format!("Quota read error: {}", e)))?;
+        Ok(used.unwrap_or(0))
+    }
+
+    async fn try_add(
+        &self,
+        counter: &str,
+        window_start: Option<DateTime<Utc>>,
+        expires_at: Option<DateTime<Utc>>,
+        amount: u64,
+        limit: u64,
+    ) -> Result<Option<u64>, ApiError> {
+        let mut conn = self.conn.clone();
+        let used: i64 = redis::Script::new(TRY_ADD_SCRIPT)
+            .key(Self::key(counter, window_start))
+            .arg(amount)
+            .arg(limit)
+            .arg(expires_at.map(|at| at.timestamp()).unwrap_or(0))
+            .invoke_async(&mut conn)
+            .await
+            .map_err(|e| ApiError::cache(format!("Quota script error: {}", e)))?;
+        Ok(u64::try_from(used).ok())
+    }
+}
+
+/// Đếm mức dùng quota theo (tenant, resource)
+///
+/// Limits are kept in process (set them on every replica at startup); usage
+/// lives in the [`QuotaStore`], in memory unless [`with_store`](Self::with_store)
+/// is given a shared one such as [`RedisQuotaStore`].
+#[derive(Clone)]
+pub struct QuotaTracker {
+    quotas: Arc<RwLock<HashMap<(TenantId, String), Quota>>>,
+    store: Arc<dyn QuotaStore>,
+    clock: Arc<dyn Clock>,
+}
+
+impl QuotaTracker {
+    pub fn new() -> Self {
+        Self {
+            quotas: Arc::new(RwLock::new(HashMap::new())),
+            store: Arc::new(InMemoryQuotaStore::new()),
+            clock: Arc::new(SystemClock),
+        }
+    }
+
+    /// Keep usage in `store` instead of this process
+    pub fn with_store(mut self, store: Arc<dyn QuotaStore>) -> Self {
+        self.store = store;
+        self
+    }
+
+    /// Đọc thời gian từ `clock` thay vì system time (dùng trong tests)
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    pub fn set_quota(&self, tenant_id: &str, resource: &str, quota: Quota) -> Result<(), ApiError> {
+        self.quotas
+            .write()
+            .map_err(|_| ApiError::internal("Failed to acquire write lock on quotas"))?
+            .insert((tenant_id.to_string(), resource.to_string()), quota);
+        Ok(())
+    }
+
+    /// Remaining allowance; `None` when the tenant has no quota on `resource`
+    pub async fn remaining(&self, tenant_id: &str, resource: &str) -> Result<Option<u64>, ApiError> {
+        let Some(quota) = self.quota(tenant_id, resource)? else {
+            return Ok(None);
+        };
+        let (window_start, _) = quota.period.window(self.clock.now());
+
+        let used = self.store.used(&counter(tenant_id, resource), window_start).await?;
+        Ok(Some(quota.limit.saturating_sub(used)))
+    }
+
+    /// Use `amount` of `resource`; `RateLimitExceeded` (with `retry_after`
+    /// until the next window, if any) when that would exceed the quota
+    pub async fn consume(
+        &self,
+        tenant_id: &str,
+        resource: &str,
+        amount: u64,
+    ) -> Result<Option<u64>, ApiError> {
+        let Some(quota) = self.quota(tenant_id, resource)? else {
+            return Ok(None);
+        };
+        let now = self.clock.now();
+        let (window_start, next_window) = quota.period.window(now);
+
+        let used = self
+            .store
+            .try_add(&counter(tenant_id, resource), window_start, next_window, amount, quota.limit)
+            .await?;
+        match used {
+            Some(used) => Ok(Some(quota.limit.saturating_sub(used))),
+            None => {
+                let retry_after = next_window.map(|next| (next - now).num_seconds().max(1) as u64);
+                Err(ApiError::rate_limit(
+                    format!("Quota exceeded for {} (limit {})", resource, quota.limit),
+                    retry_after,
+                ))
+            }
+        }
+    }
+
+    fn quota(&self, tenant_id: &str, resource: &str) -> Result<Option<Quota>, ApiError> {
+        Ok(self
+            .quotas
+            .read()
+            .map_err(|_| ApiError::internal("Failed to acquire read lock on quotas"))?
+            .get(&(tenant_id.to_string(), resource.to_string()))
+            .copied())
+    }
+}
+
+impl Default for QuotaTracker {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Khóa của bộ đếm; tenant id được escape để `a:b` + `c` khác `a` + `b:c`
+fn counter(tenant_id: &str, resource: &str) -> String {
+    format!("{}:{}", tenant_id.replace('%', "%25").replace(':', "%3A"), resource)
+}
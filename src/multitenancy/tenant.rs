@@ -2,6 +2,7 @@ use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::sync::{Arc, RwLock};
 use crate::errors::ApiError;
+use super::quota::{Quota, QuotaTracker};
 
 pub type TenantId = String;
 
@@ -18,15 +19,45 @@ pub struct Tenant {
 /// Tenant manager
 pub struct TenantManager {
     tenants: Arc<RwLock<HashMap<TenantId, Tenant>>>,
+    quotas: QuotaTracker,
 }
 
 impl TenantManager {
     pub fn new() -> Self {
         Self {
             tenants: Arc::new(RwLock::new(HashMap::new())),
+            quotas: QuotaTracker::new(),
         }
     }
 
+    /// Dùng `quotas` thay cho tracker mặc định (vd. tracker dùng Redis hoặc MockClock)
+    pub fn with_quotas(mut self, quotas: QuotaTracker) -> Self {
+        self.quotas = quotas;
+        self
+    }
+
+    /// Plan limit for `resource`, e.g. `requests` per month or `users` in total
+    pub fn set_quota(&self, id: &str, resource: &str, quota: Quota) -> Result<(), ApiError> {
+        self.quotas.set_quota(id, resource, quota)
+    }
+
+    /// Remaining allowance for `resource` in the current window; `None` means
+    /// the tenant has no quota on it
+    pub async fn check_quota(&self, id: &str, resource: &str) -> Result<Option<u64>, ApiError> {
+        self.quotas.remaining(id, resource).await
+    }
+
+    /// Use `amount` of `resource`, failing with `RateLimitExceeded` once the
+    /// quota is used up
+    pub async fn consume_quota(
+        &self,
+        id: &str,
+        resource: &str,
+        amount: u64,
+    ) -> Result<Option<u64>, ApiError> {
+        self.quotas.consume(id, resource, amount).await
+    }
+
     pub fn add_tenant(&self, tenant: Tenant) -> Result<(), ApiError> {
         if let Ok(mut tenants) = self.tenants.write() {
             tenants.insert(tenant.id.clone(), tenant);
@@ -0,0 +1,46 @@
+//! OpenAPI spec cho REST API, sinh bằng `utoipa` từ các handler đã annotate
+//!
+//! Spec được phục vụ tại `/api-docs/openapi.json`, Swagger UI tại `/swagger-ui/`.
+
+use actix_web::web;
+use utoipa::OpenApi;
+use utoipa_swagger_ui::SwaggerUi;
+
+use crate::errors::api_error::ErrorCode;
+use crate::errors::ErrorResponse;
+use crate::handlers::{health_handler, upload_handler, user_handler};
+use crate::models::{CreateUserRequest, UpdateUserRequest, UploadedFile, User};
+
+pub const OPENAPI_JSON_PATH: &str = "/api-docs/openapi.json";
+
+#[derive(OpenApi)]
+#[openapi(
+    info(title = "Rust API Template"),
+    paths(
+        health_handler::health_check,
+        health_handler::readiness_check,
+        health_handler::liveness_check,
+        user_handler::get_users,
+        user_handler::export_users,
+        user_handler::get_user_by_id,
+        user_handler::create_user,
+        user_handler::create_users_batch,
+        user_handler::update_user,
+        user_handler::delete_user,
+        user_handler::restore_user,
+        upload_handler::upload_files,
+    ),
+    // `ErrorResponse` là envelope chung cho mọi response lỗi
+    components(schemas(User, CreateUserRequest, UpdateUserRequest, UploadedFile, ErrorResponse, ErrorCode)),
+    tags(
+        (name = "health", description = "Health, readiness and liveness probes"),
+        (name = "users", description = "User management"),
+        (name = "uploads", description = "File uploads"),
+    )
+)]
+pub struct ApiDoc;
+
+/// Serve the spec and Swagger UI
+pub fn configure_docs_routes(cfg: &mut web::ServiceConfig) {
+    cfg.service(SwaggerUi::new("/swagger-ui/{_:.*}").url(OPENAPI_JSON_PATH, ApiDoc::openapi()));
+}
@@ -1,4 +1,7 @@
 use async_trait::async_trait;
+use futures_util::future::{BoxFuture, FutureExt};
+use std::sync::Mutex;
+use std::time::Instant;
 use crate::errors::ApiError;
 
 /// Command trait
@@ -7,6 +10,92 @@ pub trait Command: Send + Sync {
     type Result: Send;
     
     async fn execute(&self) -> Result<Self::Result, ApiError>;
+
+    /// Tên dùng trong log và metrics
+    fn name(&self) -> &'static str {
+        std::any::type_name::<Self>()
+    }
+
+    /// Kiểm tra dữ liệu trước khi execute; `ValidationMiddleware` gọi hàm này
+    fn validate(&self) -> Result<(), ApiError> {
+        Ok(())
+    }
+}
+
+/// Phần của `Command` mà middleware được thấy, không phụ thuộc `Result` type
+pub trait CommandInfo: Send + Sync {
+    fn name(&self) -> &'static str;
+    fn validate(&self) -> Result<(), ApiError>;
+}
+
+impl<C: Command> CommandInfo for C {
+    fn name(&self) -> &'static str {
+        Command::name(self)
+    }
+
+    fn validate(&self) -> Result<(), ApiError> {
+        Command::validate(self)
+    }
+}
+
+/// Bọc việc thực thi command (validation, logging, metrics, auth...)
+///
+/// Call `next.run()` to continue down the pipeline; returning without it
+/// short-circuits and the command is never executed.
+#[async_trait]
+pub trait CommandMiddleware: Send + Sync {
+    async fn handle(&self, command: &dyn CommandInfo, next: Next<'_>) -> Result<(), ApiError>;
+}
+
+/// Phần còn lại của pipeline sau middleware hiện tại
+pub struct Next<'a> {
+    middlewares: &'a [Box<dyn CommandMiddleware>],
+    command: &'a dyn CommandInfo,
+    terminal: BoxFuture<'a, Result<(), ApiError>>,
+}
+
+impl<'a> Next<'a> {
+    pub async fn run(self) -> Result<(), ApiError> {
+        match self.middlewares.split_first() {
+            Some((middleware, rest)) => {
+                let next = Next {
+                    middlewares: rest,
+                    command: self.command,
+                    terminal: self.terminal,
+                };
+                middleware.handle(self.command, next).await
+            }
+            None => self.terminal.await,
+        }
+    }
+}
+
+/// Từ chối command không hợp lệ trước khi execute
+pub struct ValidationMiddleware;
+
+#[async_trait]
+impl CommandMiddleware for ValidationMiddleware {
+    async fn handle(&self, command: &dyn CommandInfo, next: Next<'_>) -> Result<(), ApiError> {
+        command.validate()?;
+        next.run().await
+    }
+}
+
+/// Log tên command, kết quả và thời gian thực thi
+pub struct LoggingMiddleware;
+
+#[async_trait]
+impl CommandMiddleware for LoggingMiddleware {
+    async fn handle(&self, command: &dyn CommandInfo, next: Next<'_>) -> Result<(), ApiError> {
+        let started = Instant::now();
+        let result = next.run().await;
+        let duration_ms = started.elapsed().as_millis() as u64;
+        match &result {
+            Ok(()) => tracing::info!(command = command.name(), duration_ms, "Command executed"),
+            Err(e) => tracing::warn!(command = command.name(), duration_ms, error = %e, "Command failed"),
+        }
+        result
+    }
 }
 
 /// Query trait
@@ -30,17 +119,45 @@ pub trait QueryHandler<Q: Query>: Send + Sync {
 }
 
 /// Command bus
+///
+/// Middlewares run in the order they were added, outermost first.
 pub struct CommandBus {
-    // Placeholder for command routing
+    middlewares: Vec<Box<dyn CommandMiddleware>>,
 }
 
 impl CommandBus {
     pub fn new() -> Self {
-        Self {}
+        Self {
+            middlewares: Vec::new(),
+        }
+    }
+
+    pub fn with_middleware(mut self, middleware: Box<dyn CommandMiddleware>) -> Self {
+        self.middlewares.push(middleware);
+        self
     }
 
     pub async fn dispatch<C: Command>(&self, command: C) -> Result<C::Result, ApiError> {
-        command.execute().await
+        // Middleware chỉ thấy `Result<(), _>`, kết quả thật được giữ ở đây
+        let slot = Mutex::new(None);
+        let terminal = async {
+            let result = command.execute().await?;
+            *slot.lock().unwrap_or_else(|e| e.into_inner()) = Some(result);
+            Ok(())
+        }
+        .boxed();
+
+        Next {
+            middlewares: &self.middlewares,
+            command: &command,
+            terminal,
+        }
+        .run()
+        .await?;
+
+        slot.into_inner()
+            .unwrap_or_else(|e| e.into_inner())
+            .ok_or_else(|| ApiError::internal("Command middleware did not run the command"))
     }
 }
 
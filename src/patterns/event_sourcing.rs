@@ -1,7 +1,8 @@
 use chrono::{DateTime, Utc};
+use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
-use std::sync::{Arc, RwLock};
+use std::sync::{Arc, Mutex};
 use crate::errors::ApiError;
 
 /// Event trait
@@ -22,24 +23,64 @@ pub struct StoredEvent {
     pub version: u64,
 }
 
+impl StoredEvent {
+    /// Đọc `payload` thành kiểu cụ thể thay vì truy cập từng field JSON
+    pub fn deserialize_payload<T: DeserializeOwned>(&self) -> Result<T, ApiError> {
+        serde_json::from_value(self.payload.clone()).map_err(|e| {
+            ApiError::internal(format!(
+                "Failed to deserialize {} payload (event {}): {}",
+                self.event_type, self.id, e
+            ))
+        })
+    }
+}
+
+/// Event kèm số thứ tự toàn cục trong store
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SequencedEvent {
+    /// Strictly increasing across all aggregates, starting at 1
+    pub global_seq: u64,
+    pub event: StoredEvent,
+}
+
 /// Event store trait
 pub trait EventStore: Send + Sync {
     fn append(&self, event: StoredEvent) -> Result<(), ApiError>;
     fn get_events(&self, aggregate_id: &str) -> Result<Vec<StoredEvent>, ApiError>;
     fn get_events_since(&self, aggregate_id: &str, version: u64) -> Result<Vec<StoredEvent>, ApiError>;
+    /// Every event with `global_seq` greater than the given one, in order
+    /// (projection catch-up)
+    fn get_all_since(&self, global_seq: u64) -> Result<Vec<SequencedEvent>, ApiError>;
 }
 
-/// In-memory event store (for demo)
+/// In-memory event store (for demo and tests)
+///
+/// Appends are serialized by one `Mutex`, so `global_seq` is assigned in
+/// append order with no gaps.
 pub struct InMemoryEventStore {
-    events: Arc<RwLock<HashMap<String, Vec<StoredEvent>>>>,
+    inner: Arc<Mutex<InMemoryEvents>>,
+}
+
+#[derive(Default)]
+struct InMemoryEvents {
+    /// Log toàn cục; phần tử thứ i có `global_seq = i + 1`
+    log: Vec<SequencedEvent>,
+    /// Vị trí trong `log` theo aggregate
+    by_aggregate: HashMap<String, Vec<usize>>,
 }
 
 impl InMemoryEventStore {
     pub fn new() -> Self {
         Self {
-            events: Arc::new(RwLock::new(HashMap::new())),
+            inner: Arc::new(Mutex::new(InMemoryEvents::default())),
         }
     }
+
+    fn lock(&self) -> Result<std::sync::MutexGuard<'_, InMemoryEvents>, ApiError> {
+        self.inner
+            .lock()
+            .map_err(|_| ApiError::internal("Failed to acquire lock on event store"))
+    }
 }
 
 impl Default for InMemoryEventStore {
@@ -50,30 +91,42 @@ impl Default for InMemoryEventStore {
 
 impl EventStore for InMemoryEventStore {
     fn append(&self, event: StoredEvent) -> Result<(), ApiError> {
-        let mut events = self.events.write().map_err(|_| {
-            ApiError::internal("Failed to acquire write lock on event store")
-        })?;
-        
-        events
+        let mut inner = self.lock()?;
+
+        let position = inner.log.len();
+        inner
+            .by_aggregate
             .entry(event.aggregate_id.clone())
-            .or_insert_with(Vec::new)
-            .push(event);
-        
+            .or_default()
+            .push(position);
+        inner.log.push(SequencedEvent {
+            global_seq: position as u64 + 1,
+            event,
+        });
+
         Ok(())
     }
 
     fn get_events(&self, aggregate_id: &str) -> Result<Vec<StoredEvent>, ApiError> {
-        let events = self.events.read().map_err(|_| {
-            ApiError::internal("Failed to acquire read lock on event store")
-        })?;
-        
-        Ok(events.get(aggregate_id).cloned().unwrap_or_default())
+        let inner = self.lock()?;
+
+        Ok(inner
+            .by_aggregate
+            .get(aggregate_id)
+            .map(|positions| positions.iter().map(|&i| inner.log[i].event.clone()).collect())
+            .unwrap_or_default())
     }
 
     fn get_events_since(&self, aggregate_id: &str, version: u64) -> Result<Vec<StoredEvent>, ApiError> {
         let events = self.get_events(aggregate_id)?;
         Ok(events.into_iter().filter(|e| e.version > version).collect())
     }
+
+    fn get_all_since(&self, global_seq: u64) -> Result<Vec<SequencedEvent>, ApiError> {
+        let inner = self.lock()?;
+        let start = global_seq.min(inner.log.len() as u64) as usize;
+        Ok(inner.log[start..].to_vec())
+    }
 }
 
 /// Aggregate trait
@@ -81,6 +134,30 @@ pub trait Aggregate: Send + Sync {
     fn aggregate_id(&self) -> &str;
     fn version(&self) -> u64;
     fn apply_event(&mut self, event: &StoredEvent) -> Result<(), ApiError>;
+
+    /// Apply `events` in order, starting from the current version
+    ///
+    /// Each event must carry the next version (`version() + 1`, then `+ 2`, ...);
+    /// a gap or duplicate stops the replay with `Conflict` on `version`.
+    fn replay(&mut self, events: &[StoredEvent]) -> Result<(), ApiError> {
+        let mut expected = self.version() + 1;
+        for event in events {
+            if event.version != expected {
+                return Err(ApiError::Conflict {
+                    message: format!(
+                        "Version mismatch replaying aggregate {}: expected version {}, got {}",
+                        self.aggregate_id(),
+                        expected,
+                        event.version
+                    ),
+                    field: Some("version".to_string()),
+                });
+            }
+            self.apply_event(event)?;
+            expected += 1;
+        }
+        Ok(())
+    }
 }
 
 /// Event sourcing repository
@@ -1,12 +1,30 @@
 pub mod event_sourcing;
 pub mod cqrs;
 
+#[cfg(any(feature = "database-postgres", feature = "database-mysql", feature = "database-sqlite"))]
+mod sql_event_store;
+
 #[cfg(feature = "database-postgres")]
 pub mod postgres_event_store;
 
-pub use event_sourcing::{Event, EventStore, InMemoryEventStore, Aggregate, EventSourcingRepository, StoredEvent};
-pub use cqrs::{Command, Query, CommandHandler, QueryHandler, CommandBus, QueryBus};
+#[cfg(feature = "database-mysql")]
+pub mod mysql_event_store;
+
+#[cfg(feature = "database-sqlite")]
+pub mod sqlite_event_store;
+
+pub use event_sourcing::{Event, EventStore, InMemoryEventStore, Aggregate, EventSourcingRepository, StoredEvent, SequencedEvent};
+pub use cqrs::{
+    Command, Query, CommandHandler, QueryHandler, CommandBus, QueryBus, CommandInfo, CommandMiddleware,
+    Next, ValidationMiddleware, LoggingMiddleware,
+};
 
 #[cfg(feature = "database-postgres")]
 pub use postgres_event_store::PostgresEventStore;
 
+#[cfg(feature = "database-mysql")]
+pub use mysql_event_store::MySqlEventStore;
+
+#[cfg(feature = "database-sqlite")]
+pub use sqlite_event_store::SqliteEventStore;
+
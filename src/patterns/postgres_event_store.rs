@@ -1,24 +1,58 @@
 use sqlx::PgPool;
+use crate::database::QueryTimer;
 use crate::errors::ApiError;
-use super::event_sourcing::{EventStore, StoredEvent};
+use super::event_sourcing::{EventStore, SequencedEvent, StoredEvent};
+use super::sql_event_store::{append_error, block_on, sequenced_event, stored_event, EventRow, SequencedEventRow};
+
+/// Advisory lock key serializing appends (ASCII "events")
+const EVENT_APPEND_LOCK: i64 = 0x6576_656e_7473;
 
 /// PostgreSQL-backed event store implementation
 pub struct PostgresEventStore {
     pool: PgPool,
+    timer: QueryTimer,
 }
 
 impl PostgresEventStore {
     /// Create a new PostgreSQL event store
     pub fn new(pool: PgPool) -> Self {
-        Self { pool }
+        Self {
+            pool,
+            timer: QueryTimer::default(),
+        }
+    }
+
+    /// Time queries with `timer` (slow query log, `db_query_duration_seconds`)
+    pub fn with_query_timer(mut self, timer: QueryTimer) -> Self {
+        self.timer = timer;
+        self
     }
 
     /// Async version of append - preferred for async contexts
+    ///
+    /// Appends take a transaction-scoped advisory lock, so `global_seq` is
+    /// handed out in commit order: a sequence value taken by one transaction
+    /// can't become visible after a higher one that committed first, which
+    /// would make [`get_all_since_async`](Self::get_all_since_async) readers
+    /// skip it.
     pub async fn append_async(&self, event: StoredEvent) -> Result<(), ApiError> {
         let event_id = uuid::Uuid::parse_str(&event.id)
             .map_err(|e| ApiError::bad_request(&format!("Invalid event ID: {}", e)))?;
 
-        sqlx::query(
+        let mut tx = self
+            .pool
+            .begin()
+            .await
+            .map_err(|e| ApiError::database(format!("Failed to append event: {}", e)))?;
+
+        // Giữ tới khi commit/rollback; các append khác chờ ở đây
+        let lock = sqlx::query("SELECT pg_advisory_xact_lock($1)").bind(EVENT_APPEND_LOCK);
+        self.timer
+            .time("events.append_lock", lock.execute(&mut *tx))
+            .await
+            .map_err(|e| ApiError::database(format!("Failed to lock events for append: {}", e)))?;
+
+        let query = sqlx::query(
             r#"
             INSERT INTO events (id, aggregate_id, event_type, payload, timestamp, version)
             VALUES ($1, $2, $3, $4, $5, $6)
@@ -29,31 +63,26 @@ impl PostgresEventStore {
         .bind(&event.event_type)
         .bind(&event.payload)
         .bind(event.timestamp)
-        .bind(event.version as i64)
-        .execute(&self.pool)
-        .await
-        .map_err(|e| {
-            // Check for unique constraint violation (concurrent write)
-            if let Some(db_err) = e.as_database_error() {
-                if db_err.constraint() == Some("unique_aggregate_version") {
-                    return ApiError::Conflict {
-                        message: format!(
-                            "Version conflict for aggregate {}: version {} already exists",
-                            event.aggregate_id, event.version
-                        ),
-                        field: Some("version".to_string()),
-                    };
-                }
-            }
-            ApiError::database(format!("Failed to append event: {}", e))
-        })?;
+        .bind(event.version as i64);
+        self
+            .timer
+            .time("events.append", query.execute(&mut *tx))
+            .await
+            .map_err(|e| {
+                // Check for unique constraint violation (concurrent write)
+                append_error(e, &event, |db_err| db_err.constraint() == Some("unique_aggregate_version"))
+            })?;
+
+        tx.commit()
+            .await
+            .map_err(|e| ApiError::database(format!("Failed to append event: {}", e)))?;
 
         Ok(())
     }
 
     /// Async version of get_events - preferred for async contexts
     pub async fn get_events_async(&self, aggregate_id: &str) -> Result<Vec<StoredEvent>, ApiError> {
-        let rows = sqlx::query_as::<_, (uuid::Uuid, String, String, serde_json::Value, chrono::DateTime<chrono::Utc>, i64)>(
+        let query = sqlx::query_as::<_, EventRow<uuid::Uuid>>(
             r#"
             SELECT id, aggregate_id, event_type, payload, timestamp, version
             FROM events
@@ -61,29 +90,19 @@ impl PostgresEventStore {
             ORDER BY version ASC
             "#
         )
-        .bind(aggregate_id)
-        .fetch_all(&self.pool)
-        .await
-        .map_err(|e| ApiError::database(format!("Failed to fetch events: {}", e)))?;
-
-        let events = rows
-            .into_iter()
-            .map(|(id, aggregate_id, event_type, payload, timestamp, version)| StoredEvent {
-                id: id.to_string(),
-                aggregate_id,
-                event_type,
-                payload,
-                timestamp,
-                version: version as u64,
-            })
-            .collect();
-
-        Ok(events)
+        .bind(aggregate_id);
+        let rows = self
+            .timer
+            .time("events.get_events", query.fetch_all(&self.pool))
+            .await
+            .map_err(|e| ApiError::database(format!("Failed to fetch events: {}", e)))?;
+
+        Ok(rows.into_iter().map(stored_event).collect())
     }
 
     /// Async version of get_events_since - preferred for async contexts
     pub async fn get_events_since_async(&self, aggregate_id: &str, version: u64) -> Result<Vec<StoredEvent>, ApiError> {
-        let rows = sqlx::query_as::<_, (uuid::Uuid, String, String, serde_json::Value, chrono::DateTime<chrono::Utc>, i64)>(
+        let query = sqlx::query_as::<_, EventRow<uuid::Uuid>>(
             r#"
             SELECT id, aggregate_id, event_type, payload, timestamp, version
             FROM events
@@ -92,29 +111,19 @@ impl PostgresEventStore {
             "#
         )
         .bind(aggregate_id)
-        .bind(version as i64)
-        .fetch_all(&self.pool)
-        .await
-        .map_err(|e| ApiError::database(format!("Failed to fetch events: {}", e)))?;
-
-        let events = rows
-            .into_iter()
-            .map(|(id, aggregate_id, event_type, payload, timestamp, version)| StoredEvent {
-                id: id.to_string(),
-                aggregate_id,
-                event_type,
-                payload,
-                timestamp,
-                version: version as u64,
-            })
-            .collect();
-
-        Ok(events)
+        .bind(version as i64);
+        let rows = self
+            .timer
+            .time("events.get_events_since", query.fetch_all(&self.pool))
+            .await
+            .map_err(|e| ApiError::database(format!("Failed to fetch events: {}", e)))?;
+
+        Ok(rows.into_iter().map(stored_event).collect())
     }
 
     /// Get all events by event type (useful for projections)
     pub async fn get_events_by_type(&self, event_type: &str) -> Result<Vec<StoredEvent>, ApiError> {
-        let rows = sqlx::query_as::<_, (uuid::Uuid, String, String, serde_json::Value, chrono::DateTime<chrono::Utc>, i64)>(
+        let query = sqlx::query_as::<_, EventRow<uuid::Uuid>>(
             r#"
             SELECT id, aggregate_id, event_type, payload, timestamp, version
             FROM events
@@ -122,24 +131,14 @@ impl PostgresEventStore {
             ORDER BY timestamp ASC
             "#
         )
-        .bind(event_type)
-        .fetch_all(&self.pool)
-        .await
-        .map_err(|e| ApiError::database(format!("Failed to fetch events by type: {}", e)))?;
-
-        let events = rows
-            .into_iter()
-            .map(|(id, aggregate_id, event_type, payload, timestamp, version)| StoredEvent {
-                id: id.to_string(),
-                aggregate_id,
-                event_type,
-                payload,
-                timestamp,
-                version: version as u64,
-            })
-            .collect();
-
-        Ok(events)
+        .bind(event_type);
+        let rows = self
+            .timer
+            .time("events.get_events_by_type", query.fetch_all(&self.pool))
+            .await
+            .map_err(|e| ApiError::database(format!("Failed to fetch events by type: {}", e)))?;
+
+        Ok(rows.into_iter().map(stored_event).collect())
     }
 
     /// Get events within a time range (temporal queries)
@@ -149,7 +148,7 @@ impl PostgresEventStore {
         start: chrono::DateTime<chrono::Utc>,
         end: chrono::DateTime<chrono::Utc>,
     ) -> Result<Vec<StoredEvent>, ApiError> {
-        let rows = sqlx::query_as::<_, (uuid::Uuid, String, String, serde_json::Value, chrono::DateTime<chrono::Utc>, i64)>(
+        let query = sqlx::query_as::<_, EventRow<uuid::Uuid>>(
             r#"
             SELECT id, aggregate_id, event_type, payload, timestamp, version
             FROM events
@@ -159,53 +158,57 @@ impl PostgresEventStore {
         )
         .bind(aggregate_id)
         .bind(start)
-        .bind(end)
-        .fetch_all(&self.pool)
-        .await
-        .map_err(|e| ApiError::database(format!("Failed to fetch events in range: {}", e)))?;
-
-        let events = rows
-            .into_iter()
-            .map(|(id, aggregate_id, event_type, payload, timestamp, version)| StoredEvent {
-                id: id.to_string(),
-                aggregate_id,
-                event_type,
-                payload,
-                timestamp,
-                version: version as u64,
-            })
-            .collect();
-
-        Ok(events)
+        .bind(end);
+        let rows = self
+            .timer
+            .time("events.get_events_in_range", query.fetch_all(&self.pool))
+            .await
+            .map_err(|e| ApiError::database(format!("Failed to fetch events in range: {}", e)))?;
+
+        Ok(rows.into_iter().map(stored_event).collect())
+    }
+
+    /// Async version of get_all_since - events across all aggregates in
+    /// `global_seq` order
+    ///
+    /// `global_seq` is strictly increasing in commit order (see
+    /// [`append_async`](Self::append_async)), so once an event is returned no
+    /// event with a lower position can appear later. It may still have gaps
+    /// left by rolled-back inserts.
+    pub async fn get_all_since_async(&self, global_seq: u64) -> Result<Vec<SequencedEvent>, ApiError> {
+        let query = sqlx::query_as::<_, SequencedEventRow<uuid::Uuid>>(
+            r#"
+            SELECT global_seq, id, aggregate_id, event_type, payload, timestamp, version
+            FROM events
+            WHERE global_seq > $1
+            ORDER BY global_seq ASC
+            "#
+        )
+        .bind(global_seq as i64);
+        let rows = self
+            .timer
+            .time("events.get_all_since", query.fetch_all(&self.pool))
+            .await
+            .map_err(|e| ApiError::database(format!("Failed to fetch events: {}", e)))?;
+
+        Ok(rows.into_iter().map(sequenced_event).collect())
     }
 }
 
 impl EventStore for PostgresEventStore {
     fn append(&self, event: StoredEvent) -> Result<(), ApiError> {
-        // Delegate to async version using block_in_place
-        tokio::task::block_in_place(|| {
-            tokio::runtime::Handle::current().block_on(async {
-                self.append_async(event).await
-            })
-        })
+        block_on(self.append_async(event))
     }
 
     fn get_events(&self, aggregate_id: &str) -> Result<Vec<StoredEvent>, ApiError> {
-        // Delegate to async version using block_in_place
-        tokio::task::block_in_place(|| {
-            tokio::runtime::Handle::current().block_on(async {
-                self.get_events_async(aggregate_id).await
-            })
-        })
+        block_on(self.get_events_async(aggregate_id))
     }
 
     fn get_events_since(&self, aggregate_id: &str, version: u64) -> Result<Vec<StoredEvent>, ApiError> {
-        // Delegate to async version using block_in_place
-        tokio::task::block_in_place(|| {
-            tokio::runtime::Handle::current().block_on(async {
-                self.get_events_since_async(aggregate_id, version).await
-            })
-        })
+        block_on(self.get_events_since_async(aggregate_id, version))
     }
-}
 
+    fn get_all_since(&self, global_seq: u64) -> Result<Vec<SequencedEvent>, ApiError> {
+        block_on(self.get_all_since_async(global_seq))
+    }
+}
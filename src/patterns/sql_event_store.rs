@@ -0,0 +1,57 @@
+// Phần dùng chung giữa các event store SQL (Postgres, MySQL, SQLite)
+
+use chrono::{DateTime, Utc};
+use std::future::Future;
+
+use super::event_sourcing::{SequencedEvent, StoredEvent};
+use crate::errors::ApiError;
+
+/// `(id, aggregate_id, event_type, payload, timestamp, version)`; `I` is
+/// `Uuid` on Postgres and the textual id on MySQL/SQLite
+pub(crate) type EventRow<I> = (I, String, String, serde_json::Value, DateTime<Utc>, i64);
+
+/// [`EventRow`] preceded by `global_seq`
+pub(crate) type SequencedEventRow<I> = (i64, I, String, String, serde_json::Value, DateTime<Utc>, i64);
+
+pub(crate) fn stored_event<I: ToString>(row: EventRow<I>) -> StoredEvent {
+    let (id, aggregate_id, event_type, payload, timestamp, version) = row;
+    StoredEvent {
+        id: id.to_string(),
+        aggregate_id,
+        event_type,
+        payload,
+        timestamp,
+        version: version as u64,
+    }
+}
+
+pub(crate) fn sequenced_event<I: ToString>(row: SequencedEventRow<I>) -> SequencedEvent {
+    let (global_seq, id, aggregate_id, event_type, payload, timestamp, version) = row;
+    SequencedEvent {
+        global_seq: global_seq as u64,
+        event: stored_event((id, aggregate_id, event_type, payload, timestamp, version)),
+    }
+}
+
+/// Map an insert error to `Conflict` when `is_version_conflict` recognises
+/// the backend's unique violation on `(aggregate_id, version)`
+pub(crate) fn append_error<F>(err: sqlx::Error, event: &StoredEvent, is_version_conflict: F) -> ApiError
+where
+    F: Fn(&dyn sqlx::error::DatabaseError) -> bool,
+{
+    if err.as_database_error().is_some_and(is_version_conflict) {
+        return ApiError::Conflict {
+            message: format!(
+                "Version conflict for aggregate {}: version {} already exists",
+                event.aggregate_id, event.version
+            ),
+            field: Some("version".to_string()),
+        };
+    }
+    ApiError::database(format!("Failed to append event: {}", err))
+}
+
+/// Run an async store call from the sync `EventStore` trait
+pub(crate) fn block_on<F: Future>(fut: F) -> F::Output {
+    tokio::task::block_in_place(|| tokio::runtime::Handle::current().block_on(fut))
+}
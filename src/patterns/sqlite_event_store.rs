@@ -0,0 +1,141 @@
+use sqlx::SqlitePool;
+use crate::database::QueryTimer;
+use crate::errors::ApiError;
+use super::event_sourcing::{EventStore, SequencedEvent, StoredEvent};
+use super::sql_event_store::{append_error, block_on, sequenced_event, stored_event, EventRow, SequencedEventRow};
+
+/// SQLite-backed event store implementation
+///
+/// Uses the `events` table from `migrations/sqlite`; ids are stored as
+/// `TEXT` and `global_seq` is the `INTEGER PRIMARY KEY AUTOINCREMENT` rowid.
+pub struct SqliteEventStore {
+    pool: SqlitePool,
+    timer: QueryTimer,
+}
+
+impl SqliteEventStore {
+    /// Create a new SQLite event store
+    pub fn new(pool: SqlitePool) -> Self {
+        Self {
+            pool,
+            timer: QueryTimer::default(),
+        }
+    }
+
+    /// Time queries with `timer` (slow query log, `db_query_duration_seconds`)
+    pub fn with_query_timer(mut self, timer: QueryTimer) -> Self {
+        self.timer = timer;
+        self
+    }
+
+    /// Async version of append - preferred for async contexts
+    pub async fn append_async(&self, event: StoredEvent) -> Result<(), ApiError> {
+        let event_id = uuid::Uuid::parse_str(&event.id)
+            .map_err(|e| ApiError::bad_request(format!("Invalid event ID: {}", e)))?;
+
+        let query = sqlx::query(
+            r#"
+            INSERT INTO events (id, aggregate_id, event_type, payload, timestamp, version)
+            VALUES (?, ?, ?, ?, ?, ?)
+            "#
+        )
+        .bind(event_id.to_string())
+        .bind(&event.aggregate_id)
+        .bind(&event.event_type)
+        .bind(&event.payload)
+        .bind(event.timestamp)
+        .bind(event.version as i64);
+        self
+            .timer
+            .time("events.append", query.execute(&self.pool))
+            .await
+            .map_err(|e| {
+                // SQLite chỉ báo tên cột: "UNIQUE constraint failed: events.aggregate_id, events.version"
+                append_error(e, &event, |db_err| {
+                    db_err.is_unique_violation() && db_err.message().contains("events.version")
+                })
+            })?;
+
+        Ok(())
+    }
+
+    /// Async version of get_events - preferred for async contexts
+    pub async fn get_events_async(&self, aggregate_id: &str) -> Result<Vec<StoredEvent>, ApiError> {
+        let query = sqlx::query_as::<_, EventRow<String>>(
+            r#"
+            SELECT id, aggregate_id, event_type, payload, timestamp, version
+            FROM events
+            WHERE aggregate_id = ?
+            ORDER BY version ASC
+            "#
+        )
+        .bind(aggregate_id);
+        let rows = self
+            .timer
+            .time("events.get_events", query.fetch_all(&self.pool))
+            .await
+            .map_err(|e| ApiError::database(format!("Failed to fetch events: {}", e)))?;
+
+        Ok(rows.into_iter().map(stored_event).collect())
+    }
+
+    /// Async version of get_events_since - preferred for async contexts
+    pub async fn get_events_since_async(&self, aggregate_id: &str, version: u64) -> Result<Vec<StoredEvent>, ApiError> {
+        let query = sqlx::query_as::<_, EventRow<String>>(
+            r#"
+            SELECT id, aggregate_id, event_type, payload, timestamp, version
+            FROM events
+            WHERE aggregate_id = ? AND version > ?
+            ORDER BY version ASC
+            "#
+        )
+        .bind(aggregate_id)
+        .bind(version as i64);
+        let rows = self
+            .timer
+            .time("events.get_events_since", query.fetch_all(&self.pool))
+            .await
+            .map_err(|e| ApiError::database(format!("Failed to fetch events: {}", e)))?;
+
+        Ok(rows.into_iter().map(stored_event).collect())
+    }
+
+    /// Async version of get_all_since - events across all aggregates in
+    /// `global_seq` order
+    pub async fn get_all_since_async(&self, global_seq: u64) -> Result<Vec<SequencedEvent>, ApiError> {
+        let query = sqlx::query_as::<_, SequencedEventRow<String>>(
+            r#"
+            SELECT global_seq, id, aggregate_id, event_type, payload, timestamp, version
+            FROM events
+            WHERE global_seq > ?
+            ORDER BY global_seq ASC
+            "#
+        )
+        .bind(global_seq as i64);
+        let rows = self
+            .timer
+            .time("events.get_all_since", query.fetch_all(&self.pool))
+            .await
+            .map_err(|e| ApiError::database(format!("Failed to fetch events: {}", e)))?;
+
+        Ok(rows.into_iter().map(sequenced_event).collect())
+    }
+}
+
+impl EventStore for SqliteEventStore {
+    fn append(&self, event: StoredEvent) -> Result<(), ApiError> {
+        block_on(self.append_async(event))
+    }
+
+    fn get_events(&self, aggregate_id: &str) -> Result<Vec<StoredEvent>, ApiError> {
+        block_on(self.get_events_async(aggregate_id))
+    }
+
+    fn get_events_since(&self, aggregate_id: &str, version: u64) -> Result<Vec<StoredEvent>, ApiError> {
+        block_on(self.get_events_since_async(aggregate_id, version))
+    }
+
+    fn get_all_since(&self, global_seq: u64) -> Result<Vec<SequencedEvent>, ApiError> {
+        block_on(self.get_all_since_async(global_seq))
+    }
+}
@@ -0,0 +1,10 @@
+use actix_web::web;
+use crate::handlers::{delete_flag, list_flags, list_jobs, update_flag};
+
+/// Routes dưới `/admin`; mount trong scope có `AuthMiddleware`
+pub fn configure_admin_routes(cfg: &mut web::ServiceConfig) {
+    cfg.route("/jobs", web::get().to(list_jobs))
+        .route("/flags", web::get().to(list_flags))
+        .route("/flags/{name}", web::put().to(update_flag))
+        .route("/flags/{name}", web::delete().to(delete_flag));
+}
@@ -0,0 +1,6 @@
+use actix_web::web;
+use crate::handlers::event_stream;
+
+pub fn configure_event_routes(cfg: &mut web::ServiceConfig) {
+    cfg.route("/events/stream", web::get().to(event_stream));
+}
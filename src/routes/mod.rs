@@ -1,5 +1,15 @@
 pub mod user_routes;
 pub mod health_routes;
+pub mod upload_routes;
+pub mod admin_routes;
+
+#[cfg(feature = "websocket")]
+pub mod event_routes;
 
 pub use user_routes::configure_user_routes;
 pub use health_routes::configure_health_routes;
+pub use upload_routes::configure_upload_routes;
+pub use admin_routes::configure_admin_routes;
+
+#[cfg(feature = "websocket")]
+pub use event_routes::configure_event_routes;
@@ -0,0 +1,6 @@
+use actix_web::web;
+use crate::handlers::upload_files;
+
+pub fn configure_upload_routes(cfg: &mut web::ServiceConfig) {
+    cfg.route("/uploads", web::post().to(upload_files));
+}
@@ -3,15 +3,22 @@ use crate::handlers::{
     get_users,
     get_user_by_id,
     create_user,
+    create_users_batch,
+    export_users,
     update_user,
     delete_user,
+    restore_user,
 };
 
 pub fn configure_user_routes(cfg: &mut web::ServiceConfig) {
     cfg
         .route("/users", web::get().to(get_users))
         .route("/users", web::post().to(create_user))
+        .route("/users/batch", web::post().to(create_users_batch))
+        // Phải đăng ký trước `/users/{id}`
+        .route("/users/export", web::get().to(export_users))
         .route("/users/{id}", web::get().to(get_user_by_id))
         .route("/users/{id}", web::put().to(update_user))
-        .route("/users/{id}", web::delete().to(delete_user));
+        .route("/users/{id}", web::delete().to(delete_user))
+        .route("/users/{id}/restore", web::post().to(restore_user));
 }
@@ -2,6 +2,8 @@ use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::sync::{Arc, RwLock};
+use super::audit_sink::{AuditSink, SinkWriter, DEFAULT_SINK_CAPACITY};
+use crate::utils::clock::{Clock, SystemClock};
 
 /// Audit event type
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
@@ -39,8 +41,8 @@ pub enum AuditEventType {
     Custom(String),
 }
 
-/// Audit event severity
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+/// Audit event severity, ordered from least to most severe
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord)]
 #[serde(rename_all = "UPPERCASE")]
 pub enum AuditSeverity {
     Info,
@@ -76,9 +78,14 @@ pub enum AuditResult {
 
 impl AuditEvent {
     pub fn new(event_type: AuditEventType, action: String) -> Self {
+        Self::new_with_clock(event_type, action, &SystemClock)
+    }
+
+    /// Like [`AuditEvent::new`], timestamped by `clock`
+    pub fn new_with_clock(event_type: AuditEventType, action: String, clock: &dyn Clock) -> Self {
         Self {
             id: uuid::Uuid::new_v4().to_string(),
-            timestamp: Utc::now(),
+            timestamp: clock.now(),
             event_type,
             severity: AuditSeverity::Info,
             user_id: None,
@@ -127,10 +134,52 @@ impl AuditEvent {
     }
 }
 
+/// Filter for querying audit events
+#[derive(Debug, Clone)]
+pub struct AuditQuery {
+    /// Event types to include (empty matches all types)
+    pub event_types: Vec<AuditEventType>,
+    /// Minimum severity to include
+    pub severity_at_least: AuditSeverity,
+    /// Include events at or after this time
+    pub from: Option<DateTime<Utc>>,
+    /// Include events at or before this time
+    pub to: Option<DateTime<Utc>>,
+    /// Maximum number of events to return
+    pub limit: Option<usize>,
+}
+
+impl Default for AuditQuery {
+    fn default() -> Self {
+        Self {
+            event_types: Vec::new(),
+            severity_at_least: AuditSeverity::Info,
+            from: None,
+            to: None,
+            limit: None,
+        }
+    }
+}
+
+impl AuditQuery {
+    fn matches(&self, event: &AuditEvent) -> bool {
+        (self.event_types.is_empty() || self.event_types.contains(&event.event_type))
+            && event.severity >= self.severity_at_least
+            && self.from.map_or(true, |from| event.timestamp >= from)
+            && self.to.map_or(true, |to| event.timestamp <= to)
+    }
+}
+
 /// Audit logger
+///
+/// Recent events are kept in memory for querying. Attach a sink with
+/// [`AuditLogger::with_sink`] to persist them; pending events are flushed
+/// to the sink when the logger is dropped, waiting at most
+/// [`SINK_SHUTDOWN_TIMEOUT`](super::audit_sink::SINK_SHUTDOWN_TIMEOUT).
 pub struct AuditLogger {
     events: Arc<RwLock<Vec<AuditEvent>>>,
     max_events: usize,
+    sink: Option<SinkWriter>,
 }
 
 impl AuditLogger {
@@ -138,9 +187,21 @@ impl AuditLogger {
         Self {
             events: Arc::new(RwLock::new(Vec::new())),
             max_events,
+            sink: None,
         }
     }
 
+    /// Persist events to a sink, written from a background thread
+    pub fn with_sink<S: AuditSink + 'static>(self, sink: S) -> Self {
+        self.with_sink_capacity(sink, DEFAULT_SINK_CAPACITY)
+    }
+
+    /// Persist events to a sink, buffering at most `capacity` pending events
+    pub fn with_sink_capacity<S: AuditSink + 'static>(mut self, sink: S, capacity: usize) -> Self {
+        self.sink = Some(SinkWriter::spawn(sink, capacity));
+        self
+    }
+
     /// Log an audit event
     pub fn log(&self, event: AuditEvent) {
         // Log to structured logger
@@ -156,7 +217,11 @@ impl AuditLogger {
             "Audit event"
         );
 
-        // Store in memory (for demo purposes)
+        if let Some(sink) = &self.sink {
+            sink.send(event.clone());
+        }
+
+        // Keep recent events in memory for queries
         if let Ok(mut events) = self.events.write() {
             events.push(event);
 
@@ -191,6 +256,23 @@ impl AuditLogger {
             Vec::new()
         }
     }
+
+    /// Query events matching a filter, newest first
+    pub fn query(&self, filter: AuditQuery) -> Vec<AuditEvent> {
+        if let Ok(events) = self.events.read() {
+            let mut matched: Vec<AuditEvent> = events
+                .iter()
+                .filter(|e| filter.matches(e))
+                .cloned()
+                .collect();
+
+            matched.sort_by(|a, b| b.timestamp.cmp(&a.timestamp));
+            matched.truncate(filter.limit.unwrap_or(usize::MAX));
+            matched
+        } else {
+            Vec::new()
+        }
+    }
 }
 
 impl Default for AuditLogger {
@@ -0,0 +1,222 @@
+use std::fs::{File, OpenOptions};
+use std::io::Write;
+use std::path::Path;
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError, SyncSender, TrySendError};
+use std::sync::Mutex;
+use std::thread::JoinHandle;
+use std::time::Duration;
+use crate::errors::ApiError;
+use super::audit::AuditEvent;
+
+/// Default number of events buffered between the logger and its sink
+pub const DEFAULT_SINK_CAPACITY: usize = 1024;
+
+/// How long dropping the logger waits for the sink to drain pending events
+pub const SINK_SHUTDOWN_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Destination for persisted audit events
+pub trait AuditSink: Send + Sync {
+    fn write(&self, event: &AuditEvent);
+}
+
+/// Appends audit events to a file, one JSON object per line
+pub struct FileAuditSink {
+    file: Mutex<File>,
+}
+
+impl FileAuditSink {
+    pub fn new(path: impl AsRef<Path>) -> Result<Self, ApiError> {
+        let file = OpenOptions::new().create(true).append(true).open(path)?;
+        Ok(Self {
+            file: Mutex::new(file),
+        })
+    }
+}
+
+impl AuditSink for FileAuditSink {
+    fn write(&self, event: &AuditEvent) {
+        let line = match serde_json::to_string(event) {
+            Ok(line) => line,
+            Err(e) => {
+                tracing::error!(event_id = %event.id, "Failed to serialize audit event: {}", e);
+                return;
+            }
+        };
+
+        if let Ok(mut file) = self.file.lock() {
+            if let Err(e) = writeln!(file, "{}", line) {
+                tracing::error!(event_id = %event.id, "Failed to write audit event: {}", e);
+            }
+        }
+    }
+}
+
+/// Stores audit events in the `audit_events` table
+///
+/// The sink runs its own single-threaded runtime and connection on the
+/// writer thread, so it never waits on the server's runtime (which may be
+/// the one blocked shutting the logger down).
+#[cfg(feature = "database-postgres")]
+pub struct PostgresAuditSink {
+    options: sqlx::postgres::PgConnectOptions,
+    connection: std::sync::OnceLock<Option<(sqlx::PgPool, tokio::runtime::Runtime)>>,
+}
+
+#[cfg(feature = "database-postgres")]
+impl PostgresAuditSink {
+    /// Create a new sink connecting with the same options as `pool`
+    pub fn new(pool: sqlx::PgPool) -> Self {
+        Self {
+            options: (*pool.connect_options()).clone(),
+            connection: std::sync::OnceLock::new(),
+        }
+    }
+
+    /// Build the runtime and pool on first use, i.e. on the writer thread
+    fn connection(&self) -> Option<&(sqlx::PgPool, tokio::runtime::Runtime)> {
+        self.connection
+            .get_or_init(|| {
+                let runtime = tokio::runtime::Builder::new_current_thread()
+                    .enable_all()
+                    .build()
+                    .map_err(|e| tracing::error!("Failed to start audit sink runtime: {}", e))
+                    .ok()?;
+                // Pool chạy task nền trên runtime hiện tại nên phải tạo bên trong nó
+                let pool = {
+                    let _guard = runtime.enter();
+                    sqlx::postgres::PgPoolOptions::new()
+                        .max_connections(1)
+                        .acquire_timeout(SINK_SHUTDOWN_TIMEOUT)
+                        .connect_lazy_with(self.options.clone())
+                };
+                Some((pool, runtime))
+            })
+            .as_ref()
+    }
+
+    async fn insert(pool: &sqlx::PgPool, event: &AuditEvent) -> Result<(), ApiError> {
+        let event_id = uuid::Uuid::parse_str(&event.id)
+            .map_err(|e| ApiError::bad_request(format!("Invalid event ID: {}", e)))?;
+
+        sqlx::query(
+            r#"
+            INSERT INTO audit_events
+                (id, timestamp, event_type, severity, user_id, ip_address, resource, action, result, metadata, request_id)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11)
+            "#
+        )
+        .bind(event_id)
+        .bind(event.timestamp)
+        .bind(enum_label(&event.event_type))
+        .bind(enum_label(&event.severity))
+        .bind(&event.user_id)
+        .bind(&event.ip_address)
+        .bind(&event.resource)
+        .bind(&event.action)
+        .bind(enum_label(&event.result))
+        .bind(serde_json::to_value(&event.metadata)?)
+        .bind(&event.request_id)
+        .execute(pool)
+        .await
+        .map_err(|e| ApiError::database(format!("Failed to store audit event: {}", e)))?;
+
+        Ok(())
+    }
+}
+
+#[cfg(feature = "database-postgres")]
+impl AuditSink for PostgresAuditSink {
+    fn write(&self, event: &AuditEvent) {
+        let Some((pool, runtime)) = self.connection() else {
+            tracing::error!(event_id = %event.id, "Audit sink has no runtime, dropping event");
+            return;
+        };
+
+        // Runs on the logger's writer thread, so blocking here doesn't stall requests
+        if let Err(e) = runtime.block_on(Self::insert(pool, event)) {
+            tracing::error!(event_id = %event.id, "Failed to persist audit event: {}", e);
+        }
+    }
+}
+
+/// Serialized name of an enum value (e.g. `LOGIN_SUCCESS`)
+#[cfg(feature = "database-postgres")]
+fn enum_label<T: serde::Serialize>(value: &T) -> String {
+    match serde_json::to_value(value) {
+        Ok(serde_json::Value::String(label)) => label,
+        Ok(other) => other.to_string(),
+        Err(_) => String::new(),
+    }
+}
+
+/// Forwards events to a sink on a background thread through a bounded channel
+pub(crate) struct SinkWriter {
+    sender: Option<SyncSender<AuditEvent>>,
+    handle: Option<JoinHandle<()>>,
+    /// Disconnects once the writer thread has finished
+    finished: Receiver<()>,
+}
+
+impl SinkWriter {
+    pub(crate) fn spawn<S: AuditSink + 'static>(sink: S, capacity: usize) -> Self {
+        let (sender, receiver) = mpsc::sync_channel::<AuditEvent>(capacity);
+        let (done, finished) = mpsc::channel::<()>();
+
+        let handle = std::thread::Builder::new()
+            .name("audit-sink-writer".to_string())
+            .spawn(move || {
+                // Dropped last, after the sink, even if the sink panics
+                let _done = done;
+                while let Ok(event) = receiver.recv() {
+                    sink.write(&event);
+                }
+                drop(sink);
+            })
+            .map_err(|e| tracing::error!("Failed to start audit sink writer: {}", e))
+            .ok();
+
+        Self {
+            sender: handle.as_ref().map(|_| sender),
+            handle,
+            finished,
+        }
+    }
+
+    /// Queue an event without blocking. Events are dropped if the buffer is full.
+    pub(crate) fn send(&self, event: AuditEvent) {
+        if let Some(sender) = &self.sender {
+            match sender.try_send(event) {
+                Ok(()) => {}
+                Err(TrySendError::Full(event)) => {
+                    tracing::warn!(event_id = %event.id, "Audit sink buffer full, dropping event");
+                }
+                Err(TrySendError::Disconnected(event)) => {
+                    tracing::error!(event_id = %event.id, "Audit sink writer stopped, dropping event");
+                }
+            }
+        }
+    }
+}
+
+impl Drop for SinkWriter {
+    fn drop(&mut self) {
+        // Closing the channel lets the writer drain pending events and exit
+        self.sender.take();
+        let Some(handle) = self.handle.take() else {
+            return;
+        };
+
+        // Không join vô hạn: drop có thể chạy trên runtime mà sink đang chờ
+        match self.finished.recv_timeout(SINK_SHUTDOWN_TIMEOUT) {
+            Err(RecvTimeoutError::Disconnected) => {
+                let _ = handle.join();
+            }
+            Ok(()) | Err(RecvTimeoutError::Timeout) => {
+                tracing::warn!(
+                    "Audit sink did not finish within {:?}, abandoning pending events",
+                    SINK_SHUTDOWN_TIMEOUT
+                );
+            }
+        }
+    }
+}
@@ -1,18 +1,149 @@
 use actix_web::{
     dev::{forward_ready, Service, ServiceRequest, ServiceResponse, Transform},
+    http::header::{HeaderName, HeaderValue},
     Error,
 };
 use futures_util::future::LocalBoxFuture;
 use std::future::{ready, Ready};
+use std::rc::Rc;
 
 pub mod secrets;
 pub mod audit;
+pub mod audit_sink;
+pub mod redact;
 
 pub use secrets::{SecretsManager, SecretsConfig, SecretsBackend, Secret};
-pub use audit::{AuditLogger, AuditEvent, AuditEventType, AuditSeverity, AuditResult};
+pub use audit::{AuditLogger, AuditEvent, AuditEventType, AuditSeverity, AuditResult, AuditQuery};
+pub use audit_sink::{AuditSink, FileAuditSink, SINK_SHUTDOWN_TIMEOUT};
+pub use redact::{redact, Redactor, DEFAULT_REDACT_FIELDS, REDACTED};
+
+#[cfg(feature = "database-postgres")]
+pub use audit_sink::PostgresAuditSink;
+
+/// Strict-Transport-Security settings
+#[derive(Debug, Clone)]
+pub struct HstsConfig {
+    pub max_age_secs: u64,
+    pub include_subdomains: bool,
+    pub preload: bool,
+}
+
+impl Default for HstsConfig {
+    fn default() -> Self {
+        Self {
+            max_age_secs: 31_536_000,
+            include_subdomains: true,
+            preload: false,
+        }
+    }
+}
+
+impl HstsConfig {
+    fn header_value(&self) -> String {
+        let mut value = format!("max-age={}", self.max_age_secs);
+        if self.include_subdomains {
+            value.push_str("; includeSubDomains");
+        }
+        if self.preload {
+            value.push_str("; preload");
+        }
+        value
+    }
+}
+
+/// Security headers configuration
+///
+/// Each header is omitted when its field is `None` (or `false` for the
+/// fixed-value headers).
+#[derive(Debug, Clone)]
+pub struct SecurityHeadersConfig {
+    pub content_security_policy: Option<String>,
+    /// Leave unset when the app is not served over HTTPS
+    pub hsts: Option<HstsConfig>,
+    pub frame_options: Option<String>,
+    pub content_type_options: bool,
+    pub xss_protection: bool,
+    pub referrer_policy: Option<String>,
+    pub permissions_policy: Option<String>,
+}
+
+impl Default for SecurityHeadersConfig {
+    fn default() -> Self {
+        Self {
+            content_security_policy: Some(
+                "default-src 'self'; script-src 'self' 'unsafe-inline'; style-src 'self' 'unsafe-inline'"
+                    .to_string(),
+            ),
+            hsts: Some(HstsConfig::default()),
+            frame_options: Some("DENY".to_string()),
+            content_type_options: true,
+            xss_protection: true,
+            referrer_policy: Some("strict-origin-when-cross-origin".to_string()),
+            permissions_policy: Some("geolocation=(), microphone=(), camera=()".to_string()),
+        }
+    }
+}
+
+impl SecurityHeadersConfig {
+    fn to_headers(&self) -> Vec<(HeaderName, HeaderValue)> {
+        let entries = [
+            // Prevent clickjacking
+            ("x-frame-options", self.frame_options.clone()),
+            // Prevent MIME sniffing
+            (
+                "x-content-type-options",
+                self.content_type_options.then(|| "nosniff".to_string()),
+            ),
+            // XSS Protection
+            (
+                "x-xss-protection",
+                self.xss_protection.then(|| "1; mode=block".to_string()),
+            ),
+            // Content Security Policy
+            ("content-security-policy", self.content_security_policy.clone()),
+            // Strict Transport Security (HTTPS only)
+            (
+                "strict-transport-security",
+                self.hsts.as_ref().map(HstsConfig::header_value),
+            ),
+            ("referrer-policy", self.referrer_policy.clone()),
+            ("permissions-policy", self.permissions_policy.clone()),
+        ];
+
+        entries
+            .into_iter()
+            .filter_map(|(name, value)| {
+                let value = value?;
+                match HeaderValue::from_str(&value) {
+                    Ok(value) => Some((HeaderName::from_static(name), value)),
+                    Err(_) => {
+                        tracing::warn!(header = name, "Ignoring invalid security header value");
+                        None
+                    }
+                }
+            })
+            .collect()
+    }
+}
 
 /// Security Headers Middleware
-pub struct SecurityHeaders;
+pub struct SecurityHeaders {
+    headers: Rc<Vec<(HeaderName, HeaderValue)>>,
+}
+
+impl SecurityHeaders {
+    pub fn new(config: SecurityHeadersConfig) -> Self {
+        Self {
+            headers: Rc::new(config.to_headers()),
+        }
+    }
+}
+
+impl Default for SecurityHeaders {
+    fn default() -> Self {
+        Self::new(SecurityHeadersConfig::default())
+    }
+}
 
 impl<S, B> Transform<S, ServiceRequest> for SecurityHeaders
 where
@@ -27,12 +158,16 @@ where
     type Future = Ready<Result<Self::Transform, Self::InitError>>;
 
     fn new_transform(&self, service: S) -> Self::Future {
-        ready(Ok(SecurityHeadersMiddleware { service }))
+        ready(Ok(SecurityHeadersMiddleware {
+            service,
+            headers: Rc::clone(&self.headers),
+        }))
     }
 }
 
 pub struct SecurityHeadersMiddleware<S> {
     service: S,
+    headers: Rc<Vec<(HeaderName, HeaderValue)>>,
 }
 
 impl<S, B> Service<ServiceRequest> for SecurityHeadersMiddleware<S>
@@ -49,56 +184,16 @@ where
 
     fn call(&self, req: ServiceRequest) -> Self::Future {
         let fut = self.service.call(req);
+        let security_headers = Rc::clone(&self.headers);
 
         Box::pin(async move {
             let mut res = fut.await?;
 
             // Add security headers
             let headers = res.headers_mut();
-            
-            // Prevent clickjacking
-            headers.insert(
-                actix_web::http::header::HeaderName::from_static("x-frame-options"),
-                actix_web::http::header::HeaderValue::from_static("DENY"),
-            );
-
-            // XSS Protection
-            headers.insert(
-                actix_web::http::header::HeaderName::from_static("x-content-type-options"),
-                actix_web::http::header::HeaderValue::from_static("nosniff"),
-            );
-
-            // Prevent MIME sniffing
-            headers.insert(
-                actix_web::http::header::HeaderName::from_static("x-xss-protection"),
-                actix_web::http::header::HeaderValue::from_static("1; mode=block"),
-            );
-
-            // Content Security Policy
-            headers.insert(
-                actix_web::http::header::HeaderName::from_static("content-security-policy"),
-                actix_web::http::header::HeaderValue::from_static(
-                    "default-src 'self'; script-src 'self' 'unsafe-inline'; style-src 'self' 'unsafe-inline'"
-                ),
-            );
-
-            // Strict Transport Security (HTTPS only)
-            headers.insert(
-                actix_web::http::header::HeaderName::from_static("strict-transport-security"),
-                actix_web::http::header::HeaderValue::from_static("max-age=31536000; includeSubDomains"),
-            );
-
-            // Referrer Policy
-            headers.insert(
-                actix_web::http::header::HeaderName::from_static("referrer-policy"),
-                actix_web::http::header::HeaderValue::from_static("strict-origin-when-cross-origin"),
-            );
-
-            // Permissions Policy
-            headers.insert(
-                actix_web::http::header::HeaderName::from_static("permissions-policy"),
-                actix_web::http::header::HeaderValue::from_static("geolocation=(), microphone=(), camera=()"),
-            );
+            for (name, value) in security_headers.iter() {
+                headers.insert(name.clone(), value.clone());
+            }
 
             Ok(res)
         })
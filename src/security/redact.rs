@@ -0,0 +1,88 @@
+use serde_json::Value;
+
+/// Giá trị thay thế cho field nhạy cảm
+pub const REDACTED: &str = "***";
+
+/// Fields masked by [`Redactor::default`]
+pub const DEFAULT_REDACT_FIELDS: &[&str] = &["password", "token", "secret", "authorization"];
+
+/// Che giá trị của các field nhạy cảm trước khi log hoặc trả về trong lỗi
+///
+/// A key is sensitive when it contains one of the configured names,
+/// case-insensitively, so `access_token`, `clientSecret` and `new_password`
+/// are masked too.
+#[derive(Debug, Clone)]
+pub struct Redactor {
+    fields: Vec<String>,
+}
+
+impl Redactor {
+    pub fn new<I, S>(fields: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        Self {
+            fields: fields.into_iter().map(|f| f.into().to_lowercase()).collect(),
+        }
+    }
+
+    /// Mask `field` in addition to the current list
+    pub fn with_field(mut self, field: impl Into<String>) -> Self {
+        self.fields.push(field.into().to_lowercase());
+        self
+    }
+
+    pub fn is_sensitive(&self, key: &str) -> bool {
+        let key = key.to_lowercase();
+        self.fields.iter().any(|field| key.contains(field.as_str()))
+    }
+
+    /// Replace values of sensitive keys with `***`, in nested objects and arrays too
+    pub fn redact(&self, value: &mut Value) {
+        match value {
+            Value::Object(map) => {
+                for (key, value) in map.iter_mut() {
+                    if self.is_sensitive(key) {
+                        *value = Value::String(REDACTED.to_string());
+                    } else {
+                        self.redact(value);
+                    }
+                }
+            }
+            Value::Array(items) => items.iter_mut().for_each(|item| self.redact(item)),
+            _ => {}
+        }
+    }
+
+    /// Query string with the values of sensitive parameters replaced by `***`,
+    /// e.g. `token=eyJ...&page=2` → `token=***&page=2`
+    pub fn redact_query(&self, query: &str) -> String {
+        query
+            .split('&')
+            .map(|pair| match pair.split_once('=') {
+                Some((key, _)) if self.is_sensitive(key) => format!("{}={}", key, REDACTED),
+                _ => pair.to_string(),
+            })
+            .collect::<Vec<_>>()
+            .join("&")
+    }
+
+    /// Redacted copy of a JSON body; `None` if `body` is not valid JSON
+    pub fn redact_body(&self, body: &[u8]) -> Option<String> {
+        let mut value: Value = serde_json::from_slice(body).ok()?;
+        self.redact(&mut value);
+        Some(value.to_string())
+    }
+}
+
+impl Default for Redactor {
+    fn default() -> Self {
+        Self::new(DEFAULT_REDACT_FIELDS.iter().copied())
+    }
+}
+
+/// [`Redactor::redact`] with the default field list
+pub fn redact(value: &mut Value) {
+    Redactor::default().redact(value)
+}
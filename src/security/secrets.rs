@@ -1,9 +1,14 @@
 use crate::errors::ApiError;
-use std::collections::HashMap;
-use std::sync::{Arc, RwLock};
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::sync::{Arc, Mutex, RwLock, Weak};
+use std::time::Duration;
+use zeroize::Zeroize;
 
 /// Secret value wrapper
-#[derive(Debug, Clone)]
+///
+/// The plaintext is redacted from `Debug` output and wiped from memory on drop.
+#[derive(Clone)]
 pub struct Secret {
     value: String,
     version: u32,
@@ -21,6 +26,26 @@ impl Secret {
     pub fn version(&self) -> u32 {
         self.version
     }
+
+    fn with_version(mut self, version: u32) -> Self {
+        self.version = version;
+        self
+    }
+}
+
+impl fmt::Debug for Secret {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Secret")
+            .field("version", &self.version)
+            .field("value", &"***")
+            .finish()
+    }
+}
+
+impl Drop for Secret {
+    fn drop(&mut self) {
+        self.value.zeroize();
+    }
 }
 
 /// Secrets manager configuration
@@ -31,7 +56,7 @@ pub struct SecretsConfig {
     pub refresh_interval_secs: u64,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Clone)]
 pub enum SecretsBackend {
     Environment,
     Vault {
@@ -45,6 +70,25 @@ pub enum SecretsBackend {
     },
 }
 
+impl fmt::Debug for SecretsBackend {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Environment => f.write_str("Environment"),
+            Self::Vault { url, mount_path, .. } => f
+                .debug_struct("Vault")
+                .field("url", url)
+                .field("token", &"***")
+                .field("mount_path", mount_path)
+                .finish(),
+            Self::AwsSecretsManager { region, secret_prefix } => f
+                .debug_struct("AwsSecretsManager")
+                .field("region", region)
+                .field("secret_prefix", secret_prefix)
+                .finish(),
+        }
+    }
+}
+
 impl Default for SecretsConfig {
     fn default() -> Self {
         Self {
@@ -55,20 +99,38 @@ impl Default for SecretsConfig {
     }
 }
 
+type SecretCache = RwLock<HashMap<String, Secret>>;
+
 /// Secrets manager
+///
+/// When `auto_refresh` is enabled, every fetched secret is re-read from the
+/// backend every `refresh_interval_secs`; the cached version is bumped
+/// whenever the value changes.
 pub struct SecretsManager {
     config: SecretsConfig,
-    cache: Arc<RwLock<HashMap<String, Secret>>>,
+    backend: BackendClient,
+    cache: Arc<SecretCache>,
+    refreshing: Arc<Mutex<HashSet<String>>>,
 }
 
 impl SecretsManager {
     pub fn new(config: SecretsConfig) -> Self {
         Self {
+            backend: BackendClient::new(config.backend.clone()),
             config,
             cache: Arc::new(RwLock::new(HashMap::new())),
+            refreshing: Arc::new(Mutex::new(HashSet::new())),
         }
     }
 
+    /// Use a pre-built AWS Secrets Manager client instead of one loaded from
+    /// the environment for the configured region
+    #[cfg(feature = "secrets-aws")]
+    pub fn with_aws_client(mut self, client: aws_sdk_secretsmanager::Client) -> Self {
+        self.backend.aws_client = Arc::new(tokio::sync::OnceCell::new_with(Some(client)));
+        self
+    }
+
     /// Get a secret by key
     pub async fn get_secret(&self, key: &str) -> Result<Secret, ApiError> {
         // Check cache first
@@ -82,22 +144,119 @@ impl SecretsManager {
         }
 
         // Fetch from backend
-        let secret = self.fetch_from_backend(key).await?;
+        let secret = self.backend.fetch(key).await?;
 
-        // Update cache
-        {
+        // Update cache, keeping the version of a previously cached value
+        let secret = {
             let mut cache = self.cache.write().map_err(|_| {
                 ApiError::internal("Failed to acquire write lock on secrets cache")
             })?;
+            let secret = match cache.get(key) {
+                Some(cached) if cached.value == secret.value => cached.clone(),
+                Some(cached) => secret.with_version(cached.version + 1),
+                None => secret,
+            };
             cache.insert(key.to_string(), secret.clone());
+            secret
+        };
+
+        if self.config.auto_refresh {
+            self.spawn_refresh(key);
         }
 
         Ok(secret)
     }
 
+    /// Start a background task that periodically re-fetches a secret
+    fn spawn_refresh(&self, key: &str) {
+        let is_new = self
+            .refreshing
+            .lock()
+            .map(|mut refreshing| refreshing.insert(key.to_string()))
+            .unwrap_or(false);
+        if !is_new {
+            return;
+        }
+
+        let backend = self.backend.clone();
+        let interval = Duration::from_secs(self.config.refresh_interval_secs.max(1));
+        let cache = Arc::downgrade(&self.cache);
+        let key = key.to_string();
+
+        tokio::spawn(async move {
+            Self::refresh_loop(backend, cache, key, interval).await;
+        });
+    }
+
+    async fn refresh_loop(
+        backend: BackendClient,
+        cache: Weak<SecretCache>,
+        key: String,
+        interval: Duration,
+    ) {
+        loop {
+            tokio::time::sleep(interval).await;
+
+            // Stop once the manager has been dropped
+            let Some(cache) = cache.upgrade() else {
+                return;
+            };
+
+            match backend.fetch(&key).await {
+                Ok(fresh) => {
+                    if let Ok(mut cache) = cache.write() {
+                        let version = match cache.get(&key) {
+                            Some(cached) if cached.value == fresh.value => continue,
+                            Some(cached) => cached.version + 1,
+                            None => fresh.version,
+                        };
+                        cache.insert(key.clone(), fresh.with_version(version));
+                        tracing::info!(key = %key, version, "Secret refreshed");
+                    }
+                }
+                Err(e) => {
+                    tracing::warn!(key = %key, "Failed to refresh secret: {}", e);
+                }
+            }
+        }
+    }
+
+
+    /// Rotate a secret
+    pub async fn rotate_secret(&self, key: &str) -> Result<Secret, ApiError> {
+        // Invalidate cache
+        {
+            let mut cache = self.cache.write().map_err(|_| {
+                ApiError::internal("Failed to acquire write lock on secrets cache")
+            })?;
+            cache.remove(key);
+        }
+
+        // Fetch new value
+        self.get_secret(key).await
+    }
+}
+
+/// Backend handle shared between the manager and its refresh tasks
+#[derive(Clone)]
+struct BackendClient {
+    backend: SecretsBackend,
+    #[cfg(feature = "secrets-aws")]
+    aws_client: Arc<tokio::sync::OnceCell<aws_sdk_secretsmanager::Client>>,
+}
+
+impl BackendClient {
+    fn new(backend: SecretsBackend) -> Self {
+        Self {
+            backend,
+            #[cfg(feature = "secrets-aws")]
+            aws_client: Arc::new(tokio::sync::OnceCell::new()),
+        }
+    }
+
     /// Fetch secret from backend
-    async fn fetch_from_backend(&self, key: &str) -> Result<Secret, ApiError> {
-        match &self.config.backend {
+    async fn fetch(&self, key: &str) -> Result<Secret, ApiError> {
+        match &self.backend {
             SecretsBackend::Environment => {
                 let value = std::env::var(key).map_err(|_| {
                     ApiError::configuration(format!("Environment variable {} not found", key))
@@ -105,7 +264,7 @@ impl SecretsManager {
                 Ok(Secret::new(value))
             }
             SecretsBackend::Vault { url, token, mount_path } => {
-                self.fetch_from_vault(key, url, token, mount_path).await
+                Self::fetch_from_vault(key, url, token, mount_path).await
             }
             SecretsBackend::AwsSecretsManager { region, secret_prefix } => {
                 self.fetch_from_aws(key, region, secret_prefix).await
@@ -113,51 +272,185 @@ impl SecretsManager {
         }
     }
 
+    /// Fetch from HashiCorp Vault (KV v2 secrets engine)
+    #[cfg(feature = "secrets-vault")]
+    async fn fetch_from_vault(
+        key: &str,
+        url: &str,
+        token: &str,
+        mount_path: &str,
+    ) -> Result<Secret, ApiError> {
+        use crate::monitoring::TracePropagation;
+        use reqwest::StatusCode;
+        use serde::Deserialize;
+
+        #[derive(Deserialize)]
+        struct VaultResponse {
+            data: VaultData,
+        }
+
+        #[derive(Deserialize)]
+        struct VaultData {
+            data: VaultSecret,
+        }
+
+        #[derive(Deserialize)]
+        struct VaultSecret {
+            value: String,
+        }
+
+        let endpoint = format!(
+            "{}/v1/{}/data/{}",
+            url.trim_end_matches('/'),
+            mount_path.trim_matches('/'),
+            key
+        );
+
+        let response = reqwest::Client::new()
+            .get(&endpoint)
+            .header("X-Vault-Token", token)
+            .with_trace_context()
+            .send()
+            .await
+            .map_err(|e| ApiError::ExternalServiceError {
+                service: "vault".to_string(),
+                message: format!("Failed to reach Vault: {}", e),
+                source: Some(Box::new(e)),
+            })?;
+
+        match response.status() {
+            StatusCode::FORBIDDEN => {
+                return Err(ApiError::authentication("Vault rejected the configured token"));
+            }
+            StatusCode::NOT_FOUND => {
+                return Err(ApiError::not_found_resource(
+                    format!("Secret {} not found in Vault", key),
+                    "secret",
+                ));
+            }
+            status if !status.is_success() => {
+                return Err(ApiError::external_service(
+                    format!("Vault returned status {}", status),
+                    "vault",
+                ));
+            }
+            _ => {}
+        }
+
+        let body: VaultResponse = response
+            .json()
+            .await
+            .map_err(|e| ApiError::ExternalServiceError {
+                service: "vault".to_string(),
+                message: format!("Failed to parse Vault response for key {}: {}", key, e),
+                source: Some(Box::new(e)),
+            })?;
+
+        Ok(Secret::new(body.data.data.value))
+    }
+
     /// Fetch from HashiCorp Vault
+    #[cfg(not(feature = "secrets-vault"))]
     async fn fetch_from_vault(
-        &self,
         key: &str,
         url: &str,
         token: &str,
         mount_path: &str,
     ) -> Result<Secret, ApiError> {
-        // Placeholder for Vault integration
-        // In production, use vaultrs crate
         let _ = (url, token, mount_path);
         Err(ApiError::configuration(format!(
-            "Vault integration not implemented for key: {}",
+            "Vault support requires the `secrets-vault` feature (key: {})",
             key
         )))
     }
 
     /// Fetch from AWS Secrets Manager
+    ///
+    /// The secret id is `{secret_prefix}{key}`. The secret string may be the
+    /// raw value or a JSON object of the form `{"value": "..."}`.
+    #[cfg(feature = "secrets-aws")]
+    async fn fetch_from_aws(
+        &self,
+        key: &str,
+        region: &str,
+        secret_prefix: &str,
+    ) -> Result<Secret, ApiError> {
+        use aws_sdk_secretsmanager::error::{DisplayErrorContext, ProvideErrorMetadata};
+
+        let client = self
+            .aws_client
+            .get_or_init(|| async {
+                let config = aws_config::defaults(aws_config::BehaviorVersion::latest())
+                    .region(aws_config::Region::new(region.to_string()))
+                    .load()
+                    .await;
+                aws_sdk_secretsmanager::Client::new(&config)
+            })
+            .await;
+
+        let secret_id = format!("{}{}", secret_prefix, key);
+        let output = client
+            .get_secret_value()
+            .secret_id(&secret_id)
+            .send()
+            .await
+            .map_err(|err| match err.code() {
+                Some("ResourceNotFoundException") => ApiError::not_found_resource(
+                    format!("Secret {} not found in AWS Secrets Manager", secret_id),
+                    "secret",
+                ),
+                Some("AccessDeniedException") => ApiError::authorization(format!(
+                    "Access denied to secret {} in AWS Secrets Manager",
+                    secret_id
+                )),
+                Some("ThrottlingException") | Some("TooManyRequestsException") => {
+                    ApiError::rate_limit("AWS Secrets Manager request was throttled", None)
+                }
+                _ => ApiError::ExternalServiceError {
+                    service: "aws-secretsmanager".to_string(),
+                    message: format!(
+                        "Failed to fetch secret {}: {}",
+                        secret_id,
+                        DisplayErrorContext(&err)
+                    ),
+                    source: Some(Box::new(err)),
+                },
+            })?;
+
+        let raw = output.secret_string().ok_or_else(|| {
+            ApiError::external_service(
+                format!("Secret {} has no string value", secret_id),
+                "aws-secretsmanager",
+            )
+        })?;
+
+        Ok(Secret::new(parse_aws_secret_string(raw)))
+    }
+
+    /// Fetch from AWS Secrets Manager
+    #[cfg(not(feature = "secrets-aws"))]
     async fn fetch_from_aws(
         &self,
         key: &str,
         region: &str,
         secret_prefix: &str,
     ) -> Result<Secret, ApiError> {
-        // Placeholder for AWS Secrets Manager integration
-        // In production, use aws-sdk-secretsmanager
         let _ = (region, secret_prefix);
         Err(ApiError::configuration(format!(
-            "AWS Secrets Manager integration not implemented for key: {}",
+            "AWS Secrets Manager support requires the `secrets-aws` feature (key: {})",
             key
         )))
     }
+}
 
-    /// Rotate a secret
-    pub async fn rotate_secret(&self, key: &str) -> Result<Secret, ApiError> {
-        // Invalidate cache
-        {
-            let mut cache = self.cache.write().map_err(|_| {
-                ApiError::internal("Failed to acquire write lock on secrets cache")
-            })?;
-            cache.remove(key);
-        }
-
-        // Fetch new value
-        self.get_secret(key).await
+/// Extract the value from an AWS secret string, accepting `{"value": "..."}` JSON
+#[cfg(feature = "secrets-aws")]
+fn parse_aws_secret_string(raw: &str) -> String {
+    match serde_json::from_str::<serde_json::Value>(raw) {
+        Ok(serde_json::Value::Object(map)) => match map.get("value") {
+            Some(serde_json::Value::String(value)) => value.clone(),
+            _ => raw.to_string(),
+        },
+        _ => raw.to_string(),
     }
 }
-
@@ -0,0 +1,163 @@
+use async_trait::async_trait;
+use std::sync::{Arc, RwLock};
+
+use crate::errors::ApiError;
+
+#[cfg(feature = "email")]
+use crate::config::settings::EmailSettings;
+
+/// Email đã soạn, dùng bởi `MockEmailService` để kiểm tra trong tests
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EmailMessage {
+    pub to: String,
+    pub subject: String,
+    pub body_html: String,
+    pub body_text: String,
+}
+
+/// Gửi email (SMTP trong production, mock trong tests)
+#[async_trait]
+pub trait EmailService: Send + Sync {
+    async fn send(
+        &self,
+        to: &str,
+        subject: &str,
+        body_html: &str,
+        body_text: &str,
+    ) -> Result<(), ApiError>;
+}
+
+/// Soạn và gửi email đặt lại mật khẩu
+pub async fn send_password_reset(
+    email_service: &dyn EmailService,
+    to: &str,
+    reset_url: &str,
+) -> Result<(), ApiError> {
+    let body_html = format!(
+        "<p>We received a request to reset your password.</p>\
+         <p><a href=\"{url}\">Reset your password</a></p>\
+         <p>If you didn't request this, you can ignore this email.</p>",
+        url = reset_url
+    );
+    let body_text = format!(
+        "We received a request to reset your password.\n\n\
+         Reset your password: {url}\n\n\
+         If you didn't request this, you can ignore this email.",
+        url = reset_url
+    );
+
+    email_service
+        .send(to, "Reset your password", &body_html, &body_text)
+        .await
+}
+
+/// SMTP email service dùng `lettre`, cấu hình từ `EmailSettings`
+#[cfg(feature = "email")]
+pub struct SmtpEmailService {
+    transport: lettre::AsyncSmtpTransport<lettre::Tokio1Executor>,
+    from: lettre::message::Mailbox,
+}
+
+#[cfg(feature = "email")]
+impl SmtpEmailService {
+    /// Build an SMTP transport using STARTTLS and, if set, credentials
+    pub fn new(settings: &EmailSettings) -> Result<Self, ApiError> {
+        use lettre::transport::smtp::authentication::Credentials;
+        use lettre::{AsyncSmtpTransport, Tokio1Executor};
+
+        let from = settings.from_address.parse().map_err(|e| {
+            ApiError::configuration(format!("Invalid SMTP_FROM address: {}", e))
+        })?;
+
+        let mut builder = AsyncSmtpTransport::<Tokio1Executor>::starttls_relay(&settings.smtp_host)
+            .map_err(|e| ApiError::external_service(format!("SMTP relay error: {}", e), "email"))?
+            .port(settings.smtp_port);
+
+        if !settings.smtp_username.is_empty() {
+            builder = builder.credentials(Credentials::new(
+                settings.smtp_username.clone(),
+                settings.smtp_password.clone(),
+            ));
+        }
+
+        Ok(Self {
+            transport: builder.build(),
+            from,
+        })
+    }
+}
+
+#[cfg(feature = "email")]
+#[async_trait]
+impl EmailService for SmtpEmailService {
+    async fn send(
+        &self,
+        to: &str,
+        subject: &str,
+        body_html: &str,
+        body_text: &str,
+    ) -> Result<(), ApiError> {
+        use lettre::message::{Mailbox, MultiPart};
+        use lettre::{AsyncTransport, Message};
+
+        let to: Mailbox = to
+            .parse()
+            .map_err(|e| ApiError::validation_field(format!("Invalid recipient address: {}", e), "to"))?;
+
+        let message = Message::builder()
+            .from(self.from.clone())
+            .to(to)
+            .subject(subject)
+            .multipart(MultiPart::alternative_plain_html(
+                body_text.to_string(),
+                body_html.to_string(),
+            ))
+            .map_err(|e| ApiError::external_service(format!("Failed to build email: {}", e), "email"))?;
+
+        self.transport
+            .send(message)
+            .await
+            .map_err(|e| ApiError::external_service(format!("Failed to send email: {}", e), "email"))?;
+
+        Ok(())
+    }
+}
+
+/// Email service ghi lại các email đã gửi, dùng cho tests
+#[derive(Clone, Default)]
+pub struct MockEmailService {
+    sent: Arc<RwLock<Vec<EmailMessage>>>,
+}
+
+impl MockEmailService {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Emails sent so far, oldest first
+    pub fn sent(&self) -> Vec<EmailMessage> {
+        self.sent.read().map(|sent| sent.clone()).unwrap_or_default()
+    }
+}
+
+#[async_trait]
+impl EmailService for MockEmailService {
+    async fn send(
+        &self,
+        to: &str,
+        subject: &str,
+        body_html: &str,
+        body_text: &str,
+    ) -> Result<(), ApiError> {
+        self.sent
+            .write()
+            .map_err(|_| ApiError::internal("Failed to acquire write lock on mock email service"))?
+            .push(EmailMessage {
+                to: to.to_string(),
+                subject: subject.to_string(),
+                body_html: body_html.to_string(),
+                body_text: body_text.to_string(),
+            });
+        Ok(())
+    }
+}
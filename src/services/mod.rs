@@ -1,6 +1,19 @@
 // Services layer - Business logic layer
 // Tách business logic khỏi handlers để dễ test và tái sử dụng
 
+pub mod email_service;
+pub mod storage_service;
+pub mod user_repository;
 pub mod user_service;
 
+pub use email_service::{send_password_reset, EmailMessage, EmailService, MockEmailService};
+pub use storage_service::{InMemoryStorageService, StorageService, StoredObject};
+pub use user_repository::{BatchCreateError, InMemoryUserRepository, UserRepository};
 pub use user_service::UserService;
+
+#[cfg(feature = "email")]
+pub use email_service::SmtpEmailService;
+#[cfg(feature = "storage-s3")]
+pub use storage_service::S3StorageService;
+#[cfg(feature = "database-postgres")]
+pub use user_repository::PostgresUserRepository;
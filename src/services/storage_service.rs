@@ -0,0 +1,256 @@
+use async_trait::async_trait;
+use std::collections::HashMap;
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
+
+use crate::errors::ApiError;
+
+#[cfg(feature = "storage-s3")]
+use crate::config::settings::StorageSettings;
+
+/// Object storage cho uploads/downloads
+#[async_trait]
+pub trait StorageService: Send + Sync {
+    async fn put_object(
+        &self,
+        key: &str,
+        body: Vec<u8>,
+        content_type: Option<&str>,
+    ) -> Result<(), ApiError>;
+
+    async fn get_object(&self, key: &str) -> Result<Vec<u8>, ApiError>;
+
+    /// URL cho phép client tải object trực tiếp, hết hạn sau `expires_in`
+    async fn presign_get(&self, key: &str, expires_in: Duration) -> Result<String, ApiError>;
+
+    async fn delete_object(&self, key: &str) -> Result<(), ApiError>;
+}
+
+/// Object đã lưu trong `InMemoryStorageService`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StoredObject {
+    pub body: Vec<u8>,
+    pub content_type: Option<String>,
+}
+
+/// In-memory storage service (dùng cho development và tests)
+#[derive(Clone, Default)]
+pub struct InMemoryStorageService {
+    objects: Arc<RwLock<HashMap<String, StoredObject>>>,
+}
+
+impl InMemoryStorageService {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn object(&self, key: &str) -> Option<StoredObject> {
+        self.objects.read().ok()?.get(key).cloned()
+    }
+
+    /// Keys of all stored objects, sorted
+    pub fn keys(&self) -> Vec<String> {
+        let mut keys: Vec<String> = self
+            .objects
+            .read()
+            .map(|objects| objects.keys().cloned().collect())
+            .unwrap_or_default();
+        keys.sort();
+        keys
+    }
+
+    fn write(&self) -> Result<std::sync::RwLockWriteGuard<'_, HashMap<String, StoredObject>>, ApiError> {
+        self.objects
+            .write()
+            .map_err(|_| ApiError::internal("Failed to acquire write lock on storage"))
+    }
+}
+
+#[async_trait]
+impl StorageService for InMemoryStorageService {
+    async fn put_object(
+        &self,
+        key: &str,
+        body: Vec<u8>,
+        content_type: Option<&str>,
+    ) -> Result<(), ApiError> {
+        self.write()?.insert(
+            key.to_string(),
+            StoredObject {
+                body,
+                content_type: content_type.map(String::from),
+            },
+        );
+        Ok(())
+    }
+
+    async fn get_object(&self, key: &str) -> Result<Vec<u8>, ApiError> {
+        self.object(key)
+            .map(|object| object.body)
+            .ok_or_else(|| ApiError::not_found_resource(format!("Object {} not found", key), "object"))
+    }
+
+    async fn presign_get(&self, key: &str, _expires_in: Duration) -> Result<String, ApiError> {
+        Ok(format!("memory://{}", key))
+    }
+
+    async fn delete_object(&self, key: &str) -> Result<(), ApiError> {
+        self.write()?.remove(key);
+        Ok(())
+    }
+}
+
+/// S3 storage service dùng `aws-sdk-s3`, cấu hình từ `StorageSettings`
+#[cfg(feature = "storage-s3")]
+pub struct S3StorageService {
+    client: aws_sdk_s3::Client,
+    bucket: String,
+    presign_expiry: Duration,
+}
+
+#[cfg(feature = "storage-s3")]
+impl S3StorageService {
+    /// Load AWS credentials from the environment and build a client for the
+    /// configured region, bucket and (optional) custom endpoint
+    pub async fn new(settings: &StorageSettings) -> Self {
+        let sdk_config = aws_config::defaults(aws_config::BehaviorVersion::latest())
+            .region(aws_config::Region::new(settings.aws_region.clone()))
+            .load()
+            .await;
+
+        let mut config = aws_sdk_s3::config::Builder::from(&sdk_config);
+        if let Some(endpoint) = &settings.s3_endpoint {
+            // S3-compatible stores thường không hỗ trợ virtual-hosted bucket
+            config = config.endpoint_url(endpoint).force_path_style(true);
+        }
+
+        Self::from_client(
+            aws_sdk_s3::Client::from_conf(config.build()),
+            settings.s3_bucket.clone(),
+            Duration::from_secs(settings.presign_expiry_secs),
+        )
+    }
+
+    pub fn from_client(client: aws_sdk_s3::Client, bucket: impl Into<String>, presign_expiry: Duration) -> Self {
+        Self {
+            client,
+            bucket: bucket.into(),
+            presign_expiry,
+        }
+    }
+
+    /// Configured lifetime for presigned URLs (`S3_PRESIGN_EXPIRY_SECS`)
+    pub fn presign_expiry(&self) -> Duration {
+        self.presign_expiry
+    }
+
+    /// Presigned PUT URL so clients can upload directly
+    pub async fn presign_put(&self, key: &str, expires_in: Duration) -> Result<String, ApiError> {
+        let presigning = presigning_config(expires_in)?;
+        let request = self
+            .client
+            .put_object()
+            .bucket(&self.bucket)
+            .key(key)
+            .presigned(presigning)
+            .await
+            .map_err(|e| s3_error(e, "presign upload of", key))?;
+        Ok(request.uri().to_string())
+    }
+}
+
+#[cfg(feature = "storage-s3")]
+#[async_trait]
+impl StorageService for S3StorageService {
+    async fn put_object(
+        &self,
+        key: &str,
+        body: Vec<u8>,
+        content_type: Option<&str>,
+    ) -> Result<(), ApiError> {
+        self.client
+            .put_object()
+            .bucket(&self.bucket)
+            .key(key)
+            .body(aws_sdk_s3::primitives::ByteStream::from(body))
+            .set_content_type(content_type.map(String::from))
+            .send()
+            .await
+            .map_err(|e| s3_error(e, "upload", key))?;
+        Ok(())
+    }
+
+    async fn get_object(&self, key: &str) -> Result<Vec<u8>, ApiError> {
+        let output = self
+            .client
+            .get_object()
+            .bucket(&self.bucket)
+            .key(key)
+            .send()
+            .await
+            .map_err(|e| s3_error(e, "download", key))?;
+
+        let body = output.body.collect().await.map_err(|e| ApiError::ExternalServiceError {
+            service: "s3".to_string(),
+            message: format!("Failed to read object {}: {}", key, e),
+            source: Some(Box::new(e)),
+        })?;
+        Ok(body.into_bytes().to_vec())
+    }
+
+    async fn presign_get(&self, key: &str, expires_in: Duration) -> Result<String, ApiError> {
+        let presigning = presigning_config(expires_in)?;
+        let request = self
+            .client
+            .get_object()
+            .bucket(&self.bucket)
+            .key(key)
+            .presigned(presigning)
+            .await
+            .map_err(|e| s3_error(e, "presign download of", key))?;
+        Ok(request.uri().to_string())
+    }
+
+    async fn delete_object(&self, key: &str) -> Result<(), ApiError> {
+        self.client
+            .delete_object()
+            .bucket(&self.bucket)
+            .key(key)
+            .send()
+            .await
+            .map_err(|e| s3_error(e, "delete", key))?;
+        Ok(())
+    }
+}
+
+/// S3 giới hạn presigned URL tối đa 7 ngày
+#[cfg(feature = "storage-s3")]
+fn presigning_config(expires_in: Duration) -> Result<aws_sdk_s3::presigning::PresigningConfig, ApiError> {
+    aws_sdk_s3::presigning::PresigningConfig::expires_in(expires_in)
+        .map_err(|e| ApiError::validation_field(format!("Invalid presign expiry: {}", e), "expires_in"))
+}
+
+#[cfg(feature = "storage-s3")]
+fn s3_error<E>(
+    err: aws_sdk_s3::error::SdkError<E, aws_sdk_s3::config::http::HttpResponse>,
+    action: &str,
+    key: &str,
+) -> ApiError
+where
+    E: aws_sdk_s3::error::ProvideErrorMetadata + std::error::Error + Send + Sync + 'static,
+{
+    use aws_sdk_s3::error::{DisplayErrorContext, ProvideErrorMetadata};
+
+    match err.code() {
+        Some("NoSuchKey") | Some("NotFound") => {
+            ApiError::not_found_resource(format!("Object {} not found", key), "object")
+        }
+        Some("AccessDenied") => ApiError::authorization(format!("Access denied to object {}", key)),
+        Some("SlowDown") => ApiError::rate_limit("S3 request was throttled", None),
+        _ => ApiError::ExternalServiceError {
+            service: "s3".to_string(),
+            message: format!("Failed to {} object {}: {}", action, key, DisplayErrorContext(&err)),
+            source: Some(Box::new(err)),
+        },
+    }
+}
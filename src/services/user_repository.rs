@@ -0,0 +1,644 @@
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use futures_util::stream::{self, BoxStream, StreamExt};
+use std::collections::HashMap;
+use std::sync::{Arc, RwLock};
+
+use crate::errors::{ApiError, ApiResult};
+use crate::models::{ListQuery, Paginated, Pagination, User};
+use crate::services::UserService;
+
+/// Nơi lưu trữ users; handlers chỉ phụ thuộc vào trait này nên cùng một bộ
+/// handlers chạy được với in-memory hoặc database
+#[async_trait]
+pub trait UserRepository: Send + Sync {
+    /// All users, including soft-deleted ones
+    async fn find_all(&self) -> ApiResult<Vec<User>>;
+
+    /// One page of the users matching `query`, in its order, with the number
+    /// of matches; soft-deleted users only with `include_deleted`
+    async fn find_page(
+        &self,
+        query: &ListQuery,
+        pagination: Pagination,
+        include_deleted: bool,
+    ) -> ApiResult<Paginated<User>>;
+
+    async fn find_by_id(&self, id: &str) -> ApiResult<Option<User>>;
+
+    /// Like [`find_by_id`](Self::find_by_id), but never from a replica: use it
+    /// to read a user before writing it back, so a lagging replica can't hide
+    /// a recent change (or the user) from the write
+    async fn find_by_id_for_update(&self, id: &str) -> ApiResult<Option<User>> {
+        self.find_by_id(id).await
+    }
+
+    /// User whose email matches case-insensitively, including soft-deleted ones
+    async fn find_by_email(&self, email: &str) -> ApiResult<Option<User>>;
+
+    /// Store a new user; `Conflict` (field `email`) if the email is already
+    /// used. Postgres enforces this with the unique index on `users.email`.
+    async fn create(&self, user: User) -> ApiResult<User>;
+
+    /// Replace the stored user with the same id; `Conflict` if the new email
+    /// belongs to another user, `NotFound` if the user doesn't exist
+    async fn update(&self, user: User) -> ApiResult<User>;
+
+    /// Like [`update`](Self::update), but only while the stored user's
+    /// `updated_at` is still `expected_updated_at`; `Conflict` if it changed
+    /// since the caller read it. The check is part of the same write.
+    async fn update_if_unmodified(
+        &self,
+        user: User,
+        expected_updated_at: DateTime<Utc>,
+    ) -> ApiResult<User>;
+
+    /// User linked to an OAuth identity (provider, provider user id)
+    async fn find_by_identity(
+        &self,
+        provider: &str,
+        provider_user_id: &str,
+    ) -> ApiResult<Option<User>>;
+
+    /// Link an OAuth identity to a user; re-linking replaces the old user
+    async fn link_identity(
+        &self,
+        provider: &str,
+        provider_user_id: &str,
+        user_id: &str,
+    ) -> ApiResult<()>;
+
+    /// Permanently remove a user; returns whether one was removed
+    async fn delete(&self, id: &str) -> ApiResult<bool>;
+
+    /// Store all users or none of them (one transaction on Postgres)
+    async fn create_all(&self, users: Vec<User>) -> Result<Vec<User>, BatchCreateError>;
+
+    /// Stream all users (including soft-deleted) without loading them all
+    /// into memory; the stream ends after the first error
+    fn stream_all(&self) -> BoxStream<'static, ApiResult<User>>;
+}
+
+/// Failure of [`UserRepository::create_all`]; nothing was stored
+#[derive(Debug)]
+pub struct BatchCreateError {
+    /// Index of the user that could not be stored (0 when the failure is not
+    /// tied to a record, e.g. the transaction could not be started)
+    pub index: usize,
+    pub error: ApiError,
+}
+
+fn email_conflict() -> ApiError {
+    ApiError::Conflict {
+        message: "Email already exists".to_string(),
+        field: Some("email".to_string()),
+    }
+}
+
+fn user_not_found(id: &str) -> ApiError {
+    ApiError::not_found_resource(format!("User with id {} not found", id), "user")
+}
+
+fn user_modified() -> ApiError {
+    ApiError::Conflict {
+        message: "Resource has been modified since it was retrieved".to_string(),
+        field: None,
+    }
+}
+
+/// In-memory user repository (dùng cho development và tests)
+#[derive(Clone, Default)]
+pub struct InMemoryUserRepository {
+    users: Arc<RwLock<Vec<User>>>,
+    /// (provider, provider_user_id) -> user id
+    identities: Arc<RwLock<HashMap<(String, String), String>>>,
+}
+
+impl InMemoryUserRepository {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_users(users: Vec<User>) -> Self {
+        Self {
+            users: Arc::new(RwLock::new(users)),
+            ..Self::default()
+        }
+    }
+
+    fn read(&self) -> ApiResult<std::sync::RwLockReadGuard<'_, Vec<User>>> {
+        self.users
+            .read()
+            .map_err(|_| ApiError::internal("Failed to acquire read lock on users"))
+    }
+
+    fn write(&self) -> ApiResult<std::sync::RwLockWriteGuard<'_, Vec<User>>> {
+        self.users
+            .write()
+            .map_err(|_| ApiError::internal("Failed to acquire write lock on users"))
+    }
+}
+
+#[async_trait]
+impl UserRepository for InMemoryUserRepository {
+    async fn find_all(&self) -> ApiResult<Vec<User>> {
+        Ok(self.read()?.clone())
+    }
+
+    async fn find_page(
+        &self,
+        query: &ListQuery,
+        pagination: Pagination,
+        include_deleted: bool,
+    ) -> ApiResult<Paginated<User>> {
+        let users = UserService::visible(&self.read()?, include_deleted);
+        let users = UserService::apply_list_query(&users, query);
+        Ok(Paginated::from_slice(&users, pagination))
+    }
+
+    async fn find_by_id(&self, id: &str) -> ApiResult<Option<User>> {
+        Ok(self.read()?.iter().find(|u| u.id == id).cloned())
+    }
+
+    async fn find_by_email(&self, email: &str) -> ApiResult<Option<User>> {
+        Ok(self
+            .read()?
+            .iter()
+            .find(|u| u.email.eq_ignore_ascii_case(email))
+            .cloned())
+    }
+
+    async fn create(&self, user: User) -> ApiResult<User> {
+        let mut users = self.write()?;
+        if UserService::check_email_exists(&users, &user.email, None) {
+            return Err(email_conflict());
+        }
+        users.push(user.clone());
+        Ok(user)
+    }
+
+    async fn update(&self, user: User) -> ApiResult<User> {
+        let mut users = self.write()?;
+        if UserService::check_email_exists(&users, &user.email, Some(&user.id)) {
+            return Err(email_conflict());
+        }
+        let stored = users
+            .iter_mut()
+            .find(|u| u.id == user.id)
+            .ok_or_else(|| user_not_found(&user.id))?;
+        *stored = user.clone();
+        Ok(user)
+    }
+
+    async fn update_if_unmodified(
+        &self,
+        user: User,
+        expected_updated_at: DateTime<Utc>,
+    ) -> ApiResult<User> {
+        let mut users = self.write()?;
+        if UserService::check_email_exists(&users, &user.email, Some(&user.id)) {
+            return Err(email_conflict());
+        }
+        let stored = users
+            .iter_mut()
+            .find(|u| u.id == user.id)
+            .ok_or_else(|| user_not_found(&user.id))?;
+        if stored.updated_at != expected_updated_at {
+            return Err(user_modified());
+        }
+        *stored = user.clone();
+        Ok(user)
+    }
+
+    async fn find_by_identity(
+        &self,
+        provider: &str,
+        provider_user_id: &str,
+    ) -> ApiResult<Option<User>> {
+        let user_id = self
+            .identities
+            .read()
+            .map_err(|_| ApiError::internal("Failed to acquire read lock on OAuth identities"))?
+            .get(&(provider.to_string(), provider_user_id.to_string()))
+            .cloned();
+        match user_id {
+            Some(user_id) => self.find_by_id(&user_id).await,
+            None => Ok(None),
+        }
+    }
+
+    async fn link_identity(
+        &self,
+        provider: &str,
+        provider_user_id: &str,
+        user_id: &str,
+    ) -> ApiResult<()> {
+        self.identities
+            .write()
+            .map_err(|_| ApiError::internal("Failed to acquire write lock on OAuth identities"))?
+            .insert(
+                (provider.to_string(), provider_user_id.to_string()),
+                user_id.to_string(),
+            );
+        Ok(())
+    }
+
+    async fn delete(&self, id: &str) -> ApiResult<bool> {
+        let mut users = self.write()?;
+        let before = users.len();
+        users.retain(|u| u.id != id);
+        if let Ok(mut identities) = self.identities.write() {
+            identities.retain(|_, user_id| user_id != id);
+        }
+        Ok(users.len() < before)
+    }
+
+    async fn create_all(&self, new_users: Vec<User>) -> Result<Vec<User>, BatchCreateError> {
+        let mut users = self.write().map_err(|error| BatchCreateError { index: 0, error })?;
+
+        // Kiểm tra trùng email (với dữ liệu cũ và trong cùng batch) trước khi ghi
+        for (index, user) in new_users.iter().enumerate() {
+            let duplicate = UserService::check_email_exists(&users, &user.email, None)
+                || new_users[..index]
+                    .iter()
+                    .any(|u| u.email.eq_ignore_ascii_case(&user.email));
+            if duplicate {
+                return Err(BatchCreateError {
+                    index,
+                    error: email_conflict(),
+                });
+            }
+        }
+
+        users.extend(new_users.iter().cloned());
+        Ok(new_users)
+    }
+
+    fn stream_all(&self) -> BoxStream<'static, ApiResult<User>> {
+        match self.read() {
+            Ok(users) => stream::iter(users.clone().into_iter().map(Ok)).boxed(),
+            Err(e) => stream::once(async move { Err(e) }).boxed(),
+        }
+    }
+}
+
+/// User repository trên bảng `users` của Postgres
+///
+/// `find_all`, `find_by_id` and `stream_all` read from a replica when the
+/// router has any; everything else, including `find_by_id_for_update`, uses
+/// the primary. Queries are timed with the attached
+/// [`QueryTimer`](crate::database::QueryTimer), except the
+/// `stream_all` cursor whose duration depends on the client.
+#[cfg(feature = "database-postgres")]
+pub struct PostgresUserRepository {
+    pools: crate::database::PoolRouter,
+    timer: crate::database::QueryTimer,
+}
+
+#[cfg(feature = "database-postgres")]
+impl PostgresUserRepository {
+    pub fn new(pool: sqlx::PgPool) -> Self {
+        Self::with_router(crate::database::PoolRouter::primary_only(pool))
+    }
+
+    pub fn with_router(pools: crate::database::PoolRouter) -> Self {
+        Self {
+            pools,
+            timer: crate::database::QueryTimer::default(),
+        }
+    }
+
+    pub fn with_query_timer(mut self, timer: crate::database::QueryTimer) -> Self {
+        self.timer = timer;
+        self
+    }
+
+    fn from_row(row: &sqlx::postgres::PgRow) -> User {
+        use sqlx::Row;
+
+        User {
+            id: row.get::<uuid::Uuid, _>("id").to_string(),
+            name: row.get("name"),
+            email: row.get("email"),
+            // NULL = chưa biết tuổi (vd. user tạo từ OAuth)
+            age: row.get::<Option<i32>, _>("age").unwrap_or_default() as u32,
+            phone: row.get("phone"),
+            role: row.get("role"),
+            is_active: row.get("is_active"),
+            created_at: row.get("created_at"),
+            updated_at: row.get("updated_at"),
+            deleted_at: row.get("deleted_at"),
+        }
+    }
+
+    async fn insert<'e, E>(&self, executor: E, user: &User) -> ApiResult<User>
+    where
+        E: sqlx::PgExecutor<'e>,
+    {
+        let id = uuid::Uuid::parse_str(&user.id)
+            .map_err(|e| ApiError::bad_request(format!("Invalid user ID: {}", e)))?;
+
+        let sql = format!(
+            r#"
+            INSERT INTO users (id, name, email, age, phone, role, is_active, created_at, updated_at, deleted_at)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10)
+            RETURNING {}
+            "#,
+            USER_COLUMNS
+        );
+        let query = sqlx::query(&sql)
+            .bind(id)
+            .bind(&user.name)
+            .bind(&user.email)
+            .bind(age_column(user.age))
+            .bind(&user.phone)
+            .bind(&user.role)
+            .bind(user.is_active)
+            .bind(user.created_at)
+            .bind(user.updated_at)
+            .bind(user.deleted_at);
+        let row = self.timer.time("users.insert", query.fetch_one(executor)).await?;
+
+        Ok(Self::from_row(&row))
+    }
+
+    /// `UPDATE`, guarded by `updated_at = expected` when given; a guarded
+    /// update that matches no row is a conflict if the user still exists
+    async fn update_where(
+        &self,
+        user: User,
+        expected_updated_at: Option<DateTime<Utc>>,
+    ) -> ApiResult<User> {
+        let Ok(id) = uuid::Uuid::parse_str(&user.id) else {
+            return Err(user_not_found(&user.id));
+        };
+
+        let sql = format!(
+            r#"
+            UPDATE users
+            SET name = $2, email = $3, age = $4, phone = $5, role = $6, is_active = $7, deleted_at = $8
+            WHERE id = $1 AND ($9::timestamptz IS NULL OR updated_at = $9)
+            RETURNING {}
+            "#,
+            USER_COLUMNS
+        );
+        let query = sqlx::query(&sql)
+            .bind(id)
+            .bind(&user.name)
+            .bind(&user.email)
+            .bind(age_column(user.age))
+            .bind(&user.phone)
+            .bind(&user.role)
+            .bind(user.is_active)
+            .bind(user.deleted_at)
+            .bind(expected_updated_at);
+        let row = self
+            .timer
+            .time("users.update", query.fetch_optional(self.pools.write_pool()))
+            .await?;
+
+        match row {
+            Some(row) => Ok(Self::from_row(&row)),
+            None if expected_updated_at.is_some() => {
+                let exists = sqlx::query("SELECT 1 FROM users WHERE id = $1")
+                    .bind(id)
+                    .fetch_optional(self.pools.write_pool())
+                    .await?
+                    .is_some();
+                Err(if exists { user_modified() } else { user_not_found(&user.id) })
+            }
+            None => Err(user_not_found(&user.id)),
+        }
+    }
+}
+
+/// `age` 0 means unknown and is stored as NULL, which the column's
+/// `CHECK (age > 0 ...)` accepts
+#[cfg(feature = "database-postgres")]
+fn age_column(age: u32) -> Option<i32> {
+    (age > 0).then_some(age as i32)
+}
+
+#[cfg(feature = "database-postgres")]
+const USER_COLUMNS: &str =
+    "id, name, email, age, phone, role, is_active, created_at, updated_at, deleted_at";
+
+#[cfg(feature = "database-postgres")]
+const SELECT_ALL_USERS: &str = "SELECT id, name, email, age, phone, role, is_active, created_at, updated_at, deleted_at \
+     FROM users ORDER BY created_at";
+
+/// Số row đệm giữa cursor và response khi stream
+#[cfg(feature = "database-postgres")]
+const STREAM_BUFFER_ROWS: usize = 64;
+
+#[cfg(feature = "database-postgres")]
+#[async_trait]
+impl UserRepository for PostgresUserRepository {
+    async fn find_all(&self) -> ApiResult<Vec<User>> {
+        let sql = format!("SELECT {} FROM users ORDER BY created_at", USER_COLUMNS);
+        let rows = self
+            .timer
+            .time("users.find_all", sqlx::query(&sql).fetch_all(self.pools.read_pool()))
+            .await?;
+        Ok(rows.iter().map(Self::from_row).collect())
+    }
+
+    async fn find_page(
+        &self,
+        query: &ListQuery,
+        pagination: Pagination,
+        include_deleted: bool,
+    ) -> ApiResult<Paginated<User>> {
+        use sqlx::{Postgres, QueryBuilder, Row};
+
+        let mut count = QueryBuilder::<Postgres>::new("SELECT COUNT(*) FROM users WHERE (");
+        count.push_bind(include_deleted).push(" OR deleted_at IS NULL)");
+        query.push_filters(&mut count);
+        let total: i64 = self
+            .timer
+            .time("users.count_page", count.build().fetch_one(self.pools.read_pool()))
+            .await?
+            .get(0);
+
+        let sql = format!("SELECT {} FROM users WHERE (", USER_COLUMNS);
+        let mut page = QueryBuilder::<Postgres>::new(sql);
+        page.push_bind(include_deleted).push(" OR deleted_at IS NULL)");
+        query.push_sql(&mut page, "created_at, id");
+        page.push(" LIMIT ").push_bind(i64::from(pagination.per_page));
+        page.push(" OFFSET ").push_bind(pagination.offset() as i64);
+        let rows = self
+            .timer
+            .time("users.find_page", page.build().fetch_all(self.pools.read_pool()))
+            .await?;
+
+        Ok(Paginated::new(
+            rows.iter().map(Self::from_row).collect(),
+            total as usize,
+            pagination,
+        ))
+    }
+
+    async fn find_by_id(&self, id: &str) -> ApiResult<Option<User>> {
+        // Id không phải UUID thì chắc chắn không tồn tại
+        let Ok(id) = uuid::Uuid::parse_str(id) else {
+            return Ok(None);
+        };
+
+        let sql = format!("SELECT {} FROM users WHERE id = $1", USER_COLUMNS);
+        let query = sqlx::query(&sql).bind(id);
+        let row = self
+            .timer
+            .time("users.find_by_id", query.fetch_optional(self.pools.read_pool()))
+            .await?;
+        Ok(row.as_ref().map(Self::from_row))
+    }
+
+    async fn find_by_id_for_update(&self, id: &str) -> ApiResult<Option<User>> {
+        let Ok(id) = uuid::Uuid::parse_str(id) else {
+            return Ok(None);
+        };
+
+        let sql = format!("SELECT {} FROM users WHERE id = $1", USER_COLUMNS);
+        let query = sqlx::query(&sql).bind(id);
+        let row = self
+            .timer
+            .time("users.find_by_id_for_update", query.fetch_optional(self.pools.write_pool()))
+            .await?;
+        Ok(row.as_ref().map(Self::from_row))
+    }
+
+    async fn create(&self, user: User) -> ApiResult<User> {
+        self.insert(self.pools.write_pool(), &user).await
+    }
+
+    async fn find_by_email(&self, email: &str) -> ApiResult<Option<User>> {
+        let sql = format!("SELECT {} FROM users WHERE LOWER(email) = LOWER($1)", USER_COLUMNS);
+        let query = sqlx::query(&sql).bind(email);
+        // Primary: kết quả quyết định có tạo user mới hay không
+        let row = self
+            .timer
+            .time("users.find_by_email", query.fetch_optional(self.pools.write_pool()))
+            .await?;
+        Ok(row.as_ref().map(Self::from_row))
+    }
+
+    async fn update(&self, user: User) -> ApiResult<User> {
+        self.update_where(user, None).await
+    }
+
+    async fn update_if_unmodified(
+        &self,
+        user: User,
+        expected_updated_at: DateTime<Utc>,
+    ) -> ApiResult<User> {
+        self.update_where(user, Some(expected_updated_at)).await
+    }
+
+    async fn find_by_identity(
+        &self,
+        provider: &str,
+        provider_user_id: &str,
+    ) -> ApiResult<Option<User>> {
+        let sql = format!(
+            r#"
+            SELECT {} FROM users
+            WHERE id = (
+                SELECT user_id FROM oauth_identities
+                WHERE provider = $1 AND provider_user_id = $2
+            )
+            "#,
+            USER_COLUMNS
+        );
+        let query = sqlx::query(&sql).bind(provider).bind(provider_user_id);
+        let row = self
+            .timer
+            .time("users.find_by_identity", query.fetch_optional(self.pools.write_pool()))
+            .await?;
+        Ok(row.as_ref().map(Self::from_row))
+    }
+
+    async fn link_identity(
+        &self,
+        provider: &str,
+        provider_user_id: &str,
+        user_id: &str,
+    ) -> ApiResult<()> {
+        let user_id = uuid::Uuid::parse_str(user_id).map_err(|_| user_not_found(user_id))?;
+
+        let query = sqlx::query(
+            r#"
+            INSERT INTO oauth_identities (provider, provider_user_id, user_id)
+            VALUES ($1, $2, $3)
+            ON CONFLICT (provider, provider_user_id) DO UPDATE SET user_id = EXCLUDED.user_id
+            "#,
+        )
+        .bind(provider)
+        .bind(provider_user_id)
+        .bind(user_id);
+        self.timer
+            .time("oauth_identities.link", query.execute(self.pools.write_pool()))
+            .await?;
+        Ok(())
+    }
+
+    async fn delete(&self, id: &str) -> ApiResult<bool> {
+        let Ok(id) = uuid::Uuid::parse_str(id) else {
+            return Ok(false);
+        };
+
+        let query = sqlx::query("DELETE FROM users WHERE id = $1").bind(id);
+        let result = self
+            .timer
+            .time("users.delete", query.execute(self.pools.write_pool()))
+            .await?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    async fn create_all(&self, users: Vec<User>) -> Result<Vec<User>, BatchCreateError> {
+        let mut tx = self
+            .pools
+            .write_pool()
+            .begin()
+            .await
+            .map_err(|e| BatchCreateError { index: 0, error: e.into() })?;
+
+        let mut created = Vec::with_capacity(users.len());
+        for (index, user) in users.iter().enumerate() {
+            // Lỗi ở bất kỳ record nào: tx bị drop -> rollback
+            let user = self
+                .insert(&mut *tx, user)
+                .await
+                .map_err(|error| BatchCreateError { index, error })?;
+            created.push(user);
+        }
+
+        tx.commit()
+            .await
+            .map_err(|e| BatchCreateError { index: 0, error: e.into() })?;
+        Ok(created)
+    }
+
+    fn stream_all(&self) -> BoxStream<'static, ApiResult<User>> {
+        // Cursor chạy trong task riêng (sở hữu pool), đẩy row qua channel có
+        // giới hạn nên bộ nhớ không phụ thuộc số row
+        let pool = self.pools.read_pool().clone();
+        let (sender, receiver) = tokio::sync::mpsc::channel(STREAM_BUFFER_ROWS);
+
+        tokio::spawn(async move {
+            let mut rows = sqlx::query(SELECT_ALL_USERS).fetch(&pool);
+            while let Some(row) = rows.next().await {
+                let item = row.map(|row| Self::from_row(&row)).map_err(ApiError::from);
+                let failed = item.is_err();
+                // Receiver bị drop nghĩa là client đã ngắt kết nối
+                if sender.send(item).await.is_err() || failed {
+                    break;
+                }
+            }
+        });
+
+        stream::unfold(receiver, |mut receiver| async move {
+            receiver.recv().await.map(|item| (item, receiver))
+        })
+        .boxed()
+    }
+}
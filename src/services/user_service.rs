@@ -1,9 +1,17 @@
-use crate::errors::ApiResult;
-use crate::models::{User, CreateUserRequest, UpdateUserRequest};
-use crate::utils::Validator;
+use crate::errors::{ApiError, ApiResult};
+use crate::models::{
+    User, CreateUserRequest, UpdateUserRequest, FilterValue, ListQuery, SortDirection,
+};
+use std::cmp::Ordering;
+use crate::utils::{Validator, DEFAULT_PHONE_REGION};
 use uuid::Uuid;
 use chrono::Utc;
 
+#[cfg(feature = "auth-oauth2")]
+use crate::auth::oauth2::OAuth2UserInfo;
+#[cfg(feature = "auth-oauth2")]
+use crate::services::UserRepository;
+
 /// Service layer cho User business logic
 pub struct UserService;
 
@@ -15,16 +23,23 @@ impl UserService {
         Validator::validate_length("name", &req.name, 2, 100)?;
         Validator::validate_email(&req.email)?;
         Validator::validate_range("age", req.age, 1, 150)?;
+        let phone = req
+            .phone
+            .as_deref()
+            .map(|phone| Validator::validate_phone(phone, DEFAULT_PHONE_REGION))
+            .transpose()?;
 
         Ok(User {
             id: Uuid::new_v4().to_string(),
             name: req.name.clone(),
             email: req.email.clone(),
             age: req.age,
+            phone,
             role: "user".to_string(),
             is_active: true,
             created_at: Utc::now(),
             updated_at: Utc::now(),
+            deleted_at: None,
         })
     }
 
@@ -46,14 +61,150 @@ impl UserService {
             user.age = age;
         }
 
+        if let Some(phone) = &req.phone {
+            user.phone = Some(Validator::validate_phone(phone, DEFAULT_PHONE_REGION)?);
+        }
+
         user.updated_at = Utc::now();
         Ok(())
     }
 
-    /// Check email đã tồn tại chưa
+    /// Check email đã tồn tại chưa (không phân biệt hoa thường)
     pub fn check_email_exists(users: &[User], email: &str, exclude_id: Option<&str>) -> bool {
         users.iter().any(|u| {
-            u.email == email && exclude_id.map_or(true, |id| u.id != id)
+            u.email.eq_ignore_ascii_case(email) && exclude_id.map_or(true, |id| u.id != id)
         })
     }
+
+    /// Soft delete: đánh dấu `deleted_at` thay vì xóa record
+    pub fn soft_delete(user: &mut User) {
+        let now = Utc::now();
+        user.deleted_at = Some(now);
+        user.updated_at = now;
+    }
+
+    /// Khôi phục user đã soft delete
+    pub fn restore(user: &mut User) {
+        user.deleted_at = None;
+        user.updated_at = Utc::now();
+    }
+
+    /// Users hiển thị: bỏ qua user đã xóa trừ khi `include_deleted`
+    pub fn visible(users: &[User], include_deleted: bool) -> Vec<User> {
+        users
+            .iter()
+            .filter(|u| include_deleted || !u.is_deleted())
+            .cloned()
+            .collect()
+    }
+
+    /// Lọc và sắp xếp danh sách user theo ListQuery
+    pub fn apply_list_query(users: &[User], query: &ListQuery) -> Vec<User> {
+        let mut result: Vec<User> = users
+            .iter()
+            .filter(|u| {
+                query
+                    .filters
+                    .iter()
+                    .all(|f| Self::matches(u, f.field, &f.value))
+            })
+            .cloned()
+            .collect();
+
+        result.sort_by(|a, b| {
+            query.sort.iter().fold(Ordering::Equal, |ordering, sort| {
+                ordering.then_with(|| {
+                    let ordering = Self::compare_field(a, b, sort.field);
+                    match sort.direction {
+                        SortDirection::Asc => ordering,
+                        SortDirection::Desc => ordering.reverse(),
+                    }
+                })
+            })
+        });
+
+        result
+    }
+
+    /// Same comparison as the Postgres repository: by value, not by text, and
+    /// timestamps at Postgres' microsecond precision
+    fn matches(user: &User, field: &str, value: &FilterValue) -> bool {
+        match (field, value) {
+            ("name", FilterValue::Text(v)) => &user.name == v,
+            ("email", FilterValue::Text(v)) => &user.email == v,
+            ("age", FilterValue::Integer(v)) => i64::from(user.age) == *v,
+            ("role", FilterValue::Text(v)) => &user.role == v,
+            ("created_at", FilterValue::Timestamp(v)) => {
+                user.created_at.timestamp_micros() == v.timestamp_micros()
+            }
+            _ => false,
+        }
+    }
+
+    fn compare_field(a: &User, b: &User, field: &str) -> Ordering {
+        match field {
+            "name" => a.name.cmp(&b.name),
+            "email" => a.email.cmp(&b.email),
+            "age" => a.age.cmp(&b.age),
+            "role" => a.role.cmp(&b.role),
+            "created_at" => a.created_at.cmp(&b.created_at),
+            _ => Ordering::Equal,
+        }
+    }
+
+    /// Tạo hoặc cập nhật user từ OAuth login, keyed by (provider, provider user id)
+    ///
+    /// A new identity is linked to an existing account with the same email
+    /// only when `link_by_verified_email` is set and the provider asserts the
+    /// email is verified; otherwise an email collision is a conflict.
+    #[cfg(feature = "auth-oauth2")]
+    pub async fn upsert_oauth_user(
+        users: &dyn UserRepository,
+        info: &OAuth2UserInfo,
+        link_by_verified_email: bool,
+    ) -> ApiResult<User> {
+        // Identity đã liên kết: cập nhật profile
+        if let Some(mut user) = users.find_by_identity(&info.provider, &info.id).await? {
+            if let Some(name) = &info.name {
+                user.name = name.clone();
+            }
+            user.updated_at = Utc::now();
+            return users.update(user).await;
+        }
+
+        let email = info
+            .email
+            .clone()
+            .unwrap_or_else(|| format!("{}+{}@oauth.invalid", info.provider, info.id));
+
+        if let Some(existing) = users.find_by_email(&email).await? {
+            if link_by_verified_email && info.email_verified && info.email.is_some() {
+                users.link_identity(&info.provider, &info.id, &existing.id).await?;
+                return Ok(existing);
+            }
+            return Err(ApiError::Conflict {
+                message: "An account with this email already exists".to_string(),
+                field: Some("email".to_string()),
+            });
+        }
+
+        let user = users
+            .create(User {
+                id: Uuid::new_v4().to_string(),
+                name: info.name.clone().unwrap_or_else(|| email.clone()),
+                email,
+                // Provider không trả về tuổi; repository lưu 0 thành NULL
+                age: 0,
+                phone: None,
+                role: "user".to_string(),
+                is_active: true,
+                created_at: Utc::now(),
+                updated_at: Utc::now(),
+                deleted_at: None,
+            })
+            .await?;
+
+        users.link_identity(&info.provider, &info.id, &user.id).await?;
+        Ok(user)
+    }
 }
@@ -1,71 +1,134 @@
-use std::sync::Mutex;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use crate::features::{ABTestManager, FeatureFlagManager};
+use crate::health::{HealthCheckable, Watchdog};
 use crate::models::User;
+use crate::multitenancy::TenantManager;
+use crate::security::AuditLogger;
+use crate::services::{InMemoryUserRepository, StorageService, UserRepository};
 
 #[cfg(feature = "database-postgres")]
 use sqlx::PgPool;
 
+#[cfg(feature = "database-postgres")]
+use crate::database::Database;
+
 #[cfg(feature = "cache-redis")]
 use crate::cache::CacheManager;
 
+#[cfg(feature = "observability-metrics")]
+use crate::metrics::MetricsCollector;
+
 pub struct AppState {
-    pub users: Mutex<Vec<User>>,
+    pub users: Arc<dyn UserRepository>,
 
     #[cfg(feature = "database-postgres")]
     pub db_pool: Option<PgPool>,
 
     #[cfg(feature = "cache-redis")]
     pub cache_manager: Option<CacheManager>,
+
+    #[cfg(feature = "observability-metrics")]
+    pub metrics: Option<Arc<MetricsCollector>>,
+
+    pub audit_logger: Option<Arc<AuditLogger>>,
+
+    pub feature_flags: Option<FeatureFlagManager>,
+
+    pub ab_tests: Option<ABTestManager>,
+
+    pub tenant_manager: Option<Arc<TenantManager>>,
+
+    /// Dependencies probed by the readiness check
+    pub health_checks: Vec<Arc<dyn HealthCheckable>>,
+
+    /// When the process started serving, for the health endpoint's uptime
+    pub start_time: Instant,
+
+    /// Runtime watchdog backing the liveness probe
+    pub watchdog: Option<Arc<Watchdog>>,
+
+    /// Object storage for `POST /uploads`
+    pub storage: Option<Arc<dyn StorageService>>,
 }
 
 impl AppState {
     pub fn new() -> Self {
         Self {
-            users: Mutex::new(Vec::new()),
+            users: Arc::new(InMemoryUserRepository::new()),
             #[cfg(feature = "database-postgres")]
             db_pool: None,
             #[cfg(feature = "cache-redis")]
             cache_manager: None,
+            #[cfg(feature = "observability-metrics")]
+            metrics: None,
+            audit_logger: None,
+            feature_flags: None,
+            ab_tests: None,
+            tenant_manager: None,
+            health_checks: Vec::new(),
+            start_time: Instant::now(),
+            watchdog: None,
+            storage: None,
         }
     }
 
+    pub fn uptime(&self) -> Duration {
+        self.start_time.elapsed()
+    }
+
+    /// Build state with only the subsystems that are attached
+    pub fn builder() -> AppStateBuilder {
+        AppStateBuilder::default()
+    }
+
     pub fn with_users(users: Vec<User>) -> Self {
+        Self::with_user_repository(Arc::new(InMemoryUserRepository::with_users(users)))
+    }
+
+    pub fn with_user_repository(users: Arc<dyn UserRepository>) -> Self {
         Self {
-            users: Mutex::new(users),
-            #[cfg(feature = "database-postgres")]
-            db_pool: None,
-            #[cfg(feature = "cache-redis")]
-            cache_manager: None,
+            users,
+            ..Self::new()
         }
     }
 
     #[cfg(feature = "database-postgres")]
     pub fn with_db_pool(db_pool: PgPool) -> Self {
         Self {
-            users: Mutex::new(Vec::new()),
-            db_pool: Some(db_pool),
-            #[cfg(feature = "cache-redis")]
-            cache_manager: None,
+            db_pool: Some(db_pool.clone()),
+            health_checks: vec![Arc::new(Database::from_pool(db_pool))],
+            ..Self::new()
         }
     }
 
     #[cfg(feature = "cache-redis")]
     pub fn with_cache(cache_manager: CacheManager) -> Self {
         Self {
-            users: Mutex::new(Vec::new()),
-            #[cfg(feature = "database-postgres")]
-            db_pool: None,
-            cache_manager: Some(cache_manager),
+            cache_manager: Some(cache_manager.clone()),
+            health_checks: vec![Arc::new(cache_manager)],
+            ..Self::new()
         }
     }
 
     #[cfg(all(feature = "database-postgres", feature = "cache-redis"))]
     pub fn with_all(db_pool: PgPool, cache_manager: CacheManager) -> Self {
         Self {
-            users: Mutex::new(Vec::new()),
-            db_pool: Some(db_pool),
-            cache_manager: Some(cache_manager),
+            db_pool: Some(db_pool.clone()),
+            cache_manager: Some(cache_manager.clone()),
+            health_checks: vec![
+                Arc::new(Database::from_pool(db_pool)),
+                Arc::new(cache_manager),
+            ],
+            ..Self::new()
         }
     }
+
+    /// Register an additional dependency for the readiness check
+    pub fn with_health_check(mut self, check: Arc<dyn HealthCheckable>) -> Self {
+        self.health_checks.push(check);
+        self
+    }
 }
 
 impl Default for AppState {
@@ -73,3 +136,137 @@ impl Default for AppState {
         Self::new()
     }
 }
+
+/// Builder cho `AppState`; subsystem nào không gắn vào sẽ là `None`
+///
+/// Database và cache không được gắn vẫn xuất hiện trong readiness check với
+/// trạng thái `not_configured`.
+#[derive(Default)]
+pub struct AppStateBuilder {
+    users: Option<Arc<dyn UserRepository>>,
+    #[cfg(feature = "database-postgres")]
+    database: Option<Database>,
+    #[cfg(feature = "cache-redis")]
+    cache_manager: Option<CacheManager>,
+    #[cfg(feature = "observability-metrics")]
+    metrics: Option<Arc<MetricsCollector>>,
+    audit_logger: Option<Arc<AuditLogger>>,
+    feature_flags: Option<FeatureFlagManager>,
+    ab_tests: Option<ABTestManager>,
+    tenant_manager: Option<Arc<TenantManager>>,
+    health_checks: Vec<Arc<dyn HealthCheckable>>,
+    watchdog: Option<Arc<Watchdog>>,
+    storage: Option<Arc<dyn StorageService>>,
+}
+
+impl AppStateBuilder {
+    /// Seed an in-memory user repository
+    pub fn with_users(self, users: Vec<User>) -> Self {
+        self.with_user_repository(Arc::new(InMemoryUserRepository::with_users(users)))
+    }
+
+    pub fn with_user_repository(mut self, users: Arc<dyn UserRepository>) -> Self {
+        self.users = Some(users);
+        self
+    }
+
+    #[cfg(feature = "database-postgres")]
+    pub fn with_database(mut self, database: Database) -> Self {
+        self.database = Some(database);
+        self
+    }
+
+    #[cfg(feature = "cache-redis")]
+    pub fn with_cache(mut self, cache_manager: CacheManager) -> Self {
+        self.cache_manager = Some(cache_manager);
+        self
+    }
+
+    #[cfg(feature = "observability-metrics")]
+    pub fn with_metrics(mut self, metrics: Arc<MetricsCollector>) -> Self {
+        self.metrics = Some(metrics);
+        self
+    }
+
+    pub fn with_audit_logger(mut self, audit_logger: Arc<AuditLogger>) -> Self {
+        self.audit_logger = Some(audit_logger);
+        self
+    }
+
+    pub fn with_feature_flags(mut self, feature_flags: FeatureFlagManager) -> Self {
+        self.feature_flags = Some(feature_flags);
+        self
+    }
+
+    pub fn with_ab_tests(mut self, ab_tests: ABTestManager) -> Self {
+        self.ab_tests = Some(ab_tests);
+        self
+    }
+
+    pub fn with_tenant_manager(mut self, tenant_manager: Arc<TenantManager>) -> Self {
+        self.tenant_manager = Some(tenant_manager);
+        self
+    }
+
+    /// Register an additional dependency for the readiness check
+    pub fn with_health_check(mut self, check: Arc<dyn HealthCheckable>) -> Self {
+        self.health_checks.push(check);
+        self
+    }
+
+    /// Fail the liveness probe when this watchdog goes stale
+    pub fn with_watchdog(mut self, watchdog: Arc<Watchdog>) -> Self {
+        self.watchdog = Some(watchdog);
+        self
+    }
+
+    pub fn with_storage(mut self, storage: Arc<dyn StorageService>) -> Self {
+        self.storage = Some(storage);
+        self
+    }
+
+    pub fn build(self) -> AppState {
+        let mut health_checks: Vec<Arc<dyn HealthCheckable>> = Vec::new();
+
+        #[cfg(feature = "database-postgres")]
+        let db_pool = match self.database {
+            Some(database) => {
+                let pool = database.pool().clone();
+                health_checks.push(Arc::new(database));
+                Some(pool)
+            }
+            None => {
+                health_checks.push(Arc::new(crate::health::NotConfigured::new("database")));
+                None
+            }
+        };
+
+        #[cfg(feature = "cache-redis")]
+        match &self.cache_manager {
+            Some(cache_manager) => health_checks.push(Arc::new(cache_manager.clone())),
+            None => health_checks.push(Arc::new(crate::health::NotConfigured::new("cache"))),
+        }
+
+        health_checks.extend(self.health_checks);
+
+        AppState {
+            users: self
+                .users
+                .unwrap_or_else(|| Arc::new(InMemoryUserRepository::new())),
+            #[cfg(feature = "database-postgres")]
+            db_pool,
+            #[cfg(feature = "cache-redis")]
+            cache_manager: self.cache_manager,
+            #[cfg(feature = "observability-metrics")]
+            metrics: self.metrics,
+            audit_logger: self.audit_logger,
+            feature_flags: self.feature_flags,
+            ab_tests: self.ab_tests,
+            tenant_manager: self.tenant_manager,
+            health_checks,
+            start_time: Instant::now(),
+            watchdog: self.watchdog,
+            storage: self.storage,
+        }
+    }
+}
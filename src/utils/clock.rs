@@ -0,0 +1,56 @@
+use chrono::{DateTime, Duration, Utc};
+use std::sync::{Arc, RwLock};
+
+/// Source of the current time, injectable so time-dependent logic can be
+/// tested without sleeping
+pub trait Clock: Send + Sync {
+    fn now(&self) -> DateTime<Utc>;
+}
+
+/// Wall-clock time
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> DateTime<Utc> {
+        Utc::now()
+    }
+}
+
+/// Manually controlled clock for tests; clones share the same time
+#[derive(Debug, Clone)]
+pub struct MockClock {
+    now: Arc<RwLock<DateTime<Utc>>>,
+}
+
+impl MockClock {
+    pub fn new(start: DateTime<Utc>) -> Self {
+        Self {
+            now: Arc::new(RwLock::new(start)),
+        }
+    }
+
+    pub fn advance(&self, duration: Duration) {
+        if let Ok(mut now) = self.now.write() {
+            *now += duration;
+        }
+    }
+
+    pub fn set(&self, time: DateTime<Utc>) {
+        if let Ok(mut now) = self.now.write() {
+            *now = time;
+        }
+    }
+}
+
+impl Default for MockClock {
+    fn default() -> Self {
+        Self::new(Utc::now())
+    }
+}
+
+impl Clock for MockClock {
+    fn now(&self) -> DateTime<Utc> {
+        self.now.read().map(|now| *now).unwrap_or_else(|_| Utc::now())
+    }
+}
@@ -0,0 +1,170 @@
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+
+use crate::errors::ApiError;
+
+/// Byte đứng đầu payload MessagePack
+pub const MESSAGEPACK_TAG: u8 = 0x02;
+
+/// Byte đứng đầu payload bincode
+pub const BINCODE_TAG: u8 = 0x03;
+
+/// Serialization of cache values and message payloads
+///
+/// Encoded bytes start with the format tag, except JSON which stays untagged
+/// so values written before codecs existed still decode. JSON never starts
+/// with a control byte, and neither tag collides with the cache's gzip
+/// marker, so payloads in different formats can share a store.
+pub trait Codec {
+    fn format(&self) -> PayloadFormat;
+
+    fn encode<T: Serialize + ?Sized>(&self, value: &T) -> Result<Vec<u8>, ApiError>;
+
+    /// Fails if `bytes` were written in another format; use [`decode_any`] when
+    /// the format is not known in advance
+    fn decode<T: DeserializeOwned>(&self, bytes: &[u8]) -> Result<T, ApiError>;
+}
+
+/// Untagged JSON (default)
+#[derive(Debug, Clone, Copy, Default)]
+pub struct JsonCodec;
+
+impl Codec for JsonCodec {
+    fn format(&self) -> PayloadFormat {
+        PayloadFormat::Json
+    }
+
+    fn encode<T: Serialize + ?Sized>(&self, value: &T) -> Result<Vec<u8>, ApiError> {
+        serde_json::to_vec(value).map_err(|e| ApiError::internal(format!("JSON encode error: {}", e)))
+    }
+
+    fn decode<T: DeserializeOwned>(&self, bytes: &[u8]) -> Result<T, ApiError> {
+        serde_json::from_slice(bytes).map_err(|e| ApiError::internal(format!("JSON decode error: {}", e)))
+    }
+}
+
+/// MessagePack with field names, so struct changes stay compatible like JSON
+#[derive(Debug, Clone, Copy, Default)]
+pub struct MessagePackCodec;
+
+impl Codec for MessagePackCodec {
+    fn format(&self) -> PayloadFormat {
+        PayloadFormat::MessagePack
+    }
+
+    fn encode<T: Serialize + ?Sized>(&self, value: &T) -> Result<Vec<u8>, ApiError> {
+        let mut bytes = vec![MESSAGEPACK_TAG];
+        rmp_serde::encode::write_named(&mut bytes, value)
+            .map_err(|e| ApiError::internal(format!("MessagePack encode error: {}", e)))?;
+        Ok(bytes)
+    }
+
+    fn decode<T: DeserializeOwned>(&self, bytes: &[u8]) -> Result<T, ApiError> {
+        rmp_serde::from_slice(strip_tag(bytes, MESSAGEPACK_TAG, "MessagePack")?)
+            .map_err(|e| ApiError::internal(format!("MessagePack decode error: {}", e)))
+    }
+}
+
+/// Bincode: smallest and fastest, but positional, so reader and writer must
+/// share the exact type (no `serde_json::Value` or untagged enums)
+#[derive(Debug, Clone, Copy, Default)]
+pub struct BincodeCodec;
+
+impl Codec for BincodeCodec {
+    fn format(&self) -> PayloadFormat {
+        PayloadFormat::Bincode
+    }
+
+    fn encode<T: Serialize + ?Sized>(&self, value: &T) -> Result<Vec<u8>, ApiError> {
+        let mut bytes = vec![BINCODE_TAG];
+        bincode::serialize_into(&mut bytes, value)
+            .map_err(|e| ApiError::internal(format!("Bincode encode error: {}", e)))?;
+        Ok(bytes)
+    }
+
+    fn decode<T: DeserializeOwned>(&self, bytes: &[u8]) -> Result<T, ApiError> {
+        bincode::deserialize(strip_tag(bytes, BINCODE_TAG, "Bincode")?)
+            .map_err(|e| ApiError::internal(format!("Bincode decode error: {}", e)))
+    }
+}
+
+fn strip_tag<'a>(bytes: &'a [u8], tag: u8, name: &str) -> Result<&'a [u8], ApiError> {
+    match bytes.split_first() {
+        Some((&first, rest)) if first == tag => Ok(rest),
+        _ => Err(ApiError::internal(format!("Payload is not tagged as {}", name))),
+    }
+}
+
+/// Codec được chọn lúc runtime (config, header)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PayloadFormat {
+    #[default]
+    Json,
+    MessagePack,
+    Bincode,
+}
+
+impl PayloadFormat {
+    /// Format of encoded bytes, from their tag
+    pub fn detect(bytes: &[u8]) -> Self {
+        match bytes.first() {
+            Some(&MESSAGEPACK_TAG) => Self::MessagePack,
+            Some(&BINCODE_TAG) => Self::Bincode,
+            _ => Self::Json,
+        }
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Json => "json",
+            Self::MessagePack => "msgpack",
+            Self::Bincode => "bincode",
+        }
+    }
+}
+
+impl std::str::FromStr for PayloadFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "json" => Ok(Self::Json),
+            "msgpack" | "messagepack" | "message_pack" => Ok(Self::MessagePack),
+            "bincode" => Ok(Self::Bincode),
+            other => Err(format!("Unknown payload format: {}", other)),
+        }
+    }
+}
+
+impl std::fmt::Display for PayloadFormat {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl Codec for PayloadFormat {
+    fn format(&self) -> PayloadFormat {
+        *self
+    }
+
+    fn encode<T: Serialize + ?Sized>(&self, value: &T) -> Result<Vec<u8>, ApiError> {
+        match self {
+            Self::Json => JsonCodec.encode(value),
+            Self::MessagePack => MessagePackCodec.encode(value),
+            Self::Bincode => BincodeCodec.encode(value),
+        }
+    }
+
+    fn decode<T: DeserializeOwned>(&self, bytes: &[u8]) -> Result<T, ApiError> {
+        match self {
+            Self::Json => JsonCodec.decode(bytes),
+            Self::MessagePack => MessagePackCodec.decode(bytes),
+            Self::Bincode => BincodeCodec.decode(bytes),
+        }
+    }
+}
+
+/// Decode bytes written by any codec, choosing it from the tag
+pub fn decode_any<T: DeserializeOwned>(bytes: &[u8]) -> Result<T, ApiError> {
+    PayloadFormat::detect(bytes).decode(bytes)
+}
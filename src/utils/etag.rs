@@ -0,0 +1,51 @@
+use actix_web::{http::header, HttpRequest};
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+
+use crate::errors::ApiError;
+
+/// Compute a weak ETag (`W/"..."`) from the JSON serialization of a value
+pub fn weak_etag<T: Serialize>(value: &T) -> Result<String, ApiError> {
+    let bytes = serde_json::to_vec(value)
+        .map_err(|e| ApiError::internal(format!("Failed to serialize value for ETag: {}", e)))?;
+    let digest = Sha256::digest(&bytes);
+    Ok(format!("W/\"{}\"", hex::encode(&digest[..16])))
+}
+
+/// Whether `If-None-Match` matches the current ETag, i.e. the client's
+/// cached copy is still fresh and a `304 Not Modified` can be sent
+pub fn if_none_match(req: &HttpRequest, etag: &str) -> bool {
+    header_matches(req, header::IF_NONE_MATCH, etag).unwrap_or(false)
+}
+
+/// Check `If-Match` for optimistic concurrency
+///
+/// Passes when the header is absent or lists the current ETag; otherwise the
+/// client is working from a stale copy and a conflict is returned. ETags are
+/// compared weakly since they are all weak.
+pub fn check_if_match(req: &HttpRequest, etag: &str) -> Result<(), ApiError> {
+    match header_matches(req, header::IF_MATCH, etag) {
+        None | Some(true) => Ok(()),
+        Some(false) => Err(ApiError::Conflict {
+            message: "Resource has been modified since it was retrieved".to_string(),
+            field: None,
+        }),
+    }
+}
+
+/// `None` when the header is missing, otherwise whether any listed tag matches
+fn header_matches(req: &HttpRequest, name: header::HeaderName, etag: &str) -> Option<bool> {
+    let value = req.headers().get(name)?.to_str().ok()?;
+    let current = opaque_tag(etag);
+
+    Some(
+        value
+            .split(',')
+            .map(str::trim)
+            .any(|tag| tag == "*" || opaque_tag(tag) == current),
+    )
+}
+
+fn opaque_tag(tag: &str) -> &str {
+    tag.strip_prefix("W/").unwrap_or(tag)
+}
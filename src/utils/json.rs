@@ -0,0 +1,173 @@
+use actix_web::dev::Payload;
+use actix_web::error::{JsonPayloadError, PayloadError};
+use actix_web::web::{Json, JsonConfig, PayloadConfig};
+use actix_web::{Error, FromRequest, HttpRequest};
+use futures_util::future::LocalBoxFuture;
+use serde::de::DeserializeOwned;
+use std::ops::{Deref, DerefMut};
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use crate::errors::ApiError;
+use crate::security::Redactor;
+
+/// `JsonConfig` with a body limit whose errors use our `ErrorResponse` format
+pub fn json_config(max_body_bytes: usize) -> JsonConfig {
+    JsonConfig::default()
+        .limit(max_body_bytes)
+        .error_handler(move |err, _req| json_error(err, max_body_bytes).into())
+}
+
+/// `PayloadConfig` for `Bytes`/`String` extractors with the same limit
+pub fn payload_config(max_body_bytes: usize) -> PayloadConfig {
+    PayloadConfig::new(max_body_bytes)
+}
+
+/// Map Actix JSON extractor errors to `ApiError`
+pub fn json_error(err: JsonPayloadError, max_body_bytes: usize) -> ApiError {
+    match err {
+        JsonPayloadError::Overflow { .. }
+        | JsonPayloadError::OverflowKnownLength { .. }
+        | JsonPayloadError::Payload(PayloadError::Overflow) => ApiError::payload_too_large(max_body_bytes),
+        JsonPayloadError::ContentType => {
+            ApiError::bad_request("Content-Type must be application/json")
+        }
+        JsonPayloadError::Deserialize(e) => ApiError::bad_request(format!("Invalid JSON body: {}", e)),
+        other => ApiError::bad_request(format!("Invalid request body: {}", other)),
+    }
+}
+
+/// JSON body extractor that reports the path of the offending field
+///
+/// The body is first read as a `serde_json::Value` through the regular
+/// `Json` extractor (so `JsonConfig` limits and error handling apply), then
+/// converted to `T` while tracking the field path, e.g. `items[0].age`.
+#[derive(Debug)]
+pub struct ApiJson<T>(pub T);
+
+impl<T> ApiJson<T> {
+    pub fn into_inner(self) -> T {
+        self.0
+    }
+}
+
+impl<T> Deref for ApiJson<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.0
+    }
+}
+
+impl<T> DerefMut for ApiJson<T> {
+    fn deref_mut(&mut self) -> &mut T {
+        &mut self.0
+    }
+}
+
+impl<T: DeserializeOwned + 'static> FromRequest for ApiJson<T> {
+    type Error = Error;
+    type Future = LocalBoxFuture<'static, Result<Self, Self::Error>>;
+
+    fn from_request(req: &HttpRequest, payload: &mut Payload) -> Self::Future {
+        let value = Json::<serde_json::Value>::from_request(req, payload);
+
+        Box::pin(async move {
+            let value = value.await?.into_inner();
+            serde_path_to_error::deserialize(value)
+                .map(ApiJson)
+                .map_err(|e| deserialize_error(e).into())
+        })
+    }
+}
+
+/// `ApiError::BadRequest` naming the field that failed to deserialize
+///
+/// serde messages can quote the rejected value, so for a sensitive field
+/// (see [`Redactor`]) only the path is reported.
+pub fn deserialize_error(err: serde_path_to_error::Error<serde_json::Error>) -> ApiError {
+    let path = err.path().to_string();
+    let inner = err.into_inner();
+
+    if Redactor::default().is_sensitive(&path) {
+        return ApiError::bad_request(format!("Invalid value for `{}`", path));
+    }
+
+    let message = if path == "." {
+        format!("Invalid JSON body: {}", inner)
+    } else {
+        format!("Invalid value for `{}`: {}", path, inner)
+    };
+
+    ApiError::BadRequest {
+        message,
+        source: Some(Box::new(inner)),
+    }
+}
+
+static IDS_AS_STRINGS: AtomicBool = AtomicBool::new(false);
+
+/// Serialize fields using [`id_as_string`] as JSON strings (process-wide)
+///
+/// Set once at startup from `JSON_IDS_AS_STRINGS`; JavaScript clients lose
+/// precision on integers above 2^53.
+pub fn set_ids_as_strings(enabled: bool) {
+    IDS_AS_STRINGS.store(enabled, Ordering::Relaxed);
+}
+
+pub fn ids_as_strings() -> bool {
+    IDS_AS_STRINGS.load(Ordering::Relaxed)
+}
+
+/// serde `with` helper for ids: `#[serde(with = "id_as_string")]`
+///
+/// Works on integer ids and on `String` ids such as `User.id`. When
+/// [`ids_as_strings`] is on every id is written as a string
+/// (`"9007199254740993"`); otherwise integer ids are plain numbers and other
+/// ids (UUIDs) stay strings. Reading accepts both forms regardless of the
+/// setting.
+pub mod id_as_string {
+    use serde::{de, Deserialize, Deserializer, Serializer};
+    use std::fmt::Display;
+    use std::str::FromStr;
+
+    pub fn serialize<T, S>(value: &T, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        T: Display,
+        S: Serializer,
+    {
+        let id = value.to_string();
+        if super::ids_as_strings() {
+            return serializer.serialize_str(&id);
+        }
+
+        if let Ok(id) = id.parse::<i64>() {
+            serializer.serialize_i64(id)
+        } else if let Ok(id) = id.parse::<u64>() {
+            serializer.serialize_u64(id)
+        } else {
+            serializer.serialize_str(&id)
+        }
+    }
+
+    pub fn deserialize<'de, T, D>(deserializer: D) -> Result<T, D::Error>
+    where
+        T: FromStr,
+        T::Err: Display,
+        D: Deserializer<'de>,
+    {
+        #[derive(Deserialize)]
+        #[serde(untagged)]
+        enum NumberOrString {
+            Signed(i64),
+            Unsigned(u64),
+            String(String),
+        }
+
+        let id = match NumberOrString::deserialize(deserializer)? {
+            NumberOrString::Signed(value) => value.to_string(),
+            NumberOrString::Unsigned(value) => value.to_string(),
+            NumberOrString::String(value) => value.trim().to_string(),
+        };
+        id.parse().map_err(de::Error::custom)
+    }
+}
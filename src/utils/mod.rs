@@ -1,5 +1,26 @@
 pub mod validator;
 pub mod performance;
+pub mod etag;
+pub mod json;
+pub mod negotiation;
+pub mod clock;
+pub mod retry;
+pub mod timeout;
+pub mod codec;
+pub mod pagination;
 
-pub use validator::Validator;
+pub use validator::{JsonSchema, Validator, DEFAULT_PHONE_REGION};
 pub use performance::{Timer, ParallelProcessor, BatchProcessor, PoolConfig};
+pub use etag::{check_if_match, if_none_match, weak_etag};
+pub use json::{
+    id_as_string, ids_as_strings, json_config, json_error, payload_config, set_ids_as_strings, ApiJson,
+};
+pub use negotiation::{csv_field, CsvRecord, ResponseFormat, TOTAL_COUNT_HEADER};
+pub use clock::{Clock, MockClock, SystemClock};
+pub use retry::{is_transient, retry_with_backoff, RetryPolicy};
+pub use timeout::with_timeout;
+pub use codec::{decode_any, BincodeCodec, Codec, JsonCodec, MessagePackCodec, PayloadFormat};
+pub use pagination::{decode_cursor, encode_cursor, Cursor, CursorScope, DEFAULT_CURSOR_TTL};
+
+#[cfg(feature = "observability-metrics")]
+pub use performance::TimerGuard;
@@ -0,0 +1,150 @@
+use actix_web::{http::header, HttpRequest, HttpResponse, HttpResponseBuilder};
+use serde::Serialize;
+use std::borrow::Cow;
+
+use crate::errors::ApiError;
+use crate::models::{ApiResponse, Paginated};
+
+/// Header với tổng số item khi list được trả về dạng CSV (không có envelope)
+pub const TOTAL_COUNT_HEADER: &str = "X-Total-Count";
+
+/// Định dạng response chọn theo header `Accept`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ResponseFormat {
+    Json,
+    MessagePack,
+    /// List endpoints only
+    Csv,
+}
+
+/// One CSV line per value, for list endpoints that support `text/csv`
+pub trait CsvRecord {
+    /// Header line, ending with `\n`
+    const CSV_HEADER: &'static str;
+
+    /// Data line, ending with `\n`; quote fields with [`csv_field`]
+    fn csv_row(&self) -> String;
+}
+
+/// Quote field CSV nếu cần (RFC 4180)
+pub fn csv_field(value: &str) -> Cow<'_, str> {
+    if value.contains([',', '"', '\n', '\r']) {
+        Cow::Owned(format!("\"{}\"", value.replace('"', "\"\"")))
+    } else {
+        Cow::Borrowed(value)
+    }
+}
+
+impl ResponseFormat {
+    /// Format có quality cao nhất trong `Accept` mà endpoint hỗ trợ
+    ///
+    /// No `Accept` header (or `*/*`) means JSON. `text/csv` is only
+    /// accepted when `allow_csv` is set; `406 Not Acceptable` if nothing
+    /// listed is supported.
+    pub fn negotiate(req: &HttpRequest, allow_csv: bool) -> Result<Self, ApiError> {
+        let Some(accept) = req
+            .headers()
+            .get(header::ACCEPT)
+            .and_then(|v| v.to_str().ok())
+            .filter(|v| !v.trim().is_empty())
+        else {
+            return Ok(Self::Json);
+        };
+
+        ranked_media_types(accept)
+            .iter()
+            .find_map(|media_type| Self::from_media_type(media_type, allow_csv))
+            .ok_or_else(|| {
+                let supported = if allow_csv {
+                    "application/json, application/msgpack, text/csv"
+                } else {
+                    "application/json, application/msgpack"
+                };
+                ApiError::not_acceptable(format!("Unsupported Accept: {} (supported: {})", accept, supported))
+            })
+    }
+
+    fn from_media_type(media_type: &str, allow_csv: bool) -> Option<Self> {
+        match media_type {
+            "application/json" | "application/*" | "*/*" => Some(Self::Json),
+            "application/msgpack" | "application/x-msgpack" | "application/vnd.msgpack" => {
+                Some(Self::MessagePack)
+            }
+            "text/csv" | "text/*" if allow_csv => Some(Self::Csv),
+            _ => None,
+        }
+    }
+
+    pub fn content_type(self) -> &'static str {
+        match self {
+            Self::Json => "application/json",
+            Self::MessagePack => "application/msgpack",
+            Self::Csv => "text/csv; charset=utf-8",
+        }
+    }
+
+    /// `body` as JSON or MessagePack; CSV needs [`respond_list`](Self::respond_list)
+    pub fn respond<T: Serialize>(
+        self,
+        builder: &mut HttpResponseBuilder,
+        body: &T,
+    ) -> Result<HttpResponse, ApiError> {
+        let bytes = match self {
+            Self::Json => serde_json::to_vec(body)
+                .map_err(|e| ApiError::internal(format!("Failed to serialize response: {}", e)))?,
+            Self::MessagePack => rmp_serde::to_vec_named(body)
+                .map_err(|e| ApiError::internal(format!("Failed to encode MessagePack response: {}", e)))?,
+            Self::Csv => return Err(ApiError::not_acceptable("text/csv is only available for lists")),
+        };
+
+        Ok(builder
+            .content_type(self.content_type())
+            .insert_header((header::VARY, "Accept"))
+            .body(bytes))
+    }
+
+    /// Like [`respond`](Self::respond); as CSV only the items of the page are
+    /// written, with the total in `X-Total-Count`
+    pub fn respond_list<T: Serialize + CsvRecord>(
+        self,
+        builder: &mut HttpResponseBuilder,
+        body: &ApiResponse<Paginated<T>>,
+    ) -> Result<HttpResponse, ApiError> {
+        if self != Self::Csv {
+            return self.respond(builder, body);
+        }
+
+        let mut csv = String::from(T::CSV_HEADER);
+        let mut total = 0;
+        if let Some(page) = &body.data {
+            csv.extend(page.items.iter().map(CsvRecord::csv_row));
+            total = page.total;
+        }
+
+        Ok(builder
+            .content_type(self.content_type())
+            .insert_header((header::VARY, "Accept"))
+            .insert_header((TOTAL_COUNT_HEADER, total.to_string()))
+            .body(csv))
+    }
+}
+
+/// Media types in `Accept`, lowercased, highest `q` first; `q=0` entries are
+/// dropped and ties keep header order
+fn ranked_media_types(accept: &str) -> Vec<String> {
+    let mut ranked: Vec<(String, f32)> = accept
+        .split(',')
+        .filter_map(|entry| {
+            let mut parts = entry.split(';');
+            let media_type = parts.next()?.trim().to_ascii_lowercase();
+            let quality = parts
+                .filter_map(|param| param.trim().strip_prefix("q="))
+                .find_map(|q| q.trim().parse::<f32>().ok())
+                .unwrap_or(1.0);
+            (!media_type.is_empty() && quality > 0.0).then_some((media_type, quality))
+        })
+        .collect();
+
+    ranked.sort_by(|a, b| b.1.total_cmp(&a.1));
+    ranked.into_iter().map(|(media_type, _)| media_type).collect()
+}
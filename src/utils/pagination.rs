@@ -0,0 +1,110 @@
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use chacha20poly1305::aead::{Aead, AeadCore, KeyInit, OsRng, Payload};
+use chacha20poly1305::{XChaCha20Poly1305, XNonce};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::time::Duration;
+
+use crate::errors::ApiError;
+use crate::models::SortDirection;
+
+/// Thời gian sống mặc định của cursor
+pub const DEFAULT_CURSOR_TTL: Duration = Duration::from_secs(15 * 60);
+
+const NONCE_LEN: usize = 24;
+
+/// Vị trí trong danh sách phân trang theo cursor
+///
+/// `key` holds the sort key of the last item on the page (e.g.
+/// `[created_at, id]`); the next page starts strictly after it.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Cursor {
+    /// Sort field the cursor was issued for
+    pub sort: String,
+    pub key: Vec<serde_json::Value>,
+    pub direction: SortDirection,
+}
+
+/// Who and which listing a cursor was issued for
+///
+/// A cursor only decodes under the scope it was encoded with, so it can't be
+/// replayed by another user, in another tenant or against another filter.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct CursorScope<'a> {
+    /// Caller, e.g. the JWT `sub`
+    pub subject: &'a str,
+    pub tenant_id: Option<&'a str>,
+    /// Canonical form of the list filter (e.g. the query without the cursor)
+    pub filter: &'a str,
+}
+
+#[derive(Serialize, Deserialize)]
+struct SealedCursor {
+    cursor: Cursor,
+    /// Unix timestamp (giây)
+    expires_at: i64,
+}
+
+/// Opaque base64url token for `cursor`, valid for `ttl`
+///
+/// The cursor is encrypted with XChaCha20-Poly1305 under a key derived from
+/// `secret`, with `scope` as associated data: clients can pass the token back
+/// but can't read, forge or edit it, nor use it outside `scope`.
+pub fn encode_cursor(
+    cursor: &Cursor,
+    scope: &CursorScope,
+    secret: &[u8],
+    ttl: Duration,
+) -> Result<String, ApiError> {
+    let sealed = SealedCursor {
+        cursor: cursor.clone(),
+        expires_at: chrono::Utc::now().timestamp().saturating_add(ttl.as_secs() as i64),
+    };
+    let payload = serde_json::to_vec(&sealed)
+        .map_err(|e| ApiError::internal(format!("Failed to encode cursor: {}", e)))?;
+    let aad = associated_data(scope)?;
+
+    let nonce = XChaCha20Poly1305::generate_nonce(&mut OsRng);
+    let ciphertext = cipher(secret)
+        .encrypt(&nonce, Payload { msg: &payload, aad: &aad })
+        .map_err(|_| ApiError::internal("Failed to encrypt cursor"))?;
+
+    let mut token = nonce.to_vec();
+    token.extend_from_slice(&ciphertext);
+    Ok(URL_SAFE_NO_PAD.encode(token))
+}
+
+/// Decrypt a token from `encode_cursor`; a malformed, tampered, expired or
+/// out-of-scope token is a `BadRequest`
+pub fn decode_cursor(token: &str, scope: &CursorScope, secret: &[u8]) -> Result<Cursor, ApiError> {
+    let invalid = || ApiError::bad_request("Invalid pagination cursor");
+
+    let token = URL_SAFE_NO_PAD.decode(token).map_err(|_| invalid())?;
+    if token.len() < NONCE_LEN {
+        return Err(invalid());
+    }
+    let (nonce, ciphertext) = token.split_at(NONCE_LEN);
+    let aad = associated_data(scope)?;
+
+    // Tag sai (sửa token, sai secret hoặc sai scope) thì không giải mã được
+    let payload = cipher(secret)
+        .decrypt(XNonce::from_slice(nonce), Payload { msg: ciphertext, aad: &aad })
+        .map_err(|_| invalid())?;
+    let sealed: SealedCursor = serde_json::from_slice(&payload).map_err(|_| invalid())?;
+
+    if sealed.expires_at <= chrono::Utc::now().timestamp() {
+        return Err(ApiError::bad_request("Pagination cursor has expired"));
+    }
+    Ok(sealed.cursor)
+}
+
+/// Any secret length works: the key is its SHA-256
+fn cipher(secret: &[u8]) -> XChaCha20Poly1305 {
+    XChaCha20Poly1305::new(&Sha256::digest(secret))
+}
+
+fn associated_data(scope: &CursorScope) -> Result<Vec<u8>, ApiError> {
+    serde_json::to_vec(scope)
+        .map_err(|e| ApiError::internal(format!("Failed to encode cursor scope: {}", e)))
+}
@@ -1,5 +1,10 @@
+use std::future::Future;
 use std::time::{Duration, Instant};
 use rayon::prelude::*;
+use tokio::sync::{mpsc, oneshot};
+use tokio::task::JoinHandle;
+
+use crate::errors::ApiError;
 
 /// Performance timer for measuring execution time
 pub struct Timer {
@@ -35,6 +40,49 @@ impl Drop for Timer {
     }
 }
 
+#[cfg(feature = "observability-metrics")]
+impl Timer {
+    /// Record the elapsed time (in seconds) into `histogram` when the
+    /// returned guard goes out of scope
+    pub fn observe_into(self, histogram: &prometheus::HistogramVec, labels: &[&str]) -> TimerGuard {
+        let histogram = histogram
+            .get_metric_with_label_values(labels)
+            .map_err(|e| {
+                tracing::warn!(label = %self.label, "Timer histogram labels rejected: {}", e);
+            })
+            .ok();
+
+        TimerGuard {
+            timer: self,
+            histogram,
+        }
+    }
+}
+
+/// RAII guard that observes a `Timer` into a histogram on drop, including
+/// on early return or error paths
+#[cfg(feature = "observability-metrics")]
+pub struct TimerGuard {
+    timer: Timer,
+    histogram: Option<prometheus::Histogram>,
+}
+
+#[cfg(feature = "observability-metrics")]
+impl TimerGuard {
+    pub fn elapsed(&self) -> Duration {
+        self.timer.elapsed()
+    }
+}
+
+#[cfg(feature = "observability-metrics")]
+impl Drop for TimerGuard {
+    fn drop(&mut self) {
+        if let Some(histogram) = &self.histogram {
+            histogram.observe(self.timer.elapsed().as_secs_f64());
+        }
+    }
+}
+
 /// Parallel processing utilities using Rayon
 pub struct ParallelProcessor;
 
@@ -84,6 +132,18 @@ impl ParallelProcessor {
     {
         items.into_par_iter().reduce(|| identity.clone(), op)
     }
+
+    /// Run an async function over items with at most `limit` futures in
+    /// flight; results keep the input order
+    pub async fn process_bounded<T, F, Fut, R>(items: Vec<T>, limit: usize, f: F) -> Vec<R>
+    where
+        F: Fn(T) -> Fut,
+        Fut: Future<Output = R>,
+    {
+        use futures_util::stream::{self, StreamExt};
+
+        stream::iter(items).map(f).buffered(limit.max(1)).collect().await
+    }
 }
 
 /// Connection pool configuration
@@ -108,12 +168,135 @@ impl Default for PoolConfig {
     }
 }
 
+enum BatchCommand<T> {
+    Item(T),
+    Flush(oneshot::Sender<()>),
+}
+
 /// Batch processing utilities
-pub struct BatchProcessor;
+///
+/// A `BatchProcessor` created with [`BatchProcessor::new`] accumulates items
+/// and hands them to the flush function once `max_size` items are queued or
+/// `max_delay` has passed since the first item of the batch, whichever comes
+/// first. Dropping the processor flushes any partially-filled batch.
+pub struct BatchProcessor<T> {
+    sender: Option<mpsc::Sender<BatchCommand<T>>>,
+    worker: Option<JoinHandle<()>>,
+}
+
+impl<T: Send + 'static> BatchProcessor<T> {
+    pub fn new<F, Fut>(max_size: usize, max_delay: Duration, flush_fn: F) -> Self
+    where
+        F: Fn(Vec<T>) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = ()> + Send + 'static,
+    {
+        let max_size = max_size.max(1);
+        let (sender, receiver) = mpsc::channel(max_size);
+        let worker = tokio::spawn(Self::run(receiver, max_size, max_delay, flush_fn));
+
+        Self {
+            sender: Some(sender),
+            worker: Some(worker),
+        }
+    }
+
+    /// Queue an item for the next batch
+    pub async fn push(&self, item: T) -> Result<(), ApiError> {
+        self.send(BatchCommand::Item(item)).await
+    }
+
+    /// Flush the current batch now, waiting until the flush function returns
+    pub async fn flush(&self) -> Result<(), ApiError> {
+        let (ack, done) = oneshot::channel();
+        self.send(BatchCommand::Flush(ack)).await?;
+        done.await
+            .map_err(|_| ApiError::internal("Batch processor stopped before flushing"))
+    }
+
+    /// Stop accepting items and wait for the remaining batch to be flushed
+    pub async fn shutdown(mut self) {
+        self.sender.take();
+        if let Some(worker) = self.worker.take() {
+            let _ = worker.await;
+        }
+    }
+
+    async fn send(&self, command: BatchCommand<T>) -> Result<(), ApiError> {
+        let sender = self
+            .sender
+            .as_ref()
+            .ok_or_else(|| ApiError::internal("Batch processor is shut down"))?;
+        sender
+            .send(command)
+            .await
+            .map_err(|_| ApiError::internal("Batch processor worker has stopped"))
+    }
+
+    async fn run<F, Fut>(
+        mut receiver: mpsc::Receiver<BatchCommand<T>>,
+        max_size: usize,
+        max_delay: Duration,
+        flush_fn: F,
+    ) where
+        F: Fn(Vec<T>) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = ()> + Send + 'static,
+    {
+        let mut batch: Vec<T> = Vec::with_capacity(max_size);
+        let mut deadline: Option<tokio::time::Instant> = None;
+
+        loop {
+            let command = match deadline {
+                Some(at) => tokio::select! {
+                    command = receiver.recv() => command,
+                    _ = tokio::time::sleep_until(at) => {
+                        deadline = None;
+                        flush_fn(std::mem::take(&mut batch)).await;
+                        continue;
+                    }
+                },
+                None => receiver.recv().await,
+            };
+
+            match command {
+                Some(BatchCommand::Item(item)) => {
+                    if batch.is_empty() {
+                        deadline = Some(tokio::time::Instant::now() + max_delay);
+                    }
+                    batch.push(item);
+                    if batch.len() >= max_size {
+                        deadline = None;
+                        flush_fn(std::mem::take(&mut batch)).await;
+                    }
+                }
+                Some(BatchCommand::Flush(ack)) => {
+                    deadline = None;
+                    if !batch.is_empty() {
+                        flush_fn(std::mem::take(&mut batch)).await;
+                    }
+                    let _ = ack.send(());
+                }
+                None => {
+                    // All senders dropped: flush what is left and stop
+                    if !batch.is_empty() {
+                        flush_fn(batch).await;
+                    }
+                    return;
+                }
+            }
+        }
+    }
+}
+
+impl<T> Drop for BatchProcessor<T> {
+    fn drop(&mut self) {
+        // Closing the channel makes the worker flush the pending batch
+        self.sender.take();
+    }
+}
 
-impl BatchProcessor {
+impl<T> BatchProcessor<T> {
     /// Process items in batches
-    pub async fn process_batches<T, F, Fut, R>(
+    pub async fn process_batches<F, Fut, R>(
         items: Vec<T>,
         batch_size: usize,
         f: F,
@@ -134,7 +317,7 @@ impl BatchProcessor {
     }
 
     /// Process items in batches with parallel execution
-    pub async fn process_batches_parallel<T, F, Fut, R>(
+    pub async fn process_batches_parallel<F, Fut, R>(
         items: Vec<T>,
         batch_size: usize,
         f: F,
@@ -0,0 +1,140 @@
+use actix_web::{http::StatusCode, ResponseError};
+use std::future::Future;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use crate::errors::ApiError;
+
+/// Quyết định một lỗi có nên thử lại hay không
+pub type RetryPredicate = Arc<dyn Fn(&ApiError) -> bool + Send + Sync>;
+
+/// Retry settings for [`retry_with_backoff`]
+///
+/// The delay after attempt `n` is `initial_backoff * multiplier^(n-1)`,
+/// capped at `max_backoff`; with `jitter` a random value between half and
+/// the full delay is used so that callers don't retry in lockstep.
+#[derive(Clone)]
+pub struct RetryPolicy {
+    /// Total attempts, including the first one
+    pub max_attempts: u32,
+    pub initial_backoff: Duration,
+    pub max_backoff: Duration,
+    pub multiplier: f64,
+    /// Give up instead of sleeping past this much time since the first attempt
+    pub max_elapsed: Option<Duration>,
+    pub jitter: bool,
+    should_retry: RetryPredicate,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 3,
+            initial_backoff: Duration::from_millis(100),
+            max_backoff: Duration::from_secs(5),
+            multiplier: 2.0,
+            max_elapsed: Some(Duration::from_secs(30)),
+            jitter: true,
+            should_retry: Arc::new(is_transient),
+        }
+    }
+}
+
+impl RetryPolicy {
+    pub fn new(max_attempts: u32) -> Self {
+        Self {
+            max_attempts,
+            ..Default::default()
+        }
+    }
+
+    pub fn with_backoff(mut self, initial: Duration, max: Duration) -> Self {
+        self.initial_backoff = initial;
+        self.max_backoff = max;
+        self
+    }
+
+    pub fn with_max_elapsed(mut self, max_elapsed: Duration) -> Self {
+        self.max_elapsed = Some(max_elapsed);
+        self
+    }
+
+    pub fn without_jitter(mut self) -> Self {
+        self.jitter = false;
+        self
+    }
+
+    /// Replace the default [`is_transient`] check
+    pub fn with_should_retry<P>(mut self, should_retry: P) -> Self
+    where
+        P: Fn(&ApiError) -> bool + Send + Sync + 'static,
+    {
+        self.should_retry = Arc::new(should_retry);
+        self
+    }
+
+    pub fn should_retry(&self, err: &ApiError) -> bool {
+        (self.should_retry)(err)
+    }
+
+    /// Delay before the attempt following attempt `attempt` (1-based)
+    pub fn backoff(&self, attempt: u32) -> Duration {
+        let exp = self.multiplier.powi(attempt.saturating_sub(1) as i32);
+        let delay = self.initial_backoff.mul_f64(exp).min(self.max_backoff);
+        if self.jitter {
+            delay.mul_f64(0.5 + rand::random::<f64>() / 2.0)
+        } else {
+            delay
+        }
+    }
+}
+
+/// Default retry check: 5xx errors other than `501 Not Implemented`
+///
+/// Client errors (4xx, including 429) are never retried, since sending the
+/// same request again cannot change the outcome.
+pub fn is_transient(err: &ApiError) -> bool {
+    let status = err.status_code();
+    status.is_server_error() && status != StatusCode::NOT_IMPLEMENTED
+}
+
+/// Run `f` until it succeeds, `policy` says the error is not retryable, or
+/// the attempt/elapsed limits are reached; the last error is returned
+///
+/// Only wrap idempotent operations.
+pub async fn retry_with_backoff<F, Fut, T>(policy: RetryPolicy, mut f: F) -> Result<T, ApiError>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<T, ApiError>>,
+{
+    let started = Instant::now();
+    let mut attempt = 1;
+
+    loop {
+        let err = match f().await {
+            Ok(value) => return Ok(value),
+            Err(err) => err,
+        };
+
+        if attempt >= policy.max_attempts || !policy.should_retry(&err) {
+            return Err(err);
+        }
+
+        let delay = policy.backoff(attempt);
+        if policy
+            .max_elapsed
+            .is_some_and(|max| started.elapsed() + delay > max)
+        {
+            return Err(err);
+        }
+
+        tracing::warn!(
+            attempt,
+            delay_ms = delay.as_millis() as u64,
+            error = %err,
+            "Retrying after error"
+        );
+        tokio::time::sleep(delay).await;
+        attempt += 1;
+    }
+}
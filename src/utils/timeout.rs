@@ -0,0 +1,21 @@
+use std::future::Future;
+use std::time::Duration;
+
+use crate::errors::ApiError;
+
+/// Chạy `fut` nhưng không quá `duration`
+///
+/// Returns `ApiError::GatewayTimeout` naming the duration if `fut` is still
+/// pending when it elapses; `fut` is dropped at that point.
+pub async fn with_timeout<F, T>(duration: Duration, fut: F) -> Result<T, ApiError>
+where
+    F: Future<Output = T>,
+{
+    tokio::select! {
+        value = fut => Ok(value),
+        _ = tokio::time::sleep(duration) => Err(ApiError::gateway_timeout(format!(
+            "Operation timed out after {:?}",
+            duration
+        ))),
+    }
+}
@@ -1,12 +1,117 @@
 use crate::errors::ApiError;
+use phonenumber::{country, Mode};
+use serde_json::Value;
+
+/// Region dùng khi số điện thoại không có mã quốc gia (`+84...`)
+pub const DEFAULT_PHONE_REGION: &str = "VN";
+
+/// Ký tự `atext` hợp lệ trong local part không có dấu nháy (RFC 5322)
+const ATEXT_SPECIALS: &str = "!#$%&'*+-/=?^_`{|}~";
 
 /// Utility struct cho validation
 pub struct Validator;
 
 impl Validator {
+    /// Kiểm tra email theo addr-spec của RFC 5322
+    ///
+    /// Local part là dot-atom (`first.last+tag`) hoặc quoted string
+    /// (`"john doe"`); domain có thể là IDN và được chuyển sang punycode
+    /// trước khi kiểm tra label. Domain literal (`[127.0.0.1]`) và domain
+    /// không có dấu chấm bị từ chối.
+    pub fn is_valid_email(email: &str) -> bool {
+        if email.len() > 254 {
+            return false;
+        }
+
+        // Tách ở '@' cuối cùng vì quoted local part có thể chứa '@'
+        let Some((local, domain)) = email.rsplit_once('@') else {
+            return false;
+        };
+
+        Self::is_valid_local_part(local) && Self::is_valid_domain(domain)
+    }
+
+    fn is_valid_local_part(local: &str) -> bool {
+        if local.is_empty() || local.len() > 64 {
+            return false;
+        }
+
+        if let Some(quoted) = local.strip_prefix('"').and_then(|l| l.strip_suffix('"')) {
+            let mut chars = quoted.chars();
+            while let Some(c) = chars.next() {
+                match c {
+                    // Escape bất kỳ ký tự ASCII in được nào
+                    '\\' => match chars.next() {
+                        Some(escaped) if (' '..='~').contains(&escaped) => {}
+                        _ => return false,
+                    },
+                    '"' => return false,
+                    c if (' '..='~').contains(&c) => {}
+                    _ => return false,
+                }
+            }
+            return true;
+        }
+
+        local.split('.').all(|atom| {
+            !atom.is_empty()
+                && atom
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || ATEXT_SPECIALS.contains(c))
+        })
+    }
+
+    fn is_valid_domain(domain: &str) -> bool {
+        let Ok(ascii) = idna::domain_to_ascii(domain) else {
+            return false;
+        };
+
+        if ascii.is_empty() || ascii.len() > 253 || !ascii.contains('.') {
+            return false;
+        }
+
+        let labels: Vec<&str> = ascii.split('.').collect();
+        let valid_labels = labels.iter().all(|label| {
+            !label.is_empty()
+                && label.len() <= 63
+                && !label.starts_with('-')
+                && !label.ends_with('-')
+                && label.chars().all(|c| c.is_ascii_alphanumeric() || c == '-')
+        });
+
+        // TLD toàn số thường là địa chỉ IP viết nhầm
+        let tld_is_numeric = labels
+            .last()
+            .is_some_and(|tld| tld.chars().all(|c| c.is_ascii_digit()));
+
+        valid_labels && !tld_is_numeric
+    }
+
+    /// Parse số điện thoại quốc tế, trả về dạng E.164 nếu hợp lệ
+    ///
+    /// `default_region` (ISO 3166-1 alpha-2, ví dụ `"VN"`) được dùng cho số
+    /// không có tiền tố `+`.
+    pub fn is_valid_phone(number: &str, default_region: &str) -> Option<String> {
+        let region = default_region.to_ascii_uppercase().parse::<country::Id>().ok();
+        let parsed = phonenumber::parse(region, number).ok()?;
+
+        if phonenumber::is_valid(&parsed) {
+            Some(parsed.format().mode(Mode::E164).to_string())
+        } else {
+            None
+        }
+    }
+
+    /// Validate số điện thoại và trả về dạng E.164
+    pub fn validate_phone(phone: &str, default_region: &str) -> Result<String, ApiError> {
+        Self::is_valid_phone(phone, default_region).ok_or_else(|| {
+            ApiError::validation_field(format!("Invalid phone number: {}", phone), "phone")
+        })
+    }
+
     /// Validate email format
     pub fn validate_email(email: &str) -> Result<(), ApiError> {
-        if email.contains('@') && email.contains('.') {
+        if Self::is_valid_email(email) {
             Ok(())
         } else {
             Err(ApiError::validation_field(
@@ -62,4 +167,96 @@ impl Validator {
             Ok(())
         }
     }
+
+    /// `instance_path` của jsonschema (`/items/0/age`) -> `items[0].age`
+    ///
+    /// A segment is an index only when the value it is taken from is an
+    /// array, so numeric object keys (`{"2024": ..}`) stay `scores.2024`.
+    fn json_pointer_to_field(pointer: &str, instance: &Value) -> String {
+        let mut current = Some(instance);
+        pointer
+            .split('/')
+            .skip(1)
+            .map(|segment| segment.replace("~1", "/").replace("~0", "~"))
+            .fold(String::new(), |field, segment| match current {
+                Some(Value::Array(items)) => {
+                    current = segment.parse::<usize>().ok().and_then(|i| items.get(i));
+                    format!("{}[{}]", field, segment)
+                }
+                value => {
+                    current = value.and_then(|v| v.get(&segment));
+                    Self::join_field(&field, &segment)
+                }
+            })
+    }
+
+    fn join_field(parent: &str, name: &str) -> String {
+        if parent.is_empty() {
+            name.to_string()
+        } else {
+            format!("{}.{}", parent, name)
+        }
+    }
+}
+
+/// JSON Schema đã compile, dùng lại cho mọi lần validate
+///
+/// ```ignore
+/// static ORDER_SCHEMA: LazyLock<JsonSchema> =
+///     LazyLock::new(|| JsonSchema::compile(&order_schema()).expect("valid schema"));
+///
+/// ORDER_SCHEMA.validate(&body)?;
+/// ```
+pub struct JsonSchema {
+    validator: jsonschema::Validator,
+}
+
+impl JsonSchema {
+    /// Compile `schema` once; an invalid schema is an `InternalError`
+    pub fn compile(schema: &Value) -> Result<Self, ApiError> {
+        jsonschema::validator_for(schema)
+            .map(|validator| Self { validator })
+            .map_err(|e| ApiError::internal(format!("Invalid JSON Schema: {}", e)))
+    }
+
+    /// Validate `instance`, reporting every violation in one `ValidationError`
+    ///
+    /// `field` lists the paths of all violations (`items[0].age`, empty for
+    /// the root), comma-separated; for `required` the path points at the
+    /// missing property. `message` repeats each path with its reason.
+    pub fn validate(&self, instance: &Value) -> Result<(), ApiError> {
+        let violations: Vec<(String, String)> = self
+            .validator
+            .iter_errors(instance)
+            .map(|error| {
+                let mut field =
+                    Validator::json_pointer_to_field(&error.instance_path.to_string(), instance);
+                if let jsonschema::error::ValidationErrorKind::Required { property } = &error.kind {
+                    let property = property
+                        .as_str()
+                        .map(str::to_string)
+                        .unwrap_or_else(|| property.to_string());
+                    field = Validator::join_field(&field, &property);
+                }
+                (field, error.to_string())
+            })
+            .collect();
+
+        if violations.is_empty() {
+            return Ok(());
+        }
+
+        let message = violations
+            .iter()
+            .map(|(field, reason)| format!("{}: {}", field, reason))
+            .collect::<Vec<_>>()
+            .join("; ");
+        let mut fields: Vec<&str> = Vec::new();
+        for (field, _) in &violations {
+            if !fields.contains(&field.as_str()) {
+                fields.push(field);
+            }
+        }
+        Err(ApiError::validation_field(message, fields.join(", ")))
+    }
 }
@@ -0,0 +1,433 @@
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use std::collections::VecDeque;
+use std::net::{IpAddr, SocketAddr};
+use std::sync::{Arc, Mutex, RwLock};
+use std::time::{Duration, Instant};
+
+use super::signature::{sign, SIGNATURE_HEADER, TIMESTAMP_HEADER};
+use crate::errors::ApiError;
+use crate::patterns::StoredEvent;
+use crate::utils::{retry_with_backoff, with_timeout, RetryPolicy};
+
+/// Header chứa id của delivery, giống nhau giữa các lần retry
+pub const DELIVERY_ID_HEADER: &str = "X-Webhook-Delivery";
+
+/// Thời gian tối đa cho một lần POST
+pub const DEFAULT_DELIVERY_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Số delivery gần nhất được giữ lại để xem lại
+pub const MAX_RECORDED_DELIVERIES: usize = 1000;
+
+/// Endpoint nhận webhook của khách hàng
+#[derive(Debug, Clone)]
+pub struct WebhookEndpoint {
+    pub id: String,
+    pub url: String,
+    /// Key of the `X-Signature` HMAC
+    pub secret: String,
+    /// Event types sent to this endpoint; empty means all of them
+    pub event_types: Vec<String>,
+}
+
+impl WebhookEndpoint {
+    pub fn new(id: impl Into<String>, url: impl Into<String>, secret: impl Into<String>) -> Self {
+        Self {
+            id: id.into(),
+            url: url.into(),
+            secret: secret.into(),
+            event_types: Vec::new(),
+        }
+    }
+
+    pub fn with_event_types<I, S>(mut self, event_types: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.event_types = event_types.into_iter().map(Into::into).collect();
+        self
+    }
+
+    pub fn subscribes_to(&self, event_type: &str) -> bool {
+        self.event_types.is_empty() || self.event_types.iter().any(|t| t == event_type)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DeliveryStatus {
+    Delivered,
+    Failed,
+}
+
+/// One POST to the endpoint
+#[derive(Debug, Clone, Serialize)]
+pub struct DeliveryAttempt {
+    pub attempt: u32,
+    /// Response status; `None` on timeout or connection error
+    pub status_code: Option<u16>,
+    pub error: Option<String>,
+    pub duration_ms: u64,
+    pub attempted_at: DateTime<Utc>,
+}
+
+/// Kết quả gửi một payload tới một endpoint, kèm tất cả các lần thử
+#[derive(Debug, Clone, Serialize)]
+pub struct WebhookDelivery {
+    pub id: String,
+    pub endpoint_id: String,
+    pub status: DeliveryStatus,
+    pub attempts: Vec<DeliveryAttempt>,
+}
+
+/// Gửi webhook có ký HMAC, retry khi lỗi tạm thời
+///
+/// 5xx responses, timeouts and connection errors are retried with the
+/// [`RetryPolicy`]; 4xx responses fail the delivery immediately. Endpoints
+/// registered with [`subscribe`](Self::subscribe) receive events passed to
+/// [`publish`](Self::publish).
+///
+/// Endpoint URLs come from customers, so redirects are not followed and
+/// hosts resolving only to loopback, private, link-local or otherwise
+/// non-public addresses are refused (checked again at connect time, so DNS
+/// rebinding cannot get around it). Trace and request-id headers are never
+/// sent to endpoints.
+#[derive(Clone)]
+pub struct WebhookDispatcher {
+    client: reqwest::Client,
+    allow_private_networks: bool,
+    retry_policy: RetryPolicy,
+    timeout: Duration,
+    endpoints: Arc<RwLock<Vec<WebhookEndpoint>>>,
+    deliveries: Arc<RwLock<VecDeque<WebhookDelivery>>>,
+}
+
+impl WebhookDispatcher {
+    pub fn new() -> Self {
+        Self::with_client(default_client(false))
+    }
+
+    /// Use `client` as-is: it should disable redirects and filter resolved
+    /// addresses itself. URLs are still checked before each attempt.
+    pub fn with_client(client: reqwest::Client) -> Self {
+        Self {
+            client,
+            allow_private_networks: false,
+            retry_policy: RetryPolicy::default(),
+            timeout: DEFAULT_DELIVERY_TIMEOUT,
+            endpoints: Arc::new(RwLock::new(Vec::new())),
+            deliveries: Arc::new(RwLock::new(VecDeque::new())),
+        }
+    }
+
+    /// Also deliver to loopback and private addresses, e.g. receivers on
+    /// the same network or in tests. Replaces the client with the default
+    /// one, which still does not follow redirects.
+    pub fn allow_private_networks(mut self) -> Self {
+        self.allow_private_networks = true;
+        self.client = default_client(true);
+        self
+    }
+
+    pub fn with_retry_policy(mut self, policy: RetryPolicy) -> Self {
+        self.retry_policy = policy;
+        self
+    }
+
+    /// Timeout of each attempt
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Register `endpoint`, replacing one with the same id
+    pub fn subscribe(&self, endpoint: WebhookEndpoint) -> Result<(), ApiError> {
+        let mut endpoints = self
+            .endpoints
+            .write()
+            .map_err(|_| ApiError::internal("Failed to acquire write lock on webhook endpoints"))?;
+        endpoints.retain(|e| e.id != endpoint.id);
+        endpoints.push(endpoint);
+        Ok(())
+    }
+
+    /// Remove an endpoint; returns whether it was registered
+    pub fn unsubscribe(&self, endpoint_id: &str) -> Result<bool, ApiError> {
+        let mut endpoints = self
+            .endpoints
+            .write()
+            .map_err(|_| ApiError::internal("Failed to acquire write lock on webhook endpoints"))?;
+        let before = endpoints.len();
+        endpoints.retain(|e| e.id != endpoint_id);
+        Ok(endpoints.len() < before)
+    }
+
+    pub fn endpoints_for(&self, event_type: &str) -> Result<Vec<WebhookEndpoint>, ApiError> {
+        let endpoints = self
+            .endpoints
+            .read()
+            .map_err(|_| ApiError::internal("Failed to acquire read lock on webhook endpoints"))?;
+        Ok(endpoints
+            .iter()
+            .filter(|e| e.subscribes_to(event_type))
+            .cloned()
+            .collect())
+    }
+
+    /// POST `event_payload` to `endpoint`, retrying transient failures
+    ///
+    /// A delivery that still fails after the retries is returned with status
+    /// `Failed`, not as an error. The returned delivery is also kept in
+    /// [`deliveries`](Self::deliveries).
+    pub async fn deliver(
+        &self,
+        endpoint: &WebhookEndpoint,
+        event_payload: &serde_json::Value,
+    ) -> Result<WebhookDelivery, ApiError> {
+        let body = serde_json::to_vec(event_payload)
+            .map_err(|e| ApiError::internal(format!("Failed to serialize webhook payload: {}", e)))?;
+        let delivery_id = uuid::Uuid::new_v4().to_string();
+        let attempts = Mutex::new(Vec::new());
+
+        let result = retry_with_backoff(self.retry_policy.clone(), || {
+            let attempt = self.send_attempt(endpoint, &body, &delivery_id);
+            let attempts = &attempts;
+            async move {
+                let (mut record, result) = attempt.await;
+                let mut attempts = attempts.lock().unwrap_or_else(|e| e.into_inner());
+                record.attempt = attempts.len() as u32 + 1;
+                attempts.push(record);
+                result
+            }
+        })
+        .await;
+
+        let delivery = WebhookDelivery {
+            id: delivery_id,
+            endpoint_id: endpoint.id.clone(),
+            status: if result.is_ok() {
+                DeliveryStatus::Delivered
+            } else {
+                DeliveryStatus::Failed
+            },
+            attempts: attempts.into_inner().unwrap_or_else(|e| e.into_inner()),
+        };
+
+        match &result {
+            Ok(()) => tracing::info!(
+                endpoint = %endpoint.id,
+                delivery = %delivery.id,
+                attempts = delivery.attempts.len(),
+                "Webhook delivered"
+            ),
+            Err(e) => tracing::warn!(
+                endpoint = %endpoint.id,
+                delivery = %delivery.id,
+                attempts = delivery.attempts.len(),
+                "Webhook delivery failed: {}",
+                e
+            ),
+        }
+
+        self.record(delivery.clone())?;
+        Ok(delivery)
+    }
+
+    /// One POST signed with the current timestamp; 5xx, timeouts and
+    /// connection errors are returned as transient (5xx) errors, refused
+    /// URLs and other non-success statuses as `BadRequest`
+    async fn send_attempt(
+        &self,
+        endpoint: &WebhookEndpoint,
+        body: &[u8],
+        delivery_id: &str,
+    ) -> (DeliveryAttempt, Result<(), ApiError>) {
+        let started = Instant::now();
+        let attempted_at = Utc::now();
+        let timestamp = attempted_at.timestamp();
+
+        if let Err(e) = self.check_destination(endpoint).await {
+            let attempt = DeliveryAttempt {
+                attempt: 0,
+                status_code: None,
+                error: Some(e.to_string()),
+                duration_ms: started.elapsed().as_millis() as u64,
+                attempted_at,
+            };
+            return (attempt, Err(e));
+        }
+
+        let request = self
+            .client
+            .post(&endpoint.url)
+            .header(reqwest::header::CONTENT_TYPE, "application/json")
+            .header(TIMESTAMP_HEADER, timestamp.to_string())
+            .header(SIGNATURE_HEADER, sign(&endpoint.secret, timestamp, body))
+            .header(DELIVERY_ID_HEADER, delivery_id)
+            .body(body.to_vec());
+
+        let (status_code, result) = match with_timeout(self.timeout, request.send()).await {
+            Ok(Ok(response)) => {
+                let status = response.status();
+                let result = if status.is_success() {
+                    Ok(())
+                } else if status.is_server_error() {
+                    Err(ApiError::external_service(
+                        format!("Webhook endpoint {} returned {}", endpoint.id, status),
+                        "webhook",
+                    ))
+                } else {
+                    Err(ApiError::bad_request(format!(
+                        "Webhook endpoint {} rejected delivery with {}",
+                        endpoint.id, status
+                    )))
+                };
+                (Some(status.as_u16()), result)
+            }
+            Ok(Err(e)) => (
+                None,
+                Err(ApiError::external_service(
+                    format!("Webhook delivery to {} failed: {}", endpoint.id, e),
+                    "webhook",
+                )),
+            ),
+            Err(timeout) => (None, Err(timeout)),
+        };
+
+        let attempt = DeliveryAttempt {
+            attempt: 0,
+            status_code,
+            error: result.as_ref().err().map(|e| e.to_string()),
+            duration_ms: started.elapsed().as_millis() as u64,
+            attempted_at,
+        };
+        (attempt, result)
+    }
+
+    /// Refuse URLs that are not http(s) or whose host has no public address
+    async fn check_destination(&self, endpoint: &WebhookEndpoint) -> Result<(), ApiError> {
+        let refused = |reason: String| {
+            ApiError::bad_request(format!("Webhook endpoint {} refused: {}", endpoint.id, reason))
+        };
+
+        let url = reqwest::Url::parse(&endpoint.url).map_err(|e| refused(e.to_string()))?;
+        if !matches!(url.scheme(), "http" | "https") {
+            return Err(refused(format!("unsupported scheme {}", url.scheme())));
+        }
+        if self.allow_private_networks {
+            return Ok(());
+        }
+
+        let host = url.host_str().ok_or_else(|| refused("missing host".to_string()))?;
+        let host = host.trim_start_matches('[').trim_end_matches(']');
+        let port = url.port_or_known_default().unwrap_or_default();
+        public_addresses(host, port).await.map(|_| ()).map_err(refused)
+    }
+
+    /// Deliver `event` to every endpoint subscribed to its type, concurrently
+    pub async fn publish(&self, event: &StoredEvent) -> Result<Vec<WebhookDelivery>, ApiError> {
+        let payload = serde_json::to_value(event)
+            .map_err(|e| ApiError::internal(format!("Failed to serialize event {}: {}", event.id, e)))?;
+        let endpoints = self.endpoints_for(&event.event_type)?;
+
+        futures_util::future::join_all(endpoints.iter().map(|endpoint| self.deliver(endpoint, &payload)))
+            .await
+            .into_iter()
+            .collect()
+    }
+
+    /// Most recent deliveries, oldest first
+    pub fn deliveries(&self) -> Result<Vec<WebhookDelivery>, ApiError> {
+        let deliveries = self
+            .deliveries
+            .read()
+            .map_err(|_| ApiError::internal("Failed to acquire read lock on webhook deliveries"))?;
+        Ok(deliveries.iter().cloned().collect())
+    }
+
+    fn record(&self, delivery: WebhookDelivery) -> Result<(), ApiError> {
+        let mut deliveries = self
+            .deliveries
+            .write()
+            .map_err(|_| ApiError::internal("Failed to acquire write lock on webhook deliveries"))?;
+        if deliveries.len() >= MAX_RECORDED_DELIVERIES {
+            deliveries.pop_front();
+        }
+        deliveries.push_back(delivery);
+        Ok(())
+    }
+}
+
+impl Default for WebhookDispatcher {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn default_client(allow_private_networks: bool) -> reqwest::Client {
+    let builder = reqwest::Client::builder().redirect(reqwest::redirect::Policy::none());
+    let builder = if allow_private_networks {
+        builder
+    } else {
+        builder.dns_resolver(Arc::new(PublicAddressResolver))
+    };
+    builder.build().expect("default webhook client configuration is valid")
+}
+
+/// Resolver trả về chỉ các địa chỉ public, chặn DNS rebinding lúc connect
+struct PublicAddressResolver;
+
+impl reqwest::dns::Resolve for PublicAddressResolver {
+    fn resolve(&self, name: reqwest::dns::Name) -> reqwest::dns::Resolving {
+        let host = name.as_str().to_string();
+        Box::pin(async move {
+            let addresses = public_addresses(&host, 0).await?;
+            Ok(Box::new(addresses.into_iter()) as reqwest::dns::Addrs)
+        })
+    }
+}
+
+/// Public addresses of `host` (an IP literal or a name), or why there are none
+async fn public_addresses(host: &str, port: u16) -> Result<Vec<SocketAddr>, String> {
+    let addresses: Vec<SocketAddr> = tokio::net::lookup_host((host, port))
+        .await
+        .map_err(|e| format!("cannot resolve {}: {}", host, e))?
+        .filter(|address| is_public_address(address.ip()))
+        .collect();
+
+    if addresses.is_empty() {
+        return Err(format!("{} does not resolve to a public address", host));
+    }
+    Ok(addresses)
+}
+
+fn is_public_address(ip: IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(v4) => {
+            let [a, b, ..] = v4.octets();
+            !(v4.is_private()
+                || v4.is_loopback()
+                || v4.is_link_local()
+                || v4.is_unspecified()
+                || v4.is_broadcast()
+                || v4.is_documentation()
+                || v4.is_multicast()
+                || a == 0
+                // 100.64.0.0/10 (carrier-grade NAT)
+                || (a == 100 && (b & 0xc0) == 64))
+        }
+        IpAddr::V6(v6) => match v6.to_ipv4_mapped() {
+            Some(v4) => is_public_address(IpAddr::V4(v4)),
+            None => {
+                let first = v6.segments()[0];
+                !(v6.is_loopback()
+                    || v6.is_unspecified()
+                    || v6.is_multicast()
+                    // fc00::/7 unique local, fe80::/10 link-local
+                    || (first & 0xfe00) == 0xfc00
+                    || (first & 0xffc0) == 0xfe80)
+            }
+        },
+    }
+}
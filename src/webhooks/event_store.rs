@@ -0,0 +1,59 @@
+use std::sync::Arc;
+
+use super::dispatcher::WebhookDispatcher;
+use crate::errors::ApiError;
+use crate::patterns::{EventStore, SequencedEvent, StoredEvent};
+
+/// Event store gửi mỗi event mới tới các webhook endpoint đã subscribe
+///
+/// Wraps another store: once `append` succeeds, the event is published by
+/// the dispatcher on a background task, so slow endpoints never hold up the
+/// write. Appends made outside a Tokio runtime are stored but not published.
+pub struct WebhookEventStore<S: EventStore> {
+    inner: S,
+    dispatcher: Arc<WebhookDispatcher>,
+}
+
+impl<S: EventStore> WebhookEventStore<S> {
+    pub fn new(inner: S, dispatcher: Arc<WebhookDispatcher>) -> Self {
+        Self { inner, dispatcher }
+    }
+
+    pub fn dispatcher(&self) -> &Arc<WebhookDispatcher> {
+        &self.dispatcher
+    }
+
+    fn fan_out(&self, event: StoredEvent) {
+        let Ok(runtime) = tokio::runtime::Handle::try_current() else {
+            tracing::warn!(event = %event.id, "No Tokio runtime, webhooks not published");
+            return;
+        };
+
+        let dispatcher = self.dispatcher.clone();
+        runtime.spawn(async move {
+            if let Err(e) = dispatcher.publish(&event).await {
+                tracing::error!(event = %event.id, "Failed to publish webhooks: {}", e);
+            }
+        });
+    }
+}
+
+impl<S: EventStore> EventStore for WebhookEventStore<S> {
+    fn append(&self, event: StoredEvent) -> Result<(), ApiError> {
+        self.inner.append(event.clone())?;
+        self.fan_out(event);
+        Ok(())
+    }
+
+    fn get_events(&self, aggregate_id: &str) -> Result<Vec<StoredEvent>, ApiError> {
+        self.inner.get_events(aggregate_id)
+    }
+
+    fn get_events_since(&self, aggregate_id: &str, version: u64) -> Result<Vec<StoredEvent>, ApiError> {
+        self.inner.get_events_since(aggregate_id, version)
+    }
+
+    fn get_all_since(&self, global_seq: u64) -> Result<Vec<SequencedEvent>, ApiError> {
+        self.inner.get_all_since(global_seq)
+    }
+}
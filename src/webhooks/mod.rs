@@ -0,0 +1,15 @@
+// Webhook delivery - POST domain events tới endpoint của khách hàng
+
+pub mod dispatcher;
+pub mod event_store;
+pub mod signature;
+
+pub use dispatcher::{
+    DeliveryAttempt, DeliveryStatus, WebhookDelivery, WebhookDispatcher, WebhookEndpoint,
+    DEFAULT_DELIVERY_TIMEOUT, DELIVERY_ID_HEADER,
+};
+pub use event_store::WebhookEventStore;
+pub use signature::{
+    sign, verify, verify_with_tolerance, DEFAULT_SIGNATURE_TOLERANCE, SIGNATURE_HEADER,
+    TIMESTAMP_HEADER,
+};
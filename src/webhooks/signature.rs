@@ -0,0 +1,55 @@
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+use std::time::Duration;
+
+/// Header chứa chữ ký HMAC của `{timestamp}.{body}`
+pub const SIGNATURE_HEADER: &str = "X-Signature";
+
+/// Header chứa Unix timestamp (giây) đã được ký cùng body
+pub const TIMESTAMP_HEADER: &str = "X-Webhook-Timestamp";
+
+/// Độ lệch tối đa giữa timestamp và đồng hồ của receiver
+pub const DEFAULT_SIGNATURE_TOLERANCE: Duration = Duration::from_secs(300);
+
+const SIGNATURE_PREFIX: &str = "sha256=";
+
+type HmacSha256 = Hmac<Sha256>;
+
+/// `sha256=<hex HMAC-SHA256 of "{timestamp}.{body}">` keyed with the endpoint
+/// secret; binding the timestamp lets receivers reject replays
+pub fn sign(secret: &str, timestamp: i64, body: &[u8]) -> String {
+    let mac = signed_payload(secret, timestamp, body);
+    format!("{}{}", SIGNATURE_PREFIX, hex::encode(mac.finalize().into_bytes()))
+}
+
+/// Check an `X-Signature` value in constant time, as a receiver would
+///
+/// Only the signature is checked; use [`verify_with_tolerance`] to also
+/// reject stale timestamps.
+pub fn verify(secret: &str, timestamp: i64, body: &[u8], signature: &str) -> bool {
+    let Some(Ok(expected)) = signature.strip_prefix(SIGNATURE_PREFIX).map(hex::decode) else {
+        return false;
+    };
+
+    signed_payload(secret, timestamp, body).verify_slice(&expected).is_ok()
+}
+
+/// [`verify`], and `timestamp` is within `tolerance` of the current time
+pub fn verify_with_tolerance(
+    secret: &str,
+    timestamp: i64,
+    body: &[u8],
+    signature: &str,
+    tolerance: Duration,
+) -> bool {
+    let age = chrono::Utc::now().timestamp().abs_diff(timestamp);
+    age <= tolerance.as_secs() && verify(secret, timestamp, body, signature)
+}
+
+fn signed_payload(secret: &str, timestamp: i64, body: &[u8]) -> HmacSha256 {
+    let mut mac = HmacSha256::new_from_slice(secret.as_bytes()).expect("HMAC accepts keys of any length");
+    mac.update(timestamp.to_string().as_bytes());
+    mac.update(b".");
+    mac.update(body);
+    mac
+}
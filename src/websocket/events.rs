@@ -0,0 +1,155 @@
+use futures_util::stream::{self, BoxStream, StreamExt};
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+use tokio::sync::broadcast;
+
+use super::messages::ServerMessage;
+
+/// Số event giữ lại để client resume bằng `Last-Event-ID`
+pub const DEFAULT_EVENT_HISTORY: usize = 256;
+
+/// Event đã publish, kèm id tăng dần
+#[derive(Debug, Clone)]
+pub struct StreamEvent {
+    pub id: u64,
+    pub message: ServerMessage,
+}
+
+/// Nguồn event chung cho WebSocket sessions và SSE streams
+///
+/// Keeps the most recent events so subscribers that reconnect with the id of
+/// the last event they saw get everything published since.
+#[derive(Clone)]
+pub struct EventBus {
+    inner: Arc<Mutex<EventBusInner>>,
+    sender: broadcast::Sender<StreamEvent>,
+}
+
+struct EventBusInner {
+    next_id: u64,
+    history: VecDeque<StreamEvent>,
+    capacity: usize,
+}
+
+impl EventBus {
+    pub fn new() -> Self {
+        Self::with_history(DEFAULT_EVENT_HISTORY)
+    }
+
+    pub fn with_history(capacity: usize) -> Self {
+        let capacity = capacity.max(1);
+        let (sender, _) = broadcast::channel(capacity);
+        Self {
+            inner: Arc::new(Mutex::new(EventBusInner {
+                next_id: 1,
+                history: VecDeque::with_capacity(capacity),
+                capacity,
+            })),
+            sender,
+        }
+    }
+
+    /// Publish to every subscriber; returns the id assigned to the event
+    pub fn publish(&self, message: ServerMessage) -> u64 {
+        let mut inner = self.inner.lock().unwrap_or_else(|e| e.into_inner());
+
+        let event = StreamEvent {
+            id: inner.next_id,
+            message,
+        };
+        inner.next_id += 1;
+
+        if inner.history.len() == inner.capacity {
+            inner.history.pop_front();
+        }
+        inner.history.push_back(event.clone());
+
+        // Không có subscriber thì send lỗi, bỏ qua
+        let _ = self.sender.send(event.clone());
+        event.id
+    }
+
+    pub fn subscriber_count(&self) -> usize {
+        self.sender.receiver_count()
+    }
+
+    /// Events published from now on, preceded by the retained events after
+    /// `last_event_id` when one is given
+    pub fn subscribe(&self, last_event_id: Option<u64>) -> BoxStream<'static, StreamEvent> {
+        // Subscribe và chụp history dưới cùng một lock với `publish` nên
+        // không bị mất hay lặp event giữa phần replay và phần live
+        let (replay, receiver) = {
+            let inner = self.inner.lock().unwrap_or_else(|e| e.into_inner());
+            let replay: Vec<StreamEvent> = match last_event_id {
+                Some(last_id) => inner
+                    .history
+                    .iter()
+                    .filter(|event| event.id > last_id)
+                    .cloned()
+                    .collect(),
+                None => Vec::new(),
+            };
+            (replay, self.sender.subscribe())
+        };
+
+        let live = stream::unfold(receiver, |mut receiver| async move {
+            loop {
+                match receiver.recv().await {
+                    Ok(event) => return Some((event, receiver)),
+                    Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                        tracing::warn!(skipped, "Event subscriber lagged, events dropped");
+                    }
+                    Err(broadcast::error::RecvError::Closed) => return None,
+                }
+            }
+        });
+
+        stream::iter(replay).chain(live).boxed()
+    }
+}
+
+impl Default for EventBus {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn message(n: u64) -> ServerMessage {
+        ServerMessage::Message {
+            topic: "test".to_string(),
+            payload: serde_json::json!({ "n": n }),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_subscribe_replays_after_last_event_id() {
+        let bus = EventBus::new();
+        for n in 1..=3 {
+            bus.publish(message(n));
+        }
+
+        let events = bus.subscribe(Some(1));
+        bus.publish(message(4));
+
+        let ids: Vec<u64> = events.take(3).map(|e| e.id).collect().await;
+        assert_eq!(ids, vec![2, 3, 4]);
+    }
+
+    #[tokio::test]
+    async fn test_history_is_bounded() {
+        let bus = EventBus::with_history(2);
+        for n in 1..=5 {
+            bus.publish(message(n));
+        }
+
+        let events = bus.subscribe(Some(0));
+        bus.publish(message(6));
+
+        let ids: Vec<u64> = events.take(3).map(|e| e.id).collect().await;
+        assert_eq!(ids, vec![4, 5, 6]);
+    }
+}
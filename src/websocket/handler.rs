@@ -0,0 +1,67 @@
+use actix_web::{web, HttpRequest, HttpResponse};
+use actix_web_actors::ws;
+use serde::Deserialize;
+
+use super::server::WebSocketServer;
+use crate::auth::{Claims, JwtManager};
+use crate::errors::ApiError;
+
+/// Subprotocol mang token: `Sec-WebSocket-Protocol: bearer, <jwt>`
+///
+/// Browsers cannot set `Authorization` on a WebSocket, so the token travels as
+/// the second protocol entry; the server echoes `bearer` back.
+pub const BEARER_PROTOCOL: &str = "bearer";
+
+#[derive(Deserialize)]
+struct TokenQuery {
+    token: Option<String>,
+}
+
+/// GET /ws - Nâng cấp lên WebSocket sau khi xác thực JWT
+///
+/// The token comes from the `token` query parameter or the
+/// `Sec-WebSocket-Protocol` header. Without a valid one the upgrade is answered
+/// with 401 and no WebSocket is established.
+pub async fn ws_connect(
+    req: HttpRequest,
+    stream: web::Payload,
+    server: web::Data<WebSocketServer>,
+    jwt: web::Data<JwtManager>,
+) -> Result<HttpResponse, actix_web::Error> {
+    let claims = authenticate_upgrade(&req, &jwt)?;
+    tracing::info!(user_id = %claims.sub, "WebSocket handshake authenticated");
+
+    let session = server.new_session().with_user(claims.sub);
+    let builder = ws::WsResponseBuilder::new(session, &req, stream);
+    if protocol_token(&req).is_some() {
+        builder.protocols(&[BEARER_PROTOCOL]).start()
+    } else {
+        builder.start()
+    }
+}
+
+/// Claims of the JWT presented with a WebSocket upgrade request
+pub fn authenticate_upgrade(req: &HttpRequest, jwt: &JwtManager) -> Result<Claims, ApiError> {
+    let token = query_token(req)
+        .or_else(|| protocol_token(req))
+        .ok_or_else(|| ApiError::unauthorized("Missing token"))?;
+    jwt.verify_token(&token)
+}
+
+fn query_token(req: &HttpRequest) -> Option<String> {
+    web::Query::<TokenQuery>::from_query(req.query_string())
+        .ok()
+        .and_then(|query| query.into_inner().token)
+        .filter(|token| !token.is_empty())
+}
+
+/// Entry following `bearer` in `Sec-WebSocket-Protocol`
+fn protocol_token(req: &HttpRequest) -> Option<String> {
+    let protocols = req.headers().get("Sec-WebSocket-Protocol")?.to_str().ok()?;
+    let mut entries = protocols.split(',').map(str::trim);
+    entries.find(|entry| entry.eq_ignore_ascii_case(BEARER_PROTOCOL))?;
+    entries
+        .next()
+        .filter(|token| !token.is_empty())
+        .map(String::from)
+}
@@ -1,8 +1,11 @@
 pub mod server;
 pub mod session;
 pub mod messages;
+pub mod events;
+pub mod handler;
 
 pub use server::WebSocketServer;
-pub use session::WebSocketSession;
+pub use session::{MessageRateLimit, RateLimitAction, WebSocketSession};
 pub use messages::{ClientMessage, ServerMessage};
-
+pub use events::{EventBus, StreamEvent};
+pub use handler::{authenticate_upgrade, ws_connect, BEARER_PROTOCOL};
@@ -1,18 +1,53 @@
 use std::collections::HashMap;
 use std::sync::{Arc, RwLock};
 use actix::Addr;
-use super::session::WebSocketSession;
+use super::events::EventBus;
+use super::messages::ServerMessage;
+use super::session::{MessageRateLimit, WebSocketSession};
 
 /// WebSocket server for managing connections
 #[derive(Clone)]
 pub struct WebSocketServer {
     sessions: Arc<RwLock<HashMap<String, Addr<WebSocketSession>>>>,
+    events: EventBus,
+    rate_limit: Option<MessageRateLimit>,
 }
 
 impl WebSocketServer {
     pub fn new() -> Self {
+        Self::with_events(EventBus::new())
+    }
+
+    /// Share `events` with other transports (e.g. `GET /events/stream`)
+    pub fn with_events(events: EventBus) -> Self {
         Self {
             sessions: Arc::new(RwLock::new(HashMap::new())),
+            events,
+            rate_limit: None,
+        }
+    }
+
+    /// Apply `rate_limit` to every session created by [`new_session`](Self::new_session)
+    pub fn with_rate_limit(mut self, rate_limit: MessageRateLimit) -> Self {
+        self.rate_limit = Some(rate_limit);
+        self
+    }
+
+    pub fn events(&self) -> &EventBus {
+        &self.events
+    }
+
+    /// Push a message to every WebSocket session and SSE stream
+    pub fn broadcast(&self, message: ServerMessage) -> u64 {
+        self.events.publish(message)
+    }
+
+    /// New session that receives this server's broadcasts
+    pub fn new_session(&self) -> WebSocketSession {
+        let session = WebSocketSession::with_events(self.events.clone());
+        match &self.rate_limit {
+            Some(rate_limit) => session.with_rate_limit(rate_limit.clone()),
+            None => session,
         }
     }
 
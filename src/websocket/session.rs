@@ -1,20 +1,112 @@
-use actix::{Actor, StreamHandler, Handler, Message as ActixMessage};
+use actix::{Actor, ActorContext, AsyncContext, StreamHandler, Handler, Message as ActixMessage};
 use actix_web_actors::ws;
 use std::time::Instant;
+use super::events::{EventBus, StreamEvent};
 use super::messages::{ClientMessage, ServerMessage};
+use crate::middleware::{RateLimitConfig, RateLimiter};
+
+/// Key duy nhất trong limiter riêng của mỗi session
+const RATE_LIMIT_KEY: &str = "session";
+
+/// What a session does with a message over its rate limit
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RateLimitAction {
+    /// Ignore the message and keep the connection
+    Drop,
+    /// Close with 1008 (policy violation)
+    Close,
+}
+
+/// Per-session limit on inbound client messages
+#[derive(Debug, Clone)]
+pub struct MessageRateLimit {
+    pub limit: RateLimitConfig,
+    pub on_exceeded: RateLimitAction,
+}
 
 /// WebSocket session
 pub struct WebSocketSession {
     /// Client must send ping at least once per 10 seconds
     hb: Instant,
+    /// Broadcasts forwarded to this client
+    events: Option<EventBus>,
+    /// `sub` of the JWT presented at handshake
+    user_id: Option<String>,
+    rate_limit: Option<(RateLimiter, RateLimitAction)>,
 }
 
 impl WebSocketSession {
     pub fn new() -> Self {
-        Self { hb: Instant::now() }
+        Self {
+            hb: Instant::now(),
+            events: None,
+            user_id: None,
+            rate_limit: None,
+        }
+    }
+
+    pub fn with_events(events: EventBus) -> Self {
+        Self {
+            events: Some(events),
+            ..Self::new()
+        }
+    }
+
+    /// Attach the user authenticated during the handshake
+    pub fn with_user(mut self, user_id: impl Into<String>) -> Self {
+        self.user_id = Some(user_id.into());
+        self
+    }
+
+    pub fn user_id(&self) -> Option<&str> {
+        self.user_id.as_deref()
+    }
+
+    /// Limit inbound messages; each session gets its own budget
+    pub fn with_rate_limit(mut self, rate_limit: MessageRateLimit) -> Self {
+        self.rate_limit = Some((RateLimiter::new(rate_limit.limit), rate_limit.on_exceeded));
+        self
+    }
+
+    /// `false` if the message must not be handled; closes the session when
+    /// the policy says so
+    fn allow_message(&mut self, ctx: &mut ws::WebsocketContext<Self>) -> bool {
+        let Some((limiter, action)) = &self.rate_limit else {
+            return true;
+        };
+        let Err((retry_after, _)) = limiter.check_rate_limit(RATE_LIMIT_KEY) else {
+            return true;
+        };
+
+        match action {
+            RateLimitAction::Drop => {
+                tracing::debug!(user_id = ?self.user_id, retry_after, "WebSocket message dropped by rate limit");
+            }
+            RateLimitAction::Close => {
+                tracing::warn!(user_id = ?self.user_id, "WebSocket session closed: rate limit exceeded");
+                ctx.close(Some(ws::CloseReason {
+                    code: ws::CloseCode::Policy,
+                    description: Some("Rate limit exceeded".to_string()),
+                }));
+                ctx.stop();
+            }
+        }
+        false
     }
 
     fn handle_client_message(&mut self, msg: ClientMessage, ctx: &mut ws::WebsocketContext<Self>) {
+        // Ngoài ping, mọi message cần user đã xác thực
+        if self.user_id.is_none() && !matches!(msg, ClientMessage::Ping) {
+            let error = ServerMessage::Error {
+                message: "Authentication required".to_string(),
+            };
+            if let Ok(json) = serde_json::to_string(&error) {
+                ctx.text(json);
+            }
+            return;
+        }
+        let user_id = self.user_id.as_deref().unwrap_or_default();
+
         match msg {
             ClientMessage::Ping => {
                 let response = ServerMessage::Pong;
@@ -23,21 +115,21 @@ impl WebSocketSession {
                 }
             }
             ClientMessage::Subscribe { topic } => {
-                tracing::info!("Client subscribed to topic: {}", topic);
+                tracing::info!(user_id = %user_id, "Client subscribed to topic: {}", topic);
                 let response = ServerMessage::Subscribed { topic };
                 if let Ok(json) = serde_json::to_string(&response) {
                     ctx.text(json);
                 }
             }
             ClientMessage::Unsubscribe { topic } => {
-                tracing::info!("Client unsubscribed from topic: {}", topic);
+                tracing::info!(user_id = %user_id, "Client unsubscribed from topic: {}", topic);
                 let response = ServerMessage::Unsubscribed { topic };
                 if let Ok(json) = serde_json::to_string(&response) {
                     ctx.text(json);
                 }
             }
             ClientMessage::Message { topic, payload } => {
-                tracing::info!("Received message on topic {}: {:?}", topic, payload);
+                tracing::info!(user_id = %user_id, "Received message on topic {}: {:?}", topic, payload);
                 // Echo back for demo
                 let response = ServerMessage::Message { topic, payload };
                 if let Ok(json) = serde_json::to_string(&response) {
@@ -57,8 +149,11 @@ impl Default for WebSocketSession {
 impl Actor for WebSocketSession {
     type Context = ws::WebsocketContext<Self>;
 
-    fn started(&mut self, _ctx: &mut Self::Context) {
+    fn started(&mut self, ctx: &mut Self::Context) {
         tracing::info!("WebSocket session started");
+        if let Some(events) = &self.events {
+            ctx.add_stream(events.subscribe(None));
+        }
     }
 
     fn stopped(&mut self, _: &mut Self::Context) {
@@ -78,6 +173,9 @@ impl StreamHandler<Result<ws::Message, ws::ProtocolError>> for WebSocketSession
             }
             Ok(ws::Message::Text(text)) => {
                 self.hb = Instant::now();
+                if !self.allow_message(ctx) {
+                    return;
+                }
                 if let Ok(client_msg) = serde_json::from_str::<ClientMessage>(&text) {
                     self.handle_client_message(client_msg, ctx);
                 } else {
@@ -115,3 +213,14 @@ impl Handler<BroadcastMessage> for WebSocketSession {
     }
 }
 
+
+impl StreamHandler<StreamEvent> for WebSocketSession {
+    fn handle(&mut self, event: StreamEvent, ctx: &mut Self::Context) {
+        if let Ok(json) = serde_json::to_string(&event.message) {
+            ctx.text(json);
+        }
+    }
+
+    // Event stream kết thúc không có nghĩa là đóng kết nối WebSocket
+    fn finished(&mut self, _ctx: &mut Self::Context) {}
+}
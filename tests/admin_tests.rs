@@ -0,0 +1,316 @@
+#[cfg(all(test, feature = "observability-metrics"))]
+mod admin_jobs_tests {
+    use actix_web::{test, web, App};
+    use async_trait::async_trait;
+    use rust_template::auth::{AuthMiddleware, JwtManager};
+    use rust_template::errors::ApiError;
+    use rust_template::handlers::JobsState;
+    use rust_template::jobs::{Job, JobExecutor, JobResult, JobScheduler, JobStatus, Schedule};
+    use rust_template::metrics::MetricsCollector;
+    use rust_template::routes::configure_admin_routes;
+    use serde_json::Value;
+    use std::collections::HashMap;
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    const JWT_SECRET: &str = "test-secret-key-with-at-least-32-chars";
+
+    struct SendReport;
+
+    #[async_trait]
+    impl Job for SendReport {
+        async fn execute(&self) -> Result<JobResult, ApiError> {
+            Ok(JobResult {
+                success: true,
+                message: None,
+                data: None,
+            })
+        }
+
+        fn job_type(&self) -> &str {
+            "send_report"
+        }
+    }
+
+    async fn wait_until_finished(executor: &JobExecutor, job_id: &str) {
+        for _ in 0..100 {
+            let status = executor.get_job_status(job_id).map(|job| job.status);
+            if matches!(status, Some(JobStatus::Completed | JobStatus::Failed)) {
+                return;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        panic!("job {} did not finish", job_id);
+    }
+
+    fn jobs_state(executor: Arc<JobExecutor>, scheduler: JobScheduler) -> web::Data<JobsState> {
+        web::Data::new(JobsState { executor, scheduler })
+    }
+
+    #[actix_web::test]
+    async fn test_completed_job_is_counted_and_listed() {
+        let metrics = MetricsCollector::new();
+        let executor = Arc::new(JobExecutor::new().with_metrics(metrics.clone()));
+        let scheduler = JobScheduler::new();
+        scheduler.schedule("send_report".to_string(), Schedule::Interval(chrono::Duration::hours(1)));
+
+        let job_id = executor.submit(SendReport).await.unwrap();
+        wait_until_finished(&executor, &job_id).await;
+
+        let succeeded = metrics.jobs_total.with_label_values(&["send_report", "success"]);
+        assert_eq!(succeeded.get(), 1);
+
+        let jwt = JwtManager::new(JWT_SECRET.to_string(), 1);
+        let token = jwt.create_token("admin-1", "admin@example.com", "admin").unwrap();
+        let app = test::init_service(
+            App::new().service(
+                web::scope("/admin")
+                    .wrap(AuthMiddleware::new(jwt))
+                    .app_data(jobs_state(executor, scheduler))
+                    .configure(configure_admin_routes),
+            ),
+        )
+        .await;
+
+        let req = test::TestRequest::get()
+            .uri("/admin/jobs")
+            .insert_header(("Authorization", format!("Bearer {}", token)))
+            .to_request();
+        let body: Value = test::call_and_read_body_json(&app, req).await;
+
+        let job = &body["data"]["jobs"][0];
+        assert_eq!(body["data"]["count"], 1);
+        assert_eq!(job["name"], "send_report");
+        assert_eq!(job["status"], "COMPLETED");
+        assert_eq!(job["runs"], 1);
+        assert!(job["last_run_at"].is_string());
+        assert!(job["next_run_at"].is_string());
+    }
+
+    #[actix_web::test]
+    async fn test_scheduler_dispatches_due_jobs_to_executor() {
+        let executor = Arc::new(JobExecutor::new());
+        let scheduler = JobScheduler::new();
+        scheduler.schedule("send_report".to_string(), Schedule::once_after(chrono::Duration::zero()));
+
+        let mut jobs: HashMap<String, Arc<dyn Job>> = HashMap::new();
+        jobs.insert("send_report".to_string(), Arc::new(SendReport));
+        let handle = scheduler.start_dispatch(Duration::from_millis(10), executor.clone(), jobs);
+
+        let mut job_id = None;
+        for _ in 0..100 {
+            if let Some(job) = executor.list_jobs().into_iter().next() {
+                job_id = Some(job.id);
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        handle.abort();
+
+        let job_id = job_id.expect("scheduled job was not submitted");
+        wait_until_finished(&executor, &job_id).await;
+        let job = executor.get_job_status(&job_id).unwrap();
+        assert_eq!(job.job_type, "send_report");
+        assert!(matches!(job.status, JobStatus::Completed));
+    }
+
+    #[actix_web::test]
+    async fn test_listing_requires_jobs_permission() {
+        let jwt = JwtManager::new(JWT_SECRET.to_string(), 1);
+        let token = jwt.create_token("user-1", "user@example.com", "user").unwrap();
+        let app = test::init_service(
+            App::new().service(
+                web::scope("/admin")
+                    .wrap(AuthMiddleware::new(jwt))
+                    .app_data(jobs_state(Arc::new(JobExecutor::new()), JobScheduler::new()))
+                    .configure(configure_admin_routes),
+            ),
+        )
+        .await;
+
+        let req = test::TestRequest::get()
+            .uri("/admin/jobs")
+            .insert_header(("Authorization", format!("Bearer {}", token)))
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), 403);
+
+        // AuthMiddleware từ chối trước khi tới handler
+        let req = test::TestRequest::get().uri("/admin/jobs").to_request();
+        let err = test::try_call_service(&app, req).await.unwrap_err();
+        assert_eq!(err.as_response_error().status_code(), 401);
+    }
+}
+
+#[cfg(test)]
+mod admin_flags_tests {
+    use actix_web::{test, web, App};
+    use rust_template::auth::{AuthMiddleware, JwtManager};
+    use rust_template::features::{FeatureFlag, FeatureFlagManager};
+    use rust_template::handlers::FeatureFlagsState;
+    use rust_template::routes::configure_admin_routes;
+    use rust_template::security::{AuditEventType, AuditLogger};
+    use serde_json::{json, Value};
+    use std::sync::Arc;
+
+    const JWT_SECRET: &str = "test-secret-key-with-at-least-32-chars";
+
+    fn state() -> FeatureFlagsState {
+        let flags = FeatureFlagManager::new();
+        flags.add_flag(FeatureFlag {
+            name: "new_checkout".to_string(),
+            enabled: false,
+            description: "Checkout v2".to_string(),
+            rollout_percentage: 100,
+        });
+        FeatureFlagsState {
+            flags,
+            audit: Arc::new(AuditLogger::new(100)),
+        }
+    }
+
+    fn token(role: &str) -> String {
+        JwtManager::new(JWT_SECRET.to_string(), 1)
+            .create_token("admin-1", "admin@example.com", role)
+            .unwrap()
+    }
+
+    macro_rules! app {
+        ($state:expr) => {
+            test::init_service(
+                App::new().service(
+                    web::scope("/admin")
+                        .wrap(AuthMiddleware::new(JwtManager::new(JWT_SECRET.to_string(), 1)))
+                        .app_data(web::Data::new($state.clone()))
+                        .configure(configure_admin_routes),
+                ),
+            )
+            .await
+        };
+    }
+
+    #[actix_web::test]
+    async fn test_toggling_a_flag_changes_is_enabled() {
+        let state = state();
+        let app = app!(state);
+        assert!(!state.flags.is_enabled("new_checkout"));
+
+        let req = test::TestRequest::put()
+            .uri("/admin/flags/new_checkout")
+            .insert_header(("Authorization", format!("Bearer {}", token("admin"))))
+            .set_json(json!({ "enabled": true, "rollout_percentage": 25 }))
+            .to_request();
+        let body: Value = test::call_and_read_body_json(&app, req).await;
+
+        assert_eq!(body["data"]["enabled"], true);
+        assert_eq!(body["data"]["rollout_percentage"], 25);
+        assert_eq!(body["data"]["description"], "Checkout v2");
+        assert!(state.flags.is_enabled("new_checkout"));
+
+        let events = state.audit.get_events_by_user("admin-1", 10);
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].event_type, AuditEventType::ConfigurationChange);
+        assert_eq!(events[0].action, "feature_flag.update");
+        assert_eq!(events[0].resource.as_deref(), Some("feature_flag:new_checkout"));
+
+        let req = test::TestRequest::get()
+            .uri("/admin/flags")
+            .insert_header(("Authorization", format!("Bearer {}", token("admin"))))
+            .to_request();
+        let body: Value = test::call_and_read_body_json(&app, req).await;
+        assert_eq!(body["data"]["count"], 1);
+        assert_eq!(body["data"]["flags"][0]["enabled"], true);
+    }
+
+    #[actix_web::test]
+    async fn test_put_creates_and_delete_removes() {
+        let state = state();
+        let app = app!(state);
+
+        let req = test::TestRequest::put()
+            .uri("/admin/flags/dark_mode")
+            .insert_header(("Authorization", format!("Bearer {}", token("admin"))))
+            .set_json(json!({ "enabled": true }))
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), 200);
+        assert!(state.flags.is_enabled("dark_mode"));
+
+        let req = test::TestRequest::delete()
+            .uri("/admin/flags/dark_mode")
+            .insert_header(("Authorization", format!("Bearer {}", token("admin"))))
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), 204);
+        assert!(!state.flags.is_enabled("dark_mode"));
+        assert!(state.flags.get_flag("dark_mode").is_none());
+
+        let actions: Vec<String> = state
+            .audit
+            .get_events_by_user("admin-1", 10)
+            .into_iter()
+            .map(|e| e.action)
+            .collect();
+        assert!(actions.contains(&"feature_flag.create".to_string()));
+        assert!(actions.contains(&"feature_flag.delete".to_string()));
+
+        let req = test::TestRequest::delete()
+            .uri("/admin/flags/dark_mode")
+            .insert_header(("Authorization", format!("Bearer {}", token("admin"))))
+            .to_request();
+        assert_eq!(test::call_service(&app, req).await.status(), 404);
+    }
+
+    #[actix_web::test]
+    async fn test_invalid_rollout_is_rejected() {
+        let state = state();
+        let app = app!(state);
+
+        let req = test::TestRequest::put()
+            .uri("/admin/flags/new_checkout")
+            .insert_header(("Authorization", format!("Bearer {}", token("admin"))))
+            .set_json(json!({ "rollout_percentage": 150 }))
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), 422);
+        assert_eq!(state.flags.get_flag("new_checkout").unwrap().rollout_percentage, 100);
+    }
+
+    #[actix_web::test]
+    async fn test_malformed_body_names_the_field() {
+        let state = state();
+        let app = app!(state);
+
+        let req = test::TestRequest::put()
+            .uri("/admin/flags/new_checkout")
+            .insert_header(("Authorization", format!("Bearer {}", token("admin"))))
+            .set_json(json!({ "enabled": "yes" }))
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), 400);
+        let body: Value = test::read_body_json(resp).await;
+        assert!(body["message"].as_str().unwrap().contains("`enabled`"));
+    }
+
+    #[actix_web::test]
+    async fn test_changes_require_flags_permission() {
+        let state = state();
+        let app = app!(state);
+
+        let req = test::TestRequest::put()
+            .uri("/admin/flags/new_checkout")
+            .insert_header(("Authorization", format!("Bearer {}", token("user"))))
+            .set_json(json!({ "enabled": true }))
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), 403);
+        assert!(!state.flags.is_enabled("new_checkout"));
+
+        let req = test::TestRequest::put()
+            .uri("/admin/flags/new_checkout")
+            .set_json(json!({ "enabled": true }))
+            .to_request();
+        let err = test::try_call_service(&app, req).await.unwrap_err();
+        assert_eq!(err.as_response_error().status_code(), 401);
+    }
+}
@@ -1,6 +1,8 @@
 // Integration tests cho API
 // Chạy tests: cargo test
 
+mod common;
+
 #[cfg(test)]
 mod tests {
     // TODO: Thêm integration tests cho từng endpoint
@@ -45,3 +47,1527 @@ mod tests {
     }
     */
 }
+
+#[cfg(test)]
+mod user_pagination_tests {
+    use crate::common::users;
+    use actix_web::{test, web, App};
+    use rust_template::routes::configure_user_routes;
+    use rust_template::state::AppState;
+    use serde_json::Value;
+
+    async fn get_json(uri: &str, count: usize) -> (u16, Value) {
+        let app = test::init_service(
+            App::new()
+                .app_data(web::Data::new(AppState::with_users(users(count))))
+                .configure(configure_user_routes),
+        )
+        .await;
+        let resp = test::call_service(&app, test::TestRequest::get().uri(uri).to_request()).await;
+        let status = resp.status().as_u16();
+        let body: Value = test::read_body_json(resp).await;
+        (status, body)
+    }
+
+    #[actix_web::test]
+    async fn test_first_page() {
+        let (status, body) = get_json("/users?page=1&per_page=2", 5).await;
+
+        assert_eq!(status, 200);
+        let data = &body["data"];
+        assert_eq!(data["items"].as_array().unwrap().len(), 2);
+        assert_eq!(data["items"][0]["id"], "user-0");
+        assert_eq!(data["total"], 5);
+        assert_eq!(data["total_pages"], 3);
+    }
+
+    #[actix_web::test]
+    async fn test_last_partial_page() {
+        let (status, body) = get_json("/users?page=3&per_page=2", 5).await;
+
+        assert_eq!(status, 200);
+        let items = body["data"]["items"].as_array().unwrap();
+        assert_eq!(items.len(), 1);
+        assert_eq!(items[0]["id"], "user-4");
+    }
+
+    #[actix_web::test]
+    async fn test_out_of_range_page_is_empty() {
+        let (status, body) = get_json("/users?page=10&per_page=2", 5).await;
+
+        assert_eq!(status, 200);
+        let data = &body["data"];
+        assert!(data["items"].as_array().unwrap().is_empty());
+        assert_eq!(data["page"], 10);
+        assert_eq!(data["total"], 5);
+        assert_eq!(data["total_pages"], 3);
+    }
+
+    #[actix_web::test]
+    async fn test_invalid_pagination_params() {
+        let (status, body) = get_json("/users?per_page=1000", 5).await;
+        assert_eq!(status, 422);
+        assert_eq!(body["field"], "per_page");
+
+        let (status, body) = get_json("/users?page=0", 5).await;
+        assert_eq!(status, 422);
+        assert_eq!(body["field"], "page");
+    }
+}
+
+#[cfg(test)]
+mod list_query_tests {
+    use crate::common;
+    use rust_template::errors::ApiError;
+    use rust_template::models::{FilterValue, ListQuery, SortDirection, User, USER_LIST_FIELDS};
+    use rust_template::services::UserService;
+    use std::collections::HashMap;
+
+    fn params(pairs: &[(&str, &str)]) -> HashMap<String, String> {
+        pairs
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect()
+    }
+
+    fn user(name: &str, age: u32, role: &str) -> User {
+        common::user(&name.to_lowercase()).name(name).age(age).role(role).build()
+    }
+
+    #[test]
+    fn test_multi_field_sort_with_descending_prefix() {
+        let query = ListQuery::from_params(&params(&[("sort", "name,-age")]), USER_LIST_FIELDS)
+            .unwrap();
+
+        assert_eq!(query.sort.len(), 2);
+        assert_eq!(query.sort[0].column, "name");
+        assert_eq!(query.sort[0].direction, SortDirection::Asc);
+        assert_eq!(query.sort[1].column, "age");
+        assert_eq!(query.sort[1].direction, SortDirection::Desc);
+
+        let users = vec![user("Bob", 20, "user"), user("Alice", 30, "user"), user("Bob", 40, "admin")];
+        let sorted = UserService::apply_list_query(&users, &query);
+        let order: Vec<(String, u32)> = sorted.iter().map(|u| (u.name.clone(), u.age)).collect();
+
+        assert_eq!(
+            order,
+            vec![("Alice".to_string(), 30), ("Bob".to_string(), 40), ("Bob".to_string(), 20)]
+        );
+    }
+
+    #[test]
+    fn test_filter_by_whitelisted_field() {
+        let query = ListQuery::from_params(&params(&[("filter[role]", "admin")]), USER_LIST_FIELDS)
+            .unwrap();
+
+        let users = vec![user("Alice", 30, "user"), user("Bob", 40, "admin")];
+        let filtered = UserService::apply_list_query(&users, &query);
+
+        assert_eq!(filtered.len(), 1);
+        assert_eq!(filtered[0].name, "Bob");
+    }
+
+    #[test]
+    fn test_filters_compare_typed_values() {
+        let mut alice = user("Alice", 30, "user");
+        alice.created_at = "2024-05-01T10:00:00.123456789Z".parse().unwrap();
+        let users = vec![alice, user("Bob", 40, "admin")];
+
+        // Cùng thời điểm viết khác dạng, ở độ chính xác micro giây như Postgres
+        for created_at in ["2024-05-01T10:00:00.123456Z", "2024-05-01T17:00:00.123456+07:00"] {
+            let params = params(&[("filter[created_at]", created_at)]);
+            let query = ListQuery::from_params(&params, USER_LIST_FIELDS).unwrap();
+            let filtered = UserService::apply_list_query(&users, &query);
+            assert_eq!(filtered.len(), 1, "{}", created_at);
+            assert_eq!(filtered[0].name, "Alice");
+        }
+
+        let query = ListQuery::from_params(&params(&[("filter[age]", " 40")]), USER_LIST_FIELDS)
+            .unwrap();
+        assert_eq!(query.filters[0].value, FilterValue::Integer(40));
+        assert_eq!(UserService::apply_list_query(&users, &query)[0].name, "Bob");
+
+        for (key, value) in [("filter[age]", "forty"), ("filter[created_at]", "yesterday")] {
+            let err = ListQuery::from_params(&params(&[(key, value)]), USER_LIST_FIELDS)
+                .unwrap_err();
+            assert!(
+                matches!(err, ApiError::ValidationError { field: Some(ref f), .. } if f == key)
+            );
+        }
+    }
+
+    #[test]
+    fn test_non_whitelisted_fields_are_rejected() {
+        let sort = ListQuery::from_params(&params(&[("sort", "password_hash")]), USER_LIST_FIELDS);
+        assert!(matches!(sort, Err(ApiError::ValidationError { .. })));
+
+        let filter = ListQuery::from_params(
+            &params(&[("filter[name; DROP TABLE users]", "x")]),
+            USER_LIST_FIELDS,
+        );
+        assert!(matches!(filter, Err(ApiError::ValidationError { .. })));
+    }
+
+    #[cfg(feature = "database-postgres")]
+    #[test]
+    fn test_sql_fragment_uses_bind_parameters() {
+        let query = ListQuery::from_params(
+            &params(&[("sort", "-created_at"), ("filter[email]", "a@example.com")]),
+            USER_LIST_FIELDS,
+        )
+        .unwrap();
+
+        let mut builder = sqlx::QueryBuilder::<sqlx::Postgres>::new(
+            "SELECT * FROM users WHERE deleted_at IS NULL",
+        );
+        query.push_sql(&mut builder, "id");
+
+        assert_eq!(
+            builder.sql(),
+            "SELECT * FROM users WHERE deleted_at IS NULL AND email = $1 \
+             ORDER BY created_at DESC, id"
+        );
+    }
+}
+
+#[cfg(test)]
+mod api_response_tests {
+    use actix_web::{test, web, App};
+    use rust_template::middleware::RequestId;
+    use rust_template::models::ApiResponse;
+    use rust_template::routes::configure_health_routes;
+    use serde_json::json;
+
+    #[test]
+    fn test_success_is_backward_compatible() {
+        let body = serde_json::to_value(ApiResponse::success("ok", 1)).unwrap();
+
+        assert_eq!(body, json!({ "success": true, "message": "ok", "data": 1 }));
+    }
+
+    #[test]
+    fn test_success_with_meta() {
+        let response = ApiResponse::success_with_meta("ok", vec![1, 2], json!({ "total": 2 }))
+            .with_request_id("req-1");
+        let body = serde_json::to_value(response).unwrap();
+
+        assert_eq!(body["meta"]["total"], 2);
+        assert_eq!(body["request_id"], "req-1");
+    }
+
+    #[actix_web::test]
+    async fn test_request_id_is_populated_by_middleware() {
+        let app = test::init_service(
+            App::new()
+                .wrap(RequestId)
+                .configure(configure_health_routes),
+        )
+        .await;
+        let req = test::TestRequest::get()
+            .uri("/health/live")
+            .insert_header(("X-Request-Id", "req-live"))
+            .to_request();
+
+        let body: serde_json::Value = test::call_and_read_body_json(&app, req).await;
+        assert_eq!(body["request_id"], "req-live");
+    }
+}
+
+#[cfg(test)]
+mod soft_delete_tests {
+    use crate::common::user;
+    use actix_web::{test, web, App};
+    use rust_template::routes::configure_user_routes;
+    use rust_template::state::AppState;
+    use serde_json::Value;
+
+    fn state() -> web::Data<AppState> {
+        web::Data::new(AppState::with_users(vec![user("user-1")
+            .name("Alice")
+            .email("alice@example.com")
+            .build()]))
+    }
+
+    #[actix_web::test]
+    async fn test_deleted_user_is_hidden_and_can_be_restored() {
+        let data = state();
+        let app = test::init_service(App::new().app_data(data.clone()).configure(configure_user_routes)).await;
+
+        let resp = test::call_service(&app, test::TestRequest::delete().uri("/users/user-1").to_request()).await;
+        assert_eq!(resp.status(), 200);
+
+        // Record is kept, only marked as deleted
+        let stored = data.users.find_by_id("user-1").await.unwrap().unwrap();
+        assert!(stored.deleted_at.is_some());
+
+        let body: Value = test::call_and_read_body_json(&app, test::TestRequest::get().uri("/users").to_request()).await;
+        assert_eq!(body["data"]["total"], 0);
+
+        let resp = test::call_service(&app, test::TestRequest::get().uri("/users/user-1").to_request()).await;
+        assert_eq!(resp.status(), 404);
+
+        let body: Value = test::call_and_read_body_json(
+            &app,
+            test::TestRequest::get().uri("/users?include_deleted=true").to_request(),
+        )
+        .await;
+        assert_eq!(body["data"]["total"], 1);
+
+        let resp = test::call_service(
+            &app,
+            test::TestRequest::post().uri("/users/user-1/restore").to_request(),
+        )
+        .await;
+        assert_eq!(resp.status(), 200);
+
+        let body: Value = test::call_and_read_body_json(&app, test::TestRequest::get().uri("/users").to_request()).await;
+        assert_eq!(body["data"]["total"], 1);
+        assert!(body["data"]["items"][0]["deleted_at"].is_null());
+    }
+
+    #[actix_web::test]
+    async fn test_deleting_twice_is_not_found() {
+        let app = test::init_service(App::new().app_data(state()).configure(configure_user_routes)).await;
+
+        let resp = test::call_service(&app, test::TestRequest::delete().uri("/users/user-1").to_request()).await;
+        assert_eq!(resp.status(), 200);
+
+        let resp = test::call_service(&app, test::TestRequest::delete().uri("/users/user-1").to_request()).await;
+        assert_eq!(resp.status(), 404);
+    }
+
+    #[actix_web::test]
+    async fn test_invalid_include_deleted_is_rejected() {
+        let app = test::init_service(App::new().app_data(state()).configure(configure_user_routes)).await;
+
+        let resp = test::call_service(
+            &app,
+            test::TestRequest::get().uri("/users?include_deleted=maybe").to_request(),
+        )
+        .await;
+        assert_eq!(resp.status(), 422);
+    }
+}
+
+#[cfg(test)]
+mod validator_tests {
+    use rust_template::errors::ApiError;
+    use rust_template::utils::{JsonSchema, Validator};
+    use serde_json::json;
+
+    #[test]
+    fn test_email_cases() {
+        let cases = [
+            ("a@b.c", true),
+            ("user+tag@sub.domain.com", true),
+            ("first.last@example.com", true),
+            ("o'reilly@example.ie", true),
+            ("\"john doe\"@example.com", true),
+            ("\"john@home\"@example.com", true),
+            ("\"escaped\\\"quote\"@example.com", true),
+            ("user@bücher.de", true),
+            ("user@例え.jp", true),
+            ("", false),
+            ("plainaddress", false),
+            ("@example.com", false),
+            ("user@", false),
+            ("user@localhost", false),
+            ("user@-example.com", false),
+            ("user@example..com", false),
+            ("user@192.168.0.1", false),
+            (".user@example.com", false),
+            ("user.@example.com", false),
+            ("us..er@example.com", false),
+            ("us er@example.com", false),
+            ("a@b@example.com", false),
+            ("\"unterminated@example.com", false),
+        ];
+
+        for (email, expected) in cases {
+            assert_eq!(Validator::is_valid_email(email), expected, "email: {:?}", email);
+        }
+
+        let long_local = format!("{}@example.com", "a".repeat(65));
+        assert!(!Validator::is_valid_email(&long_local));
+    }
+
+    #[test]
+    fn test_phone_cases() {
+        let cases = [
+            ("+84 912 345 678", "VN", Some("+84912345678")),
+            ("0912345678", "VN", Some("+84912345678")),
+            ("(202) 456-1111", "US", Some("+12024561111")),
+            ("+44 20 7946 0018", "US", Some("+442079460018")),
+            ("12345", "VN", None),
+            ("not a phone", "VN", None),
+        ];
+
+        for (number, region, expected) in cases {
+            assert_eq!(
+                Validator::is_valid_phone(number, region).as_deref(),
+                expected,
+                "phone: {:?} ({})",
+                number,
+                region
+            );
+        }
+    }
+
+    #[test]
+    fn test_validate_phone_reports_field() {
+        let err = Validator::validate_phone("123", "VN").unwrap_err();
+        assert!(err.to_string().contains("Invalid phone number"));
+    }
+
+    fn order_schema() -> serde_json::Value {
+        json!({
+            "type": "object",
+            "required": ["customer", "items"],
+            "properties": {
+                "customer": { "type": "string", "minLength": 1 },
+                "items": {
+                    "type": "array",
+                    "items": {
+                        "type": "object",
+                        "required": ["sku"],
+                        "properties": {
+                            "sku": { "type": "string" },
+                            "quantity": { "type": "integer", "minimum": 1 }
+                        }
+                    }
+                }
+            }
+        })
+    }
+
+    #[test]
+    fn test_json_schema_accepts_valid_instance() {
+        let schema = JsonSchema::compile(&order_schema()).unwrap();
+        let instance = json!({
+            "customer": "alice",
+            "items": [{ "sku": "A-1", "quantity": 2 }]
+        });
+
+        assert!(schema.validate(&instance).is_ok());
+        // Schema compile một lần, validate nhiều lần
+        assert!(schema.validate(&json!({ "customer": "bob", "items": [] })).is_ok());
+    }
+
+    #[test]
+    fn test_json_schema_reports_all_violations_in_one_error() {
+        let schema = JsonSchema::compile(&order_schema()).unwrap();
+        let instance = json!({
+            "items": [
+                { "sku": "A-1", "quantity": 0 },
+                { "quantity": 1 }
+            ]
+        });
+
+        let err = schema.validate(&instance).unwrap_err();
+        let ApiError::ValidationError { field, message, .. } = &err else {
+            panic!("expected ValidationError, got {:?}", err);
+        };
+        let mut fields: Vec<&str> = field.as_deref().unwrap().split(", ").collect();
+        fields.sort();
+
+        assert_eq!(fields, vec!["customer", "items[0].quantity", "items[1].sku"]);
+        assert!(message.contains("items[0].quantity: "));
+        assert_eq!(err.to_error_response_with(false).status_code, 422);
+    }
+
+    #[test]
+    fn test_json_schema_keeps_numeric_object_keys_as_properties() {
+        let schema = JsonSchema::compile(&json!({
+            "type": "object",
+            "properties": {
+                "scores": {
+                    "type": "object",
+                    "additionalProperties": { "type": "integer" }
+                },
+                "rounds": { "type": "array", "items": { "type": "integer" } }
+            }
+        }))
+        .unwrap();
+
+        let err = schema
+            .validate(&json!({ "scores": { "2024": "high" }, "rounds": [1, "two"] }))
+            .unwrap_err();
+        let ApiError::ValidationError { field, .. } = err else {
+            panic!("expected ValidationError");
+        };
+        let mut fields: Vec<String> = field.unwrap().split(", ").map(String::from).collect();
+        fields.sort();
+
+        assert_eq!(fields, vec!["rounds[1]", "scores.2024"]);
+    }
+
+    #[test]
+    fn test_json_schema_rejects_invalid_schema() {
+        let err = JsonSchema::compile(&json!({ "type": "not-a-type" })).err().unwrap();
+        assert!(matches!(err, ApiError::InternalError { .. }));
+    }
+}
+
+#[cfg(test)]
+mod email_service_tests {
+    use rust_template::services::{send_password_reset, EmailService, MockEmailService};
+
+    #[tokio::test]
+    async fn test_password_reset_email_is_addressed_to_user() {
+        let mailer = MockEmailService::new();
+
+        send_password_reset(&mailer, "alice@example.com", "https://app.example.com/reset?token=abc")
+            .await
+            .unwrap();
+
+        let sent = mailer.sent();
+        assert_eq!(sent.len(), 1);
+        assert_eq!(sent[0].to, "alice@example.com");
+        assert_eq!(sent[0].subject, "Reset your password");
+        assert!(sent[0].body_text.contains("https://app.example.com/reset?token=abc"));
+        assert!(sent[0].body_html.contains("https://app.example.com/reset?token=abc"));
+    }
+
+    #[tokio::test]
+    async fn test_mock_records_messages_in_order() {
+        let mailer = MockEmailService::new();
+
+        mailer.send("a@example.com", "First", "<p>1</p>", "1").await.unwrap();
+        mailer.send("b@example.com", "Second", "<p>2</p>", "2").await.unwrap();
+
+        let subjects: Vec<_> = mailer.sent().into_iter().map(|m| m.subject).collect();
+        assert_eq!(subjects, vec!["First", "Second"]);
+    }
+}
+
+#[cfg(all(test, feature = "storage-s3"))]
+mod storage_service_tests {
+    use actix_web::ResponseError;
+    use rust_template::config::settings::StorageSettings;
+    use rust_template::services::{S3StorageService, StorageService};
+    use std::time::Duration;
+
+    /// Client with static credentials; presigning is done locally, no network
+    fn offline_service() -> S3StorageService {
+        use aws_sdk_s3::config::{BehaviorVersion, Credentials, Region};
+
+        let config = aws_sdk_s3::Config::builder()
+            .behavior_version(BehaviorVersion::latest())
+            .region(Region::new("us-east-1"))
+            .credentials_provider(Credentials::new("AKIDTEST", "secret", None, None, "test"))
+            .build();
+        S3StorageService::from_client(aws_sdk_s3::Client::from_conf(config), "uploads", Duration::from_secs(900))
+    }
+
+    #[tokio::test]
+    async fn test_presign_get_includes_expiry_and_key() {
+        let service = offline_service();
+
+        let url = service
+            .presign_get("avatars/alice.png", Duration::from_secs(300))
+            .await
+            .unwrap();
+
+        assert!(url.contains("uploads"), "url: {}", url);
+        assert!(url.contains("avatars/alice.png"), "url: {}", url);
+        assert!(url.contains("X-Amz-Expires=300"), "url: {}", url);
+        assert!(url.contains("X-Amz-Signature="), "url: {}", url);
+    }
+
+    #[tokio::test]
+    async fn test_presign_rejects_expiry_over_seven_days() {
+        let service = offline_service();
+
+        let result = service
+            .presign_get("avatars/alice.png", Duration::from_secs(8 * 24 * 60 * 60))
+            .await;
+
+        assert!(result.is_err());
+    }
+
+    /// Chạy với LocalStack: S3_ENDPOINT=http://localhost:4566 S3_BUCKET=test-bucket
+    #[tokio::test]
+    async fn test_localstack_round_trip() {
+        let Ok(endpoint) = std::env::var("S3_ENDPOINT") else {
+            return;
+        };
+        let settings = StorageSettings {
+            s3_enabled: true,
+            aws_region: "us-east-1".to_string(),
+            s3_bucket: std::env::var("S3_BUCKET").unwrap_or_else(|_| "test-bucket".to_string()),
+            s3_endpoint: Some(endpoint),
+            presign_expiry_secs: 900,
+        };
+        let service = S3StorageService::new(&settings).await;
+        let key = format!("tests/{}.txt", uuid::Uuid::new_v4());
+
+        service
+            .put_object(&key, b"hello".to_vec(), Some("text/plain"))
+            .await
+            .unwrap();
+        assert_eq!(service.get_object(&key).await.unwrap(), b"hello");
+
+        service.delete_object(&key).await.unwrap();
+        let err = service.get_object(&key).await.unwrap_err();
+        assert_eq!(err.status_code(), actix_web::http::StatusCode::NOT_FOUND);
+    }
+}
+
+#[cfg(test)]
+mod user_repository_tests {
+    use crate::common;
+    use rust_template::errors::ApiError;
+    use rust_template::models::User;
+    use rust_template::services::{InMemoryUserRepository, UserRepository};
+
+    fn user(id: &str, email: &str) -> User {
+        common::user(id).email(email).build()
+    }
+
+    #[tokio::test]
+    async fn test_create_and_find() {
+        let repo = InMemoryUserRepository::new();
+
+        repo.create(user("u1", "a@example.com")).await.unwrap();
+        repo.create(user("u2", "b@example.com")).await.unwrap();
+
+        assert_eq!(repo.find_all().await.unwrap().len(), 2);
+        assert_eq!(repo.find_by_id("u2").await.unwrap().unwrap().email, "b@example.com");
+        assert!(repo.find_by_id("missing").await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_duplicate_email_conflicts() {
+        let repo = InMemoryUserRepository::with_users(vec![user("u1", "a@example.com")]);
+
+        let err = repo.create(user("u2", "a@example.com")).await.unwrap_err();
+        assert!(matches!(err, ApiError::Conflict { .. }));
+
+        repo.create(user("u2", "b@example.com")).await.unwrap();
+        let err = repo.update(user("u2", "a@example.com")).await.unwrap_err();
+        assert!(matches!(err, ApiError::Conflict { .. }));
+    }
+
+    #[actix_web::test]
+    async fn test_duplicate_email_over_http_is_409_with_field() {
+        use actix_web::{test, web, App};
+        use rust_template::routes::configure_user_routes;
+        use rust_template::state::AppState;
+        use serde_json::{json, Value};
+
+        let state = AppState::with_users(vec![user("u1", "taken@example.com")]);
+        let app = test::init_service(
+            App::new()
+                .app_data(web::Data::new(state))
+                .configure(configure_user_routes),
+        )
+        .await;
+
+        let req = test::TestRequest::post()
+            .uri("/users")
+            .set_json(json!({
+                "name": "Someone",
+                "email": "taken@example.com",
+                "age": 25,
+                "password": "SecurePass123!"
+            }))
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+
+        assert_eq!(resp.status(), 409);
+        let body: Value = test::read_body_json(resp).await;
+        assert_eq!(body["error_code"], "Conflict");
+        assert_eq!(body["field"], "email");
+    }
+
+    #[tokio::test]
+    async fn test_update_replaces_stored_user() {
+        let repo = InMemoryUserRepository::with_users(vec![user("u1", "a@example.com")]);
+
+        let mut updated = user("u1", "a@example.com");
+        updated.name = "Renamed".to_string();
+        repo.update(updated).await.unwrap();
+
+        assert_eq!(repo.find_by_id("u1").await.unwrap().unwrap().name, "Renamed");
+        assert!(repo.update(user("missing", "c@example.com")).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_delete_removes_user() {
+        let repo = InMemoryUserRepository::with_users(vec![user("u1", "a@example.com")]);
+
+        assert!(repo.delete("u1").await.unwrap());
+        assert!(!repo.delete("u1").await.unwrap());
+        assert!(repo.find_all().await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_find_by_email_ignores_case() {
+        let repo = InMemoryUserRepository::with_users(vec![user("u1", "Alice@Example.com")]);
+
+        assert_eq!(repo.find_by_email("alice@example.com").await.unwrap().unwrap().id, "u1");
+        assert!(repo.find_by_email("bob@example.com").await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_update_if_unmodified_rejects_stale_version() {
+        let repo = InMemoryUserRepository::with_users(vec![user("u1", "a@example.com")]);
+        let read = repo.find_by_id("u1").await.unwrap().unwrap();
+
+        // Một request khác cập nhật user sau khi `read` được đọc
+        let mut other = read.clone();
+        other.name = "Other".to_string();
+        other.updated_at = read.updated_at + chrono::Duration::seconds(1);
+        repo.update_if_unmodified(other, read.updated_at).await.unwrap();
+
+        let mut stale = read.clone();
+        stale.name = "Stale".to_string();
+        let err = repo.update_if_unmodified(stale, read.updated_at).await.unwrap_err();
+        assert!(matches!(err, ApiError::Conflict { .. }));
+        assert_eq!(repo.find_by_id("u1").await.unwrap().unwrap().name, "Other");
+    }
+
+    #[tokio::test]
+    async fn test_linked_identity_finds_user() {
+        let repo = InMemoryUserRepository::with_users(vec![user("u1", "a@example.com")]);
+
+        assert!(repo.find_by_identity("github", "42").await.unwrap().is_none());
+        repo.link_identity("github", "42", "u1").await.unwrap();
+        assert_eq!(repo.find_by_identity("github", "42").await.unwrap().unwrap().id, "u1");
+
+        repo.delete("u1").await.unwrap();
+        assert!(repo.find_by_identity("github", "42").await.unwrap().is_none());
+    }
+}
+#[cfg(test)]
+mod write_path_read_tests {
+    use crate::common::user;
+    use actix_web::{test, web, App};
+    use async_trait::async_trait;
+    use chrono::{DateTime, Utc};
+    use futures_util::stream::BoxStream;
+    use rust_template::errors::ApiResult;
+    use rust_template::models::{ListQuery, Paginated, Pagination, User};
+    use rust_template::routes::configure_user_routes;
+    use rust_template::services::{BatchCreateError, InMemoryUserRepository, UserRepository};
+    use rust_template::state::AppState;
+    use serde_json::json;
+    use std::sync::Arc;
+
+    /// Replica chưa kịp thấy user: `find_by_id` không tìm thấy, primary thì có
+    struct LaggingReplica(InMemoryUserRepository);
+
+    #[async_trait]
+    impl UserRepository for LaggingReplica {
+        async fn find_all(&self) -> ApiResult<Vec<User>> {
+            Ok(Vec::new())
+        }
+
+        async fn find_page(
+            &self,
+            _query: &ListQuery,
+            pagination: Pagination,
+            _include_deleted: bool,
+        ) -> ApiResult<Paginated<User>> {
+            Ok(Paginated::new(Vec::new(), 0, pagination))
+        }
+
+        async fn find_by_id(&self, _id: &str) -> ApiResult<Option<User>> {
+            Ok(None)
+        }
+
+        async fn find_by_id_for_update(&self, id: &str) -> ApiResult<Option<User>> {
+            self.0.find_by_id(id).await
+        }
+
+        async fn find_by_email(&self, email: &str) -> ApiResult<Option<User>> {
+            self.0.find_by_email(email).await
+        }
+
+        async fn create(&self, user: User) -> ApiResult<User> {
+            self.0.create(user).await
+        }
+
+        async fn update(&self, user: User) -> ApiResult<User> {
+            self.0.update(user).await
+        }
+
+        async fn update_if_unmodified(
+            &self,
+            user: User,
+            expected_updated_at: DateTime<Utc>,
+        ) -> ApiResult<User> {
+            self.0.update_if_unmodified(user, expected_updated_at).await
+        }
+
+        async fn find_by_identity(
+            &self,
+            provider: &str,
+            provider_user_id: &str,
+        ) -> ApiResult<Option<User>> {
+            self.0.find_by_identity(provider, provider_user_id).await
+        }
+
+        async fn link_identity(
+            &self,
+            provider: &str,
+            provider_user_id: &str,
+            user_id: &str,
+        ) -> ApiResult<()> {
+            self.0.link_identity(provider, provider_user_id, user_id).await
+        }
+
+        async fn delete(&self, id: &str) -> ApiResult<bool> {
+            self.0.delete(id).await
+        }
+
+        async fn create_all(&self, users: Vec<User>) -> Result<Vec<User>, BatchCreateError> {
+            self.0.create_all(users).await
+        }
+
+        fn stream_all(&self) -> BoxStream<'static, ApiResult<User>> {
+            self.0.stream_all()
+        }
+    }
+
+    #[actix_web::test]
+    async fn test_update_delete_and_restore_read_from_primary() {
+        let primary =
+            InMemoryUserRepository::with_users(vec![user("u1").email("a@example.com").build()]);
+        let state = AppState::with_user_repository(Arc::new(LaggingReplica(primary.clone())));
+        let app = test::init_service(
+            App::new()
+                .app_data(web::Data::new(state))
+                .configure(configure_user_routes),
+        )
+        .await;
+
+        let req = test::TestRequest::put()
+            .uri("/users/u1")
+            .set_json(json!({ "name": "Renamed" }))
+            .to_request();
+        assert_eq!(test::call_service(&app, req).await.status(), 200);
+        assert_eq!(primary.find_by_id("u1").await.unwrap().unwrap().name, "Renamed");
+
+        let req = test::TestRequest::delete().uri("/users/u1").to_request();
+        assert_eq!(test::call_service(&app, req).await.status(), 200);
+        assert!(primary.find_by_id("u1").await.unwrap().unwrap().is_deleted());
+
+        let req = test::TestRequest::post().uri("/users/u1/restore").to_request();
+        assert_eq!(test::call_service(&app, req).await.status(), 200);
+        assert!(!primary.find_by_id("u1").await.unwrap().unwrap().is_deleted());
+    }
+}
+
+#[cfg(test)]
+mod batch_create_tests {
+    use actix_web::{test, web, App};
+    use rust_template::routes::configure_user_routes;
+    use rust_template::state::AppState;
+    use serde_json::{json, Value};
+
+    fn new_user(name: &str, email: &str, age: u32) -> Value {
+        json!({ "name": name, "email": email, "password": "SecurePass123!", "age": age })
+    }
+
+    async fn post_batch(data: web::Data<AppState>, uri: &str, body: Value) -> (u16, Value) {
+        let app = test::init_service(App::new().app_data(data).configure(configure_user_routes)).await;
+        let req = test::TestRequest::post().uri(uri).set_json(body).to_request();
+        let resp = test::call_service(&app, req).await;
+        let status = resp.status().as_u16();
+        (status, test::read_body_json(resp).await)
+    }
+
+    #[actix_web::test]
+    async fn test_all_valid_returns_201() {
+        let data = web::Data::new(AppState::new());
+        let body = json!([new_user("Alice", "alice@example.com", 30), new_user("Bob", "bob@example.com", 40)]);
+
+        let (status, body) = post_batch(data.clone(), "/users/batch", body).await;
+
+        assert_eq!(status, 201);
+        assert_eq!(body["data"]["created"].as_array().unwrap().len(), 2);
+        assert!(body["data"]["errors"].as_array().unwrap().is_empty());
+        assert_eq!(data.users.find_all().await.unwrap().len(), 2);
+    }
+
+    #[actix_web::test]
+    async fn test_mixed_batch_reports_errors_by_index() {
+        let data = web::Data::new(AppState::new());
+        let body = json!([
+            new_user("Alice", "alice@example.com", 30),
+            new_user("Bob", "not-an-email", 40),
+            new_user("Carol", "carol@example.com", 200),
+            new_user("Alice Again", "alice@example.com", 31),
+            new_user("Dave", "dave@example.com", 50),
+        ]);
+
+        let (status, body) = post_batch(data.clone(), "/users/batch", body).await;
+
+        assert_eq!(status, 207);
+        let created: Vec<&str> = body["data"]["created"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|u| u["email"].as_str().unwrap())
+            .collect();
+        assert!(created.contains(&"dave@example.com"));
+        assert_eq!(created.len(), 2);
+
+        let errors = body["data"]["errors"].as_array().unwrap();
+        let failed: Vec<u64> = errors.iter().map(|e| e["index"].as_u64().unwrap()).collect();
+        assert_eq!(failed.len(), 3);
+        assert!(failed.starts_with(&[1, 2]));
+        assert_eq!(errors[1]["error"]["field"], "age");
+        assert_eq!(data.users.find_all().await.unwrap().len(), 2);
+    }
+
+    #[actix_web::test]
+    async fn test_all_or_nothing_rolls_back_on_conflict() {
+        let data = web::Data::new(AppState::new());
+        post_batch(data.clone(), "/users", new_user("Existing", "taken@example.com", 30)).await;
+
+        let body = json!([
+            new_user("Alice", "alice@example.com", 30),
+            new_user("Taken", "taken@example.com", 40),
+        ]);
+        let (status, body) = post_batch(data.clone(), "/users/batch?all_or_nothing=true", body).await;
+
+        assert_eq!(status, 409);
+        assert_eq!(body["success"], false);
+        assert!(body["data"]["created"].as_array().unwrap().is_empty());
+        assert_eq!(body["data"]["errors"][0]["index"], 1);
+        assert_eq!(body["data"]["errors"][0]["error"]["error_code"], "Conflict");
+
+        // Alice was not stored even though her record was valid
+        let users = data.users.find_all().await.unwrap();
+        assert_eq!(users.len(), 1);
+        assert_eq!(users[0].email, "taken@example.com");
+    }
+
+    #[actix_web::test]
+    async fn test_all_or_nothing_stops_on_validation_error() {
+        let data = web::Data::new(AppState::new());
+        let body = json!([new_user("Alice", "alice@example.com", 30), new_user("B", "bob@example.com", 40)]);
+
+        let (status, body) = post_batch(data.clone(), "/users/batch?all_or_nothing=true", body).await;
+
+        assert_eq!(status, 422);
+        assert_eq!(body["data"]["errors"][0]["index"], 1);
+        assert!(data.users.find_all().await.unwrap().is_empty());
+    }
+
+    #[actix_web::test]
+    async fn test_emails_differing_only_in_case_are_duplicates() {
+        let data = web::Data::new(AppState::new());
+        let body = json!([
+            new_user("Alice", "alice@example.com", 30),
+            new_user("Alice Upper", "Alice@Example.com", 31),
+        ]);
+
+        let (status, body) = post_batch(data.clone(), "/users/batch", body).await;
+
+        assert_eq!(status, 207);
+        assert_eq!(body["data"]["errors"][0]["index"], 1);
+        assert_eq!(body["data"]["errors"][0]["error"]["field"], "email");
+        assert_eq!(data.users.find_all().await.unwrap().len(), 1);
+
+        let body = json!([
+            new_user("Bob", "bob@example.com", 30),
+            new_user("Bob Upper", "BOB@example.com", 31),
+        ]);
+        let (status, _) = post_batch(data.clone(), "/users/batch?all_or_nothing=true", body).await;
+
+        assert_eq!(status, 409);
+        assert_eq!(data.users.find_all().await.unwrap().len(), 1);
+    }
+}
+
+#[cfg(test)]
+mod export_tests {
+    use crate::common;
+    use actix_web::{test, web, App};
+    use rust_template::models::User;
+    use rust_template::routes::configure_user_routes;
+    use rust_template::state::AppState;
+    use serde_json::Value;
+
+    fn user(id: &str, name: &str, deleted: bool) -> User {
+        let user = common::user(id).name(name);
+        if deleted { user.deleted() } else { user }.build()
+    }
+
+    async fn export(uri: &str) -> (u16, String, String) {
+        let data = web::Data::new(AppState::with_users(vec![
+            user("u1", "Alice", false),
+            user("u2", "Smith, \"Bob\"", false),
+            user("u3", "Deleted", true),
+        ]));
+        let app = test::init_service(App::new().app_data(data).configure(configure_user_routes)).await;
+        let resp = test::call_service(&app, test::TestRequest::get().uri(uri).to_request()).await;
+        let status = resp.status().as_u16();
+        let content_type = resp
+            .headers()
+            .get("content-type")
+            .map(|v| v.to_str().unwrap().to_string())
+            .unwrap_or_default();
+        let body = test::read_body(resp).await;
+        (status, content_type, String::from_utf8(body.to_vec()).unwrap())
+    }
+
+    #[actix_web::test]
+    async fn test_ndjson_export_parses_line_by_line() {
+        let (status, content_type, body) = export("/users/export").await;
+
+        assert_eq!(status, 200);
+        assert_eq!(content_type, "application/x-ndjson");
+        let users: Vec<Value> = body
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        assert_eq!(users.len(), 2);
+        assert_eq!(users[0]["id"], "u1");
+        assert_eq!(users[1]["name"], "Smith, \"Bob\"");
+    }
+
+    #[actix_web::test]
+    async fn test_ndjson_export_includes_deleted_on_request() {
+        let (_, _, body) = export("/users/export?include_deleted=true").await;
+
+        assert_eq!(body.lines().count(), 3);
+    }
+
+    #[actix_web::test]
+    async fn test_csv_export_has_header_and_quoted_fields() {
+        let (status, content_type, body) = export("/users/export?format=csv").await;
+
+        assert_eq!(status, 200);
+        assert!(content_type.starts_with("text/csv"));
+        let lines: Vec<&str> = body.lines().collect();
+        assert_eq!(lines.len(), 3);
+        assert!(lines[0].starts_with("id,name,email"));
+        assert!(lines[1].starts_with("u1,Alice,u1@example.com,30,,user,true,"));
+        assert!(lines[2].starts_with("u2,\"Smith, \"\"Bob\"\"\",u2@example.com"));
+    }
+
+    #[actix_web::test]
+    async fn test_unknown_format_is_rejected() {
+        let (status, _, body) = export("/users/export?format=xml").await;
+
+        assert_eq!(status, 422);
+        assert!(body.contains("format"));
+    }
+}
+
+#[cfg(all(test, feature = "docs"))]
+mod openapi_tests {
+    use actix_web::{test, App};
+    use rust_template::openapi::configure_docs_routes;
+    use serde_json::Value;
+
+    #[actix_web::test]
+    async fn test_openapi_spec_documents_user_paths_and_error_envelope() {
+        let app = test::init_service(App::new().configure(configure_docs_routes)).await;
+        let req = test::TestRequest::get().uri("/api-docs/openapi.json").to_request();
+        let resp = test::call_service(&app, req).await;
+
+        assert_eq!(resp.status(), 200);
+        let spec: Value = test::read_body_json(resp).await;
+        assert!(spec["openapi"].as_str().unwrap().starts_with("3."));
+
+        let paths = spec["paths"].as_object().unwrap();
+        for path in ["/users", "/users/{id}", "/users/batch", "/users/export", "/health/ready"] {
+            assert!(paths.contains_key(path), "missing path {}", path);
+        }
+        assert!(paths["/users"]["get"].is_object());
+        assert!(paths["/users"]["post"].is_object());
+
+        let schemas = spec["components"]["schemas"].as_object().unwrap();
+        assert!(schemas.contains_key("User"));
+        assert!(schemas.contains_key("CreateUserRequest"));
+        assert!(schemas.contains_key("ErrorResponse"));
+
+        let not_found = &paths["/users/{id}"]["get"]["responses"]["404"];
+        assert_eq!(
+            not_found["content"]["application/json"]["schema"]["$ref"],
+            "#/components/schemas/ErrorResponse"
+        );
+    }
+
+    #[actix_web::test]
+    async fn test_swagger_ui_is_served() {
+        let app = test::init_service(App::new().configure(configure_docs_routes)).await;
+        let req = test::TestRequest::get().uri("/swagger-ui/").to_request();
+        let resp = test::call_service(&app, req).await;
+
+        assert_eq!(resp.status(), 200);
+    }
+}
+
+#[cfg(test)]
+mod upload_tests {
+    use actix_web::{test, web, App};
+    use rust_template::routes::configure_upload_routes;
+    use rust_template::services::InMemoryStorageService;
+    use rust_template::state::AppState;
+    use serde_json::Value;
+    use std::sync::Arc;
+
+    const BOUNDARY: &str = "test-boundary";
+
+    /// (field, filename, content type, body); filename `None` là field thường
+    fn multipart_body(parts: &[(&str, Option<&str>, &str, &str)]) -> String {
+        let mut body = String::new();
+        for (name, filename, content_type, content) in parts {
+            body.push_str(&format!("--{}\r\n", BOUNDARY));
+            match filename {
+                Some(filename) => body.push_str(&format!(
+                    "Content-Disposition: form-data; name=\"{}\"; filename=\"{}\"\r\nContent-Type: {}\r\n\r\n",
+                    name, filename, content_type
+                )),
+                None => body.push_str(&format!("Content-Disposition: form-data; name=\"{}\"\r\n\r\n", name)),
+            }
+            body.push_str(content);
+            body.push_str("\r\n");
+        }
+        body.push_str(&format!("--{}--\r\n", BOUNDARY));
+        body
+    }
+
+    async fn upload(storage: Option<InMemoryStorageService>, body: String) -> (u16, Value) {
+        let mut builder = AppState::builder();
+        if let Some(storage) = storage {
+            builder = builder.with_storage(Arc::new(storage));
+        }
+        let app = test::init_service(
+            App::new()
+                .app_data(web::Data::new(builder.build()))
+                .configure(configure_upload_routes),
+        )
+        .await;
+
+        let req = test::TestRequest::post()
+            .uri("/uploads")
+            .insert_header((
+                "content-type",
+                format!("multipart/form-data; boundary={}", BOUNDARY),
+            ))
+            .set_payload(body)
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+        let status = resp.status().as_u16();
+        (status, test::read_body_json(resp).await)
+    }
+
+    #[actix_web::test]
+    async fn test_upload_stores_object_and_returns_metadata() {
+        let storage = InMemoryStorageService::new();
+        let body = multipart_body(&[
+            ("description", None, "", "ignored"),
+            ("file", Some("hello world.txt"), "text/plain", "hello world"),
+        ]);
+
+        let (status, body) = upload(Some(storage.clone()), body).await;
+
+        assert_eq!(status, 201);
+        let file = &body["data"][0];
+        assert_eq!(file["field"], "file");
+        assert_eq!(file["filename"], "hello world.txt");
+        assert_eq!(file["size"], 11);
+        assert_eq!(file["content_type"], "text/plain");
+
+        let key = file["key"].as_str().unwrap();
+        assert!(key.starts_with("uploads/"));
+        assert!(key.ends_with("-hello_world.txt"));
+        let stored = storage.object(key).unwrap();
+        assert_eq!(stored.body, b"hello world");
+        assert_eq!(stored.content_type.as_deref(), Some("text/plain"));
+        assert_eq!(storage.keys().len(), 1);
+    }
+
+    #[actix_web::test]
+    async fn test_disallowed_content_type_cleans_up_earlier_files() {
+        let storage = InMemoryStorageService::new();
+        let body = multipart_body(&[
+            ("first", Some("a.csv"), "text/csv", "a,b\n1,2"),
+            ("second", Some("run.sh"), "application/x-sh", "rm -rf /"),
+        ]);
+
+        let (status, body) = upload(Some(storage.clone()), body).await;
+
+        assert_eq!(status, 422);
+        assert_eq!(body["field"], "content_type");
+        assert!(storage.keys().is_empty());
+    }
+
+    #[actix_web::test]
+    async fn test_upload_without_files_is_rejected() {
+        let body = multipart_body(&[("description", None, "", "no file here")]);
+
+        let (status, _) = upload(Some(InMemoryStorageService::new()), body).await;
+
+        assert_eq!(status, 422);
+    }
+
+    #[actix_web::test]
+    async fn test_upload_without_storage_is_unavailable() {
+        let body = multipart_body(&[("file", Some("a.txt"), "text/plain", "a")]);
+
+        let (status, _) = upload(None, body).await;
+
+        assert_eq!(status, 503);
+    }
+}
+
+#[cfg(all(test, feature = "websocket"))]
+mod event_stream_tests {
+    use actix_web::body::MessageBody;
+    use actix_web::{test, web, App};
+    use rust_template::handlers::EventStreamConfig;
+    use rust_template::routes::configure_event_routes;
+    use rust_template::websocket::{EventBus, ServerMessage, WebSocketServer};
+    use std::time::Duration;
+
+    fn message(n: u64) -> ServerMessage {
+        ServerMessage::Message {
+            topic: "news".to_string(),
+            payload: serde_json::json!({ "n": n }),
+        }
+    }
+
+    /// Mở stream và trả về body; `last_event_id` gửi qua header `Last-Event-ID`
+    async fn open_stream(
+        events: EventBus,
+        keep_alive: Duration,
+        last_event_id: Option<&str>,
+    ) -> actix_web::body::BoxBody {
+        let app = test::init_service(
+            App::new()
+                .app_data(web::Data::new(events))
+                .app_data(web::Data::new(EventStreamConfig { keep_alive }))
+                .configure(configure_event_routes),
+        )
+        .await;
+
+        let mut req = test::TestRequest::get().uri("/events/stream");
+        if let Some(id) = last_event_id {
+            req = req.insert_header(("Last-Event-ID", id));
+        }
+        let resp = test::call_service(&app, req.to_request()).await;
+        assert_eq!(resp.status(), 200);
+        assert_eq!(resp.headers().get("content-type").unwrap(), "text/event-stream");
+        resp.into_body()
+    }
+
+    async fn next_chunk(body: &mut actix_web::body::BoxBody) -> String {
+        let chunk = tokio::time::timeout(
+            Duration::from_secs(5),
+            std::future::poll_fn(|cx| std::pin::Pin::new(&mut *body).poll_next(cx)),
+        )
+        .await
+        .expect("timed out waiting for event")
+        .expect("stream ended")
+        .expect("stream error");
+        String::from_utf8(chunk.to_vec()).unwrap()
+    }
+
+    #[actix_web::test]
+    async fn test_stream_delivers_published_events() {
+        let server = WebSocketServer::new();
+        let mut body = open_stream(server.events().clone(), Duration::from_secs(60), None).await;
+
+        // Cùng nguồn event với WebSocket sessions
+        server.broadcast(message(1));
+        server.broadcast(message(2));
+
+        assert_eq!(
+            next_chunk(&mut body).await,
+            "id: 1\ndata: {\"type\":\"message\",\"topic\":\"news\",\"payload\":{\"n\":1}}\n\n"
+        );
+        assert!(next_chunk(&mut body).await.starts_with("id: 2\n"));
+    }
+
+    #[actix_web::test]
+    async fn test_stream_resumes_after_last_event_id() {
+        let events = EventBus::new();
+        for n in 1..=3 {
+            events.publish(message(n));
+        }
+
+        let mut body = open_stream(events.clone(), Duration::from_secs(60), Some("1")).await;
+        events.publish(message(4));
+
+        for id in 2..=4 {
+            assert!(next_chunk(&mut body).await.starts_with(&format!("id: {}\n", id)));
+        }
+    }
+
+    #[actix_web::test]
+    async fn test_stream_sends_keep_alive_comments() {
+        let mut body = open_stream(EventBus::new(), Duration::from_millis(20), None).await;
+
+        assert_eq!(next_chunk(&mut body).await, ": keep-alive\n\n");
+    }
+
+    #[actix_web::test]
+    async fn test_invalid_last_event_id_is_rejected() {
+        let app = test::init_service(
+            App::new()
+                .app_data(web::Data::new(EventBus::new()))
+                .configure(configure_event_routes),
+        )
+        .await;
+
+        let req = test::TestRequest::get()
+            .uri("/events/stream")
+            .insert_header(("Last-Event-ID", "abc"))
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+
+        assert_eq!(resp.status(), 400);
+    }
+}
+
+#[cfg(test)]
+mod money_tests {
+    use rust_decimal::Decimal;
+    use rust_template::errors::ApiError;
+    use rust_template::models::Money;
+    use std::str::FromStr;
+
+    fn usd(amount: &str) -> Money {
+        Money::parse(amount, "USD").unwrap()
+    }
+
+    #[test]
+    fn test_serializes_amount_as_string() {
+        let price = usd("19.99");
+        let json = serde_json::to_value(price).unwrap();
+        assert_eq!(json, serde_json::json!({"amount": "19.99", "currency": "USD"}));
+
+        let parsed: Money = serde_json::from_value(json).unwrap();
+        assert_eq!(parsed, price);
+    }
+
+    #[test]
+    fn test_round_trip_keeps_precision_floats_lose() {
+        // 0.1 + 0.2 != 0.3 với f64
+        let total = usd("0.1").checked_add(&usd("0.2")).unwrap();
+        assert_eq!(total, usd("0.3"));
+
+        let large = usd("12345678901234567.89");
+        let json = serde_json::to_string(&large).unwrap();
+        assert!(json.contains("\"12345678901234567.89\""));
+        assert_eq!(serde_json::from_str::<Money>(&json).unwrap(), large);
+    }
+
+    #[test]
+    fn test_deserialize_rejects_invalid_currency_and_number_amount() {
+        assert!(serde_json::from_str::<Money>(r#"{"amount":"1.00","currency":"US"}"#).is_err());
+        assert!(serde_json::from_str::<Money>(r#"{"amount":"abc","currency":"USD"}"#).is_err());
+
+        let lowercase: Money = serde_json::from_str(r#"{"amount":"1.00","currency":"eur"}"#).unwrap();
+        assert_eq!(lowercase.currency().as_str(), "EUR");
+    }
+
+    #[test]
+    fn test_mixed_currency_is_rejected() {
+        let eur = Money::parse("5.00", "EUR").unwrap();
+
+        let err = usd("10.00").checked_add(&eur).unwrap_err();
+        assert!(matches!(err, ApiError::ValidationError { .. }));
+        assert!(usd("10.00").checked_sub(&eur).is_err());
+        assert!(Money::sum("USD", &[usd("1.00"), eur]).is_err());
+    }
+
+    #[test]
+    fn test_arithmetic_helpers() {
+        let seat = usd("9.99");
+        let subtotal = seat.checked_mul(Decimal::from(3)).unwrap();
+        assert_eq!(subtotal, usd("29.97"));
+
+        let discounted = subtotal
+            .checked_mul(Decimal::from_str("0.85").unwrap())
+            .unwrap()
+            .round(2);
+        assert_eq!(discounted.amount().to_string(), "25.47");
+
+        let refund = usd("5.00").checked_sub(&usd("7.50")).unwrap();
+        assert!(refund.is_negative());
+        assert_eq!(Money::sum("USD", &[usd("1.25"), usd("2.75")]).unwrap(), usd("4.00"));
+        assert_eq!(usd("4.00").to_string(), "4.00 USD");
+    }
+}
+
+#[cfg(test)]
+mod cursor_pagination_tests {
+    use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+    use base64::Engine;
+    use rust_template::errors::ApiError;
+    use rust_template::models::SortDirection;
+    use rust_template::utils::{
+        decode_cursor, encode_cursor, Cursor, CursorScope, DEFAULT_CURSOR_TTL,
+    };
+    use std::time::Duration;
+
+    const SECRET: &[u8] = b"cursor-test-secret";
+
+    const SCOPE: CursorScope<'static> = CursorScope {
+        subject: "user-1",
+        tenant_id: Some("acme"),
+        filter: "status=active",
+    };
+
+    fn cursor() -> Cursor {
+        Cursor {
+            sort: "created_at".to_string(),
+            key: vec![serde_json::json!("2024-05-01T10:00:00Z"), serde_json::json!(42)],
+            direction: SortDirection::Desc,
+        }
+    }
+
+    fn token() -> String {
+        encode_cursor(&cursor(), &SCOPE, SECRET, DEFAULT_CURSOR_TTL).unwrap()
+    }
+
+    fn is_bad_request(result: Result<Cursor, ApiError>) -> bool {
+        matches!(result, Err(ApiError::BadRequest { .. }))
+    }
+
+    #[test]
+    fn test_round_trip() {
+        assert_eq!(decode_cursor(&token(), &SCOPE, SECRET).unwrap(), cursor());
+    }
+
+    #[test]
+    fn test_token_is_encrypted_and_url_safe() {
+        let token = token();
+        assert!(token.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_')));
+
+        // Không đọc được gì từ token, kể cả khi decode base64
+        let raw = URL_SAFE_NO_PAD.decode(&token).unwrap();
+        let raw = String::from_utf8_lossy(&raw);
+        for plaintext in ["created_at", "2024-05-01", "desc", "expires_at"] {
+            assert!(!raw.contains(plaintext), "token leaks {}", plaintext);
+        }
+
+        // Nonce ngẫu nhiên: cùng cursor cho token khác nhau
+        assert_ne!(token, self::token());
+    }
+
+    #[test]
+    fn test_every_edited_byte_is_rejected() {
+        let raw = URL_SAFE_NO_PAD.decode(token()).unwrap();
+
+        for i in 0..raw.len() {
+            let mut edited = raw.clone();
+            edited[i] ^= 0x01;
+            let edited = URL_SAFE_NO_PAD.encode(edited);
+            assert!(is_bad_request(decode_cursor(&edited, &SCOPE, SECRET)), "byte {} edited", i);
+        }
+    }
+
+    #[test]
+    fn test_cursor_is_bound_to_its_scope() {
+        let token = token();
+        let other_scopes = [
+            CursorScope { subject: "user-2", ..SCOPE },
+            CursorScope { tenant_id: Some("globex"), ..SCOPE },
+            CursorScope { tenant_id: None, ..SCOPE },
+            CursorScope { filter: "status=deleted", ..SCOPE },
+        ];
+
+        for scope in other_scopes {
+            assert!(is_bad_request(decode_cursor(&token, &scope, SECRET)), "{:?}", scope);
+        }
+    }
+
+    #[test]
+    fn test_expired_cursor_is_rejected() {
+        let expired = encode_cursor(&cursor(), &SCOPE, SECRET, Duration::ZERO).unwrap();
+
+        let err = decode_cursor(&expired, &SCOPE, SECRET).unwrap_err();
+        assert!(matches!(err, ApiError::BadRequest { ref message, .. } if message.contains("expired")));
+    }
+
+    #[test]
+    fn test_wrong_secret_and_garbage_are_rejected() {
+        let token = token();
+
+        assert!(is_bad_request(decode_cursor(&token, &SCOPE, b"other-secret")));
+        for garbage in ["", "abc", "not.base64!", "eyJ9", &token[..token.len() - 2]] {
+            assert!(is_bad_request(decode_cursor(garbage, &SCOPE, SECRET)), "{:?}", garbage);
+        }
+    }
+}
+
+#[cfg(test)]
+mod id_serialization_tests {
+    use crate::common;
+    use rust_template::models::User;
+    use rust_template::utils::{id_as_string, set_ids_as_strings};
+    use serde::{Deserialize, Serialize};
+    use serde_json::json;
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Record {
+        #[serde(with = "id_as_string")]
+        id: i64,
+        #[serde(with = "id_as_string")]
+        owner_id: u64,
+    }
+
+    const BIG: i64 = (1 << 53) + 1;
+
+    fn user(id: &str) -> User {
+        common::user(id).name("Alice").email("alice@example.com").build()
+    }
+
+    // Cấu hình là global nên bật/tắt trong cùng một test
+    #[test]
+    fn test_ids_follow_global_setting() {
+        let record = Record { id: BIG, owner_id: u64::MAX };
+        let numeric_user = user("9007199254740993");
+        let uuid_user = user("550e8400-e29b-41d4-a716-446655440000");
+
+        set_ids_as_strings(true);
+        let as_strings = serde_json::to_value(&record).unwrap();
+        let user_as_string = serde_json::to_value(&numeric_user).unwrap();
+        set_ids_as_strings(false);
+        let as_numbers = serde_json::to_value(&record).unwrap();
+        let user_as_number = serde_json::to_value(&numeric_user).unwrap();
+        let uuid_off = serde_json::to_value(&uuid_user).unwrap();
+
+        assert_eq!(as_strings, json!({ "id": "9007199254740993", "owner_id": "18446744073709551615" }));
+        assert_eq!(as_numbers, json!({ "id": BIG, "owner_id": u64::MAX }));
+
+        assert_eq!(user_as_string["id"], json!("9007199254740993"));
+        assert_eq!(user_as_number["id"], json!(BIG));
+        // UUID không phải số nên luôn là string
+        assert_eq!(uuid_off["id"], json!("550e8400-e29b-41d4-a716-446655440000"));
+    }
+
+    #[test]
+    fn test_user_id_reads_numbers_and_strings() {
+        for id in [json!(BIG), json!("9007199254740993")] {
+            let mut value = serde_json::to_value(user("placeholder")).unwrap();
+            value["id"] = id;
+            let parsed: User = serde_json::from_value(value).unwrap();
+            assert_eq!(parsed.id, "9007199254740993");
+        }
+    }
+
+    #[test]
+    fn test_deserialize_accepts_both_forms() {
+        let expected = Record { id: BIG, owner_id: 7 };
+
+        let from_strings: Record =
+            serde_json::from_value(json!({ "id": "9007199254740993", "owner_id": "7" })).unwrap();
+        let from_numbers: Record = serde_json::from_value(json!({ "id": BIG, "owner_id": 7 })).unwrap();
+
+        assert_eq!(from_strings, expected);
+        assert_eq!(from_numbers, expected);
+        assert!(serde_json::from_value::<Record>(json!({ "id": "abc", "owner_id": 7 })).is_err());
+    }
+}
+
@@ -0,0 +1,227 @@
+//! Event store tests shared by the Postgres, MySQL and SQLite stores
+
+/// Expand the event store test suite for `$store`, built from the pool
+/// returned by `async fn $setup()` (migrated, with an empty `events` table)
+///
+/// ```ignore
+/// crate::event_store_tests!(MySqlEventStore, setup_test_db);
+/// ```
+#[macro_export]
+macro_rules! event_store_tests {
+    ($store:ident, $setup:ident) => {
+        use chrono::Utc;
+        use rust_template::errors::ApiError;
+        use rust_template::patterns::StoredEvent;
+
+        #[tokio::test]
+        async fn test_append_and_get_events_async() {
+            let pool = $setup().await;
+            let store = $store::new(pool);
+
+            // Create test events
+            let event1 = StoredEvent {
+                id: uuid::Uuid::new_v4().to_string(),
+                aggregate_id: "user-123".to_string(),
+                event_type: "UserCreated".to_string(),
+                payload: serde_json::json!({"name": "John", "email": "john@example.com"}),
+                timestamp: Utc::now(),
+                version: 1,
+            };
+
+            let event2 = StoredEvent {
+                id: uuid::Uuid::new_v4().to_string(),
+                aggregate_id: "user-123".to_string(),
+                event_type: "UserUpdated".to_string(),
+                payload: serde_json::json!({"name": "John Doe"}),
+                timestamp: Utc::now(),
+                version: 2,
+            };
+
+            // Append events
+            store.append_async(event1.clone()).await.unwrap();
+            store.append_async(event2.clone()).await.unwrap();
+
+            // Retrieve events
+            let events = store.get_events_async("user-123").await.unwrap();
+
+            assert_eq!(events.len(), 2);
+            assert_eq!(events[0].event_type, "UserCreated");
+            assert_eq!(events[1].event_type, "UserUpdated");
+            assert_eq!(events[0].version, 1);
+            assert_eq!(events[1].version, 2);
+        }
+
+        #[tokio::test]
+        async fn test_get_events_since_async() {
+            let pool = $setup().await;
+            let store = $store::new(pool);
+
+            // Create multiple events
+            for i in 1..=5 {
+                let event = StoredEvent {
+                    id: uuid::Uuid::new_v4().to_string(),
+                    aggregate_id: "user-456".to_string(),
+                    event_type: format!("Event{}", i),
+                    payload: serde_json::json!({"version": i}),
+                    timestamp: Utc::now(),
+                    version: i,
+                };
+                store.append_async(event).await.unwrap();
+            }
+
+            // Get events since version 2
+            let events = store.get_events_since_async("user-456", 2).await.unwrap();
+
+            assert_eq!(events.len(), 3); // versions 3, 4, 5
+            assert_eq!(events[0].version, 3);
+            assert_eq!(events[1].version, 4);
+            assert_eq!(events[2].version, 5);
+        }
+
+        #[tokio::test]
+        async fn test_different_aggregates_isolated() {
+            let pool = $setup().await;
+            let store = $store::new(pool);
+
+            // Create events for different aggregates
+            let event1 = StoredEvent {
+                id: uuid::Uuid::new_v4().to_string(),
+                aggregate_id: "user-1".to_string(),
+                event_type: "UserCreated".to_string(),
+                payload: serde_json::json!({}),
+                timestamp: Utc::now(),
+                version: 1,
+            };
+
+            let event2 = StoredEvent {
+                id: uuid::Uuid::new_v4().to_string(),
+                aggregate_id: "user-2".to_string(),
+                event_type: "UserCreated".to_string(),
+                payload: serde_json::json!({}),
+                timestamp: Utc::now(),
+                version: 1,
+            };
+
+            store.append_async(event1).await.unwrap();
+            store.append_async(event2).await.unwrap();
+
+            // Verify isolation
+            let events1 = store.get_events_async("user-1").await.unwrap();
+            let events2 = store.get_events_async("user-2").await.unwrap();
+
+            assert_eq!(events1.len(), 1);
+            assert_eq!(events2.len(), 1);
+            assert_eq!(events1[0].aggregate_id, "user-1");
+            assert_eq!(events2[0].aggregate_id, "user-2");
+        }
+
+        #[tokio::test]
+        async fn test_version_conflict() {
+            let pool = $setup().await;
+            let store = $store::new(pool);
+
+            // Create first event
+            let event1 = StoredEvent {
+                id: uuid::Uuid::new_v4().to_string(),
+                aggregate_id: "user-789".to_string(),
+                event_type: "UserCreated".to_string(),
+                payload: serde_json::json!({}),
+                timestamp: Utc::now(),
+                version: 1,
+            };
+
+            store.append_async(event1).await.unwrap();
+
+            // Try to append another event with same version (should fail)
+            let event2 = StoredEvent {
+                id: uuid::Uuid::new_v4().to_string(),
+                aggregate_id: "user-789".to_string(),
+                event_type: "UserUpdated".to_string(),
+                payload: serde_json::json!({}),
+                timestamp: Utc::now(),
+                version: 1, // Same version - should conflict
+            };
+
+            let result = store.append_async(event2).await;
+            assert!(matches!(result, Err(ApiError::Conflict { .. })));
+        }
+
+        #[tokio::test]
+        async fn test_get_all_since_async_orders_across_aggregates() {
+            let pool = $setup().await;
+            let store = $store::new(pool);
+
+            for aggregate_id in ["order-1", "order-2", "order-1"] {
+                let version = store.get_events_async(aggregate_id).await.unwrap().len() as u64 + 1;
+                store
+                    .append_async(StoredEvent {
+                        id: uuid::Uuid::new_v4().to_string(),
+                        aggregate_id: aggregate_id.to_string(),
+                        event_type: "OrderUpdated".to_string(),
+                        payload: serde_json::json!({}),
+                        timestamp: Utc::now(),
+                        version,
+                    })
+                    .await
+                    .unwrap();
+            }
+
+            let all = store.get_all_since_async(0).await.unwrap();
+            let aggregates: Vec<&str> = all.iter().map(|e| e.event.aggregate_id.as_str()).collect();
+            assert_eq!(aggregates, vec!["order-1", "order-2", "order-1"]);
+            assert!(all.windows(2).all(|w| w[0].global_seq < w[1].global_seq));
+
+            let tail = store.get_all_since_async(all[0].global_seq).await.unwrap();
+            assert_eq!(tail.len(), 2);
+        }
+
+        #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+        async fn test_reader_does_not_skip_concurrent_appends() {
+            let pool = $setup().await;
+            let store = std::sync::Arc::new($store::new(pool));
+
+            let writers: Vec<_> = (0..20)
+                .map(|i| {
+                    let store = store.clone();
+                    tokio::spawn(async move {
+                        store
+                            .append_async(StoredEvent {
+                                id: uuid::Uuid::new_v4().to_string(),
+                                aggregate_id: format!("account-{}", i),
+                                event_type: "AccountOpened".to_string(),
+                                payload: serde_json::json!({}),
+                                timestamp: Utc::now(),
+                                version: 1,
+                            })
+                            .await
+                            .unwrap();
+                    })
+                })
+                .collect();
+
+            // Reader tiến cursor trong lúc các writer còn đang commit; nếu một
+            // global_seq thấp hơn commit sau thì reader bỏ lỡ nó và không bao
+            // giờ đủ 20
+            let read_all = async {
+                let mut position = 0;
+                let mut seen = 0;
+                while seen < 20 {
+                    for event in store.get_all_since_async(position).await.unwrap() {
+                        position = event.global_seq;
+                        seen += 1;
+                    }
+                    tokio::task::yield_now().await;
+                }
+                seen
+            };
+            let seen = tokio::time::timeout(std::time::Duration::from_secs(10), read_all)
+                .await
+                .expect("reader skipped events committed out of order");
+
+            for writer in writers {
+                writer.await.unwrap();
+            }
+            assert_eq!(seen, 20);
+        }
+    };
+}
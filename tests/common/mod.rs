@@ -0,0 +1,123 @@
+//! Helpers shared by the integration test crates (`mod common;`)
+
+#![allow(dead_code)]
+
+pub mod event_store;
+
+#[cfg(feature = "cache-redis")]
+use rust_template::cache::CacheManager;
+use chrono::Utc;
+use rust_template::models::User;
+
+/// Builder for test users: `user("u1").name("Alice").build()`
+///
+/// Defaults: name `Test User`, email `<id>@example.com`, age 30, role
+/// `user`, active, created and updated now, not deleted.
+pub struct UserBuilder {
+    user: User,
+}
+
+pub fn user(id: &str) -> UserBuilder {
+    UserBuilder {
+        user: User {
+            id: id.to_string(),
+            name: "Test User".to_string(),
+            email: format!("{}@example.com", id),
+            age: 30,
+            phone: None,
+            role: "user".to_string(),
+            is_active: true,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+            deleted_at: None,
+        },
+    }
+}
+
+impl UserBuilder {
+    pub fn name(mut self, name: &str) -> Self {
+        self.user.name = name.to_string();
+        self
+    }
+
+    pub fn email(mut self, email: &str) -> Self {
+        self.user.email = email.to_string();
+        self
+    }
+
+    pub fn age(mut self, age: u32) -> Self {
+        self.user.age = age;
+        self
+    }
+
+    pub fn role(mut self, role: &str) -> Self {
+        self.user.role = role.to_string();
+        self
+    }
+
+    /// Soft-deleted now
+    pub fn deleted(mut self) -> Self {
+        self.user.deleted_at = Some(Utc::now());
+        self
+    }
+
+    pub fn build(self) -> User {
+        self.user
+    }
+}
+
+/// `count` users `user-0`, `user-1`, ... named `User <i>`, aged `20 + i`
+pub fn users(count: usize) -> Vec<User> {
+    (0..count)
+        .map(|i| {
+            user(&format!("user-{}", i))
+                .name(&format!("User {}", i))
+                .email(&format!("user{}@example.com", i))
+                .age(20 + i as u32)
+                .build()
+        })
+        .collect()
+}
+
+/// `REDIS_URL`, or a local Redis
+pub fn redis_url() -> String {
+    std::env::var("REDIS_URL").unwrap_or_else(|_| "redis://localhost:6379".to_string())
+}
+
+#[cfg(feature = "cache-redis")]
+pub async fn setup_cache() -> CacheManager {
+    CacheManager::new(&redis_url())
+        .await
+        .expect("Failed to connect to test Redis")
+}
+
+/// Redis giả: nhận kết nối nhưng trả lỗi cho mọi command (backend down)
+pub async fn failing_redis() -> String {
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("redis://{}", listener.local_addr().unwrap());
+    tokio::spawn(async move {
+        while let Ok((mut socket, _)) = listener.accept().await {
+            tokio::spawn(async move {
+                let mut buf = [0u8; 4096];
+                while let Ok(n) = socket.read(&mut buf).await {
+                    if n == 0 {
+                        break;
+                    }
+                    // Một reply cho mỗi command (RESP array `*N`) trong gói
+                    let commands = buf[..n]
+                        .split(|&b| b == b'\n')
+                        .filter(|line| line.starts_with(b"*"))
+                        .count();
+                    for _ in 0..commands {
+                        if socket.write_all(b"-ERR backend unavailable\r\n").await.is_err() {
+                            return;
+                        }
+                    }
+                }
+            });
+        }
+    });
+    url
+}
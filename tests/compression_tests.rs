@@ -0,0 +1,124 @@
+mod common;
+
+#[cfg(test)]
+mod compression_tests {
+    use crate::common::users;
+    use actix_web::{http::header, test, web, web::Bytes, App, HttpResponse};
+    use flate2::read::GzDecoder;
+    use rust_template::middleware::{Compression, CompressionMode};
+    use rust_template::routes::configure_user_routes;
+    use rust_template::state::AppState;
+    use serde_json::Value;
+    use std::convert::Infallible;
+    use std::io::Read;
+
+    async fn get(mode: CompressionMode, uri: &str, accept_encoding: &str) -> (Option<String>, Vec<u8>) {
+        let app = test::init_service(
+            App::new()
+                .app_data(web::Data::new(AppState::with_users(users(50))))
+                .wrap(Compression::new(mode))
+                .configure(configure_user_routes),
+        )
+        .await;
+        let req = test::TestRequest::get()
+            .uri(uri)
+            .insert_header((header::ACCEPT_ENCODING, accept_encoding))
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), 200);
+        let encoding = resp
+            .headers()
+            .get(header::CONTENT_ENCODING)
+            .map(|v| v.to_str().unwrap().to_string());
+        (encoding, test::read_body(resp).await.to_vec())
+    }
+
+    fn gunzip(body: &[u8]) -> String {
+        let mut decoded = String::new();
+        GzDecoder::new(body).read_to_string(&mut decoded).unwrap();
+        decoded
+    }
+
+    #[actix_web::test]
+    async fn test_large_response_is_gzip_encoded() {
+        let (encoding, body) = get(CompressionMode::Auto, "/users?per_page=50", "gzip").await;
+
+        assert_eq!(encoding.as_deref(), Some("gzip"));
+        let body: Value = serde_json::from_str(&gunzip(&body)).unwrap();
+        assert_eq!(body["data"]["items"].as_array().unwrap().len(), 50);
+    }
+
+    #[actix_web::test]
+    async fn test_small_response_is_not_compressed() {
+        let (encoding, body) = get(CompressionMode::Auto, "/users/user-1", "gzip").await;
+
+        assert!(body.len() < 1024);
+        assert_eq!(encoding, None);
+        let body: Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(body["data"]["id"], "user-1");
+    }
+
+    #[actix_web::test]
+    async fn test_ndjson_export_stream_is_compressed() {
+        let (encoding, body) = get(CompressionMode::Gzip, "/users/export", "gzip").await;
+
+        assert_eq!(encoding.as_deref(), Some("gzip"));
+        assert_eq!(gunzip(&body).lines().count(), 50);
+    }
+
+    #[actix_web::test]
+    async fn test_event_stream_and_identity_are_not_compressed() {
+        async fn events() -> HttpResponse {
+            let chunks = futures_util::stream::iter(
+                (0..100).map(|i| Ok::<_, Infallible>(Bytes::from(format!("data: {}\n\n", i)))),
+            );
+            HttpResponse::Ok()
+                .content_type("text/event-stream")
+                .streaming(chunks)
+        }
+        async fn identity() -> HttpResponse {
+            HttpResponse::Ok()
+                .insert_header((header::CONTENT_ENCODING, "identity"))
+                .body("x".repeat(4096))
+        }
+
+        let app = test::init_service(
+            App::new()
+                .wrap(Compression::new(CompressionMode::Auto))
+                .route("/events", web::get().to(events))
+                .route("/identity", web::get().to(identity)),
+        )
+        .await;
+
+        for uri in ["/events", "/identity"] {
+            let req = test::TestRequest::get()
+                .uri(uri)
+                .insert_header((header::ACCEPT_ENCODING, "gzip, br"))
+                .to_request();
+            let resp = test::call_service(&app, req).await;
+            assert!(!resp.headers().contains_key(header::CONTENT_ENCODING), "{}", uri);
+            let body = test::read_body(resp).await;
+            assert!(body.starts_with(b"data: 0") || body.starts_with(b"xxx"), "{}", uri);
+        }
+    }
+
+    #[actix_web::test]
+    async fn test_mode_limits_encodings() {
+        let (encoding, _) = get(CompressionMode::Auto, "/users?per_page=50", "gzip;q=0.5, br").await;
+        assert_eq!(encoding.as_deref(), Some("br"));
+
+        let (encoding, _) = get(CompressionMode::Gzip, "/users?per_page=50", "br").await;
+        assert_eq!(encoding, None);
+
+        let (encoding, _) = get(CompressionMode::Off, "/users?per_page=50", "gzip, br").await;
+        assert_eq!(encoding, None);
+    }
+
+    #[test]
+    fn test_mode_parsing() {
+        assert_eq!("off".parse::<CompressionMode>().unwrap(), CompressionMode::Off);
+        assert_eq!("br".parse::<CompressionMode>().unwrap(), CompressionMode::Brotli);
+        assert_eq!("GZIP".parse::<CompressionMode>().unwrap(), CompressionMode::Gzip);
+        assert!("zstd".parse::<CompressionMode>().is_err());
+    }
+}
@@ -0,0 +1,94 @@
+mod common;
+
+#[cfg(test)]
+mod content_negotiation_tests {
+    use crate::common;
+    use actix_web::{http::header, test, web, App};
+    use rust_template::models::User;
+    use rust_template::routes::configure_user_routes;
+    use rust_template::state::AppState;
+    use serde_json::Value;
+
+    fn user(id: &str, name: &str) -> User {
+        common::user(id).name(name).build()
+    }
+
+    async fn get(uri: &str, accept: Option<&str>) -> (u16, String, web::Bytes) {
+        let data = web::Data::new(AppState::with_users(vec![user("u1", "Alice"), user("u2", "Smith, Bob")]));
+        let app = test::init_service(App::new().app_data(data).configure(configure_user_routes)).await;
+        let mut req = test::TestRequest::get().uri(uri);
+        if let Some(accept) = accept {
+            req = req.insert_header((header::ACCEPT, accept));
+        }
+        let resp = test::call_service(&app, req.to_request()).await;
+        let status = resp.status().as_u16();
+        let content_type = resp
+            .headers()
+            .get(header::CONTENT_TYPE)
+            .map(|v| v.to_str().unwrap().to_string())
+            .unwrap_or_default();
+        (status, content_type, test::read_body(resp).await)
+    }
+
+    #[actix_web::test]
+    async fn test_json_is_the_default() {
+        for accept in [None, Some("*/*"), Some("application/json")] {
+            let (status, content_type, body) = get("/users", accept).await;
+
+            assert_eq!(status, 200, "accept: {:?}", accept);
+            assert_eq!(content_type, "application/json");
+            let body: Value = serde_json::from_slice(&body).unwrap();
+            assert_eq!(body["data"]["items"][0]["id"], "u1");
+        }
+    }
+
+    #[actix_web::test]
+    async fn test_msgpack_list_and_single_user() {
+        let (status, content_type, body) = get("/users", Some("application/msgpack")).await;
+        assert_eq!(status, 200);
+        assert_eq!(content_type, "application/msgpack");
+        let body: Value = rmp_serde::from_slice(&body).unwrap();
+        assert_eq!(body["data"]["total"], 2);
+        assert_eq!(body["data"]["items"][1]["name"], "Smith, Bob");
+
+        let (status, content_type, body) = get("/users/u1", Some("application/msgpack")).await;
+        assert_eq!(status, 200);
+        assert_eq!(content_type, "application/msgpack");
+        let body: Value = rmp_serde::from_slice(&body).unwrap();
+        assert_eq!(body["data"]["email"], "u1@example.com");
+    }
+
+    #[actix_web::test]
+    async fn test_csv_list() {
+        let (status, content_type, body) = get("/users", Some("text/csv")).await;
+
+        assert_eq!(status, 200);
+        assert!(content_type.starts_with("text/csv"));
+        let body = String::from_utf8(body.to_vec()).unwrap();
+        let lines: Vec<&str> = body.lines().collect();
+        assert_eq!(lines.len(), 3);
+        assert!(lines[0].starts_with("id,name,email"));
+        assert!(lines[2].starts_with("u2,\"Smith, Bob\",u2@example.com"));
+    }
+
+    #[actix_web::test]
+    async fn test_quality_values_pick_the_preferred_format() {
+        let (_, content_type, _) = get("/users", Some("application/json;q=0.5, text/csv")).await;
+        assert!(content_type.starts_with("text/csv"));
+
+        let (_, content_type, _) = get("/users", Some("text/csv;q=0, application/msgpack;q=0.1")).await;
+        assert_eq!(content_type, "application/msgpack");
+    }
+
+    #[actix_web::test]
+    async fn test_unsupported_accept_is_406() {
+        let (status, _, body) = get("/users", Some("application/xml")).await;
+        assert_eq!(status, 406);
+        let body: Value = serde_json::from_slice(&body).unwrap();
+        assert!(body["message"].as_str().unwrap().contains("application/xml"));
+
+        // CSV chỉ có cho list endpoint
+        let (status, _, _) = get("/users/u1", Some("text/csv")).await;
+        assert_eq!(status, 406);
+    }
+}
@@ -0,0 +1,102 @@
+#[cfg(test)]
+mod deprecation_tests {
+    use actix_web::{http::header, test, web, App, HttpResponse};
+    use chrono::{TimeZone, Utc};
+    use rust_template::middleware::{Deprecation, DEPRECATION_HEADER, SUNSET_HEADER};
+
+    fn deprecation() -> Deprecation {
+        Deprecation::new()
+            .with_sunset(Utc.with_ymd_and_hms(2027, 1, 1, 0, 0, 0).unwrap())
+            .with_replacement("/v2/reports")
+    }
+
+    async fn ok() -> HttpResponse {
+        HttpResponse::Ok().finish()
+    }
+
+    #[actix_web::test]
+    async fn test_headers_only_on_deprecated_route() {
+        let app = test::init_service(
+            App::new()
+                .service(web::resource("/reports").wrap(deprecation()).route(web::get().to(ok)))
+                .route("/v2/reports", web::get().to(ok)),
+        )
+        .await;
+
+        let resp = test::call_service(&app, test::TestRequest::get().uri("/reports").to_request()).await;
+        assert_eq!(resp.status(), 200);
+        assert_eq!(resp.headers().get(DEPRECATION_HEADER).unwrap(), "true");
+        assert_eq!(resp.headers().get(SUNSET_HEADER).unwrap(), "Fri, 01 Jan 2027 00:00:00 GMT");
+        assert_eq!(
+            resp.headers().get(header::LINK).unwrap(),
+            "</v2/reports>; rel=\"successor-version\""
+        );
+
+        let resp = test::call_service(&app, test::TestRequest::get().uri("/v2/reports").to_request()).await;
+        assert_eq!(resp.status(), 200);
+        assert!(resp.headers().get(DEPRECATION_HEADER).is_none());
+        assert!(resp.headers().get(SUNSET_HEADER).is_none());
+        assert!(resp.headers().get(header::LINK).is_none());
+    }
+
+    #[actix_web::test]
+    async fn test_sunset_and_link_are_optional() {
+        let app = test::init_service(
+            App::new().service(web::resource("/old").wrap(Deprecation::new()).route(web::get().to(ok))),
+        )
+        .await;
+
+        let resp = test::call_service(&app, test::TestRequest::get().uri("/old").to_request()).await;
+        assert_eq!(resp.headers().get(DEPRECATION_HEADER).unwrap(), "true");
+        assert!(resp.headers().get(SUNSET_HEADER).is_none());
+        assert!(resp.headers().get(header::LINK).is_none());
+    }
+
+    #[actix_web::test]
+    async fn test_successor_link_keeps_handler_links() {
+        async fn paged() -> HttpResponse {
+            HttpResponse::Ok()
+                .insert_header((header::LINK, "</reports?page=2>; rel=\"next\""))
+                .finish()
+        }
+
+        let app = test::init_service(
+            App::new().service(web::resource("/reports").wrap(deprecation()).route(web::get().to(paged))),
+        )
+        .await;
+
+        let resp = test::call_service(&app, test::TestRequest::get().uri("/reports").to_request()).await;
+        let links: Vec<_> = resp
+            .headers()
+            .get_all(header::LINK)
+            .map(|v| v.to_str().unwrap())
+            .collect();
+        assert_eq!(
+            links,
+            vec!["</reports?page=2>; rel=\"next\"", "</v2/reports>; rel=\"successor-version\""]
+        );
+    }
+
+    #[cfg(feature = "observability-metrics")]
+    #[actix_web::test]
+    async fn test_hits_are_counted_per_route() {
+        let metrics = rust_template::metrics::MetricsCollector::new();
+        let app = test::init_service(
+            App::new().service(
+                web::resource("/reports/{id}")
+                    .wrap(deprecation().with_metrics(metrics.clone()))
+                    .route(web::get().to(ok)),
+            ),
+        )
+        .await;
+
+        for id in ["1", "2"] {
+            let uri = format!("/reports/{}", id);
+            test::call_service(&app, test::TestRequest::get().uri(&uri).to_request()).await;
+        }
+
+        assert!(metrics
+            .export()
+            .contains("deprecated_endpoint_hits_total{route=\"/reports/{id}\"} 2"));
+    }
+}
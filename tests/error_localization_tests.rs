@@ -0,0 +1,145 @@
+#[cfg(test)]
+mod error_localization_tests {
+    use actix_web::{dev::ServiceResponse, test, web, App, HttpResponse};
+    use rust_template::errors::{ApiError, ErrorCode, MessageCatalog};
+    use rust_template::middleware::{Idempotency, InMemoryIdempotencyStore, Localization};
+    use serde_json::Value;
+    use std::sync::Arc;
+
+    async fn missing_user() -> Result<HttpResponse, ApiError> {
+        Err(ApiError::not_found_resource("User with id 42 not found", "user"))
+    }
+
+    async fn error_body(accept_language: Option<&str>) -> Value {
+        let app = test::init_service(
+            App::new()
+                .wrap(Localization)
+                .route("/users/42", web::get().to(missing_user)),
+        )
+        .await;
+
+        let mut req = test::TestRequest::get().uri("/users/42");
+        if let Some(accept_language) = accept_language {
+            req = req.insert_header(("Accept-Language", accept_language));
+        }
+        let resp = test::call_service(&app, req.to_request()).await;
+        assert_eq!(resp.status(), 404);
+        test::read_body_json(resp).await
+    }
+
+    #[actix_web::test]
+    async fn test_not_found_is_localized_for_vietnamese() {
+        for accept_language in ["vi", "vi-VN,vi;q=0.9,en;q=0.8", "en;q=0.5, vi"] {
+            let body = error_body(Some(accept_language)).await;
+            assert_eq!(body["message"], "Không tìm thấy tài nguyên", "{}", accept_language);
+            assert_eq!(body["error_code"], "NotFound");
+            assert_eq!(body["resource"], "user");
+        }
+    }
+
+    #[actix_web::test]
+    async fn test_english_and_missing_translations_keep_original_message() {
+        for accept_language in [None, Some("en-US"), Some("fr"), Some("*")] {
+            let body = error_body(accept_language).await;
+            assert_eq!(body["message"], "User with id 42 not found", "{:?}", accept_language);
+            assert_eq!(body["error_code"], "NotFound");
+        }
+    }
+
+    #[actix_web::test]
+    async fn test_errors_from_middleware_are_localized() {
+        let app = test::init_service(
+            App::new()
+                // Middleware trả Err thay vì response, như AuthMiddleware
+                .wrap_fn(|_req, _srv| async {
+                    Err::<ServiceResponse, _>(ApiError::unauthorized("Missing token").into())
+                })
+                .wrap(Localization)
+                .route("/users/42", web::get().to(missing_user)),
+        )
+        .await;
+
+        let req = test::TestRequest::get()
+            .uri("/users/42")
+            .insert_header(("Accept-Language", "vi-VN"))
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+
+        assert_eq!(resp.status(), 401);
+        assert_eq!(resp.headers().get("content-language").unwrap(), "vi");
+        assert_eq!(resp.headers().get("vary").unwrap(), "accept-language");
+        let body: Value = test::read_body_json(resp).await;
+        assert_eq!(body["message"], "Cần xác thực để truy cập");
+    }
+
+    #[actix_web::test]
+    async fn test_error_responses_vary_on_accept_language() {
+        let app = test::init_service(
+            App::new()
+                .wrap(Localization)
+                .route("/users/42", web::get().to(missing_user))
+                .route("/ok", web::get().to(HttpResponse::Ok)),
+        )
+        .await;
+
+        let get = |uri: &str| test::TestRequest::get().uri(uri).to_request();
+
+        let resp = test::call_service(&app, get("/users/42")).await;
+        assert_eq!(resp.headers().get("vary").unwrap(), "accept-language");
+        assert_eq!(resp.headers().get("content-language").unwrap(), "en");
+
+        let resp = test::call_service(&app, get("/ok")).await;
+        assert!(resp.headers().get("vary").is_none());
+    }
+
+    #[actix_web::test]
+    async fn test_idempotent_replay_states_its_original_language() {
+        async fn conflict() -> Result<HttpResponse, ApiError> {
+            Err(ApiError::Conflict { message: "Email taken".to_string(), field: None })
+        }
+
+        let app = test::init_service(
+            App::new()
+                .wrap(Idempotency::new(Arc::new(InMemoryIdempotencyStore::new())))
+                .wrap(Localization)
+                .route("/users", web::post().to(conflict)),
+        )
+        .await;
+
+        let post = |accept_language: &str| {
+            test::TestRequest::post()
+                .uri("/users")
+                .insert_header(("Idempotency-Key", "abc"))
+                .insert_header(("Accept-Language", accept_language))
+                .to_request()
+        };
+
+        let first = test::call_service(&app, post("vi")).await;
+        assert_eq!(first.headers().get("content-language").unwrap(), "vi");
+        let first_body = test::read_body(first).await;
+
+        // Replay giữ nguyên response đầu tiên, kèm đúng nhãn ngôn ngữ
+        let replay = test::call_service(&app, post("en")).await;
+        assert_eq!(replay.status(), 409);
+        assert_eq!(replay.headers().get("idempotent-replayed").unwrap(), "true");
+        assert_eq!(replay.headers().get("content-language").unwrap(), "vi");
+        assert_eq!(test::read_body(replay).await, first_body);
+    }
+
+    #[test]
+    fn test_catalog_lookup() {
+        let catalog =
+            MessageCatalog::builtin().with_message("pt-BR", ErrorCode::NotFound, "Não encontrado");
+
+        assert_eq!(catalog.message("VI-vn", ErrorCode::NotFound), Some("Không tìm thấy tài nguyên"));
+        assert_eq!(catalog.message("pt-br", ErrorCode::NotFound), Some("Não encontrado"));
+        assert_eq!(catalog.message("pt", ErrorCode::NotFound), None);
+        assert_eq!(catalog.language(Some("pt-BR"), ErrorCode::NotFound), "pt-br");
+        assert_eq!(catalog.language(Some("de"), ErrorCode::NotFound), "en");
+        assert_eq!(catalog.language(None, ErrorCode::NotFound), "en");
+        assert_eq!(
+            catalog.localize(Some("de"), ErrorCode::Conflict, "Email taken".to_string()),
+            "Email taken"
+        );
+    }
+}
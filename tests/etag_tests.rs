@@ -0,0 +1,62 @@
+mod common;
+
+#[cfg(test)]
+mod etag_tests {
+    use crate::common::user;
+    use actix_web::{http::header, test, web, App};
+    use rust_template::routes::configure_user_routes;
+    use rust_template::state::AppState;
+    use serde_json::json;
+
+    fn state() -> web::Data<AppState> {
+        web::Data::new(AppState::with_users(vec![user("user-1")
+            .name("Alice")
+            .email("alice@example.com")
+            .build()]))
+    }
+
+    #[actix_web::test]
+    async fn test_get_user_returns_304_when_etag_matches() {
+        let app = test::init_service(App::new().app_data(state()).configure(configure_user_routes)).await;
+
+        let resp = test::call_service(&app, test::TestRequest::get().uri("/users/user-1").to_request()).await;
+        assert_eq!(resp.status(), 200);
+        let etag = resp.headers().get(header::ETAG).unwrap().to_str().unwrap().to_string();
+        assert!(etag.starts_with("W/\""));
+
+        let req = test::TestRequest::get()
+            .uri("/users/user-1")
+            .insert_header((header::IF_NONE_MATCH, etag.clone()))
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), 304);
+        assert_eq!(resp.headers().get(header::ETAG).unwrap().to_str().unwrap(), etag);
+    }
+
+    #[actix_web::test]
+    async fn test_stale_if_match_on_update_conflicts() {
+        let app = test::init_service(App::new().app_data(state()).configure(configure_user_routes)).await;
+
+        let resp = test::call_service(&app, test::TestRequest::get().uri("/users/user-1").to_request()).await;
+        let etag = resp.headers().get(header::ETAG).unwrap().to_str().unwrap().to_string();
+
+        // First update with the fresh ETag succeeds and changes it
+        let req = test::TestRequest::put()
+            .uri("/users/user-1")
+            .insert_header((header::IF_MATCH, etag.clone()))
+            .set_json(json!({ "name": "Alice Updated" }))
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), 200);
+        assert_ne!(resp.headers().get(header::ETAG).unwrap().to_str().unwrap(), etag);
+
+        // Second update reusing the old ETag is rejected
+        let req = test::TestRequest::put()
+            .uri("/users/user-1")
+            .insert_header((header::IF_MATCH, etag))
+            .set_json(json!({ "name": "Stale Write" }))
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), 409);
+    }
+}
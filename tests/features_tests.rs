@@ -2,6 +2,8 @@ use rust_template::features::{FeatureFlagManager, FeatureFlag, ABTestManager, AB
 use rust_template::multitenancy::{TenantManager, Tenant};
 use std::collections::HashMap;
 
+mod common;
+
 #[cfg(test)]
 mod feature_flag_tests {
     use super::*;
@@ -149,6 +151,114 @@ mod feature_flag_tests {
     }
 }
 
+#[cfg(test)]
+mod tenant_flag_tests {
+    use super::*;
+    use actix_web::test::TestRequest;
+    use actix_web::{HttpMessage, HttpRequest};
+    use rust_template::auth::Claims;
+    use rust_template::features::TargetingRule;
+
+    fn manager_with(enabled: bool, rollout_percentage: u8) -> FeatureFlagManager {
+        let manager = FeatureFlagManager::new();
+        manager.add_flag(FeatureFlag {
+            name: "new_billing".to_string(),
+            enabled,
+            description: "New billing page".to_string(),
+            rollout_percentage,
+        });
+        manager
+    }
+
+    #[test]
+    fn test_globally_off_but_on_for_one_tenant() {
+        let manager = manager_with(false, 100);
+        manager.set_tenant_override("new_billing", "tenant-a", true);
+
+        assert!(manager.is_enabled_for_tenant("new_billing", "tenant-a"));
+        assert!(!manager.is_enabled_for_tenant("new_billing", "tenant-b"));
+        assert!(!manager.is_enabled("new_billing"));
+    }
+
+    #[test]
+    fn test_globally_on_but_off_for_one_tenant() {
+        let manager = manager_with(true, 100);
+        manager.set_tenant_override("new_billing", "tenant-a", false);
+
+        assert!(!manager.is_enabled_for_tenant("new_billing", "tenant-a"));
+        assert!(manager.is_enabled_for_tenant("new_billing", "tenant-b"));
+
+        manager.remove_tenant_override("new_billing", "tenant-a");
+        assert!(manager.is_enabled_for_tenant("new_billing", "tenant-a"));
+    }
+
+    #[test]
+    fn test_override_beats_targeting_rule_beats_rollout() {
+        let manager = manager_with(true, 0);
+        manager.add_targeting_rule(
+            "new_billing",
+            TargetingRule {
+                tenant_ids: ["tenant-a".to_string(), "tenant-b".to_string()].into_iter().collect(),
+                enabled: true,
+            },
+        );
+        manager.set_tenant_override("new_billing", "tenant-b", false);
+
+        assert!(manager.is_enabled_for_tenant("new_billing", "tenant-a"));
+        assert!(!manager.is_enabled_for_tenant("new_billing", "tenant-b"));
+        // 0% rollout cho tenant không có rule
+        assert!(!manager.is_enabled_for_tenant("new_billing", "tenant-c"));
+    }
+
+    /// Giả lập AuthMiddleware với token đã xác minh
+    fn authenticated(req: TestRequest, tenant_id: Option<&str>) -> HttpRequest {
+        let req = req.to_http_request();
+        req.extensions_mut().insert(Claims {
+            sub: "user-1".to_string(),
+            email: "user@example.com".to_string(),
+            role: "user".to_string(),
+            exp: i64::MAX,
+            iat: 0,
+            iss: None,
+            aud: None,
+            roles: vec![],
+            permissions: vec![],
+            tenant_id: tenant_id.map(String::from),
+        });
+        req
+    }
+
+    #[test]
+    fn test_request_uses_verified_tenant() {
+        let manager = manager_with(false, 100);
+        manager.set_tenant_override("new_billing", "tenant-a", true);
+
+        let req = authenticated(TestRequest::default(), Some("tenant-a"));
+        assert!(manager.is_enabled_for_request("new_billing", &req));
+
+        let req = authenticated(TestRequest::default(), None);
+        assert!(!manager.is_enabled_for_request("new_billing", &req));
+
+        let req = TestRequest::default().to_http_request();
+        assert!(!manager.is_enabled_for_request("new_billing", &req));
+    }
+
+    #[test]
+    fn test_spoofed_tenant_header_does_not_enable_override() {
+        let manager = manager_with(false, 100);
+        manager.set_tenant_override("new_billing", "tenant-a", true);
+
+        let req = TestRequest::default()
+            .insert_header(("X-Tenant-ID", "tenant-a"))
+            .to_http_request();
+        assert!(!manager.is_enabled_for_request("new_billing", &req));
+
+        let spoofed = TestRequest::default().insert_header(("X-Tenant-ID", "tenant-a"));
+        let req = authenticated(spoofed, Some("tenant-b"));
+        assert!(!manager.is_enabled_for_request("new_billing", &req));
+    }
+}
+
 #[cfg(test)]
 mod ab_test_tests {
     use super::*;
@@ -166,7 +276,7 @@ mod ab_test_tests {
             ],
         };
         
-        manager.add_test(test);
+        manager.add_test(test).unwrap();
         
         let variant = manager.get_variant("button_color", "user123");
         assert!(variant.is_some());
@@ -187,7 +297,7 @@ mod ab_test_tests {
             ],
         };
         
-        manager.add_test(test);
+        manager.add_test(test).unwrap();
         
         let variant = manager.get_variant("disabled_test", "user123");
         assert!(variant.is_none());
@@ -206,7 +316,7 @@ mod ab_test_tests {
             ],
         };
         
-        manager.add_test(test);
+        manager.add_test(test).unwrap();
         
         // Same user should get same variant
         let variant1 = manager.get_variant("consistency_test", "user123");
@@ -228,7 +338,7 @@ mod ab_test_tests {
             ],
         };
         
-        manager.add_test(test);
+        manager.add_test(test).unwrap();
         
         // All users should get variant A
         for i in 0..10 {
@@ -249,7 +359,7 @@ mod ab_test_tests {
             ],
         };
         
-        manager.add_test(test.clone());
+        manager.add_test(test.clone()).unwrap();
         
         let retrieved = manager.get_test("test1");
         assert!(retrieved.is_some());
@@ -266,7 +376,7 @@ mod ab_test_tests {
                 enabled: true,
                 variants: vec![],
             };
-            manager.add_test(test);
+            manager.add_test(test).unwrap();
         }
         
         let tests = manager.list_tests();
@@ -283,7 +393,7 @@ mod ab_test_tests {
             variants: vec![],
         };
         
-        manager.add_test(test);
+        manager.add_test(test).unwrap();
         assert!(manager.get_test("temp_test").is_some());
         
         manager.remove_test("temp_test");
@@ -372,3 +482,523 @@ mod multitenancy_tests {
     }
 }
 
+
+#[cfg(test)]
+mod tenant_quota_tests {
+    use super::*;
+    use actix_web::{test, web, App, HttpResponse};
+    use chrono::{Duration, TimeZone, Utc};
+    use rust_template::auth::{AuthMiddleware, JwtManager};
+    use rust_template::errors::ApiError;
+    use rust_template::multitenancy::{Quota, QuotaPeriod, QuotaTracker, TenantQuota};
+    use rust_template::utils::clock::MockClock;
+    use std::sync::Arc;
+
+    const JWT_SECRET: &str = "test-secret-key-with-at-least-32-chars";
+
+    fn manager(clock: &MockClock) -> TenantManager {
+        TenantManager::new().with_quotas(QuotaTracker::new().with_clock(Arc::new(clock.clone())))
+    }
+
+    #[tokio::test]
+    async fn test_quota_is_enforced() {
+        let clock = MockClock::default();
+        let manager = manager(&clock);
+        manager
+            .set_quota("acme", "users", Quota { limit: 2, period: QuotaPeriod::Total })
+            .unwrap();
+
+        assert_eq!(manager.check_quota("acme", "users").await.unwrap(), Some(2));
+        assert_eq!(manager.consume_quota("acme", "users", 1).await.unwrap(), Some(1));
+        assert_eq!(manager.consume_quota("acme", "users", 1).await.unwrap(), Some(0));
+
+        let exceeded = manager.consume_quota("acme", "users", 1).await;
+        assert!(matches!(
+            exceeded,
+            Err(ApiError::RateLimitExceeded { retry_after: None, .. })
+        ));
+        assert_eq!(manager.check_quota("acme", "users").await.unwrap(), Some(0));
+
+        // Tenant/resource không có quota thì không giới hạn
+        assert_eq!(manager.check_quota("globex", "users").await.unwrap(), None);
+        assert_eq!(manager.consume_quota("acme", "storage", 100).await.unwrap(), None);
+    }
+
+    #[tokio::test]
+    async fn test_monthly_quota_resets_at_window_boundary() {
+        let clock = MockClock::new(Utc.with_ymd_and_hms(2024, 1, 31, 23, 59, 0).unwrap());
+        let manager = manager(&clock);
+        manager
+            .set_quota("acme", "requests", Quota { limit: 3, period: QuotaPeriod::Monthly })
+            .unwrap();
+
+        manager.consume_quota("acme", "requests", 3).await.unwrap();
+        let exceeded = manager.consume_quota("acme", "requests", 1).await;
+        assert!(matches!(
+            exceeded,
+            Err(ApiError::RateLimitExceeded { retry_after: Some(60), .. })
+        ));
+
+        clock.advance(Duration::minutes(1));
+        assert_eq!(manager.check_quota("acme", "requests").await.unwrap(), Some(3));
+        assert_eq!(manager.consume_quota("acme", "requests", 1).await.unwrap(), Some(2));
+    }
+
+    #[actix_web::test]
+    async fn test_middleware_counts_the_tenant_from_the_token() {
+        let clock = MockClock::default();
+        let manager = Arc::new(manager(&clock));
+        manager
+            .set_quota("acme", "requests", Quota { limit: 1, period: QuotaPeriod::Daily })
+            .unwrap();
+
+        let jwt = JwtManager::new(JWT_SECRET.to_string(), 1);
+        let acme = jwt.create_tenant_token("user-1", "user@acme.com", "user", "acme").unwrap();
+        let app = test::init_service(
+            App::new()
+                .wrap(TenantQuota::new(manager.clone(), "requests"))
+                .wrap(AuthMiddleware::new(jwt.clone()))
+                .route("/", web::get().to(HttpResponse::Ok)),
+        )
+        .await;
+        let request = |token: &str| {
+            test::TestRequest::get()
+                .uri("/")
+                .insert_header(("Authorization", format!("Bearer {}", token)))
+        };
+
+        let resp = test::call_service(&app, request(&acme).to_request()).await;
+        assert_eq!(resp.status(), 200);
+        assert_eq!(resp.headers().get("x-quota-remaining").unwrap(), "0");
+
+        let err = test::try_call_service(&app, request(&acme).to_request()).await.unwrap_err();
+        assert_eq!(err.as_response_error().status_code(), 429);
+
+        // Token không có tenant thì không tính quota
+        let plain = jwt.create_token("user-2", "user@example.com", "user").unwrap();
+        let resp = test::call_service(&app, request(&plain).to_request()).await;
+        assert_eq!(resp.status(), 200);
+        assert!(resp.headers().get("x-quota-remaining").is_none());
+    }
+
+    #[actix_web::test]
+    async fn test_tenant_header_is_not_trusted() {
+        let clock = MockClock::default();
+        let manager = Arc::new(manager(&clock));
+        for tenant in ["acme", "globex"] {
+            manager
+                .set_quota(tenant, "requests", Quota { limit: 1, period: QuotaPeriod::Daily })
+                .unwrap();
+        }
+
+        let jwt = JwtManager::new(JWT_SECRET.to_string(), 1);
+        let acme = jwt.create_tenant_token("user-1", "user@acme.com", "user", "acme").unwrap();
+        let app = test::init_service(
+            App::new()
+                .wrap(TenantQuota::new(manager.clone(), "requests"))
+                .wrap(AuthMiddleware::new(jwt))
+                .route("/", web::get().to(HttpResponse::Ok)),
+        )
+        .await;
+
+        // X-Tenant-ID không đổi được tenant bị tính quota
+        let req = test::TestRequest::get()
+            .uri("/")
+            .insert_header(("Authorization", format!("Bearer {}", acme)))
+            .insert_header(("X-Tenant-ID", "globex"))
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), 200);
+
+        assert_eq!(manager.check_quota("acme", "requests").await.unwrap(), Some(0));
+        assert_eq!(manager.check_quota("globex", "requests").await.unwrap(), Some(1));
+    }
+}
+
+#[cfg(all(test, feature = "cache-redis"))]
+mod redis_quota_tests {
+    use rust_template::multitenancy::{Quota, QuotaPeriod, QuotaTracker, RedisQuotaStore};
+    use std::sync::Arc;
+
+    async fn setup_store() -> Arc<RedisQuotaStore> {
+        Arc::new(RedisQuotaStore::new(&crate::common::setup_cache().await))
+    }
+
+    #[tokio::test]
+    async fn test_replicas_share_usage() {
+        let store = setup_store().await;
+        let tenant = uuid::Uuid::new_v4().to_string();
+        let replica = || {
+            let tracker = QuotaTracker::new().with_store(store.clone());
+            tracker
+                .set_quota(&tenant, "requests", Quota { limit: 2, period: QuotaPeriod::Daily })
+                .unwrap();
+            tracker
+        };
+        let (first, second) = (replica(), replica());
+
+        assert_eq!(first.consume(&tenant, "requests", 1).await.unwrap(), Some(1));
+        assert_eq!(second.consume(&tenant, "requests", 1).await.unwrap(), Some(0));
+        assert!(first.consume(&tenant, "requests", 1).await.is_err());
+        assert_eq!(second.remaining(&tenant, "requests").await.unwrap(), Some(0));
+    }
+}
+
+#[cfg(test)]
+mod flag_store_tests {
+    use super::*;
+    use rust_template::features::{FlagStore, InMemoryFlagStore};
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    fn flag(name: &str, enabled: bool) -> FeatureFlag {
+        FeatureFlag {
+            name: name.to_string(),
+            enabled,
+            description: String::new(),
+            rollout_percentage: 100,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_flag_written_through_one_manager_is_visible_to_another() {
+        let store: Arc<dyn FlagStore> = Arc::new(InMemoryFlagStore::new());
+        let replica_a = FeatureFlagManager::new().with_store(store.clone());
+        let replica_b = FeatureFlagManager::new().with_store(store.clone());
+
+        replica_a.set_flag(flag("new_checkout", true)).await.unwrap();
+        assert!(replica_a.is_enabled("new_checkout"));
+        assert!(!replica_b.is_enabled("new_checkout"));
+
+        assert_eq!(replica_b.load().await.unwrap(), 1);
+        assert!(replica_b.is_enabled("new_checkout"));
+
+        // Xóa cũng lan sang replica khác
+        replica_a.delete_flag("new_checkout").await.unwrap();
+        replica_b.load().await.unwrap();
+        assert!(replica_b.get_flag("new_checkout").is_none());
+    }
+
+    #[tokio::test]
+    async fn test_local_only_flags_are_replaced_on_load() {
+        let store = Arc::new(InMemoryFlagStore::new());
+        store.save_flag(&flag("stored", true)).await.unwrap();
+
+        let manager = FeatureFlagManager::new().with_store(store);
+        manager.add_flag(flag("local_only", true));
+        manager.load().await.unwrap();
+
+        assert!(manager.is_enabled("stored"));
+        assert!(manager.get_flag("local_only").is_none());
+    }
+
+    #[tokio::test]
+    async fn test_manager_without_store_keeps_flags() {
+        let manager = FeatureFlagManager::new();
+        manager.set_flag(flag("memory", true)).await.unwrap();
+
+        assert_eq!(manager.load().await.unwrap(), 1);
+        assert!(manager.is_enabled("memory"));
+        assert!(manager.start_refresh(Duration::from_millis(10)).is_none());
+    }
+
+    #[tokio::test]
+    async fn test_refresh_converges_replicas() {
+        let store: Arc<dyn FlagStore> = Arc::new(InMemoryFlagStore::new());
+        let writer = FeatureFlagManager::new().with_store(store.clone());
+        let reader = FeatureFlagManager::new().with_store(store);
+        let refresh = reader.start_refresh(Duration::from_millis(10)).unwrap();
+
+        writer.set_flag(flag("dark_mode", true)).await.unwrap();
+        for _ in 0..50 {
+            if reader.is_enabled("dark_mode") {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        refresh.abort();
+
+        assert!(reader.is_enabled("dark_mode"));
+    }
+
+    #[tokio::test]
+    async fn test_ab_tests_are_shared_through_the_store() {
+        let store: Arc<dyn FlagStore> = Arc::new(InMemoryFlagStore::new());
+        let replica_a = ABTestManager::new().with_store(store.clone());
+        let replica_b = ABTestManager::new().with_store(store);
+
+        replica_a
+            .set_test(ABTest {
+                name: "pricing".to_string(),
+                enabled: true,
+                variants: vec![Variant {
+                    name: "control".to_string(),
+                    weight: 100,
+                }],
+            })
+            .await
+            .unwrap();
+
+        replica_b.load().await.unwrap();
+        assert_eq!(replica_b.get_variant("pricing", "user-1").as_deref(), Some("control"));
+
+        replica_a.delete_test("pricing").await.unwrap();
+        replica_b.load().await.unwrap();
+        assert!(replica_b.get_test("pricing").is_none());
+    }
+
+    /// Store chậm để tái hiện các thao tác chồng lên nhau
+    struct SlowStore(InMemoryFlagStore);
+
+    const DELAY: Duration = Duration::from_millis(30);
+
+    type StoreResult<T> = Result<T, rust_template::errors::ApiError>;
+
+    #[async_trait::async_trait]
+    impl FlagStore for SlowStore {
+        async fn load_flags(&self) -> StoreResult<Vec<FeatureFlag>> {
+            let flags = self.0.load_flags().await;
+            tokio::time::sleep(DELAY).await;
+            flags
+        }
+
+        async fn save_flag(&self, flag: &FeatureFlag) -> StoreResult<()> {
+            tokio::time::sleep(DELAY).await;
+            self.0.save_flag(flag).await
+        }
+
+        async fn delete_flag(&self, name: &str) -> StoreResult<()> {
+            self.0.delete_flag(name).await
+        }
+
+        async fn load_tests(&self) -> StoreResult<Vec<ABTest>> {
+            let tests = self.0.load_tests().await;
+            tokio::time::sleep(DELAY).await;
+            tests
+        }
+
+        async fn save_test(&self, test: &ABTest) -> StoreResult<()> {
+            tokio::time::sleep(DELAY).await;
+            self.0.save_test(test).await
+        }
+
+        async fn delete_test(&self, name: &str) -> StoreResult<()> {
+            self.0.delete_test(name).await
+        }
+    }
+
+    #[tokio::test]
+    async fn test_concurrent_partial_updates_keep_both_changes() {
+        let store = Arc::new(SlowStore(InMemoryFlagStore::new()));
+        let manager = FeatureFlagManager::new().with_store(store);
+        manager.set_flag(flag("search", false)).await.unwrap();
+
+        let enable = manager.update_flag("search", |current| FeatureFlag {
+            enabled: true,
+            ..current.cloned().unwrap()
+        });
+        let rollout = manager.update_flag("search", |current| FeatureFlag {
+            rollout_percentage: 25,
+            ..current.cloned().unwrap()
+        });
+        let (enable, rollout) = tokio::join!(enable, rollout);
+        enable.unwrap();
+        rollout.unwrap();
+
+        let flag = manager.get_flag("search").unwrap();
+        assert!(flag.enabled);
+        assert_eq!(flag.rollout_percentage, 25);
+    }
+
+    #[tokio::test]
+    async fn test_reload_does_not_undo_a_concurrent_set() {
+        let store = Arc::new(SlowStore(InMemoryFlagStore::new()));
+        let manager = FeatureFlagManager::new().with_store(store);
+
+        // load đọc store (còn trống) trước khi set_flag ghi
+        let (loaded, set) = tokio::join!(manager.load(), async {
+            tokio::time::sleep(DELAY / 3).await;
+            manager.set_flag(flag("dark_mode", true)).await
+        });
+        loaded.unwrap();
+        set.unwrap();
+
+        assert!(manager.is_enabled("dark_mode"));
+    }
+
+    #[tokio::test]
+    async fn test_ab_test_reload_does_not_undo_a_concurrent_set() {
+        let store = Arc::new(SlowStore(InMemoryFlagStore::new()));
+        let manager = ABTestManager::new().with_store(store);
+        let test = ABTest {
+            name: "pricing".to_string(),
+            enabled: true,
+            variants: vec![Variant {
+                name: "control".to_string(),
+                weight: 100,
+            }],
+        };
+
+        let (loaded, set) = tokio::join!(manager.load(), async {
+            tokio::time::sleep(DELAY / 3).await;
+            manager.set_test(test).await
+        });
+        loaded.unwrap();
+        set.unwrap();
+
+        assert!(manager.get_test("pricing").is_some());
+    }
+}
+
+#[cfg(test)]
+mod rollout_bucketing_tests {
+    use super::*;
+    use rust_template::features::{bucket, in_rollout};
+    use std::collections::HashSet;
+
+    fn enrolled(manager: &FeatureFlagManager, users: &[String]) -> HashSet<String> {
+        users
+            .iter()
+            .filter(|user| manager.is_enabled_for_user("new_search", user))
+            .cloned()
+            .collect()
+    }
+
+    fn set_rollout(manager: &FeatureFlagManager, rollout_percentage: u8) {
+        manager.add_flag(FeatureFlag {
+            name: "new_search".to_string(),
+            enabled: true,
+            description: String::new(),
+            rollout_percentage,
+        });
+    }
+
+    #[test]
+    fn test_raising_rollout_only_adds_users() {
+        let users: Vec<String> = (0..2000).map(|i| format!("user-{}", i)).collect();
+        let manager = FeatureFlagManager::new();
+
+        set_rollout(&manager, 10);
+        let at_10 = enrolled(&manager, &users);
+        set_rollout(&manager, 20);
+        let at_20 = enrolled(&manager, &users);
+
+        assert!(at_10.is_subset(&at_20));
+        assert!(at_20.len() > at_10.len());
+        // Phân bố gần đúng tỉ lệ rollout
+        assert!((100..300).contains(&at_10.len()), "10% bucket has {}", at_10.len());
+        assert!((300..500).contains(&at_20.len()), "20% bucket has {}", at_20.len());
+
+        set_rollout(&manager, 100);
+        assert_eq!(enrolled(&manager, &users).len(), users.len());
+        set_rollout(&manager, 0);
+        assert!(enrolled(&manager, &users).is_empty());
+    }
+
+    #[test]
+    fn test_buckets_are_stable_and_salted_by_name() {
+        assert_eq!(bucket("new_search", "user-1"), bucket("new_search", "user-1"));
+        assert!((0..100).all(|i| bucket("flag", &format!("user-{}", i)) < 100));
+
+        // Cùng user, flag khác nhau không luôn cùng bucket
+        let differs = (0..100)
+            .map(|i| format!("user-{}", i))
+            .any(|user| bucket("flag_a", &user) != bucket("flag_b", &user));
+        assert!(differs);
+
+        for i in 0..100 {
+            let user = format!("user-{}", i);
+            assert_eq!(in_rollout("flag", &user, 30), bucket("flag", &user) < 30);
+        }
+    }
+
+    #[test]
+    fn test_ab_variant_follows_the_same_bucket() {
+        let manager = ABTestManager::new();
+        manager.add_test(ABTest {
+            name: "checkout".to_string(),
+            enabled: true,
+            variants: vec![
+                Variant { name: "control".to_string(), weight: 30 },
+                Variant { name: "treatment".to_string(), weight: 70 },
+            ],
+        })
+        .unwrap();
+
+        for i in 0..200 {
+            let user = format!("user-{}", i);
+            let expected = if bucket("checkout", &user) < 30 { "control" } else { "treatment" };
+            assert_eq!(manager.get_variant("checkout", &user).as_deref(), Some(expected));
+        }
+    }
+}
+
+#[cfg(test)]
+mod ab_test_weight_tests {
+    use super::*;
+    use rust_template::errors::ApiError;
+
+    fn test_with(variants: &[(&str, u8)]) -> ABTest {
+        ABTest {
+            name: "pricing".to_string(),
+            enabled: true,
+            variants: variants
+                .iter()
+                .map(|(name, weight)| Variant { name: name.to_string(), weight: *weight })
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn test_bad_weights_are_rejected() {
+        let manager = ABTestManager::new();
+
+        let err = manager.add_test(test_with(&[("a", 0), ("b", 0)])).unwrap_err();
+        assert!(matches!(err, ApiError::ValidationError { .. }));
+        let err = manager.add_test(test_with(&[("a", 50), ("a", 50)])).unwrap_err();
+        assert!(matches!(err, ApiError::ValidationError { .. }));
+
+        assert!(manager.get_test("pricing").is_none());
+    }
+
+    #[test]
+    fn test_empty_variants_are_allowed_but_select_nothing() {
+        let manager = ABTestManager::new();
+        manager.add_test(test_with(&[])).unwrap();
+
+        assert!(manager.get_test("pricing").is_some());
+        assert_eq!(manager.get_variant("pricing", "user-1"), None);
+    }
+
+    #[test]
+    fn test_weights_are_normalized_deterministically() {
+        // 1:3 tương đương 25:75
+        let relative = ABTestManager::new();
+        relative.add_test(test_with(&[("a", 1), ("b", 3)])).unwrap();
+        let percent = ABTestManager::new();
+        percent.add_test(test_with(&[("a", 25), ("b", 75)])).unwrap();
+
+        let users: Vec<String> = (0..1000).map(|i| format!("user-{}", i)).collect();
+        let mut a_count = 0;
+        for user in &users {
+            let variant = relative.get_variant("pricing", user);
+            assert_eq!(variant, relative.get_variant("pricing", user));
+            assert_eq!(variant, percent.get_variant("pricing", user));
+            if variant.as_deref() == Some("a") {
+                a_count += 1;
+            }
+        }
+        assert!((150..350).contains(&a_count), "variant a got {}", a_count);
+    }
+
+    #[test]
+    fn test_weights_over_255_in_total_do_not_overflow() {
+        let manager = ABTestManager::new();
+        manager.add_test(test_with(&[("a", 200), ("b", 200)])).unwrap();
+
+        let variants: std::collections::HashSet<_> = (0..200)
+            .filter_map(|i| manager.get_variant("pricing", &format!("user-{}", i)))
+            .collect();
+        assert_eq!(variants.len(), 2);
+    }
+}
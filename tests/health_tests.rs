@@ -0,0 +1,292 @@
+#[cfg(test)]
+mod health_uptime_tests {
+    use actix_web::{test, web, App};
+    use rust_template::routes::configure_health_routes;
+    use rust_template::state::AppState;
+    use serde_json::Value;
+    use std::time::Duration;
+
+    #[actix_web::test]
+    async fn test_uptime_is_non_zero_and_increases() {
+        let app = test::init_service(
+            App::new()
+                .app_data(web::Data::new(AppState::new()))
+                .configure(configure_health_routes),
+        )
+        .await;
+
+        let first: Value =
+            test::call_and_read_body_json(&app, test::TestRequest::get().uri("/health").to_request()).await;
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        let second: Value =
+            test::call_and_read_body_json(&app, test::TestRequest::get().uri("/health").to_request()).await;
+
+        let first_uptime = first["data"]["uptime_seconds"].as_f64().unwrap();
+        let second_uptime = second["data"]["uptime_seconds"].as_f64().unwrap();
+        assert!(first_uptime > 0.0);
+        assert!(second_uptime > first_uptime);
+
+        assert!(first["data"]["uptime"].as_str().unwrap().ends_with('s'));
+        assert_eq!(first["data"]["build"]["version"], env!("CARGO_PKG_VERSION"));
+        assert!(first["data"]["build"]["git_sha"].is_string());
+    }
+}
+
+#[cfg(test)]
+mod liveness_tests {
+    use actix_web::{test, web, App};
+    use rust_template::health::Watchdog;
+    use rust_template::routes::configure_health_routes;
+    use rust_template::state::AppState;
+    use rust_template::utils::MockClock;
+    use serde_json::Value;
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    async fn live(state: AppState) -> (u16, Value) {
+        let app = test::init_service(
+            App::new()
+                .app_data(web::Data::new(state))
+                .configure(configure_health_routes),
+        )
+        .await;
+        let resp = test::call_service(&app, test::TestRequest::get().uri("/health/live").to_request()).await;
+        let status = resp.status().as_u16();
+        (status, test::read_body_json(resp).await)
+    }
+
+    #[actix_web::test]
+    async fn test_fresh_tick_is_alive() {
+        let clock = MockClock::default();
+        let watchdog = Watchdog::with_clock(Duration::from_secs(10), Arc::new(clock.clone()));
+        clock.advance(chrono::Duration::seconds(5));
+
+        let (status, body) = live(AppState::builder().with_watchdog(watchdog).build()).await;
+
+        assert_eq!(status, 200);
+        assert_eq!(body["data"]["alive"], true);
+    }
+
+    #[actix_web::test]
+    async fn test_stale_tick_returns_503() {
+        let clock = MockClock::default();
+        let watchdog = Watchdog::with_clock(Duration::from_secs(10), Arc::new(clock.clone()));
+        // Không có tick nào trong 30s: runtime coi như bị treo
+        clock.advance(chrono::Duration::seconds(30));
+
+        let (status, body) = live(AppState::builder().with_watchdog(watchdog.clone()).build()).await;
+
+        assert_eq!(status, 503);
+        assert_eq!(body["data"]["alive"], false);
+        assert_eq!(body["data"]["tick_age_ms"], 30_000);
+
+        // Tick mới làm liveness hồi phục
+        watchdog.tick();
+        let (status, _) = live(AppState::builder().with_watchdog(watchdog).build()).await;
+        assert_eq!(status, 200);
+    }
+
+    #[actix_web::test]
+    async fn test_background_task_keeps_ticking() {
+        let watchdog = Watchdog::new(Duration::from_secs(10));
+        let handle = watchdog.start();
+        tokio::time::sleep(Duration::from_millis(10)).await;
+
+        assert!(watchdog.is_alive());
+        assert!(watchdog.tick_age() < Duration::from_secs(1));
+        handle.abort();
+    }
+
+    #[actix_web::test]
+    async fn test_one_stale_worker_fails_liveness() {
+        let clock = MockClock::default();
+        let watchdog = Watchdog::with_clock(Duration::from_secs(10), Arc::new(clock.clone()));
+        let healthy = watchdog.register_worker();
+        let _wedged = watchdog.register_worker();
+        clock.advance(chrono::Duration::seconds(30));
+
+        // Runtime chính và một worker vẫn tick, worker còn lại bị treo
+        watchdog.tick();
+        healthy.tick();
+        let (status, body) = live(AppState::builder().with_watchdog(watchdog).build()).await;
+
+        assert_eq!(status, 503);
+        assert_eq!(body["data"]["tick_age_ms"], 30_000);
+    }
+
+    #[actix_web::test]
+    async fn test_worker_task_keeps_ticking() {
+        let watchdog = Watchdog::new(Duration::from_secs(10));
+        let handle = watchdog.start_on_worker();
+        tokio::time::sleep(Duration::from_millis(10)).await;
+
+        assert!(watchdog.is_alive());
+        assert!(watchdog.tick_age() < Duration::from_secs(1));
+        handle.abort();
+    }
+
+    #[actix_web::test]
+    async fn test_without_watchdog_is_always_alive() {
+        let (status, body) = live(AppState::new()).await;
+
+        assert_eq!(status, 200);
+        assert_eq!(body["data"]["alive"], true);
+    }
+}
+
+#[cfg(test)]
+mod readiness_tests {
+    use actix_web::{test, web, App};
+    use async_trait::async_trait;
+    use rust_template::health::{tcp_probe, CheckResult, HealthCheckable};
+    use rust_template::routes::configure_health_routes;
+    use rust_template::state::AppState;
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    struct MockCheck {
+        name: &'static str,
+        critical: bool,
+        healthy: bool,
+    }
+
+    #[async_trait]
+    impl HealthCheckable for MockCheck {
+        fn name(&self) -> &str {
+            self.name
+        }
+
+        fn is_critical(&self) -> bool {
+            self.critical
+        }
+
+        async fn check(&self) -> CheckResult {
+            if self.healthy {
+                CheckResult::ok(1)
+            } else {
+                CheckResult::unhealthy(format!("{} down", self.name))
+            }
+        }
+    }
+
+    fn check(name: &'static str, critical: bool, healthy: bool) -> Arc<dyn HealthCheckable> {
+        Arc::new(MockCheck { name, critical, healthy })
+    }
+
+    async fn ready(state: AppState) -> (u16, serde_json::Value) {
+        let app = test::init_service(
+            App::new()
+                .app_data(web::Data::new(state))
+                .configure(configure_health_routes),
+        )
+        .await;
+        let resp = test::call_service(&app, test::TestRequest::get().uri("/health/ready").to_request()).await;
+        let status = resp.status().as_u16();
+        (status, test::read_body_json(resp).await)
+    }
+
+    #[actix_web::test]
+    async fn test_all_registered_checks_are_reported() {
+        let state = AppState::new()
+            .with_health_check(check("database", true, true))
+            .with_health_check(check("kafka", false, true));
+
+        let (status, body) = ready(state).await;
+
+        assert_eq!(status, 200);
+        assert_eq!(body["data"]["ready"], true);
+        assert_eq!(body["data"]["checks"]["overall"], "healthy");
+        assert_eq!(body["data"]["checks"]["database"]["status"], "healthy");
+        assert_eq!(body["data"]["checks"]["kafka"]["status"], "healthy");
+    }
+
+    #[actix_web::test]
+    async fn test_non_critical_failure_degrades() {
+        let state = AppState::new()
+            .with_health_check(check("database", true, true))
+            .with_health_check(check("cache", false, false));
+
+        let (status, body) = ready(state).await;
+
+        assert_eq!(status, 200);
+        assert_eq!(body["data"]["checks"]["overall"], "degraded");
+        assert_eq!(body["data"]["checks"]["cache"]["message"], "cache down");
+    }
+
+    #[actix_web::test]
+    async fn test_critical_failure_is_not_ready() {
+        let state = AppState::new()
+            .with_health_check(check("database", true, false))
+            .with_health_check(check("cache", false, true));
+
+        let (status, body) = ready(state).await;
+
+        assert_eq!(status, 503);
+        assert_eq!(body["data"]["ready"], false);
+        assert_eq!(body["data"]["checks"]["overall"], "unhealthy");
+    }
+
+    #[cfg(all(feature = "database-postgres", feature = "cache-redis"))]
+    #[actix_web::test]
+    async fn test_builder_without_database_reports_not_configured() {
+        use rust_template::cache::CacheManager;
+
+        let redis_url = std::env::var("REDIS_URL").unwrap_or_else(|_| "redis://localhost:6379".to_string());
+        let cache = CacheManager::new(&redis_url).await.unwrap();
+        let state = AppState::builder().with_cache(cache).build();
+
+        assert!(state.db_pool.is_none());
+        assert!(state.cache_manager.is_some());
+        assert!(state.audit_logger.is_none());
+
+        let (status, body) = ready(state).await;
+
+        assert_eq!(status, 200);
+        assert_eq!(body["data"]["checks"]["database"]["status"], "not_configured");
+        assert_eq!(body["data"]["checks"]["cache"]["status"], "healthy");
+    }
+
+    #[tokio::test]
+    async fn test_tcp_probe() {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap();
+
+        let url = format!("amqp://guest:guest@{}/vhost", address);
+        assert!(tcp_probe(&url, Duration::from_secs(1)).await.is_ok());
+        assert!(tcp_probe("", Duration::from_secs(1)).await.is_err());
+    }
+}
+
+#[cfg(test)]
+mod self_check_tests {
+    use rust_template::health::self_check::Unavailable;
+    use rust_template::health::{HealthCheckable, SelfCheckReport};
+    use std::sync::Arc;
+
+    #[tokio::test]
+    async fn test_passes_without_failures() {
+        let report = SelfCheckReport::run(Ok(()), &[]).await;
+
+        assert!(report.is_ok());
+        assert!(report.to_string().ends_with("Self-check passed"));
+    }
+
+    #[tokio::test]
+    async fn test_invalid_config_fails() {
+        let report = SelfCheckReport::run(Err("JWT secret too short".to_string()), &[]).await;
+
+        assert!(!report.is_ok());
+        assert!(report.to_string().contains("[FAIL] configuration: JWT secret too short"));
+    }
+
+    #[tokio::test]
+    async fn test_unreachable_dependency_fails() {
+        let checks: Vec<Arc<dyn HealthCheckable>> =
+            vec![Arc::new(Unavailable::new("cache", "connection refused"))];
+
+        let report = SelfCheckReport::run(Ok(()), &checks).await;
+
+        assert!(!report.is_ok());
+        assert!(report.to_string().contains("[FAIL] cache (unhealthy): connection refused"));
+    }
+}
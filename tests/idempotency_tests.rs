@@ -0,0 +1,145 @@
+#[cfg(test)]
+mod idempotency_tests {
+    use actix_web::{test, web, App, HttpResponse};
+    use rust_template::middleware::{Idempotency, InMemoryIdempotencyStore};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    async fn create(counter: web::Data<AtomicUsize>) -> HttpResponse {
+        let n = counter.fetch_add(1, Ordering::SeqCst) + 1;
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        HttpResponse::Created()
+            .insert_header(("Location", format!("/things/{}", n)))
+            .insert_header(("ETag", format!("W/\"{}\"", n)))
+            .json(serde_json::json!({ "id": n }))
+    }
+
+    macro_rules! app {
+        ($counter:expr) => {
+            test::init_service(
+                App::new()
+                    .app_data($counter.clone())
+                    .wrap(Idempotency::new(Arc::new(InMemoryIdempotencyStore::new())))
+                    .route("/things", web::post().to(create)),
+            )
+            .await
+        };
+    }
+
+    fn post(key: Option<&str>) -> test::TestRequest {
+        let req = test::TestRequest::post().uri("/things");
+        match key {
+            Some(key) => req.insert_header(("Idempotency-Key", key)),
+            None => req,
+        }
+    }
+
+    #[actix_web::test]
+    async fn test_duplicate_key_replays_identical_response() {
+        let counter = web::Data::new(AtomicUsize::new(0));
+        let app = app!(counter);
+
+        let first = test::call_service(&app, post(Some("abc")).to_request()).await;
+        assert_eq!(first.status(), 201);
+        let first_body = test::read_body(first).await;
+
+        let second = test::call_service(&app, post(Some("abc")).to_request()).await;
+        assert_eq!(second.status(), 201);
+        assert_eq!(second.headers().get("idempotent-replayed").unwrap(), "true");
+        assert_eq!(test::read_body(second).await, first_body);
+
+        assert_eq!(counter.load(Ordering::SeqCst), 1);
+
+        // A different key is a new request
+        let other = test::call_service(&app, post(Some("def")).to_request()).await;
+        assert_eq!(other.status(), 201);
+        assert_eq!(counter.load(Ordering::SeqCst), 2);
+    }
+
+    #[actix_web::test]
+    async fn test_concurrent_duplicate_conflicts() {
+        let counter = web::Data::new(AtomicUsize::new(0));
+        let app = app!(counter);
+
+        let (first, second) = futures::join!(
+            test::call_service(&app, post(Some("same")).to_request()),
+            test::call_service(&app, post(Some("same")).to_request()),
+        );
+
+        let mut statuses = vec![first.status().as_u16(), second.status().as_u16()];
+        statuses.sort();
+        assert_eq!(statuses, vec![201, 409]);
+        assert_eq!(counter.load(Ordering::SeqCst), 1);
+    }
+
+    #[actix_web::test]
+    async fn test_replay_keeps_location_and_etag() {
+        let counter = web::Data::new(AtomicUsize::new(0));
+        let app = app!(counter);
+
+        test::call_service(&app, post(Some("abc")).to_request()).await;
+        let replay = test::call_service(&app, post(Some("abc")).to_request()).await;
+
+        assert_eq!(replay.headers().get("location").unwrap(), "/things/1");
+        assert_eq!(replay.headers().get("etag").unwrap(), "W/\"1\"");
+    }
+
+    #[actix_web::test]
+    async fn test_key_reused_with_different_body_is_422() {
+        let counter = web::Data::new(AtomicUsize::new(0));
+        let app = app!(counter);
+
+        let first = post(Some("abc")).set_payload(r#"{"amount":10}"#).to_request();
+        assert_eq!(test::call_service(&app, first).await.status(), 201);
+
+        let other_body = post(Some("abc")).set_payload(r#"{"amount":99}"#).to_request();
+        let resp = test::call_service(&app, other_body).await;
+        assert_eq!(resp.status(), 422);
+        assert_eq!(counter.load(Ordering::SeqCst), 1);
+    }
+
+    #[actix_web::test]
+    async fn test_keys_are_scoped_per_caller() {
+        let counter = web::Data::new(AtomicUsize::new(0));
+        let app = app!(counter);
+
+        for token in ["alice", "bob"] {
+            let req = post(Some("same"))
+                .insert_header(("Authorization", format!("Bearer {}", token)))
+                .to_request();
+            let resp = test::call_service(&app, req).await;
+            assert_eq!(resp.status(), 201);
+            assert!(resp.headers().get("idempotent-replayed").is_none());
+        }
+        assert_eq!(counter.load(Ordering::SeqCst), 2);
+    }
+
+    #[actix_web::test]
+    async fn test_dropped_request_releases_key() {
+        let counter = web::Data::new(AtomicUsize::new(0));
+        let app = app!(counter);
+
+        // Client ngắt kết nối giữa chừng: future bị drop trước khi handler xong
+        let abandoned = test::call_service(&app, post(Some("abc")).to_request());
+        assert!(tokio::time::timeout(Duration::from_millis(10), abandoned).await.is_err());
+        tokio::time::sleep(Duration::from_millis(10)).await;
+
+        let retry = test::call_service(&app, post(Some("abc")).to_request()).await;
+        assert_eq!(retry.status(), 201);
+        assert!(retry.headers().get("idempotent-replayed").is_none());
+    }
+
+    #[actix_web::test]
+    async fn test_requests_without_key_pass_through() {
+        let counter = web::Data::new(AtomicUsize::new(0));
+        let app = app!(counter);
+
+        for _ in 0..2 {
+            let resp = test::call_service(&app, post(None).to_request()).await;
+            assert_eq!(resp.status(), 201);
+            assert!(resp.headers().get("idempotent-replayed").is_none());
+        }
+        assert_eq!(counter.load(Ordering::SeqCst), 2);
+    }
+}
@@ -0,0 +1,124 @@
+#[cfg(test)]
+mod request_coalescing_tests {
+    use actix_web::{test, web, App, HttpResponse};
+    use rust_template::middleware::{RequestCoalescer, COALESCED_HEADER};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::time::Duration;
+
+    async fn report(counter: web::Data<AtomicUsize>) -> HttpResponse {
+        let n = counter.fetch_add(1, Ordering::SeqCst) + 1;
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        HttpResponse::Ok()
+            .insert_header(("Set-Cookie", format!("session=run-{}", n)))
+            .json(serde_json::json!({ "run": n }))
+    }
+
+    macro_rules! app {
+        ($counter:expr, $coalescer:expr) => {
+            test::init_service(
+                App::new()
+                    .app_data($counter.clone())
+                    .wrap($coalescer.clone())
+                    .route("/report", web::get().to(report))
+                    .route("/report", web::post().to(report)),
+            )
+            .await
+        };
+    }
+
+    fn get(token: &str) -> test::TestRequest {
+        test::TestRequest::get()
+            .uri("/report?range=7d")
+            .insert_header(("Authorization", format!("Bearer {}", token)))
+    }
+
+    #[actix_web::test]
+    async fn test_concurrent_identical_gets_run_handler_once() {
+        let counter = web::Data::new(AtomicUsize::new(0));
+        let coalescer = RequestCoalescer::new();
+        let app = app!(counter, coalescer);
+
+        let responses = futures::future::join_all(
+            (0..10).map(|_| test::call_service(&app, get("alice").to_request())),
+        )
+        .await;
+
+        assert_eq!(counter.load(Ordering::SeqCst), 1);
+        let coalesced = responses
+            .iter()
+            .filter(|resp| resp.headers().contains_key(COALESCED_HEADER))
+            .count();
+        assert_eq!(coalesced, 9);
+
+        for resp in responses {
+            assert_eq!(resp.status(), 200);
+            assert_eq!(resp.headers().get("content-type").unwrap(), "application/json");
+            // Cookie chỉ dành cho leader
+            let is_leader = !resp.headers().contains_key(COALESCED_HEADER);
+            assert_eq!(resp.headers().contains_key("set-cookie"), is_leader);
+            let body: serde_json::Value = test::read_body_json(resp).await;
+            assert_eq!(body["run"], 1);
+        }
+        assert_eq!(coalescer.in_flight(), 0);
+    }
+
+    #[actix_web::test]
+    async fn test_different_callers_are_not_coalesced() {
+        let counter = web::Data::new(AtomicUsize::new(0));
+        let app = app!(counter, RequestCoalescer::new());
+
+        let (alice, bob) = futures::join!(
+            test::call_service(&app, get("alice").to_request()),
+            test::call_service(&app, get("bob").to_request()),
+        );
+
+        assert_eq!(counter.load(Ordering::SeqCst), 2);
+        assert!(!alice.headers().contains_key(COALESCED_HEADER));
+        assert!(!bob.headers().contains_key(COALESCED_HEADER));
+    }
+
+    #[actix_web::test]
+    async fn test_completed_responses_are_not_cached() {
+        let counter = web::Data::new(AtomicUsize::new(0));
+        let app = app!(counter, RequestCoalescer::new());
+
+        for run in 1..=2 {
+            let resp = test::call_service(&app, get("alice").to_request()).await;
+            let body: serde_json::Value = test::read_body_json(resp).await;
+            assert_eq!(body["run"], run);
+        }
+    }
+
+    #[actix_web::test]
+    async fn test_non_get_requests_pass_through() {
+        let counter = web::Data::new(AtomicUsize::new(0));
+        let app = app!(counter, RequestCoalescer::new());
+
+        let post = || test::TestRequest::post().uri("/report").to_request();
+        futures::join!(test::call_service(&app, post()), test::call_service(&app, post()));
+
+        assert_eq!(counter.load(Ordering::SeqCst), 2);
+    }
+
+    #[actix_web::test]
+    async fn test_conditional_and_range_requests_pass_through() {
+        for (name, value) in [
+            ("If-None-Match", "\"v1\""),
+            ("If-Modified-Since", "Wed, 01 May 2024 10:00:00 GMT"),
+            ("Range", "bytes=0-9"),
+        ] {
+            let counter = web::Data::new(AtomicUsize::new(0));
+            let app = app!(counter, RequestCoalescer::new());
+
+            let request = || get("alice").insert_header((name, value)).to_request();
+            let (first, second) = futures::join!(
+                test::call_service(&app, request()),
+                test::call_service(&app, request()),
+            );
+
+            assert_eq!(counter.load(Ordering::SeqCst), 2, "{}", name);
+            assert!(!first.headers().contains_key(COALESCED_HEADER));
+            assert!(!second.headers().contains_key(COALESCED_HEADER));
+        }
+    }
+}
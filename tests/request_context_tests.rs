@@ -0,0 +1,124 @@
+#[cfg(test)]
+mod request_context_tests {
+    use actix_web::{test, web, App, HttpMessage, HttpResponse};
+    use rust_template::auth::Claims;
+    use rust_template::middleware::{RequestContext, RequestId};
+    use serde_json::json;
+
+    async fn whoami(ctx: RequestContext, again: RequestContext) -> HttpResponse {
+        assert_eq!(ctx.request_id(), again.request_id());
+        HttpResponse::Ok().json(json!({
+            "request_id": ctx.request_id(),
+            "user": ctx.user().map(|user| user.sub.clone()),
+            "authenticated": ctx.require_user().is_ok(),
+            "tenant": ctx.tenant_id(),
+            "requested_tenant": ctx.requested_tenant_id(),
+            "locale": ctx.locale(),
+            "trace_id": ctx.trace_id(),
+        }))
+    }
+
+    fn claims() -> Claims {
+        Claims {
+            sub: "user-1".to_string(),
+            email: "user@example.com".to_string(),
+            role: "user".to_string(),
+            exp: i64::MAX,
+            iat: 0,
+            iss: None,
+            aud: None,
+            roles: vec![],
+            permissions: vec![],
+            tenant_id: Some("acme".to_string()),
+        }
+    }
+
+    #[actix_web::test]
+    async fn test_context_with_auth_and_tenant() {
+        let app = test::init_service(
+            App::new()
+                // Giả lập AuthMiddleware
+                .wrap_fn(|req, srv| {
+                    req.extensions_mut().insert(claims());
+                    actix_web::dev::Service::call(srv, req)
+                })
+                .wrap(RequestId)
+                .route("/whoami", web::get().to(whoami)),
+        )
+        .await;
+
+        let req = test::TestRequest::get()
+            .uri("/whoami")
+            .insert_header(("X-Request-Id", "req-42"))
+            .insert_header(("X-Tenant-ID", "globex"))
+            .insert_header(("Accept-Language", "en-GB;q=0.8, vi-VN, *;q=0.1"))
+            .insert_header((
+                "traceparent",
+                "00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01",
+            ))
+            .to_request();
+        let body: serde_json::Value = test::call_and_read_body_json(&app, req).await;
+
+        assert_eq!(
+            body,
+            json!({
+                "request_id": "req-42",
+                "user": "user-1",
+                "authenticated": true,
+                // Tenant lấy từ token, header chỉ là yêu cầu chưa xác minh
+                "tenant": "acme",
+                "requested_tenant": "globex",
+                "locale": "vi-VN",
+                "trace_id": "4bf92f3577b34da6a3ce929d0e0e4736",
+            })
+        );
+    }
+
+    #[actix_web::test]
+    async fn test_context_without_optional_pieces() {
+        let app = test::init_service(App::new().route("/whoami", web::get().to(whoami))).await;
+
+        let req = test::TestRequest::get().uri("/whoami").to_request();
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), 200);
+
+        let body: serde_json::Value = test::read_body_json(resp).await;
+        assert_eq!(
+            body,
+            json!({
+                "request_id": null,
+                "user": null,
+                "authenticated": false,
+                "tenant": null,
+                "requested_tenant": null,
+                "locale": null,
+                "trace_id": null,
+            })
+        );
+    }
+
+    #[actix_web::test]
+    async fn test_context_taken_before_auth_does_not_hide_the_user() {
+        let app = test::init_service(
+            App::new()
+                // Giả lập AuthMiddleware
+                .wrap_fn(|req, srv| {
+                    req.extensions_mut().insert(claims());
+                    actix_web::dev::Service::call(srv, req)
+                })
+                // Middleware ngoài cùng lấy context trước khi có user
+                .wrap_fn(|mut req, srv| {
+                    let early = futures::executor::block_on(req.extract::<RequestContext>());
+                    assert!(early.unwrap().user().is_none());
+                    actix_web::dev::Service::call(srv, req)
+                })
+                .route("/whoami", web::get().to(whoami)),
+        )
+        .await;
+
+        let req = test::TestRequest::get().uri("/whoami").to_request();
+        let body: serde_json::Value = test::call_and_read_body_json(&app, req).await;
+        assert_eq!(body["user"], "user-1");
+        assert_eq!(body["authenticated"], true);
+    }
+}
@@ -0,0 +1,179 @@
+#[cfg(test)]
+mod request_id_tests {
+    use actix_web::{test, web, App, HttpResponse};
+    use rust_template::errors::ApiError;
+    use rust_template::middleware::{RequestId, RequestIdValue};
+
+    async fn echo_request_id(request_id: RequestIdValue) -> HttpResponse {
+        HttpResponse::Ok().body(request_id.0)
+    }
+
+    async fn failing() -> Result<HttpResponse, ApiError> {
+        Err(ApiError::not_found("missing"))
+    }
+
+    macro_rules! app {
+        () => {
+            test::init_service(
+                App::new()
+                    .wrap(RequestId)
+                    .route("/echo", web::get().to(echo_request_id))
+                    .route("/fail", web::get().to(failing)),
+            )
+            .await
+        };
+    }
+
+    #[actix_web::test]
+    async fn test_inbound_request_id_is_preserved() {
+        let app = app!();
+        let req = test::TestRequest::get()
+            .uri("/echo")
+            .insert_header(("X-Request-Id", "caller-abc-123"))
+            .to_request();
+
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.headers().get("x-request-id").unwrap(), "caller-abc-123");
+
+        let body = test::read_body(resp).await;
+        assert_eq!(body, "caller-abc-123");
+    }
+
+    #[actix_web::test]
+    async fn test_traceparent_is_used_when_request_id_missing() {
+        let app = app!();
+        let req = test::TestRequest::get()
+            .uri("/echo")
+            .insert_header((
+                "traceparent",
+                "00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01",
+            ))
+            .to_request();
+
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(
+            resp.headers().get("x-request-id").unwrap(),
+            "4bf92f3577b34da6a3ce929d0e0e4736"
+        );
+    }
+
+    #[actix_web::test]
+    async fn test_invalid_request_id_is_replaced() {
+        let app = app!();
+        let req = test::TestRequest::get()
+            .uri("/echo")
+            .insert_header(("X-Request-Id", "bad id with spaces"))
+            .to_request();
+
+        let resp = test::call_service(&app, req).await;
+        let request_id = resp.headers().get("x-request-id").unwrap().to_str().unwrap();
+        assert!(uuid::Uuid::parse_str(request_id).is_ok());
+    }
+
+    #[actix_web::test]
+    async fn test_error_response_carries_request_id() {
+        let app = app!();
+        let req = test::TestRequest::get()
+            .uri("/fail")
+            .insert_header(("X-Request-Id", "req-42"))
+            .to_request();
+
+        let body: serde_json::Value = test::call_and_read_body_json(&app, req).await;
+        assert_eq!(body["request_id"], "req-42");
+    }
+}
+
+#[cfg(test)]
+mod request_correlation_tests {
+    use actix_web::{test, web, App, HttpResponse};
+    use rust_template::middleware::{Logger, RequestId};
+    use std::collections::HashMap;
+    use std::sync::{Arc, Mutex};
+    use tracing::field::{Field, Visit};
+    use tracing::span::{Attributes, Id};
+    use tracing_subscriber::layer::{Context, SubscriberExt};
+    use tracing_subscriber::Layer;
+
+    /// Ghi lại tên và fields của mọi span được tạo
+    #[derive(Clone, Default)]
+    struct SpanCapture {
+        spans: Arc<Mutex<Vec<(String, HashMap<String, String>)>>>,
+    }
+
+    impl SpanCapture {
+        fn fields(&self, name: &str) -> HashMap<String, String> {
+            self.spans
+                .lock()
+                .unwrap()
+                .iter()
+                .find(|(span, _)| span == name)
+                .map(|(_, fields)| fields.clone())
+                .unwrap_or_else(|| panic!("no {} span captured", name))
+        }
+    }
+
+    struct FieldVisitor<'a>(&'a mut HashMap<String, String>);
+
+    impl Visit for FieldVisitor<'_> {
+        fn record_str(&mut self, field: &Field, value: &str) {
+            self.0.insert(field.name().to_string(), value.to_string());
+        }
+
+        fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+            self.0.insert(field.name().to_string(), format!("{:?}", value));
+        }
+    }
+
+    impl<S: tracing::Subscriber> Layer<S> for SpanCapture {
+        fn on_new_span(&self, attrs: &Attributes<'_>, _id: &Id, _ctx: Context<'_, S>) {
+            let mut fields = HashMap::new();
+            attrs.record(&mut FieldVisitor(&mut fields));
+            self.spans
+                .lock()
+                .unwrap()
+                .push((attrs.metadata().name().to_string(), fields));
+        }
+    }
+
+    async fn request_id_of(req: test::TestRequest, capture: &SpanCapture) -> String {
+        let _guard = tracing::subscriber::set_default(tracing_subscriber::registry().with(capture.clone()));
+        let app = test::init_service(
+            App::new()
+                .wrap(Logger::default())
+                .wrap(RequestId)
+                .route("/ping", web::get().to(HttpResponse::Ok)),
+        )
+        .await;
+
+        let resp = test::call_service(&app, req.uri("/ping").to_request()).await;
+        resp.headers()
+            .get("x-request-id")
+            .unwrap()
+            .to_str()
+            .unwrap()
+            .to_string()
+    }
+
+    #[actix_web::test]
+    async fn test_generated_request_id_is_in_header_and_span() {
+        let capture = SpanCapture::default();
+
+        let request_id = request_id_of(test::TestRequest::get(), &capture).await;
+
+        assert_eq!(capture.fields("http_request")["request_id"], request_id);
+    }
+
+    #[actix_web::test]
+    async fn test_traceparent_trace_id_is_the_request_id() {
+        let capture = SpanCapture::default();
+        let req = test::TestRequest::get().insert_header((
+            "traceparent",
+            "00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01",
+        ));
+
+        let request_id = request_id_of(req, &capture).await;
+
+        assert_eq!(request_id, "4bf92f3577b34da6a3ce929d0e0e4736");
+        assert_eq!(capture.fields("http_request")["request_id"], request_id);
+    }
+}
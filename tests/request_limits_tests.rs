@@ -0,0 +1,130 @@
+#[cfg(test)]
+mod request_limits_tests {
+    use actix_web::{test, web, App, HttpResponse};
+    use rust_template::middleware::Timeout;
+    use rust_template::routes::configure_user_routes;
+    use rust_template::state::AppState;
+    use rust_template::utils::json_config;
+    use serde_json::Value;
+    use std::time::Duration;
+
+    #[actix_web::test]
+    async fn test_oversized_body_returns_structured_413() {
+        let app = test::init_service(
+            App::new()
+                .app_data(web::Data::new(AppState::new()))
+                .app_data(json_config(64))
+                .configure(configure_user_routes),
+        )
+        .await;
+
+        let req = test::TestRequest::post()
+            .uri("/users")
+            .set_json(serde_json::json!({
+                "name": "x".repeat(200),
+                "email": "big@example.com",
+                "password": "SecurePass123!",
+                "age": 30
+            }))
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), 413);
+
+        let body: Value = test::read_body_json(resp).await;
+        assert_eq!(body["success"], false);
+        assert_eq!(body["error_code"], "PayloadTooLarge");
+        assert_eq!(body["details"], "Limit is 64 bytes");
+    }
+
+    #[actix_web::test]
+    async fn test_wrong_content_type_uses_error_envelope() {
+        let app = test::init_service(
+            App::new()
+                .app_data(web::Data::new(AppState::new()))
+                .app_data(json_config(1024))
+                .configure(configure_user_routes),
+        )
+        .await;
+
+        let req = test::TestRequest::post()
+            .uri("/users")
+            .insert_header(("Content-Type", "text/plain"))
+            .set_payload("hello")
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), 400);
+
+        let body: Value = test::read_body_json(resp).await;
+        assert_eq!(body["error_code"], "BadRequest");
+    }
+
+    #[actix_web::test]
+    async fn test_slow_request_times_out() {
+        let app = test::init_service(
+            App::new()
+                .wrap(Timeout::new(Duration::from_millis(20)))
+                .route(
+                    "/slow",
+                    web::get().to(|| async {
+                        tokio::time::sleep(Duration::from_secs(1)).await;
+                        HttpResponse::Ok().finish()
+                    }),
+                ),
+        )
+        .await;
+
+        let resp = test::call_service(&app, test::TestRequest::get().uri("/slow").to_request()).await;
+        assert_eq!(resp.status(), 504);
+
+        let body: Value = test::read_body_json(resp).await;
+        assert_eq!(body["error_code"], "GatewayTimeout");
+    }
+}
+
+#[cfg(test)]
+mod json_error_tests {
+    use actix_web::{test, web, App};
+    use rust_template::routes::configure_user_routes;
+    use rust_template::state::AppState;
+    use rust_template::utils::json_config;
+    use serde_json::{json, Value};
+
+    async fn post_users(payload: &str) -> (u16, Value) {
+        let app = test::init_service(
+            App::new()
+                .app_data(web::Data::new(AppState::new()))
+                .app_data(json_config(1024))
+                .configure(configure_user_routes),
+        )
+        .await;
+
+        let req = test::TestRequest::post()
+            .uri("/users")
+            .insert_header(("Content-Type", "application/json"))
+            .set_payload(payload.to_string())
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+        let status = resp.status().as_u16();
+        (status, test::read_body_json(resp).await)
+    }
+
+    #[actix_web::test]
+    async fn test_wrong_field_type_names_field() {
+        let (status, body) = post_users(&json!({ "age": "not-a-number" }).to_string()).await;
+
+        assert_eq!(status, 400);
+        assert_eq!(body["success"], false);
+        assert_eq!(body["error_code"], "BadRequest");
+        let message = body["message"].as_str().unwrap();
+        assert!(message.contains("`age`"), "message: {}", message);
+    }
+
+    #[actix_web::test]
+    async fn test_malformed_json_uses_error_envelope() {
+        let (status, body) = post_users("{\"name\": ").await;
+
+        assert_eq!(status, 400);
+        assert_eq!(body["error_code"], "BadRequest");
+        assert!(body["message"].as_str().unwrap().starts_with("Invalid JSON body"));
+    }
+}
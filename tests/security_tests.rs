@@ -1,41 +1,48 @@
 #[cfg(feature = "auth-api-key")]
 use rust_template::auth::api_key::ApiKeyManager;
 use rust_template::middleware::rate_limit::{RateLimiter, RateLimitConfig, RateLimitAlgorithm};
-use rust_template::security::audit::{AuditLogger, AuditEvent, AuditEventType, AuditSeverity};
+use rust_template::security::audit::{AuditLogger, AuditEvent, AuditEventType, AuditSeverity, AuditQuery};
+use rust_template::security::{AuditSink, SINK_SHUTDOWN_TIMEOUT};
+use rust_template::utils::clock::MockClock;
+use std::sync::{Arc, Mutex};
+
+mod common;
 
 #[cfg(all(test, feature = "auth-api-key"))]
 mod api_key_tests {
     use super::*;
 
+    fn generate(manager: &ApiKeyManager, expires_in_days: Option<i64>) -> (String, String) {
+        let (key, api_key) = manager
+            .generate_key(
+                "test key".to_string(),
+                "user123".to_string(),
+                vec!["read".to_string(), "write".to_string()],
+                expires_in_days,
+            )
+            .unwrap();
+        (key, api_key.key_hash)
+    }
+
     #[test]
     fn test_generate_api_key() {
         let manager = ApiKeyManager::new();
-        
-        let result = manager.generate_key(
-            "user123".to_string(),
-            vec!["read".to_string(), "write".to_string()],
-            Some(3600),
-        );
-        
-        assert!(result.is_ok());
-        let (key_id, api_key) = result.unwrap();
-        assert!(!key_id.is_empty());
-        assert!(!api_key.is_empty());
+
+        let (key, key_hash) = generate(&manager, Some(30));
+
+        assert!(key.starts_with("sk_"));
+        assert!(!key_hash.is_empty());
+        assert_ne!(key, key_hash);
     }
 
     #[test]
     fn test_validate_api_key() {
         let manager = ApiKeyManager::new();
-        
-        let (_, api_key) = manager.generate_key(
-            "user123".to_string(),
-            vec!["read".to_string()],
-            Some(3600),
-        ).unwrap();
-        
-        let result = manager.validate_key(&api_key);
+        let (key, _) = generate(&manager, Some(30));
+
+        let result = manager.validate_key(&key);
         assert!(result.is_ok());
-        
+
         let key_data = result.unwrap();
         assert_eq!(key_data.user_id, "user123");
         assert!(key_data.scopes.contains(&"read".to_string()));
@@ -51,62 +58,255 @@ mod api_key_tests {
     #[test]
     fn test_revoke_api_key() {
         let manager = ApiKeyManager::new();
-        
-        let (key_id, api_key) = manager.generate_key(
-            "user123".to_string(),
-            vec!["read".to_string()],
-            None,
-        ).unwrap();
-        
+        let (key, key_hash) = generate(&manager, None);
+
         // Key should be valid
-        assert!(manager.validate_key(&api_key).is_ok());
-        
+        assert!(manager.validate_key(&key).is_ok());
+
         // Revoke key
-        manager.revoke_key(&key_id).unwrap();
-        
+        manager.revoke_key(&key_hash).unwrap();
+
         // Key should now be invalid
-        assert!(manager.validate_key(&api_key).is_err());
+        assert!(manager.validate_key(&key).is_err());
     }
 
     #[test]
     fn test_rotate_api_key() {
         let manager = ApiKeyManager::new();
-        
-        let (key_id, old_key) = manager.generate_key(
-            "user123".to_string(),
-            vec!["read".to_string()],
-            None,
-        ).unwrap();
-        
-        let new_key = manager.rotate_key(&key_id).unwrap();
-        
+        let (old_key, key_hash) = generate(&manager, None);
+
+        let (new_key, _) = manager.rotate_key(&key_hash).unwrap();
+
         // Old key should be invalid
         assert!(manager.validate_key(&old_key).is_err());
-        
+
         // New key should be valid
         assert!(manager.validate_key(&new_key).is_ok());
     }
 
     #[test]
     fn test_expired_key() {
-        let manager = ApiKeyManager::new();
-        
-        // Create key that expires in 0 seconds (immediately)
-        let (_, api_key) = manager.generate_key(
-            "user123".to_string(),
-            vec!["read".to_string()],
-            Some(0),
-        ).unwrap();
-        
-        // Wait a bit
-        std::thread::sleep(std::time::Duration::from_millis(100));
-        
-        // Key should be expired
-        let result = manager.validate_key(&api_key);
+        let clock = MockClock::default();
+        let manager = ApiKeyManager::with_clock(Arc::new(clock.clone()));
+        let (key, _) = generate(&manager, Some(1));
+
+        clock.advance(chrono::Duration::hours(23));
+        assert!(manager.validate_key(&key).is_ok());
+
+        // Key should be expired once the clock passes expires_at
+        clock.advance(chrono::Duration::hours(2));
+        let result = manager.validate_key(&key);
         assert!(result.is_err());
     }
 }
 
+#[cfg(all(test, feature = "auth-api-key"))]
+mod api_key_rotation_tests {
+    use super::*;
+    use chrono::{DateTime, Duration, Utc};
+    use rust_template::auth::{ApiKey, ApiKeyStore};
+    use rust_template::jobs::ApiKeyRotationJob;
+
+    fn key_created_at(name: &str, created_at: DateTime<Utc>, expires_at: Option<DateTime<Utc>>) -> ApiKey {
+        ApiKeyManager::issue(name.to_string(), "user123".to_string(), vec!["read".to_string()], created_at, expires_at).1
+    }
+
+    #[tokio::test]
+    async fn test_aged_keys_are_marked_due_not_rotated() {
+        let now = Utc::now();
+        let store = Arc::new(ApiKeyManager::new());
+        let audit = Arc::new(AuditLogger::new(100));
+        let (secret, aged) = ApiKeyManager::issue("aged".to_string(), "user123".to_string(), vec!["read".to_string()], now - Duration::days(91), None);
+        let fresh = key_created_at("fresh", now - Duration::days(10), None);
+        store.insert(aged.clone()).await.unwrap();
+        store.insert(fresh.clone()).await.unwrap();
+
+        let job = ApiKeyRotationJob::new(store.clone(), audit.clone(), 90).with_grace_days(7);
+        let report = job.run_at(now).await.unwrap();
+
+        assert_eq!(report.due.len(), 1);
+        assert_eq!(report.due[0].id, aged.id);
+        assert_eq!(report.due[0].rotate_by, Some(now + Duration::days(7)));
+        assert!(report.overdue.is_empty());
+        assert!(report.expired.is_empty());
+
+        // No replacement is issued and the old secret keeps working until the deadline
+        let active = store.list_active().await.unwrap();
+        assert_eq!(active.len(), 2);
+        assert!(store.validate_key(&secret).is_ok());
+
+        let events = audit.query(AuditQuery {
+            event_types: vec![AuditEventType::Custom("api_key_rotation_due".to_string())],
+            ..Default::default()
+        });
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].resource.as_deref(), Some(format!("api_key:{}", aged.id).as_str()));
+
+        // Running again does not push the deadline back
+        let report = job.run_at(now + Duration::days(1)).await.unwrap();
+        assert!(report.due.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_unrotated_keys_are_deactivated_after_grace_period() {
+        let now = Utc::now();
+        let store = Arc::new(ApiKeyManager::new());
+        let aged = key_created_at("aged", now - Duration::days(91), None);
+        store.insert(aged.clone()).await.unwrap();
+
+        let job = ApiKeyRotationJob::new(store.clone(), Arc::new(AuditLogger::new(100)), 90).with_grace_days(7);
+        job.run_at(now).await.unwrap();
+        let report = job.run_at(now + Duration::days(8)).await.unwrap();
+
+        assert_eq!(report.overdue.len(), 1);
+        assert_eq!(report.overdue[0].id, aged.id);
+        assert!(store.list_active().await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_owner_rotation_clears_the_deadline() {
+        let store = Arc::new(ApiKeyManager::new());
+        let (_, old_key) = store.generate_key("ci".to_string(), "user123".to_string(), vec![], None).unwrap();
+        store.mark_rotation_due(&old_key.key_hash, Utc::now() + Duration::days(7)).await.unwrap();
+
+        let (secret, new_key) = store.rotate_key(&old_key.key_hash).unwrap();
+
+        assert_eq!(new_key.rotate_by, None);
+        assert!(store.validate_key(&secret).is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_expired_keys_are_deactivated() {
+        let now = Utc::now();
+        let store = Arc::new(ApiKeyManager::new());
+        let audit = Arc::new(AuditLogger::new(100));
+        let expired = key_created_at("expired", now - Duration::days(5), Some(now - Duration::days(1)));
+        store.insert(expired.clone()).await.unwrap();
+
+        let job = ApiKeyRotationJob::new(store.clone(), audit.clone(), 90);
+        let report = job.run_at(now).await.unwrap();
+
+        assert!(report.due.is_empty());
+        assert_eq!(report.expired.len(), 1);
+        assert!(store.list_active().await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_zero_rotation_days_disables_rotation() {
+        let now = Utc::now();
+        let store = Arc::new(ApiKeyManager::new());
+        store.insert(key_created_at("old", now - Duration::days(1000), None)).await.unwrap();
+
+        let job = ApiKeyRotationJob::new(store.clone(), Arc::new(AuditLogger::new(100)), 0);
+        let report = job.run_at(now).await.unwrap();
+
+        assert!(report.due.is_empty());
+        assert_eq!(store.list_active().await.unwrap().len(), 1);
+    }
+}
+
+#[cfg(all(test, feature = "cache-redis"))]
+mod redis_rate_limit_tests {
+    use rust_template::cache::CacheManager;
+    use rust_template::middleware::{RateLimitFailurePolicy, RedisRateLimitConfig, RedisRateLimiter};
+    use std::time::Duration;
+
+    async fn limiter(max_requests: u32, window_secs: u64) -> RedisRateLimiter {
+        let cache = crate::common::setup_cache().await;
+
+        RedisRateLimiter::new(
+            RedisRateLimitConfig {
+                max_requests,
+                window_secs,
+                key_prefix: format!("rate_limit_test:{}", uuid::Uuid::new_v4()),
+                ..Default::default()
+            },
+            cache,
+        )
+    }
+
+    #[tokio::test]
+    async fn test_concurrent_burst_is_limited_exactly() {
+        let limiter = limiter(10, 1).await;
+
+        let decisions = futures::future::join_all((0..25).map(|_| limiter.check("client"))).await;
+        let allowed = decisions.iter().filter(|d| d.as_ref().unwrap().allowed).count();
+        assert_eq!(allowed, 10);
+
+        let rejected = limiter.check("client").await.unwrap();
+        assert!(!rejected.allowed);
+        assert_eq!(rejected.remaining, 0);
+        assert!(rejected.retry_after > Duration::ZERO);
+        assert!(rejected.retry_after <= Duration::from_secs(1));
+
+        // Window slides past the burst
+        tokio::time::sleep(rejected.retry_after + Duration::from_millis(50)).await;
+        let recovered = limiter.check("client").await.unwrap();
+        assert!(recovered.allowed);
+    }
+
+    #[tokio::test]
+    async fn test_remaining_counts_down() {
+        let limiter = limiter(3, 60).await;
+
+        let remaining: Vec<u32> = [
+            limiter.check("client").await.unwrap().remaining,
+            limiter.check("client").await.unwrap().remaining,
+            limiter.check("client").await.unwrap().remaining,
+        ]
+        .to_vec();
+        assert_eq!(remaining, vec![2, 1, 0]);
+
+        // Keys are independent
+        assert!(limiter.check("other").await.unwrap().allowed);
+    }
+
+    async fn down_limiter(on_backend_error: RateLimitFailurePolicy) -> RedisRateLimiter {
+        let cache = CacheManager::new(&crate::common::failing_redis().await)
+            .await
+            .expect("Failed to connect to fake Redis");
+
+        RedisRateLimiter::new(
+            RedisRateLimitConfig {
+                max_requests: 5,
+                window_secs: 30,
+                on_backend_error,
+                ..Default::default()
+            },
+            cache,
+        )
+    }
+
+    #[tokio::test]
+    async fn test_fail_open_allows_when_redis_is_down() {
+        let limiter = down_limiter(RateLimitFailurePolicy::FailOpen).await;
+
+        let decision = limiter.check("client").await.unwrap();
+        assert!(decision.allowed);
+        assert_eq!(decision.remaining, 5);
+    }
+
+    #[tokio::test]
+    async fn test_default_policy_returns_the_error() {
+        let limiter = down_limiter(RateLimitFailurePolicy::default()).await;
+
+        assert_eq!(RateLimitFailurePolicy::default(), RateLimitFailurePolicy::ReturnError);
+        assert!(matches!(
+            limiter.check("client").await,
+            Err(rust_template::errors::ApiError::CacheError { .. })
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_fail_closed_rejects_when_redis_is_down() {
+        let limiter = down_limiter(RateLimitFailurePolicy::FailClosed).await;
+
+        let decision = limiter.check("client").await.unwrap();
+        assert!(!decision.allowed);
+        assert_eq!(decision.retry_after, Duration::from_secs(30));
+    }
+}
+
 #[cfg(test)]
 mod rate_limit_tests {
     use super::*;
@@ -246,5 +446,1452 @@ mod audit_tests {
         let events = logger.get_recent_events(1);
         assert_eq!(events[0].severity, AuditSeverity::Critical);
     }
+
+    #[test]
+    fn test_query_by_severity_threshold() {
+        let logger = AuditLogger::new(100);
+
+        logger.log(AuditEvent::new(AuditEventType::DataRead, "read".to_string()));
+        logger.log(
+            AuditEvent::new(AuditEventType::LoginFailure, "bad password".to_string())
+                .with_severity(AuditSeverity::Warning),
+        );
+        logger.log(
+            AuditEvent::new(AuditEventType::SecurityViolation, "tampering".to_string())
+                .with_severity(AuditSeverity::Critical),
+        );
+
+        let events = logger.query(AuditQuery {
+            severity_at_least: AuditSeverity::Warning,
+            ..Default::default()
+        });
+        assert_eq!(events.len(), 2);
+        assert!(events.iter().all(|e| e.severity >= AuditSeverity::Warning));
+
+        let events = logger.query(AuditQuery {
+            event_types: vec![AuditEventType::SecurityViolation],
+            ..Default::default()
+        });
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].action, "tampering");
+    }
+
+    #[test]
+    fn test_query_by_time_window() {
+        let logger = AuditLogger::new(100);
+        let now = chrono::Utc::now();
+        let clock = MockClock::new(now);
+
+        for hours_ago in [1, 5, 10, 24] {
+            clock.set(now - chrono::Duration::hours(hours_ago));
+            let event = AuditEvent::new_with_clock(AuditEventType::DataUpdated, format!("{}h ago", hours_ago), &clock);
+            logger.log(event);
+        }
+
+        let events = logger.query(AuditQuery {
+            from: Some(now - chrono::Duration::hours(12)),
+            to: Some(now - chrono::Duration::hours(2)),
+            ..Default::default()
+        });
+        let actions: Vec<&str> = events.iter().map(|e| e.action.as_str()).collect();
+        assert_eq!(actions, vec!["5h ago", "10h ago"]); // newest first
+
+        let events = logger.query(AuditQuery {
+            limit: Some(1),
+            ..Default::default()
+        });
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].action, "1h ago");
+    }
+
+    struct MockSink {
+        events: Arc<Mutex<Vec<AuditEvent>>>,
+    }
+
+    impl AuditSink for MockSink {
+        fn write(&self, event: &AuditEvent) {
+            self.events.lock().unwrap().push(event.clone());
+        }
+    }
+
+    #[test]
+    fn test_logged_event_reaches_sink() {
+        let written = Arc::new(Mutex::new(Vec::new()));
+        let logger = AuditLogger::new(100).with_sink(MockSink {
+            events: written.clone(),
+        });
+
+        let event = AuditEvent::new(
+            AuditEventType::DataDeleted,
+            "User deleted".to_string(),
+        )
+        .with_user("user123".to_string());
+        let event_id = event.id.clone();
+
+        logger.log(event);
+
+        // Still queryable from the in-memory buffer
+        assert_eq!(logger.get_recent_events(10).len(), 1);
+
+        // Dropping the logger flushes pending events to the sink
+        drop(logger);
+
+        let written = written.lock().unwrap();
+        assert_eq!(written.len(), 1);
+        assert_eq!(written[0].id, event_id);
+    }
+
+    /// Sink kẹt cho tới khi test thả ra, như Postgres sink chờ runtime bị chặn
+    struct StuckSink {
+        release: Mutex<std::sync::mpsc::Receiver<()>>,
+    }
+
+    impl AuditSink for StuckSink {
+        fn write(&self, _event: &AuditEvent) {
+            let _ = self.release.lock().unwrap().recv();
+        }
+    }
+
+    #[actix_rt::test]
+    async fn test_drop_does_not_hang_on_stuck_sink() {
+        let (release, receiver) = std::sync::mpsc::channel();
+        let logger = AuditLogger::new(100).with_sink(StuckSink {
+            release: Mutex::new(receiver),
+        });
+        logger.log(AuditEvent::new(AuditEventType::DataRead, "read".to_string()));
+
+        // Drop chạy trên runtime current_thread của actix mà không bị treo
+        let started = std::time::Instant::now();
+        drop(logger);
+        let elapsed = started.elapsed();
+        release.send(()).unwrap();
+
+        assert!(elapsed >= SINK_SHUTDOWN_TIMEOUT);
+        assert!(elapsed < SINK_SHUTDOWN_TIMEOUT + std::time::Duration::from_secs(2));
+    }
+}
+
+
+#[cfg(all(test, feature = "secrets-vault"))]
+mod vault_secrets_tests {
+    use rust_template::errors::ApiError;
+    use rust_template::security::{SecretsBackend, SecretsConfig, SecretsManager};
+    use wiremock::matchers::{header, method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    fn vault_manager(server: &MockServer, auto_refresh: bool) -> SecretsManager {
+        SecretsManager::new(SecretsConfig {
+            backend: SecretsBackend::Vault {
+                url: server.uri(),
+                token: "test-token".to_string(),
+                mount_path: "secret".to_string(),
+            },
+            auto_refresh,
+            refresh_interval_secs: 1,
+        })
+    }
+
+    fn vault_body(value: &str) -> serde_json::Value {
+        serde_json::json!({
+            "data": {
+                "data": { "value": value },
+                "metadata": { "version": 1 }
+            }
+        })
+    }
+
+    #[tokio::test]
+    async fn test_vault_fetch_secret() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/v1/secret/data/db_password"))
+            .and(header("X-Vault-Token", "test-token"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(vault_body("s3cret")))
+            .mount(&server)
+            .await;
+
+        let manager = vault_manager(&server, false);
+        let secret = manager.get_secret("db_password").await.unwrap();
+
+        assert_eq!(secret.value(), "s3cret");
+        assert_eq!(secret.version(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_vault_forbidden_maps_to_authentication_error() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/v1/secret/data/db_password"))
+            .respond_with(ResponseTemplate::new(403))
+            .mount(&server)
+            .await;
+
+        let manager = vault_manager(&server, false);
+        let result = manager.get_secret("db_password").await;
+
+        assert!(matches!(result, Err(ApiError::AuthenticationError { .. })));
+    }
+
+    #[tokio::test]
+    async fn test_vault_missing_secret_maps_to_not_found() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/v1/secret/data/missing"))
+            .respond_with(ResponseTemplate::new(404))
+            .mount(&server)
+            .await;
+
+        let manager = vault_manager(&server, false);
+        let result = manager.get_secret("missing").await;
+
+        assert!(matches!(result, Err(ApiError::NotFound { .. })));
+    }
+
+    #[tokio::test]
+    async fn test_vault_auto_refresh_bumps_version() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/v1/secret/data/api_key"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(vault_body("old")))
+            .up_to_n_times(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/v1/secret/data/api_key"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(vault_body("new")))
+            .mount(&server)
+            .await;
+
+        let manager = vault_manager(&server, true);
+        let first = manager.get_secret("api_key").await.unwrap();
+        assert_eq!(first.value(), "old");
+        assert_eq!(first.version(), 1);
+
+        tokio::time::sleep(std::time::Duration::from_millis(1500)).await;
+
+        let refreshed = manager.get_secret("api_key").await.unwrap();
+        assert_eq!(refreshed.value(), "new");
+        assert_eq!(refreshed.version(), 2);
+    }
+}
+
+#[cfg(all(test, feature = "secrets-aws"))]
+mod aws_secrets_tests {
+    use aws_sdk_secretsmanager::error::ErrorMetadata;
+    use aws_sdk_secretsmanager::operation::get_secret_value::{
+        GetSecretValueError, GetSecretValueOutput,
+    };
+    use aws_sdk_secretsmanager::types::error::ResourceNotFoundException;
+    use aws_smithy_mocks::{mock, mock_client};
+    use rust_template::errors::ApiError;
+    use rust_template::security::{SecretsBackend, SecretsConfig, SecretsManager};
+
+    fn aws_manager(client: aws_sdk_secretsmanager::Client) -> SecretsManager {
+        SecretsManager::new(SecretsConfig {
+            backend: SecretsBackend::AwsSecretsManager {
+                region: "us-east-1".to_string(),
+                secret_prefix: "app/".to_string(),
+            },
+            auto_refresh: false,
+            refresh_interval_secs: 300,
+        })
+        .with_aws_client(client)
+    }
+
+    #[tokio::test]
+    async fn test_aws_fetch_raw_secret() {
+        let rule = mock!(aws_sdk_secretsmanager::Client::get_secret_value)
+            .match_requests(|req| req.secret_id() == Some("app/db_password"))
+            .then_output(|| GetSecretValueOutput::builder().secret_string("s3cret").build());
+        let manager = aws_manager(mock_client!(aws_sdk_secretsmanager, [&rule]));
+
+        let secret = manager.get_secret("db_password").await.unwrap();
+        assert_eq!(secret.value(), "s3cret");
+    }
+
+    #[tokio::test]
+    async fn test_aws_fetch_json_secret() {
+        let rule = mock!(aws_sdk_secretsmanager::Client::get_secret_value)
+            .then_output(|| {
+                GetSecretValueOutput::builder()
+                    .secret_string(r#"{"value": "from-json"}"#)
+                    .build()
+            });
+        let manager = aws_manager(mock_client!(aws_sdk_secretsmanager, [&rule]));
+
+        let secret = manager.get_secret("api_key").await.unwrap();
+        assert_eq!(secret.value(), "from-json");
+    }
+
+    #[tokio::test]
+    async fn test_aws_missing_secret_maps_to_not_found() {
+        let rule = mock!(aws_sdk_secretsmanager::Client::get_secret_value).then_error(|| {
+            GetSecretValueError::ResourceNotFoundException(
+                ResourceNotFoundException::builder().message("not found").build(),
+            )
+        });
+        let manager = aws_manager(mock_client!(aws_sdk_secretsmanager, [&rule]));
+
+        let result = manager.get_secret("missing").await;
+        assert!(matches!(result, Err(ApiError::NotFound { .. })));
+    }
+
+    #[tokio::test]
+    async fn test_aws_access_denied_maps_to_authorization_error() {
+        let rule = mock!(aws_sdk_secretsmanager::Client::get_secret_value).then_error(|| {
+            GetSecretValueError::generic(
+                ErrorMetadata::builder()
+                    .code("AccessDeniedException")
+                    .message("denied")
+                    .build(),
+            )
+        });
+        let manager = aws_manager(mock_client!(aws_sdk_secretsmanager, [&rule]));
+
+        let result = manager.get_secret("db_password").await;
+        assert!(matches!(result, Err(ApiError::AuthorizationError { .. })));
+    }
 }
 
+#[cfg(test)]
+mod secrets_tests {
+    use rust_template::security::{Secret, SecretsBackend, SecretsConfig};
+
+    #[test]
+    fn test_secret_debug_is_redacted() {
+        let secret = Secret::new("hunter2".to_string());
+        let debug = format!("{:?}", secret);
+
+        assert!(!debug.contains("hunter2"));
+        assert!(debug.contains("***"));
+        assert!(debug.contains("version: 1"));
+    }
+
+    #[test]
+    fn test_vault_token_debug_is_redacted() {
+        let config = SecretsConfig {
+            backend: SecretsBackend::Vault {
+                url: "http://vault:8200".to_string(),
+                token: "s.very-secret-token".to_string(),
+                mount_path: "secret".to_string(),
+            },
+            ..Default::default()
+        };
+        let debug = format!("{:?}", config);
+
+        assert!(!debug.contains("very-secret-token"));
+        assert!(debug.contains("http://vault:8200"));
+    }
+}
+
+#[cfg(test)]
+mod security_headers_tests {
+    use actix_web::{test, web, App, HttpResponse};
+    use rust_template::security::{HstsConfig, SecurityHeaders, SecurityHeadersConfig};
+
+    async fn ok() -> HttpResponse {
+        HttpResponse::Ok().finish()
+    }
+
+    #[actix_web::test]
+    async fn test_default_headers_match_previous_behavior() {
+        let app = test::init_service(
+            App::new()
+                .wrap(SecurityHeaders::default())
+                .route("/", web::get().to(ok)),
+        )
+        .await;
+
+        let resp = test::call_service(&app, test::TestRequest::get().uri("/").to_request()).await;
+        let headers = resp.headers();
+
+        assert_eq!(headers.get("x-frame-options").unwrap(), "DENY");
+        assert_eq!(
+            headers.get("strict-transport-security").unwrap(),
+            "max-age=31536000; includeSubDomains"
+        );
+    }
+
+    #[actix_web::test]
+    async fn test_custom_csp_and_disabled_hsts() {
+        let config = SecurityHeadersConfig {
+            content_security_policy: Some(
+                "default-src 'self'; script-src 'self' https://cdn.example.com".to_string(),
+            ),
+            hsts: None,
+            ..Default::default()
+        };
+        let app = test::init_service(
+            App::new()
+                .wrap(SecurityHeaders::new(config))
+                .route("/", web::get().to(ok)),
+        )
+        .await;
+
+        let resp = test::call_service(&app, test::TestRequest::get().uri("/").to_request()).await;
+        let headers = resp.headers();
+
+        assert_eq!(
+            headers.get("content-security-policy").unwrap(),
+            "default-src 'self'; script-src 'self' https://cdn.example.com"
+        );
+        assert!(headers.get("strict-transport-security").is_none());
+    }
+
+    #[actix_web::test]
+    async fn test_hsts_preload() {
+        let config = SecurityHeadersConfig {
+            hsts: Some(HstsConfig {
+                max_age_secs: 600,
+                include_subdomains: false,
+                preload: true,
+            }),
+            frame_options: Some("SAMEORIGIN".to_string()),
+            ..Default::default()
+        };
+        let app = test::init_service(
+            App::new()
+                .wrap(SecurityHeaders::new(config))
+                .route("/", web::get().to(ok)),
+        )
+        .await;
+
+        let resp = test::call_service(&app, test::TestRequest::get().uri("/").to_request()).await;
+        let headers = resp.headers();
+
+        assert_eq!(headers.get("strict-transport-security").unwrap(), "max-age=600; preload");
+        assert_eq!(headers.get("x-frame-options").unwrap(), "SAMEORIGIN");
+    }
+}
+
+#[cfg(test)]
+mod cors_tests {
+    use actix_web::{http::header, test, web, App, HttpResponse};
+    use rust_template::config::CorsSettings;
+    use rust_template::errors::ApiError;
+    use rust_template::middleware::build_cors;
+
+    fn settings(origins: &[&str], allow_credentials: bool) -> CorsSettings {
+        CorsSettings {
+            allowed_origins: origins.iter().map(|o| o.to_string()).collect(),
+            allowed_methods: vec!["GET".to_string(), "POST".to_string()],
+            allowed_headers: vec!["content-type".to_string()],
+            allow_credentials,
+            max_age: 600,
+        }
+    }
+
+    #[test]
+    fn test_wildcard_origin_with_credentials_is_rejected() {
+        let result = build_cors(&settings(&["*"], true));
+        assert!(matches!(result, Err(ApiError::ConfigurationError { .. })));
+    }
+
+    #[test]
+    fn test_invalid_origin_is_rejected() {
+        assert!(settings(&["example.com"], false).validate().is_err());
+        assert!(settings(&["https://example.com"], true).validate().is_ok());
+    }
+
+    #[actix_web::test]
+    async fn test_only_listed_origins_are_allowed() {
+        let cors = build_cors(&settings(&["https://app.example.com"], true)).unwrap();
+        let app = test::init_service(
+            App::new()
+                .wrap(cors)
+                .route("/", web::get().to(|| async { HttpResponse::Ok().finish() })),
+        )
+        .await;
+
+        let req = test::TestRequest::get()
+            .uri("/")
+            .insert_header((header::ORIGIN, "https://app.example.com"))
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(
+            resp.headers().get(header::ACCESS_CONTROL_ALLOW_ORIGIN).unwrap(),
+            "https://app.example.com"
+        );
+
+        let req = test::TestRequest::get()
+            .uri("/")
+            .insert_header((header::ORIGIN, "https://evil.example.com"))
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+        assert_ne!(
+            resp.headers()
+                .get(header::ACCESS_CONTROL_ALLOW_ORIGIN)
+                .and_then(|v| v.to_str().ok()),
+            Some("https://evil.example.com")
+        );
+    }
+}
+
+#[cfg(all(test, feature = "auth-oauth2"))]
+mod oauth2_state_tests {
+    use actix_web::{test, web, App};
+    use rust_template::auth::oauth2::OAuth2Config;
+    use rust_template::auth::{InMemoryStateStore, PendingAuthorization, StateStore};
+    use rust_template::auth::JwtManager;
+    use rust_template::handlers::{configure_oauth2_routes, OAuth2State};
+    use rust_template::state::AppState;
+    use serde_json::json;
+
+    fn pending() -> PendingAuthorization {
+        PendingAuthorization {
+            provider: "github".to_string(),
+            pkce_verifier: Some("verifier".to_string()),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_state_is_single_use() {
+        let store = InMemoryStateStore::new();
+        store.put("csrf-1", pending()).await.unwrap();
+
+        assert_eq!(store.take("csrf-1").await.unwrap(), Some(pending()));
+        assert_eq!(store.take("csrf-1").await.unwrap(), None);
+    }
+
+    #[tokio::test]
+    async fn test_state_expires() {
+        let store = InMemoryStateStore::with_ttl(chrono::Duration::milliseconds(50));
+        store.put("csrf-1", pending()).await.unwrap();
+
+        tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+        assert_eq!(store.take("csrf-1").await.unwrap(), None);
+    }
+
+    #[actix_web::test]
+    async fn test_callback_rejects_unknown_state() {
+        let state = web::Data::new(OAuth2State::new(
+            OAuth2Config::new(),
+            JwtManager::new("test-secret-key-with-at-least-32-chars".to_string(), 1),
+        ));
+        let app = test::init_service(
+            App::new()
+                .app_data(state)
+                .app_data(web::Data::new(AppState::new()))
+                .configure(configure_oauth2_routes),
+        )
+        .await;
+
+        for body in [
+            json!({ "provider": "github", "code": "abc", "csrf_token": "never-issued" }),
+            json!({ "provider": "github", "code": "abc" }),
+        ] {
+            let req = test::TestRequest::post()
+                .uri("/oauth2/callback")
+                .set_json(body)
+                .to_request();
+            let resp = test::call_service(&app, req).await;
+            assert_eq!(resp.status(), 401);
+        }
+    }
+}
+
+#[cfg(all(test, feature = "auth-oauth2"))]
+mod oidc_tests {
+    use rust_template::auth::oauth2::OAuth2Config;
+    use serde_json::json;
+    use wiremock::matchers::{header, method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    async fn discovery_server() -> MockServer {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/.well-known/openid-configuration"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "issuer": server.uri(),
+                "authorization_endpoint": format!("{}/authorize", server.uri()),
+                "token_endpoint": format!("{}/token", server.uri()),
+                "userinfo_endpoint": format!("{}/userinfo", server.uri()),
+            })))
+            .mount(&server)
+            .await;
+        server
+    }
+
+    async fn oidc_config(server: &MockServer) -> OAuth2Config {
+        OAuth2Config::new()
+            .add_oidc(
+                "keycloak".to_string(),
+                server.uri(),
+                "client".to_string(),
+                "secret".to_string(),
+                "http://localhost:8080/callback".to_string(),
+            )
+            .await
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_oidc_discovery_populates_endpoints() {
+        let server = discovery_server().await;
+        let config = oidc_config(&server).await;
+
+        assert!(config.list_providers().contains(&"keycloak".to_string()));
+        let auth = config.get_authorization_url("keycloak", true).unwrap();
+        assert!(auth.auth_url.starts_with(&format!("{}/authorize", server.uri())));
+    }
+
+    #[tokio::test]
+    async fn test_oidc_userinfo_maps_standard_claims() {
+        let server = discovery_server().await;
+        Mock::given(method("GET"))
+            .and(path("/userinfo"))
+            .and(header("Authorization", "Bearer access-token"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "sub": "user-123",
+                "email": "jane@example.com",
+                "name": "Jane",
+                "picture": "https://example.com/jane.png",
+            })))
+            .mount(&server)
+            .await;
+        let config = oidc_config(&server).await;
+
+        let user = config.get_user_info("keycloak", "access-token").await.unwrap();
+
+        assert_eq!(user.id, "user-123");
+        assert_eq!(user.email.as_deref(), Some("jane@example.com"));
+        assert_eq!(user.name.as_deref(), Some("Jane"));
+        assert_eq!(user.provider, "keycloak");
+    }
+
+    #[tokio::test]
+    async fn test_oidc_issuer_mismatch_is_rejected() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/.well-known/openid-configuration"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "issuer": "https://evil.example.com",
+                "authorization_endpoint": "https://evil.example.com/authorize",
+                "token_endpoint": "https://evil.example.com/token",
+            })))
+            .mount(&server)
+            .await;
+
+        let result = OAuth2Config::new()
+            .add_oidc(
+                "keycloak".to_string(),
+                server.uri(),
+                "client".to_string(),
+                "secret".to_string(),
+                "http://localhost:8080/callback".to_string(),
+            )
+            .await;
+
+        assert!(result.is_err());
+    }
+}
+
+#[cfg(all(test, feature = "auth-oauth2"))]
+mod oauth2_login_flow_tests {
+    use actix_web::{test, web, App};
+    use rust_template::auth::oauth2::OAuth2Config;
+    use rust_template::auth::JwtManager;
+    use rust_template::handlers::{configure_oauth2_routes, OAuth2State};
+    use rust_template::state::AppState;
+    use serde_json::{json, Value};
+    use wiremock::matchers::{method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    const JWT_SECRET: &str = "test-secret-key-with-at-least-32-chars";
+
+    async fn mock_provider() -> MockServer {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/.well-known/openid-configuration"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "issuer": server.uri(),
+                "authorization_endpoint": format!("{}/authorize", server.uri()),
+                "token_endpoint": format!("{}/token", server.uri()),
+                "userinfo_endpoint": format!("{}/userinfo", server.uri()),
+            })))
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/token"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "access_token": "provider-access-token",
+                "token_type": "bearer",
+            })))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/userinfo"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "sub": "provider-user-1",
+                "email": "jane@example.com",
+                "email_verified": true,
+                "name": "Jane",
+            })))
+            .mount(&server)
+            .await;
+        server
+    }
+
+    #[actix_web::test]
+    async fn test_oauth2_create_then_login() {
+        let server = mock_provider().await;
+        let config = OAuth2Config::new()
+            .add_oidc(
+                "mock".to_string(),
+                server.uri(),
+                "client".to_string(),
+                "secret".to_string(),
+                "http://localhost:8080/callback".to_string(),
+            )
+            .await
+            .unwrap();
+        let jwt = JwtManager::new(JWT_SECRET.to_string(), 1);
+        let app_state = web::Data::new(AppState::new());
+        let app = test::init_service(
+            App::new()
+                .app_data(web::Data::new(OAuth2State::new(config, jwt.clone())))
+                .app_data(app_state.clone())
+                .configure(configure_oauth2_routes),
+        )
+        .await;
+
+        let mut user_ids = Vec::new();
+        for _ in 0..2 {
+            let req = test::TestRequest::post()
+                .uri("/oauth2/auth-url")
+                .set_json(json!({ "provider": "mock", "use_pkce": true }))
+                .to_request();
+            let body: Value = test::call_and_read_body_json(&app, req).await;
+            let csrf_token = body["data"]["csrf_token"].as_str().unwrap().to_string();
+            assert!(body["data"].get("pkce_verifier").is_none());
+
+            let req = test::TestRequest::post()
+                .uri("/oauth2/callback")
+                .set_json(json!({ "provider": "mock", "code": "code", "csrf_token": csrf_token }))
+                .to_request();
+            let resp = test::call_service(&app, req).await;
+            assert_eq!(resp.status(), 200);
+            let body: Value = test::read_body_json(resp).await;
+
+            let claims = jwt.verify_token(body["data"]["token"].as_str().unwrap()).unwrap();
+            assert_eq!(claims.email, "jane@example.com");
+            assert_eq!(body["data"]["user"]["id"], claims.sub.as_str());
+            assert!(body["data"].get("access_token").is_none());
+            user_ids.push(claims.sub);
+        }
+
+        // The second login reuses the account created by the first
+        assert_eq!(user_ids[0], user_ids[1]);
+        assert_eq!(app_state.users.find_all().await.unwrap().len(), 1);
+    }
+}
+
+#[cfg(all(test, feature = "websocket"))]
+mod websocket_auth_tests {
+    use actix_web::{test, web, App};
+    use rust_template::auth::JwtManager;
+    use rust_template::websocket::{authenticate_upgrade, ws_connect, WebSocketServer, BEARER_PROTOCOL};
+
+    const JWT_SECRET: &str = "test-secret-key-with-at-least-32-chars";
+
+    fn upgrade_request(uri: &str) -> test::TestRequest {
+        test::TestRequest::get()
+            .uri(uri)
+            .insert_header(("upgrade", "websocket"))
+            .insert_header(("connection", "upgrade"))
+            .insert_header(("sec-websocket-version", "13"))
+            .insert_header(("sec-websocket-key", "dGhlIHNhbXBsZSBub25jZQ=="))
+    }
+
+    async fn handshake(req: test::TestRequest) -> actix_web::dev::ServiceResponse {
+        let app = test::init_service(
+            App::new()
+                .app_data(web::Data::new(WebSocketServer::new()))
+                .app_data(web::Data::new(JwtManager::new(JWT_SECRET.to_string(), 1)))
+                .route("/ws", web::get().to(ws_connect)),
+        )
+        .await;
+        test::call_service(&app, req.to_request()).await
+    }
+
+    fn token(user_id: &str) -> String {
+        JwtManager::new(JWT_SECRET.to_string(), 1)
+            .create_token(user_id, "ws@example.com", "user")
+            .unwrap()
+    }
+
+    #[actix_web::test]
+    async fn test_handshake_without_token_is_rejected() {
+        let resp = handshake(upgrade_request("/ws")).await;
+        assert_eq!(resp.status(), 401);
+    }
+
+    #[actix_web::test]
+    async fn test_handshake_with_invalid_token_is_rejected() {
+        let resp = handshake(upgrade_request("/ws?token=not-a-jwt")).await;
+        assert_eq!(resp.status(), 401);
+
+        let forged = JwtManager::new("another-secret-key-with-at-least-32-chars".to_string(), 1)
+            .create_token("user-1", "ws@example.com", "user")
+            .unwrap();
+        let resp = handshake(
+            upgrade_request("/ws").insert_header(("sec-websocket-protocol", format!("bearer, {}", forged))),
+        )
+        .await;
+        assert_eq!(resp.status(), 401);
+    }
+
+    #[actix_web::test]
+    async fn test_handshake_with_query_token_upgrades() {
+        let resp = handshake(upgrade_request(&format!("/ws?token={}", token("user-1")))).await;
+        assert_eq!(resp.status(), 101);
+    }
+
+    #[actix_web::test]
+    async fn test_handshake_with_protocol_token_echoes_bearer() {
+        let resp = handshake(
+            upgrade_request("/ws").insert_header(("sec-websocket-protocol", format!("bearer, {}", token("user-1")))),
+        )
+        .await;
+        assert_eq!(resp.status(), 101);
+        assert_eq!(resp.headers().get("sec-websocket-protocol").unwrap(), BEARER_PROTOCOL);
+    }
+
+    #[actix_web::test]
+    async fn test_valid_token_attaches_user_id() {
+        let jwt = JwtManager::new(JWT_SECRET.to_string(), 1);
+        let req = upgrade_request(&format!("/ws?token={}", token("user-42"))).to_http_request();
+
+        let claims = authenticate_upgrade(&req, &jwt).unwrap();
+        let session = WebSocketServer::new().new_session().with_user(claims.sub);
+
+        assert_eq!(session.user_id(), Some("user-42"));
+    }
+}
+
+#[cfg(all(test, feature = "websocket"))]
+mod websocket_rate_limit_tests {
+    use actix_web::{test, web, App};
+    use rust_template::auth::JwtManager;
+    use rust_template::middleware::{RateLimitAlgorithm, RateLimitConfig};
+    use rust_template::websocket::{ws_connect, MessageRateLimit, RateLimitAction, WebSocketServer};
+
+    const JWT_SECRET: &str = "test-secret-key-with-at-least-32-chars";
+
+    /// Masked client text frame (mask key 0 nên payload giữ nguyên)
+    fn client_text_frame(text: &str) -> Vec<u8> {
+        assert!(text.len() < 126);
+        let mut frame = vec![0x81, 0x80 | text.len() as u8, 0, 0, 0, 0];
+        frame.extend_from_slice(text.as_bytes());
+        frame
+    }
+
+    /// (opcode, payload) of each unmasked server frame
+    fn server_frames(mut bytes: &[u8]) -> Vec<(u8, Vec<u8>)> {
+        let mut frames = Vec::new();
+        while bytes.len() >= 2 {
+            let opcode = bytes[0] & 0x0f;
+            let (len, header) = match bytes[1] & 0x7f {
+                126 => (u16::from_be_bytes([bytes[2], bytes[3]]) as usize, 4),
+                len => (len as usize, 2),
+            };
+            frames.push((opcode, bytes[header..header + len].to_vec()));
+            bytes = &bytes[header + len..];
+        }
+        frames
+    }
+
+    /// Gửi `count` ping qua một session giới hạn 3 message rồi đọc toàn bộ output
+    async fn flood(action: RateLimitAction, count: usize) -> Vec<(u8, Vec<u8>)> {
+        let jwt = JwtManager::new(JWT_SECRET.to_string(), 1);
+        let token = jwt.create_token("user-1", "ws@example.com", "user").unwrap();
+        let server = WebSocketServer::new().with_rate_limit(MessageRateLimit {
+            limit: RateLimitConfig {
+                algorithm: RateLimitAlgorithm::SlidingWindow,
+                max_requests: 3,
+                window_secs: 60,
+                burst_size: None,
+            },
+            on_exceeded: action,
+        });
+        let app = test::init_service(
+            App::new()
+                .app_data(web::Data::new(server))
+                .app_data(web::Data::new(jwt))
+                .route("/ws", web::get().to(ws_connect)),
+        )
+        .await;
+
+        let payload: Vec<u8> = (0..count)
+            .flat_map(|_| client_text_frame(r#"{"type":"ping"}"#))
+            .collect();
+        let req = test::TestRequest::get()
+            .uri(&format!("/ws?token={}", token))
+            .insert_header(("upgrade", "websocket"))
+            .insert_header(("connection", "upgrade"))
+            .insert_header(("sec-websocket-version", "13"))
+            .insert_header(("sec-websocket-key", "dGhlIHNhbXBsZSBub25jZQ=="))
+            .set_payload(payload)
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), 101);
+
+        let body = tokio::time::timeout(std::time::Duration::from_secs(5), test::read_body(resp))
+            .await
+            .expect("session did not finish");
+        server_frames(&body)
+    }
+
+    fn pong_count(frames: &[(u8, Vec<u8>)]) -> usize {
+        frames
+            .iter()
+            .filter(|(opcode, payload)| *opcode == 0x1 && payload.as_slice() == br#"{"type":"pong"}"#)
+            .count()
+    }
+
+    #[actix_web::test]
+    async fn test_flood_closes_session_with_policy_violation() {
+        let frames = flood(RateLimitAction::Close, 10).await;
+
+        assert_eq!(pong_count(&frames), 3);
+        let (opcode, payload) = frames.last().expect("no frames");
+        assert_eq!(*opcode, 0x8);
+        assert_eq!(u16::from_be_bytes([payload[0], payload[1]]), 1008);
+    }
+
+    #[actix_web::test]
+    async fn test_flood_drops_messages_over_limit() {
+        let frames = flood(RateLimitAction::Drop, 10).await;
+
+        assert_eq!(pong_count(&frames), 3);
+        assert!(frames.iter().all(|(opcode, _)| *opcode != 0x8));
+    }
+}
+
+#[cfg(test)]
+mod jwt_algorithm_tests {
+    use rust_template::auth::JwtManager;
+    use rust_template::config::{JwtAlgorithm, JwtSettings};
+    use serde_json::json;
+
+    fn jwt_settings(algorithm: &str) -> serde_json::Result<JwtSettings> {
+        serde_json::from_value(json!({
+            "secret": "test-secret-key-with-at-least-32-chars",
+            "expiration_hours": 1,
+            "refresh_expiration_days": 30,
+            "algorithm": algorithm,
+            "private_key_path": null,
+            "public_key_path": null,
+        }))
+    }
+
+    #[test]
+    fn test_unknown_algorithm_fails_to_deserialize() {
+        assert!(serde_json::from_str::<JwtAlgorithm>("\"HS257\"").is_err());
+        assert!(jwt_settings("HS257").is_err());
+        assert!(jwt_settings("none").is_err());
+    }
+
+    #[test]
+    fn test_unknown_algorithm_in_env_is_a_configuration_error() {
+        use rust_template::config::Settings;
+        use rust_template::errors::ApiError;
+
+        std::env::set_var("JWT_ALGORITHM", "HS257");
+        let result = Settings::from_env();
+        std::env::remove_var("JWT_ALGORITHM");
+
+        match result {
+            Err(ApiError::ConfigurationError { key, .. }) => {
+                assert_eq!(key.as_deref(), Some("JWT_ALGORITHM"));
+            }
+            other => panic!("expected a configuration error, got {:?}", other.map(|_| ())),
+        }
+    }
+
+    #[test]
+    fn test_known_algorithms_deserialize() {
+        assert_eq!(jwt_settings("HS512").unwrap().algorithm, JwtAlgorithm::Hs512);
+        assert_eq!(jwt_settings("rs256").unwrap().algorithm, JwtAlgorithm::Rs256);
+        assert_eq!(jwt_settings("EdDSA").unwrap().algorithm, JwtAlgorithm::EdDsa);
+    }
+
+    #[test]
+    fn test_validate_matches_key_material_to_algorithm() {
+        let mut settings = jwt_settings("HS256").unwrap();
+        assert!(settings.validate(true).is_ok());
+
+        settings.secret = "short".to_string();
+        assert!(settings.validate(false).is_ok());
+        assert!(settings.validate(true).is_err());
+
+        settings.secret.clear();
+        assert!(settings.validate(false).is_err());
+
+        // Asymmetric: secret không đủ, cần cả hai key path
+        let mut settings = jwt_settings("RS256").unwrap();
+        assert!(settings.validate(false).is_err());
+        settings.private_key_path = Some("/keys/private.pem".to_string());
+        assert!(settings.validate(false).is_err());
+        settings.public_key_path = Some("/keys/public.pem".to_string());
+        assert!(settings.validate(false).is_ok());
+    }
+
+    #[test]
+    fn test_manager_signs_with_configured_algorithm() {
+        let hs512 = JwtManager::from_settings(&jwt_settings("HS512").unwrap()).unwrap();
+        assert_eq!(hs512.algorithm(), JwtAlgorithm::Hs512);
+        let token = hs512.create_token("user-1", "a@example.com", "user").unwrap();
+        assert!(hs512.verify_token(&token).is_ok());
+
+        // Cùng secret nhưng khác algorithm thì không verify được
+        let hs256 = JwtManager::from_settings(&jwt_settings("HS256").unwrap()).unwrap();
+        assert!(hs256.verify_token(&token).is_err());
+    }
+
+    #[test]
+    fn test_asymmetric_manager_requires_readable_keys() {
+        let mut settings = jwt_settings("ES256").unwrap();
+        settings.private_key_path = Some("/nonexistent/private.pem".to_string());
+        settings.public_key_path = Some("/nonexistent/public.pem".to_string());
+
+        assert!(JwtManager::from_settings(&settings).is_err());
+    }
+}
+
+#[cfg(test)]
+mod authenticated_user_tests {
+    use actix_web::{test, web, App, HttpResponse};
+    use rust_template::auth::{AuthMiddleware, AuthenticatedUser, JwtManager};
+    use serde_json::{json, Value};
+
+    const JWT_SECRET: &str = "test-secret-key-with-at-least-32-chars";
+
+    async fn whoami(user: AuthenticatedUser) -> HttpResponse {
+        HttpResponse::Ok().json(json!({
+            "sub": user.sub,
+            "roles": user.roles,
+            "can_delete": user.has_permission("users:delete"),
+        }))
+    }
+
+    #[actix_web::test]
+    async fn test_extractor_returns_verified_claims() {
+        let jwt = JwtManager::new(JWT_SECRET.to_string(), 1);
+        let token = jwt.create_token("user-1", "admin@example.com", "admin").unwrap();
+        let app = test::init_service(
+            App::new()
+                .wrap(AuthMiddleware::new(jwt))
+                .route("/me", web::get().to(whoami)),
+        )
+        .await;
+
+        let req = test::TestRequest::get()
+            .uri("/me")
+            .insert_header(("Authorization", format!("Bearer {}", token)))
+            .to_request();
+        let body: Value = test::call_and_read_body_json(&app, req).await;
+
+        assert_eq!(body["sub"], "user-1");
+        assert_eq!(body["roles"], json!(["admin"]));
+        assert_eq!(body["can_delete"], true);
+    }
+
+    #[actix_web::test]
+    async fn test_extractor_rejects_unauthenticated_request() {
+        // Route không có AuthMiddleware nên không có claims
+        let app = test::init_service(App::new().route("/me", web::get().to(whoami))).await;
+
+        let req = test::TestRequest::get().uri("/me").to_request();
+        let resp = test::call_service(&app, req).await;
+
+        assert_eq!(resp.status(), 401);
+    }
+}
+
+#[cfg(test)]
+mod redaction_tests {
+    use actix_web::{test, web, App, HttpResponse};
+    use rust_template::middleware::Logger;
+    use rust_template::security::{redact, Redactor};
+    use rust_template::utils::ApiJson;
+    use serde::Deserialize;
+    use serde_json::{json, Value};
+
+    #[test]
+    fn test_redacts_sensitive_fields_recursively() {
+        let mut body = json!({
+            "email": "alice@example.com",
+            "password": "hunter2",
+            "profile": { "api_token": "abc", "name": "Alice" },
+            "sessions": [{ "Authorization": "Bearer xyz" }],
+        });
+        redact(&mut body);
+
+        assert_eq!(body["email"], "alice@example.com");
+        assert_eq!(body["password"], "***");
+        assert_eq!(body["profile"]["api_token"], "***");
+        assert_eq!(body["profile"]["name"], "Alice");
+        assert_eq!(body["sessions"][0]["Authorization"], "***");
+    }
+
+    #[test]
+    fn test_redact_body_for_logging() {
+        let redactor = Redactor::default().with_field("ssn");
+        let logged = redactor
+            .redact_body(br#"{"username":"alice","password":"hunter2","ssn":"123-45-6789"}"#)
+            .unwrap();
+
+        assert!(!logged.contains("hunter2"));
+        assert!(!logged.contains("123-45-6789"));
+        assert!(logged.contains("alice"));
+        assert!(redactor.redact_body(b"not json").is_none());
+    }
+
+    #[test]
+    fn test_redact_query_masks_tokens() {
+        let redactor = Redactor::default();
+
+        assert_eq!(redactor.redact_query("token=eyJhbGciOi.x.y&page=2"), "token=***&page=2");
+        assert_eq!(redactor.redact_query("access_token=abc"), "access_token=***");
+        assert_eq!(redactor.redact_query("page=2&flag"), "page=2&flag");
+    }
+
+    #[derive(Deserialize)]
+    struct Login {
+        #[allow(dead_code)]
+        password: String,
+    }
+
+    async fn echo(body: web::Json<Value>) -> HttpResponse {
+        HttpResponse::Ok().json(body.into_inner())
+    }
+
+    async fn login(_body: ApiJson<Login>) -> HttpResponse {
+        HttpResponse::Ok().finish()
+    }
+
+    #[actix_web::test]
+    async fn test_body_logging_keeps_body_for_handler() {
+        let app = test::init_service(
+            App::new()
+                .wrap(Logger::new().with_request_body(1024))
+                .route("/echo", web::post().to(echo)),
+        )
+        .await;
+
+        let req = test::TestRequest::post()
+            .uri("/echo")
+            .set_json(json!({ "password": "hunter2" }))
+            .to_request();
+        let body: Value = test::call_and_read_body_json(&app, req).await;
+
+        // Chỉ log bị che, handler vẫn nhận body gốc
+        assert_eq!(body["password"], "hunter2");
+    }
+
+    #[actix_web::test]
+    async fn test_error_does_not_echo_sensitive_value() {
+        let app = test::init_service(App::new().route("/login", web::post().to(login))).await;
+
+        let req = test::TestRequest::post()
+            .uri("/login")
+            .set_json(json!({ "password": 987654321 }))
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), 400);
+
+        let body = String::from_utf8(test::read_body(resp).await.to_vec()).unwrap();
+        assert!(body.contains("password"));
+        assert!(!body.contains("987654321"));
+    }
+}
+
+#[cfg(test)]
+mod debug_capture_tests {
+    use actix_web::{test, web, App, HttpMessage, HttpResponse};
+    use futures_util::StreamExt;
+    use rust_template::auth::Claims;
+    use rust_template::middleware::{DebugCapture, DEBUG_CAPTURE_HEADER, DEBUG_CAPTURE_PERMISSION};
+    use rust_template::security::{AuditEventType, AuditLogger};
+    use serde_json::{json, Value};
+    use std::sync::Arc;
+
+    async fn login(body: web::Json<Value>) -> HttpResponse {
+        HttpResponse::Ok().json(json!({ "user": body["email"], "access_token": "secret-jwt" }))
+    }
+
+    fn claims(permissions: &[&str]) -> Claims {
+        Claims {
+            sub: "admin-1".to_string(),
+            email: "admin@example.com".to_string(),
+            role: "admin".to_string(),
+            exp: i64::MAX,
+            iat: 0,
+            iss: None,
+            aud: None,
+            roles: Vec::new(),
+            permissions: permissions.iter().map(|p| p.to_string()).collect(),
+            tenant_id: None,
+        }
+    }
+
+    fn login_request() -> test::TestRequest {
+        test::TestRequest::post()
+            .uri("/login")
+            .set_json(json!({ "email": "alice@example.com", "password": "hunter2" }))
+    }
+
+    fn captured(audit: &AuditLogger) -> Vec<rust_template::security::AuditEvent> {
+        audit
+            .get_recent_events(10)
+            .into_iter()
+            .filter(|e| e.event_type == AuditEventType::Custom("DEBUG_CAPTURE".to_string()))
+            .collect()
+    }
+
+    #[actix_web::test]
+    async fn test_capture_is_noop_when_off() {
+        let audit = Arc::new(AuditLogger::new(100));
+        let app = test::init_service(
+            App::new()
+                .wrap(DebugCapture::new(audit.clone()))
+                .route("/login", web::post().to(login)),
+        )
+        .await;
+
+        let resp = test::call_service(&app, login_request().to_request()).await;
+        assert_eq!(resp.status(), 200);
+        let body: Value = test::read_body_json(resp).await;
+        assert_eq!(body["user"], "alice@example.com");
+
+        assert!(captured(&audit).is_empty());
+    }
+
+    #[actix_web::test]
+    async fn test_captures_redacted_bodies_for_route() {
+        let audit = Arc::new(AuditLogger::new(100));
+        let app = test::init_service(
+            App::new()
+                .wrap(DebugCapture::new(audit.clone()).with_route("/login"))
+                .route("/login", web::post().to(login)),
+        )
+        .await;
+
+        let resp = test::call_service(&app, login_request().to_request()).await;
+        // Handler vẫn nhận đủ body và client nhận response gốc
+        let body: Value = test::read_body_json(resp).await;
+        assert_eq!(body["user"], "alice@example.com");
+        assert_eq!(body["access_token"], "secret-jwt");
+
+        let events = captured(&audit);
+        assert_eq!(events.len(), 1);
+        let metadata = &events[0].metadata;
+        assert_eq!(events[0].action, "POST /login");
+        assert_eq!(metadata["status"], "200");
+
+        let request_body: Value = serde_json::from_str(&metadata["request_body"]).unwrap();
+        assert_eq!(request_body["email"], "alice@example.com");
+        assert_eq!(request_body["password"], "***");
+        let response_body: Value = serde_json::from_str(&metadata["response_body"]).unwrap();
+        assert_eq!(response_body["access_token"], "***");
+    }
+
+    #[actix_web::test]
+    async fn test_header_requires_capture_permission() {
+        for (permissions, expected) in [(vec![], 0), (vec![DEBUG_CAPTURE_PERMISSION], 1)] {
+            let audit = Arc::new(AuditLogger::new(100));
+            let caller = claims(&permissions);
+            let app = test::init_service(
+                App::new()
+                    .wrap(DebugCapture::new(audit.clone()))
+                    // Giả lập AuthMiddleware
+                    .wrap_fn(move |req, srv| {
+                        req.extensions_mut().insert(caller.clone());
+                        actix_web::dev::Service::call(srv, req)
+                    })
+                    .route("/login", web::post().to(login)),
+            )
+            .await;
+
+            let req = login_request()
+                .insert_header((DEBUG_CAPTURE_HEADER, "true"))
+                .to_request();
+            test::call_service(&app, req).await;
+
+            let events = captured(&audit);
+            assert_eq!(events.len(), expected, "permissions: {:?}", permissions);
+            if let Some(event) = events.first() {
+                assert_eq!(event.user_id.as_deref(), Some("admin-1"));
+            }
+        }
+    }
+
+    #[actix_web::test]
+    async fn test_request_over_limit_is_streamed_to_handler() {
+        async fn echo_len(body: web::Bytes) -> HttpResponse {
+            HttpResponse::Ok().body(body.len().to_string())
+        }
+
+        let audit = Arc::new(AuditLogger::new(100));
+        let app = test::init_service(
+            App::new()
+                .wrap(
+                    DebugCapture::new(audit.clone())
+                        .with_route("/upload")
+                        .with_max_body_bytes(16),
+                )
+                .route("/upload", web::post().to(echo_len)),
+        )
+        .await;
+
+        let req = test::TestRequest::post()
+            .uri("/upload")
+            .set_payload(vec![b'x'; 1000])
+            .to_request();
+        let body = test::call_and_read_body(&app, req).await;
+        // Handler vẫn nhận đủ body dù capture chỉ đọc 16 byte
+        assert_eq!(body, "1000");
+
+        let events = captured(&audit);
+        assert_eq!(events.len(), 1);
+        assert!(events[0].metadata["request_body"].contains("over capture limit"));
+    }
+
+    #[actix_web::test]
+    async fn test_streaming_response_is_not_buffered() {
+        async fn events() -> HttpResponse {
+            // Stream không bao giờ kết thúc, như SSE
+            let chunks = futures_util::stream::once(async {
+                Ok::<_, std::convert::Infallible>(web::Bytes::from_static(b"data: hi\n\n"))
+            })
+            .chain(futures_util::stream::pending());
+            HttpResponse::Ok()
+                .content_type("text/event-stream")
+                .streaming(chunks)
+        }
+
+        let audit = Arc::new(AuditLogger::new(100));
+        let app = test::init_service(
+            App::new()
+                .wrap(DebugCapture::new(audit.clone()).with_route("/events"))
+                .route("/events", web::get().to(events)),
+        )
+        .await;
+
+        let req = test::TestRequest::get().uri("/events").to_request();
+        let resp = tokio::time::timeout(
+            std::time::Duration::from_secs(1),
+            test::call_service(&app, req),
+        )
+        .await
+        .expect("streaming response was buffered");
+        assert_eq!(resp.status(), 200);
+
+        let events = captured(&audit);
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].metadata["response_body"], "<streamed, not captured>");
+    }
+}
+
+#[cfg(test)]
+mod tls_config_tests {
+    use rust_template::config::ServerSettings;
+    use rust_template::middleware::CompressionMode;
+
+    fn server_settings(cert: Option<&str>, key: Option<&str>) -> ServerSettings {
+        ServerSettings {
+            host: "127.0.0.1".to_string(),
+            port: 8443,
+            workers: 1,
+            enable_https: true,
+            tls_cert_path: cert.map(str::to_string),
+            tls_key_path: key.map(str::to_string),
+            http_redirect_port: None,
+            max_body_bytes: 1024 * 1024,
+            request_timeout_secs: 30,
+            keep_alive_secs: 5,
+            shutdown_timeout_secs: 30,
+            liveness_stale_after_secs: 10,
+            sse_keep_alive_secs: 15,
+            grpc_port: 50051,
+            ws_rate_limit_messages: 50,
+            ws_rate_limit_window_secs: 10,
+            ws_rate_limit_close: true,
+            compression: CompressionMode::Auto,
+            compression_min_bytes: 1024,
+            coalesce_get_requests: false,
+            ids_as_strings: false,
+        }
+    }
+
+    #[test]
+    fn test_https_requires_cert_and_key_paths() {
+        assert!(server_settings(None, None).validate().is_err());
+        assert!(server_settings(Some("cert.pem"), None).validate().is_err());
+        assert!(server_settings(Some(""), Some("key.pem")).validate().is_err());
+        assert!(server_settings(Some("cert.pem"), Some("key.pem")).validate().is_ok());
+
+        let mut plain = server_settings(None, None);
+        plain.enable_https = false;
+        assert!(plain.validate().is_ok());
+    }
+
+    #[test]
+    fn test_redirect_port_must_differ() {
+        let mut settings = server_settings(Some("cert.pem"), Some("key.pem"));
+        settings.http_redirect_port = Some(settings.port);
+        assert!(settings.validate().is_err());
+    }
+
+    #[cfg(feature = "tls")]
+    mod loading {
+        use super::server_settings;
+        use actix_web::test::TestRequest;
+        use rust_template::config::{https_redirect, load_rustls_config};
+        use rust_template::errors::ApiError;
+
+        #[test]
+        fn test_missing_paths_are_configuration_errors() {
+            let result = load_rustls_config(&server_settings(None, None));
+            assert!(matches!(result, Err(ApiError::ConfigurationError { .. })));
+        }
+
+        #[test]
+        fn test_unreadable_or_invalid_pem_is_rejected() {
+            let dir = std::env::temp_dir().join(format!("tls-test-{}", uuid::Uuid::new_v4()));
+            std::fs::create_dir_all(&dir).unwrap();
+            let garbage = dir.join("garbage.pem");
+            std::fs::write(&garbage, "not a certificate").unwrap();
+            let garbage = garbage.to_str().unwrap();
+
+            let err = load_rustls_config(&server_settings(Some("/nonexistent/cert.pem"), Some(garbage)))
+                .unwrap_err();
+            assert!(matches!(err, ApiError::ConfigurationError { .. }));
+            assert!(err.to_string().contains("TLS_CERT_PATH"));
+
+            let err = load_rustls_config(&server_settings(Some(garbage), Some(garbage))).unwrap_err();
+            assert!(err.to_string().contains("no PEM certificate"));
+
+            std::fs::remove_dir_all(&dir).ok();
+        }
+
+        #[test]
+        fn test_http_is_redirected_to_https() {
+            let req = TestRequest::get()
+                .uri("/users?page=2")
+                .insert_header(("host", "api.example.com:8080"))
+                .to_http_request();
+
+            let resp = https_redirect(&req, 8443);
+            assert_eq!(resp.status(), 308);
+            assert_eq!(
+                resp.headers().get("location").unwrap(),
+                "https://api.example.com:8443/users?page=2"
+            );
+
+            let resp = https_redirect(&req, 443);
+            assert_eq!(resp.headers().get("location").unwrap(), "https://api.example.com/users?page=2");
+        }
+    }
+}
@@ -0,0 +1,26 @@
+mod common;
+
+#[cfg(all(test, feature = "database-sqlite"))]
+mod sqlite_event_store_tests {
+    use rust_template::patterns::SqliteEventStore;
+    use sqlx::sqlite::{SqlitePool, SqlitePoolOptions};
+
+    async fn setup_test_db() -> SqlitePool {
+        // Một connection duy nhất: mỗi connection `:memory:` là một database riêng
+        let pool = SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .expect("Failed to open in-memory database");
+
+        // Run migrations
+        sqlx::migrate!("./migrations/sqlite")
+            .run(&pool)
+            .await
+            .expect("Failed to run migrations");
+
+        pool
+    }
+
+    crate::event_store_tests!(SqliteEventStore, setup_test_db);
+}
@@ -0,0 +1,295 @@
+#[cfg(all(test, feature = "webhooks"))]
+mod webhook_tests {
+    use chrono::Utc;
+    use rust_template::patterns::{EventStore, InMemoryEventStore, StoredEvent};
+    use rust_template::utils::RetryPolicy;
+    use hmac::{Hmac, Mac};
+    use rust_template::webhooks::{
+        sign, verify, verify_with_tolerance, DeliveryStatus, WebhookDispatcher, WebhookEndpoint,
+        WebhookEventStore, DELIVERY_ID_HEADER, SIGNATURE_HEADER, TIMESTAMP_HEADER,
+    };
+    use sha2::Sha256;
+    use serde_json::json;
+    use std::sync::Arc;
+    use std::time::Duration;
+    use wiremock::matchers::{method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    const SECRET: &str = "whsec_test";
+
+    fn dispatcher() -> WebhookDispatcher {
+        // Mock server chạy trên loopback
+        WebhookDispatcher::new().allow_private_networks().with_retry_policy(
+            RetryPolicy::new(3)
+                .with_backoff(Duration::from_millis(10), Duration::from_millis(50))
+                .without_jitter(),
+        )
+    }
+
+    fn endpoint(server: &MockServer) -> WebhookEndpoint {
+        WebhookEndpoint::new("ep-1", format!("{}/hooks", server.uri()), SECRET)
+    }
+
+    fn signature_of(request: &wiremock::Request) -> String {
+        request.headers[SIGNATURE_HEADER].to_str().unwrap().to_string()
+    }
+
+    fn timestamp_of(request: &wiremock::Request) -> i64 {
+        request.headers[TIMESTAMP_HEADER].to_str().unwrap().parse().unwrap()
+    }
+
+    fn is_verified(request: &wiremock::Request) -> bool {
+        verify_with_tolerance(
+            SECRET,
+            timestamp_of(request),
+            &request.body,
+            &signature_of(request),
+            Duration::from_secs(60),
+        )
+    }
+
+    #[test]
+    fn test_signature_is_hmac_sha256_of_timestamp_and_body() {
+        let body = b"what do ya want for nothing?";
+        let signature = sign("Jefe", 1_700_000_000, body);
+
+        let mut mac = Hmac::<Sha256>::new_from_slice(b"Jefe").unwrap();
+        mac.update(b"1700000000.what do ya want for nothing?");
+        assert_eq!(
+            signature,
+            format!("sha256={}", hex::encode(mac.finalize().into_bytes()))
+        );
+
+        assert!(verify("Jefe", 1_700_000_000, body, &signature));
+        assert!(!verify("Jefe", 1_700_000_001, body, &signature));
+        assert!(!verify("Jefe", 1_700_000_000, b"tampered body", &signature));
+        assert!(!verify("other", 1_700_000_000, body, &signature));
+        assert!(!verify("Jefe", 1_700_000_000, body, "not-a-signature"));
+    }
+
+    #[test]
+    fn test_stale_timestamp_is_rejected() {
+        let now = Utc::now().timestamp();
+        let tolerance = Duration::from_secs(300);
+
+        let fresh = sign(SECRET, now, b"{}");
+        assert!(verify_with_tolerance(SECRET, now, b"{}", &fresh, tolerance));
+
+        // Chữ ký đúng nhưng bị replay sau 10 phút
+        let replayed = sign(SECRET, now - 600, b"{}");
+        assert!(verify(SECRET, now - 600, b"{}", &replayed));
+        assert!(!verify_with_tolerance(SECRET, now - 600, b"{}", &replayed, tolerance));
+    }
+
+    #[tokio::test]
+    async fn test_delivery_is_signed() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/hooks"))
+            .respond_with(ResponseTemplate::new(204))
+            .mount(&server)
+            .await;
+
+        let delivery = dispatcher()
+            .deliver(&endpoint(&server), &json!({ "order_id": 42 }))
+            .await
+            .unwrap();
+        assert_eq!(delivery.status, DeliveryStatus::Delivered);
+
+        let requests = server.received_requests().await.unwrap();
+        assert_eq!(requests.len(), 1);
+        assert!(is_verified(&requests[0]));
+        assert_eq!(requests[0].headers[DELIVERY_ID_HEADER].to_str().unwrap(), delivery.id);
+
+        // Không lộ trace/correlation id nội bộ cho endpoint của khách hàng
+        for name in ["traceparent", "tracestate", "x-request-id", "x-correlation-id"] {
+            assert!(!requests[0].headers.contains_key(name), "{} was forwarded", name);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_private_addresses_are_refused() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .respond_with(ResponseTemplate::new(204))
+            .mount(&server)
+            .await;
+
+        let dispatcher = WebhookDispatcher::new().with_retry_policy(
+            RetryPolicy::new(3)
+                .with_backoff(Duration::from_millis(10), Duration::from_millis(50))
+                .without_jitter(),
+        );
+
+        for url in [
+            format!("{}/hooks", server.uri()),
+            "http://localhost:9/hooks".to_string(),
+            "http://10.0.0.1/hooks".to_string(),
+            "http://169.254.169.254/latest/meta-data".to_string(),
+            "http://[::1]:9/hooks".to_string(),
+            "http://[::ffff:127.0.0.1]:9/hooks".to_string(),
+        ] {
+            let delivery = dispatcher
+                .deliver(&WebhookEndpoint::new("ep-1", url.clone(), SECRET), &json!({}))
+                .await
+                .unwrap();
+
+            assert_eq!(delivery.status, DeliveryStatus::Failed, "{}", url);
+            assert_eq!(delivery.attempts.len(), 1, "{} was retried", url);
+            assert!(delivery.attempts[0].error.as_deref().unwrap().contains("refused"));
+        }
+
+        assert!(server.received_requests().await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_redirects_are_not_followed() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/hooks"))
+            .respond_with(
+                ResponseTemplate::new(307)
+                    .insert_header("Location", format!("{}/internal", server.uri())),
+            )
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/internal"))
+            .respond_with(ResponseTemplate::new(200))
+            .mount(&server)
+            .await;
+
+        let delivery = dispatcher()
+            .deliver(&endpoint(&server), &json!({}))
+            .await
+            .unwrap();
+
+        assert_eq!(delivery.status, DeliveryStatus::Failed);
+        assert_eq!(delivery.attempts.len(), 1);
+        assert_eq!(delivery.attempts[0].status_code, Some(307));
+
+        let requests = server.received_requests().await.unwrap();
+        assert_eq!(requests.len(), 1);
+        assert_eq!(requests[0].url.path(), "/hooks");
+    }
+
+    #[tokio::test]
+    async fn test_retries_server_errors_then_succeeds() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .respond_with(ResponseTemplate::new(503))
+            .up_to_n_times(2)
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .respond_with(ResponseTemplate::new(200))
+            .mount(&server)
+            .await;
+
+        let dispatcher = dispatcher();
+        let delivery = dispatcher
+            .deliver(&endpoint(&server), &json!({ "order_id": 42 }))
+            .await
+            .unwrap();
+
+        assert_eq!(delivery.status, DeliveryStatus::Delivered);
+        let statuses: Vec<_> = delivery.attempts.iter().map(|a| a.status_code).collect();
+        assert_eq!(statuses, vec![Some(503), Some(503), Some(200)]);
+        assert_eq!(delivery.attempts.last().unwrap().attempt, 3);
+
+        // Cùng delivery id ở mọi lần thử, mỗi lần ký với timestamp của nó
+        let requests = server.received_requests().await.unwrap();
+        assert_eq!(requests.len(), 3);
+        assert!(requests
+            .iter()
+            .all(|r| r.headers[DELIVERY_ID_HEADER] == requests[0].headers[DELIVERY_ID_HEADER]));
+        assert!(requests.iter().all(is_verified));
+
+        assert_eq!(dispatcher.deliveries().unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_client_error_is_not_retried() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .respond_with(ResponseTemplate::new(410))
+            .mount(&server)
+            .await;
+
+        let delivery = dispatcher()
+            .deliver(&endpoint(&server), &json!({}))
+            .await
+            .unwrap();
+
+        assert_eq!(delivery.status, DeliveryStatus::Failed);
+        assert_eq!(delivery.attempts.len(), 1);
+        assert!(delivery.attempts[0].error.is_some());
+    }
+
+    #[tokio::test]
+    async fn test_timeout_is_retried() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .respond_with(ResponseTemplate::new(200).set_delay(Duration::from_millis(500)))
+            .up_to_n_times(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .respond_with(ResponseTemplate::new(200))
+            .mount(&server)
+            .await;
+
+        let delivery = dispatcher()
+            .with_timeout(Duration::from_millis(100))
+            .deliver(&endpoint(&server), &json!({}))
+            .await
+            .unwrap();
+
+        assert_eq!(delivery.status, DeliveryStatus::Delivered);
+        assert_eq!(delivery.attempts.len(), 2);
+        assert_eq!(delivery.attempts[0].status_code, None);
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_event_store_fans_out_to_subscribed_endpoints() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .respond_with(ResponseTemplate::new(200))
+            .mount(&server)
+            .await;
+
+        let dispatcher = Arc::new(dispatcher());
+        dispatcher
+            .subscribe(endpoint(&server).with_event_types(["OrderPlaced"]))
+            .unwrap();
+        let store = WebhookEventStore::new(InMemoryEventStore::new(), dispatcher.clone());
+
+        for (version, event_type) in [(1, "OrderPlaced"), (2, "OrderShipped")] {
+            store
+                .append(StoredEvent {
+                    id: uuid::Uuid::new_v4().to_string(),
+                    aggregate_id: "order-1".to_string(),
+                    event_type: event_type.to_string(),
+                    payload: json!({ "order_id": 1 }),
+                    timestamp: Utc::now(),
+                    version,
+                })
+                .unwrap();
+        }
+        assert_eq!(store.get_events("order-1").unwrap().len(), 2);
+
+        // Delivery chạy trên task nền
+        for _ in 0..50 {
+            if !dispatcher.deliveries().unwrap().is_empty() {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(20)).await;
+        }
+
+        let requests = server.received_requests().await.unwrap();
+        assert_eq!(requests.len(), 1);
+        let body: serde_json::Value = serde_json::from_slice(&requests[0].body).unwrap();
+        assert_eq!(body["event_type"], "OrderPlaced");
+        assert!(is_verified(&requests[0]));
+    }
+}